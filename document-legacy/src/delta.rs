@@ -0,0 +1,503 @@
+use crate::document::Document;
+use crate::layers::blend_mode::BlendMode;
+use crate::layers::folder_layer::FolderLayer;
+use crate::layers::layer_info::{Layer, LayerDataType};
+use crate::layers::layer_layer::CachedOutputData;
+use crate::LayerId;
+
+use graph_craft::document::{DocumentNode, NodeId, NodeNetwork, NodeOutput};
+use graphene_core::raster::ColorSpace;
+
+use glam::{DAffine2, DVec2};
+use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::Hasher;
+
+/// The changes which turn one state of a document into another, holding only the parts of the other state which differ from this one.
+///
+/// Applying a delta swaps those parts into the document, so it returns the delta which turns the document back.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentDelta {
+	root: Option<LayerDelta>,
+	document_network: Option<NetworkDelta>,
+	#[serde(default)]
+	working_color_space: Option<ColorSpace>,
+	#[serde(skip)]
+	state_identifier: DefaultHasher,
+}
+
+impl DocumentDelta {
+	/// The delta which turns the `from` document into the `to` document.
+	///
+	/// The transform of the root is left out, since it holds the navigation of the viewport rather than the artwork.
+	pub fn new(from: &Document, to: &Document) -> Self {
+		let root_properties = LayerProperties {
+			transform: from.root.transform,
+			..LayerProperties::of(&to.root)
+		};
+		Self {
+			root: LayerDelta::with_properties(&from.root, &to.root, root_properties),
+			document_network: NetworkDelta::new(&from.document_network, &to.document_network),
+			working_color_space: (from.working_color_space != to.working_color_space).then_some(to.working_color_space),
+			state_identifier: to.state_identifier.clone(),
+		}
+	}
+
+	/// Swaps the stored parts into the document, returning the delta which undoes this.
+	pub fn apply(self, document: &mut Document) -> Self {
+		let root_transform = document.root.transform;
+		let root = self.root.map(|delta| delta.apply(&mut document.root));
+		document.root.transform = root_transform;
+		document.root.cache_dirty = true;
+
+		Self {
+			root,
+			document_network: self.document_network.map(|delta| delta.apply(&mut document.document_network)),
+			working_color_space: self.working_color_space.map(|color_space| std::mem::replace(&mut document.working_color_space, color_space)),
+			state_identifier: std::mem::replace(&mut document.state_identifier, self.state_identifier),
+		}
+	}
+
+	/// Whether the two states of the document have the same layers, nodes and working color space.
+	pub fn is_empty(&self) -> bool {
+		self.root.is_none() && self.document_network.is_none() && self.working_color_space.is_none()
+	}
+
+	/// The identifier of the state the delta turns the document into.
+	pub fn state_identifier(&self) -> u64 {
+		self.state_identifier.finish()
+	}
+
+	/// The nodes whose values differ, as the path of the layer holding them (or `None` for the document's own network) with the ID of the node.
+	///
+	/// This is `None` if anything else differs, such as the layers, their properties, the connections of a network or the working color space.
+	pub fn changed_nodes(&self) -> Option<Vec<(Option<Vec<LayerId>>, NodeId)>> {
+		if self.working_color_space.is_some() {
+			return None;
+		}
+		let mut changed = Vec::new();
+		if let Some(root) = &self.root {
+			root.changed_nodes(&mut Vec::new(), &mut changed)?;
+		}
+		if let Some(network) = &self.document_network {
+			changed.extend(network.changed_nodes()?.map(|node_id| (None, node_id)));
+		}
+		changed.sort();
+		Some(changed)
+	}
+}
+
+/// The properties of a layer apart from its data, which are small enough to always be stored whole.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct LayerProperties {
+	visible: bool,
+	name: Option<String>,
+	transform: DAffine2,
+	preserve_aspect: bool,
+	pivot: DVec2,
+	blend_mode: BlendMode,
+	opacity: f64,
+}
+
+impl LayerProperties {
+	fn of(layer: &Layer) -> Self {
+		Self {
+			visible: layer.visible,
+			name: layer.name.clone(),
+			transform: layer.transform,
+			preserve_aspect: layer.preserve_aspect,
+			pivot: layer.pivot,
+			blend_mode: layer.blend_mode,
+			opacity: layer.opacity,
+		}
+	}
+
+	fn swap(self, layer: &mut Layer) -> Self {
+		let previous = Self::of(layer);
+		layer.visible = self.visible;
+		layer.name = self.name;
+		layer.transform = self.transform;
+		layer.preserve_aspect = self.preserve_aspect;
+		layer.pivot = self.pivot;
+		layer.blend_mode = self.blend_mode;
+		layer.opacity = self.opacity;
+		previous
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct LayerDelta {
+	properties: Option<LayerProperties>,
+	data: Option<DataDelta>,
+}
+
+impl LayerDelta {
+	fn new(from: &Layer, to: &Layer) -> Option<Self> {
+		Self::with_properties(from, to, LayerProperties::of(to))
+	}
+
+	fn with_properties(from: &Layer, to: &Layer, properties: LayerProperties) -> Option<Self> {
+		let properties = (properties != LayerProperties::of(from)).then_some(properties);
+		let data = DataDelta::new(&from.data, &to.data);
+		(properties.is_some() || data.is_some()).then_some(Self { properties, data })
+	}
+
+	fn apply(self, layer: &mut Layer) -> Self {
+		layer.cache_dirty = true;
+		Self {
+			properties: self.properties.map(|properties| properties.swap(layer)),
+			data: self.data.map(|data| data.apply(&mut layer.data)),
+		}
+	}
+
+	fn changed_nodes(&self, path: &mut Vec<LayerId>, changed: &mut Vec<(Option<Vec<LayerId>>, NodeId)>) -> Option<()> {
+		if self.properties.is_some() {
+			return None;
+		}
+		match &self.data {
+			None => {}
+			Some(DataDelta::Replace(_)) => return None,
+			Some(DataDelta::Network { network, .. }) => changed.extend(network.changed_nodes()?.map(|node_id| (Some(path.clone()), node_id))),
+			Some(DataDelta::Folder(folder)) => {
+				if folder.layer_ids.is_some() {
+					return None;
+				}
+				for (id, child) in &folder.children {
+					let ChildDelta::Change(child) = child else { return None };
+					path.push(*id);
+					let result = child.changed_nodes(path, changed);
+					path.pop();
+					result?;
+				}
+			}
+		}
+		Some(())
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum DataDelta {
+	/// The data is swapped whole, as when the layer changes between types.
+	Replace(Box<LayerDataType>),
+	Folder(FolderDelta),
+	/// The network of a node graph layer is changed along with the output it had.
+	Network {
+		network: NetworkDelta,
+		cached_output_data: CachedOutputData,
+	},
+}
+
+impl DataDelta {
+	fn new(from: &LayerDataType, to: &LayerDataType) -> Option<Self> {
+		match (from, to) {
+			(LayerDataType::Folder(from), LayerDataType::Folder(to)) => FolderDelta::new(from, to).map(DataDelta::Folder),
+			(LayerDataType::Layer(from), LayerDataType::Layer(to)) => NetworkDelta::new(&from.network, &to.network).map(|network| DataDelta::Network {
+				network,
+				cached_output_data: to.cached_output_data.clone(),
+			}),
+			(from, to) if from == to => None,
+			(_, to) => Some(DataDelta::Replace(Box::new(to.clone()))),
+		}
+	}
+
+	fn apply(self, data: &mut LayerDataType) -> Self {
+		match (self, data) {
+			(DataDelta::Folder(delta), LayerDataType::Folder(folder)) => DataDelta::Folder(delta.apply(folder)),
+			(DataDelta::Network { network, cached_output_data }, LayerDataType::Layer(layer)) => DataDelta::Network {
+				network: network.apply(&mut layer.network),
+				cached_output_data: std::mem::replace(&mut layer.cached_output_data, cached_output_data),
+			},
+			(DataDelta::Replace(replacement), data) => DataDelta::Replace(Box::new(std::mem::replace(data, *replacement))),
+			(delta, _) => {
+				warn!("The layer has changed type since the history step was stored");
+				delta
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FolderDelta {
+	/// The order of the layers in the folder, when it differs.
+	layer_ids: Option<Vec<LayerId>>,
+	next_assignment_id: LayerId,
+	children: Vec<(LayerId, ChildDelta)>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum ChildDelta {
+	Insert(Box<Layer>),
+	Remove,
+	Change(Box<LayerDelta>),
+}
+
+impl FolderDelta {
+	fn new(from: &FolderLayer, to: &FolderLayer) -> Option<Self> {
+		let from_layers = from.layer_ids.iter().zip(&from.layers).collect::<HashMap<_, _>>();
+		let to_layers = to.layer_ids.iter().zip(&to.layers).collect::<HashMap<_, _>>();
+
+		let mut children = Vec::new();
+		for (&&id, to_layer) in &to_layers {
+			match from_layers.get(&id) {
+				Some(from_layer) => children.extend(LayerDelta::new(from_layer, to_layer).map(|delta| (id, ChildDelta::Change(Box::new(delta))))),
+				None => children.push((id, ChildDelta::Insert(Box::new((*to_layer).clone())))),
+			}
+		}
+		children.extend(from_layers.keys().filter(|id| !to_layers.contains_key(*id)).map(|&&id| (id, ChildDelta::Remove)));
+
+		let layer_ids = (from.layer_ids != to.layer_ids).then(|| to.layer_ids.clone());
+		let unchanged = children.is_empty() && layer_ids.is_none() && from.next_assignment_id == to.next_assignment_id;
+		(!unchanged).then_some(Self {
+			layer_ids,
+			next_assignment_id: to.next_assignment_id,
+			children,
+		})
+	}
+
+	fn apply(self, folder: &mut FolderLayer) -> Self {
+		let previous_ids = std::mem::take(&mut folder.layer_ids);
+		let mut layers = previous_ids.iter().copied().zip(std::mem::take(&mut folder.layers)).collect::<HashMap<_, _>>();
+
+		let mut children = Vec::with_capacity(self.children.len());
+		for (id, child) in self.children {
+			let inverse = match child {
+				ChildDelta::Insert(layer) => layers.insert(id, *layer).map_or(ChildDelta::Remove, |layer| ChildDelta::Insert(Box::new(layer))),
+				ChildDelta::Remove => match layers.remove(&id) {
+					Some(layer) => ChildDelta::Insert(Box::new(layer)),
+					None => continue,
+				},
+				ChildDelta::Change(delta) => match layers.get_mut(&id) {
+					Some(layer) => ChildDelta::Change(Box::new(delta.apply(layer))),
+					None => continue,
+				},
+			};
+			children.push((id, inverse));
+		}
+
+		let layer_ids = self.layer_ids.clone().unwrap_or_else(|| previous_ids.clone());
+		(folder.layer_ids, folder.layers) = layer_ids.into_iter().filter_map(|id| Some((id, layers.remove(&id)?))).unzip();
+
+		Self {
+			layer_ids: self.layer_ids.map(|_| previous_ids),
+			next_assignment_id: std::mem::replace(&mut folder.next_assignment_id, self.next_assignment_id),
+			children,
+		}
+	}
+}
+
+/// The changes to a node network, where the nodes are stored individually so that a large node, such as one holding an image, is only kept when it differs.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct NetworkDelta {
+	/// The inputs, outputs and disabled nodes of the network, when they differ.
+	connections: Option<NetworkConnections>,
+	/// The nodes which differ, or `None` for those which don't exist in the other state.
+	nodes: Vec<(NodeId, Option<DocumentNode>)>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+struct NetworkConnections {
+	inputs: Vec<NodeId>,
+	outputs: Vec<NodeOutput>,
+	disabled: Vec<NodeId>,
+	previous_outputs: Option<Vec<NodeOutput>>,
+}
+
+impl NetworkConnections {
+	fn of(network: &NodeNetwork) -> Self {
+		Self {
+			inputs: network.inputs.clone(),
+			outputs: network.outputs.clone(),
+			disabled: network.disabled.clone(),
+			previous_outputs: network.previous_outputs.clone(),
+		}
+	}
+
+	fn swap(self, network: &mut NodeNetwork) -> Self {
+		Self {
+			inputs: std::mem::replace(&mut network.inputs, self.inputs),
+			outputs: std::mem::replace(&mut network.outputs, self.outputs),
+			disabled: std::mem::replace(&mut network.disabled, self.disabled),
+			previous_outputs: std::mem::replace(&mut network.previous_outputs, self.previous_outputs),
+		}
+	}
+}
+
+impl NetworkDelta {
+	fn new(from: &NodeNetwork, to: &NodeNetwork) -> Option<Self> {
+		if from == to {
+			return None;
+		}
+
+		let connections = NetworkConnections::of(to);
+		let connections = (connections != NetworkConnections::of(from)).then_some(connections);
+		let changed = to.nodes.iter().filter(|&(id, node)| from.nodes.get(id) != Some(node));
+		let mut nodes = changed.map(|(&id, node)| (id, Some(node.clone()))).collect::<Vec<_>>();
+		nodes.extend(from.nodes.keys().filter(|id| !to.nodes.contains_key(id)).map(|&id| (id, None)));
+
+		Some(Self { connections, nodes })
+	}
+
+	fn apply(self, network: &mut NodeNetwork) -> Self {
+		let nodes = self.nodes.into_iter().map(|(id, node)| match node {
+			Some(node) => (id, network.nodes.insert(id, node)),
+			None => (id, network.nodes.remove(&id)),
+		});
+		let nodes = nodes.collect();
+		let connections = self.connections.map(|connections| connections.swap(network));
+		Self { connections, nodes }
+	}
+
+	/// The nodes which differ, if nothing else about the network does.
+	fn changed_nodes(&self) -> Option<impl Iterator<Item = NodeId> + '_> {
+		self.connections.is_none().then(|| self.nodes.iter().map(|&(id, _)| id))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::layers::style::PathStyle;
+	use crate::Operation;
+
+	/// A copy of the layer without the caches left by rendering it, which deltas don't keep.
+	fn without_caches(layer: &Layer) -> Layer {
+		let mut layer = layer.clone();
+		layer.cache_dirty = false;
+		if let LayerDataType::Folder(folder) = &mut layer.data {
+			folder.layers = folder.layers.iter().map(without_caches).collect();
+		}
+		layer
+	}
+
+	fn assert_same(document: &Document, expected: &Document) {
+		assert_eq!(without_caches(&document.root), without_caches(&expected.root));
+		assert_eq!(document.document_network, expected.document_network);
+		assert_eq!(document.working_color_space, expected.working_color_space);
+		assert_eq!(document.current_state_identifier(), expected.current_state_identifier());
+	}
+
+	/// Applies the delta from one document to the other, then the inverse it returned and the inverse of that, checking that each reaches the right document.
+	fn assert_round_trips(from: &Document, to: &Document) {
+		let delta = DocumentDelta::new(from, to);
+		assert!(!delta.is_empty());
+
+		let mut document = from.clone();
+		let inverse = delta.apply(&mut document);
+		assert_same(&document, to);
+		let redo = inverse.apply(&mut document);
+		assert_same(&document, from);
+		redo.apply(&mut document);
+		assert_same(&document, to);
+	}
+
+	fn with_folders(paths: &[&[LayerId]]) -> Document {
+		let mut document = Document::default();
+		for path in paths {
+			document
+				.handle_operation(Operation::CreateFolder {
+					path: path.to_vec(),
+					insert_index: -1,
+				})
+				.unwrap();
+		}
+		document
+	}
+
+	#[test]
+	fn unchanged_documents_have_empty_deltas() {
+		let document = with_folders(&[&[1], &[1, 2]]);
+		assert!(DocumentDelta::new(&document, &document.clone()).is_empty());
+	}
+
+	#[test]
+	fn layer_properties_round_trip() {
+		let from = with_folders(&[&[1], &[1, 2]]);
+		let mut to = from.clone();
+		let operations = [
+			Operation::SetLayerName {
+				path: vec![1, 2],
+				name: "Group".into(),
+			},
+			Operation::SetLayerVisibility { path: vec![1], visible: false },
+			Operation::SetLayerOpacity { path: vec![1, 2], opacity: 0.5 },
+			Operation::SetLayerBlendMode {
+				path: vec![1, 2],
+				blend_mode: BlendMode::Multiply,
+			},
+			Operation::SetLayerTransform {
+				path: vec![1],
+				transform: DAffine2::from_translation(DVec2::new(10., 20.)).to_cols_array(),
+			},
+		];
+		for operation in operations {
+			to.handle_operation(operation).unwrap();
+		}
+
+		assert_round_trips(&from, &to);
+	}
+
+	#[test]
+	fn inserted_removed_and_reordered_layers_round_trip() {
+		let from = with_folders(&[&[1], &[2], &[3], &[3, 4]]);
+		let mut to = from.clone();
+		to.handle_operation(Operation::DeleteLayer { path: vec![2] }).unwrap();
+		to.handle_operation(Operation::CreateFolder { path: vec![5], insert_index: 0 }).unwrap();
+		to.handle_operation(Operation::DeleteLayer { path: vec![3, 4] }).unwrap();
+		to.handle_operation(Operation::CreateFolder { path: vec![3, 6], insert_index: -1 }).unwrap();
+
+		assert_round_trips(&from, &to);
+	}
+
+	#[test]
+	fn replaced_layer_data_round_trips() {
+		let mut from = Document::default();
+		let add_rect = Operation::AddRect {
+			path: vec![1],
+			insert_index: -1,
+			transform: DAffine2::IDENTITY.to_cols_array(),
+			style: PathStyle::default(),
+		};
+		from.handle_operation(add_rect).unwrap();
+		let mut to = from.clone();
+		to.layer_mut(&[1]).unwrap().data = LayerDataType::Folder(FolderLayer::default());
+
+		assert_round_trips(&from, &to);
+	}
+
+	#[test]
+	fn node_graph_layer_networks_round_trip() {
+		let mut from = Document::default();
+		let add_frame = Operation::AddFrame {
+			path: vec![1],
+			insert_index: -1,
+			transform: DAffine2::IDENTITY.to_cols_array(),
+			network: from.document_network.clone(),
+		};
+		from.handle_operation(add_frame).unwrap();
+		let mut to = from.clone();
+		let LayerDataType::Layer(layer) = &mut to.layer_mut(&[1]).unwrap().data else {
+			panic!("The frame should be a node graph layer")
+		};
+		let node_id = *layer.network.nodes.keys().next().unwrap();
+		let node = layer.network.nodes[&node_id].clone();
+		layer.network.nodes.get_mut(&node_id).unwrap().metadata.position.x += 10;
+		layer.network.nodes.insert(node_id + 1, node);
+
+		assert_round_trips(&from, &to);
+	}
+
+	#[test]
+	fn document_network_and_color_space_round_trip() {
+		let from = Document::default();
+		let mut to = from.clone();
+		let node_id = *to.document_network.nodes.keys().next().unwrap();
+		let node = to.document_network.nodes[&node_id].clone();
+		to.document_network.nodes.get_mut(&node_id).unwrap().metadata.position.x += 10;
+		to.document_network.nodes.insert(node_id + 1, node);
+		to.document_network.disabled.push(node_id);
+		to.working_color_space = ColorSpace::DisplayP3;
+
+		assert_round_trips(&from, &to);
+	}
+}
@@ -6,6 +6,8 @@ use crate::layers::shape_layer::ShapeLayer;
 use crate::layers::style::RenderData;
 use crate::{DocumentError, DocumentResponse, Operation};
 
+use graphene_core::raster::ColorSpace;
+
 use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
 use std::cmp::max;
@@ -30,6 +32,9 @@ pub struct Document {
 	pub document_network: graph_craft::document::NodeNetwork,
 	#[serde(default)]
 	pub commit_hash: String,
+	/// The color space the colors of the document are in, which imported images are converted into and exported images are converted from.
+	#[serde(default)]
+	pub working_color_space: ColorSpace,
 }
 
 impl PartialEq for Document {
@@ -57,6 +62,7 @@ impl Default for Document {
 				network
 			},
 			commit_hash: String::new(),
+			working_color_space: ColorSpace::default(),
 		}
 	}
 }
@@ -121,3 +121,69 @@ impl BlendMode {
 		]
 	}
 }
+
+impl From<BlendMode> for graphene_core::raster::BlendMode {
+	fn from(blend_mode: BlendMode) -> Self {
+		use graphene_core::raster::BlendMode as GrapheneBlendMode;
+
+		match blend_mode {
+			// Normal group
+			BlendMode::Normal => GrapheneBlendMode::Normal,
+			// Darken group
+			BlendMode::Darken => GrapheneBlendMode::Darken,
+			BlendMode::Multiply => GrapheneBlendMode::Multiply,
+			BlendMode::ColorBurn => GrapheneBlendMode::ColorBurn,
+			// Lighten group
+			BlendMode::Lighten => GrapheneBlendMode::Lighten,
+			BlendMode::Screen => GrapheneBlendMode::Screen,
+			BlendMode::ColorDodge => GrapheneBlendMode::ColorDodge,
+			// Contrast group
+			BlendMode::Overlay => GrapheneBlendMode::Overlay,
+			BlendMode::SoftLight => GrapheneBlendMode::SoftLight,
+			BlendMode::HardLight => GrapheneBlendMode::HardLight,
+			// Inversion group
+			BlendMode::Difference => GrapheneBlendMode::Difference,
+			BlendMode::Exclusion => GrapheneBlendMode::Exclusion,
+			// Component group
+			BlendMode::Hue => GrapheneBlendMode::Hue,
+			BlendMode::Saturation => GrapheneBlendMode::Saturation,
+			BlendMode::Color => GrapheneBlendMode::Color,
+			BlendMode::Luminosity => GrapheneBlendMode::Luminosity,
+		}
+	}
+}
+
+impl TryFrom<graphene_core::raster::BlendMode> for BlendMode {
+	type Error = graphene_core::raster::BlendMode;
+
+	/// Fails with the original blend mode for the ones that can't be rendered in SVG.
+	fn try_from(blend_mode: graphene_core::raster::BlendMode) -> Result<Self, Self::Error> {
+		use graphene_core::raster::BlendMode as GrapheneBlendMode;
+
+		Ok(match blend_mode {
+			// Normal group
+			GrapheneBlendMode::Normal => BlendMode::Normal,
+			// Darken group
+			GrapheneBlendMode::Darken => BlendMode::Darken,
+			GrapheneBlendMode::Multiply => BlendMode::Multiply,
+			GrapheneBlendMode::ColorBurn => BlendMode::ColorBurn,
+			// Lighten group
+			GrapheneBlendMode::Lighten => BlendMode::Lighten,
+			GrapheneBlendMode::Screen => BlendMode::Screen,
+			GrapheneBlendMode::ColorDodge => BlendMode::ColorDodge,
+			// Contrast group
+			GrapheneBlendMode::Overlay => BlendMode::Overlay,
+			GrapheneBlendMode::SoftLight => BlendMode::SoftLight,
+			GrapheneBlendMode::HardLight => BlendMode::HardLight,
+			// Inversion group
+			GrapheneBlendMode::Difference => BlendMode::Difference,
+			GrapheneBlendMode::Exclusion => BlendMode::Exclusion,
+			// Component group
+			GrapheneBlendMode::Hue => BlendMode::Hue,
+			GrapheneBlendMode::Saturation => BlendMode::Saturation,
+			GrapheneBlendMode::Color => BlendMode::Color,
+			GrapheneBlendMode::Luminosity => BlendMode::Luminosity,
+			other => return Err(other),
+		})
+	}
+}
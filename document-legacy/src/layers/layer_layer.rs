@@ -58,14 +58,16 @@ impl LayerData for LayerLayer {
 				let layer_bounds = vector_data.bounding_box().unwrap_or_default();
 				let transformed_bounds = vector_data.bounding_box_with_transform(transform).unwrap_or_default();
 
-				let _ = write!(svg, "<path d=\"");
-				for subpath in &vector_data.subpaths {
-					let _ = subpath.subpath_to_svg(svg, transform);
+				for (subpaths, style) in vector_data.styled_subpaths() {
+					let _ = write!(svg, "<path d=\"");
+					for subpath in subpaths {
+						let _ = subpath.subpath_to_svg(svg, transform);
+					}
+					svg.push('"');
+
+					svg.push_str(&style.render(render_data.view_mode, svg_defs, transform, layer_bounds, transformed_bounds));
+					let _ = write!(svg, "/>");
 				}
-				svg.push('"');
-
-				svg.push_str(&vector_data.style.render(render_data.view_mode, svg_defs, transform, layer_bounds, transformed_bounds));
-				let _ = write!(svg, "/>");
 			}
 			CachedOutputData::BlobURL(blob_url) => {
 				// Render the image if it exists
@@ -126,8 +128,8 @@ impl LayerData for LayerLayer {
 
 	fn intersects_quad(&self, quad: Quad, path: &mut Vec<LayerId>, intersections: &mut Vec<Vec<LayerId>>, _render_data: &RenderData) {
 		if let CachedOutputData::VectorPath(vector_data) = &self.cached_output_data {
-			let filled_style = vector_data.style.fill().is_some();
-			if vector_data.subpaths.iter().any(|subpath| intersect_quad_subpath(quad, subpath, filled_style || subpath.closed())) {
+			let mut styled_subpaths = vector_data.styled_subpaths().into_iter();
+			if styled_subpaths.any(|(subpaths, style)| subpaths.iter().any(|subpath| intersect_quad_subpath(quad, subpath, style.fill().is_some() || subpath.closed()))) {
 				intersections.push(path.clone());
 			}
 		} else if intersect_quad_bez_path(quad, &self.bounds(), true) {
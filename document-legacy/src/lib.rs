@@ -5,6 +5,8 @@ extern crate log;
 pub mod boolean_ops;
 /// Contains constant values used by this crate.
 pub mod consts;
+/// Differences between states of a document, which the undo history stores instead of whole copies.
+pub mod delta;
 pub mod document;
 /// Defines errors that can occur when using this crate.
 pub mod error;
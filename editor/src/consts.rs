@@ -30,6 +30,13 @@ pub const SNAP_POINT_UNSNAPPED_OPACITY: f64 = 0.4;
 pub const SNAP_POINT_TOLERANCE: f64 = 5.;
 pub const SNAP_POINT_SIZE: f64 = 5.;
 
+// Snapping curve
+pub const SNAP_CURVE_SAMPLES: usize = 32;
+pub const SNAP_CURVE_END_TOLERANCE: f64 = 1e-6;
+
+// Snapping measurement
+pub const SNAP_MEASUREMENT_LABEL_OFFSET: f64 = 8.;
+
 pub const DRAG_THRESHOLD: f64 = 1.;
 
 pub const PATH_OUTLINE_WEIGHT: f64 = 2.;
@@ -72,16 +79,29 @@ pub const ASYMPTOTIC_EFFECT: f64 = 0.5;
 pub const SCALE_EFFECT: f64 = 0.5;
 
 // Colors
+pub const EXTRACTED_PALETTE_SIZE: usize = 8;
 pub const COLOR_ACCENT: Color = Color::from_rgbf32_unchecked(0x00 as f32 / 255., 0xA8 as f32 / 255., 0xFF as f32 / 255.);
 
 // Fonts
 pub const DEFAULT_FONT_FAMILY: &str = "Merriweather";
 pub const DEFAULT_FONT_STYLE: &str = "Normal (400)";
+/// The fonts, as family and style with the ISO 15924 codes of the scripts they're loaded for, which glyphs missing from the font of a text are taken from.
+/// Each is only loaded once a text has characters of its scripts which the loaded fonts don't cover, where the one without scripts is for any other script.
+pub const FALLBACK_FONTS: [(&str, &str, &[&str]); 5] = [
+	("Noto Sans", "Normal (400)", &[]),
+	("Noto Sans Arabic", "Normal (400)", &["Arab"]),
+	("Noto Sans Hebrew", "Normal (400)", &["Hebr"]),
+	("Noto Sans JP", "Normal (400)", &["Hani", "Hira", "Kana"]),
+	("Noto Emoji", "Normal (400)", &["Zsye"]),
+];
 
 // Document
-pub const GRAPHITE_DOCUMENT_VERSION: &str = "0.0.17"; // Remember to update the demo artwork in /demos with both this version number and the contents so it remains editable
+pub const GRAPHITE_DOCUMENT_VERSION: &str = "0.0.19"; // Remember to update the demo artwork in /demos with both this version number and the contents so it remains editable
 pub const DEFAULT_DOCUMENT_NAME: &str = "Untitled Document";
 pub const FILE_SAVE_SUFFIX: &str = ".graphite";
-pub const MAX_UNDO_HISTORY_LEN: usize = 100; // TODO: Add this to user preferences
+pub const BRUSH_PRESET_SUFFIX: &str = ".graphite-brush";
+pub const MAX_UNDO_HISTORY_SIZE: usize = 256 * 1024 * 1024; // In bytes. TODO: Add this to user preferences
+pub const MAX_JOURNAL_ENTRIES: usize = 256; // Entries journaled since the last full save, after which the document is saved in full again to keep its journal short
+pub const HISTORY_COALESCING_INTERVAL: f64 = 500.; // In milliseconds between the pointer movements of successive changes to the same node values, such as while a number is dragged
 
 pub const VIEWPORT_ZOOM_TO_FIT_PADDING_SCALE_FACTOR: f32 = 1.05;
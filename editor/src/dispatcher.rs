@@ -1,5 +1,6 @@
 use crate::consts::{DEFAULT_FONT_FAMILY, DEFAULT_FONT_STYLE};
 use crate::messages::debug::utility_types::MessageLoggingVerbosity;
+use crate::messages::portfolio::document::utility_types::history::step_label;
 use crate::messages::prelude::*;
 
 use graphene_core::text::Font;
@@ -86,6 +87,7 @@ impl Dispatcher {
 
 			// Create a new queue for the child messages
 			let mut queue = VecDeque::new();
+			let discriminant = message.to_discriminant();
 
 			// Process the action by forwarding it to the relevant message handler, or saving the FrontendMessage to be sent to the frontend
 			#[remain::sorted]
@@ -182,6 +184,9 @@ impl Dispatcher {
 				}
 			}
 
+			// Name the history steps of the transactions started by this message after it
+			Self::label_transactions(&mut queue, discriminant);
+
 			// If there are child messages, append the queue to the list of queues
 			if !queue.is_empty() {
 				self.message_queues.push(queue);
@@ -191,6 +196,31 @@ impl Dispatcher {
 		}
 	}
 
+	/// Follows each message in the queue which starts a transaction with one naming the history step it will become after the message which caused it.
+	fn label_transactions(queue: &mut VecDeque<Message>, cause: MessageDiscriminant) {
+		let starts_transaction = |message: &Message| {
+			matches!(
+				message,
+				Message::Portfolio(PortfolioMessage::Document(
+					DocumentMessage::StartTransaction | DocumentMessage::StartCoalescingTransaction | DocumentMessage::BackupDocument { .. }
+				))
+			)
+		};
+		if !queue.iter().any(starts_transaction) {
+			return;
+		}
+
+		let label = step_label(&cause.local_name());
+		let mut index = 0;
+		while index < queue.len() {
+			if starts_transaction(&queue[index]) {
+				index += 1;
+				queue.insert(index, DocumentMessage::LabelTransaction { label: label.clone() }.into());
+			}
+			index += 1;
+		}
+	}
+
 	pub fn collect_actions(&self) -> ActionList {
 		// TODO: Reduce the number of heap allocations
 		let mut list = Vec::new();
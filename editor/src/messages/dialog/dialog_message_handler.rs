@@ -90,6 +90,8 @@ impl MessageHandler<DialogMessage, (&PortfolioMessageHandler, &PreferencesMessag
 
 					self.export_dialog = ExportDialogMessageHandler {
 						scale_factor: 1.,
+						color_profile: document.document_legacy.working_color_space,
+						animation: document.animation,
 						artboards,
 						has_selection: document.selected_layers().next().is_some(),
 						..Default::default()
@@ -1,6 +1,7 @@
 use crate::messages::frontend::utility_types::{ExportBounds, FileType};
 use crate::messages::prelude::*;
 
+use graphene_core::raster::ColorSpace;
 use serde::{Deserialize, Serialize};
 
 #[impl_message(Message, DialogMessage, ExportDialog)]
@@ -8,6 +9,10 @@ use serde::{Deserialize, Serialize};
 pub enum ExportDialogMessage {
 	FileType(FileType),
 	ScaleFactor(f64),
+	HighBitDepth(bool),
+	ColorProfile(ColorSpace),
+	FrameRate(f64),
+	Duration(f64),
 	TransparentBackground(bool),
 	ExportBounds(ExportBounds),
 
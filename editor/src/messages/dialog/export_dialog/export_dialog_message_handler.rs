@@ -1,14 +1,22 @@
 use crate::messages::frontend::utility_types::{ExportBounds, FileType};
 use crate::messages::layout::utility_types::widget_prelude::*;
+use crate::messages::portfolio::document::utility_types::misc::AnimationSettings;
 use crate::messages::prelude::*;
 
 use document_legacy::LayerId;
+use graphene_core::raster::ColorSpace;
 
 /// A dialog to allow users to customize their file export.
 #[derive(Debug, Clone, Default)]
 pub struct ExportDialogMessageHandler {
 	pub file_type: FileType,
 	pub scale_factor: f64,
+	/// Whether PNG and TIFF files are exported with 16 bits per channel instead of 8.
+	pub high_bit_depth: bool,
+	/// The color space raster exports are converted into and tagged with.
+	pub color_profile: ColorSpace,
+	/// The frame rate and duration of animated exports.
+	pub animation: AnimationSettings,
 	pub bounds: ExportBounds,
 	pub transparent_background: bool,
 	pub artboards: HashMap<LayerId, String>,
@@ -20,6 +28,10 @@ impl MessageHandler<ExportDialogMessage, &PortfolioMessageHandler> for ExportDia
 		match message {
 			ExportDialogMessage::FileType(export_type) => self.file_type = export_type,
 			ExportDialogMessage::ScaleFactor(factor) => self.scale_factor = factor,
+			ExportDialogMessage::HighBitDepth(high_bit_depth) => self.high_bit_depth = high_bit_depth,
+			ExportDialogMessage::ColorProfile(color_profile) => self.color_profile = color_profile,
+			ExportDialogMessage::FrameRate(frame_rate) => self.animation.frame_rate = frame_rate,
+			ExportDialogMessage::Duration(duration) => self.animation.duration = duration,
 			ExportDialogMessage::TransparentBackground(transparent_background) => self.transparent_background = transparent_background,
 			ExportDialogMessage::ExportBounds(export_area) => self.bounds = export_area,
 
@@ -27,8 +39,11 @@ impl MessageHandler<ExportDialogMessage, &PortfolioMessageHandler> for ExportDia
 				file_name: portfolio.active_document().map(|document| document.name.clone()).unwrap_or_default(),
 				file_type: self.file_type,
 				scale_factor: self.scale_factor,
+				high_bit_depth: self.high_bit_depth,
+				color_profile: self.color_profile,
 				bounds: self.bounds,
 				transparent_background: self.file_type != FileType::Jpg && self.transparent_background,
+				animation: self.animation,
 			}),
 		}
 
@@ -62,10 +77,21 @@ impl DialogLayoutHolder for ExportDialogMessageHandler {
 
 impl LayoutHolder for ExportDialogMessageHandler {
 	fn layout(&self) -> Layout {
-		let entries = [(FileType::Png, "PNG"), (FileType::Jpg, "JPG"), (FileType::Svg, "SVG")]
-			.into_iter()
-			.map(|(val, name)| RadioEntryData::new(name).on_update(move |_| ExportDialogMessage::FileType(val).into()))
-			.collect();
+		let entries = [
+			(FileType::Png, "PNG"),
+			(FileType::Jpg, "JPG"),
+			(FileType::Svg, "SVG"),
+			(FileType::Pdf, "PDF"),
+			(FileType::Tiff, "TIFF"),
+			(FileType::Exr, "EXR"),
+			(FileType::Gif, "GIF"),
+			(FileType::WebP, "WebP"),
+			(FileType::Apng, "APNG"),
+			(FileType::PngSequence, "PNG Seq"),
+		]
+		.into_iter()
+		.map(|(val, name)| RadioEntryData::new(name).on_update(move |_| ExportDialogMessage::FileType(val).into()))
+		.collect();
 
 		let export_type = vec![
 			TextLabel::new("File Type").table_align(true).min_width(100).widget_holder(),
@@ -80,12 +106,68 @@ impl LayoutHolder for ExportDialogMessageHandler {
 				.unit("")
 				.min(0.)
 				.max((1u64 << std::f64::MANTISSA_DIGITS) as f64)
-				.disabled(self.file_type == FileType::Svg)
+				.disabled(matches!(self.file_type, FileType::Svg | FileType::Pdf))
 				.on_update(|number_input: &NumberInput| ExportDialogMessage::ScaleFactor(number_input.value.unwrap()).into())
 				.min_width(200)
 				.widget_holder(),
 		];
 
+		let bit_depth_entries = [(false, "8-bit"), (true, "16-bit")]
+			.into_iter()
+			.map(|(val, name)| RadioEntryData::new(name).on_update(move |_| ExportDialogMessage::HighBitDepth(val).into()))
+			.collect();
+
+		// Only PNG and TIFF offer a choice, JPEG is always 8-bit and OpenEXR always stores 32-bit floats
+		let bit_depth = vec![
+			TextLabel::new("Bit Depth").table_align(true).min_width(100).widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			RadioInput::new(bit_depth_entries)
+				.selected_index(self.high_bit_depth as u32)
+				.disabled(!matches!(self.file_type, FileType::Png | FileType::Tiff))
+				.widget_holder(),
+		];
+
+		let color_profile_entries = vec![ColorSpace::list()
+			.into_iter()
+			.map(|color_space| DropdownEntryData::new(color_space.to_string()).on_update(move |_| ExportDialogMessage::ColorProfile(color_space).into()))
+			.collect()];
+		let color_profile_index = ColorSpace::list().iter().position(|&color_space| color_space == self.color_profile);
+
+		// Vector formats keep the document's sRGB colors
+		let color_profile = vec![
+			TextLabel::new("Color Profile").table_align(true).min_width(100).widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			DropdownInput::new(color_profile_entries)
+				.selected_index(color_profile_index.map(|index| index as u32))
+				.disabled(matches!(self.file_type, FileType::Svg | FileType::Pdf))
+				.widget_holder(),
+		];
+
+		let frame_rate = vec![
+			TextLabel::new("Frame Rate").table_align(true).min_width(100).widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			NumberInput::new(Some(self.animation.frame_rate))
+				.unit(" fps")
+				.min(1.)
+				.max(120.)
+				.disabled(!self.file_type.is_animated())
+				.on_update(|number_input: &NumberInput| ExportDialogMessage::FrameRate(number_input.value.unwrap()).into())
+				.min_width(200)
+				.widget_holder(),
+		];
+
+		let duration = vec![
+			TextLabel::new("Duration").table_align(true).min_width(100).widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			NumberInput::new(Some(self.animation.duration))
+				.unit(" s")
+				.min(0.)
+				.disabled(!self.file_type.is_animated())
+				.on_update(|number_input: &NumberInput| ExportDialogMessage::Duration(number_input.value.unwrap()).into())
+				.min_width(200)
+				.widget_holder(),
+		];
+
 		let artboards = self.artboards.iter().map(|(&val, name)| (ExportBounds::Artboard(val), name.to_string(), false));
 		let mut export_area_options = vec![
 			(ExportBounds::AllArtwork, "All Artwork".to_string(), false),
@@ -116,6 +198,10 @@ impl LayoutHolder for ExportDialogMessageHandler {
 		Layout::WidgetLayout(WidgetLayout::new(vec![
 			LayoutGroup::Row { widgets: export_type },
 			LayoutGroup::Row { widgets: resolution },
+			LayoutGroup::Row { widgets: bit_depth },
+			LayoutGroup::Row { widgets: color_profile },
+			LayoutGroup::Row { widgets: frame_rate },
+			LayoutGroup::Row { widgets: duration },
 			LayoutGroup::Row { widgets: export_area },
 			LayoutGroup::Row { widgets: transparent_background },
 		]))
@@ -1,6 +1,7 @@
 use super::utility_types::{FrontendDocumentDetails, FrontendImageData, MouseCursorIcon};
 use crate::messages::layout::utility_types::widget_prelude::*;
 use crate::messages::portfolio::document::node_graph::{FrontendNode, FrontendNodeLink, FrontendNodeType};
+use crate::messages::portfolio::document::utility_types::clipboards::PasteMode;
 use crate::messages::portfolio::document::utility_types::layer_panel::{JsRawBuffer, LayerPanelEntry, RawBuffer};
 use crate::messages::prelude::*;
 use crate::messages::tool::utility_types::HintData;
@@ -46,6 +47,12 @@ pub enum FrontendMessage {
 		#[serde(rename = "commitDate")]
 		commit_date: String,
 	},
+	TriggerClipboardWrite {
+		/// The serialized clipboard content, which the editor reads back when it's pasted.
+		content: String,
+		/// A rendering of the copied layers for pasting into other apps.
+		svg: Option<String>,
+	},
 	TriggerCopyToClipboardBlobUrl {
 		#[serde(rename = "blobUrl")]
 		blob_url: String,
@@ -56,16 +63,19 @@ pub enum FrontendMessage {
 		#[serde(rename = "blobUrl")]
 		blob_url: String,
 	},
-	TriggerDownloadRaster {
-		svg: String,
+	TriggerDownloadImage {
 		name: String,
 		mime: String,
-		size: (f64, f64),
+		#[serde(rename = "imageData")]
+		image_data: Vec<u8>,
 	},
 	TriggerDownloadTextFile {
 		document: String,
 		name: String,
 	},
+	TriggerFallbackFontsLoad {
+		fonts: Vec<Font>,
+	},
 	TriggerFetchAndOpenDocument {
 		url: String,
 	},
@@ -78,6 +88,8 @@ pub enum FrontendMessage {
 		open: bool,
 	},
 	TriggerImport,
+	TriggerImportBrushPreset,
+	TriggerImportBrushTip,
 	TriggerIndexedDbRemoveDocument {
 		#[serde(rename = "documentId")]
 		document_id: u64,
@@ -87,10 +99,17 @@ pub enum FrontendMessage {
 		details: FrontendDocumentDetails,
 		version: String,
 	},
+	TriggerIndexedDbWriteJournalEntry {
+		#[serde(rename = "documentId")]
+		document_id: u64,
+		entry: String,
+	},
 	TriggerLoadAutoSaveDocuments,
 	TriggerLoadPreferences,
 	TriggerOpenDocument,
-	TriggerPaste,
+	TriggerPaste {
+		mode: PasteMode,
+	},
 	TriggerRasterizeRegionBelowLayer {
 		#[serde(rename = "documentId")]
 		document_id: u64,
@@ -107,10 +126,6 @@ pub enum FrontendMessage {
 		preferences: PreferencesMessageHandler,
 	},
 	TriggerTextCommit,
-	TriggerTextCopy {
-		#[serde(rename = "copyText")]
-		copy_text: String,
-	},
 	TriggerViewportResize,
 	TriggerVisitLink {
 		url: String,
@@ -197,6 +212,11 @@ pub enum FrontendMessage {
 		layout_target: LayoutTarget,
 		diff: Vec<WidgetDiff>,
 	},
+	UpdateHistoryPanelLayout {
+		#[serde(rename = "layoutTarget")]
+		layout_target: LayoutTarget,
+		diff: Vec<WidgetDiff>,
+	},
 	UpdateImageData {
 		#[serde(rename = "documentId")]
 		document_id: u64,
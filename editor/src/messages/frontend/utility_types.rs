@@ -46,6 +46,14 @@ pub enum FileType {
 	Png,
 	Jpg,
 	Svg,
+	Pdf,
+	Tiff,
+	Exr,
+	Gif,
+	WebP,
+	Apng,
+	/// A ZIP archive of numbered PNG files, one for each frame.
+	PngSequence,
 }
 
 impl FileType {
@@ -54,8 +62,34 @@ impl FileType {
 			FileType::Png => "image/png",
 			FileType::Jpg => "image/jpeg",
 			FileType::Svg => "image/svg+xml",
+			FileType::Pdf => "application/pdf",
+			FileType::Tiff => "image/tiff",
+			FileType::Exr => "image/x-exr",
+			FileType::Gif => "image/gif",
+			FileType::WebP => "image/webp",
+			FileType::Apng => "image/apng",
+			FileType::PngSequence => "application/zip",
 		}
 	}
+
+	pub fn extension(self) -> &'static str {
+		match self {
+			FileType::Png | FileType::Apng => "png",
+			FileType::Jpg => "jpg",
+			FileType::Svg => "svg",
+			FileType::Pdf => "pdf",
+			FileType::Tiff => "tiff",
+			FileType::Exr => "exr",
+			FileType::Gif => "gif",
+			FileType::WebP => "webp",
+			FileType::PngSequence => "zip",
+		}
+	}
+
+	/// Whether the file holds every frame of the document's animation rather than a single image.
+	pub fn is_animated(self) -> bool {
+		matches!(self, FileType::Gif | FileType::WebP | FileType::Apng | FileType::PngSequence)
+	}
 }
 
 #[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Serialize, Deserialize, specta::Type)]
@@ -60,11 +60,47 @@ impl ScrollDelta {
 	}
 }
 
+/// The input of a pen or other pressure sensitive pointer which accompanies its position.
+#[derive(Debug, Copy, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StylusState {
+	/// Normalized pressure in the 0 to 1 range. Browsers report 0.5 for a mouse with a button held down.
+	pub pressure: f64,
+	/// The tilt of the pen along the x and y axes of the screen, in degrees between -90 and 90.
+	pub tilt: DVec2,
+	/// The clockwise rotation of the pen around its own axis, in degrees between 0 and 360.
+	pub twist: f64,
+	/// The time at which the input was sampled, in milliseconds.
+	pub timestamp: f64,
+}
+
+impl Default for StylusState {
+	fn default() -> Self {
+		Self {
+			pressure: 0.5,
+			tilt: DVec2::ZERO,
+			twist: 0.,
+			timestamp: 0.,
+		}
+	}
+}
+
+impl StylusState {
+	pub fn new(pressure: f64, tilt_x: f64, tilt_y: f64, twist: f64, timestamp: f64) -> Self {
+		Self {
+			pressure,
+			tilt: DVec2::new(tilt_x, tilt_y),
+			twist,
+			timestamp,
+		}
+	}
+}
+
 #[derive(Debug, Copy, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct MouseState {
 	pub position: ViewportPosition,
 	pub mouse_keys: MouseKeys,
 	pub scroll_delta: ScrollDelta,
+	pub stylus: StylusState,
 }
 
 impl MouseState {
@@ -77,6 +113,7 @@ impl MouseState {
 			position: (x, y).into(),
 			mouse_keys: MouseKeys::default(),
 			scroll_delta: ScrollDelta::default(),
+			stylus: StylusState::default(),
 		}
 	}
 
@@ -87,6 +124,7 @@ impl MouseState {
 			position,
 			mouse_keys,
 			scroll_delta: ScrollDelta::default(),
+			stylus: StylusState::default(),
 		}
 	}
 
@@ -103,6 +141,7 @@ pub struct EditorMouseState {
 	pub editor_position: EditorPosition,
 	pub mouse_keys: MouseKeys,
 	pub scroll_delta: ScrollDelta,
+	pub stylus: StylusState,
 }
 
 impl EditorMouseState {
@@ -115,6 +154,7 @@ impl EditorMouseState {
 			editor_position: (x, y).into(),
 			mouse_keys: MouseKeys::default(),
 			scroll_delta: ScrollDelta::default(),
+			stylus: StylusState::default(),
 		}
 	}
 
@@ -125,6 +165,7 @@ impl EditorMouseState {
 			editor_position,
 			mouse_keys,
 			scroll_delta: ScrollDelta::default(),
+			stylus: StylusState::default(),
 		}
 	}
 
@@ -133,6 +174,7 @@ impl EditorMouseState {
 			position: self.editor_position - active_viewport_bounds.top_left,
 			mouse_keys: self.mouse_keys,
 			scroll_delta: self.scroll_delta,
+			stylus: self.stylus,
 		}
 	}
 }
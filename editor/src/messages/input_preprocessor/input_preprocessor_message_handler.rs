@@ -166,7 +166,7 @@ impl InputPreprocessorMessageHandler {
 #[cfg(test)]
 mod test {
 	use crate::messages::input_mapper::utility_types::input_keyboard::{Key, ModifierKeys};
-	use crate::messages::input_mapper::utility_types::input_mouse::EditorMouseState;
+	use crate::messages::input_mapper::utility_types::input_mouse::{EditorMouseState, StylusState};
 	use crate::messages::portfolio::utility_types::KeyboardPlatformLayout;
 	use crate::messages::prelude::*;
 
@@ -218,6 +218,22 @@ mod test {
 		assert_eq!(responses.pop_front(), Some(InputMapperMessage::KeyDown(Key::Shift).into()));
 	}
 
+	#[test]
+	fn process_action_mouse_move_records_stylus() {
+		let mut input_preprocessor = InputPreprocessorMessageHandler::default();
+
+		let mut editor_mouse_state = EditorMouseState::from_editor_position(4., 809.);
+		editor_mouse_state.stylus = StylusState::new(0.75, 30., -15., 90., 1234.);
+		let modifier_keys = ModifierKeys::empty();
+		let message = InputPreprocessorMessage::PointerMove { editor_mouse_state, modifier_keys };
+
+		let mut responses = VecDeque::new();
+
+		input_preprocessor.process_message(message, &mut responses, KeyboardPlatformLayout::Standard);
+
+		assert_eq!(input_preprocessor.mouse.stylus, StylusState::new(0.75, 30., -15., 90., 1234.));
+	}
+
 	#[test]
 	fn process_action_key_down_handle_modifier_keys() {
 		let mut input_preprocessor = InputPreprocessorMessageHandler::default();
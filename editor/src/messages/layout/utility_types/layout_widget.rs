@@ -27,6 +27,8 @@ pub enum LayoutTarget {
 	DocumentMode,
 	/// The button below the tool shelf and directly above the working colors which lets the user toggle the node graph overlaid on the canvas.
 	GraphViewOverlayButton,
+	/// The steps and snapshots of the document's history listed in the History panel.
+	HistoryPanel,
 	/// Options for opacity seen at the top of the Layers panel.
 	LayerTreeOptions,
 	/// The dropdown menu at the very top of the application: File, Edit, etc.
@@ -1,7 +1,8 @@
 use crate::messages::frontend::utility_types::{ExportBounds, FileType};
 use crate::messages::input_mapper::utility_types::input_keyboard::Key;
+use crate::messages::portfolio::document::utility_types::history::HistoryStepId;
 use crate::messages::portfolio::document::utility_types::layer_panel::LayerMetadata;
-use crate::messages::portfolio::document::utility_types::misc::{AlignAggregate, AlignAxis, FlipAxis};
+use crate::messages::portfolio::document::utility_types::misc::{AlignAggregate, AlignAxis, AnimationSettings, ExportSlice, FlipAxis};
 use crate::messages::prelude::*;
 
 use document_legacy::document::Document as DocumentLegacy;
@@ -10,7 +11,7 @@ use document_legacy::layers::style::ViewMode;
 use document_legacy::LayerId;
 use document_legacy::Operation as DocumentOperation;
 use graph_craft::document::NodeId;
-use graphene_core::raster::Image;
+use graphene_core::raster::{ColorSpace, Image};
 use graphene_core::Color;
 use serde::{Deserialize, Serialize};
 
@@ -62,7 +63,11 @@ pub enum DocumentMessage {
 	CreateEmptyFolder {
 		container_path: Vec<LayerId>,
 	},
+	CreateHistorySnapshot,
 	DebugPrintDocument,
+	DeleteHistorySnapshot {
+		index: usize,
+	},
 	DeleteLayer {
 		layer_path: Vec<LayerId>,
 	},
@@ -72,17 +77,27 @@ pub enum DocumentMessage {
 	DirtyRenderDocumentInOutlineView,
 	DocumentHistoryBackward,
 	DocumentHistoryForward,
+	DocumentHistoryJump {
+		step: Option<HistoryStepId>,
+	},
 	DocumentStructureChanged,
 	DownloadLayerImageOutput {
 		layer_path: Vec<LayerId>,
 	},
 	DuplicateSelectedLayers,
+	ExportAllSlices,
 	ExportDocument {
 		file_name: String,
 		file_type: FileType,
 		scale_factor: f64,
+		high_bit_depth: bool,
+		color_profile: ColorSpace,
 		bounds: ExportBounds,
 		transparent_background: bool,
+		animation: AnimationSettings,
+	},
+	ExtractPalette {
+		layer_path: Vec<LayerId>,
 	},
 	FlipSelectedLayers {
 		flip_axis: FlipAxis,
@@ -106,6 +121,12 @@ pub enum DocumentMessage {
 	InputFrameRasterizeRegionBelowLayer {
 		layer_path: Vec<LayerId>,
 	},
+	JumpToHistoryStep {
+		step: Option<HistoryStepId>,
+	},
+	LabelTransaction {
+		label: String,
+	},
 	LayerChanged {
 		affected_layer_path: Vec<LayerId>,
 	},
@@ -114,6 +135,10 @@ pub enum DocumentMessage {
 		insert_index: isize,
 		reverse_index: bool,
 	},
+	NodeGraphEvaluated {
+		layer_path: Vec<LayerId>,
+		time: f64,
+	},
 	NudgeSelectedLayers {
 		delta_x: f64,
 		delta_y: f64,
@@ -124,14 +149,33 @@ pub enum DocumentMessage {
 		image: Image<Color>,
 		mouse: Option<(f64, f64)>,
 	},
+	PasteImageFile {
+		data: Vec<u8>,
+		mouse: Option<(f64, f64)>,
+	},
+	PasteLayeredImage {
+		name: String,
+		data: Vec<u8>,
+		mouse: Option<(f64, f64)>,
+	},
+	PasteSvg {
+		name: String,
+		svg: String,
+		mouse: Option<(f64, f64)>,
+	},
 	Redo,
 	RenameDocument {
 		new_name: String,
 	},
+	RenameHistorySnapshot {
+		index: usize,
+		name: String,
+	},
 	RenameLayer {
 		layer_path: Vec<LayerId>,
 		new_name: String,
 	},
+	RenderAnimationFrame,
 	RenderDocument,
 	RollbackTransaction,
 	SaveDocument,
@@ -151,6 +195,9 @@ pub enum DocumentMessage {
 	SetBlendModeForSelectedLayers {
 		blend_mode: BlendMode,
 	},
+	SetExportSlice {
+		slice: ExportSlice,
+	},
 	SetImageBlobUrl {
 		layer_path: Vec<LayerId>,
 		blob_url: String,
@@ -179,9 +226,19 @@ pub enum DocumentMessage {
 		bounding_box_snapping: Option<bool>,
 		node_snapping: Option<bool>,
 	},
+	SetSwatches {
+		swatches: Vec<Color>,
+	},
+	SetTime {
+		time: f64,
+	},
 	SetViewMode {
 		view_mode: ViewMode,
 	},
+	SetWorkingColorSpace {
+		color_space: ColorSpace,
+	},
+	StartCoalescingTransaction,
 	StartTransaction,
 	ToggleLayerExpansion {
 		layer_path: Vec<LayerId>,
@@ -1,15 +1,22 @@
 use super::utility_types::error::EditorError;
 use super::utility_types::misc::{DocumentRenderMode, SnappingOptions, SnappingState};
 use crate::application::generate_uuid;
-use crate::consts::{ASYMPTOTIC_EFFECT, DEFAULT_DOCUMENT_NAME, FILE_SAVE_SUFFIX, GRAPHITE_DOCUMENT_VERSION, SCALE_EFFECT, SCROLLBAR_SPACING, VIEWPORT_ZOOM_TO_FIT_PADDING_SCALE_FACTOR};
+use crate::consts::{
+	ASYMPTOTIC_EFFECT, DEFAULT_DOCUMENT_NAME, EXTRACTED_PALETTE_SIZE, FILE_SAVE_SUFFIX, GRAPHITE_DOCUMENT_VERSION, HISTORY_COALESCING_INTERVAL, SCALE_EFFECT, SCROLLBAR_SPACING,
+	VIEWPORT_ZOOM_TO_FIT_PADDING_SCALE_FACTOR,
+};
 use crate::messages::frontend::utility_types::ExportBounds;
 use crate::messages::frontend::utility_types::FileType;
 use crate::messages::input_mapper::utility_types::macros::action_keys;
 use crate::messages::layout::utility_types::widget_prelude::*;
 use crate::messages::portfolio::document::properties_panel::utility_types::PropertiesPanelMessageHandlerData;
-use crate::messages::portfolio::document::utility_types::clipboards::Clipboard;
+use crate::messages::portfolio::document::utility_types::clipboards::{node_fonts, Clipboard};
+use crate::messages::portfolio::document::utility_types::history::{step_label, Coalescing, DocumentHistory, HistoryStepId};
+use crate::messages::portfolio::document::utility_types::journal::DocumentJournal;
 use crate::messages::portfolio::document::utility_types::layer_panel::{LayerMetadata, LayerPanelEntry, RawBuffer};
-use crate::messages::portfolio::document::utility_types::misc::{AlignAggregate, AlignAxis, DocumentMode, DocumentSave, FlipAxis};
+use crate::messages::portfolio::document::utility_types::migration::{self, UPGRADABLE_DOCUMENT_VERSIONS};
+use crate::messages::portfolio::document::utility_types::misc::{export_file_names, AlignAggregate, AlignAxis, AnimationSettings, DocumentMode, DocumentSave, ExportSlice, FlipAxis, TargetDocument};
+use crate::messages::portfolio::document::utility_types::svg_import::{self, SvgElement, SvgImport};
 use crate::messages::portfolio::document::utility_types::vectorize_layer_metadata;
 use crate::messages::portfolio::utility_types::PersistentData;
 use crate::messages::prelude::*;
@@ -19,17 +26,27 @@ use crate::node_graph_executor::NodeGraphExecutor;
 use document_legacy::document::Document as DocumentLegacy;
 use document_legacy::layers::blend_mode::BlendMode;
 use document_legacy::layers::folder_layer::FolderLayer;
-use document_legacy::layers::layer_info::{LayerDataType, LayerDataTypeDiscriminant};
+use document_legacy::layers::layer_info::{Layer, LayerData, LayerDataType, LayerDataTypeDiscriminant};
 use document_legacy::layers::layer_layer::CachedOutputData;
-use document_legacy::layers::style::{RenderData, ViewMode};
+use document_legacy::layers::style::{PathStyle, RenderData, Stroke, ViewMode};
 use document_legacy::{DocumentError, DocumentResponse, LayerId, Operation as DocumentOperation};
 use graph_craft::document::value::TaggedValue;
-use graph_craft::document::{NodeInput, NodeNetwork};
-use graphene_core::raster::ImageFrame;
+use graph_craft::document::{DocumentNodeImplementation, NodeInput, NodeNetwork};
+use graphene_core::raster::color_space::convert_with;
+use graphene_core::raster::{dominant_colors, ColorSpace, Image, ImageFrame, LuminanceCalculation};
+use graphene_core::renderer::{GraphicElementRendered, Paint, RasterRender, RenderParams};
 use graphene_core::text::Font;
-
-use glam::{DAffine2, DVec2};
+use graphene_core::vector::{StyleRegion, VectorData};
+use graphene_core::{Color, GraphicElement, GraphicElementData, GraphicGroup};
+use graphene_std::animation::{encode_animation, AnimationFormat, AnimationFrame};
+use graphene_std::archive::zip_files;
+use graphene_std::export::{decode_image, encode_image, RasterFormat};
+use graphene_std::layered_image::{decode_layered_image, LayeredImage};
+use graphene_std::pdf::{encode_pdf, PdfPage};
+
+use glam::{DAffine2, DVec2, UVec2};
 use serde::{Deserialize, Serialize};
+use std::hash::Hash;
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct DocumentMessageHandler {
@@ -44,11 +61,31 @@ pub struct DocumentMessageHandler {
 	#[serde(skip)]
 	pub snapping_state: SnappingState,
 	pub overlays_visible: bool,
+	#[serde(default)]
+	pub animation: AnimationSettings,
+	/// The time in seconds at which the layers' node graphs are evaluated.
+	#[serde(default)]
+	pub time: f64,
+	/// The layers and artboards exported together by the "Export All" action.
+	#[serde(default)]
+	pub export_slices: Vec<ExportSlice>,
+	/// The document's color swatches, which can be filled with the dominant colors of a layer's image.
+	#[serde(default)]
+	pub swatches: Vec<Color>,
+	#[serde(skip)]
+	animation_export: Option<AnimationExport>,
 
 	#[serde(skip)]
-	pub document_undo_history: VecDeque<DocumentSave>,
+	document_history: DocumentHistory,
+	/// The message being handled, after which a step backed up while handling it is named.
+	#[serde(skip)]
+	handled_message: Option<DocumentMessageDiscriminant>,
+	/// The time of the last pointer input when the last coalescing transaction was started, in milliseconds.
 	#[serde(skip)]
-	pub document_redo_history: VecDeque<DocumentSave>,
+	last_coalescing_timestamp: Option<f64>,
+	/// The changes committed since the document was last saved in full, which are written out as they're made so that they can be recovered after a crash.
+	#[serde(skip)]
+	journal: DocumentJournal,
 	/// Don't allow aborting transactions whilst undoing to avoid #559
 	#[serde(skip)]
 	undo_in_progress: bool,
@@ -83,9 +120,16 @@ impl Default for DocumentMessageHandler {
 			view_mode: ViewMode::default(),
 			snapping_state: SnappingState::default(),
 			overlays_visible: true,
-
-			document_undo_history: VecDeque::new(),
-			document_redo_history: VecDeque::new(),
+			animation: AnimationSettings::default(),
+			time: 0.,
+			export_slices: Vec::new(),
+			swatches: Vec::new(),
+			animation_export: None,
+
+			document_history: DocumentHistory::default(),
+			handled_message: None,
+			last_coalescing_timestamp: None,
+			journal: DocumentJournal::default(),
 			undo_in_progress: false,
 
 			layer_metadata: vec![(vec![], LayerMetadata::new(true))].into_iter().collect(),
@@ -100,6 +144,36 @@ impl Default for DocumentMessageHandler {
 	}
 }
 
+/// An animated export in progress, which collects a frame each time the layers finish evaluating at the next frame's time.
+#[derive(Clone, Debug)]
+struct AnimationExport {
+	name: String,
+	file_type: FileType,
+	size: UVec2,
+	transform: DAffine2,
+	transparent_background: bool,
+	/// The artboard the frames are clipped to, when exporting one.
+	artboard: Option<LayerId>,
+	color_profile: ColorSpace,
+	format: AnimationFormat,
+	frames: Vec<AnimationFrame>,
+	/// The layers whose graphs have yet to be evaluated at the current frame's time.
+	pending_layers: HashSet<Vec<LayerId>>,
+	/// The document's time before exporting, which is restored afterwards.
+	previous_time: f64,
+}
+
+/// The parts of the document drawn into a raster export.
+#[derive(Clone, Copy, Debug)]
+struct ExportRegion<'a> {
+	/// Whether only the artwork is drawn, leaving out the artboards and the area around them which are otherwise drawn below it.
+	transparent_background: bool,
+	/// The layer whose artwork is drawn along with its children, or an empty path for all of the artwork.
+	artwork: &'a [LayerId],
+	/// The artboard the artwork is clipped to, when exporting one.
+	artboard: Option<LayerId>,
+}
+
 impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &PersistentData, &PreferencesMessageHandler, &mut NodeGraphExecutor)> for DocumentMessageHandler {
 	#[remain::check]
 	fn process_message(
@@ -110,6 +184,8 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 	) {
 		use DocumentMessage::*;
 
+		self.handled_message = Some(message.to_discriminant());
+		self.write_journal(document_id, responses);
 		let render_data = RenderData::new(&persistent_data.font_cache, self.view_mode, Some(ipp.document_bounds()));
 
 		#[remain::sorted]
@@ -197,6 +273,8 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					artwork_document: &self.document_legacy,
 					artboard_document: &self.artboard_message_handler.artboards_document,
 					selected_layers: &mut self.layer_metadata.iter().filter_map(|(path, data)| data.selected.then_some(path.as_slice())),
+					export_slices: &self.export_slices,
+					swatches: &self.swatches,
 					node_graph_message_handler: &self.node_graph_handler,
 					executor,
 				};
@@ -214,7 +292,7 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 			// Messages
 			AbortTransaction => {
 				if !self.undo_in_progress {
-					self.undo(responses).unwrap_or_else(|e| warn!("{}", e));
+					self.abort(responses).unwrap_or_else(|e| warn!("{}", e));
 					responses.extend([RenderDocument.into(), DocumentStructureChanged.into()]);
 				}
 			}
@@ -276,8 +354,17 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					layout: Layout::WidgetLayout(Default::default()),
 					layout_target: LayoutTarget::LayerTreeOptions,
 				});
+
+				// Clear the History panel
+				responses.add(LayoutMessage::SendLayout {
+					layout: Layout::WidgetLayout(Default::default()),
+					layout_target: LayoutTarget::HistoryPanel,
+				});
+			}
+			CommitTransaction => {
+				self.record_journal();
+				self.write_journal(document_id, responses);
 			}
-			CommitTransaction => (),
 			CopyToClipboardLayerImageOutput { layer_path } => {
 				let layer = self.document_legacy.layer(&layer_path).ok();
 
@@ -300,9 +387,18 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					set_expanded: true,
 				});
 			}
+			CreateHistorySnapshot => {
+				let name = format!("Snapshot {}", self.document_history.snapshots().len() + 1);
+				self.document_history.snapshot(name, &self.document_legacy, &self.artboard_message_handler, &self.layer_metadata);
+				self.update_history_panel(responses);
+			}
 			DebugPrintDocument => {
 				info!("{:#?}\n{:#?}", self.document_legacy, self.layer_metadata);
 			}
+			DeleteHistorySnapshot { index } => {
+				self.document_history.delete_snapshot(index);
+				self.update_history_panel(responses);
+			}
 			DeleteLayer { layer_path } => {
 				responses.add_front(DocumentOperation::DeleteLayer { path: layer_path.clone() });
 				responses.add_front(BroadcastEvent::ToolAbort);
@@ -334,9 +430,13 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 			}
 			DocumentHistoryBackward => self.undo(responses).unwrap_or_else(|e| warn!("{}", e)),
 			DocumentHistoryForward => self.redo(responses).unwrap_or_else(|e| warn!("{}", e)),
+			DocumentHistoryJump { step } => self.jump(step, responses).unwrap_or_else(|e| warn!("{}", e)),
 			DocumentStructureChanged => {
 				let data_buffer: RawBuffer = self.serialize_root().as_slice().into();
-				responses.add(FrontendMessage::UpdateDocumentLayerTreeStructure { data_buffer })
+				responses.add(FrontendMessage::UpdateDocumentLayerTreeStructure { data_buffer });
+
+				// The pixels of deleted layers are no longer needed for exports
+				executor.retain_layer_images(document_id, &self.document_legacy);
 			}
 			DownloadLayerImageOutput { layer_path } => {
 				let layer = self.document_legacy.layer(&layer_path).ok();
@@ -357,15 +457,102 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					responses.add(DocumentOperation::DuplicateLayer { path: path.to_vec() });
 				}
 			}
+			ExportAllSlices => {
+				let old_transforms = self.remove_document_transform();
+
+				let slices = self.export_slices.clone();
+				let images = &executor.layer_images(document_id, &self.document_legacy);
+				let mut names = export_file_names(&slices).into_iter();
+				let mut files = Vec::new();
+				let mut mime = "";
+				for slice in &slices {
+					// Artboards are exported along with their background and the artwork over them, whereas layers are exported on their own over transparency
+					let (document, transparent_background, artwork, artboard) = match slice.document {
+						TargetDocument::Artwork => (&self.document_legacy, true, slice.layer_path.as_slice(), None),
+						TargetDocument::Artboard => (&self.artboard_message_handler.artboards_document, false, [].as_slice(), slice.layer_path.last().copied()),
+					};
+					let render_mode = || if artwork.is_empty() { DocumentRenderMode::Root } else { DocumentRenderMode::Layer(artwork) };
+					// Slices of deleted or empty layers have nothing to export
+					let bounds = document
+						.viewport_bounding_box(&slice.layer_path, &render_data)
+						.ok()
+						.flatten()
+						.filter(|[min, max]| (*max - *min).cmpgt(DVec2::ZERO).all());
+
+					for (format, name) in slice.formats.iter().zip(names.by_ref()) {
+						let Some(bounds) = bounds else { continue };
+						let size = bounds[1] - bounds[0];
+						let transform = (DAffine2::from_translation(bounds[0]) * DAffine2::from_scale(size)).inverse();
+
+						let data = match format.file_type {
+							FileType::Svg => Ok(self.render_document(size, transform, transparent_background, persistent_data, render_mode()).into_bytes()),
+							FileType::Pdf => Ok(encode_pdf(&[self.render_document_pdf_page(bounds, transparent_background, artwork, images)])),
+							file_type => {
+								let size = (size * format.scale_factor).round().max(DVec2::ONE).as_uvec2();
+								// Assets are handed off in sRGB, the color space displays and apps assume for untagged images
+								let format = raster_format(file_type, false);
+								let region = ExportRegion {
+									transparent_background,
+									artwork,
+									artboard,
+								};
+								self.encode_raster_export(size, transform, format, ColorSpace::Srgb, region, images)
+							}
+						};
+						match data {
+							Ok(data) => {
+								files.push((name, data));
+								mime = format.file_type.to_mime();
+							}
+							Err(description) => {
+								responses.add(DialogMessage::DisplayDialogError {
+									title: format!("Unable to export {name}"),
+									description,
+								});
+							}
+						}
+					}
+				}
+
+				self.restore_document_transform(old_transforms);
+
+				// A single file is downloaded as is, while several are packaged together since the browser can only download one file at a time
+				match files.len() {
+					0 => {}
+					1 => {
+						let (name, image_data) = files.remove(0);
+						responses.add(FrontendMessage::TriggerDownloadImage {
+							name,
+							mime: mime.to_string(),
+							image_data,
+						});
+					}
+					_ => responses.add(FrontendMessage::TriggerDownloadImage {
+						name: format!("{}.zip", self.name.trim_end_matches(FILE_SAVE_SUFFIX)),
+						mime: "application/zip".to_string(),
+						image_data: zip_files(&files),
+					}),
+				}
+			}
 			ExportDocument {
 				file_name,
 				file_type,
 				scale_factor,
+				high_bit_depth,
+				color_profile,
 				bounds,
 				transparent_background,
+				animation,
 			} => {
 				let old_transforms = self.remove_document_transform();
 
+				// PDF exports of documents with artboards get a page for each artboard
+				let artboard_pages = file_type == FileType::Pdf && bounds == ExportBounds::AllArtwork && !self.artboard_message_handler.artboard_ids.is_empty();
+				let artboard = match bounds {
+					ExportBounds::Artboard(id) => Some(id),
+					_ => None,
+				};
+
 				// Calculate the bounding box of the region to be exported
 				let bounds = match bounds {
 					ExportBounds::AllArtwork => self.all_layer_bounds(&render_data),
@@ -376,22 +563,103 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 				let size = bounds[1] - bounds[0];
 				let transform = (DAffine2::from_translation(bounds[0]) * DAffine2::from_scale(size)).inverse();
 
-				let document = self.render_document(size, transform, transparent_background, persistent_data, DocumentRenderMode::Root);
-
-				self.restore_document_transform(old_transforms);
-
-				let file_suffix = &format!(".{file_type:?}").to_lowercase();
+				let file_suffix = &format!(".{}", file_type.extension());
 				let name = match file_name.ends_with(FILE_SAVE_SUFFIX) {
 					true => file_name.replace(FILE_SAVE_SUFFIX, file_suffix),
 					false => file_name + file_suffix,
 				};
 
-				if file_type == FileType::Svg {
+				if file_type.is_animated() {
+					// Frames are collected asynchronously as the layers' graphs are evaluated at each frame's time
+					if transform.is_finite() && self.animation_export.is_none() {
+						let format = match file_type {
+							FileType::Gif => AnimationFormat::Gif,
+							FileType::WebP => AnimationFormat::WebP,
+							FileType::Apng => AnimationFormat::Apng,
+							_ => AnimationFormat::PngSequence,
+						};
+						let size = (size * scale_factor).round().max(DVec2::ONE).as_uvec2();
+						if let Err(description) = format.check_size(size.x, size.y, animation.frame_count()) {
+							responses.add(DialogMessage::DisplayDialogError {
+								title: "Unable to export the document".to_string(),
+								description,
+							});
+							return;
+						}
+
+						self.animation = animation;
+						self.animation_export = Some(AnimationExport {
+							name,
+							file_type,
+							size,
+							transform,
+							transparent_background,
+							artboard,
+							color_profile,
+							format,
+							frames: Vec::new(),
+							pending_layers: HashSet::new(),
+							previous_time: self.time,
+						});
+						responses.add(RenderAnimationFrame);
+					}
+				} else if file_type == FileType::Svg {
+					let document = self.render_document(size, transform, transparent_background, persistent_data, DocumentRenderMode::Root);
 					responses.add(FrontendMessage::TriggerDownloadTextFile { document, name });
-				} else {
-					let mime = file_type.to_mime().to_string();
-					let size = (size * scale_factor).into();
-					responses.add(FrontendMessage::TriggerDownloadRaster { svg: document, name, mime, size });
+				} else if file_type == FileType::Pdf {
+					let page_bounds = match artboard_pages {
+						true => self
+							.artboard_message_handler
+							.artboard_ids
+							.iter()
+							.filter_map(|&id| self.artboard_message_handler.artboards_document.layer(&[id]).ok().and_then(|layer| layer.aabb(&render_data)))
+							.collect(),
+						false => vec![bounds],
+					};
+					let images = &executor.layer_images(document_id, &self.document_legacy);
+					let pages = page_bounds
+						.into_iter()
+						.filter(|[min, max]| (*max - *min).cmpgt(DVec2::ZERO).all())
+						.map(|bounds| self.render_document_pdf_page(bounds, transparent_background, &[], images))
+						.collect::<Vec<_>>();
+
+					// An empty export region has no pages
+					if !pages.is_empty() {
+						let mime = file_type.to_mime().to_string();
+						responses.add(FrontendMessage::TriggerDownloadImage {
+							name,
+							mime,
+							image_data: encode_pdf(&pages),
+						});
+					}
+				} else if transform.is_finite() {
+					// An empty export region has nothing to rasterize
+					let size = (size * scale_factor).round().max(DVec2::ONE).as_uvec2();
+					let format = raster_format(file_type, high_bit_depth);
+					let region = ExportRegion {
+						transparent_background,
+						artwork: &[],
+						artboard,
+					};
+					match self.encode_raster_export(size, transform, format, color_profile, region, &executor.layer_images(document_id, &self.document_legacy)) {
+						Ok(image_data) => {
+							let mime = file_type.to_mime().to_string();
+							responses.add(FrontendMessage::TriggerDownloadImage { name, mime, image_data });
+						}
+						Err(description) => responses.add(DialogMessage::DisplayDialogError {
+							title: "Unable to export the document".to_string(),
+							description,
+						}),
+					}
+				}
+
+				self.restore_document_transform(old_transforms);
+			}
+			ExtractPalette { layer_path } => {
+				if let Some(image) = executor.layer_images(document_id, &self.document_legacy).get(&layer_path) {
+					let palette = dominant_colors(image, EXTRACTED_PALETTE_SIZE);
+					let swatches = palette.into_iter().map(|entry| entry.color).collect();
+					responses.add(SetSwatches { swatches });
 				}
 			}
 			FlipSelectedLayers { flip_axis } => {
@@ -501,6 +769,19 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					responses.add(message);
 				}
 			}
+			JumpToHistoryStep { step } => {
+				self.undo_in_progress = true;
+				responses.add(BroadcastEvent::ToolAbort);
+				responses.add(DocumentHistoryJump { step });
+				responses.add(BroadcastEvent::DocumentIsDirty);
+				responses.add(RenderDocument);
+				responses.add(FolderChanged { affected_folder_path: vec![] });
+				responses.add(UndoFinished);
+			}
+			LabelTransaction { label } => {
+				self.document_history.set_label(label);
+				self.update_history_panel(responses);
+			}
 			LayerChanged { affected_layer_path } => {
 				if let Ok(layer_entry) = self.layer_panel_entry(affected_layer_path.clone(), &render_data) {
 					responses.add(FrontendMessage::UpdateDocumentLayerDetails { data: layer_entry });
@@ -530,6 +811,13 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					insert_index,
 				});
 			}
+			NodeGraphEvaluated { layer_path, time } => {
+				let Some(export) = &mut self.animation_export else { return };
+				// Ignore evaluations from before the current frame's time was set
+				if time == self.time && export.pending_layers.remove(&layer_path) && export.pending_layers.is_empty() {
+					self.capture_animation_frame(document_id, executor, responses);
+				}
+			}
 			NudgeSelectedLayers {
 				delta_x,
 				delta_y,
@@ -581,21 +869,8 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 			PasteImage { image, mouse } => {
 				let image_size = DVec2::new(image.width as f64, image.height as f64);
 
-				let Some(image_node_type) = crate::messages::portfolio::document::node_graph::resolve_document_node_type("Image") else {
-					warn!("Image node should be in registry");
-					return;
-				};
-				let Some(transform_node_type) = crate::messages::portfolio::document::node_graph::resolve_document_node_type("Transform") else {
-					warn!("Transform node should be in registry");
-					return;
-				};
-				let Some(downres_node_type) = crate::messages::portfolio::document::node_graph::resolve_document_node_type("Downres") else {
-					warn!("Downres node should be in registry");
-					return;
-				};
-
+				let Some(network) = image_layer_network(image) else { return };
 				let path = vec![generate_uuid()];
-				let mut network = NodeNetwork::default();
 
 				// Transform of parent folder
 				let to_parent_folder = self.document_legacy.generate_transform_across_scope(&path[..path.len() - 1], None).unwrap_or_default();
@@ -615,20 +890,6 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 
 				responses.add(DocumentMessage::StartTransaction);
 
-				network.push_node(
-					image_node_type.to_document_node(
-						[graph_craft::document::NodeInput::value(
-							graph_craft::document::value::TaggedValue::ImageFrame(ImageFrame { image, transform: DAffine2::IDENTITY }),
-							false,
-						)],
-						graph_craft::document::DocumentNodeMetadata::position((8, 4)),
-					),
-					false,
-				);
-				network.push_node(transform_node_type.to_document_node_default_inputs([], Default::default()), true);
-				network.push_node(downres_node_type.to_document_node_default_inputs([], Default::default()), true);
-				network.push_output_node();
-
 				responses.add(DocumentOperation::AddFrame {
 					path: path.clone(),
 					insert_index: -1,
@@ -651,6 +912,96 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 				// Force chosen tool to be Select Tool after importing image.
 				responses.add(ToolMessage::ActivateTool { tool_type: ToolType::Select });
 			}
+			PasteImageFile { data, mouse } => match decode_image(&data, self.document_legacy.working_color_space) {
+				Ok(image) => responses.add(PasteImage { image, mouse }),
+				Err(error) => responses.add(DialogMessage::DisplayDialogError {
+					title: "Unable to import the image".to_string(),
+					description: error.to_string(),
+				}),
+			},
+			PasteLayeredImage { name, data, mouse } => {
+				let LayeredImage { width, height, layers } = match decode_layered_image(&data, self.document_legacy.working_color_space) {
+					Ok(image) => image,
+					Err(description) => {
+						responses.add(DialogMessage::DisplayDialogError {
+							title: "Unable to import the layered image".to_string(),
+							description,
+						});
+						return;
+					}
+				};
+
+				let path = vec![generate_uuid()];
+				let placement = self.import_placement(&path, DVec2::new(width as f64, height as f64), mouse, ipp);
+
+				responses.add(DocumentMessage::StartTransaction);
+				responses.add(DocumentMessage::DeselectAllLayers);
+
+				// The folder holding the layers is named after the file and maps the file's pixels onto the document
+				let name = name.rsplit_once('.').map_or(name.as_str(), |(stem, _)| stem).to_string();
+				responses.add(DocumentOperation::CreateFolder { path: path.clone(), insert_index: -1 });
+				responses.add(DocumentOperation::SetLayerTransform {
+					path: path.clone(),
+					transform: placement.to_cols_array(),
+				});
+				responses.add(DocumentOperation::SetLayerName { path: path.clone(), name });
+				for layer in layers.iter().cloned() {
+					let mut layer_path = path.clone();
+					layer_path.push(generate_uuid());
+					add_graphic_element(layer, layer_path, responses);
+				}
+
+				responses.add(DocumentMessage::SetSelectedLayers {
+					replacement_selected_layers: vec![path],
+				});
+
+				// Force chosen tool to be Select Tool after importing the file.
+				responses.add(ToolMessage::ActivateTool { tool_type: ToolType::Select });
+			}
+			PasteSvg { name, svg, mouse } => {
+				let SvgImport { size, root } = match svg_import::parse_svg(&svg, self.document_legacy.working_color_space) {
+					Ok(import) => import,
+					Err(description) => {
+						responses.add(DialogMessage::DisplayDialogError {
+							title: "Unable to import the SVG file".to_string(),
+							description,
+						});
+						return;
+					}
+				};
+
+				let path = vec![generate_uuid()];
+				let placement = self.import_placement(&path, size, mouse, ipp);
+
+				// Name the folder holding the file's contents after the file
+				let name = name.rsplit_once('.').map_or(name.as_str(), |(stem, _)| stem).to_string();
+				let root = match root {
+					SvgElement::Group {
+						transform,
+						blend_mode,
+						opacity,
+						children,
+						..
+					} => SvgElement::Group {
+						name: Some(name).filter(|name| !name.is_empty()),
+						transform: placement * transform,
+						blend_mode,
+						opacity,
+						children,
+					},
+					element => element,
+				};
+
+				responses.add(DocumentMessage::StartTransaction);
+				responses.add(DocumentMessage::DeselectAllLayers);
+				add_svg_element(root, path.clone(), responses);
+				responses.add(DocumentMessage::SetSelectedLayers {
+					replacement_selected_layers: vec![path],
+				});
+
+				// Force chosen tool to be Select Tool after importing the file.
+				responses.add(ToolMessage::ActivateTool { tool_type: ToolType::Select });
+			}
 			Redo => {
 				responses.add(SelectToolMessage::Abort);
 				responses.add(DocumentHistoryForward);
@@ -663,7 +1014,31 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 				responses.add(PortfolioMessage::UpdateOpenDocumentsList);
 				responses.add(NodeGraphMessage::UpdateNewNodeGraph);
 			}
+			RenameHistorySnapshot { index, name } => {
+				self.document_history.rename_snapshot(index, name);
+				self.update_history_panel(responses);
+			}
 			RenameLayer { layer_path, new_name } => responses.add(DocumentOperation::RenameLayer { layer_path, new_name }),
+			RenderAnimationFrame => {
+				let Some(frame) = self.animation_export.as_ref().map(|export| export.frames.len() as u32) else {
+					return;
+				};
+				self.time = self.animation.frame_time(frame);
+
+				// Only the layers whose evaluation was requested are waited on
+				let mut layers = self.visible_layer_networks();
+				layers.retain(|layer_path| match self.rasterize_region_below_layer(document_id, layer_path.clone(), preferences, persistent_data) {
+					Some(message) => {
+						responses.add(message);
+						true
+					}
+					None => false,
+				});
+				match layers.is_empty() {
+					true => self.capture_animation_frame(document_id, executor, responses),
+					false => self.animation_export.as_mut().unwrap().pending_layers = layers.into_iter().collect(),
+				}
+			}
 			RenderDocument => {
 				responses.add(FrontendMessage::UpdateDocumentArtwork {
 					svg: self.document_legacy.render_root(&render_data),
@@ -784,6 +1159,19 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					responses.add(DocumentOperation::SetLayerBlendMode { path: path.to_vec(), blend_mode });
 				}
 			}
+			SetExportSlice { slice } => {
+				let existing = self.export_slices.iter().position(|other| other.layer_path == slice.layer_path && other.document == slice.document);
+				// A slice without any formats is no longer exported
+				match (existing, slice.formats.is_empty()) {
+					(Some(index), true) => {
+						self.export_slices.remove(index);
+					}
+					(Some(index), false) => self.export_slices[index] = slice,
+					(None, false) => self.export_slices.push(slice),
+					(None, true) => {}
+				}
+				responses.add(PropertiesPanelMessage::ResendActiveProperties);
+			}
 			SetImageBlobUrl {
 				layer_path,
 				blob_url,
@@ -866,10 +1254,68 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 					self.snapping_state.node_snapping = state
 				};
 			}
+			SetSwatches { swatches } => {
+				self.swatches = swatches;
+				responses.add(PropertiesPanelMessage::ResendActiveProperties);
+			}
+			SetTime { time } => {
+				self.time = time.max(0.);
+				for layer_path in self.visible_layer_networks() {
+					if let Some(message) = self.rasterize_region_below_layer(document_id, layer_path, preferences, persistent_data) {
+						responses.add(message);
+					}
+				}
+				responses.add(PortfolioMessage::RenderGraphUsingRasterizedRegionBelowLayer {
+					document_id,
+					layer_path: Vec::new(),
+					input_image_data: vec![],
+					size: (0, 0),
+				});
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
 			SetViewMode { view_mode } => {
 				self.view_mode = view_mode;
 				responses.add_front(DocumentMessage::DirtyRenderDocument);
 			}
+			SetWorkingColorSpace { color_space } => {
+				let previous = self.document_legacy.working_color_space;
+				if color_space == previous {
+					return;
+				}
+				self.backup(responses);
+
+				// Existing colors are reinterpreted rather than converted, as when assigning a profile in other editors
+				self.document_legacy.working_color_space = color_space;
+				color_space.hash(&mut self.document_legacy.state_identifier);
+				// Luminance computed for the previous working space is moved over to the new one, while the other calculations are left as they were chosen
+				let (from, to) = (LuminanceCalculation::from(previous), LuminanceCalculation::from(color_space));
+				retarget_luminance_calculations(&mut self.document_legacy.document_network, from, to);
+				retarget_layer_luminance_calculations(&mut self.document_legacy.root.data, from, to);
+
+				// Images loaded by the graphs are decoded into the working space, so the graphs are evaluated again
+				for layer_path in self.visible_layer_networks() {
+					if let Some(message) = self.rasterize_region_below_layer(document_id, layer_path, preferences, persistent_data) {
+						responses.add(message);
+					}
+				}
+				responses.add(PortfolioMessage::RenderGraphUsingRasterizedRegionBelowLayer {
+					document_id,
+					layer_path: Vec::new(),
+					input_image_data: vec![],
+					size: (0, 0),
+				});
+				responses.add(PropertiesPanelMessage::ResendActiveProperties);
+				responses.add(BroadcastEvent::DocumentIsDirty);
+				responses.add(PortfolioMessage::UpdateDocumentWidgets);
+			}
+			StartCoalescingTransaction => {
+				// Changes are only merged while the pointer keeps moving between them, as when a number is dragged, rather than when values are entered one after another
+				let timestamp = ipp.mouse.stylus.timestamp;
+				let coalesce = self.last_coalescing_timestamp.is_some_and(|last| timestamp > last && timestamp - last <= HISTORY_COALESCING_INTERVAL);
+				self.last_coalescing_timestamp = Some(timestamp);
+				let coalescing = if coalesce { Coalescing::Continue } else { Coalescing::Start };
+				self.backup_coalescing(coalescing, responses);
+			}
 			StartTransaction => self.backup(responses),
 			ToggleLayerExpansion { layer_path } => {
 				self.layer_metadata_mut(&layer_path).expanded ^= true;
@@ -985,6 +1431,14 @@ impl MessageHandler<DocumentMessage, (u64, &InputPreprocessorMessageHandler, &Pe
 }
 
 impl DocumentMessageHandler {
+	/// The transform centering imported content of the given size on the mouse or the center of the viewport, in the space of the parent folder.
+	fn import_placement(&self, path: &[LayerId], size: DVec2, mouse: Option<(f64, f64)>, ipp: &InputPreprocessorMessageHandler) -> DAffine2 {
+		let to_parent_folder = self.document_legacy.generate_transform_across_scope(&path[..path.len() - 1], None).unwrap_or_default();
+		let viewport_location = mouse.map_or(ipp.viewport_bounds.center(), |pos| pos.into());
+		let center_in_viewport = DAffine2::from_translation(viewport_location - ipp.viewport_bounds.top_left);
+		to_parent_folder.inverse() * center_in_viewport * DAffine2::from_translation(size / -2.)
+	}
+
 	pub fn rasterize_region_below_layer(&mut self, document_id: u64, layer_path: Vec<LayerId>, _preferences: &PreferencesMessageHandler, persistent_data: &PersistentData) -> Option<Message> {
 		// Prepare the node graph input image
 
@@ -1025,6 +1479,56 @@ impl DocumentMessageHandler {
 		Some(response)
 	}
 
+	/// The visible layers whose output comes from a node graph, which need evaluating again when the time changes.
+	fn visible_layer_networks(&self) -> Vec<Vec<LayerId>> {
+		let mut paths = Vec::new();
+		if self.document_legacy.visible_layers(&mut Vec::new(), &mut paths).is_err() {
+			return Vec::new();
+		}
+		paths.retain(|path| self.document_legacy.layer(path).map_or(false, |layer| layer.as_layer_network().is_ok()));
+		paths
+	}
+
+	/// Renders the current frame of the animated export once every layer has been evaluated at its time, then moves on to the next frame or finishes the export.
+	fn capture_animation_frame(&mut self, document_id: u64, executor: &mut NodeGraphExecutor, responses: &mut VecDeque<Message>) {
+		let Some(mut export) = self.animation_export.take() else { return };
+
+		let old_transforms = self.remove_document_transform();
+		let region = ExportRegion {
+			transparent_background: export.transparent_background,
+			artwork: &[],
+			artboard: export.artboard,
+		};
+		let mut image = self.render_document_raster(export.size, export.transform, region, &executor.layer_images(document_id, &self.document_legacy));
+		self.restore_document_transform(old_transforms);
+		if export.color_profile != self.document_legacy.working_color_space {
+			let conversion = self.document_legacy.working_color_space.conversion_to(export.color_profile);
+			image.data.iter_mut().for_each(|color| *color = convert_with(&conversion, *color));
+		}
+		export.frames.push(AnimationFrame::new(&image, export.color_profile));
+
+		if (export.frames.len() as u32) < self.animation.frame_count() {
+			self.animation_export = Some(export);
+			let message = DocumentMessage::RenderAnimationFrame;
+			responses.add(PortfolioMessage::DocumentPassMessage { document_id, message });
+			return;
+		}
+
+		match encode_animation(export.frames, self.animation.frame_rate, export.format, export.color_profile) {
+			Ok(image_data) => {
+				let mime = export.file_type.to_mime().to_string();
+				responses.add(FrontendMessage::TriggerDownloadImage { name: export.name, mime, image_data });
+			}
+			Err(description) => responses.add(DialogMessage::DisplayDialogError {
+				title: "Unable to export the document".to_string(),
+				description,
+			}),
+		}
+
+		let message = DocumentMessage::SetTime { time: export.previous_time };
+		responses.add(PortfolioMessage::DocumentPassMessage { document_id, message });
+	}
+
 	/// Remove the artwork and artboard pan/tilt/zoom to render it without the user's viewport navigation, and save it to be restored at the end
 	pub(crate) fn remove_document_transform(&mut self) -> [DAffine2; 2] {
 		let old_artwork_transform = self.document_legacy.root.transform;
@@ -1056,6 +1560,13 @@ impl DocumentMessageHandler {
 			DocumentRenderMode::Root => (self.document_legacy.render_root(&render_data), None),
 			DocumentRenderMode::OnlyBelowLayerInFolder(below_layer_path) => (self.document_legacy.render_layers_below(below_layer_path, &render_data).unwrap(), None),
 			DocumentRenderMode::LayerCutout(layer_path, background) => (self.document_legacy.render_layer(layer_path, &render_data).unwrap(), Some(background)),
+			DocumentRenderMode::Layer(layer_path) => {
+				// The layer is rendered in its parent folder's space, so it's placed by the transforms of the folders above it
+				let parent_transform = layer_path.split_last().and_then(|(_, parent_path)| self.document_legacy.multiply_transforms(parent_path).ok());
+				let matrix = parent_transform.unwrap_or_default().to_cols_array().map(|entry| entry.to_string()).join(",");
+				let layer = self.document_legacy.render_layer(layer_path, &render_data).unwrap_or_default();
+				(format!(r#"<g transform="matrix({matrix})">{layer}</g>"#), None)
+			}
 		};
 		let artboards = match transparent_background {
 			false => self.artboard_message_handler.artboards_document.render_root(&render_data),
@@ -1082,6 +1593,110 @@ impl DocumentMessageHandler {
 		svg
 	}
 
+	/// Renders the selected layers on their own into an SVG which is framed to their bounds in document space, for other apps to paste.
+	pub fn render_selected_layers(&mut self, render_data: &RenderData) -> Option<String> {
+		let document_transform = self.document_legacy.root.transform.inverse();
+		let paths = self.selected_layers_without_children().into_iter().map(|path| path.to_vec()).collect::<Vec<_>>();
+
+		let mut bounds: Option<[DVec2; 2]> = None;
+		let mut artwork = String::new();
+		for path in paths {
+			let Some((_, parent_path)) = path.split_last() else { continue };
+			let (Ok(parent_transform), Ok(layer)) = (self.document_legacy.multiply_transforms(parent_path), self.document_legacy.layer(&path)) else {
+				continue;
+			};
+			let parent_transform = document_transform * parent_transform;
+			if let Some([min, max]) = layer.data.bounding_box(parent_transform * layer.transform, render_data) {
+				bounds = Some(bounds.map_or([min, max], |[old_min, old_max]| [old_min.min(min), old_max.max(max)]));
+			}
+
+			let Some(layer_svg) = self.document_legacy.render_layer(&path, render_data) else { continue };
+			let matrix = parent_transform.to_cols_array().map(|entry| entry.to_string()).join(",");
+			artwork += &format!(r#"<g transform="matrix({matrix})">{layer_svg}</g>"#);
+		}
+
+		let [min, max] = bounds?;
+		let size = max - min;
+		Some(format!(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" viewBox="{} {} {} {}" width="{}" height="{}">{artwork}</svg>"#,
+			min.x, min.y, size.x, size.y, size.x, size.y
+		))
+	}
+
+	/// Renders the region of the document like [`Self::render_document`], but into pixels with the CPU rasterizer so raster exports don't depend on the host.
+	fn render_document_raster(&self, size: UVec2, transform: DAffine2, region: ExportRegion, images: &HashMap<Vec<LayerId>, Image<Color>>) -> Image<Color> {
+		let mut render = RasterRender::new(size.x, size.y, DAffine2::from_scale(size.as_dvec2()) * transform);
+		let render_params = RenderParams::new(ViewMode::Normal, None, false);
+		let render_legacy_document = |render: &mut RasterRender, document: &DocumentLegacy, layer_path: &[LayerId]| {
+			let mut graphic_group = GraphicGroup::EMPTY;
+			graphic_group.extend(legacy_document_graphic_element(document, layer_path, images));
+			graphic_group.render_raster(render, &render_params);
+		};
+
+		if !region.transparent_background {
+			let outside_artboards_color = if self.artboard_message_handler.artboard_ids.is_empty() { "ffffff" } else { "222222" };
+			let outside_artboards = Color::from_rgb_str(outside_artboards_color).map_or(Color::WHITE, |color| color.to_gamma_srgb());
+			let image_bounds = vec![DVec2::ZERO, DVec2::new(size.x as f64, 0.), size.as_dvec2(), DVec2::new(0., size.y as f64)];
+			render.fill_polygons(&[image_bounds], &Paint::solid(outside_artboards));
+
+			render_legacy_document(&mut render, &self.artboard_message_handler.artboards_document, &[]);
+		}
+
+		// The artwork is clipped to the artboard being exported, like the contents of an artboard in the node graph
+		let artboard_outline = region.artboard.and_then(|id| {
+			let transform = render.transform * self.artboard_message_handler.artboards_document.multiply_transforms(&[id]).ok()?;
+			Some([DVec2::ZERO, DVec2::X, DVec2::ONE, DVec2::Y].map(|corner| transform.transform_point2(corner)))
+		});
+		match artboard_outline {
+			Some(outline) => {
+				let mut artwork = render.new_layer();
+				render_legacy_document(&mut artwork, &self.document_legacy, region.artwork);
+				artwork.clip(&outline);
+				render.composite(&artwork.image, graphene_core::raster::BlendMode::Normal, 1.);
+			}
+			None => render_legacy_document(&mut render, &self.document_legacy, region.artwork),
+		}
+
+		render.image
+	}
+
+	/// Renders the region of the document mapped onto the unit square by the transform into a raster image of the given size, encoded in the format.
+	fn encode_raster_export(
+		&self,
+		size: UVec2,
+		transform: DAffine2,
+		format: RasterFormat,
+		color_profile: ColorSpace,
+		region: ExportRegion,
+		images: &HashMap<Vec<LayerId>, Image<Color>>,
+	) -> Result<Vec<u8>, String> {
+		let mut image = self.render_document_raster(size, transform, region, images);
+		if color_profile != self.document_legacy.working_color_space {
+			let conversion = self.document_legacy.working_color_space.conversion_to(color_profile);
+			image.data.iter_mut().for_each(|color| *color = convert_with(&conversion, *color));
+		}
+		encode_image(image, format, color_profile)
+	}
+
+	/// A PDF page showing the region of the document within the bounds, which are in document space.
+	///
+	/// Only the artwork of the layer at the `artwork` path is shown, or all of it if the path is empty.
+	pub fn render_document_pdf_page(&self, bounds: [DVec2; 2], transparent_background: bool, artwork: &[LayerId], images: &HashMap<Vec<LayerId>, Image<Color>>) -> PdfPage {
+		// Artboards are drawn below the document as its background
+		let documents = match transparent_background {
+			true => vec![(&self.document_legacy, artwork)],
+			false => vec![(&self.artboard_message_handler.artboards_document, [].as_slice()), (&self.document_legacy, artwork)],
+		};
+		let mut graphic_group = GraphicGroup::EMPTY;
+		graphic_group.extend(documents.into_iter().filter_map(|(document, layer_path)| legacy_document_graphic_element(document, layer_path, images)));
+
+		PdfPage {
+			size: bounds[1] - bounds[0],
+			transform: DAffine2::from_translation(-bounds[0]),
+			graphic_group,
+		}
+	}
+
 	pub fn serialize_document(&self) -> String {
 		let val = serde_json::to_string(self);
 		// We fully expect the serialization to succeed
@@ -1091,7 +1706,11 @@ impl DocumentMessageHandler {
 	pub fn deserialize_document(serialized_content: &str) -> Result<Self, DocumentError> {
 		let deserialized_result: Result<Self, DocumentError> = serde_json::from_str(serialized_content).map_err(|e| DocumentError::InvalidFile(e.to_string()));
 		match deserialized_result {
-			Ok(document) => {
+			Ok(mut document) => {
+				if UPGRADABLE_DOCUMENT_VERSIONS.contains(&document.version.as_str()) {
+					migration::upgrade_document(&mut document.document_legacy);
+					document.version = GRAPHITE_DOCUMENT_VERSION.to_string();
+				}
 				if document.version == GRAPHITE_DOCUMENT_VERSION {
 					Ok(document)
 				} else {
@@ -1123,10 +1742,7 @@ impl DocumentMessageHandler {
 	}
 
 	pub fn is_unmodified_default(&self) -> bool {
-		self.serialize_root().len() == Self::default().serialize_root().len()
-			&& self.document_undo_history.is_empty()
-			&& self.document_redo_history.is_empty()
-			&& self.name.starts_with(DEFAULT_DOCUMENT_NAME)
+		self.serialize_root().len() == Self::default().serialize_root().len() && self.document_history.is_empty() && self.name.starts_with(DEFAULT_DOCUMENT_NAME)
 	}
 
 	fn select_layer(&mut self, path: &[LayerId], render_data: &RenderData) -> Option<Message> {
@@ -1327,11 +1943,10 @@ impl DocumentMessageHandler {
 
 	/// Places a document into the history system
 	fn backup_with_document(&mut self, document: DocumentLegacy, artboard: ArtboardMessageHandler, layer_metadata: HashMap<Vec<LayerId>, LayerMetadata>, responses: &mut VecDeque<Message>) {
-		self.document_redo_history.clear();
-		self.document_undo_history.push_back(DocumentSave { document, artboard, layer_metadata });
-		if self.document_undo_history.len() > crate::consts::MAX_UNDO_HISTORY_LEN {
-			self.document_undo_history.pop_front();
-		}
+		let save = DocumentSave { document, artboard, layer_metadata };
+		self.journal.record(&save.document, &save.artboard, &save.layer_metadata);
+		self.document_history.backup(save, Coalescing::Never, self.handled_message_label());
+		self.update_history_panel(responses);
 
 		// Push the UpdateOpenDocumentsList message to the bus in order to update the save status of the open documents
 		responses.add(PortfolioMessage::UpdateOpenDocumentsList);
@@ -1342,6 +1957,56 @@ impl DocumentMessageHandler {
 		self.backup_with_document(self.document_legacy.clone(), self.artboard_message_handler.clone(), self.layer_metadata.clone(), responses);
 	}
 
+	/// Copies the entire document into the history system, where the step can be merged with the ones next to it if they all only change the values of the same nodes
+	fn backup_coalescing(&mut self, coalescing: Coalescing, responses: &mut VecDeque<Message>) {
+		let save = DocumentSave {
+			document: self.document_legacy.clone(),
+			artboard: self.artboard_message_handler.clone(),
+			layer_metadata: self.layer_metadata.clone(),
+		};
+		self.journal.record(&save.document, &save.artboard, &save.layer_metadata);
+		self.document_history.backup(save, coalescing, self.handled_message_label());
+		self.update_history_panel(responses);
+
+		responses.add(PortfolioMessage::UpdateOpenDocumentsList);
+	}
+
+	/// Records the document's changes since the last entry of its journal, as the start of a transaction or its commit makes the changes before it final.
+	fn record_journal(&mut self) {
+		self.journal.record(&self.document_legacy, &self.artboard_message_handler, &self.layer_metadata);
+	}
+
+	/// Writes out the entries recorded in the journal, or saves the document in full if it was changed before it had a save for the journal to start from or the journal has grown too long.
+	fn write_journal(&mut self, document_id: u64, responses: &mut VecDeque<Message>) {
+		for entry in self.journal.take_entries() {
+			responses.add(FrontendMessage::TriggerIndexedDbWriteJournalEntry { document_id, entry });
+		}
+		if self.journal.take_full_save_request() {
+			responses.add(PortfolioMessage::AutoSaveDocument { document_id });
+		}
+	}
+
+	/// Starts the journal over from the document's current state, once it has been saved in full.
+	pub fn restart_journal(&mut self) {
+		self.journal.restart(DocumentSave {
+			document: self.document_legacy.clone(),
+			artboard: self.artboard_message_handler.clone(),
+			layer_metadata: self.layer_metadata.clone(),
+		});
+	}
+
+	/// Replays the changes journaled since the document was last saved in full, which is the state it was loaded in, returning whether any were recovered.
+	pub fn replay_journal(&mut self, entries: &[String]) -> bool {
+		let replayed = DocumentJournal::replay(entries, &mut self.document_legacy, &mut self.artboard_message_handler, &mut self.layer_metadata);
+		self.restart_journal();
+		replayed > 0
+	}
+
+	/// The label of a step backed up while handling the current message, which is replaced by a `LabelTransaction` message when the transaction was started on behalf of another message.
+	fn handled_message_label(&self) -> String {
+		self.handled_message.map(|message| step_label(&message.local_name())).unwrap_or_default()
+	}
+
 	/// Push a message backing up the document in its current state
 	pub fn backup_nonmut(&self, responses: &mut VecDeque<Message>) {
 		responses.add(DocumentMessage::BackupDocument {
@@ -1353,25 +2018,19 @@ impl DocumentMessageHandler {
 
 	pub fn rollback(&mut self, responses: &mut VecDeque<Message>) -> Result<(), EditorError> {
 		self.backup(responses);
-		self.undo(responses)
+		self.abort(responses)
 		// TODO: Consider if we should check if the document is saved
 	}
 
-	/// Replace the document with a new document save, returning the document save.
-	pub fn replace_document(&mut self, DocumentSave { document, artboard, layer_metadata }: DocumentSave) -> DocumentSave {
-		// Keeping the root is required if the bounds of the viewport have changed during the operation
-		let old_root = self.document_legacy.root.transform;
-		let old_artboard_root = self.artboard_message_handler.artboards_document.root.transform;
-		let document = std::mem::replace(&mut self.document_legacy, document);
-		let artboard = std::mem::replace(&mut self.artboard_message_handler, artboard);
-		self.document_legacy.root.transform = old_root;
-		self.artboard_message_handler.artboards_document.root.transform = old_artboard_root;
-		self.document_legacy.root.cache_dirty = true;
-		self.artboard_message_handler.artboards_document.root.cache_dirty = true;
-
-		let layer_metadata = std::mem::replace(&mut self.layer_metadata, layer_metadata);
+	/// Returns the document to its state at the start of the current transaction without keeping the transaction's changes as a step that can be redone.
+	pub fn abort(&mut self, responses: &mut VecDeque<Message>) -> Result<(), EditorError> {
+		let selected_paths: Vec<Vec<LayerId>> = self.selected_layers().map(|path| path.to_vec()).collect();
+		if !self.document_history.abort(&mut self.document_legacy, &mut self.artboard_message_handler, &mut self.layer_metadata) {
+			return Err(EditorError::NoTransactionInProgress);
+		}
 
-		DocumentSave { document, artboard, layer_metadata }
+		self.history_step_changed(selected_paths, responses);
+		Ok(())
 	}
 
 	pub fn undo(&mut self, responses: &mut VecDeque<Message>) -> Result<(), EditorError> {
@@ -1379,34 +2038,14 @@ impl DocumentMessageHandler {
 		responses.add(PortfolioMessage::UpdateOpenDocumentsList);
 
 		let selected_paths: Vec<Vec<LayerId>> = self.selected_layers().map(|path| path.to_vec()).collect();
-
-		match self.document_undo_history.pop_back() {
-			Some(DocumentSave { document, artboard, layer_metadata }) => {
-				// Update the currently displayed layer on the Properties panel if the selection changes after an undo action
-				// Also appropriately update the Properties panel if an undo action results in a layer being deleted
-				let prev_selected_paths: Vec<Vec<LayerId>> = layer_metadata.iter().filter_map(|(layer_id, metadata)| metadata.selected.then_some(layer_id.clone())).collect();
-
-				if prev_selected_paths != selected_paths {
-					responses.add(BroadcastEvent::SelectionChanged);
-				}
-
-				let document_save = self.replace_document(DocumentSave { document, artboard, layer_metadata });
-
-				self.document_redo_history.push_back(document_save);
-				if self.document_redo_history.len() > crate::consts::MAX_UNDO_HISTORY_LEN {
-					self.document_redo_history.pop_front();
-				}
-
-				for layer in self.layer_metadata.keys() {
-					responses.add(DocumentMessage::LayerChanged { affected_layer_path: layer.clone() })
-				}
-
-				responses.add(NodeGraphMessage::SendGraph { should_rerender: true });
-
-				Ok(())
-			}
-			None => Err(EditorError::NoTransactionInProgress),
+		if !self.document_history.undo(&mut self.document_legacy, &mut self.artboard_message_handler, &mut self.layer_metadata) {
+			return Err(EditorError::NoTransactionInProgress);
 		}
+
+		// Update the currently displayed layer on the Properties panel if the selection changes after an undo action
+		// Also appropriately update the Properties panel if an undo action results in a layer being deleted
+		self.history_step_changed(selected_paths, responses);
+		Ok(())
 	}
 
 	pub fn redo(&mut self, responses: &mut VecDeque<Message>) -> Result<(), EditorError> {
@@ -1414,43 +2053,53 @@ impl DocumentMessageHandler {
 		responses.add(PortfolioMessage::UpdateOpenDocumentsList);
 
 		let selected_paths: Vec<Vec<LayerId>> = self.selected_layers().map(|path| path.to_vec()).collect();
+		if !self.document_history.redo(&mut self.document_legacy, &mut self.artboard_message_handler, &mut self.layer_metadata) {
+			return Err(EditorError::NoTransactionInProgress);
+		}
 
-		match self.document_redo_history.pop_back() {
-			Some(DocumentSave { document, artboard, layer_metadata }) => {
-				// Update currently displayed layer on property panel if selection changes after redo action
-				// Also appropriately update property panel if redo action results in a layer being added
-				let next_selected_paths: Vec<Vec<LayerId>> = layer_metadata.iter().filter_map(|(layer_id, metadata)| metadata.selected.then_some(layer_id.clone())).collect();
+		// Update currently displayed layer on property panel if selection changes after redo action
+		// Also appropriately update property panel if redo action results in a layer being added
+		self.history_step_changed(selected_paths, responses);
+		Ok(())
+	}
 
-				if next_selected_paths != selected_paths {
-					responses.add(BroadcastEvent::SelectionChanged);
-				}
+	/// Moves the document to the end of a step in the history, which can be on another branch than the current one.
+	pub fn jump(&mut self, step: Option<HistoryStepId>, responses: &mut VecDeque<Message>) -> Result<(), EditorError> {
+		responses.add(PortfolioMessage::UpdateOpenDocumentsList);
 
-				let document_save = self.replace_document(DocumentSave { document, artboard, layer_metadata });
-				self.document_undo_history.push_back(document_save);
-				if self.document_undo_history.len() > crate::consts::MAX_UNDO_HISTORY_LEN {
-					self.document_undo_history.pop_front();
-				}
+		let selected_paths: Vec<Vec<LayerId>> = self.selected_layers().map(|path| path.to_vec()).collect();
+		if !self
+			.document_history
+			.jump(step, &mut self.document_legacy, &mut self.artboard_message_handler, &mut self.layer_metadata)
+		{
+			return Err(EditorError::Misc("The history step no longer exists".to_string()));
+		}
 
-				for layer in self.layer_metadata.keys() {
-					responses.add(DocumentMessage::LayerChanged { affected_layer_path: layer.clone() })
-				}
+		self.history_step_changed(selected_paths, responses);
+		Ok(())
+	}
 
-				responses.add(NodeGraphMessage::SendGraph { should_rerender: true });
+	/// Updates the panels after the history has moved the document to another state, given the layers which were selected before.
+	fn history_step_changed(&self, mut previous_selected_paths: Vec<Vec<LayerId>>, responses: &mut VecDeque<Message>) {
+		let mut selected_paths: Vec<Vec<LayerId>> = self.selected_layers().map(|path| path.to_vec()).collect();
+		previous_selected_paths.sort();
+		selected_paths.sort();
+		if previous_selected_paths != selected_paths {
+			responses.add(BroadcastEvent::SelectionChanged);
+		}
 
-				Ok(())
-			}
-			None => Err(EditorError::NoTransactionInProgress),
+		for layer in self.layer_metadata.keys() {
+			responses.add(DocumentMessage::LayerChanged { affected_layer_path: layer.clone() })
 		}
+
+		responses.add(NodeGraphMessage::SendGraph { should_rerender: true });
+		self.update_history_panel(responses);
 	}
 
 	pub fn current_identifier(&self) -> u64 {
 		// We can use the last state of the document to serve as the identifier to compare against
 		// This is useful since when the document is empty the identifier will be 0
-		self.document_undo_history
-			.iter()
-			.last()
-			.map(|DocumentSave { document, .. }| document.current_state_identifier())
-			.unwrap_or(0)
+		self.document_history.current_identifier()
 	}
 
 	pub fn is_auto_saved(&self) -> bool {
@@ -1556,15 +2205,7 @@ impl DocumentMessageHandler {
 						responses.add(DocumentMessage::InputFrameRasterizeRegionBelowLayer { layer_path: path.clone() });
 					}
 					for node in layer.network.nodes.values() {
-						for input in &node.inputs {
-							if let NodeInput::Value {
-								tagged_value: TaggedValue::Font(font),
-								..
-							} = input
-							{
-								fonts.insert(font.clone());
-							}
-						}
+						fonts.extend(node_fonts(node).cloned());
 					}
 				}
 				_ => {}
@@ -1578,6 +2219,33 @@ impl DocumentMessageHandler {
 		}
 	}
 
+	/// The text of the text nodes in the layers, each along with the font it's set in, which fallback fonts may be needed for.
+	pub fn layer_texts(&self) -> Vec<(Font, String)> {
+		fn walk_layers(data: &LayerDataType, texts: &mut Vec<(Font, String)>) {
+			match data {
+				LayerDataType::Folder(folder) => folder.layers().iter().for_each(|layer| walk_layers(&layer.data, texts)),
+				LayerDataType::Layer(layer) => {
+					for node in layer.network.nodes.values() {
+						let value = |index| match node.inputs.get(index) {
+							Some(NodeInput::Value { tagged_value, .. }) => Some(tagged_value),
+							_ => None,
+						};
+						match (value(1), value(2)) {
+							(Some(TaggedValue::String(text)), Some(TaggedValue::Font(font))) => texts.push((font.clone(), text.clone())),
+							(Some(TaggedValue::RichText(rich_text)), _) => texts.extend(rich_text.runs.iter().map(|run| (run.style.font.clone(), run.text.clone()))),
+							_ => {}
+						}
+					}
+				}
+				LayerDataType::Shape(_) => {}
+			}
+		}
+
+		let mut texts = Vec::new();
+		walk_layers(&self.document_legacy.root.data, &mut texts);
+		texts
+	}
+
 	pub fn update_document_widgets(&self, responses: &mut VecDeque<Message>) {
 		let snapping_state = self.snapping_state.clone();
 		let mut widgets = vec![
@@ -1668,6 +2336,28 @@ impl DocumentMessageHandler {
 			})
 			.widget_holder(),
 			PopoverButton::new("View Mode", "Coming soon").widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			DropdownInput::new(vec![ColorSpace::list()
+				.into_iter()
+				.map(|color_space| DropdownEntryData::new(color_space.to_string()).on_update(move |_| DocumentMessage::SetWorkingColorSpace { color_space }.into()))
+				.collect()])
+			.selected_index(
+				ColorSpace::list()
+					.iter()
+					.position(|&color_space| color_space == self.document_legacy.working_color_space)
+					.map(|index| index as u32),
+			)
+			.tooltip("Working Color Space")
+			.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			NumberInput::new(Some(self.time))
+				.unit(" s")
+				.min(0.)
+				.mode_increment()
+				.increment_step(1. / self.animation.frame_rate)
+				.tooltip("Animation Time")
+				.on_update(|number_input: &NumberInput| DocumentMessage::SetTime { time: number_input.value.unwrap() }.into())
+				.widget_holder(),
 			Separator::new(SeparatorType::Section).widget_holder(),
 			IconButton::new("ZoomIn", 24)
 				.tooltip("Zoom In")
@@ -1857,6 +2547,74 @@ impl DocumentMessageHandler {
 		});
 	}
 
+	/// Lists the named snapshots and the steps of the history in the History panel, with steps on other branches indented below the step they branch from.
+	pub fn update_history_panel(&self, responses: &mut VecDeque<Message>) {
+		let history = &self.document_history;
+		let pending_label = history.pending_label();
+		let step_row = |step: Option<HistoryStepId>, label: &str, depth: usize| {
+			let mut widgets: Vec<_> = (0..depth).map(|_| Separator::new(SeparatorType::Unrelated).widget_holder()).collect();
+			widgets.push(
+				TextButton::new(label)
+					.emphasized(pending_label.is_none() && step == history.current())
+					.on_update(move |_| DocumentMessage::JumpToHistoryStep { step }.into())
+					.widget_holder(),
+			);
+			LayoutGroup::Row { widgets }
+		};
+
+		let mut rows = vec![LayoutGroup::Row {
+			widgets: vec![TextButton::new("New Snapshot")
+				.tooltip("Name the current state of the document so it can be returned to")
+				.on_update(|_| DocumentMessage::CreateHistorySnapshot.into())
+				.widget_holder()],
+		}];
+		rows.extend(history.snapshots().iter().enumerate().map(|(index, snapshot)| {
+			let step = snapshot.step;
+			LayoutGroup::Row {
+				widgets: vec![
+					TextInput::new(&snapshot.name)
+						.on_update(move |text_input: &TextInput| {
+							DocumentMessage::RenameHistorySnapshot {
+								index,
+								name: text_input.value.clone(),
+							}
+							.into()
+						})
+						.widget_holder(),
+					Separator::new(SeparatorType::Related).widget_holder(),
+					TextButton::new("Restore")
+						.tooltip("Return the document to this snapshot")
+						.on_update(move |_| DocumentMessage::JumpToHistoryStep { step }.into())
+						.widget_holder(),
+					IconButton::new("Trash", 24)
+						.tooltip("Delete Snapshot")
+						.on_update(move |_| DocumentMessage::DeleteHistorySnapshot { index }.into())
+						.widget_holder(),
+				],
+			}
+		}));
+		rows.push(LayoutGroup::Row {
+			widgets: vec![Separator::new(SeparatorType::Section).widget_holder()],
+		});
+
+		rows.push(step_row(None, "Initial State", 0));
+		let steps = history.steps();
+		rows.extend(steps.iter().map(|&(step, label, depth)| step_row(Some(step), label, depth)));
+
+		// The current transaction is listed as the step it will become, since it usually changes the document
+		if let Some(label) = pending_label {
+			let depth = steps.iter().find(|(step, _, _)| Some(*step) == history.current()).map_or(0, |&(_, _, depth)| depth);
+			let mut widgets: Vec<_> = (0..depth).map(|_| Separator::new(SeparatorType::Unrelated).widget_holder()).collect();
+			widgets.push(TextButton::new(label).emphasized(true).widget_holder());
+			rows.push(LayoutGroup::Row { widgets });
+		}
+
+		responses.add(LayoutMessage::SendLayout {
+			layout: Layout::WidgetLayout(WidgetLayout::new(rows)),
+			layout_target: LayoutTarget::HistoryPanel,
+		});
+	}
+
 	pub fn selected_layers_reorder(&mut self, relative_index_offset: isize, responses: &mut VecDeque<Message>) {
 		self.backup(responses);
 
@@ -1910,3 +2668,276 @@ impl DocumentMessageHandler {
 		}
 	}
 }
+
+/// The layer at the path along with its children as a graphic element placed in the document, or the whole document if the path is empty.
+fn legacy_document_graphic_element(document: &DocumentLegacy, layer_path: &[LayerId], images: &HashMap<Vec<LayerId>, Image<Color>>) -> Option<GraphicElement> {
+	let Some((_, parent_path)) = layer_path.split_last() else {
+		return legacy_graphic_element(&document.root, &mut Vec::new(), DAffine2::IDENTITY, images);
+	};
+	let parent_transform = document.multiply_transforms(parent_path).ok()?;
+	let layer = document.layer(layer_path).ok()?;
+	legacy_graphic_element(layer, &mut layer_path.to_vec(), parent_transform, images)
+}
+
+/// Converts a layer of the legacy document into a [`GraphicElement`] that can be rendered by the node graph renderers.
+/// The pixels of image layers aren't stored in the document, so they are looked up in the node graph executor's last output for that layer path.
+fn legacy_graphic_element(layer: &Layer, path: &mut Vec<LayerId>, parent_transform: DAffine2, images: &HashMap<Vec<LayerId>, Image<Color>>) -> Option<GraphicElement> {
+	let transform = parent_transform * layer.transform;
+
+	// The SVG renderer draws paths in the parent folder's space, so strokes scale with the enclosing folders but not with the layer itself
+	let stroke_scale = parent_transform.matrix2.determinant().abs().sqrt();
+	let scale_stroke = |style: &mut PathStyle| {
+		if let Some(stroke) = style.stroke() {
+			let dash_lengths = stroke.dash_lengths.iter().map(|length| length * stroke_scale as f32).collect();
+			style.set_stroke(Stroke {
+				weight: stroke.weight * stroke_scale,
+				dash_offset: stroke.dash_offset * stroke_scale,
+				dash_lengths,
+				..stroke
+			});
+		}
+	};
+	let vector_data = |subpaths, mut style: PathStyle, mut style_regions: Vec<StyleRegion>| {
+		scale_stroke(&mut style);
+		style_regions.iter_mut().for_each(|region| scale_stroke(&mut region.style));
+		VectorData {
+			transform,
+			style,
+			style_regions,
+			..VectorData::from_subpaths(subpaths)
+		}
+	};
+
+	let graphic_element_data = match &layer.data {
+		LayerDataType::Folder(folder) => {
+			let mut graphic_group = GraphicGroup::EMPTY;
+			for (layer, &id) in folder.layers().iter().zip(&folder.layer_ids) {
+				path.push(id);
+				graphic_group.extend(legacy_graphic_element(layer, path, transform, images));
+				path.pop();
+			}
+			graphic_group.into()
+		}
+		LayerDataType::Shape(shape) => vector_data(shape.shape.to_bezier_rs(), shape.style.clone(), Vec::new()).into(),
+		LayerDataType::Layer(layer_layer) => match &layer_layer.cached_output_data {
+			CachedOutputData::VectorPath(data) => vector_data(data.subpaths.clone(), data.style.clone(), data.style_regions.clone()).into(),
+			CachedOutputData::BlobURL(_) => ImageFrame {
+				image: images.get(path.as_slice())?.clone(),
+				transform,
+			}
+			.into(),
+			CachedOutputData::None | CachedOutputData::SurfaceId(_) => return None,
+		},
+	};
+
+	Some(GraphicElement {
+		name: layer.name.clone().unwrap_or_default(),
+		blend_mode: layer.blend_mode.into(),
+		opacity: layer.opacity as f32,
+		visible: layer.visible,
+		locked: false,
+		collapsed: false,
+		graphic_element_data,
+	})
+}
+
+/// Sets the luminance calculations of the nodes in the network (and the networks nested in it) which are `from` to `to`.
+fn retarget_luminance_calculations(network: &mut NodeNetwork, from: LuminanceCalculation, to: LuminanceCalculation) {
+	for node in network.nodes.values_mut() {
+		if let DocumentNodeImplementation::Network(nested) = &mut node.implementation {
+			retarget_luminance_calculations(nested, from, to);
+		}
+		for input in &mut node.inputs {
+			if let NodeInput::Value {
+				tagged_value: TaggedValue::LuminanceCalculation(calculation),
+				..
+			} = input
+			{
+				if *calculation == from {
+					*calculation = to;
+				}
+			}
+		}
+	}
+}
+
+/// Sets the luminance calculations which are `from` to `to` in the networks of the layer and the layers within it.
+fn retarget_layer_luminance_calculations(data: &mut LayerDataType, from: LuminanceCalculation, to: LuminanceCalculation) {
+	match data {
+		LayerDataType::Folder(folder) => folder.layers_mut().iter_mut().for_each(|layer| retarget_layer_luminance_calculations(&mut layer.data, from, to)),
+		LayerDataType::Layer(layer) => retarget_luminance_calculations(&mut layer.network, from, to),
+		LayerDataType::Shape(_) => {}
+	}
+}
+
+/// Creates the network of an image layer, which holds the image in an Image node followed by the Transform and Downres nodes.
+fn image_layer_network(image: Image<Color>) -> Option<NodeNetwork> {
+	let Some(image_node_type) = crate::messages::portfolio::document::node_graph::resolve_document_node_type("Image") else {
+		warn!("Image node should be in registry");
+		return None;
+	};
+	let Some(transform_node_type) = crate::messages::portfolio::document::node_graph::resolve_document_node_type("Transform") else {
+		warn!("Transform node should be in registry");
+		return None;
+	};
+	let Some(downres_node_type) = crate::messages::portfolio::document::node_graph::resolve_document_node_type("Downres") else {
+		warn!("Downres node should be in registry");
+		return None;
+	};
+
+	let mut network = NodeNetwork::default();
+	network.push_node(
+		image_node_type.to_document_node(
+			[graph_craft::document::NodeInput::value(
+				graph_craft::document::value::TaggedValue::ImageFrame(ImageFrame { image, transform: DAffine2::IDENTITY }),
+				false,
+			)],
+			graph_craft::document::DocumentNodeMetadata::position((8, 4)),
+		),
+		false,
+	);
+	network.push_node(transform_node_type.to_document_node_default_inputs([], Default::default()), true);
+	network.push_node(downres_node_type.to_document_node_default_inputs([], Default::default()), true);
+	network.push_output_node();
+	Some(network)
+}
+
+/// The raster format a file type is encoded in, where PNG and TIFF files can hold either 8 or 16 bits per channel.
+fn raster_format(file_type: FileType, high_bit_depth: bool) -> RasterFormat {
+	match (file_type, high_bit_depth) {
+		(FileType::Jpg, _) => RasterFormat::Jpeg,
+		(FileType::Tiff, true) => RasterFormat::Tiff16,
+		(FileType::Tiff, false) => RasterFormat::Tiff,
+		(FileType::Exr, _) => RasterFormat::OpenExr,
+		(_, true) => RasterFormat::Png16,
+		(_, false) => RasterFormat::Png,
+	}
+}
+
+/// Adds a layer of an imported Photoshop or OpenRaster file at `path`, where a group becomes a folder holding its layers and an image becomes an image layer placed by its transform.
+fn add_graphic_element(element: GraphicElement, path: Vec<LayerId>, responses: &mut VecDeque<Message>) {
+	let GraphicElement {
+		name,
+		blend_mode,
+		opacity,
+		visible,
+		graphic_element_data,
+		..
+	} = element;
+
+	match graphic_element_data {
+		GraphicElementData::GraphicGroup(group) => {
+			responses.add(DocumentOperation::CreateFolder { path: path.clone(), insert_index: -1 });
+			for child in group.iter().cloned() {
+				let mut child_path = path.clone();
+				child_path.push(generate_uuid());
+				add_graphic_element(child, child_path, responses);
+			}
+		}
+		GraphicElementData::ImageFrame(ImageFrame { image, transform }) => {
+			let Some(network) = image_layer_network(image) else { return };
+			responses.add(DocumentOperation::AddFrame {
+				path: path.clone(),
+				insert_index: -1,
+				transform: DAffine2::ZERO.to_cols_array(),
+				network,
+			});
+			responses.add(GraphOperationMessage::TransformSet {
+				layer: path.clone(),
+				transform,
+				transform_in: TransformIn::Local,
+				skip_rerender: false,
+			});
+			responses.add(DocumentMessage::InputFrameRasterizeRegionBelowLayer { layer_path: path.clone() });
+		}
+		// Layered image files only contain images and groups
+		_ => return,
+	}
+
+	if !name.is_empty() {
+		responses.add(DocumentOperation::SetLayerName { path: path.clone(), name });
+	}
+	// Blend modes which can't be rendered yet fall back to normal
+	let blend_mode = BlendMode::try_from(blend_mode).unwrap_or(BlendMode::Normal);
+	if blend_mode != BlendMode::Normal {
+		responses.add(DocumentOperation::SetLayerBlendMode { path: path.clone(), blend_mode });
+	}
+	if opacity < 1. {
+		responses.add(DocumentOperation::SetLayerOpacity {
+			path: path.clone(),
+			opacity: opacity as f64,
+		});
+	}
+	if !visible {
+		responses.add(DocumentOperation::SetLayerVisibility { path, visible });
+	}
+}
+
+/// Adds an element of an imported SVG file as a new layer at `path`, which is appended to the top of its parent folder.
+fn add_svg_element(element: SvgElement, path: Vec<LayerId>, responses: &mut VecDeque<Message>) {
+	let set_name = |name: Option<String>, responses: &mut VecDeque<Message>| {
+		if let Some(name) = name {
+			responses.add(DocumentOperation::SetLayerName { path: path.clone(), name });
+		}
+	};
+
+	match element {
+		SvgElement::Group {
+			name,
+			transform,
+			blend_mode,
+			opacity,
+			children,
+		} => {
+			responses.add(DocumentOperation::CreateFolder { path: path.clone(), insert_index: -1 });
+			responses.add(DocumentOperation::SetLayerTransform {
+				path: path.clone(),
+				transform: transform.to_cols_array(),
+			});
+			if blend_mode != BlendMode::Normal {
+				responses.add(DocumentOperation::SetLayerBlendMode { path: path.clone(), blend_mode });
+			}
+			if opacity < 1. {
+				responses.add(DocumentOperation::SetLayerOpacity { path: path.clone(), opacity });
+			}
+			set_name(name, responses);
+
+			for child in children {
+				let mut child_path = path.clone();
+				child_path.push(generate_uuid());
+				add_svg_element(child, child_path, responses);
+			}
+		}
+		SvgElement::Vector { name, subpaths, fill, stroke } => {
+			responses.add(DocumentOperation::AddFrame {
+				path: path.clone(),
+				insert_index: -1,
+				transform: DAffine2::ZERO.to_cols_array(),
+				network: crate::messages::portfolio::document::node_graph::new_vector_network(subpaths),
+			});
+			set_name(name, responses);
+			responses.add(GraphOperationMessage::FillSet { layer: path.clone(), fill });
+			if let Some(stroke) = stroke {
+				responses.add(GraphOperationMessage::StrokeSet { layer: path.clone(), stroke });
+			}
+			responses.add(DocumentMessage::InputFrameRasterizeRegionBelowLayer { layer_path: path });
+		}
+		SvgElement::Image { name, transform, image } => {
+			let Some(network) = image_layer_network(image) else { return };
+			responses.add(DocumentOperation::AddFrame {
+				path: path.clone(),
+				insert_index: -1,
+				transform: DAffine2::ZERO.to_cols_array(),
+				network,
+			});
+			set_name(name, responses);
+			responses.add(GraphOperationMessage::TransformSet {
+				layer: path.clone(),
+				transform,
+				transform_in: TransformIn::Local,
+				skip_rerender: false,
+			});
+			responses.add(DocumentMessage::InputFrameRasterizeRegionBelowLayer { layer_path: path });
+		}
+	}
+}
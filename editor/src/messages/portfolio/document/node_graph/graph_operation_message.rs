@@ -1,8 +1,9 @@
 use crate::messages::prelude::*;
 
 use graph_craft::document::NodeId;
+use graphene_core::raster::FloodFill;
 use graphene_core::uuid::ManipulatorGroupId;
-use graphene_core::vector::brush_stroke::BrushStroke;
+use graphene_core::vector::brush_stroke::{BrushStroke, BrushTipImages};
 use graphene_core::vector::style::{Fill, Stroke};
 use graphene_core::vector::ManipulatorPointId;
 use graphene_core::Artboard;
@@ -52,6 +53,15 @@ pub enum GraphOperationMessage {
 	Brush {
 		layer: LayerIdentifier,
 		strokes: Vec<BrushStroke>,
+		tips: BrushTipImages,
+	},
+	PerspectiveWarp {
+		layer: LayerIdentifier,
+		corners: [DVec2; 4],
+	},
+	FloodFill {
+		layer: LayerIdentifier,
+		fill: FloodFill,
 	},
 
 	NewArtboard {
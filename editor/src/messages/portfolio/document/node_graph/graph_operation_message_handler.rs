@@ -5,7 +5,8 @@ use document_legacy::document::Document;
 use document_legacy::{LayerId, Operation};
 use graph_craft::document::value::TaggedValue;
 use graph_craft::document::{generate_uuid, DocumentNode, DocumentNodeMetadata, NodeId, NodeInput, NodeNetwork, NodeOutput};
-use graphene_core::vector::brush_stroke::BrushStroke;
+use graphene_core::raster::FloodFill;
+use graphene_core::vector::brush_stroke::{BrushStroke, BrushTipImages};
 use graphene_core::vector::style::{Fill, FillType, Stroke};
 use graphene_core::Artboard;
 use transform_utils::LayerBounds;
@@ -299,8 +300,8 @@ impl<'a> ModifyInputsContext<'a> {
 	}
 
 	fn vector_modify(&mut self, modification: VectorDataModification) {
-		// TODO: Allow modifying a graph with a "Text" node.
-		if self.network.nodes.values().any(|node| node.name == "Text") {
+		// TODO: Allow modifying a graph with a "Text" or "Rich Text" node.
+		if self.network.nodes.values().any(|node| matches!(node.name.as_str(), "Text" | "Rich Text")) {
 			return;
 		}
 
@@ -337,9 +338,30 @@ impl<'a> ModifyInputsContext<'a> {
 		self.update_bounds([old_bounds_min, old_bounds_max], [new_bounds_min, new_bounds_max]);
 	}
 
-	fn brush_modify(&mut self, strokes: Vec<BrushStroke>) {
+	fn brush_modify(&mut self, strokes: Vec<BrushStroke>, tips: BrushTipImages) {
 		self.modify_inputs("Brush", false, |inputs| {
 			inputs[2] = NodeInput::value(TaggedValue::BrushStrokes(strokes), false);
+			inputs[4] = NodeInput::value(TaggedValue::BrushTipImages(tips), false);
+		});
+	}
+
+	fn perspective_warp(&mut self, corners: [DVec2; 4]) {
+		self.modify_inputs("Perspective Warp", false, |inputs| {
+			inputs[1] = NodeInput::value(TaggedValue::VecDVec2(corners.to_vec()), false);
+		});
+	}
+
+	/// Repeated fills are added to the same node, each of them filling the result of the fills before it.
+	fn flood_fill(&mut self, fill: FloodFill) {
+		self.modify_inputs("Flood Fill", false, |inputs| {
+			let NodeInput::Value {
+				tagged_value: TaggedValue::FloodFills(fills),
+				..
+			} = &mut inputs[1]
+			else {
+				return;
+			};
+			fills.push(fill);
 		});
 	}
 
@@ -454,9 +476,19 @@ impl MessageHandler<GraphOperationMessage, (&mut Document, &mut NodeGraphMessage
 					modify_inputs.vector_modify(modification);
 				}
 			}
-			GraphOperationMessage::Brush { layer, strokes } => {
+			GraphOperationMessage::Brush { layer, strokes, tips } => {
+				if let Some(mut modify_inputs) = ModifyInputsContext::new(&layer, document, node_graph, responses) {
+					modify_inputs.brush_modify(strokes, tips);
+				}
+			}
+			GraphOperationMessage::PerspectiveWarp { layer, corners } => {
+				if let Some(mut modify_inputs) = ModifyInputsContext::new(&layer, document, node_graph, responses) {
+					modify_inputs.perspective_warp(corners);
+				}
+			}
+			GraphOperationMessage::FloodFill { layer, fill } => {
 				if let Some(mut modify_inputs) = ModifyInputsContext::new(&layer, document, node_graph, responses) {
-					modify_inputs.brush_modify(strokes);
+					modify_inputs.flood_fill(fill);
 				}
 			}
 			GraphOperationMessage::NewArtboard { id, artboard } => {
@@ -57,7 +57,7 @@ pub enum NodeGraphMessage {
 		layer_path: Vec<document_legacy::LayerId>,
 	},
 	PasteNodes {
-		serialized_nodes: String,
+		nodes: Vec<(NodeId, DocumentNode)>,
 	},
 	RunDocumentGraph,
 	SelectNodes {
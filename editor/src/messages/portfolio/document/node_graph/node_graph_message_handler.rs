@@ -1,6 +1,7 @@
 pub use self::document_node_types::*;
 use crate::messages::input_mapper::utility_types::macros::action_keys;
 use crate::messages::layout::utility_types::widget_prelude::*;
+use crate::messages::portfolio::document::utility_types::clipboards::ClipboardContent;
 use crate::messages::prelude::*;
 use crate::node_graph_executor::{GraphIdentifier, NodeGraphExecutor};
 
@@ -8,6 +9,7 @@ use document_legacy::document::Document;
 use document_legacy::LayerId;
 use graph_craft::document::value::TaggedValue;
 use graph_craft::document::{DocumentNode, DocumentNodeImplementation, NodeId, NodeInput, NodeNetwork, NodeOutput};
+use graphene_core::raster::LuminanceCalculation;
 use graphene_core::*;
 mod document_node_types;
 mod node_properties;
@@ -89,6 +91,8 @@ pub struct FrontendNode {
 	pub previewed: bool,
 	#[serde(rename = "thumbnailSvg")]
 	pub thumbnail_svg: Option<String>,
+	/// Why the node failed during the last evaluation, shown as a badge on the node.
+	pub error: Option<String>,
 }
 
 // (link_start, link_end, link_end_input_index)
@@ -347,6 +351,7 @@ impl NodeGraphMessageHandler {
 
 			let graph_identifier = GraphIdentifier::new(layer_id);
 			let thumbnail_svg = executor.thumbnails.get(&graph_identifier).and_then(|thumbnails| thumbnails.get(id)).map(|svg| svg.to_string());
+			let error = executor.node_errors.get(&graph_identifier).and_then(|errors| errors.get(id)).cloned();
 
 			nodes.push(FrontendNode {
 				id: *id,
@@ -359,6 +364,7 @@ impl NodeGraphMessageHandler {
 				previewed: network.outputs_contain(*id),
 				disabled: network.disabled.contains(id),
 				thumbnail_svg,
+				error,
 			})
 		}
 		responses.add(FrontendMessage::UpdateNodeGraph { nodes, links });
@@ -483,11 +489,8 @@ impl MessageHandler<NodeGraphMessage, (&mut Document, &NodeGraphExecutor, u64, &
 				let new_ids = &self.selected_nodes.iter().copied().enumerate().map(|(new, old)| (old, new as NodeId)).collect();
 				let copied_nodes: Vec<_> = Self::copy_nodes(network, new_ids).collect();
 
-				// Prefix to show that this is nodes
-				let mut copy_text = String::from("graphite/nodes: ");
-				copy_text += &serde_json::to_string(&copied_nodes).expect("Could not serialize paste");
-
-				responses.add(FrontendMessage::TriggerTextCopy { copy_text });
+				let content = ClipboardContent::from_nodes(copied_nodes).to_clipboard_text();
+				responses.add(FrontendMessage::TriggerClipboardWrite { content, svg: None });
 			}
 			NodeGraphMessage::CreateNode { node_id, node_type, x, y } => {
 				let node_id = node_id.unwrap_or_else(crate::application::generate_uuid);
@@ -502,10 +505,16 @@ impl MessageHandler<NodeGraphMessage, (&mut Document, &NodeGraphExecutor, u64, &
 
 				responses.add(DocumentMessage::StartTransaction);
 
-				let document_node = document_node_type.to_document_node(
-					document_node_type.inputs.iter().map(|input| input.default.clone()),
-					graph_craft::document::DocumentNodeMetadata::position((x, y)),
-				);
+				// Luminance is computed for the document's working color space unless another calculation is chosen
+				let working_luminance = LuminanceCalculation::from(document.working_color_space);
+				let inputs = document_node_type.inputs.iter().map(|input| match &input.default {
+					NodeInput::Value {
+						tagged_value: TaggedValue::LuminanceCalculation(_),
+						exposed,
+					} => NodeInput::value(TaggedValue::LuminanceCalculation(working_luminance), *exposed),
+					default => default.clone(),
+				});
+				let document_node = document_node_type.to_document_node(inputs, graph_craft::document::DocumentNodeMetadata::position((x, y)));
 				responses.add(NodeGraphMessage::InsertNode { node_id, document_node });
 
 				responses.add(NodeGraphMessage::SendGraph { should_rerender: false });
@@ -676,20 +685,12 @@ impl MessageHandler<NodeGraphMessage, (&mut Document, &NodeGraphExecutor, u64, &
 				self.collect_nested_addresses(document, document_name, responses);
 				self.update_selected(document, responses);
 			}
-			NodeGraphMessage::PasteNodes { serialized_nodes } => {
+			NodeGraphMessage::PasteNodes { nodes: data } => {
 				let Some(network) = self.get_active_network(document) else {
 					warn!("No network");
 					return;
 				};
 
-				let data = match serde_json::from_str::<Vec<(NodeId, DocumentNode)>>(&serialized_nodes) {
-					Ok(d) => d,
-					Err(e) => {
-						warn!("Invalid node data {e:?}");
-						return;
-					}
-				};
-
 				if data.is_empty() {
 					return;
 				}
@@ -751,7 +752,11 @@ impl MessageHandler<NodeGraphMessage, (&mut Document, &NodeGraphExecutor, u64, &
 			NodeGraphMessage::SetInputValue { node_id, input_index, value } => {
 				if let Some(network) = self.get_active_network(document) {
 					if let Some(node) = network.nodes.get(&node_id) {
-						responses.add(DocumentMessage::StartTransaction);
+						// Repeated changes to the same inputs, such as while a number is dragged, are merged into one history step
+						responses.add(DocumentMessage::StartCoalescingTransaction);
+						if changes_text(&value) {
+							responses.add(PortfolioMessage::LoadFallbackFonts);
+						}
 
 						let input = NodeInput::Value { tagged_value: value, exposed: false };
 						responses.add(NodeGraphMessage::SetNodeInput { node_id, input_index, input });
@@ -792,6 +797,9 @@ impl MessageHandler<NodeGraphMessage, (&mut Document, &NodeGraphExecutor, u64, &
 						if input_index >= node.inputs.len() {
 							node.inputs.extend(((node.inputs.len() - 1)..input_index).map(|_| NodeInput::Network(generic!(T))));
 						}
+						if changes_text(&value) {
+							responses.add(PortfolioMessage::LoadFallbackFonts);
+						}
 						node.inputs[input_index] = NodeInput::Value { tagged_value: value, exposed: false };
 						if network.connected_to_output(*node_id) {
 							responses.add(DocumentMessage::InputFrameRasterizeRegionBelowLayer { layer_path });
@@ -920,3 +928,8 @@ impl MessageHandler<NodeGraphMessage, (&mut Document, &NodeGraphExecutor, u64, &
 		}
 	}
 }
+
+/// Whether setting an input to the value can change which glyphs a text needs, so that the fallback fonts may need loading.
+fn changes_text(value: &TaggedValue) -> bool {
+	matches!(value, TaggedValue::String(_) | TaggedValue::RichText(_) | TaggedValue::Font(_))
+}
@@ -11,8 +11,11 @@ use graph_craft::NodeIdentifier;
 #[cfg(feature = "gpu")]
 use graphene_core::application_io::SurfaceHandle;
 use graphene_core::raster::brush_cache::BrushCache;
-use graphene_core::raster::{BlendMode, Color, Image, ImageFrame, LuminanceCalculation, NoiseType, RedGreenBlue, RelativeAbsolute, SelectiveColorChoice};
-use graphene_core::text::Font;
+use graphene_core::raster::{
+	BlendMode, Color, ColorSpace, EdgeDetectionOperator, FloodFillMode, Image, ImageChannel, ImageFrame, LuminanceCalculation, NoiseType, RedGreenBlue, RelativeAbsolute, SelectiveColorChoice,
+};
+use graphene_core::text::{Font, FontVariation, RichText, TextAlign, TextOverflow, TextPathSide, TextStyle, TypesettingConfig};
+use graphene_core::vector::brush_stroke::BrushTipImages;
 use graphene_core::vector::VectorData;
 use graphene_core::*;
 
@@ -151,6 +154,19 @@ fn static_nodes() -> Vec<DocumentNodeType> {
 			properties: node_properties::color_properties,
 			..Default::default()
 		},
+		DocumentNodeType {
+			name: "Time",
+			category: "Inputs",
+			identifier: NodeImplementation::proto("graphene_core::ExtractTime"),
+			inputs: vec![DocumentInputType {
+				name: "In",
+				data_type: FrontendGraphDataType::General,
+				default: NodeInput::Network(concrete!(WasmEditorApi)),
+			}],
+			outputs: vec![DocumentOutputType::new("Seconds", FrontendGraphDataType::Number)],
+			properties: |_document_node, _node_id, _context| node_properties::string_properties("The document's animation time in seconds, which animated exports step through frame by frame"),
+			..Default::default()
+		},
 		DocumentNodeType {
 			name: "Identity",
 			category: "Structural",
@@ -314,8 +330,8 @@ fn static_nodes() -> Vec<DocumentNodeType> {
 			name: "Load Image",
 			category: "Structural",
 			identifier: NodeImplementation::DocumentNode(NodeNetwork {
-				inputs: vec![0, 0],
-				outputs: vec![NodeOutput::new(1, 0)],
+				inputs: vec![0, 0, 4],
+				outputs: vec![NodeOutput::new(3, 0)],
 				nodes: [
 					DocumentNode {
 						name: "Load Resource".to_string(),
@@ -323,10 +339,31 @@ fn static_nodes() -> Vec<DocumentNodeType> {
 						implementation: DocumentNodeImplementation::Unresolved(NodeIdentifier::new("graphene_std::wasm_application_io::LoadResourceNode<_>")),
 						..Default::default()
 					},
+					// The image is decoded into the working color space of the document
 					DocumentNode {
 						name: "Decode Image".to_string(),
-						inputs: vec![NodeInput::node(0, 0)],
-						implementation: DocumentNodeImplementation::Unresolved(NodeIdentifier::new("graphene_std::wasm_application_io::DecodeImageNode")),
+						inputs: vec![NodeInput::node(0, 0), NodeInput::node(4, 0)],
+						implementation: DocumentNodeImplementation::Unresolved(NodeIdentifier::new("graphene_std::wasm_application_io::DecodeImageNode<_>")),
+						..Default::default()
+					},
+					// The monitor node is used to display the error in the UI if the image fails to load.
+					DocumentNode {
+						name: "Monitor".to_string(),
+						inputs: vec![NodeInput::node(1, 0)],
+						implementation: DocumentNodeImplementation::proto("graphene_core::memo::MonitorNode<_>"),
+						skip_deduplication: true,
+						..Default::default()
+					},
+					DocumentNode {
+						name: "Image Or Placeholder".to_string(),
+						inputs: vec![NodeInput::node(2, 0)],
+						implementation: DocumentNodeImplementation::Unresolved(NodeIdentifier::new("graphene_std::wasm_application_io::ImageOrPlaceholderNode")),
+						..Default::default()
+					},
+					DocumentNode {
+						name: "Working Color Space".to_string(),
+						inputs: vec![NodeInput::Network(concrete!(WasmEditorApi))],
+						implementation: DocumentNodeImplementation::Unresolved(NodeIdentifier::new("graphene_core::ExtractColorSpace")),
 						..Default::default()
 					},
 				]
@@ -347,6 +384,11 @@ fn static_nodes() -> Vec<DocumentNodeType> {
 					data_type: FrontendGraphDataType::General,
 					default: NodeInput::value(TaggedValue::String("graphite:null".to_string()), false),
 				},
+				DocumentInputType {
+					name: "color space api",
+					data_type: FrontendGraphDataType::General,
+					default: NodeInput::Network(concrete!(WasmEditorApi)),
+				},
 			],
 			outputs: vec![DocumentOutputType {
 				name: "Image Frame",
@@ -737,6 +779,19 @@ fn static_nodes() -> Vec<DocumentNodeType> {
 			properties: node_properties::luminance_properties,
 			..Default::default()
 		},
+		DocumentNodeType {
+			name: "Convert Color Space",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_core::raster::ConvertColorSpaceNode<_, _>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("From", TaggedValue::ColorSpace(ColorSpace::Srgb), false),
+				DocumentInputType::value("To", TaggedValue::ColorSpace(ColorSpace::DisplayP3), false),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::convert_color_space_properties,
+			..Default::default()
+		},
 		DocumentNodeType {
 			name: "Extract Channel",
 			category: "Image Adjustments",
@@ -830,12 +885,13 @@ fn static_nodes() -> Vec<DocumentNodeType> {
 		DocumentNodeType {
 			name: "Brush",
 			category: "Brush",
-			identifier: NodeImplementation::proto("graphene_std::brush::BrushNode<_, _, _>"),
+			identifier: NodeImplementation::proto("graphene_std::brush::BrushNode<_, _, _, _>"),
 			inputs: vec![
 				DocumentInputType::value("Background", TaggedValue::ImageFrame(ImageFrame::empty()), true),
 				DocumentInputType::value("Bounds", TaggedValue::ImageFrame(ImageFrame::empty()), true),
 				DocumentInputType::value("Trace", TaggedValue::BrushStrokes(Vec::new()), false),
 				DocumentInputType::value("Cache", TaggedValue::BrushCache(BrushCache::new_proto()), false),
+				DocumentInputType::value("Tip Images", TaggedValue::BrushTipImages(BrushTipImages::default()), false),
 			],
 			outputs: vec![DocumentOutputType {
 				name: "Image",
@@ -2060,17 +2116,83 @@ fn static_nodes() -> Vec<DocumentNodeType> {
 		DocumentNodeType {
 			name: "Text",
 			category: "Vector",
-			identifier: NodeImplementation::proto("graphene_core::text::TextGenerator<_, _, _>"),
+			identifier: NodeImplementation::proto("graphene_core::text::TextGenerator<_, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _>"),
 			inputs: vec![
 				DocumentInputType::none(),
 				DocumentInputType::value("Text", TaggedValue::String("hello world".to_string()), false),
 				DocumentInputType::value("Font", TaggedValue::Font(Font::new(DEFAULT_FONT_FAMILY.into(), DEFAULT_FONT_STYLE.into())), false),
 				DocumentInputType::value("Size", TaggedValue::F64(24.), false),
+				DocumentInputType::value("Line Height", TaggedValue::F64(1.2), false),
+				DocumentInputType::value("Letter Spacing", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Paragraph Spacing", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Align", TaggedValue::TextAlign(TextAlign::Left), false),
+				DocumentInputType::value("Box Width", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Box Height", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Overflow", TaggedValue::TextOverflow(TextOverflow::Visible), false),
+				DocumentInputType::value("Features", TaggedValue::String(String::new()), false),
+				DocumentInputType::value("Weight", TaggedValue::OptionalF64(None), false),
+				DocumentInputType::value("Width", TaggedValue::OptionalF64(None), false),
+				DocumentInputType::value("Slant", TaggedValue::OptionalF64(None), false),
+				DocumentInputType::value("Optical Size", TaggedValue::OptionalF64(None), false),
+				DocumentInputType::value("Variations", TaggedValue::String(String::new()), false),
 			],
 			outputs: vec![DocumentOutputType::new("Vector", FrontendGraphDataType::Subpath)],
 			properties: node_properties::node_section_font,
 			..Default::default()
 		},
+		DocumentNodeType {
+			name: "Rich Text",
+			category: "Vector",
+			identifier: NodeImplementation::proto("graphene_core::text::RichTextGenerator<_, _, _, _, _, _, _, _, _, _, _, _, _, _>"),
+			inputs: vec![
+				DocumentInputType::none(),
+				DocumentInputType::value(
+					"Text",
+					TaggedValue::RichText(RichText::new("hello world", TextStyle::new(Font::new(DEFAULT_FONT_FAMILY.into(), DEFAULT_FONT_STYLE.into()), 24.))),
+					false,
+				),
+				DocumentInputType::value("Line Height", TaggedValue::F64(1.2), false),
+				DocumentInputType::value("Letter Spacing", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Paragraph Spacing", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Align", TaggedValue::TextAlign(TextAlign::Left), false),
+				DocumentInputType::value("Box Width", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Box Height", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Overflow", TaggedValue::TextOverflow(TextOverflow::Visible), false),
+				DocumentInputType::value("Features", TaggedValue::String(String::new()), false),
+				DocumentInputType::value("Weight", TaggedValue::OptionalF64(None), false),
+				DocumentInputType::value("Width", TaggedValue::OptionalF64(None), false),
+				DocumentInputType::value("Slant", TaggedValue::OptionalF64(None), false),
+				DocumentInputType::value("Optical Size", TaggedValue::OptionalF64(None), false),
+				DocumentInputType::value("Variations", TaggedValue::String(String::new()), false),
+			],
+			outputs: vec![DocumentOutputType::new("Vector", FrontendGraphDataType::Subpath)],
+			properties: node_properties::node_section_rich_text,
+			..Default::default()
+		},
+		DocumentNodeType {
+			name: "Text on Path",
+			category: "Vector",
+			identifier: NodeImplementation::proto("graphene_core::text::TextOnPathNode<_, _, _, _, _, _, _, _, _, _, _, _, _>"),
+			inputs: vec![
+				DocumentInputType::none(),
+				DocumentInputType::value("Text", TaggedValue::String("hello world".to_string()), false),
+				DocumentInputType::value("Font", TaggedValue::Font(Font::new(DEFAULT_FONT_FAMILY.into(), DEFAULT_FONT_STYLE.into())), false),
+				DocumentInputType::value("Size", TaggedValue::F64(24.), false),
+				DocumentInputType::value("Letter Spacing", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Path", TaggedValue::VectorData(graphene_core::vector::VectorData::empty()), true),
+				DocumentInputType::value("Start Offset", TaggedValue::F64(0.), false),
+				DocumentInputType::value("Align", TaggedValue::TextAlign(TextAlign::Left), false),
+				DocumentInputType::value("Side", TaggedValue::TextPathSide(TextPathSide::Above), false),
+				DocumentInputType::value("Weight", TaggedValue::OptionalF64(None), false),
+				DocumentInputType::value("Width", TaggedValue::OptionalF64(None), false),
+				DocumentInputType::value("Slant", TaggedValue::OptionalF64(None), false),
+				DocumentInputType::value("Optical Size", TaggedValue::OptionalF64(None), false),
+				DocumentInputType::value("Variations", TaggedValue::String(String::new()), false),
+			],
+			outputs: vec![DocumentOutputType::new("Vector", FrontendGraphDataType::Subpath)],
+			properties: node_properties::node_section_text_on_path,
+			..Default::default()
+		},
 		DocumentNodeType {
 			name: "Transform",
 			category: "Transform",
@@ -2098,6 +2220,45 @@ fn static_nodes() -> Vec<DocumentNodeType> {
 			outputs: vec![DocumentOutputType::new("Data", FrontendGraphDataType::Subpath)],
 			..Default::default()
 		},
+		DocumentNodeType {
+			name: "Perspective Warp",
+			category: "Transform",
+			identifier: NodeImplementation::proto("graphene_core::raster::PerspectiveWarpNode<_>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Corners", TaggedValue::VecDVec2(Vec::new()), false),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::perspective_warp_properties,
+			..Default::default()
+		},
+		DocumentNodeType {
+			name: "Mesh Warp",
+			category: "Transform",
+			identifier: NodeImplementation::proto("graphene_core::raster::MeshWarpNode<_, _, _>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Columns", TaggedValue::U32(1), false),
+				DocumentInputType::value("Rows", TaggedValue::U32(1), false),
+				DocumentInputType::value("Control Points", TaggedValue::VecDVec2(graphene_core::raster::identity_mesh(1, 1)), false),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::mesh_warp_properties,
+			..Default::default()
+		},
+		DocumentNodeType {
+			name: "Cage Warp",
+			category: "Transform",
+			identifier: NodeImplementation::proto("graphene_core::raster::CageWarpNode<_, _>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Cage", TaggedValue::VecDVec2(vec![DVec2::ZERO, DVec2::X, DVec2::ONE, DVec2::Y]), false),
+				DocumentInputType::value("Deformed Cage", TaggedValue::VecDVec2(vec![DVec2::ZERO, DVec2::X, DVec2::ONE, DVec2::Y]), false),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::cage_warp_properties,
+			..Default::default()
+		},
 		DocumentNodeType {
 			name: "Fill",
 			category: "Vector",
@@ -2202,6 +2363,147 @@ fn static_nodes() -> Vec<DocumentNodeType> {
 			outputs: vec![DocumentOutputType::new("Segments", FrontendGraphDataType::Raster)],
 			..Default::default()
 		},
+		DocumentNodeType {
+			name: "Dilate",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_core::raster::DilateNode<_>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Radius", TaggedValue::U32(1), false),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::morphology_properties,
+			..Default::default()
+		},
+		DocumentNodeType {
+			name: "Erode",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_core::raster::ErodeNode<_>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Radius", TaggedValue::U32(1), false),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::morphology_properties,
+			..Default::default()
+		},
+		DocumentNodeType {
+			name: "Open",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_core::raster::OpenNode<_>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Radius", TaggedValue::U32(1), false),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::morphology_properties,
+			..Default::default()
+		},
+		DocumentNodeType {
+			name: "Close",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_core::raster::CloseNode<_>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Radius", TaggedValue::U32(1), false),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::morphology_properties,
+			..Default::default()
+		},
+		DocumentNodeType {
+			name: "Edge Detection",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_core::raster::EdgeDetectionNode<_, _>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Operator", TaggedValue::EdgeDetectionOperator(EdgeDetectionOperator::Sobel), false),
+				DocumentInputType::value("Channel", TaggedValue::ImageChannel(ImageChannel::Luminance), false),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::edge_detection_properties,
+			..Default::default()
+		},
+		DocumentNodeType {
+			name: "Canny Edge Detection",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_core::raster::CannyEdgeDetectionNode<_, _, _, _>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Channel", TaggedValue::ImageChannel(ImageChannel::Luminance), false),
+				DocumentInputType::value("Sigma", TaggedValue::F64(1.4), false),
+				DocumentInputType::value("Low Threshold", TaggedValue::F32(10.), false),
+				DocumentInputType::value("High Threshold", TaggedValue::F32(30.), false),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::canny_edge_detection_properties,
+			..Default::default()
+		},
+		DocumentNodeType {
+			name: "Distance Transform",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_core::raster::DistanceTransformNode<_, _, _>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Channel", TaggedValue::ImageChannel(ImageChannel::Alpha), false),
+				DocumentInputType::value("Threshold", TaggedValue::F32(50.), false),
+				DocumentInputType::value("Max Distance", TaggedValue::F64(32.), false),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::distance_transform_properties,
+			..Default::default()
+		},
+		DocumentNodeType {
+			name: "Magic Wand",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_core::raster::MagicWandNode<_, _, _, _>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Position", TaggedValue::DVec2(DVec2::ZERO), false),
+				DocumentInputType::value("Tolerance", TaggedValue::F32(10.), false),
+				DocumentInputType::value("Mode", TaggedValue::FloodFillMode(FloodFillMode::Contiguous), false),
+				DocumentInputType::value("Anti-aliasing", TaggedValue::Bool(true), false),
+			],
+			outputs: vec![DocumentOutputType::new("Mask", FrontendGraphDataType::Raster)],
+			properties: node_properties::magic_wand_properties,
+			..Default::default()
+		},
+		DocumentNodeType {
+			name: "Flood Fill",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_core::raster::FloodFillNode<_>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Fills", TaggedValue::FloodFills(Vec::new()), false),
+			],
+			outputs: vec![DocumentOutputType::new("Image", FrontendGraphDataType::Raster)],
+			properties: node_properties::flood_fill_properties,
+			..Default::default()
+		},
+		DocumentNodeType {
+			name: "Dominant Colors",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_core::raster::DominantColorsNode<_>"),
+			inputs: vec![
+				DocumentInputType::value("Image", TaggedValue::ImageFrame(ImageFrame::empty()), true),
+				DocumentInputType::value("Count", TaggedValue::U32(6), false),
+			],
+			outputs: vec![DocumentOutputType::new("Palette", FrontendGraphDataType::General)],
+			properties: node_properties::dominant_colors_properties,
+			..Default::default()
+		},
+		DocumentNodeType {
+			name: "Palette Color",
+			category: "Image Adjustments",
+			identifier: NodeImplementation::proto("graphene_core::raster::PaletteColorNode<_>"),
+			inputs: vec![
+				DocumentInputType::value("Palette", TaggedValue::Palette(Vec::new()), true),
+				DocumentInputType::value("Index", TaggedValue::U32(0), false),
+			],
+			outputs: vec![DocumentOutputType::new("Color", FrontendGraphDataType::General)],
+			properties: node_properties::palette_color_properties,
+			..Default::default()
+		},
 		DocumentNodeType {
 			name: "Index",
 			category: "Image Adjustments",
@@ -2463,8 +2765,44 @@ pub fn new_vector_network(subpaths: Vec<bezier_rs::Subpath<uuid::ManipulatorGrou
 	network
 }
 
-pub fn new_text_network(text: String, font: Font, size: f64) -> NodeNetwork {
+pub fn new_text_network(text: String, font: Font, typesetting: TypesettingConfig) -> NodeNetwork {
 	let text_generator = resolve_document_node_type("Text").expect("Text node does not exist");
+	let inputs = [
+		NodeInput::Network(concrete!(WasmEditorApi)),
+		NodeInput::value(TaggedValue::String(text), false),
+		NodeInput::value(TaggedValue::Font(font), false),
+		NodeInput::value(TaggedValue::F64(typesetting.font_size), false),
+	];
+	text_network(text_generator.to_document_node(inputs.into_iter().chain(typesetting_inputs(typesetting)), DocumentNodeMetadata::position((0, 4))))
+}
+
+pub fn new_rich_text_network(rich_text: RichText, typesetting: TypesettingConfig) -> NodeNetwork {
+	let text_generator = resolve_document_node_type("Rich Text").expect("Rich Text node does not exist");
+	let inputs = [NodeInput::Network(concrete!(WasmEditorApi)), NodeInput::value(TaggedValue::RichText(rich_text), false)];
+	text_network(text_generator.to_document_node(inputs.into_iter().chain(typesetting_inputs(typesetting)), DocumentNodeMetadata::position((0, 4))))
+}
+
+/// The inputs of a text node from its line height to its variations.
+fn typesetting_inputs(typesetting: TypesettingConfig) -> [NodeInput; 13] {
+	let ([weight, width, slant, optical_size], variations) = FontVariation::split(&typesetting.font_variations);
+	[
+		NodeInput::value(TaggedValue::F64(typesetting.line_height_ratio), false),
+		NodeInput::value(TaggedValue::F64(typesetting.letter_spacing), false),
+		NodeInput::value(TaggedValue::F64(typesetting.paragraph_spacing), false),
+		NodeInput::value(TaggedValue::TextAlign(typesetting.align), false),
+		NodeInput::value(TaggedValue::F64(typesetting.max_width.unwrap_or_default()), false),
+		NodeInput::value(TaggedValue::F64(typesetting.max_height.unwrap_or_default()), false),
+		NodeInput::value(TaggedValue::TextOverflow(typesetting.overflow), false),
+		NodeInput::value(TaggedValue::String(typesetting.font_features), false),
+		NodeInput::value(TaggedValue::OptionalF64(weight), false),
+		NodeInput::value(TaggedValue::OptionalF64(width), false),
+		NodeInput::value(TaggedValue::OptionalF64(slant), false),
+		NodeInput::value(TaggedValue::OptionalF64(optical_size), false),
+		NodeInput::value(TaggedValue::String(variations), false),
+	]
+}
+
+fn text_network(text_generator: DocumentNode) -> NodeNetwork {
 	let transform = resolve_document_node_type("Transform").expect("Transform node does not exist");
 	let fill = resolve_document_node_type("Fill").expect("Fill node does not exist");
 	let stroke = resolve_document_node_type("Stroke").expect("Stroke node does not exist");
@@ -2474,18 +2812,7 @@ pub fn new_text_network(text: String, font: Font, size: f64) -> NodeNetwork {
 		inputs: vec![0],
 		..Default::default()
 	};
-	network.push_node(
-		text_generator.to_document_node(
-			[
-				NodeInput::Network(concrete!(WasmEditorApi)),
-				NodeInput::value(TaggedValue::String(text), false),
-				NodeInput::value(TaggedValue::Font(font), false),
-				NodeInput::value(TaggedValue::F64(size), false),
-			],
-			DocumentNodeMetadata::position((0, 4)),
-		),
-		false,
-	);
+	network.push_node(text_generator, false);
 	network.push_node(transform.to_document_node_default_inputs([None], Default::default()), true);
 	network.push_node(fill.to_document_node_default_inputs([None], Default::default()), true);
 	network.push_node(stroke.to_document_node_default_inputs([None], Default::default()), true);
@@ -10,8 +10,10 @@ use graph_craft::concrete;
 use graph_craft::document::value::TaggedValue;
 use graph_craft::document::{DocumentNode, NodeId, NodeInput};
 use graph_craft::imaginate_input::{ImaginateMaskStartingFill, ImaginateSamplingMethod, ImaginateServerStatus, ImaginateStatus};
-use graphene_core::raster::{BlendMode, Color, ImageFrame, LuminanceCalculation, NoiseType, RedGreenBlue, RelativeAbsolute, SelectiveColorChoice};
-use graphene_core::text::Font;
+use graphene_core::raster::{
+	BlendMode, Color, ColorSpace, EdgeDetectionOperator, FloodFillMode, ImageChannel, ImageFrame, LuminanceCalculation, NoiseType, RedGreenBlue, RelativeAbsolute, SelectiveColorChoice,
+};
+use graphene_core::text::{Font, FontAxis, FontVariation, RichText, TextAlign, TextOverflow, TextPathSide, TextRun};
 use graphene_core::vector::style::{FillType, GradientType, LineCap, LineJoin};
 use graphene_core::{Cow, Type, TypeDescriptor};
 
@@ -323,6 +325,33 @@ fn number_widget(document_node: &DocumentNode, node_id: NodeId, index: usize, na
 	widgets
 }
 
+/// A number input which can be left unset, with a checkbox to set it and the `unset_value` shown while it isn't.
+fn optional_number_widget(document_node: &DocumentNode, node_id: NodeId, index: usize, name: &str, number_props: NumberInput, unset_value: f64, blank_assist: bool) -> Vec<WidgetHolder> {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::Number, blank_assist);
+
+	if let NodeInput::Value {
+		tagged_value: TaggedValue::OptionalF64(x),
+		exposed: false,
+	} = document_node.inputs[index]
+	{
+		let value = x.unwrap_or(unset_value);
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			CheckboxInput::new(x.is_some())
+				.icon("Edit12px")
+				.on_update(update_value(move |x: &CheckboxInput| TaggedValue::OptionalF64(x.checked.then_some(value)), node_id, index))
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			number_props
+				.value(Some(value))
+				.disabled(x.is_none())
+				.on_update(update_value(move |x: &NumberInput| TaggedValue::OptionalF64(x.value), node_id, index))
+				.widget_holder(),
+		])
+	}
+	widgets
+}
+
 //TODO Use generalized Version of this as soon as it's available
 fn color_channel(document_node: &DocumentNode, node_id: u64, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
 	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
@@ -395,6 +424,135 @@ fn blend_mode(document_node: &DocumentNode, node_id: u64, index: usize, name: &s
 	LayoutGroup::Row { widgets }.with_tooltip("Formula used for blending")
 }
 
+//TODO Use generalized Version of this as soon as it's available
+fn image_channel(document_node: &DocumentNode, node_id: u64, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
+	if let &NodeInput::Value {
+		tagged_value: TaggedValue::ImageChannel(channel),
+		exposed: false,
+	} = &document_node.inputs[index]
+	{
+		let channels = ImageChannel::list();
+		let mut entries = Vec::with_capacity(channels.len());
+		for method in channels {
+			entries.push(DropdownEntryData::new(method.to_string()).on_update(update_value(move |_| TaggedValue::ImageChannel(method), node_id, index)));
+		}
+		let entries = vec![entries];
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			DropdownInput::new(entries).selected_index(Some(channel as u32)).widget_holder(),
+		]);
+	}
+	LayoutGroup::Row { widgets }.with_tooltip("Channel of the image which is analyzed")
+}
+
+//TODO Use generalized Version of this as soon as it's available
+fn edge_detection_operator(document_node: &DocumentNode, node_id: u64, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
+	if let &NodeInput::Value {
+		tagged_value: TaggedValue::EdgeDetectionOperator(operator),
+		exposed: false,
+	} = &document_node.inputs[index]
+	{
+		let operators = EdgeDetectionOperator::list();
+		let mut entries = Vec::with_capacity(operators.len());
+		for method in operators {
+			entries.push(DropdownEntryData::new(method.to_string()).on_update(update_value(move |_| TaggedValue::EdgeDetectionOperator(method), node_id, index)));
+		}
+		let entries = vec![entries];
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			DropdownInput::new(entries).selected_index(Some(operator as u32)).widget_holder(),
+		]);
+	}
+	LayoutGroup::Row { widgets }.with_tooltip("Kernel used to approximate the image gradient")
+}
+
+//TODO Use generalized Version of this as soon as it's available
+fn flood_fill_mode(document_node: &DocumentNode, node_id: u64, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
+	if let &NodeInput::Value {
+		tagged_value: TaggedValue::FloodFillMode(mode),
+		exposed: false,
+	} = &document_node.inputs[index]
+	{
+		let modes = FloodFillMode::list();
+		let mut entries = Vec::with_capacity(modes.len());
+		for method in modes {
+			entries.push(DropdownEntryData::new(method.to_string()).on_update(update_value(move |_| TaggedValue::FloodFillMode(method), node_id, index)));
+		}
+		let entries = vec![entries];
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			DropdownInput::new(entries).selected_index(Some(mode as u32)).widget_holder(),
+		]);
+	}
+	LayoutGroup::Row { widgets }.with_tooltip("Whether only pixels connected to the clicked pixel or all similar pixels are selected")
+}
+
+fn text_align_widget(document_node: &DocumentNode, node_id: u64, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
+	if let &NodeInput::Value {
+		tagged_value: TaggedValue::TextAlign(align),
+		exposed: false,
+	} = &document_node.inputs[index]
+	{
+		let entries = TextAlign::list()
+			.into_iter()
+			.map(|align| RadioEntryData::new(align.to_string()).on_update(update_value(move |_| TaggedValue::TextAlign(align), node_id, index)))
+			.collect();
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			RadioInput::new(entries).selected_index(align as u32).widget_holder(),
+		]);
+	}
+	LayoutGroup::Row { widgets }
+}
+
+fn text_overflow_widget(document_node: &DocumentNode, node_id: u64, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
+	if let &NodeInput::Value {
+		tagged_value: TaggedValue::TextOverflow(overflow),
+		exposed: false,
+	} = &document_node.inputs[index]
+	{
+		let entries = vec![TextOverflow::list()
+			.into_iter()
+			.map(|overflow| DropdownEntryData::new(overflow.to_string()).on_update(update_value(move |_| TaggedValue::TextOverflow(overflow), node_id, index)))
+			.collect()];
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			DropdownInput::new(entries).selected_index(Some(overflow as u32)).widget_holder(),
+		]);
+	}
+	LayoutGroup::Row { widgets }.with_tooltip("Whether the lines which extend below the text box are shown")
+}
+
+fn text_path_side_widget(document_node: &DocumentNode, node_id: u64, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
+	if let &NodeInput::Value {
+		tagged_value: TaggedValue::TextPathSide(side),
+		exposed: false,
+	} = &document_node.inputs[index]
+	{
+		let entries = TextPathSide::list()
+			.into_iter()
+			.map(|side| RadioEntryData::new(side.to_string()).on_update(update_value(move |_| TaggedValue::TextPathSide(side), node_id, index)))
+			.collect();
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			RadioInput::new(entries).selected_index(side as u32).widget_holder(),
+		]);
+	}
+	LayoutGroup::Row { widgets }.with_tooltip("Whether the glyphs stand on the path or hang from it, relative to the direction it is drawn in")
+}
+
 // TODO: Generalize this for all dropdowns ( also see blend_mode and channel_extration )
 fn luminance_calculation(document_node: &DocumentNode, node_id: u64, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
 	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
@@ -418,6 +576,26 @@ fn luminance_calculation(document_node: &DocumentNode, node_id: u64, index: usiz
 	LayoutGroup::Row { widgets }.with_tooltip("Formula used to calculate the luminance of a pixel")
 }
 
+fn color_space_widget(document_node: &DocumentNode, node_id: u64, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
+	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
+	if let &NodeInput::Value {
+		tagged_value: TaggedValue::ColorSpace(color_space),
+		exposed: false,
+	} = &document_node.inputs[index]
+	{
+		let entries = vec![ColorSpace::list()
+			.into_iter()
+			.map(|space| DropdownEntryData::new(space.to_string()).on_update(update_value(move |_| TaggedValue::ColorSpace(space), node_id, index)))
+			.collect()];
+
+		widgets.extend_from_slice(&[
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			DropdownInput::new(entries).selected_index(Some(color_space as u32)).widget_holder(),
+		]);
+	}
+	LayoutGroup::Row { widgets }
+}
+
 fn line_cap_widget(document_node: &DocumentNode, node_id: u64, index: usize, name: &str, blank_assist: bool) -> LayoutGroup {
 	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::General, blank_assist);
 	if let &NodeInput::Value {
@@ -618,6 +796,158 @@ fn gradient_positions(rows: &mut Vec<LayoutGroup>, document_node: &DocumentNode,
 	}
 }
 
+/// Builds the update message for a widget which changes one run of the rich text.
+fn update_run<T>(
+	rich_text: &RichText,
+	run_index: usize,
+	node_id: NodeId,
+	input_index: usize,
+	modify: impl Fn(&mut TextRun, &T) + 'static + Send + Sync,
+) -> impl Fn(&T) -> Message + 'static + Send + Sync {
+	let rich_text = rich_text.clone();
+	update_value(
+		move |widget: &T| {
+			let mut rich_text = rich_text.clone();
+			modify(&mut rich_text.runs[run_index], widget);
+			TaggedValue::RichText(rich_text)
+		},
+		node_id,
+		input_index,
+	)
+}
+
+fn rich_text_runs(rows: &mut Vec<LayoutGroup>, document_node: &DocumentNode, name: &str, node_id: NodeId, input_index: usize) {
+	let NodeInput::Value {
+		tagged_value: TaggedValue::RichText(rich_text),
+		exposed: false,
+	} = &document_node.inputs[input_index]
+	else {
+		let widgets = start_widgets(document_node, node_id, input_index, name, FrontendGraphDataType::Text, true);
+		rows.push(LayoutGroup::Row { widgets });
+		return;
+	};
+
+	let indent = || {
+		let mut widgets = vec![TextLabel::new("").widget_holder()];
+		add_blank_assist(&mut widgets);
+		widgets.push(Separator::new(SeparatorType::Unrelated).widget_holder());
+		widgets
+	};
+
+	for (run_index, run) in rich_text.runs.iter().enumerate() {
+		let style = &run.style;
+
+		let mut widgets = start_widgets(document_node, node_id, input_index, &format!("Run {}", run_index + 1), FrontendGraphDataType::Text, true);
+		widgets.push(Separator::new(SeparatorType::Unrelated).widget_holder());
+		widgets.push(
+			TextAreaInput::new(run.text.clone())
+				.on_update(update_run(rich_text, run_index, node_id, input_index, |run, input: &TextAreaInput| run.text = input.value.clone()))
+				.widget_holder(),
+		);
+		if rich_text.runs.len() > 1 {
+			let on_update = {
+				let rich_text = rich_text.clone();
+				move |_: &IconButton| {
+					let mut rich_text = rich_text.clone();
+					rich_text.runs.remove(run_index);
+					TaggedValue::RichText(rich_text)
+				}
+			};
+			widgets.push(Separator::new(SeparatorType::Related).widget_holder());
+			widgets.push(
+				IconButton::new("Remove", 16)
+					.tooltip("Remove this run")
+					.on_update(update_value(on_update, node_id, input_index))
+					.widget_holder(),
+			);
+		}
+		rows.push(LayoutGroup::Row { widgets });
+
+		let set_font = |run: &mut TextRun, input: &FontInput| run.style.font = Font::new(input.font_family.clone(), input.font_style.clone());
+		let mut widgets = indent();
+		widgets.push(
+			FontInput::new(style.font.font_family.clone(), style.font.font_style.clone())
+				.on_update(update_run(rich_text, run_index, node_id, input_index, set_font))
+				.widget_holder(),
+		);
+		rows.push(LayoutGroup::Row { widgets });
+		let mut widgets = indent();
+		widgets.push(
+			FontInput::new(style.font.font_family.clone(), style.font.font_style.clone())
+				.is_style_picker(true)
+				.on_update(update_run(rich_text, run_index, node_id, input_index, set_font))
+				.widget_holder(),
+		);
+		rows.push(LayoutGroup::Row { widgets });
+
+		let mut widgets = indent();
+		widgets.extend([
+			NumberInput::new(Some(style.font_size))
+				.label("Size")
+				.unit(" px")
+				.min(1.)
+				.on_update(update_run(rich_text, run_index, node_id, input_index, |run, input: &NumberInput| {
+					run.style.font_size = input.value.unwrap()
+				}))
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			NumberInput::new(Some(style.baseline_shift))
+				.label("Shift")
+				.unit(" px")
+				.tooltip("Raises the run above the baseline, or lowers it below with a negative shift")
+				.on_update(update_run(rich_text, run_index, node_id, input_index, |run, input: &NumberInput| {
+					run.style.baseline_shift = input.value.unwrap()
+				}))
+				.widget_holder(),
+		]);
+		rows.push(LayoutGroup::Row { widgets });
+
+		let mut widgets = indent();
+		widgets.extend([
+			ColorInput::new(style.fill)
+				.tooltip("The fill of the run, or none to use the fill of the layer")
+				.on_update(update_run(rich_text, run_index, node_id, input_index, |run, input: &ColorInput| run.style.fill = input.value))
+				.widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			CheckboxInput::new(style.underline)
+				.on_update(update_run(rich_text, run_index, node_id, input_index, |run, input: &CheckboxInput| run.style.underline = input.checked))
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			TextLabel::new("Underline").widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			CheckboxInput::new(style.strikethrough)
+				.on_update(update_run(rich_text, run_index, node_id, input_index, |run, input: &CheckboxInput| {
+					run.style.strikethrough = input.checked
+				}))
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			TextLabel::new("Strikethrough").widget_holder(),
+		]);
+		rows.push(LayoutGroup::Row { widgets });
+	}
+
+	// New runs continue with the style of the last one
+	let on_update = {
+		let rich_text = rich_text.clone();
+		move |_: &TextButton| {
+			let mut rich_text = rich_text.clone();
+			if let Some(style) = rich_text.runs.last().map(|run| run.style.clone()) {
+				rich_text.runs.push(TextRun { text: String::new(), style });
+			}
+			TaggedValue::RichText(rich_text)
+		}
+	};
+	let mut widgets = indent();
+	widgets.push(
+		TextButton::new("Add Run")
+			.icon(Some("Add".into()))
+			.tooltip("Add a run of text after the last one")
+			.on_update(update_value(on_update, node_id, input_index))
+			.widget_holder(),
+	);
+	rows.push(LayoutGroup::Row { widgets });
+}
+
 fn color_widget(document_node: &DocumentNode, node_id: u64, index: usize, name: &str, color_props: ColorInput, blank_assist: bool) -> LayoutGroup {
 	let mut widgets = start_widgets(document_node, node_id, index, name, FrontendGraphDataType::Number, blank_assist);
 
@@ -758,6 +1088,7 @@ pub fn output_properties(_document_node: &DocumentNode, _node_id: NodeId, contex
 
 	let layer_path_1 = context.layer_path.to_vec();
 	let layer_path_2 = context.layer_path.to_vec();
+	let layer_path_3 = context.layer_path.to_vec();
 
 	let label = TextLabel::new("The graph's output is drawn in the layer").widget_holder();
 	let download_button = TextButton::new("Download Render Output")
@@ -770,12 +1101,18 @@ pub fn output_properties(_document_node: &DocumentNode, _node_id: NodeId, contex
 		.disabled(disabled)
 		.on_update(move |_| DocumentMessage::CopyToClipboardLayerImageOutput { layer_path: layer_path_2.clone() }.into())
 		.widget_holder();
+	let palette_button = TextButton::new("Extract Palette")
+		.tooltip("Replace the document swatches with the dominant colors of the rendered image output")
+		.disabled(disabled)
+		.on_update(move |_| DocumentMessage::ExtractPalette { layer_path: layer_path_3.clone() }.into())
+		.widget_holder();
 
 	vec![
 		LayoutGroup::Row { widgets: vec![label] },
 		LayoutGroup::Row {
 			widgets: vec![download_button, Separator::new(SeparatorType::Related).widget_holder(), copy_button],
 		},
+		LayoutGroup::Row { widgets: vec![palette_button] },
 	]
 }
 
@@ -801,6 +1138,13 @@ pub fn luminance_properties(document_node: &DocumentNode, node_id: NodeId, _cont
 	vec![luminance_calc]
 }
 
+pub fn convert_color_space_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let from = color_space_widget(document_node, node_id, 1, "From", true);
+	let to = color_space_widget(document_node, node_id, 2, "To", true);
+
+	vec![from, to]
+}
+
 pub fn insert_channel_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
 	let color_channel = color_channel(document_node, node_id, 2, "Into", true);
 
@@ -829,6 +1173,77 @@ pub fn pixel_noise_properties(document_node: &DocumentNode, node_id: NodeId, _co
 	]
 }
 
+pub fn morphology_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let radius = number_widget(document_node, node_id, 1, "Radius", NumberInput::default().unit("px").min(0.).max(100.).int(), true);
+
+	vec![LayoutGroup::Row { widgets: radius }]
+}
+
+pub fn edge_detection_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let operator = edge_detection_operator(document_node, node_id, 1, "Operator", true);
+	let channel = image_channel(document_node, node_id, 2, "Channel", true);
+
+	vec![operator, channel]
+}
+
+pub fn canny_edge_detection_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let channel = image_channel(document_node, node_id, 1, "Channel", true);
+	let sigma = number_widget(document_node, node_id, 2, "Sigma", NumberInput::default().min(0.).max(10.), true);
+	let low_threshold = number_widget(document_node, node_id, 3, "Low Threshold", NumberInput::default().min(0.).max(100.).unit("%"), true);
+	let high_threshold = number_widget(document_node, node_id, 4, "High Threshold", NumberInput::default().min(0.).max(100.).unit("%"), true);
+
+	vec![
+		channel,
+		LayoutGroup::Row { widgets: sigma },
+		LayoutGroup::Row { widgets: low_threshold },
+		LayoutGroup::Row { widgets: high_threshold },
+	]
+}
+
+pub fn distance_transform_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let channel = image_channel(document_node, node_id, 1, "Channel", true);
+	let threshold = number_widget(document_node, node_id, 2, "Threshold", NumberInput::default().min(0.).max(100.).unit("%"), true);
+	let max_distance = number_widget(document_node, node_id, 3, "Max Distance", NumberInput::default().min(1.).unit("px"), true);
+
+	vec![channel, LayoutGroup::Row { widgets: threshold }, LayoutGroup::Row { widgets: max_distance }]
+}
+
+pub fn magic_wand_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let position = vec2_widget(document_node, node_id, 1, "Position", "X", "Y", " px", add_blank_assist);
+	let tolerance = number_widget(document_node, node_id, 2, "Tolerance", NumberInput::default().min(0.).max(100.).unit("%"), true);
+	let mode = flood_fill_mode(document_node, node_id, 3, "Mode", true);
+	let anti_alias = bool_widget(document_node, node_id, 4, "Anti-aliasing", true);
+
+	vec![position, LayoutGroup::Row { widgets: tolerance }, mode, LayoutGroup::Row { widgets: anti_alias }]
+}
+
+pub fn flood_fill_properties(document_node: &DocumentNode, _node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let count = match &document_node.inputs[1] {
+		NodeInput::Value {
+			tagged_value: TaggedValue::FloodFills(fills),
+			..
+		} => fills.len(),
+		_ => 0,
+	};
+
+	match count {
+		1 => string_properties("1 fill made with the Fill tool"),
+		count => string_properties(format!("{count} fills made with the Fill tool")),
+	}
+}
+
+pub fn dominant_colors_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let count = number_widget(document_node, node_id, 1, "Count", NumberInput::default().min(1.).max(32.).int(), true);
+
+	vec![LayoutGroup::Row { widgets: count }]
+}
+
+pub fn palette_color_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let index = number_widget(document_node, node_id, 1, "Index", NumberInput::default().min(0.).int(), true);
+
+	vec![LayoutGroup::Row { widgets: index }]
+}
+
 pub fn adjust_hsl_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
 	let hue_shift = number_widget(document_node, node_id, 1, "Hue Shift", NumberInput::default().min(-180.).max(180.).unit("°"), true);
 	let saturation_shift = number_widget(document_node, node_id, 2, "Saturation Shift", NumberInput::default().min(-100.).max(100.).unit("%"), true);
@@ -1200,6 +1615,32 @@ pub fn line_properties(document_node: &DocumentNode, node_id: NodeId, _context:
 	let operand = |name: &str, index| vec2_widget(document_node, node_id, index, name, "X", "Y", "px", add_blank_assist);
 	vec![operand("Start", 1), operand("End", 2)]
 }
+
+pub fn perspective_warp_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let corners = vec_dvec2_input(document_node, node_id, 1, "Corners", TextInput::default().centered(true), true);
+
+	vec![LayoutGroup::Row { widgets: corners }.with_tooltip("Top left, top right, bottom right and bottom left corner in layer space")]
+}
+
+pub fn mesh_warp_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let columns = number_widget(document_node, node_id, 1, "Columns", NumberInput::default().min(1.).max(16.).int(), true);
+	let rows = number_widget(document_node, node_id, 2, "Rows", NumberInput::default().min(1.).max(16.).int(), true);
+	let control_points = vec_dvec2_input(document_node, node_id, 3, "Control Points", TextInput::default().centered(true), true);
+
+	vec![
+		LayoutGroup::Row { widgets: columns },
+		LayoutGroup::Row { widgets: rows },
+		LayoutGroup::Row { widgets: control_points }.with_tooltip("Row by row bezier control points in image coordinates, (3 × Columns + 1) × (3 × Rows + 1) of them"),
+	]
+}
+
+pub fn cage_warp_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let cage = vec_dvec2_input(document_node, node_id, 1, "Cage", TextInput::default().centered(true), true);
+	let deformed_cage = vec_dvec2_input(document_node, node_id, 2, "Deformed Cage", TextInput::default().centered(true), true);
+
+	vec![LayoutGroup::Row { widgets: cage }, LayoutGroup::Row { widgets: deformed_cage }]
+}
+
 pub fn spline_properties(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
 	vec![LayoutGroup::Row {
 		widgets: vec_dvec2_input(document_node, node_id, 1, "Points", TextInput::default().centered(true), true),
@@ -1264,7 +1705,7 @@ pub fn transform_properties(document_node: &DocumentNode, node_id: NodeId, _cont
 	vec![translation, rotation, scale]
 }
 
-pub fn node_section_font(document_node: &DocumentNode, node_id: NodeId, _context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+pub fn node_section_font(document_node: &DocumentNode, node_id: NodeId, context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
 	let text = text_area_widget(document_node, node_id, 1, "Text", true);
 	let (font, style) = font_inputs(document_node, node_id, 2, "Font", true);
 	let size = number_widget(document_node, node_id, 3, "Size", NumberInput::default().unit(" px").min(1.), true);
@@ -1274,9 +1715,119 @@ pub fn node_section_font(document_node: &DocumentNode, node_id: NodeId, _context
 		result.push(LayoutGroup::Row { widgets: style });
 	}
 	result.push(LayoutGroup::Row { widgets: size });
+
+	let axes = match &document_node.inputs[2] {
+		NodeInput::Value {
+			tagged_value: TaggedValue::Font(font),
+			..
+		} => context.persistent_data.font_cache.variation_axes(font),
+		_ => Vec::new(),
+	};
+	result.extend(typesetting_rows(document_node, node_id, 4, &axes));
+	result
+}
+
+pub fn node_section_rich_text(document_node: &DocumentNode, node_id: NodeId, context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let mut result = Vec::new();
+	rich_text_runs(&mut result, document_node, "Text", node_id, 1);
+
+	// The axis ranges shown are those of the font of the first run
+	let axes = match &document_node.inputs[1] {
+		NodeInput::Value {
+			tagged_value: TaggedValue::RichText(rich_text),
+			..
+		} => rich_text.runs.first().map(|run| context.persistent_data.font_cache.variation_axes(&run.style.font)).unwrap_or_default(),
+		_ => Vec::new(),
+	};
+	result.extend(typesetting_rows(document_node, node_id, 2, &axes));
 	result
 }
 
+pub fn node_section_text_on_path(document_node: &DocumentNode, node_id: NodeId, context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
+	let text = text_area_widget(document_node, node_id, 1, "Text", true);
+	let (font, style) = font_inputs(document_node, node_id, 2, "Font", true);
+	let size = number_widget(document_node, node_id, 3, "Size", NumberInput::default().unit(" px").min(1.), true);
+	let letter_spacing = number_widget(document_node, node_id, 4, "Letter Spacing", NumberInput::default().unit(" px"), true);
+	let path = start_widgets(document_node, node_id, 5, "Path", FrontendGraphDataType::Subpath, true);
+	let start_offset = number_widget(document_node, node_id, 6, "Start Offset", NumberInput::default().unit(" px"), true);
+	let align = text_align_widget(document_node, node_id, 7, "Align", true);
+	let side = text_path_side_widget(document_node, node_id, 8, "Side", true);
+
+	let mut result = vec![LayoutGroup::Row { widgets: text }, LayoutGroup::Row { widgets: font }];
+	if let Some(style) = style {
+		result.push(LayoutGroup::Row { widgets: style });
+	}
+	result.extend([
+		LayoutGroup::Row { widgets: size },
+		LayoutGroup::Row { widgets: letter_spacing },
+		LayoutGroup::Row { widgets: path },
+		LayoutGroup::Row { widgets: start_offset }.with_tooltip("The distance the text is moved along the path"),
+		align,
+		side,
+	]);
+
+	let axes = match &document_node.inputs[2] {
+		NodeInput::Value {
+			tagged_value: TaggedValue::Font(font),
+			..
+		} => context.persistent_data.font_cache.variation_axes(font),
+		_ => Vec::new(),
+	};
+	result.extend(variation_rows(document_node, node_id, 9, &axes));
+	result
+}
+
+/// The rows for the line height, spacing, alignment, box, font feature and variation axis inputs shared by the text nodes, starting at the input index of the line height.
+fn typesetting_rows(document_node: &DocumentNode, node_id: NodeId, first_index: usize, axes: &[FontAxis]) -> Vec<LayoutGroup> {
+	let line_height = number_widget(document_node, node_id, first_index, "Line Height", NumberInput::default().unit("x").min(0.).step(0.1), true);
+	let letter_spacing = number_widget(document_node, node_id, first_index + 1, "Letter Spacing", NumberInput::default().unit(" px"), true);
+	let paragraph_spacing = number_widget(document_node, node_id, first_index + 2, "Paragraph Spacing", NumberInput::default().unit(" px"), true);
+	let align = text_align_widget(document_node, node_id, first_index + 3, "Align", true);
+	let box_width = number_widget(document_node, node_id, first_index + 4, "Box Width", NumberInput::default().unit(" px").min(0.), true);
+	let box_height = number_widget(document_node, node_id, first_index + 5, "Box Height", NumberInput::default().unit(" px").min(0.), true);
+	let overflow = text_overflow_widget(document_node, node_id, first_index + 6, "Overflow", true);
+	let features = text_widget(document_node, node_id, first_index + 7, "Features", true);
+
+	let mut rows = vec![
+		LayoutGroup::Row { widgets: line_height },
+		LayoutGroup::Row { widgets: letter_spacing },
+		LayoutGroup::Row { widgets: paragraph_spacing },
+		align,
+		LayoutGroup::Row { widgets: box_width }.with_tooltip("The width lines are wrapped to fit, or 0 for lines which only end at line breaks"),
+		LayoutGroup::Row { widgets: box_height }.with_tooltip("The height of the text box, or 0 for a box which grows to fit the lines"),
+		overflow,
+		LayoutGroup::Row { widgets: features }
+			.with_tooltip("OpenType features separated by commas, such as \"smcp, tnum, ss01\", where a leading \"-\" turns off one that is on by default like \"-liga\""),
+	];
+	rows.extend(variation_rows(document_node, node_id, first_index + 8, axes));
+	rows
+}
+
+/// The rows for the weight, width, slant and optical size inputs of the text nodes followed by their other variations, starting at the input index of the weight.
+///
+/// The axis inputs are limited to the range of the matching axis in `axes`, the axes of the current font.
+fn variation_rows(document_node: &DocumentNode, node_id: NodeId, first_index: usize, axes: &[FontAxis]) -> Vec<LayoutGroup> {
+	let axis_widget = |offset: usize, name: &str| {
+		let tag = FontVariation::STANDARD_AXES[offset];
+		let Some(axis) = axes.iter().find(|axis| axis.tag == tag) else {
+			let widgets = optional_number_widget(document_node, node_id, first_index + offset, name, NumberInput::default(), 0., true);
+			return LayoutGroup::Row { widgets }.with_tooltip("The font has no such axis, or leave it unset for the font's default");
+		};
+		let input = NumberInput::default().min(axis.min).max(axis.max);
+		let widgets = optional_number_widget(document_node, node_id, first_index + offset, name, input, axis.default, true);
+		LayoutGroup::Row { widgets }.with_tooltip(format!("From {} to {}, or leave it unset for the font's default of {}", axis.min, axis.max, axis.default))
+	};
+	let variations = text_widget(document_node, node_id, first_index + 4, "Variations", true);
+
+	vec![
+		axis_widget(0, "Weight"),
+		axis_widget(1, "Width"),
+		axis_widget(2, "Slant"),
+		axis_widget(3, "Optical Size"),
+		LayoutGroup::Row { widgets: variations }.with_tooltip("Other axes of a variable font as tags with values separated by commas, such as \"GRAD=50, XTRA=400\""),
+	]
+}
+
 pub fn imaginate_properties(document_node: &DocumentNode, node_id: NodeId, context: &mut NodePropertiesContext) -> Vec<LayoutGroup> {
 	let imaginate_node = [context.nested_path, &[node_id]].concat();
 
@@ -1,6 +1,8 @@
 mod overlays_message;
 mod overlays_message_handler;
 
+pub mod utility_types;
+
 #[doc(inline)]
 pub use overlays_message::{OverlaysMessage, OverlaysMessageDiscriminant};
 #[doc(inline)]
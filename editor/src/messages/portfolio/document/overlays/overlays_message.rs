@@ -1,3 +1,4 @@
+use super::utility_types::OverlayLabel;
 use crate::messages::prelude::*;
 
 use document_legacy::Operation as DocumentOperation;
@@ -15,6 +16,9 @@ pub enum OverlaysMessage {
 	// Messages
 	ClearAllOverlays,
 	Rerender,
+	SetLabels {
+		labels: Vec<OverlayLabel>,
+	},
 }
 
 impl From<DocumentOperation> for OverlaysMessage {
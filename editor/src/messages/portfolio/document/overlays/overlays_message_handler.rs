@@ -1,3 +1,4 @@
+use super::utility_types::OverlayLabel;
 use crate::messages::portfolio::utility_types::PersistentData;
 use crate::messages::prelude::*;
 
@@ -7,6 +8,7 @@ use document_legacy::layers::style::{RenderData, ViewMode};
 #[derive(Debug, Clone, Default)]
 pub struct OverlaysMessageHandler {
 	pub overlays_document: DocumentLegacy,
+	labels: Vec<OverlayLabel>,
 }
 
 impl MessageHandler<OverlaysMessage, (bool, &PersistentData, &InputPreprocessorMessageHandler)> for OverlaysMessageHandler {
@@ -26,6 +28,7 @@ impl MessageHandler<OverlaysMessage, (bool, &PersistentData, &InputPreprocessorM
 			// Messages
 			ClearAllOverlays => {
 				self.overlays_document = DocumentLegacy::default();
+				self.labels.clear();
 			}
 			Rerender =>
 			// Render overlays
@@ -33,12 +36,17 @@ impl MessageHandler<OverlaysMessage, (bool, &PersistentData, &InputPreprocessorM
 				responses.add(FrontendMessage::UpdateDocumentOverlays {
 					svg: if overlays_visible {
 						let render_data = RenderData::new(&persistent_data.font_cache, ViewMode::Normal, Some(ipp.document_bounds()));
-						self.overlays_document.render_root(&render_data)
+						let labels = self.labels.iter().map(OverlayLabel::render).collect::<String>();
+						self.overlays_document.render_root(&render_data) + &labels
 					} else {
 						String::from("")
 					},
 				})
 			}
+			SetLabels { labels } => {
+				self.labels = labels;
+				responses.add(OverlaysMessage::Rerender);
+			}
 		}
 	}
 
@@ -0,0 +1,24 @@
+use crate::consts::COLOR_ACCENT;
+
+use glam::DVec2;
+use serde::{Deserialize, Serialize};
+
+/// A line of text drawn over the canvas, such as the distance readouts shown while snapping.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct OverlayLabel {
+	/// The center of the text, in viewport space.
+	pub position: DVec2,
+	pub text: String,
+}
+
+impl OverlayLabel {
+	pub fn render(&self) -> String {
+		format!(
+			r##"<text x="{}" y="{}" text-anchor="middle" dominant-baseline="central" font-size="11" fill="#{}" stroke="#ffffff" stroke-width="3" paint-order="stroke">{}</text>"##,
+			self.position.x,
+			self.position.y,
+			COLOR_ACCENT.rgb_hex(),
+			self.text
+		)
+	}
+}
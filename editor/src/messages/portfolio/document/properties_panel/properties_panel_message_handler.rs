@@ -27,6 +27,8 @@ impl<'a> MessageHandler<PropertiesPanelMessage, (&PersistentData, PropertiesPane
 			artwork_document,
 			artboard_document,
 			selected_layers,
+			export_slices,
+			swatches,
 			node_graph_message_handler,
 			executor,
 		} = data;
@@ -139,9 +141,10 @@ impl<'a> MessageHandler<PropertiesPanelMessage, (&PersistentData, PropertiesPane
 				if let Some((path, target_document)) = self.active_selection.clone() {
 					let document = get_document(target_document);
 					let layer = document.layer(&path).unwrap();
+					let export_slice = export_slices.iter().find(|slice| slice.layer_path == path && slice.document == target_document);
 					match target_document {
-						TargetDocument::Artboard => register_artboard_layer_properties(layer, responses, persistent_data),
-						TargetDocument::Artwork => register_artwork_layer_properties(document, path, layer, responses, persistent_data, node_graph_message_handler, executor),
+						TargetDocument::Artboard => register_artboard_layer_properties(path, layer, export_slice, responses, persistent_data),
+						TargetDocument::Artwork => register_artwork_layer_properties(document, path, layer, export_slice, responses, persistent_data, node_graph_message_handler, executor),
 					}
 				} else {
 					let context = crate::messages::portfolio::document::node_graph::NodePropertiesContext {
@@ -153,7 +156,7 @@ impl<'a> MessageHandler<PropertiesPanelMessage, (&PersistentData, PropertiesPane
 						executor,
 						network: &artwork_document.document_network,
 					};
-					register_document_graph_properties(context, node_graph_message_handler, document_name);
+					register_document_graph_properties(context, node_graph_message_handler, document_name, swatches);
 				}
 			}
 			UpdateSelectedDocumentProperties => responses.add(PropertiesPanelMessage::SetActiveLayers {
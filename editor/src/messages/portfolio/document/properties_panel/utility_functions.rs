@@ -1,7 +1,9 @@
 use super::utility_types::TransformOp;
 use crate::application::generate_uuid;
+use crate::messages::frontend::utility_types::FileType;
 use crate::messages::layout::utility_types::widget_prelude::*;
 use crate::messages::portfolio::document::node_graph::NodePropertiesContext;
+use crate::messages::portfolio::document::utility_types::misc::{ExportFormat, ExportSlice, TargetDocument};
 use crate::messages::portfolio::utility_types::PersistentData;
 use crate::messages::prelude::*;
 use crate::node_graph_executor::NodeGraphExecutor;
@@ -64,7 +66,13 @@ pub fn apply_transform_operation(layer: &Layer, transform_op: TransformOp, value
 	((pivot * delta * pivot.inverse()) * layer.transform).to_cols_array()
 }
 
-pub fn register_artboard_layer_properties(layer: &Layer, responses: &mut VecDeque<Message>, persistent_data: &PersistentData) {
+pub fn register_artboard_layer_properties(
+	layer_path: Vec<document_legacy::LayerId>,
+	layer: &Layer,
+	export_slice: Option<&ExportSlice>,
+	responses: &mut VecDeque<Message>,
+	persistent_data: &PersistentData,
+) {
 	let options_bar = vec![LayoutGroup::Row {
 		widgets: vec![
 			IconLabel::new("NodeArtboard").tooltip("Artboard").widget_holder(),
@@ -186,6 +194,8 @@ pub fn register_artboard_layer_properties(layer: &Layer, responses: &mut VecDequ
 			],
 		}]
 	};
+	let name = layer.name.clone().unwrap_or_else(|| "Untitled Artboard".to_string());
+	let properties_body = [properties_body, vec![node_section_export(export_slice, layer_path, TargetDocument::Artboard, name)]].concat();
 
 	responses.add(LayoutMessage::SendLayout {
 		layout: Layout::WidgetLayout(WidgetLayout::new(options_bar)),
@@ -197,10 +207,12 @@ pub fn register_artboard_layer_properties(layer: &Layer, responses: &mut VecDequ
 	});
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn register_artwork_layer_properties(
 	document: &Document,
 	layer_path: Vec<document_legacy::LayerId>,
 	layer: &Layer,
+	export_slice: Option<&ExportSlice>,
 	responses: &mut VecDeque<Message>,
 	persistent_data: &PersistentData,
 	node_graph_message_handler: &NodeGraphMessageHandler,
@@ -222,7 +234,7 @@ pub fn register_artwork_layer_properties(
 		],
 	}];
 
-	let properties_body = match &layer.data {
+	let mut properties_body = match &layer.data {
 		LayerDataType::Shape(shape) => {
 			if let Some(fill_layout) = node_section_fill(shape.style.fill()) {
 				vec![
@@ -254,6 +266,8 @@ pub fn register_artwork_layer_properties(
 			vec![node_section_transform(layer, persistent_data)]
 		}
 	};
+	let name = layer.name.clone().unwrap_or_else(|| "Untitled Layer".to_string());
+	properties_body.push(node_section_export(export_slice, layer_path, TargetDocument::Artwork, name));
 
 	responses.add(LayoutMessage::SendLayout {
 		layout: Layout::WidgetLayout(WidgetLayout::new(options_bar)),
@@ -265,9 +279,10 @@ pub fn register_artwork_layer_properties(
 	});
 }
 
-pub fn register_document_graph_properties(mut context: NodePropertiesContext, node_graph_message_handler: &NodeGraphMessageHandler, document_name: &str) {
+pub fn register_document_graph_properties(mut context: NodePropertiesContext, node_graph_message_handler: &NodeGraphMessageHandler, document_name: &str, swatches: &[Color]) {
 	let mut properties_sections = Vec::new();
 	node_graph_message_handler.collate_properties(&mut context, &mut properties_sections);
+	properties_sections.push(node_section_swatches(swatches));
 	let options_bar = vec![LayoutGroup::Row {
 		widgets: vec![
 			IconLabel::new("File").tooltip("Document").widget_holder(),
@@ -926,3 +941,161 @@ impl DAffine2Utils for DAffine2 {
 		DAffine2::from_scale_angle_translation((width, height).into(), new_rotation, self.translation + angle_translation_adjustment)
 	}
 }
+
+/// The document's swatches, which are edited as a whole list at a time.
+fn node_section_swatches(swatches: &[Color]) -> LayoutGroup {
+	let update = {
+		let swatches = swatches.to_vec();
+		move |modify: &dyn Fn(&mut Vec<Color>)| -> Message {
+			let mut swatches = swatches.clone();
+			modify(&mut swatches);
+			DocumentMessage::SetSwatches { swatches }.into()
+		}
+	};
+	let update = Arc::new(update);
+
+	let mut layout = swatches
+		.iter()
+		.enumerate()
+		.map(|(index, &color)| {
+			let (update_color, update_remove) = (update.clone(), update.clone());
+			LayoutGroup::Row {
+				widgets: vec![
+					ColorInput::new(Some(color))
+						.allow_none(false)
+						.on_update(move |color_input: &ColorInput| update_color(&|swatches| swatches[index] = color_input.value.unwrap_or(color)))
+						.widget_holder(),
+					Separator::new(SeparatorType::Related).widget_holder(),
+					IconButton::new("WorkingColorsPrimary", 16)
+						.tooltip("Use as the primary working color")
+						.on_update(move |_: &IconButton| ToolMessage::SelectPrimaryColor { color }.into())
+						.widget_holder(),
+					Separator::new(SeparatorType::Related).widget_holder(),
+					IconButton::new("Remove", 16)
+						.tooltip("Remove this swatch")
+						.on_update(move |_: &IconButton| {
+							update_remove(&|swatches| {
+								swatches.remove(index);
+							})
+						})
+						.widget_holder(),
+				],
+			}
+		})
+		.collect::<Vec<_>>();
+
+	if swatches.is_empty() {
+		layout.push(LayoutGroup::Row {
+			widgets: vec![TextLabel::new("Extract a palette from the output of an image layer to add swatches").widget_holder()],
+		});
+	} else {
+		layout.push(LayoutGroup::Row {
+			widgets: vec![TextButton::new("Clear Swatches")
+				.on_update(|_| DocumentMessage::SetSwatches { swatches: Vec::new() }.into())
+				.widget_holder()],
+		});
+	}
+
+	LayoutGroup::Section { name: "Swatches".into(), layout }
+}
+
+/// The formats a layer or artboard is exported in by the "Export All" action, which are edited as a whole slice at a time.
+fn node_section_export(export_slice: Option<&ExportSlice>, layer_path: Vec<document_legacy::LayerId>, document: TargetDocument, layer_name: String) -> LayoutGroup {
+	let slice = export_slice.cloned().unwrap_or(ExportSlice {
+		name: layer_name,
+		layer_path,
+		document,
+		formats: Vec::new(),
+	});
+	let update = {
+		let slice = slice.clone();
+		move |modify: &dyn Fn(&mut ExportSlice)| -> Message {
+			let mut slice = slice.clone();
+			modify(&mut slice);
+			DocumentMessage::SetExportSlice { slice }.into()
+		}
+	};
+	let update = Arc::new(update);
+
+	let mut layout = Vec::new();
+	if !slice.formats.is_empty() {
+		let update = update.clone();
+		layout.push(LayoutGroup::Row {
+			widgets: vec![
+				TextLabel::new("Name").widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(), // TODO: These three separators add up to 24px,
+				Separator::new(SeparatorType::Unrelated).widget_holder(), // TODO: which is the width of the Assist area.
+				Separator::new(SeparatorType::Unrelated).widget_holder(), // TODO: Remove these when we have proper entry row formatting that includes room for Assists.
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				TextInput::new(slice.name.clone())
+					.tooltip("The name of the exported files, before each format's suffix")
+					.on_update(move |text_input: &TextInput| update(&|slice| slice.name = text_input.value.clone()))
+					.widget_holder(),
+			],
+		});
+	}
+
+	for (index, format) in slice.formats.iter().enumerate() {
+		let (update_scale, update_suffix, update_remove) = (update.clone(), update.clone(), update.clone());
+		let file_type_entries = vec![ExportFormat::FILE_TYPES
+			.into_iter()
+			.map(|(file_type, name)| {
+				let update = update.clone();
+				DropdownEntryData::new(name).on_update(move |_| update(&|slice| slice.formats[index].file_type = file_type))
+			})
+			.collect()];
+		let file_type_index = ExportFormat::FILE_TYPES.iter().position(|&(file_type, _)| file_type == format.file_type);
+		let is_vector = matches!(format.file_type, FileType::Svg | FileType::Pdf);
+
+		layout.push(LayoutGroup::Row {
+			widgets: vec![
+				TextLabel::new(if index == 0 { "Formats" } else { "" }).widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				Separator::new(SeparatorType::Unrelated).widget_holder(), // TODO: These three separators add up to 24px,
+				Separator::new(SeparatorType::Unrelated).widget_holder(), // TODO: which is the width of the Assist area.
+				Separator::new(SeparatorType::Unrelated).widget_holder(), // TODO: Remove these when we have proper entry row formatting that includes room for Assists.
+				Separator::new(SeparatorType::Unrelated).widget_holder(),
+				NumberInput::new(Some(format.scale_factor))
+					.unit("x")
+					.min(0.01)
+					.tooltip("Scale factor, relative to the size in the document")
+					.disabled(is_vector)
+					.on_update(move |number_input: &NumberInput| update_scale(&|slice| slice.formats[index].scale_factor = number_input.value.unwrap()))
+					.widget_holder(),
+				Separator::new(SeparatorType::Related).widget_holder(),
+				TextInput::new(format.suffix.clone())
+					.tooltip("Suffix appended to the name")
+					.on_update(move |text_input: &TextInput| update_suffix(&|slice| slice.formats[index].suffix = text_input.value.clone()))
+					.widget_holder(),
+				Separator::new(SeparatorType::Related).widget_holder(),
+				DropdownInput::new(file_type_entries).selected_index(file_type_index.map(|index| index as u32)).widget_holder(),
+				Separator::new(SeparatorType::Related).widget_holder(),
+				IconButton::new("Remove", 16)
+					.tooltip("Remove this format")
+					.on_update(move |_: &IconButton| {
+						update_remove(&|slice| {
+							slice.formats.remove(index);
+						})
+					})
+					.widget_holder(),
+			],
+		});
+	}
+
+	layout.push(LayoutGroup::Row {
+		widgets: vec![
+			TextButton::new("Add Format")
+				.tooltip("Export this layer at the next scale factor")
+				.on_update(move |_| update(&|slice| slice.add_format()))
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			TextButton::new("Export All")
+				.tooltip("Export every layer and artboard with formats, in a ZIP archive if there are several files")
+				.on_update(|_| DocumentMessage::ExportAllSlices.into())
+				.widget_holder(),
+		],
+	});
+
+	LayoutGroup::Section { name: "Export".into(), layout }
+}
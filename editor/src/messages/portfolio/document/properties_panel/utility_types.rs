@@ -1,8 +1,10 @@
 use document_legacy::document::Document as DocumentLegacy;
 use document_legacy::LayerId;
+use graphene_core::Color;
 
 use serde::{Deserialize, Serialize};
 
+use crate::messages::portfolio::document::utility_types::misc::ExportSlice;
 use crate::{messages::prelude::NodeGraphMessageHandler, node_graph_executor::NodeGraphExecutor};
 
 pub struct PropertiesPanelMessageHandlerData<'a> {
@@ -10,6 +12,8 @@ pub struct PropertiesPanelMessageHandlerData<'a> {
 	pub artwork_document: &'a DocumentLegacy,
 	pub artboard_document: &'a DocumentLegacy,
 	pub selected_layers: &'a mut dyn Iterator<Item = &'a [LayerId]>,
+	pub export_slices: &'a [ExportSlice],
+	pub swatches: &'a [Color],
 	pub node_graph_message_handler: &'a NodeGraphMessageHandler,
 	pub executor: &'a mut NodeGraphExecutor,
 }
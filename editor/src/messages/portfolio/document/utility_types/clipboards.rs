@@ -1,9 +1,21 @@
 use super::layer_panel::LayerMetadata;
 
-use document_legacy::layers::layer_info::Layer;
+use document_legacy::layers::blend_mode::BlendMode;
+use document_legacy::layers::layer_info::{Layer, LayerDataType};
+use graph_craft::document::value::TaggedValue;
+use graph_craft::document::{DocumentNode, NodeId, NodeInput};
+use graphene_core::text::Font;
+use graphene_core::vector::style::{Fill, FillType, Gradient, Stroke};
 
 use serde::{Deserialize, Serialize};
 
+/// The start of the text written to the system clipboard, by which copied content is recognized when it's pasted back into the editor.
+pub const CLIPBOARD_TEXT_PREFIX: &str = "graphite/clipboard: ";
+/// The start of the text older versions of the editor wrote to the system clipboard for copied layers, which is still pasted.
+pub const LEGACY_LAYER_CLIPBOARD_TEXT_PREFIX: &str = "graphite/layer: ";
+/// The start of the text older versions of the editor wrote to the system clipboard for copied nodes, which is still pasted.
+pub const LEGACY_NODE_CLIPBOARD_TEXT_PREFIX: &str = "graphite/nodes: ";
+
 #[repr(u8)]
 #[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, specta::Type)]
 pub enum Clipboard {
@@ -16,8 +28,276 @@ pub enum Clipboard {
 
 pub const INTERNAL_CLIPBOARD_COUNT: u8 = Clipboard::_InternalClipboardCount as u8;
 
+/// Which parts of the copied layers are pasted.
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug, Default, specta::Type)]
+pub enum PasteMode {
+	/// The layers and nodes themselves are pasted.
+	#[default]
+	All,
+	/// The fill, stroke, blend mode and opacity of the first copied layer are applied to the selected layers.
+	StyleOnly,
+	/// The transform of the first copied layer is applied to the selected layers.
+	TransformOnly,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct CopyBufferEntry {
 	pub layer: Layer,
 	pub layer_metadata: LayerMetadata,
 }
+
+/// Everything copied at once, along with the fonts it uses, so that it can be pasted into any document, including one in another instance of the editor.
+///
+/// Images are embedded in the values of the nodes which use them, so they're carried along with the layers and nodes.
+/// The rendered output of the layers is left out, since it only exists in the browser tab it was rendered in, so the layers are rendered again when pasted.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ClipboardContent {
+	pub layers: Vec<CopyBufferEntry>,
+	/// The copied nodes under the ids by which the links between them refer to each other.
+	pub nodes: Vec<(NodeId, DocumentNode)>,
+	/// The fonts used by the layers and nodes, which are loaded when pasting into a document which hasn't used them yet.
+	pub fonts: Vec<Font>,
+}
+
+impl ClipboardContent {
+	pub fn from_layers(layers: Vec<CopyBufferEntry>) -> Self {
+		let mut fonts = Vec::new();
+		for entry in &layers {
+			collect_layer_fonts(&entry.layer.data, &mut fonts);
+		}
+		Self { layers, fonts, ..Default::default() }
+	}
+
+	pub fn from_nodes(nodes: Vec<(NodeId, DocumentNode)>) -> Self {
+		let mut fonts = Vec::new();
+		for (_, node) in &nodes {
+			add_fonts(node_fonts(node), &mut fonts);
+		}
+		Self { nodes, fonts, ..Default::default() }
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.layers.is_empty() && self.nodes.is_empty()
+	}
+
+	/// The text written to the system clipboard, which is parsed back with [`Self::from_clipboard_text`].
+	pub fn to_clipboard_text(&self) -> String {
+		CLIPBOARD_TEXT_PREFIX.to_string() + &serde_json::to_string(self).expect("Could not serialize the clipboard content")
+	}
+
+	/// Parses the text read from the system clipboard, with or without its prefix, including the layers or nodes copied by older versions of the editor.
+	pub fn from_clipboard_text(text: &str) -> Result<Self, serde_json::Error> {
+		if let Some(layers) = text.strip_prefix(LEGACY_LAYER_CLIPBOARD_TEXT_PREFIX) {
+			return serde_json::from_str(layers).map(Self::from_layers);
+		}
+		if let Some(nodes) = text.strip_prefix(LEGACY_NODE_CLIPBOARD_TEXT_PREFIX) {
+			return serde_json::from_str(nodes).map(Self::from_nodes);
+		}
+		serde_json::from_str(text.strip_prefix(CLIPBOARD_TEXT_PREFIX).unwrap_or(text))
+	}
+}
+
+/// The appearance of a layer which "Paste Style Only" carries over to other layers.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LayerStyle {
+	/// The fill of the layer's "Fill" node, if it has one.
+	pub fill: Option<Fill>,
+	/// The stroke of the layer's "Stroke" node, if it has one.
+	pub stroke: Option<Stroke>,
+	pub blend_mode: BlendMode,
+	pub opacity: f64,
+}
+
+impl LayerStyle {
+	pub fn of(layer: &Layer) -> Self {
+		let nodes: Vec<&DocumentNode> = match &layer.data {
+			LayerDataType::Layer(layer) => layer.network.nodes.values().collect(),
+			_ => Vec::new(),
+		};
+		Self {
+			fill: nodes.iter().find(|node| node.name == "Fill").and_then(|node| fill_of(node)),
+			stroke: nodes.iter().find(|node| node.name == "Stroke").and_then(|node| stroke_of(node)),
+			blend_mode: layer.blend_mode,
+			opacity: layer.opacity,
+		}
+	}
+}
+
+/// The fonts used by the values of a node's inputs.
+pub fn node_fonts(node: &DocumentNode) -> impl Iterator<Item = &Font> {
+	node.inputs.iter().flat_map(|input| match input {
+		NodeInput::Value {
+			tagged_value: TaggedValue::Font(font),
+			..
+		} => vec![font],
+		NodeInput::Value {
+			tagged_value: TaggedValue::RichText(rich_text),
+			..
+		} => rich_text.fonts().collect(),
+		_ => Vec::new(),
+	})
+}
+
+fn add_fonts<'a>(new_fonts: impl Iterator<Item = &'a Font>, fonts: &mut Vec<Font>) {
+	for font in new_fonts {
+		if !fonts.contains(font) {
+			fonts.push(font.clone());
+		}
+	}
+}
+
+fn collect_layer_fonts(data: &LayerDataType, fonts: &mut Vec<Font>) {
+	match data {
+		LayerDataType::Folder(folder) => folder.layers().iter().for_each(|layer| collect_layer_fonts(&layer.data, fonts)),
+		LayerDataType::Layer(layer) => layer.network.nodes.values().for_each(|node| add_fonts(node_fonts(node), fonts)),
+		LayerDataType::Shape(_) => {}
+	}
+}
+
+fn input_value(node: &DocumentNode, index: usize) -> Option<&TaggedValue> {
+	match node.inputs.get(index)? {
+		NodeInput::Value { tagged_value, .. } => Some(tagged_value),
+		_ => None,
+	}
+}
+
+/// Reads the fill from the inputs of a "Fill" node, in the order they're set by the `FillSet` graph operation.
+fn fill_of(node: &DocumentNode) -> Option<Fill> {
+	let TaggedValue::FillType(fill_type) = input_value(node, 1)? else { return None };
+	match fill_type {
+		FillType::None => Some(Fill::None),
+		FillType::Solid => match input_value(node, 2)? {
+			TaggedValue::OptionalColor(Some(color)) => Some(Fill::Solid(*color)),
+			TaggedValue::OptionalColor(None) => Some(Fill::None),
+			_ => None,
+		},
+		FillType::Gradient => match (input_value(node, 3)?, input_value(node, 4)?, input_value(node, 5)?, input_value(node, 6)?, input_value(node, 7)?) {
+			(TaggedValue::GradientType(gradient_type), TaggedValue::DVec2(start), TaggedValue::DVec2(end), TaggedValue::DAffine2(transform), TaggedValue::GradientPositions(positions)) => {
+				Some(Fill::Gradient(Gradient {
+					start: *start,
+					end: *end,
+					transform: *transform,
+					positions: positions.clone(),
+					gradient_type: *gradient_type,
+				}))
+			}
+			_ => None,
+		},
+	}
+}
+
+/// Reads the stroke from the inputs of a "Stroke" node, in the order they're set by the `StrokeSet` graph operation.
+fn stroke_of(node: &DocumentNode) -> Option<Stroke> {
+	let values = (1..=7).map(|index| input_value(node, index)).collect::<Option<Vec<_>>>()?;
+	match values[..] {
+		[TaggedValue::OptionalColor(color), TaggedValue::F32(weight), TaggedValue::VecF32(dash_lengths), TaggedValue::F32(dash_offset), TaggedValue::LineCap(line_cap), TaggedValue::LineJoin(line_join), TaggedValue::F32(miter_limit)] => {
+			Some(Stroke {
+				color: *color,
+				weight: *weight as f64,
+				dash_lengths: dash_lengths.clone(),
+				dash_offset: *dash_offset as f64,
+				line_cap: *line_cap,
+				line_join: *line_join,
+				line_join_miter_limit: *miter_limit as f64,
+			})
+		}
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use document_legacy::layers::layer_layer::LayerLayer;
+	use glam::DAffine2;
+	use graph_craft::document::NodeNetwork;
+	use graphene_core::raster::color::Color;
+	use graphene_core::vector::style::{LineCap, LineJoin};
+
+	fn node(name: &str, values: Vec<TaggedValue>) -> DocumentNode {
+		let inputs = std::iter::once(NodeInput::node(0, 0)).chain(values.into_iter().map(|value| NodeInput::value(value, false)));
+		DocumentNode {
+			name: name.into(),
+			inputs: inputs.collect(),
+			..Default::default()
+		}
+	}
+
+	fn layer(nodes: Vec<DocumentNode>) -> Layer {
+		let network = NodeNetwork {
+			nodes: nodes.into_iter().enumerate().map(|(id, node)| (id as NodeId + 1, node)).collect(),
+			..Default::default()
+		};
+		Layer::new(LayerDataType::Layer(LayerLayer { network, ..Default::default() }), DAffine2::IDENTITY.to_cols_array())
+	}
+
+	#[test]
+	fn style_is_read_from_the_fill_and_stroke_nodes() {
+		let fill = node("Fill", vec![TaggedValue::FillType(FillType::Solid), TaggedValue::OptionalColor(Some(Color::RED))]);
+		let stroke = node(
+			"Stroke",
+			vec![
+				TaggedValue::OptionalColor(Some(Color::BLACK)),
+				TaggedValue::F32(2.),
+				TaggedValue::VecF32(vec![4., 1.]),
+				TaggedValue::F32(0.5),
+				TaggedValue::LineCap(LineCap::Round),
+				TaggedValue::LineJoin(LineJoin::Bevel),
+				TaggedValue::F32(4.),
+			],
+		);
+		let mut styled = layer(vec![fill, stroke]);
+		styled.blend_mode = BlendMode::Multiply;
+		styled.opacity = 0.5;
+
+		let style = LayerStyle::of(&styled);
+		assert_eq!(style.fill, Some(Fill::Solid(Color::RED)));
+		let stroke = style.stroke.unwrap();
+		assert_eq!(
+			(stroke.color, stroke.weight, stroke.dash_lengths, stroke.line_cap),
+			(Some(Color::BLACK), 2., vec![4., 1.], LineCap::Round)
+		);
+		assert_eq!((style.blend_mode, style.opacity), (BlendMode::Multiply, 0.5));
+
+		// A layer without the nodes keeps its own fill and stroke
+		let style = LayerStyle::of(&layer(Vec::new()));
+		assert_eq!((style.fill, style.stroke), (None, None));
+	}
+
+	#[test]
+	fn content_round_trips_through_the_clipboard_text() {
+		let font = Font::new("Source Sans Pro".into(), "Bold (700)".into());
+		let text = node("Text", vec![TaggedValue::String("Hello".into()), TaggedValue::Font(font.clone())]);
+		let content = ClipboardContent::from_layers(vec![CopyBufferEntry {
+			layer: layer(vec![text.clone(), text.clone()]),
+			layer_metadata: LayerMetadata::new(false),
+		}]);
+		assert_eq!(content.fonts, [font.clone()]);
+
+		let clipboard_text = content.to_clipboard_text();
+		assert!(clipboard_text.starts_with(CLIPBOARD_TEXT_PREFIX));
+		assert_eq!(ClipboardContent::from_clipboard_text(&clipboard_text).unwrap(), content);
+
+		let nodes = ClipboardContent::from_nodes(vec![(1, text)]);
+		assert_eq!(nodes.fonts, [font]);
+		assert!(nodes.layers.is_empty() && !nodes.is_empty());
+	}
+
+	#[test]
+	fn content_copied_by_older_versions_is_pasted() {
+		let font = Font::new("Source Sans Pro".into(), "Bold (700)".into());
+		let text = node("Text", vec![TaggedValue::String("Hello".into()), TaggedValue::Font(font.clone())]);
+		let layers = vec![CopyBufferEntry {
+			layer: layer(vec![text.clone()]),
+			layer_metadata: LayerMetadata::new(false),
+		}];
+
+		let layer_text = LEGACY_LAYER_CLIPBOARD_TEXT_PREFIX.to_string() + &serde_json::to_string(&layers).unwrap();
+		assert_eq!(ClipboardContent::from_clipboard_text(&layer_text).unwrap(), ClipboardContent::from_layers(layers));
+
+		let nodes = vec![(1, text)];
+		let node_text = LEGACY_NODE_CLIPBOARD_TEXT_PREFIX.to_string() + &serde_json::to_string(&nodes).unwrap();
+		let content = ClipboardContent::from_clipboard_text(&node_text).unwrap();
+		assert_eq!((content.nodes, content.fonts), (nodes, vec![font]));
+	}
+}
@@ -0,0 +1,686 @@
+use super::layer_panel::LayerMetadata;
+use super::misc::DocumentSave;
+use crate::consts::MAX_UNDO_HISTORY_SIZE;
+use crate::messages::prelude::ArtboardMessageHandler;
+
+use document_legacy::delta::DocumentDelta;
+use document_legacy::document::Document as DocumentLegacy;
+use document_legacy::LayerId;
+use graph_craft::document::NodeId;
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+pub type HistoryStepId = u64;
+
+/// The history of a document, which stores each step as the changes it made instead of a copy of the whole document.
+///
+/// The steps form a tree rather than a line: making a change after undoing starts a new branch from the current step, and the steps which were undone are kept on their own branch.
+/// Only the state of the document at the start of the current transaction is copied, and the changes since then become a step once the document is backed up again or the history is moved through.
+/// The steps are limited by their total size in bytes rather than their number, so that steps which change large images are dropped sooner than those which move a layer.
+#[derive(Debug, Clone, Default)]
+pub struct DocumentHistory {
+	/// The steps in the order they were made.
+	steps: BTreeMap<HistoryStepId, HistoryStep>,
+	/// The step at whose end the document is, or `None` if it is at the oldest kept state.
+	current: Option<HistoryStepId>,
+	next_id: HistoryStepId,
+	/// Counts the moves onto steps, so that redoing follows the branch which was visited last.
+	visits: u64,
+	snapshots: Vec<HistorySnapshot>,
+	transaction_start: Option<TransactionStart>,
+	/// The total size of the steps.
+	size: usize,
+}
+
+/// A named state of the document, whose step is kept in the history for as long as the snapshot exists.
+#[derive(Debug, Clone)]
+pub struct HistorySnapshot {
+	pub name: String,
+	/// The step at whose end the state is, or `None` for the oldest kept state.
+	pub step: Option<HistoryStepId>,
+}
+
+/// Whether the step a transaction becomes is merged with the steps next to it, when they all only change the values of the same nodes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coalescing {
+	/// The step is kept apart from the steps next to it.
+	Never,
+	/// The steps of later coalescing transactions can be merged into the step, but it isn't merged into the one before.
+	Start,
+	/// The step is merged into the one before.
+	Continue,
+}
+
+#[derive(Debug, Clone)]
+struct TransactionStart {
+	save: DocumentSave,
+	coalescing: Coalescing,
+	label: String,
+}
+
+#[derive(Debug, Clone)]
+struct HistoryStep {
+	label: String,
+	/// The step which this one follows, or `None` if it follows the oldest kept state.
+	parent: Option<HistoryStepId>,
+	/// The changes which return the document to the start of the step if it is at or past the step's end, or move it to the end of the step otherwise.
+	changes: StepChanges,
+	/// The identifier of the state of the document at the end of the step.
+	state_identifier: u64,
+	last_visit: u64,
+}
+
+impl DocumentHistory {
+	/// Starts a transaction from the given state of the document, turning the changes made in the previous transaction into a step.
+	///
+	/// A coalescing transaction is merged into the step before it when both only change the values of the same nodes, so that dragging a number in the Properties panel makes a single step.
+	pub fn backup(&mut self, save: DocumentSave, coalescing: Coalescing, label: String) {
+		self.commit(&save.document, &save.artboard, &save.layer_metadata);
+		self.transaction_start = Some(TransactionStart { save, coalescing, label });
+	}
+
+	/// Renames the step which the current transaction will become.
+	pub fn set_label(&mut self, label: String) {
+		if let Some(start) = &mut self.transaction_start {
+			start.label = label;
+		}
+	}
+
+	/// Returns the document to its state at the start of the current step, or returns `false` if there is none.
+	pub fn undo(&mut self, document: &mut DocumentLegacy, artboard: &mut ArtboardMessageHandler, layer_metadata: &mut HashMap<Vec<LayerId>, LayerMetadata>) -> bool {
+		// An unfinished transaction which hasn't changed anything is what gets undone, rather than the step before it
+		let unfinished = self.transaction_start.is_some();
+		if !self.commit(document, artboard, layer_metadata) && unfinished {
+			return true;
+		}
+
+		let Some(step) = self.current else { return false };
+		self.cross(step, document, artboard, layer_metadata);
+		self.current = self.steps[&step].parent;
+		self.limit_size();
+		true
+	}
+
+	/// Moves the document to the end of the step after the current one on the branch which was visited last, or returns `false` if there is none.
+	pub fn redo(&mut self, document: &mut DocumentLegacy, artboard: &mut ArtboardMessageHandler, layer_metadata: &mut HashMap<Vec<LayerId>, LayerMetadata>) -> bool {
+		self.commit(document, artboard, layer_metadata);
+
+		let next = self.steps.iter().filter(|(_, step)| step.parent == self.current).max_by_key(|(_, step)| step.last_visit);
+		let Some((&next, _)) = next else { return false };
+		self.enter(next, document, artboard, layer_metadata);
+		self.limit_size();
+		true
+	}
+
+	/// Moves the document to the end of the given step, or to the oldest kept state for `None`, through the steps between it and the current one.
+	///
+	/// Returns `false` if the step is no longer in the history.
+	pub fn jump(&mut self, target: Option<HistoryStepId>, document: &mut DocumentLegacy, artboard: &mut ArtboardMessageHandler, layer_metadata: &mut HashMap<Vec<LayerId>, LayerMetadata>) -> bool {
+		self.commit(document, artboard, layer_metadata);
+		if target.is_some_and(|target| !self.steps.contains_key(&target)) {
+			return false;
+		}
+
+		// Undo back to the last step which the target follows, then redo along the target's branch
+		let target_path = self.path(target);
+		while let Some(step) = self.current.filter(|step| !target_path.contains(step)) {
+			self.cross(step, document, artboard, layer_metadata);
+			self.current = self.steps[&step].parent;
+		}
+		let shared = target_path.iter().position(|&step| Some(step) == self.current).unwrap_or(target_path.len());
+		for &step in target_path[..shared].iter().rev() {
+			self.enter(step, document, artboard, layer_metadata);
+		}
+		self.limit_size();
+		true
+	}
+
+	/// Returns the document to its state at the start of the current transaction and forgets the transaction, or returns `false` if there is none.
+	pub fn abort(&mut self, document: &mut DocumentLegacy, artboard: &mut ArtboardMessageHandler, layer_metadata: &mut HashMap<Vec<LayerId>, LayerMetadata>) -> bool {
+		let Some(TransactionStart { save, .. }) = self.transaction_start.take() else { return false };
+		StepChanges::new(document, artboard, layer_metadata, &save, false).apply(document, artboard, layer_metadata);
+		true
+	}
+
+	/// Names the current state of the document, turning the changes made in the current transaction into a step so that the snapshot includes them.
+	pub fn snapshot(&mut self, name: String, document: &DocumentLegacy, artboard: &ArtboardMessageHandler, layer_metadata: &HashMap<Vec<LayerId>, LayerMetadata>) {
+		self.commit(document, artboard, layer_metadata);
+		self.snapshots.push(HistorySnapshot { name, step: self.current });
+	}
+
+	pub fn rename_snapshot(&mut self, index: usize, name: String) {
+		if let Some(snapshot) = self.snapshots.get_mut(index) {
+			snapshot.name = name;
+		}
+	}
+
+	/// Forgets a snapshot, letting its step be dropped from the history once it runs out of space.
+	pub fn delete_snapshot(&mut self, index: usize) {
+		if index < self.snapshots.len() {
+			self.snapshots.remove(index);
+			self.limit_size();
+		}
+	}
+
+	pub fn snapshots(&self) -> &[HistorySnapshot] {
+		&self.snapshots
+	}
+
+	/// The step at whose end the document is, or `None` if it is at the oldest kept state.
+	pub fn current(&self) -> Option<HistoryStepId> {
+		self.current
+	}
+
+	/// The label of the step which the current transaction will become, if it changes anything.
+	pub fn pending_label(&self) -> Option<&str> {
+		self.transaction_start.as_ref().map(|start| start.label.as_str())
+	}
+
+	/// The steps in the order they're listed in the History panel, with the label of each and how many branches deep it is.
+	///
+	/// Every step is listed after the one it follows, and steps which follow one that already had a step after them start a deeper branch.
+	pub fn steps(&self) -> Vec<(HistoryStepId, &str, usize)> {
+		let mut following: HashMap<Option<HistoryStepId>, Vec<HistoryStepId>> = HashMap::new();
+		for (&id, step) in &self.steps {
+			following.entry(step.parent).or_default().push(id);
+		}
+
+		// Steps after the first one which follows the same step are indented, and pushed in reverse so that the first is listed first
+		let next_steps = |parent: Option<HistoryStepId>, depth: usize| {
+			let next = following.get(&parent).map_or(&[][..], Vec::as_slice);
+			next.iter().enumerate().rev().map(move |(index, &id)| (id, depth + (index > 0) as usize))
+		};
+		let mut listed = Vec::with_capacity(self.steps.len());
+		let mut stack: Vec<_> = next_steps(None, 0).collect();
+		while let Some((id, depth)) = stack.pop() {
+			listed.push((id, self.steps[&id].label.as_str(), depth));
+			stack.extend(next_steps(Some(id), depth));
+		}
+		listed
+	}
+
+	/// The identifier of the state of the document at the start of the current transaction or, once it is finished, at the end of the current step.
+	pub fn current_identifier(&self) -> u64 {
+		match (&self.transaction_start, self.current) {
+			(Some(start), _) => start.save.document.current_state_identifier(),
+			(None, Some(step)) => self.steps[&step].state_identifier,
+			(None, None) => 0,
+		}
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.steps.is_empty() && self.snapshots.is_empty() && self.transaction_start.is_none()
+	}
+
+	/// Turns the changes from the start of the current transaction to the given state into a step after the current one, returning whether there were any.
+	fn commit(&mut self, document: &DocumentLegacy, artboard: &ArtboardMessageHandler, layer_metadata: &HashMap<Vec<LayerId>, LayerMetadata>) -> bool {
+		let Some(TransactionStart { save: mut start, coalescing, label }) = self.transaction_start.take() else {
+			return false;
+		};
+
+		let coalesce = coalescing != Coalescing::Never;
+		let changes = StepChanges::new(document, artboard, layer_metadata, &start, coalesce);
+		let state_identifier = document.current_state_identifier();
+
+		// Only a step which nothing has been done after yet can be merged into, since otherwise the states after it would no longer follow from its end
+		let pinned = |step: &HistoryStepId| self.snapshots.iter().any(|snapshot| snapshot.step == Some(*step));
+		let has_children = |step: &HistoryStepId| self.steps.values().any(|other| other.parent == Some(*step));
+		let coalesces = |step: &HistoryStepId| {
+			coalescing == Coalescing::Continue && changes.coalesce_key.is_some() && self.steps[step].changes.coalesce_key == changes.coalesce_key && !pinned(step) && !has_children(step)
+		};
+		if let Some(previous) = self.current.filter(coalesces) {
+			// Undoing the step before on the copy of the document returns it to where that step started, so the merged step covers both
+			let mut step = self.steps.remove(&previous).expect("The current step should be in the history");
+			self.size -= step.changes.size;
+			step.changes.apply(&mut start.document, &mut start.artboard, &mut start.layer_metadata);
+			step.changes = StepChanges::new(document, artboard, layer_metadata, &start, coalesce);
+			step.state_identifier = state_identifier;
+			self.size += step.changes.size;
+			self.steps.insert(previous, step);
+		} else if !changes.is_empty() {
+			let id = self.next_id;
+			self.next_id += 1;
+			self.visits += 1;
+			self.size += changes.size;
+			let step = HistoryStep {
+				label,
+				parent: self.current,
+				changes,
+				state_identifier,
+				last_visit: self.visits,
+			};
+			self.steps.insert(id, step);
+			self.current = Some(id);
+		} else {
+			return false;
+		}
+
+		self.limit_size();
+		true
+	}
+
+	/// Moves the document onto the end of a step which follows the current one.
+	fn enter(&mut self, step: HistoryStepId, document: &mut DocumentLegacy, artboard: &mut ArtboardMessageHandler, layer_metadata: &mut HashMap<Vec<LayerId>, LayerMetadata>) {
+		self.cross(step, document, artboard, layer_metadata);
+		self.visits += 1;
+		self.steps.get_mut(&step).expect("The entered step should be in the history").last_visit = self.visits;
+		self.current = Some(step);
+	}
+
+	/// Moves the document to the other end of a step, keeping the changes which move it back.
+	///
+	/// Since the changes in each direction can differ in size, the history should be limited to its budget once it's done moving.
+	fn cross(&mut self, step: HistoryStepId, document: &mut DocumentLegacy, artboard: &mut ArtboardMessageHandler, layer_metadata: &mut HashMap<Vec<LayerId>, LayerMetadata>) {
+		let id = step;
+		let mut step = self.steps.remove(&id).expect("Only steps in the history can be crossed");
+		self.size -= step.changes.size;
+		step.changes = step.changes.apply(document, artboard, layer_metadata);
+		self.size += step.changes.size;
+		self.steps.insert(id, step);
+	}
+
+	/// The given step followed by each step before it.
+	fn path(&self, mut step: Option<HistoryStepId>) -> Vec<HistoryStepId> {
+		let mut path = Vec::new();
+		while let Some(id) = step {
+			path.push(id);
+			step = self.steps[&id].parent;
+		}
+		path
+	}
+
+	/// Drops the oldest steps which the document can do without until the history fits its size budget.
+	fn limit_size(&mut self) {
+		while self.size > MAX_UNDO_HISTORY_SIZE {
+			let Some(id) = self.droppable_step() else { break };
+			let step = self.steps.remove(&id).expect("The dropped step should be in the history");
+			self.size -= step.changes.size;
+
+			// If the step followed the oldest kept state, the state at its end becomes the oldest kept one
+			for following in self.steps.values_mut().filter(|following| following.parent == Some(id)) {
+				following.parent = None;
+			}
+			for snapshot in self.snapshots.iter_mut().filter(|snapshot| snapshot.step == Some(id)) {
+				snapshot.step = None;
+			}
+		}
+	}
+
+	/// The oldest step which is either the last of its branch without being current, or the only step after the oldest kept state once the document is past it.
+	///
+	/// Steps whose states are named by a snapshot are never dropped, and neither is the current step, so at least one step can always be undone.
+	fn droppable_step(&self) -> Option<HistoryStepId> {
+		let pinned: HashSet<_> = self.snapshots.iter().map(|snapshot| snapshot.step).collect();
+		let parents: HashSet<_> = self.steps.values().map(|step| step.parent).collect();
+
+		let mut branch_ends = self.steps.keys().filter(|&&id| Some(id) != self.current && !pinned.contains(&Some(id)) && !parents.contains(&Some(id)));
+		let branch_end = branch_ends.next().copied();
+
+		let mut first_steps = self.steps.iter().filter(|(_, step)| step.parent.is_none()).map(|(&id, _)| id);
+		let only_first_step = first_steps.next().filter(|_| first_steps.next().is_none());
+		let first_step = only_first_step.filter(|&id| self.current.is_some() && self.current != Some(id) && !pinned.contains(&None));
+
+		branch_end.into_iter().chain(first_step).min()
+	}
+}
+
+/// Names a history step after the message which made it, such as "Delete Selected Layers" for `Portfolio.Document.DeleteSelectedLayers` or "Select Tool" for `Tool.Select.DragStop`.
+pub fn step_label(message_name: &str) -> String {
+	let mut segments = message_name.split('.');
+	if let (Some("Tool"), Some(tool), Some(_)) = (segments.next(), segments.next(), segments.next()) {
+		if tool != "TransformLayer" {
+			return format!("{} Tool", split_words(tool));
+		}
+	}
+
+	split_words(message_name.rsplit('.').next().unwrap_or_default())
+}
+
+/// Separates the words of a name written in `UpperCamelCase`, keeping acronyms such as "SVG" together.
+fn split_words(name: &str) -> String {
+	let chars: Vec<char> = name.chars().collect();
+	let mut words = String::with_capacity(name.len() + 8);
+	for (index, &char) in chars.iter().enumerate() {
+		let previous = index.checked_sub(1).map(|index| chars[index]);
+		let next = chars.get(index + 1);
+		let starts_word = char.is_uppercase() && (previous.is_some_and(|previous| !previous.is_uppercase()) || (previous.is_some() && next.is_some_and(|next| next.is_lowercase())));
+		if starts_word {
+			words.push(' ');
+		}
+		words.push(char);
+	}
+	words
+}
+
+/// The changes which move the document from one end of a step to the other.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepChanges {
+	document: DocumentDelta,
+	artboards: DocumentDelta,
+	artboard_ids: Option<Vec<LayerId>>,
+	layer_metadata: Vec<(Vec<LayerId>, Option<LayerMetadata>)>,
+	/// The nodes whose values the step changes, if it came from a coalescing transaction and changes nothing else.
+	#[serde(skip)]
+	coalesce_key: Option<Vec<(Option<Vec<LayerId>>, NodeId)>>,
+	/// The size of the step in bytes, as estimated by the length of its serialized form.
+	#[serde(skip)]
+	size: usize,
+}
+
+impl StepChanges {
+	pub fn new(document: &DocumentLegacy, artboard: &ArtboardMessageHandler, layer_metadata: &HashMap<Vec<LayerId>, LayerMetadata>, to: &DocumentSave, coalesce: bool) -> Self {
+		Self::between(document, artboard, layer_metadata, &to.document, &to.artboard, &to.layer_metadata, coalesce)
+	}
+
+	/// The changes which move the document from the given state to the one given by the parts in `to`, for when that state isn't held in a [`DocumentSave`].
+	pub fn between(
+		document: &DocumentLegacy,
+		artboard: &ArtboardMessageHandler,
+		layer_metadata: &HashMap<Vec<LayerId>, LayerMetadata>,
+		to_document: &DocumentLegacy,
+		to_artboard: &ArtboardMessageHandler,
+		to_metadata: &HashMap<Vec<LayerId>, LayerMetadata>,
+		coalesce: bool,
+	) -> Self {
+		let changed_metadata = to_metadata.iter().filter(|&(path, metadata)| layer_metadata.get(path) != Some(metadata));
+		let removed_metadata = layer_metadata.keys().filter(|path| !to_metadata.contains_key(*path)).map(|path| (path.clone(), None));
+
+		let mut changes = Self {
+			document: DocumentDelta::new(document, to_document),
+			artboards: DocumentDelta::new(&artboard.artboards_document, &to_artboard.artboards_document),
+			artboard_ids: (artboard.artboard_ids != to_artboard.artboard_ids).then(|| to_artboard.artboard_ids.clone()),
+			layer_metadata: changed_metadata.map(|(path, &metadata)| (path.clone(), Some(metadata))).chain(removed_metadata).collect(),
+			coalesce_key: None,
+			size: 0,
+		};
+		if coalesce && changes.artboards.is_empty() && changes.artboard_ids.is_none() && changes.layer_metadata.is_empty() {
+			changes.coalesce_key = changes.document.changed_nodes().filter(|nodes| !nodes.is_empty());
+		}
+		changes.size = serialized_size(&changes);
+		changes
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.document.is_empty() && self.artboards.is_empty() && self.artboard_ids.is_none() && self.layer_metadata.is_empty()
+	}
+
+	/// Moves the document to the other end of the step, returning the changes which move it back.
+	pub fn apply(self, document: &mut DocumentLegacy, artboard: &mut ArtboardMessageHandler, layer_metadata: &mut HashMap<Vec<LayerId>, LayerMetadata>) -> Self {
+		let metadata = self.layer_metadata.into_iter().map(|(path, metadata)| {
+			let previous = match metadata {
+				Some(metadata) => layer_metadata.insert(path.clone(), metadata),
+				None => layer_metadata.remove(&path),
+			};
+			(path, previous)
+		});
+
+		let mut inverse = Self {
+			layer_metadata: metadata.collect(),
+			document: self.document.apply(document),
+			artboards: self.artboards.apply(&mut artboard.artboards_document),
+			artboard_ids: self.artboard_ids.map(|ids| std::mem::replace(&mut artboard.artboard_ids, ids)),
+			coalesce_key: None,
+			size: 0,
+		};
+		inverse.size = serialized_size(&inverse);
+		inverse
+	}
+}
+
+/// Counts the bytes written to it, so that the size of a value can be measured by serializing it without keeping the result.
+struct ByteCounter(usize);
+
+impl std::io::Write for ByteCounter {
+	fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+		self.0 += buf.len();
+		Ok(buf.len())
+	}
+
+	fn flush(&mut self) -> std::io::Result<()> {
+		Ok(())
+	}
+}
+
+fn serialized_size(value: &impl Serialize) -> usize {
+	let mut counter = ByteCounter(0);
+	serde_json::to_writer(&mut counter, value).unwrap_or_else(|error| warn!("Failed to measure the size of a history step: {error}"));
+	counter.0
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::test_utils::document_save;
+	use document_legacy::Operation;
+	use graphene_core::raster::ColorSpace;
+
+	fn rename(document: &mut DocumentLegacy, name: &str) {
+		document.handle_operation(Operation::SetLayerName { path: vec![1], name: name.into() }).unwrap();
+	}
+
+	fn name(document: &DocumentLegacy) -> Option<&str> {
+		document.layer(&[1]).unwrap().name.as_deref()
+	}
+
+	#[test]
+	fn undo_and_redo_steps() {
+		let (mut document, mut artboard, mut layer_metadata) = (DocumentLegacy::default(), ArtboardMessageHandler::default(), HashMap::new());
+		let mut history = DocumentHistory::default();
+
+		history.backup(document_save(&document, &layer_metadata), Coalescing::Never, "Create Folder".into());
+		document.handle_operation(Operation::CreateFolder { path: vec![1], insert_index: -1 }).unwrap();
+		layer_metadata.insert(vec![1], LayerMetadata::new(true));
+		history.backup(document_save(&document, &layer_metadata), Coalescing::Never, "Empty".into());
+		// A transaction which changes nothing doesn't become a step
+		history.backup(document_save(&document, &layer_metadata), Coalescing::Never, "Rename".into());
+		rename(&mut document, "Group");
+		let named = document.current_state_identifier();
+
+		assert!(history.undo(&mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(name(&document), None);
+		assert!(history.undo(&mut document, &mut artboard, &mut layer_metadata));
+		assert!(document.layer(&[1]).is_err());
+		assert!(layer_metadata.is_empty());
+		assert!(!history.undo(&mut document, &mut artboard, &mut layer_metadata));
+
+		assert!(history.redo(&mut document, &mut artboard, &mut layer_metadata));
+		assert!(history.redo(&mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(name(&document), Some("Group"));
+		assert_eq!(document.current_state_identifier(), named);
+		assert!(!history.redo(&mut document, &mut artboard, &mut layer_metadata));
+
+		let labels: Vec<_> = history.steps().into_iter().map(|(_, label, depth)| (label, depth)).collect();
+		assert_eq!(labels, [("Create Folder", 0), ("Rename", 0)]);
+	}
+
+	#[test]
+	fn undo_restores_the_working_color_space() {
+		let (mut document, mut artboard, mut layer_metadata) = (DocumentLegacy::default(), ArtboardMessageHandler::default(), HashMap::new());
+		let mut history = DocumentHistory::default();
+
+		history.backup(document_save(&document, &layer_metadata), Coalescing::Never, "Set Working Color Space".into());
+		document.working_color_space = ColorSpace::DisplayP3;
+		history.backup(document_save(&document, &layer_metadata), Coalescing::Never, String::new());
+
+		assert!(history.undo(&mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(document.working_color_space, ColorSpace::Srgb);
+		assert!(history.redo(&mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(document.working_color_space, ColorSpace::DisplayP3);
+	}
+
+	#[test]
+	fn editing_after_undo_keeps_a_branch() {
+		let (mut document, mut artboard, mut layer_metadata) = (DocumentLegacy::default(), ArtboardMessageHandler::default(), HashMap::new());
+		document.handle_operation(Operation::CreateFolder { path: vec![1], insert_index: -1 }).unwrap();
+		let mut history = DocumentHistory::default();
+
+		for name in ["A", "B"] {
+			history.backup(document_save(&document, &layer_metadata), Coalescing::Never, name.into());
+			rename(&mut document, name);
+		}
+		assert!(history.undo(&mut document, &mut artboard, &mut layer_metadata));
+		history.backup(document_save(&document, &layer_metadata), Coalescing::Never, "C".into());
+		rename(&mut document, "C");
+		history.backup(document_save(&document, &layer_metadata), Coalescing::Never, String::new());
+
+		let steps: Vec<_> = history.steps().into_iter().map(|(id, label, depth)| (id, label.to_string(), depth)).collect();
+		let labels: Vec<_> = steps.iter().map(|(_, label, depth)| (label.as_str(), *depth)).collect();
+		assert_eq!(labels, [("A", 0), ("B", 0), ("C", 1)]);
+
+		assert!(history.jump(Some(steps[1].0), &mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(name(&document), Some("B"));
+		assert!(history.jump(None, &mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(name(&document), None);
+
+		// Redoing follows the branch which was visited last
+		assert!(history.redo(&mut document, &mut artboard, &mut layer_metadata));
+		assert!(history.redo(&mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(name(&document), Some("B"));
+		assert!(history.jump(Some(steps[2].0), &mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(name(&document), Some("C"));
+		assert!(history.undo(&mut document, &mut artboard, &mut layer_metadata));
+		assert!(history.redo(&mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(name(&document), Some("C"));
+	}
+
+	#[test]
+	fn aborting_discards_the_transaction() {
+		let (mut document, mut artboard, mut layer_metadata) = (DocumentLegacy::default(), ArtboardMessageHandler::default(), HashMap::new());
+		document.handle_operation(Operation::CreateFolder { path: vec![1], insert_index: -1 }).unwrap();
+		let mut history = DocumentHistory::default();
+
+		history.backup(document_save(&document, &layer_metadata), Coalescing::Never, "A".into());
+		rename(&mut document, "A");
+		history.backup(document_save(&document, &layer_metadata), Coalescing::Never, "B".into());
+		rename(&mut document, "B");
+
+		assert!(history.abort(&mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(name(&document), Some("A"));
+		assert!(!history.abort(&mut document, &mut artboard, &mut layer_metadata));
+		assert!(!history.redo(&mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(history.steps().len(), 1);
+	}
+
+	#[test]
+	fn coalescing_transactions_merge() {
+		let (mut document, mut artboard, mut layer_metadata) = (DocumentLegacy::default(), ArtboardMessageHandler::default(), HashMap::new());
+		let node_id = *document.document_network.nodes.keys().next().unwrap();
+		let start = document.document_network.nodes[&node_id].metadata.position;
+		let mut history = DocumentHistory::default();
+
+		for x in 20..25 {
+			history.backup(document_save(&document, &layer_metadata), Coalescing::Continue, "Set Input Value".into());
+			document.document_network.nodes.get_mut(&node_id).unwrap().metadata.position.x = x;
+		}
+
+		assert!(history.undo(&mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(document.document_network.nodes[&node_id].metadata.position, start);
+		assert!(!history.undo(&mut document, &mut artboard, &mut layer_metadata));
+	}
+
+	#[test]
+	fn coalescing_starts_a_new_step() {
+		let (mut document, mut artboard, mut layer_metadata) = (DocumentLegacy::default(), ArtboardMessageHandler::default(), HashMap::new());
+		let node_id = *document.document_network.nodes.keys().next().unwrap();
+		let mut history = DocumentHistory::default();
+
+		// Two drags of the same number, each starting a step which the rest of the drag is merged into
+		for (x, coalescing) in [(20, Coalescing::Start), (21, Coalescing::Continue), (30, Coalescing::Start), (31, Coalescing::Continue)] {
+			history.backup(document_save(&document, &layer_metadata), coalescing, "Set Input Value".into());
+			document.document_network.nodes.get_mut(&node_id).unwrap().metadata.position.x = x;
+		}
+
+		assert!(history.undo(&mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(document.document_network.nodes[&node_id].metadata.position.x, 21);
+	}
+
+	#[test]
+	fn coalescing_keeps_the_branches_after_a_step() {
+		let (mut document, mut artboard, mut layer_metadata) = (DocumentLegacy::default(), ArtboardMessageHandler::default(), HashMap::new());
+		let node_id = *document.document_network.nodes.keys().next().unwrap();
+		let mut history = DocumentHistory::default();
+		let set_x = |document: &mut DocumentLegacy, x| document.document_network.nodes.get_mut(&node_id).unwrap().metadata.position.x = x;
+
+		history.backup(document_save(&document, &layer_metadata), Coalescing::Continue, "Set Input Value".into());
+		set_x(&mut document, 20);
+		history.backup(document_save(&document, &layer_metadata), Coalescing::Never, "Move".into());
+		set_x(&mut document, 30);
+		assert!(history.undo(&mut document, &mut artboard, &mut layer_metadata));
+
+		// The step which was undone back to already has a step after it, so the edit starts a branch instead of changing it
+		history.backup(document_save(&document, &layer_metadata), Coalescing::Continue, "Set Input Value".into());
+		set_x(&mut document, 21);
+		history.backup(document_save(&document, &layer_metadata), Coalescing::Never, String::new());
+		let steps: Vec<_> = history.steps().into_iter().map(|(id, _, depth)| (id, depth)).collect();
+		assert_eq!(steps.iter().map(|(_, depth)| *depth).collect::<Vec<_>>(), [0, 0, 1]);
+
+		assert!(history.jump(Some(steps[1].0), &mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(document.document_network.nodes[&node_id].metadata.position.x, 30);
+		assert!(history.undo(&mut document, &mut artboard, &mut layer_metadata));
+		assert_eq!(document.document_network.nodes[&node_id].metadata.position.x, 20);
+	}
+
+	/// Adds a step after the current one which is measured as the given size, then limits the history to its budget.
+	fn push_step(history: &mut DocumentHistory, size: usize) -> HistoryStepId {
+		let (document, artboard, layer_metadata) = (DocumentLegacy::default(), ArtboardMessageHandler::default(), HashMap::new());
+		let mut changes = StepChanges::between(&document, &artboard, &layer_metadata, &document, &artboard, &layer_metadata, false);
+		changes.size = size;
+
+		let id = history.next_id;
+		history.next_id += 1;
+		history.size += size;
+		let step = HistoryStep {
+			label: id.to_string(),
+			parent: history.current,
+			changes,
+			state_identifier: 0,
+			last_visit: 0,
+		};
+		history.steps.insert(id, step);
+		history.current = Some(id);
+		history.limit_size();
+		id
+	}
+
+	#[test]
+	fn oldest_steps_are_dropped_past_the_size_budget() {
+		let mut history = DocumentHistory::default();
+		let ids: Vec<_> = (0..3).map(|_| push_step(&mut history, MAX_UNDO_HISTORY_SIZE / 3)).collect();
+		assert_eq!(history.steps.keys().copied().collect::<Vec<_>>(), ids);
+
+		let last = push_step(&mut history, MAX_UNDO_HISTORY_SIZE / 3);
+		assert_eq!(history.steps.keys().copied().collect::<Vec<_>>(), [ids[1], ids[2], last]);
+		// The step after the dropped one now follows the oldest kept state
+		assert_eq!(history.steps[&ids[1]].parent, None);
+		assert!(history.size <= MAX_UNDO_HISTORY_SIZE);
+	}
+
+	#[test]
+	fn the_current_step_is_never_dropped() {
+		let mut history = DocumentHistory::default();
+		for _ in 0..3 {
+			let id = push_step(&mut history, MAX_UNDO_HISTORY_SIZE + 1);
+			assert_eq!(history.steps.keys().copied().collect::<Vec<_>>(), [id]);
+			assert_eq!(history.current, Some(id));
+		}
+
+		// Undoing can grow a step past the budget, since its changes in each direction can differ in size, and the branch after it is dropped first
+		let mut history = DocumentHistory::default();
+		let ids: Vec<_> = (0..3).map(|_| push_step(&mut history, MAX_UNDO_HISTORY_SIZE / 3)).collect();
+		history.current = Some(ids[0]);
+		history.steps.get_mut(&ids[0]).unwrap().changes.size += MAX_UNDO_HISTORY_SIZE;
+		history.size += MAX_UNDO_HISTORY_SIZE;
+		history.limit_size();
+		assert_eq!(history.steps.keys().copied().collect::<Vec<_>>(), [ids[0]]);
+		assert_eq!(history.current, Some(ids[0]));
+	}
+
+	#[test]
+	fn labels_name_the_message() {
+		assert_eq!(step_label("Portfolio.Document.DeleteSelectedLayers"), "Delete Selected Layers");
+		assert_eq!(step_label("Portfolio.Document.NodeGraph.SetInputValue"), "Set Input Value");
+		assert_eq!(step_label("Tool.Select.DragStop"), "Select Tool");
+		assert_eq!(step_label("Portfolio.Document.ImportSVGFile"), "Import SVG File");
+	}
+}
@@ -0,0 +1,201 @@
+use super::history::StepChanges;
+use super::layer_panel::LayerMetadata;
+use super::misc::DocumentSave;
+use crate::consts::MAX_JOURNAL_ENTRIES;
+use crate::messages::prelude::ArtboardMessageHandler;
+
+use document_legacy::document::Document as DocumentLegacy;
+use document_legacy::LayerId;
+
+use std::collections::HashMap;
+
+/// Records the changes of the transactions committed to a document since it was last saved in full, so that they can be replayed onto that save to recover the work lost in a crash.
+///
+/// Each entry holds the parts of the document it changes as they are afterwards, so replaying entries onto a state which already includes them leaves it as it is.
+/// A journal left behind by a save which was interrupted before the journal was truncated is therefore harmless.
+#[derive(Debug, Clone, Default)]
+pub struct DocumentJournal {
+	/// The state of the document as of the last entry, which the next entry holds the changes from, or `None` until the document has been saved in full.
+	base: Option<DocumentSave>,
+	/// The serialized entries which have been recorded but not yet written out.
+	entries: Vec<String>,
+	/// The number of entries recorded since the last full save, including those already written out.
+	recorded: usize,
+	/// Whether changes were made while the journal had no full save to record them from, or it has grown long enough to be replaced by one.
+	needs_full_save: bool,
+	/// Whether the full save has been requested already, so that it isn't requested again while it's on its way.
+	full_save_requested: bool,
+}
+
+impl DocumentJournal {
+	/// Starts the journal over from a state of the document which has been saved in full, dropping the entries which that save already includes.
+	pub fn restart(&mut self, save: DocumentSave) {
+		self.base = Some(save);
+		self.entries.clear();
+		self.recorded = 0;
+		self.needs_full_save = false;
+		self.full_save_requested = false;
+	}
+
+	/// Records an entry with the changes which turn the state of the last entry into the given state, if it differs.
+	///
+	/// Only the changed parts are copied, and the journal's own state is brought up to date by applying the entry to it once it has been serialized.
+	pub fn record(&mut self, document: &DocumentLegacy, artboard: &ArtboardMessageHandler, layer_metadata: &HashMap<Vec<LayerId>, LayerMetadata>) {
+		let Some(base) = &mut self.base else {
+			self.needs_full_save = !self.full_save_requested;
+			return;
+		};
+
+		let changes = StepChanges::between(&base.document, &base.artboard, &base.layer_metadata, document, artboard, layer_metadata, false);
+		if changes.is_empty() {
+			return;
+		}
+		match serde_json::to_string(&changes) {
+			Ok(entry) => self.entries.push(entry),
+			Err(error) => warn!("Failed to serialize a journal entry: {error}"),
+		}
+		changes.apply(&mut base.document, &mut base.artboard, &mut base.layer_metadata);
+		self.recorded += 1;
+		if self.recorded >= MAX_JOURNAL_ENTRIES {
+			self.needs_full_save = !self.full_save_requested;
+		}
+	}
+
+	/// Takes the entries recorded since this was last called, so that they can be written out.
+	pub fn take_entries(&mut self) -> Vec<String> {
+		std::mem::take(&mut self.entries)
+	}
+
+	/// Returns whether the document should be saved in full, either for the journal to have a state to record its changes from or to replace its entries, which is only requested once.
+	pub fn take_full_save_request(&mut self) -> bool {
+		let needs_full_save = std::mem::take(&mut self.needs_full_save);
+		self.full_save_requested |= needs_full_save;
+		needs_full_save
+	}
+
+	/// Replays the entries of a journal onto the state of the document it was started from, returning the number of entries which were applied.
+	///
+	/// Replaying stops at the first entry which can't be read, since the entries after it build on its changes.
+	pub fn replay(entries: &[String], document: &mut DocumentLegacy, artboard: &mut ArtboardMessageHandler, layer_metadata: &mut HashMap<Vec<LayerId>, LayerMetadata>) -> usize {
+		for (index, entry) in entries.iter().enumerate() {
+			match serde_json::from_str::<StepChanges>(entry) {
+				Ok(changes) => {
+					changes.apply(document, artboard, layer_metadata);
+				}
+				Err(error) => {
+					warn!("Failed to read entry {index} of the journal, so the entries from it onward are lost: {error}");
+					return index;
+				}
+			}
+		}
+		entries.len()
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::test_utils::document_save;
+	use document_legacy::Operation;
+
+	fn name(document: &DocumentLegacy, path: &[LayerId]) -> Option<String> {
+		document.layer(path).ok().and_then(|layer| layer.name.clone())
+	}
+
+	#[test]
+	fn replaying_recovers_the_changes_since_the_save() {
+		let (mut document, mut layer_metadata) = (DocumentLegacy::default(), HashMap::new());
+		document.handle_operation(Operation::CreateFolder { path: vec![1], insert_index: -1 }).unwrap();
+		let saved = document_save(&document, &layer_metadata);
+		let mut journal = DocumentJournal::default();
+		journal.restart(saved.clone());
+
+		document.handle_operation(Operation::CreateFolder { path: vec![2], insert_index: -1 }).unwrap();
+		layer_metadata.insert(vec![2], LayerMetadata::new(true));
+		journal.record(&document, &ArtboardMessageHandler::default(), &layer_metadata);
+		// A transaction which changes nothing isn't recorded
+		journal.record(&document, &ArtboardMessageHandler::default(), &layer_metadata);
+		document.handle_operation(Operation::SetLayerName { path: vec![2], name: "Group".into() }).unwrap();
+		document.handle_operation(Operation::DeleteLayer { path: vec![1] }).unwrap();
+		journal.record(&document, &ArtboardMessageHandler::default(), &layer_metadata);
+
+		let entries = journal.take_entries();
+		assert_eq!(entries.len(), 2);
+		assert!(journal.take_entries().is_empty());
+
+		let DocumentSave {
+			document: mut recovered,
+			mut artboard,
+			layer_metadata: mut recovered_metadata,
+		} = saved;
+		assert_eq!(DocumentJournal::replay(&entries, &mut recovered, &mut artboard, &mut recovered_metadata), 2);
+		assert!(recovered.layer(&[1]).is_err());
+		assert_eq!(name(&recovered, &[2]).as_deref(), Some("Group"));
+		assert_eq!(recovered.root.as_folder().unwrap().layer_ids, document.root.as_folder().unwrap().layer_ids);
+		assert_eq!(recovered_metadata, layer_metadata);
+
+		// Replaying the journal again onto the state which already includes it changes nothing
+		assert_eq!(DocumentJournal::replay(&entries, &mut recovered, &mut artboard, &mut recovered_metadata), 2);
+		assert_eq!(name(&recovered, &[2]).as_deref(), Some("Group"));
+		assert!(recovered.layer(&[1]).is_err());
+	}
+
+	#[test]
+	fn replaying_stops_at_an_unreadable_entry() {
+		let mut document = DocumentLegacy::default();
+		let saved = document_save(&document, &HashMap::new());
+		let mut journal = DocumentJournal::default();
+		journal.restart(saved.clone());
+
+		for id in [1, 2] {
+			document.handle_operation(Operation::CreateFolder { path: vec![id], insert_index: -1 }).unwrap();
+			journal.record(&document, &ArtboardMessageHandler::default(), &HashMap::new());
+		}
+		let mut entries = journal.take_entries();
+		let length = entries[1].len();
+		entries[1].truncate(length / 2);
+
+		let DocumentSave {
+			document: mut recovered,
+			mut artboard,
+			mut layer_metadata,
+		} = saved;
+		assert_eq!(DocumentJournal::replay(&entries, &mut recovered, &mut artboard, &mut layer_metadata), 1);
+		assert!(recovered.layer(&[1]).is_ok());
+		assert!(recovered.layer(&[2]).is_err());
+	}
+
+	#[test]
+	fn changes_before_a_full_save_request_one() {
+		let mut journal = DocumentJournal::default();
+		journal.record(&DocumentLegacy::default(), &ArtboardMessageHandler::default(), &HashMap::new());
+		assert!(journal.take_entries().is_empty());
+		assert!(journal.take_full_save_request());
+		journal.record(&DocumentLegacy::default(), &ArtboardMessageHandler::default(), &HashMap::new());
+		assert!(!journal.take_full_save_request());
+
+		journal.restart(document_save(&DocumentLegacy::default(), &HashMap::new()));
+		journal.record(&DocumentLegacy::default(), &ArtboardMessageHandler::default(), &HashMap::new());
+		assert!(!journal.take_full_save_request());
+	}
+
+	#[test]
+	fn long_journals_request_a_full_save() {
+		let mut document = DocumentLegacy::default();
+		let mut journal = DocumentJournal::default();
+		journal.restart(document_save(&document, &HashMap::new()));
+
+		for id in 0..MAX_JOURNAL_ENTRIES as LayerId {
+			assert!(!journal.take_full_save_request());
+			document.handle_operation(Operation::CreateFolder { path: vec![id], insert_index: -1 }).unwrap();
+			journal.record(&document, &ArtboardMessageHandler::default(), &HashMap::new());
+			journal.take_entries();
+		}
+		assert!(journal.take_full_save_request());
+
+		journal.restart(document_save(&document, &HashMap::new()));
+		document.handle_operation(Operation::DeleteLayer { path: vec![0] }).unwrap();
+		journal.record(&document, &ArtboardMessageHandler::default(), &HashMap::new());
+		assert!(!journal.take_full_save_request());
+	}
+}
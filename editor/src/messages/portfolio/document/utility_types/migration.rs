@@ -0,0 +1,246 @@
+//! Upgrades of the documents saved by older versions of the editor, for the nodes whose definitions have changed since.
+
+use crate::messages::portfolio::document::node_graph::resolve_document_node_type;
+
+use document_legacy::document::Document as DocumentLegacy;
+use document_legacy::layers::layer_info::LayerDataType;
+use graph_craft::document::value::TaggedValue;
+use graph_craft::document::{DocumentNode, DocumentNodeImplementation, NodeInput, NodeNetwork};
+use graphene_core::raster::FloodFill;
+
+/// The versions of the documents which [`upgrade_document`] brings up to date.
+pub const UPGRADABLE_DOCUMENT_VERSIONS: &[&str] = &["0.0.17", "0.0.18"];
+
+/// The identifier of the text node from before it had the typesetting inputs, which only took the text, font and size.
+const THREE_INPUT_TEXT_GENERATOR: &str = "graphene_core::text::TextGenerator<_, _, _>";
+/// The line height ratio of the text laid out before it could be set, which is kept for the text nodes of older documents.
+const THREE_INPUT_TEXT_LINE_HEIGHT: f64 = 1.;
+/// The identifier of the brush node from before the images of image tips were held by the node rather than by each stroke.
+const THREE_INPUT_BRUSH: &str = "graphene_std::brush::BrushNode<_, _, _>";
+/// The identifier of the flood fill node from before it held all the fills of the fill tool, which took the inputs of a single fill.
+const SINGLE_FLOOD_FILL: &str = "graphene_core::raster::FloodFillNode<_, _, _, _, _>";
+
+/// Upgrades the nodes of a document saved as one of the [`UPGRADABLE_DOCUMENT_VERSIONS`] to their current definitions, keeping how they were rendered.
+pub fn upgrade_document(document: &mut DocumentLegacy) {
+	upgrade_network(&mut document.document_network);
+	upgrade_layers(&mut document.root.data);
+}
+
+fn upgrade_layers(data: &mut LayerDataType) {
+	match data {
+		LayerDataType::Folder(folder) => folder.layers_mut().iter_mut().for_each(|layer| upgrade_layers(&mut layer.data)),
+		LayerDataType::Layer(layer) => upgrade_network(&mut layer.network),
+		LayerDataType::Shape(_) => {}
+	}
+}
+
+fn upgrade_network(network: &mut NodeNetwork) {
+	for node in network.nodes.values_mut() {
+		if let DocumentNodeImplementation::Network(nested) = &mut node.implementation {
+			upgrade_network(nested);
+		}
+		match node.name.as_str() {
+			"Text" => upgrade_text_node(node),
+			"Load Image" => upgrade_load_image_node(node),
+			"Brush" => upgrade_brush_node(node),
+			"Flood Fill" => upgrade_flood_fill_node(node),
+			_ => {}
+		}
+	}
+}
+
+/// Gives the text node the typesetting inputs it has gained since, set to their defaults apart from the line height, which is kept at what it used to be.
+fn upgrade_text_node(node: &mut DocumentNode) {
+	let DocumentNodeImplementation::Unresolved(identifier) = &node.implementation else { return };
+	if identifier.name != THREE_INPUT_TEXT_GENERATOR {
+		return;
+	}
+	let Some(definition) = resolve_document_node_type("Text") else { return };
+
+	node.implementation = definition.generate_implementation();
+	let new_inputs = definition.inputs.iter().skip(node.inputs.len()).map(|input| match input.name {
+		"Line Height" => NodeInput::value(TaggedValue::F64(THREE_INPUT_TEXT_LINE_HEIGHT), false),
+		_ => input.default.clone(),
+	});
+	node.inputs.extend(new_inputs);
+}
+
+/// Replaces the network of the image loading node from before it decoded images into the working color space, giving it the input this takes.
+///
+/// This also upgrades the older nodes which decoded the image as their output, from before load failures were passed through the graph.
+fn upgrade_load_image_node(node: &mut DocumentNode) {
+	let Some(definition) = resolve_document_node_type("Load Image") else { return };
+	if node.inputs.len() >= definition.inputs.len() {
+		return;
+	}
+
+	node.implementation = definition.generate_implementation();
+	let new_inputs = definition.inputs.iter().skip(node.inputs.len()).map(|input| input.default.clone());
+	node.inputs.extend(new_inputs);
+}
+
+/// Gives the brush node the input holding the images of its image tips, which starts out empty.
+fn upgrade_brush_node(node: &mut DocumentNode) {
+	let DocumentNodeImplementation::Unresolved(identifier) = &node.implementation else { return };
+	if identifier.name != THREE_INPUT_BRUSH {
+		return;
+	}
+	let Some(definition) = resolve_document_node_type("Brush") else { return };
+
+	node.implementation = definition.generate_implementation();
+	let new_inputs = definition.inputs.iter().skip(node.inputs.len()).map(|input| input.default.clone());
+	node.inputs.extend(new_inputs);
+}
+
+/// Replaces the inputs of the flood fill node from before it held all the fills of the fill tool with the list of fills, holding the one fill it did.
+fn upgrade_flood_fill_node(node: &mut DocumentNode) {
+	let DocumentNodeImplementation::Unresolved(identifier) = &node.implementation else { return };
+	if identifier.name != SINGLE_FLOOD_FILL {
+		return;
+	}
+	let Some(definition) = resolve_document_node_type("Flood Fill") else { return };
+
+	let value = |index: usize| match node.inputs.get(index) {
+		Some(NodeInput::Value { tagged_value, .. }) => Some(tagged_value.clone()),
+		_ => None,
+	};
+	let fill = match (value(1), value(2), value(3), value(4), value(5)) {
+		(Some(TaggedValue::DVec2(position)), Some(TaggedValue::Color(color)), Some(TaggedValue::F32(tolerance)), Some(TaggedValue::FloodFillMode(mode)), Some(TaggedValue::Bool(anti_alias))) => {
+			Some(FloodFill {
+				position,
+				color,
+				tolerance,
+				mode,
+				anti_alias,
+			})
+		}
+		_ => None,
+	};
+
+	node.implementation = definition.generate_implementation();
+	node.inputs.truncate(1);
+	node.inputs.push(NodeInput::value(TaggedValue::FloodFills(fill.into_iter().collect()), false));
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graph_craft::document::{NodeId, NodeOutput};
+	use graphene_core::raster::{Color, FloodFillMode};
+	use graphene_core::text::Font;
+
+	use glam::DVec2;
+
+	fn text_value(node: &DocumentNode, name: &str) -> Option<TaggedValue> {
+		let index = resolve_document_node_type("Text")?.inputs.iter().position(|input| input.name == name)?;
+		match &node.inputs[index] {
+			NodeInput::Value { tagged_value, .. } => Some(tagged_value.clone()),
+			_ => None,
+		}
+	}
+
+	#[test]
+	fn text_nodes_gain_the_typesetting_inputs() {
+		let definition = resolve_document_node_type("Text").unwrap();
+		let font = Font::new("Merriweather".into(), "Normal (400)".into());
+		let node = DocumentNode {
+			name: "Text".into(),
+			inputs: vec![
+				definition.inputs[0].default.clone(),
+				NodeInput::value(TaggedValue::String("Hello".into()), false),
+				NodeInput::value(TaggedValue::Font(font), false),
+				NodeInput::value(TaggedValue::F64(36.), false),
+			],
+			implementation: DocumentNodeImplementation::Unresolved(THREE_INPUT_TEXT_GENERATOR.into()),
+			..Default::default()
+		};
+		let mut network = NodeNetwork {
+			nodes: [(0 as NodeId, node)].into_iter().collect(),
+			..Default::default()
+		};
+
+		upgrade_network(&mut network);
+		let node = &network.nodes[&0];
+		assert_eq!(node.inputs.len(), definition.inputs.len());
+		assert_eq!(node.implementation, definition.generate_implementation());
+		assert_eq!(text_value(node, "Size"), Some(TaggedValue::F64(36.)));
+		assert_eq!(text_value(node, "Line Height"), Some(TaggedValue::F64(1.)));
+
+		// Upgrading again leaves the node as it is
+		let upgraded = node.clone();
+		upgrade_network(&mut network);
+		assert_eq!(network.nodes[&0], upgraded);
+	}
+
+	#[test]
+	fn brush_nodes_gain_the_tip_images() {
+		let definition = resolve_document_node_type("Brush").unwrap();
+		let mut node = DocumentNode {
+			name: "Brush".into(),
+			inputs: definition.inputs[..4].iter().map(|input| input.default.clone()).collect(),
+			implementation: DocumentNodeImplementation::Unresolved(THREE_INPUT_BRUSH.into()),
+			..Default::default()
+		};
+
+		upgrade_brush_node(&mut node);
+		assert_eq!(node.implementation, definition.generate_implementation());
+		assert_eq!(node.inputs.len(), definition.inputs.len());
+	}
+
+	#[test]
+	fn flood_fill_nodes_hold_their_fill_in_the_list() {
+		let definition = resolve_document_node_type("Flood Fill").unwrap();
+		let fill = FloodFill {
+			position: DVec2::new(4., 2.),
+			color: Color::WHITE,
+			tolerance: 20.,
+			mode: FloodFillMode::Global,
+			anti_alias: false,
+		};
+		let mut node = DocumentNode {
+			name: "Flood Fill".into(),
+			inputs: vec![
+				definition.inputs[0].default.clone(),
+				NodeInput::value(TaggedValue::DVec2(fill.position), false),
+				NodeInput::value(TaggedValue::Color(fill.color), false),
+				NodeInput::value(TaggedValue::F32(fill.tolerance), false),
+				NodeInput::value(TaggedValue::FloodFillMode(fill.mode), false),
+				NodeInput::value(TaggedValue::Bool(fill.anti_alias), false),
+			],
+			implementation: DocumentNodeImplementation::Unresolved(SINGLE_FLOOD_FILL.into()),
+			..Default::default()
+		};
+
+		upgrade_flood_fill_node(&mut node);
+		assert_eq!(node.implementation, definition.generate_implementation());
+		assert_eq!(node.inputs.len(), definition.inputs.len());
+		assert_eq!(node.inputs[1], NodeInput::value(TaggedValue::FloodFills(vec![fill]), false));
+	}
+
+	#[test]
+	fn image_loading_nodes_pass_load_failures_on() {
+		let definition = resolve_document_node_type("Load Image").unwrap();
+		let DocumentNodeImplementation::Network(mut network) = definition.generate_implementation() else {
+			panic!("Load Image should be a network")
+		};
+		// Before load failures were passed on, the decoded image was the output
+		network.nodes.retain(|_, node| matches!(node.name.as_str(), "Load Resource" | "Decode Image"));
+		let decode = *network.nodes.iter().find(|(_, node)| node.name == "Decode Image").unwrap().0;
+		network.outputs = vec![NodeOutput::new(decode, 0)];
+
+		let mut node = DocumentNode {
+			name: "Load Image".into(),
+			inputs: definition.inputs[..2].iter().map(|input| input.default.clone()).collect(),
+			implementation: DocumentNodeImplementation::Network(network),
+			..Default::default()
+		};
+		upgrade_load_image_node(&mut node);
+		assert_eq!(node.implementation, definition.generate_implementation());
+		assert_eq!(node.inputs.len(), definition.inputs.len());
+
+		// Upgrading again leaves the node as it is
+		let upgraded = node.clone();
+		upgrade_load_image_node(&mut node);
+		assert_eq!(node, upgraded);
+	}
+}
@@ -1,4 +1,5 @@
 pub use super::layer_panel::{LayerMetadata, LayerPanelEntry};
+use crate::messages::frontend::utility_types::FileType;
 use crate::messages::prelude::ArtboardMessageHandler;
 
 use document_legacy::document::Document as DocumentLegacy;
@@ -73,6 +74,96 @@ pub enum DocumentRenderMode<'a> {
 	Root,
 	OnlyBelowLayerInFolder(&'a [LayerId]),
 	LayerCutout(&'a [LayerId], Color),
+	/// Only the layer and its children, in their place in the document.
+	Layer(&'a [LayerId]),
+}
+
+/// The timing of the document's animation, which is rendered by evaluating the layers' node graphs at each frame's time.
+#[derive(PartialEq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub struct AnimationSettings {
+	pub frame_rate: f64,
+	/// The length of the animation in seconds.
+	pub duration: f64,
+}
+
+impl Default for AnimationSettings {
+	fn default() -> Self {
+		Self { frame_rate: 24., duration: 2. }
+	}
+}
+
+impl AnimationSettings {
+	pub fn frame_count(&self) -> u32 {
+		(self.duration * self.frame_rate).round().max(1.) as u32
+	}
+
+	/// The time in seconds at which a frame is shown.
+	pub fn frame_time(&self, frame: u32) -> f64 {
+		frame as f64 / self.frame_rate
+	}
+}
+
+/// A layer or artboard marked for export, whose region of the document is exported in each of its formats by the "Export All" action.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ExportSlice {
+	pub name: String,
+	pub layer_path: Vec<LayerId>,
+	pub document: TargetDocument,
+	pub formats: Vec<ExportFormat>,
+}
+
+impl ExportSlice {
+	/// Adds a format at the next whole scale factor, so the formats added to a new slice are 1x, 2x, 3x and so on.
+	pub fn add_format(&mut self) {
+		let file_type = self.formats.last().map_or(FileType::Png, |format| format.file_type);
+		let scale_factor = self.formats.iter().map(|format| format.scale_factor.floor()).fold(0., f64::max) + 1.;
+		self.formats.push(ExportFormat::new(file_type, scale_factor));
+	}
+}
+
+/// The names of the files exported for every format of the slices, in order, where repeated names are numbered so each file name is unique.
+pub fn export_file_names(slices: &[ExportSlice]) -> Vec<String> {
+	let mut names: Vec<String> = Vec::new();
+	for slice in slices {
+		for format in &slice.formats {
+			let stem = format!("{}{}", slice.name, format.suffix);
+			let extension = format.file_type.extension();
+			let mut name = format!("{stem}.{extension}");
+			let mut number = 2;
+			while names.contains(&name) {
+				name = format!("{stem} {number}.{extension}");
+				number += 1;
+			}
+			names.push(name);
+		}
+	}
+	names
+}
+
+/// One of the files exported for a slice, where the scale factor multiplies the slice's size in document pixels and the suffix is appended to its name.
+#[derive(PartialEq, Clone, Debug, Serialize, Deserialize)]
+pub struct ExportFormat {
+	pub file_type: FileType,
+	pub scale_factor: f64,
+	pub suffix: String,
+}
+
+impl ExportFormat {
+	/// The file types available to slices, which are the ones holding a single image.
+	pub const FILE_TYPES: [(FileType, &'static str); 6] = [
+		(FileType::Png, "PNG"),
+		(FileType::Jpg, "JPG"),
+		(FileType::Svg, "SVG"),
+		(FileType::Pdf, "PDF"),
+		(FileType::Tiff, "TIFF"),
+		(FileType::Exr, "EXR"),
+	];
+
+	/// A format with the conventional suffix for its scale factor, such as `@2x`, and no suffix at 1x.
+	pub fn new(file_type: FileType, scale_factor: f64) -> Self {
+		let suffix = if scale_factor == 1. { String::new() } else { format!("@{scale_factor}x") };
+		Self { file_type, scale_factor, suffix }
+	}
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -107,3 +198,30 @@ impl fmt::Display for SnappingOptions {
 		}
 	}
 }
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn export_slice_formats() {
+		let mut slice = ExportSlice {
+			name: "icon".to_string(),
+			layer_path: vec![1],
+			document: TargetDocument::Artwork,
+			formats: Vec::new(),
+		};
+		(0..3).for_each(|_| slice.add_format());
+		slice.formats.push(ExportFormat::new(FileType::Svg, 1.));
+		slice.formats.push(ExportFormat::new(FileType::Svg, 1.));
+		let other = ExportSlice {
+			layer_path: vec![2],
+			formats: vec![ExportFormat::new(FileType::Png, 2.)],
+			..slice.clone()
+		};
+		assert_eq!(
+			export_file_names(&[slice, other]),
+			["icon.png", "icon@2x.png", "icon@3x.png", "icon.svg", "icon 2.svg", "icon@2x 2.png"]
+		);
+	}
+}
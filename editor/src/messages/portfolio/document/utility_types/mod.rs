@@ -1,6 +1,10 @@
 pub mod clipboards;
 pub mod error;
+pub mod history;
+pub mod journal;
 pub mod layer_panel;
+pub mod migration;
 pub mod misc;
+pub mod svg_import;
 pub mod transformation;
 pub mod vectorize_layer_metadata;
@@ -0,0 +1,425 @@
+//! Conversion of SVG files into the layers of a document.
+//!
+//! The file is parsed by `usvg`, which resolves CSS, `use` references, basic shapes and relative units into a tree of groups, paths and images.
+//! That tree is then mapped onto folders, vector layers and image layers so every path can be edited with the Path tool after importing.
+
+use bezier_rs::{ManipulatorGroup, Subpath};
+use document_legacy::layers::blend_mode::BlendMode;
+use graphene_core::raster::{Color, ColorSpace, Image};
+use graphene_core::uuid::ManipulatorGroupId;
+use graphene_core::vector::style::{Fill, Gradient, GradientType, LineCap, LineJoin, Stroke};
+use graphene_core::vector::VectorData;
+
+use glam::{DAffine2, DVec2};
+use usvg::{NodeKind, TreeParsing};
+
+/// An element of an imported SVG file, in the form it will be added to the document.
+#[derive(Clone, Debug)]
+pub enum SvgElement {
+	/// Becomes a folder containing the children.
+	Group {
+		name: Option<String>,
+		transform: DAffine2,
+		blend_mode: BlendMode,
+		opacity: f64,
+		children: Vec<SvgElement>,
+	},
+	/// Becomes a vector layer. The subpaths are already in the space of the parent group so the layer itself is untransformed.
+	Vector {
+		name: Option<String>,
+		subpaths: Vec<Subpath<ManipulatorGroupId>>,
+		fill: Fill,
+		stroke: Option<Stroke>,
+	},
+	/// Becomes an image layer, where the transform maps the unit square onto the area covered by the image.
+	Image { name: Option<String>, transform: DAffine2, image: Image<Color> },
+}
+
+/// A parsed SVG file with the size it declares for itself.
+#[derive(Clone, Debug)]
+pub struct SvgImport {
+	pub size: DVec2,
+	/// A group holding the contents of the file, transformed from the view box to the declared size.
+	pub root: SvgElement,
+}
+
+/// Parses the source of an SVG file into the elements that can be added to a document, decoding embedded images into the given working space.
+pub fn parse_svg(svg: &str, color_space: ColorSpace) -> Result<SvgImport, String> {
+	let tree = usvg::Tree::from_str(svg, &usvg::Options::default()).map_err(|error| error.to_string())?;
+
+	let size = DVec2::new(tree.size.width() as f64, tree.size.height() as f64);
+	let transform = to_daffine2(usvg::utils::view_box_to_transform(tree.view_box.rect, tree.view_box.aspect, tree.size));
+	let mut children = Vec::new();
+	tree.root.children().for_each(|node| convert_node(&node, DAffine2::IDENTITY, color_space, &mut children));
+
+	let root = SvgElement::Group {
+		name: None,
+		transform,
+		blend_mode: BlendMode::Normal,
+		opacity: 1.,
+		children,
+	};
+	Ok(SvgImport { size, root })
+}
+
+/// Converts a node of the `usvg` tree and appends the result to `elements`, where `parent_transform` is the transform between the node and the closest enclosing group.
+fn convert_node(node: &usvg::Node, parent_transform: DAffine2, color_space: ColorSpace, elements: &mut Vec<SvgElement>) {
+	let name = |id: &str| (!id.is_empty()).then(|| id.to_string());
+
+	let element = match &*node.borrow() {
+		NodeKind::Group(group) => {
+			let transform = parent_transform * to_daffine2(group.transform);
+
+			// Unnamed groups which don't change how their children are composited, such as those `usvg` creates for transformed elements, are flattened into the parent
+			if group.id.is_empty() && group.opacity.get() >= 1. && group.blend_mode == usvg::BlendMode::Normal {
+				node.children().for_each(|child| convert_node(&child, transform, color_space, elements));
+				return;
+			}
+
+			let mut children = Vec::new();
+			node.children().for_each(|child| convert_node(&child, DAffine2::IDENTITY, color_space, &mut children));
+			if children.is_empty() {
+				return;
+			}
+			SvgElement::Group {
+				name: name(&group.id),
+				transform,
+				blend_mode: blend_mode(group.blend_mode),
+				opacity: group.opacity.get() as f64,
+				children,
+			}
+		}
+		NodeKind::Path(path) => {
+			if path.visibility != usvg::Visibility::Visible {
+				return;
+			}
+
+			let transform = parent_transform * to_daffine2(path.transform);
+			let local_subpaths = subpaths(&path.data);
+			let Some(local_bounds) = VectorData::from_subpaths(local_subpaths.clone()).bounding_box() else {
+				return;
+			};
+
+			let mut subpaths = local_subpaths;
+			subpaths.iter_mut().for_each(|subpath| subpath.apply_transform(transform));
+			let Some(bounds) = VectorData::from_subpaths(subpaths.clone()).bounding_box() else { return };
+
+			let fill = path.fill.as_ref().map_or(Fill::None, |fill| paint(&fill.paint, fill.opacity.get(), transform, local_bounds, bounds));
+			let stroke = path.stroke.as_ref().map(|stroke| {
+				// Stroke widths are specified in the path's own space, but layers draw them in the space of their parent
+				let scale = transform.matrix2.determinant().abs().sqrt();
+				let color = match paint(&stroke.paint, stroke.opacity.get(), transform, local_bounds, bounds) {
+					Fill::Solid(color) => Some(color),
+					// Strokes only support solid colors, so gradients are approximated by their first stop
+					Fill::Gradient(gradient) => gradient.positions.first().and_then(|(_, color)| *color),
+					Fill::None => None,
+				};
+				Stroke {
+					color,
+					weight: stroke.width.get() as f64 * scale,
+					dash_lengths: stroke.dasharray.iter().flatten().map(|length| length * scale as f32).collect(),
+					dash_offset: stroke.dashoffset as f64 * scale,
+					line_cap: match stroke.linecap {
+						usvg::LineCap::Butt => LineCap::Butt,
+						usvg::LineCap::Round => LineCap::Round,
+						usvg::LineCap::Square => LineCap::Square,
+					},
+					line_join: match stroke.linejoin {
+						usvg::LineJoin::Miter | usvg::LineJoin::MiterClip => LineJoin::Miter,
+						usvg::LineJoin::Round => LineJoin::Round,
+						usvg::LineJoin::Bevel => LineJoin::Bevel,
+					},
+					line_join_miter_limit: stroke.miterlimit.get() as f64,
+				}
+			});
+
+			SvgElement::Vector {
+				name: name(&path.id),
+				subpaths,
+				fill,
+				stroke,
+			}
+		}
+		NodeKind::Image(image) => {
+			if image.visibility != usvg::Visibility::Visible {
+				return;
+			}
+
+			let data = match &image.kind {
+				usvg::ImageKind::PNG(data) | usvg::ImageKind::JPEG(data) | usvg::ImageKind::GIF(data) => data,
+				// Nested SVG documents would need to be imported as a whole separate tree
+				usvg::ImageKind::SVG(_) => return,
+			};
+			let Ok(decoded) = graphene_std::export::decode_image(data, color_space) else { return };
+			let image_size = DVec2::new(decoded.width as f64, decoded.height as f64);
+
+			let transform = parent_transform * to_daffine2(image.transform) * fit_image(image.view_box, image_size);
+			SvgElement::Image {
+				name: name(&image.id),
+				transform,
+				image: decoded,
+			}
+		}
+		// Text isn't converted into paths without the fonts being available
+		NodeKind::Text(_) => return,
+	};
+	elements.push(element);
+}
+
+fn to_daffine2(transform: usvg::Transform) -> DAffine2 {
+	let usvg::Transform { sx, ky, kx, sy, tx, ty } = transform;
+	DAffine2::from_cols_array(&[sx, ky, kx, sy, tx, ty].map(|value| value as f64))
+}
+
+fn to_dvec2(point: usvg::tiny_skia_path::Point) -> DVec2 {
+	DVec2::new(point.x as f64, point.y as f64)
+}
+
+fn to_color(color: usvg::Color, opacity: f32) -> Color {
+	Color::from_rgbaf32_unchecked(color.red as f32 / 255., color.green as f32 / 255., color.blue as f32 / 255., opacity)
+}
+
+fn blend_mode(blend_mode: usvg::BlendMode) -> BlendMode {
+	match blend_mode {
+		usvg::BlendMode::Normal => BlendMode::Normal,
+		usvg::BlendMode::Multiply => BlendMode::Multiply,
+		usvg::BlendMode::Screen => BlendMode::Screen,
+		usvg::BlendMode::Overlay => BlendMode::Overlay,
+		usvg::BlendMode::Darken => BlendMode::Darken,
+		usvg::BlendMode::Lighten => BlendMode::Lighten,
+		usvg::BlendMode::ColorDodge => BlendMode::ColorDodge,
+		usvg::BlendMode::ColorBurn => BlendMode::ColorBurn,
+		usvg::BlendMode::HardLight => BlendMode::HardLight,
+		usvg::BlendMode::SoftLight => BlendMode::SoftLight,
+		usvg::BlendMode::Difference => BlendMode::Difference,
+		usvg::BlendMode::Exclusion => BlendMode::Exclusion,
+		usvg::BlendMode::Hue => BlendMode::Hue,
+		usvg::BlendMode::Saturation => BlendMode::Saturation,
+		usvg::BlendMode::Color => BlendMode::Color,
+		usvg::BlendMode::Luminosity => BlendMode::Luminosity,
+	}
+}
+
+/// Splits the path data into subpaths, converting quadratic segments into cubic ones so their handles can be edited like any other.
+fn subpaths(path: &usvg::tiny_skia_path::Path) -> Vec<Subpath<ManipulatorGroupId>> {
+	use usvg::tiny_skia_path::PathSegment;
+
+	let mut subpaths = Vec::new();
+	let mut groups: Vec<ManipulatorGroup<ManipulatorGroupId>> = Vec::new();
+	let mut start = DVec2::ZERO;
+
+	let finish = |groups: &mut Vec<ManipulatorGroup<ManipulatorGroupId>>, subpaths: &mut Vec<Subpath<ManipulatorGroupId>>, closed: bool| {
+		if groups.is_empty() {
+			return;
+		}
+		let mut groups = std::mem::take(groups);
+
+		// A closing segment back to the start is implied by the subpath being closed, so a duplicate end point is merged into the first one
+		if closed && groups.len() > 1 && groups.last().unwrap().anchor.abs_diff_eq(groups[0].anchor, 1e-6) {
+			let last = groups.pop().unwrap();
+			groups[0].in_handle = last.in_handle;
+		}
+		let closed = closed && groups.len() > 1;
+		subpaths.push(Subpath::new(groups, closed));
+	};
+
+	for segment in path.segments() {
+		// Drawing without a move after closing a subpath continues from its start
+		if !matches!(segment, PathSegment::MoveTo(_) | PathSegment::Close) && groups.is_empty() {
+			groups.push(ManipulatorGroup::new_anchor(start));
+		}
+
+		match segment {
+			PathSegment::MoveTo(point) => {
+				finish(&mut groups, &mut subpaths, false);
+				start = to_dvec2(point);
+				groups.push(ManipulatorGroup::new_anchor(start));
+			}
+			PathSegment::LineTo(point) => groups.push(ManipulatorGroup::new_anchor(to_dvec2(point))),
+			PathSegment::QuadTo(handle, point) => {
+				let (handle, point) = (to_dvec2(handle), to_dvec2(point));
+				let previous = groups.last_mut().unwrap();
+				previous.out_handle = Some(previous.anchor + (handle - previous.anchor) * (2. / 3.));
+				groups.push(ManipulatorGroup::new(point, Some(point + (handle - point) * (2. / 3.)), None));
+			}
+			PathSegment::CubicTo(handle_start, handle_end, point) => {
+				groups.last_mut().unwrap().out_handle = Some(to_dvec2(handle_start));
+				groups.push(ManipulatorGroup::new(to_dvec2(point), Some(to_dvec2(handle_end)), None));
+			}
+			PathSegment::Close => finish(&mut groups, &mut subpaths, true),
+		}
+	}
+	finish(&mut groups, &mut subpaths, false);
+
+	subpaths
+}
+
+/// Converts an SVG paint into a fill. Gradient coordinates are expressed relative to the bounding box of the layer like the gradient tool does.
+fn paint(paint: &usvg::Paint, opacity: f32, transform: DAffine2, local_bounds: [DVec2; 2], bounds: [DVec2; 2]) -> Fill {
+	let (base, gradient_type, start, end) = match paint {
+		usvg::Paint::Color(color) => return Fill::Solid(to_color(*color, opacity)),
+		usvg::Paint::LinearGradient(gradient) => (
+			&gradient.base,
+			GradientType::Linear,
+			DVec2::new(gradient.x1 as f64, gradient.y1 as f64),
+			DVec2::new(gradient.x2 as f64, gradient.y2 as f64),
+		),
+		usvg::Paint::RadialGradient(gradient) => {
+			let center = DVec2::new(gradient.cx as f64, gradient.cy as f64);
+			(&gradient.base, GradientType::Radial, center, center + DVec2::X * gradient.r.get() as f64)
+		}
+		// Patterns have no equivalent, so they are left unfilled
+		usvg::Paint::Pattern(_) => return Fill::None,
+	};
+
+	// Map the gradient's own space into the space of the layer's subpaths
+	let units = match base.units {
+		usvg::Units::UserSpaceOnUse => DAffine2::IDENTITY,
+		usvg::Units::ObjectBoundingBox => DAffine2::from_scale_angle_translation(local_bounds[1] - local_bounds[0], 0., local_bounds[0]),
+	};
+	let to_layer = transform * units * to_daffine2(base.transform);
+	let normalize = |point: DVec2| (to_layer.transform_point2(point) - bounds[0]) / (bounds[1] - bounds[0]).max(DVec2::splat(f64::EPSILON));
+
+	let positions = base
+		.stops
+		.iter()
+		.map(|stop| (stop.offset.get() as f64, Some(to_color(stop.color, stop.opacity.get() * opacity))))
+		.collect();
+	Fill::Gradient(Gradient {
+		start: normalize(start),
+		end: normalize(end),
+		transform: DAffine2::IDENTITY,
+		positions,
+		gradient_type,
+	})
+}
+
+/// The transform mapping the unit square onto the part of the view box an image of the given size covers according to its `preserveAspectRatio`.
+fn fit_image(view_box: usvg::ViewBox, image_size: DVec2) -> DAffine2 {
+	use usvg::Align;
+
+	let rect = view_box.rect;
+	let (position, size) = (DVec2::new(rect.x() as f64, rect.y() as f64), DVec2::new(rect.width() as f64, rect.height() as f64));
+	if view_box.aspect.align == Align::None {
+		return DAffine2::from_scale_angle_translation(size, 0., position);
+	}
+
+	let scales = size / image_size.max(DVec2::ONE);
+	let scale = if view_box.aspect.slice { scales.max_element() } else { scales.min_element() };
+	let fitted_size = image_size * scale;
+
+	let alignment = match view_box.aspect.align {
+		Align::None | Align::XMinYMin => DVec2::new(0., 0.),
+		Align::XMidYMin => DVec2::new(0.5, 0.),
+		Align::XMaxYMin => DVec2::new(1., 0.),
+		Align::XMinYMid => DVec2::new(0., 0.5),
+		Align::XMidYMid => DVec2::new(0.5, 0.5),
+		Align::XMaxYMid => DVec2::new(1., 0.5),
+		Align::XMinYMax => DVec2::new(0., 1.),
+		Align::XMidYMax => DVec2::new(0.5, 1.),
+		Align::XMaxYMax => DVec2::new(1., 1.),
+	};
+	DAffine2::from_scale_angle_translation(fitted_size, 0., position + (size - fitted_size) * alignment)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn root_children(svg: &str) -> Vec<SvgElement> {
+		let SvgElement::Group { children, .. } = parse_svg(svg, ColorSpace::Srgb).unwrap().root else {
+			panic!("The root should be a group")
+		};
+		children
+	}
+
+	#[test]
+	fn rectangle_becomes_closed_subpath() {
+		let children = root_children(r##"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="50"><rect x="10" y="20" width="30" height="40" fill="#ff0000" /></svg>"##);
+
+		let [SvgElement::Vector { subpaths, fill, stroke, .. }] = children.as_slice() else {
+			panic!("Expected a single vector element")
+		};
+		assert_eq!(subpaths.len(), 1);
+		assert!(subpaths[0].closed());
+		assert_eq!(subpaths[0].len(), 4);
+		assert_eq!(VectorData::from_subpaths(subpaths.clone()).bounding_box(), Some([DVec2::new(10., 20.), DVec2::new(40., 60.)]));
+		assert_eq!(fill, &Fill::Solid(Color::from_rgbaf32_unchecked(1., 0., 0., 1.)));
+		assert!(stroke.is_none());
+	}
+
+	#[test]
+	fn view_box_scales_the_root() {
+		let import = parse_svg(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" width="200" height="100" viewBox="0 0 20 10"><path d="M0 0 L20 10" stroke="black" /></svg>"#,
+			ColorSpace::Srgb,
+		)
+		.unwrap();
+		assert_eq!(import.size, DVec2::new(200., 100.));
+
+		let SvgElement::Group { transform, .. } = import.root else { panic!("The root should be a group") };
+		assert!(transform.abs_diff_eq(DAffine2::from_scale(DVec2::splat(10.)), 1e-9));
+	}
+
+	#[test]
+	fn transforms_are_applied_to_paths_and_strokes() {
+		let children = root_children(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100"><g transform="translate(10 0)"><path d="M0 0 H10" stroke="black" stroke-width="2" transform="scale(3)" /></g></svg>"#,
+		);
+
+		let [SvgElement::Vector { subpaths, stroke, .. }] = children.as_slice() else {
+			panic!("Expected the group to be flattened into its only path")
+		};
+		let anchors: Vec<_> = subpaths[0].manipulator_groups().iter().map(|group| group.anchor).collect();
+		assert_eq!(anchors, vec![DVec2::new(10., 0.), DVec2::new(40., 0.)]);
+		assert!((stroke.as_ref().unwrap().weight - 6.).abs() < 1e-6);
+	}
+
+	#[test]
+	fn groups_with_opacity_become_folders() {
+		let children = root_children(
+			r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100"><g opacity="0.5" style="mix-blend-mode: multiply"><circle cx="5" cy="5" r="5" /><circle cx="20" cy="5" r="5" /></g></svg>"#,
+		);
+
+		let [SvgElement::Group { opacity, blend_mode, children, .. }] = children.as_slice() else {
+			panic!("Expected a group")
+		};
+		assert_eq!(*opacity, 0.5);
+		assert_eq!(*blend_mode, BlendMode::Multiply);
+		assert_eq!(children.len(), 2);
+	}
+
+	#[test]
+	fn quadratic_curves_become_cubic() {
+		let children = root_children(r#"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100"><path d="M0 0 Q30 30 60 0" stroke="black" fill="none" /></svg>"#);
+
+		let [SvgElement::Vector { subpaths, .. }] = children.as_slice() else {
+			panic!("Expected a single vector element")
+		};
+		let groups = subpaths[0].manipulator_groups();
+		assert_eq!(groups[0].out_handle, Some(DVec2::new(20., 20.)));
+		assert_eq!(groups[1].in_handle, Some(DVec2::new(40., 20.)));
+	}
+
+	#[test]
+	fn linear_gradient_is_relative_to_bounds() {
+		let children = root_children(
+			r##"<svg xmlns="http://www.w3.org/2000/svg" width="100" height="100">
+				<linearGradient id="gradient" x1="10" y1="0" x2="30" y2="0" gradientUnits="userSpaceOnUse"><stop offset="0" stop-color="#000" /><stop offset="1" stop-color="#fff" /></linearGradient>
+				<rect x="10" y="10" width="20" height="20" fill="url(#gradient)" />
+			</svg>"##,
+		);
+
+		let [SvgElement::Vector { fill: Fill::Gradient(gradient), .. }] = children.as_slice() else {
+			panic!("Expected a gradient fill")
+		};
+		assert!(gradient.start.abs_diff_eq(DVec2::new(0., -0.5), 1e-6));
+		assert!(gradient.end.abs_diff_eq(DVec2::new(1., -0.5), 1e-6));
+		assert_eq!(gradient.positions.len(), 2);
+	}
+
+	#[test]
+	fn invalid_file_is_an_error() {
+		assert!(parse_svg("not an svg", ColorSpace::Srgb).is_err());
+	}
+}
@@ -1,6 +1,6 @@
 use crate::messages::input_mapper::utility_types::macros::action_keys;
 use crate::messages::layout::utility_types::widget_prelude::*;
-use crate::messages::portfolio::document::utility_types::clipboards::Clipboard;
+use crate::messages::portfolio::document::utility_types::clipboards::{Clipboard, PasteMode};
 use crate::messages::prelude::*;
 
 #[derive(Debug, Clone, Default)]
@@ -98,6 +98,12 @@ impl LayoutHolder for MenuBarMessageHandler {
 							disabled: no_active_document,
 							..MenuBarEntry::default()
 						},
+						MenuBarEntry {
+							label: "Export All Slices".into(),
+							action: MenuBarEntry::create_action(|_| DocumentMessage::ExportAllSlices.into()),
+							disabled: no_active_document,
+							..MenuBarEntry::default()
+						},
 					],
 					vec![MenuBarEntry {
 						label: "Preferences…".into(),
@@ -148,7 +154,21 @@ impl LayoutHolder for MenuBarMessageHandler {
 							label: "Paste".into(),
 							icon: Some("Paste".into()),
 							shortcut: action_keys!(FrontendMessageDiscriminant::TriggerPaste),
-							action: MenuBarEntry::create_action(|_| FrontendMessage::TriggerPaste.into()),
+							action: MenuBarEntry::create_action(|_| FrontendMessage::TriggerPaste { mode: PasteMode::All }.into()),
+							disabled: no_active_document,
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Paste Style Only".into(),
+							shortcut: action_keys!(PortfolioMessageDiscriminant::PasteStyle),
+							action: MenuBarEntry::create_action(|_| PortfolioMessage::PasteStyle.into()),
+							disabled: no_active_document,
+							..MenuBarEntry::default()
+						},
+						MenuBarEntry {
+							label: "Paste Transform Only".into(),
+							shortcut: action_keys!(PortfolioMessageDiscriminant::PasteTransform),
+							action: MenuBarEntry::create_action(|_| PortfolioMessage::PasteTransform.into()),
 							disabled: no_active_document,
 							..MenuBarEntry::default()
 						},
@@ -1,4 +1,4 @@
-use crate::messages::portfolio::document::utility_types::clipboards::Clipboard;
+use crate::messages::portfolio::document::utility_types::clipboards::{Clipboard, PasteMode};
 use crate::messages::prelude::*;
 
 use document_legacy::LayerId;
@@ -69,6 +69,7 @@ pub enum PortfolioMessage {
 	LoadDocumentResources {
 		document_id: u64,
 	},
+	LoadFallbackFonts,
 	LoadFont {
 		font: Font,
 		is_default: bool,
@@ -88,6 +89,8 @@ pub enum PortfolioMessage {
 		document_is_auto_saved: bool,
 		document_is_saved: bool,
 		document_serialized_content: String,
+		/// The entries of the document's crash-recovery journal, which are replayed onto the auto-saved content.
+		document_journal: Vec<String>,
 	},
 	// TODO: Paste message is unused, delete it?
 	Paste {
@@ -100,7 +103,10 @@ pub enum PortfolioMessage {
 	},
 	PasteSerializedData {
 		data: String,
+		mode: PasteMode,
 	},
+	PasteStyle,
+	PasteTransform,
 	PrevDocument,
 	RenderGraphUsingRasterizedRegionBelowLayer {
 		document_id: u64,
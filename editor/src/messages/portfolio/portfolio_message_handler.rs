@@ -5,13 +5,13 @@ use crate::messages::dialog::simple_dialogs;
 use crate::messages::frontend::utility_types::FrontendDocumentDetails;
 use crate::messages::input_mapper::utility_types::macros::action_keys;
 use crate::messages::layout::utility_types::widget_prelude::*;
-use crate::messages::portfolio::document::utility_types::clipboards::{Clipboard, CopyBufferEntry, INTERNAL_CLIPBOARD_COUNT};
+use crate::messages::portfolio::document::utility_types::clipboards::{Clipboard, ClipboardContent, CopyBufferEntry, LayerStyle, PasteMode, INTERNAL_CLIPBOARD_COUNT};
 use crate::messages::prelude::*;
 use crate::messages::tool::utility_types::{HintData, HintGroup};
 use crate::node_graph_executor::NodeGraphExecutor;
 
 use document_legacy::layers::layer_info::LayerDataType;
-use document_legacy::layers::style::RenderData;
+use document_legacy::layers::style::{RenderData, ViewMode};
 use document_legacy::Operation as DocumentOperation;
 use graph_craft::document::value::TaggedValue;
 use graph_craft::document::{NodeId, NodeInput};
@@ -28,7 +28,9 @@ pub struct PortfolioMessageHandler {
 	active_document_id: Option<u64>,
 	graph_view_overlay_open: bool,
 	graph_view_overlay_toggle_disabled: bool,
-	copy_buffer: [Vec<CopyBufferEntry>; INTERNAL_CLIPBOARD_COUNT as usize],
+	copy_buffer: [ClipboardContent; INTERNAL_CLIPBOARD_COUNT as usize],
+	/// The fallback fonts which have been asked for, so that they aren't asked for again while they load.
+	requested_fallback_fonts: HashSet<Font>,
 	pub persistent_data: PersistentData,
 	pub executor: NodeGraphExecutor,
 }
@@ -68,7 +70,9 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 				}
 			}
 			PortfolioMessage::AutoSaveDocument { document_id } => {
-				let document = self.documents.get(&document_id).unwrap();
+				let document = self.documents.get_mut(&document_id).unwrap();
+				// The full save includes the changes journaled so far, so the frontend truncates the document's journal when writing it
+				document.restart_journal();
 				responses.add(FrontendMessage::TriggerIndexedDbWriteDocument {
 					document: document.serialize_document(),
 					details: FrontendDocumentDetails {
@@ -148,29 +152,28 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 			}
 			PortfolioMessage::Copy { clipboard } => {
 				// We can't use `self.active_document()` because it counts as an immutable borrow of the entirety of `self`
-				if let Some(active_document) = self.active_document_id.and_then(|id| self.documents.get(&id)) {
-					let copy_val = |buffer: &mut Vec<CopyBufferEntry>| {
-						for layer_path in active_document.selected_layers_without_children() {
-							match (active_document.document_legacy.layer(layer_path).map(|t| t.clone()), *active_document.layer_metadata(layer_path)) {
-								(Ok(layer), layer_metadata) => {
-									buffer.push(CopyBufferEntry { layer, layer_metadata });
-								}
-								(Err(e), _) => warn!("Could not access selected layer {:?}: {:?}", layer_path, e),
+				if let Some(active_document) = self.active_document_id.and_then(|id| self.documents.get_mut(&id)) {
+					let mut layers = Vec::new();
+					for layer_path in active_document.selected_layers_without_children() {
+						match (active_document.document_legacy.layer(layer_path).map(|t| t.clone()), *active_document.layer_metadata(layer_path)) {
+							(Ok(layer), layer_metadata) => {
+								layers.push(CopyBufferEntry { layer, layer_metadata });
 							}
+							(Err(e), _) => warn!("Could not access selected layer {:?}: {:?}", layer_path, e),
 						}
-					};
+					}
+					let content = ClipboardContent::from_layers(layers);
 
 					if clipboard == Clipboard::Device {
-						let mut buffer = Vec::new();
-						copy_val(&mut buffer);
-						let mut copy_text = String::from("graphite/layer: ");
-						copy_text += &serde_json::to_string(&buffer).expect("Could not serialize paste");
+						let render_data = RenderData::new(&self.persistent_data.font_cache, ViewMode::Normal, None);
+						let svg = active_document.render_selected_layers(&render_data);
 
-						responses.add(FrontendMessage::TriggerTextCopy { copy_text });
+						responses.add(FrontendMessage::TriggerClipboardWrite {
+							content: content.to_clipboard_text(),
+							svg,
+						});
 					} else {
-						let copy_buffer = &mut self.copy_buffer;
-						copy_buffer[clipboard as usize].clear();
-						copy_val(&mut copy_buffer[clipboard as usize]);
+						self.copy_buffer[clipboard as usize] = content;
 					}
 				}
 			}
@@ -182,6 +185,7 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 				let document_index = self.document_index(document_id);
 				self.documents.remove(&document_id);
 				self.document_ids.remove(document_index);
+				self.executor.remove_document_images(document_id);
 
 				if self.document_ids.is_empty() {
 					self.active_document_id = None;
@@ -221,6 +225,9 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 
 				self.persistent_data.font_cache.insert(font, preview_url, data, is_default);
 				self.executor.update_font_cache(self.persistent_data.font_cache.clone());
+
+				// The text in the newly loaded font may have characters which it has no glyphs for
+				responses.add(PortfolioMessage::LoadFallbackFonts);
 			}
 			PortfolioMessage::GraphViewOverlay { open } => {
 				self.graph_view_overlay_open = open;
@@ -286,6 +293,23 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 				if let Some(document) = self.document_mut(document_id) {
 					document.load_layer_resources(responses, &document.document_legacy.root.data, Vec::new(), document_id);
 				}
+				responses.add(PortfolioMessage::LoadFallbackFonts);
+			}
+			PortfolioMessage::LoadFallbackFonts => {
+				let Some(document) = self.active_document() else { return };
+
+				let mut fonts = Vec::new();
+				for (font, text) in document.layer_texts() {
+					for fallback in self.persistent_data.font_cache.missing_fallback_fonts(&font, &text) {
+						if !fonts.contains(&fallback) && !self.requested_fallback_fonts.contains(&fallback) {
+							fonts.push(fallback);
+						}
+					}
+				}
+				if !fonts.is_empty() {
+					self.requested_fallback_fonts.extend(fonts.iter().cloned());
+					responses.add(FrontendMessage::TriggerFallbackFontsLoad { fonts });
+				}
 			}
 			PortfolioMessage::LoadFont { font, is_default } => {
 				if !self.persistent_data.font_cache.loaded_font(&font) {
@@ -325,6 +349,7 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 					document_is_auto_saved: false,
 					document_is_saved: true,
 					document_serialized_content,
+					document_journal: Vec::new(),
 				});
 			}
 			PortfolioMessage::OpenDocumentFileWithId {
@@ -333,12 +358,16 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 				document_is_auto_saved,
 				document_is_saved,
 				document_serialized_content,
+				document_journal,
 			} => {
 				let document = DocumentMessageHandler::with_name_and_content(document_name, document_serialized_content);
 				match document {
 					Ok(mut document) => {
-						document.set_auto_save_state(document_is_auto_saved);
-						document.set_save_state(document_is_saved);
+						// Recover the changes made to an auto-saved document after it was last saved in full
+						let recovered = document_is_auto_saved && document.replay_journal(&document_journal);
+
+						document.set_auto_save_state(document_is_auto_saved && !recovered);
+						document.set_save_state(document_is_saved && !recovered);
 						self.load_document(document, document_id, responses);
 					}
 					Err(e) => {
@@ -396,46 +425,112 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 					}
 				};
 
+				let content = &self.copy_buffer[clipboard as usize];
 				if insert_index == -1 {
-					for entry in self.copy_buffer[clipboard as usize].iter().rev() {
+					for entry in content.layers.iter().rev() {
 						paste(entry, responses)
 					}
 				} else {
-					for entry in self.copy_buffer[clipboard as usize].iter() {
+					for entry in content.layers.iter() {
 						paste(entry, responses)
 					}
 				}
+
+				self.load_pasted_fonts(content, responses);
+				if !content.nodes.is_empty() {
+					responses.add(NodeGraphMessage::PasteNodes { nodes: content.nodes.clone() });
+				}
 			}
-			PortfolioMessage::PasteSerializedData { data } => {
-				if let Some(document) = self.active_document() {
-					if let Ok(data) = serde_json::from_str::<Vec<CopyBufferEntry>>(&data) {
-						let shallowest_common_folder = document
-							.document_legacy
-							.shallowest_common_folder(document.selected_layers())
-							.expect("While pasting from serialized, the selected layers did not exist while attempting to find the appropriate folder path for insertion");
-						responses.add(DocumentMessage::DeselectAllLayers);
-						responses.add(DocumentMessage::StartTransaction);
+			PortfolioMessage::PasteSerializedData { data, mode } => {
+				let Some(document) = self.active_document() else { return };
+				let content = match ClipboardContent::from_clipboard_text(&data) {
+					Ok(content) => content,
+					Err(error) => {
+						warn!("Could not read the pasted clipboard content: {error}");
+						return;
+					}
+				};
+
+				match mode {
+					PasteMode::All => {
+						self.load_pasted_fonts(&content, responses);
+
+						if !content.layers.is_empty() {
+							let shallowest_common_folder = document
+								.document_legacy
+								.shallowest_common_folder(document.selected_layers())
+								.expect("While pasting from serialized, the selected layers did not exist while attempting to find the appropriate folder path for insertion");
+							responses.add(DocumentMessage::DeselectAllLayers);
+							responses.add(DocumentMessage::StartTransaction);
+
+							for entry in content.layers.iter().rev() {
+								let destination_path = [shallowest_common_folder.to_vec(), vec![generate_uuid()]].concat();
+
+								document.load_layer_resources(responses, &entry.layer.data, destination_path.clone(), self.active_document_id.unwrap());
+								responses.add(DocumentOperation::InsertLayer {
+									layer: Box::new(entry.layer.clone()),
+									destination_path: destination_path.clone(),
+									insert_index: -1,
+									duplicating: false,
+								});
+								responses.add(DocumentMessage::UpdateLayerMetadata {
+									layer_path: destination_path,
+									layer_metadata: entry.layer_metadata,
+								});
+							}
 
-						for entry in data.iter().rev() {
-							let destination_path = [shallowest_common_folder.to_vec(), vec![generate_uuid()]].concat();
+							responses.add(DocumentMessage::CommitTransaction);
+						}
 
-							document.load_layer_resources(responses, &entry.layer.data, destination_path.clone(), self.active_document_id.unwrap());
-							responses.add(DocumentOperation::InsertLayer {
-								layer: Box::new(entry.layer.clone()),
-								destination_path: destination_path.clone(),
-								insert_index: -1,
-								duplicating: false,
+						if !content.nodes.is_empty() {
+							responses.add(NodeGraphMessage::PasteNodes { nodes: content.nodes });
+						}
+					}
+					PasteMode::StyleOnly => {
+						let Some(entry) = content.layers.first() else { return };
+						let style = LayerStyle::of(&entry.layer);
+
+						responses.add(DocumentMessage::StartTransaction);
+						for path in document.selected_layers_without_children() {
+							if let Some(fill) = style.fill.clone() {
+								responses.add(GraphOperationMessage::FillSet { layer: path.to_vec(), fill });
+							}
+							if let Some(stroke) = style.stroke.clone() {
+								responses.add(GraphOperationMessage::StrokeSet { layer: path.to_vec(), stroke });
+							}
+							responses.add(DocumentOperation::SetLayerBlendMode {
+								path: path.to_vec(),
+								blend_mode: style.blend_mode,
 							});
-							responses.add(DocumentMessage::UpdateLayerMetadata {
-								layer_path: destination_path,
-								layer_metadata: entry.layer_metadata,
+							responses.add(DocumentOperation::SetLayerOpacity {
+								path: path.to_vec(),
+								opacity: style.opacity,
 							});
 						}
+						responses.add(DocumentMessage::CommitTransaction);
+					}
+					PasteMode::TransformOnly => {
+						let Some(entry) = content.layers.first() else { return };
 
+						responses.add(DocumentMessage::StartTransaction);
+						for path in document.selected_layers_without_children() {
+							responses.add(GraphOperationMessage::TransformSet {
+								layer: path.to_vec(),
+								transform: entry.layer.transform,
+								transform_in: TransformIn::Local,
+								skip_rerender: false,
+							});
+						}
 						responses.add(DocumentMessage::CommitTransaction);
 					}
 				}
 			}
+			PortfolioMessage::PasteStyle => {
+				responses.add(FrontendMessage::TriggerPaste { mode: PasteMode::StyleOnly });
+			}
+			PortfolioMessage::PasteTransform => {
+				responses.add(FrontendMessage::TriggerPaste { mode: PasteMode::TransformOnly });
+			}
 			PortfolioMessage::PrevDocument => {
 				if let Some(active_document_id) = self.active_document_id {
 					let len = self.document_ids.len();
@@ -453,7 +548,7 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 			} => {
 				let result = self.executor.submit_node_graph_evaluation(
 					(document_id, &mut self.documents),
-					layer_path,
+					layer_path.clone(),
 					(input_image_data, size),
 					(preferences, &self.persistent_data),
 					responses,
@@ -464,6 +559,11 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 						title: "Unable to update node graph".to_string(),
 						description,
 					});
+					// Report the evaluation which won't happen, so anything waiting on this layer's output isn't left waiting forever
+					if let Some(document) = self.documents.get(&document_id) {
+						let message = DocumentMessage::NodeGraphEvaluated { layer_path, time: document.time };
+						responses.add(PortfolioMessage::DocumentPassMessage { document_id, message });
+					}
 				}
 			}
 			PortfolioMessage::SelectDocument { document_id } => {
@@ -522,6 +622,7 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 			PortfolioMessage::UpdateDocumentWidgets => {
 				if let Some(document) = self.active_document() {
 					document.update_document_widgets(responses);
+					document.update_history_panel(responses);
 				}
 			}
 			PortfolioMessage::UpdateOpenDocumentsList => {
@@ -562,6 +663,8 @@ impl MessageHandler<PortfolioMessage, (&InputPreprocessorMessageHandler, &Prefer
 				let select = actions!(PortfolioMessageDiscriminant;
 					Copy,
 					Cut,
+					PasteStyle,
+					PasteTransform,
 				);
 				common.extend(select);
 			}
@@ -668,6 +771,16 @@ impl PortfolioMessageHandler {
 		self.document_ids.iter().position(|id| id == &document_id).expect("Active document is missing from document ids")
 	}
 
+	/// Loads the fonts of pasted content which haven't been used yet, such as when the content was copied from another instance of the editor.
+	fn load_pasted_fonts(&self, content: &ClipboardContent, responses: &mut VecDeque<Message>) {
+		for font in content.fonts.iter().filter(|font| !self.persistent_data.font_cache.loaded_font(font)) {
+			responses.add(FrontendMessage::TriggerFontLoad {
+				font: font.clone(),
+				is_default: false,
+			});
+		}
+	}
+
 	fn uploaded_new_font(document: &mut DocumentMessageHandler, target_font: &Font, responses: &mut VecDeque<Message>) {
 		let mut stack = vec![(&document.document_legacy.root, Vec::new())];
 
@@ -1,13 +1,31 @@
-use graphene_std::{imaginate::ImaginatePersistentData, text::FontCache};
+use crate::consts::FALLBACK_FONTS;
+
+use graphene_std::imaginate::ImaginatePersistentData;
+use graphene_std::text::{FallbackFont, Font, FontCache};
 
 use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Default)]
+#[derive(Debug)]
 pub struct PersistentData {
 	pub font_cache: FontCache,
 	pub imaginate: ImaginatePersistentData,
 }
 
+impl Default for PersistentData {
+	fn default() -> Self {
+		let mut font_cache = FontCache::default();
+		let fallback_fonts = FALLBACK_FONTS.iter().map(|&(family, style, scripts)| FallbackFont {
+			font: Font::new(family.into(), style.into()),
+			scripts: scripts.iter().map(|&script| script.into()).collect(),
+		});
+		font_cache.set_fallback_fonts(fallback_fonts.collect());
+		Self {
+			font_cache,
+			imaginate: ImaginatePersistentData::default(),
+		}
+	}
+}
+
 #[derive(PartialEq, Eq, Clone, Copy, Default, Debug, Serialize, Deserialize)]
 pub enum Platform {
 	#[default]
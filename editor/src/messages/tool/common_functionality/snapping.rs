@@ -1,11 +1,13 @@
 use super::shape_editor::ManipulatorPointInfo;
 use crate::application::generate_uuid;
 use crate::consts::{
-	COLOR_ACCENT, SNAP_AXIS_OVERLAY_FADE_DISTANCE, SNAP_AXIS_TOLERANCE, SNAP_AXIS_UNSNAPPED_OPACITY, SNAP_POINT_OVERLAY_FADE_FAR, SNAP_POINT_OVERLAY_FADE_NEAR, SNAP_POINT_SIZE, SNAP_POINT_TOLERANCE,
-	SNAP_POINT_UNSNAPPED_OPACITY,
+	COLOR_ACCENT, SNAP_AXIS_OVERLAY_FADE_DISTANCE, SNAP_AXIS_TOLERANCE, SNAP_AXIS_UNSNAPPED_OPACITY, SNAP_CURVE_END_TOLERANCE, SNAP_CURVE_SAMPLES, SNAP_MEASUREMENT_LABEL_OFFSET,
+	SNAP_POINT_OVERLAY_FADE_FAR, SNAP_POINT_OVERLAY_FADE_NEAR, SNAP_POINT_SIZE, SNAP_POINT_TOLERANCE, SNAP_POINT_UNSNAPPED_OPACITY,
 };
+use crate::messages::portfolio::document::overlays::utility_types::OverlayLabel;
 use crate::messages::prelude::*;
 
+use bezier_rs::{Bezier, TValue};
 use document_legacy::layers::layer_info::Layer;
 use document_legacy::layers::style::{self, Stroke};
 use document_legacy::{LayerId, Operation};
@@ -19,8 +21,11 @@ use std::f64::consts::PI;
 struct SnapOverlays {
 	axis_overlay_paths: Vec<Vec<LayerId>>,
 	point_overlay_paths: Vec<Vec<LayerId>>,
+	measurement_overlay_paths: Vec<Vec<LayerId>>,
 	axis_index: usize,
 	point_index: usize,
+	measurement_index: usize,
+	has_labels: bool,
 }
 
 impl SnapOverlays {
@@ -143,6 +148,38 @@ impl SnapOverlays {
 		Self::remove_unused_overlays(&mut self.point_overlay_paths, responses, self.point_index);
 	}
 
+	/// Draws the measured gaps as lines labelled with their length in document space, replacing the ones drawn before.
+	fn draw_measurements(&mut self, responses: &mut VecDeque<Message>, measurements: &[Measurement], viewport_to_document: DAffine2) {
+		self.measurement_index = 0;
+
+		let mut labels = Vec::new();
+		for measurement in measurements {
+			let vector = measurement.end - measurement.start;
+			let transform = DAffine2::from_scale_angle_translation(DVec2::new(vector.length(), 1.), vector.y.atan2(vector.x), measurement.start).to_cols_array();
+			Self::add_overlay(true, responses, transform, None, self.measurement_index, &mut self.measurement_overlay_paths);
+			self.measurement_index += 1;
+
+			// Place the label beside the line rather than over it
+			let offset = vector.perp().normalize_or_zero() * SNAP_MEASUREMENT_LABEL_OFFSET;
+			labels.push(OverlayLabel {
+				position: (measurement.start + measurement.end) / 2. - offset,
+				text: format_distance(viewport_to_document.transform_vector2(vector).length()),
+			});
+		}
+
+		Self::remove_unused_overlays(&mut self.measurement_overlay_paths, responses, self.measurement_index);
+		self.set_labels(responses, labels);
+	}
+
+	fn set_labels(&mut self, responses: &mut VecDeque<Message>, labels: Vec<OverlayLabel>) {
+		// Avoid rerendering the overlays each frame while there are no labels to show or remove
+		if labels.is_empty() && !self.has_labels {
+			return;
+		}
+		self.has_labels = !labels.is_empty();
+		responses.add(DocumentMessage::Overlays(OverlaysMessage::SetLabels { labels }));
+	}
+
 	/// Remove overlays from the pool beyond a given index. Pool entries up through that index will be kept.
 	fn remove_unused_overlays(overlay_paths: &mut Vec<Vec<LayerId>>, responses: &mut VecDeque<Message>, remove_after_index: usize) {
 		while overlay_paths.len() > remove_after_index {
@@ -154,6 +191,8 @@ impl SnapOverlays {
 	fn cleanup(&mut self, responses: &mut VecDeque<Message>) {
 		Self::remove_unused_overlays(&mut self.axis_overlay_paths, responses, 0);
 		Self::remove_unused_overlays(&mut self.point_overlay_paths, responses, 0);
+		Self::remove_unused_overlays(&mut self.measurement_overlay_paths, responses, 0);
+		self.set_labels(responses, Vec::new());
 	}
 }
 
@@ -162,6 +201,10 @@ impl SnapOverlays {
 pub struct SnapManager {
 	point_targets: Option<Vec<DVec2>>,
 	bound_targets: Option<Vec<DVec2>>,
+	/// The bounding boxes of the target layers, which dragged layers are spaced evenly against and measured to.
+	box_targets: Option<Vec<[DVec2; 2]>>,
+	/// The segments of the target paths in viewport space, which intersections, tangents and perpendiculars are found on.
+	path_targets: Vec<Bezier>,
 	snap_overlays: SnapOverlays,
 	snap_x: bool,
 	snap_y: bool,
@@ -223,18 +266,27 @@ impl SnapManager {
 	) {
 		let snapping_enabled = document_message_handler.snapping_state.snapping_enabled;
 		let bounding_box_snapping = document_message_handler.snapping_state.bounding_box_snapping;
+		self.path_targets.clear();
 		if snapping_enabled && bounding_box_snapping {
 			self.snap_x = snap_x;
 			self.snap_y = snap_y;
 
+			let viewport_size = input.viewport_bounds.size();
+			let boxes: Vec<_> = bounding_boxes
+				.filter(|[min, max]| max.x >= 0. && max.y >= 0. && min.x < viewport_size.x && min.y < viewport_size.y)
+				.collect();
 			// Could be made into sorted Vec or a HashSet for more performant lookups.
-			self.bound_targets = Some(
-				bounding_boxes
-					.flat_map(expand_bounds)
-					.filter(|&pos| pos.x >= 0. && pos.y >= 0. && pos.x < input.viewport_bounds.size().x && pos.y <= input.viewport_bounds.size().y)
-					.collect(),
-			);
-			self.point_targets = None;
+			let targets: Vec<_> = boxes
+				.iter()
+				.copied()
+				.flat_map(expand_bounds)
+				.filter(|&pos| pos.x >= 0. && pos.y >= 0. && pos.x < viewport_size.x && pos.y <= viewport_size.y)
+				.collect();
+
+			// The centers and edge midpoints can also be snapped to as points, such as to draw from the center of a shape
+			self.point_targets = Some(targets.clone());
+			self.bound_targets = Some(targets);
+			self.box_targets = Some(boxes);
 		}
 	}
 
@@ -273,6 +325,26 @@ impl SnapManager {
 		};
 
 		let transform = document_message_handler.document_legacy.multiply_transforms(path).unwrap();
+
+		// The segments move along with any of their points being dragged, so they're only kept while none are
+		if !ignore_points.iter().any(|point| point.shape_layer_path == path) {
+			let viewport_size = input.viewport_bounds.size();
+			let segments = vector_data
+				.subpaths
+				.iter()
+				.flat_map(|subpath| subpath.iter())
+				.map(|segment| segment.apply_transformation(|point| transform.transform_point2(point)))
+				.filter(|segment| {
+					let [min, max] = segment.bounding_box();
+					max.x >= 0. && max.y >= 0. && min.x < viewport_size.x && min.y < viewport_size.y
+				});
+			let first_segment = self.path_targets.len();
+			self.path_targets.extend(segments);
+
+			let midpoints: Vec<_> = self.path_targets[first_segment..].iter().map(|segment| segment.evaluate(TValue::Euclidean(0.5))).collect();
+			self.add_snap_points(document_message_handler, input, midpoints.into_iter());
+		}
+
 		let snap_points = vector_data
 			.manipulator_groups()
 			.flat_map(|group| {
@@ -307,16 +379,67 @@ impl SnapManager {
 				self.add_snap_path(document_message_handler, input, layer, path, include_handles.contains(&path), ignore_points);
 			}
 		}
+
+		let intersections = path_intersections(&self.path_targets);
+		self.add_snap_points(document_message_handler, input, intersections.into_iter());
+	}
+
+	/// Adds the points on the document's paths where a line from the given point meets them at a tangent or a right angle, such as for drawing a line from that point.
+	///
+	/// This should be called after add_all_document_handles
+	pub fn add_snap_reference(&mut self, document_message_handler: &DocumentMessageHandler, input: &InputPreprocessorMessageHandler, reference: DVec2) {
+		let points = tangent_and_perpendicular_points(&self.path_targets, reference);
+		// The reference lies on the path it was snapped to, where the line from it would have no length
+		let points = points.into_iter().filter(|point| point.distance(reference) > SNAP_POINT_TOLERANCE);
+		self.add_snap_points(document_message_handler, input, points);
 	}
 
 	/// Finds the closest snap from an array of layers to the specified snap targets in viewport coords.
 	/// Returns 0 for each axis that there is no snap less than the snap tolerance.
+	///
+	/// An axis which doesn't snap into alignment can instead snap to space the layers evenly between the target layers,
+	/// and the distances to the neighbouring target layers are shown while dragging.
 	pub fn snap_layers(&mut self, responses: &mut VecDeque<Message>, document_message_handler: &DocumentMessageHandler, snap_anchors: Vec<DVec2>, mouse_delta: DVec2) -> DVec2 {
-		if document_message_handler.snapping_state.snapping_enabled {
-			self.calculate_snap(snap_anchors.iter().map(move |&snap| mouse_delta + snap), responses)
-		} else {
-			DVec2::ZERO
+		if !document_message_handler.snapping_state.snapping_enabled {
+			return DVec2::ZERO;
 		}
+
+		let mut snap = self.calculate_snap(snap_anchors.iter().map(move |&snap| mouse_delta + snap), responses);
+
+		// The snap anchors span the bounds of the layers being moved
+		let Some(bounds) = snap_anchors
+			.iter()
+			.map(|&anchor| [anchor + mouse_delta; 2])
+			.reduce(|[min, max], [anchor, _]| [min.min(anchor), max.max(anchor)])
+		else {
+			return snap;
+		};
+		let boxes = self.box_targets.as_deref().unwrap_or_default();
+
+		let mut measurements = Vec::new();
+		let mut distributed = [false; 2];
+		for (axis, snap_axis) in [(0, self.snap_x), (1, self.snap_y)] {
+			if !snap_axis || snap[axis] != 0. {
+				continue;
+			}
+			if let Some((offset, gaps)) = distribute_along_axis(bounds, boxes, axis) {
+				snap[axis] = offset;
+				distributed[axis] = true;
+				measurements.extend(gaps);
+			}
+		}
+
+		let moved = [bounds[0] + snap, bounds[1] + snap];
+		for (axis, snap_axis) in [(0, self.snap_x), (1, self.snap_y)] {
+			if snap_axis && !distributed[axis] {
+				measurements.extend(neighbour_gaps(moved, boxes, axis));
+			}
+		}
+
+		let viewport_to_document = document_message_handler.document_legacy.root.transform.inverse();
+		self.snap_overlays.draw_measurements(responses, &measurements, viewport_to_document);
+
+		snap
 	}
 
 	/// Handles snapping of a viewport position, returning another viewport position.
@@ -333,17 +456,273 @@ impl SnapManager {
 		self.snap_overlays.cleanup(responses);
 		self.bound_targets = None;
 		self.point_targets = None;
+		self.box_targets = None;
+		self.path_targets.clear();
 	}
 }
 
 /// Converts a bounding box into a set of points for snapping
 ///
-/// Puts a point in the middle of each edge (top, bottom, left, right)
-pub fn expand_bounds([bound1, bound2]: [DVec2; 2]) -> [DVec2; 4] {
+/// Puts a point in the middle of each edge (top, bottom, left, right) and one in the center
+pub fn expand_bounds([bound1, bound2]: [DVec2; 2]) -> [DVec2; 5] {
 	[
 		DVec2::new((bound1.x + bound2.x) / 2., bound1.y),
 		DVec2::new((bound1.x + bound2.x) / 2., bound2.y),
 		DVec2::new(bound1.x, (bound1.y + bound2.y) / 2.),
 		DVec2::new(bound2.x, (bound1.y + bound2.y) / 2.),
+		(bound1 + bound2) / 2.,
 	]
 }
+
+/// A gap between two bounding boxes along an axis, from the end of the first to the start of the second, in viewport space.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Measurement {
+	pub start: DVec2,
+	pub end: DVec2,
+}
+
+impl Measurement {
+	/// Measures the gap along the axis between two boxes, halfway across the span they share.
+	fn between(before: [DVec2; 2], after: [DVec2; 2], axis: usize) -> Self {
+		let across = 1 - axis;
+		let middle = (before[0][across].max(after[0][across]) + before[1][across].min(after[1][across])) / 2.;
+
+		let (mut start, mut end) = (DVec2::ZERO, DVec2::ZERO);
+		(start[axis], end[axis]) = (before[1][axis], after[0][axis]);
+		(start[across], end[across]) = (middle, middle);
+		Self { start, end }
+	}
+}
+
+/// The boxes which overlap the given bounds across the axis, so that they're side by side along it.
+fn boxes_beside(bounds: [DVec2; 2], boxes: &[[DVec2; 2]], axis: usize) -> impl Iterator<Item = [DVec2; 2]> + Clone + '_ {
+	let across = 1 - axis;
+	boxes.iter().copied().filter(move |other| other[0][across] < bounds[1][across] && bounds[0][across] < other[1][across])
+}
+
+fn shift_along(mut bounds: [DVec2; 2], axis: usize, offset: f64) -> [DVec2; 2] {
+	bounds[0][axis] += offset;
+	bounds[1][axis] += offset;
+	bounds
+}
+
+/// Finds the smallest offset along the axis, within the snap tolerance, which spaces the bounds evenly with the boxes beside them.
+///
+/// The bounds are either placed after or before a pair of neighbouring boxes with the same gap as between them, or centered between them.
+/// Returns the offset along with the gaps which it makes equal.
+pub fn distribute_along_axis(bounds: [DVec2; 2], boxes: &[[DVec2; 2]], axis: usize) -> Option<(f64, Vec<Measurement>)> {
+	let beside = boxes_beside(bounds, boxes, axis);
+	let size = bounds[1][axis] - bounds[0][axis];
+
+	let mut closest: Option<(f64, Vec<Measurement>)> = None;
+	for first in beside.clone() {
+		// Pair each box with the nearest one after it
+		let Some(second) = beside.clone().filter(|other| other[0][axis] > first[1][axis]).min_by(|a, b| a[0][axis].total_cmp(&b[0][axis])) else {
+			continue;
+		};
+		let gap = second[0][axis] - first[1][axis];
+
+		let after = second[1][axis] + gap - bounds[0][axis];
+		let before = first[0][axis] - gap - size - bounds[0][axis];
+		let between = (first[1][axis] + second[0][axis] - size) / 2. - bounds[0][axis];
+
+		// The bounds only fit between the pair if they're narrower than the gap
+		let candidates = [
+			(after, true, [(first, second), (second, shift_along(bounds, axis, after))]),
+			(before, true, [(shift_along(bounds, axis, before), first), (first, second)]),
+			(between, gap > size, [(first, shift_along(bounds, axis, between)), (shift_along(bounds, axis, between), second)]),
+		];
+		for (offset, fits, pairs) in candidates {
+			if fits && offset.abs() <= SNAP_AXIS_TOLERANCE && !closest.as_ref().is_some_and(|(closest, _)| closest.abs() <= offset.abs()) {
+				closest = Some((offset, pairs.into_iter().map(|(before, after)| Measurement::between(before, after, axis)).collect()));
+			}
+		}
+	}
+	closest
+}
+
+/// Measures the gaps along the axis from the bounds to the nearest box beside them on either side.
+pub fn neighbour_gaps(bounds: [DVec2; 2], boxes: &[[DVec2; 2]], axis: usize) -> Vec<Measurement> {
+	let beside = boxes_beside(bounds, boxes, axis);
+	let before = beside.clone().filter(|other| other[1][axis] <= bounds[0][axis]).max_by(|a, b| a[1][axis].total_cmp(&b[1][axis]));
+	let after = beside.filter(|other| other[0][axis] >= bounds[1][axis]).min_by(|a, b| a[0][axis].total_cmp(&b[0][axis]));
+
+	let before = before.map(|before| Measurement::between(before, bounds, axis));
+	let after = after.map(|after| Measurement::between(bounds, after, axis));
+	before.into_iter().chain(after).collect()
+}
+
+/// Finds the points where the segments cross each other, leaving out the ends which neighbouring segments share.
+///
+/// The segments are swept from left to right, so each is only compared with those whose bounding boxes it overlaps horizontally.
+pub fn path_intersections(segments: &[Bezier]) -> Vec<DVec2> {
+	let bounds: Vec<_> = segments.iter().map(Bezier::bounding_box).collect();
+	let interior = |t: &f64| *t > SNAP_CURVE_END_TOLERANCE && *t < 1. - SNAP_CURVE_END_TOLERANCE;
+
+	let mut order: Vec<_> = (0..segments.len()).collect();
+	order.sort_by(|&a, &b| bounds[a][0].x.total_cmp(&bounds[b][0].x));
+
+	let mut points = Vec::new();
+	// The segments which reach the left edge of the current one, the others end before any of the segments still to come begin
+	let mut active: Vec<usize> = Vec::new();
+	for index in order {
+		let [min, max] = bounds[index];
+		active.retain(|&other| bounds[other][1].x >= min.x);
+
+		for &other in &active {
+			let [other_min, other_max] = bounds[other];
+			if min.y <= other_max.y && other_min.y <= max.y {
+				// Keeping the order of the segments in the path decides which of the two has its ends left out
+				let (segment, other) = (&segments[index.min(other)], &segments[index.max(other)]);
+				let crossings = segment.intersections(other, None, None).into_iter().filter(interior);
+				points.extend(crossings.map(|t| segment.evaluate(TValue::Parametric(t))));
+			}
+		}
+		active.push(index);
+	}
+	points
+}
+
+/// Finds the points on the segments where a line from the reference point touches them at a tangent or meets them at a right angle.
+pub fn tangent_and_perpendicular_points(segments: &[Bezier], reference: DVec2) -> Vec<DVec2> {
+	let mut points = Vec::new();
+	for segment in segments {
+		let position = |t: f64| segment.evaluate(TValue::Parametric(t));
+		let direction = |t: f64| (position(t) - reference).normalize_or_zero();
+
+		let tangents = curve_roots(|t| direction(t).perp_dot(segment.tangent(TValue::Parametric(t))));
+		let perpendiculars = curve_roots(|t| direction(t).dot(segment.tangent(TValue::Parametric(t))));
+		points.extend(tangents.into_iter().chain(perpendiculars).map(position));
+	}
+	points
+}
+
+/// Finds where a function of the curve parameter crosses zero by sampling it along the curve and narrowing down each change of sign.
+fn curve_roots(function: impl Fn(f64) -> f64) -> Vec<f64> {
+	// Values which only differ from zero by rounding, like along a straight line through the reference, don't count as a change of sign
+	const EPSILON: f64 = 1e-9;
+
+	let samples: Vec<_> = (0..=SNAP_CURVE_SAMPLES).map(|index| index as f64 / SNAP_CURVE_SAMPLES as f64).map(|t| (t, function(t))).collect();
+	samples
+		.windows(2)
+		// A root which lands right on a sample is counted once, by the interval which ends at it
+		.filter(|pair| (pair[0].1 * pair[1].1 < 0. || (pair[1].1 == 0. && pair[0].1 != 0.)) && pair[0].1.abs().max(pair[1].1.abs()) > EPSILON)
+		.map(|pair| {
+			let (mut low, mut high) = (pair[0], pair[1]);
+			for _ in 0..32 {
+				let t = (low.0 + high.0) / 2.;
+				let value = function(t);
+				if value * low.1 <= 0. {
+					high = (t, value);
+				} else {
+					low = (t, value);
+				}
+			}
+			(low.0 + high.0) / 2.
+		})
+		.filter(|t| *t > SNAP_CURVE_END_TOLERANCE && *t < 1. - SNAP_CURVE_END_TOLERANCE)
+		.collect()
+}
+
+/// Formats a distance for a readout, to at most two decimal places.
+fn format_distance(distance: f64) -> String {
+	let rounded = (distance * 100.).round() / 100.;
+	format!("{rounded}")
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn bounds(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> [DVec2; 2] {
+		[DVec2::new(min_x, min_y), DVec2::new(max_x, max_y)]
+	}
+
+	#[test]
+	fn layers_are_distributed_after_before_and_between_targets() {
+		let boxes = [bounds(0., 0., 10., 10.), bounds(20., 0., 30., 10.)];
+
+		let (offset, gaps) = distribute_along_axis(bounds(41., 2., 51., 8.), &boxes, 0).unwrap();
+		assert_eq!(offset, -1.);
+		assert_eq!(gaps.len(), 2);
+		assert_eq!((gaps[1].start, gaps[1].end), (DVec2::new(30., 5.), DVec2::new(40., 5.)));
+
+		assert_eq!(distribute_along_axis(bounds(-22., 0., -12., 10.), &boxes, 0).unwrap().0, 2.);
+
+		let (offset, gaps) = distribute_along_axis(bounds(14., 0., 18., 10.), &boxes, 0).unwrap();
+		assert_eq!(offset, -1.);
+		assert_eq!(gaps.iter().map(|gap| gap.end.x - gap.start.x).collect::<Vec<_>>(), [3., 3.]);
+
+		// Targets which aren't level with the layers, or spacing outside of the tolerance, don't snap
+		assert!(distribute_along_axis(bounds(41., 20., 51., 28.), &boxes, 0).is_none());
+		assert!(distribute_along_axis(bounds(50., 0., 60., 10.), &boxes, 0).is_none());
+
+		let boxes = [bounds(0., 0., 10., 10.), bounds(0., 20., 10., 30.)];
+		assert_eq!(distribute_along_axis(bounds(0., 39., 10., 49.), &boxes, 1).unwrap().0, 1.);
+	}
+
+	#[test]
+	fn gaps_are_measured_to_the_nearest_neighbours() {
+		let boxes = [bounds(0., 0., 10., 10.), bounds(20., 0., 30., 10.), bounds(50., 0., 60., 10.), bounds(35., 50., 40., 60.)];
+
+		let gaps = neighbour_gaps(bounds(35., 2., 40., 8.), &boxes, 0);
+		assert_eq!(gaps.iter().map(|gap| (gap.start.x, gap.end.x)).collect::<Vec<_>>(), [(30., 35.), (40., 50.)]);
+
+		let gaps = neighbour_gaps(bounds(35., 2., 40., 8.), &boxes, 1);
+		assert_eq!(gaps.len(), 1);
+		assert_eq!((gaps[0].start, gaps[0].end), (DVec2::new(37.5, 8.), DVec2::new(37.5, 50.)));
+
+		assert_eq!(format_distance(12.3456), "12.35");
+		assert_eq!(format_distance(12.), "12");
+	}
+
+	#[test]
+	fn grid_intersections_are_found() {
+		// Vertical lines listed from right to left among horizontal lines, which span all of them
+		let vertical = [25., 15., 5.].map(|x| Bezier::from_linear_dvec2(DVec2::new(x, 0.), DVec2::new(x, 30.)));
+		let horizontal = [5., 15., 25.].map(|y| Bezier::from_linear_dvec2(DVec2::new(0., y), DVec2::new(30., y)));
+		let segments: Vec<_> = vertical.into_iter().zip(horizontal).flat_map(|(vertical, horizontal)| [vertical, horizontal]).collect();
+
+		let points = path_intersections(&segments);
+		assert_eq!(points.len(), 9);
+		for x in [5., 15., 25.] {
+			for y in [5., 15., 25.] {
+				assert!(points.iter().any(|point| point.distance(DVec2::new(x, y)) < 1e-6), "Missing ({x}, {y})");
+			}
+		}
+	}
+
+	#[test]
+	fn curve_points_are_found() {
+		let segments = [
+			Bezier::from_linear_dvec2(DVec2::new(0., 0.), DVec2::new(10., 10.)),
+			Bezier::from_linear_dvec2(DVec2::new(10., 10.), DVec2::new(20., 0.)),
+			Bezier::from_linear_dvec2(DVec2::new(0., 10.), DVec2::new(10., 0.)),
+		];
+		// Segments which only meet at their ends don't intersect
+		let points = path_intersections(&segments);
+		assert_eq!(points.len(), 1);
+		assert!(points[0].distance(DVec2::new(5., 5.)) < 1e-6);
+
+		let line = [Bezier::from_linear_dvec2(DVec2::ZERO, DVec2::new(10., 0.))];
+		let points = tangent_and_perpendicular_points(&line, DVec2::new(3., 5.));
+		assert_eq!(points.len(), 1);
+		assert!(points[0].distance(DVec2::new(3., 0.)) < 1e-6);
+		assert!(tangent_and_perpendicular_points(&line, DVec2::new(-5., 0.)).is_empty());
+
+		let arc = [Bezier::from_quadratic_dvec2(DVec2::ZERO, DVec2::new(5., 10.), DVec2::new(10., 0.))];
+		let points = tangent_and_perpendicular_points(&arc, DVec2::new(5., 20.));
+		assert_eq!(points.len(), 1);
+		assert!(points[0].distance(DVec2::new(5., 5.)) < 1e-6);
+
+		let arc = [Bezier::from_cubic_dvec2(DVec2::ZERO, DVec2::new(0., 10.), DVec2::new(10., 10.), DVec2::new(10., 0.))];
+		let reference = DVec2::new(-5., 20.);
+		let points = tangent_and_perpendicular_points(&arc, reference);
+		assert_eq!(points.len(), 2);
+		for point in points {
+			let tangent = arc[0].tangent(TValue::Parametric(arc[0].project(point, None)));
+			let direction = (point - reference).normalize();
+			assert!(direction.perp_dot(tangent).abs() < 1e-3 || direction.dot(tangent).abs() < 1e-3);
+		}
+	}
+}
@@ -4,9 +4,12 @@ use crate::messages::frontend::utility_types::MouseCursorIcon;
 use crate::messages::portfolio::document::utility_types::transformation::OriginalTransforms;
 use crate::messages::prelude::*;
 
+use document_legacy::layers::layer_layer::CachedOutputData;
 use document_legacy::layers::style::{self, Fill, Stroke};
 use document_legacy::LayerId;
 use document_legacy::Operation;
+use graph_craft::document::value::TaggedValue;
+use graph_craft::document::NodeInput;
 use graphene_core::raster::color::Color;
 
 use glam::{DAffine2, DVec2};
@@ -349,3 +352,73 @@ impl BoundingBoxOverlays {
 		);
 	}
 }
+
+/// Distort mode of the transformation cage, which drags the corners of a raster layer independently and warps it into the resulting quad with a perspective warp node.
+#[derive(Clone, Debug, Default)]
+pub struct DistortCage {
+	pub layer: Vec<LayerId>,
+	/// The corners in the space of the layer's parent, in the order top left, top right, bottom right and bottom left
+	pub corners: [DVec2; 4],
+	/// Maps the space of the layer's parent to the viewport
+	pub parent_transform: DAffine2,
+	pub dragging_corner: Option<usize>,
+}
+
+impl DistortCage {
+	/// Creates the cage for a raster layer, continuing from the corners of an existing perspective warp if the layer already has one.
+	pub fn new(document: &DocumentMessageHandler, layer_path: &[LayerId]) -> Option<Self> {
+		let layer = document.document_legacy.layer(layer_path).ok()?;
+		let layer_layer = layer.as_layer().ok()?;
+		if !matches!(layer_layer.cached_output_data, CachedOutputData::BlobURL(_) | CachedOutputData::SurfaceId(_)) {
+			return None;
+		}
+
+		let existing_corners = layer_layer
+			.network
+			.primary_flow()
+			.find(|(node, _)| node.name == "Perspective Warp")
+			.and_then(|(node, _)| match node.inputs.get(1) {
+				Some(NodeInput::Value {
+					tagged_value: TaggedValue::VecDVec2(corners),
+					..
+				}) => corners.as_slice().try_into().ok(),
+				_ => None,
+			});
+		// The image of a raster layer covers the unit square of the layer's transform
+		let corners = existing_corners.unwrap_or_else(|| [DVec2::ZERO, DVec2::X, DVec2::ONE, DVec2::Y].map(|corner| layer.transform.transform_point2(corner)));
+		let parent_transform = document.document_legacy.multiply_transforms(&layer_path[..layer_path.len() - 1]).ok()?;
+
+		Some(Self {
+			layer: layer_path.to_vec(),
+			corners,
+			parent_transform,
+			dragging_corner: None,
+		})
+	}
+
+	pub fn viewport_corners(&self) -> [DVec2; 4] {
+		self.corners.map(|corner| self.parent_transform.transform_point2(corner))
+	}
+
+	/// Finds the corner within the selection threshold of the cursor
+	pub fn corner_under_cursor(&self, cursor: DVec2) -> Option<usize> {
+		self.viewport_corners()
+			.into_iter()
+			.enumerate()
+			.map(|(index, corner)| (index, corner.distance(cursor)))
+			.filter(|&(_, distance)| distance < BOUNDS_SELECT_THRESHOLD)
+			.min_by(|(_, a), (_, b)| a.total_cmp(b))
+			.map(|(index, _)| index)
+	}
+
+	/// Moves the dragged corner to the viewport position and updates the layer's perspective warp
+	pub fn drag_corner(&mut self, position: DVec2, responses: &mut VecDeque<Message>) {
+		let Some(corner) = self.dragging_corner else { return };
+		self.corners[corner] = self.parent_transform.inverse().transform_point2(position);
+
+		responses.add(GraphOperationMessage::PerspectiveWarp {
+			layer: self.layer.clone(),
+			corners: self.corners,
+		});
+	}
+}
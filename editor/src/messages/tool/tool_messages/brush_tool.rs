@@ -1,5 +1,7 @@
+use crate::consts::BRUSH_PRESET_SUFFIX;
 use crate::messages::frontend::utility_types::MouseCursorIcon;
 use crate::messages::input_mapper::utility_types::input_keyboard::{Key, MouseMotion};
+use crate::messages::input_mapper::utility_types::input_mouse::StylusState;
 use crate::messages::layout::utility_types::widget_prelude::*;
 use crate::messages::portfolio::document::node_graph::transform_utils::get_current_transform;
 use crate::messages::prelude::*;
@@ -12,11 +14,13 @@ use document_legacy::layers::layer_layer::CachedOutputData;
 use document_legacy::LayerId;
 use graph_craft::document::value::TaggedValue;
 use graph_craft::document::{NodeInput, NodeNetwork};
-use graphene_core::raster::{BlendMode, ImageFrame};
-use graphene_core::vector::brush_stroke::{BrushInputSample, BrushStroke, BrushStyle};
+use graphene_core::raster::{BlendMode, Image, ImageFrame};
+use graphene_core::vector::brush_stroke::{
+	BrushDynamics, BrushDynamicsCurve, BrushDynamicsInput, BrushInputSample, BrushPreset, BrushRotation, BrushScattering, BrushStroke, BrushStyle, BrushTip, BrushTipImages,
+};
 use graphene_core::Color;
 
-use glam::DAffine2;
+use glam::{DAffine2, DVec2};
 use serde::{Deserialize, Serialize};
 
 const EXPOSED_BLEND_MODES: &[&[BlendMode]] = {
@@ -39,6 +43,9 @@ const EXPOSED_BLEND_MODES: &[&[BlendMode]] = {
 
 const BRUSH_MAX_SIZE: f64 = 5000.;
 
+/// Number of points each subpath of a vector shape is sampled at when it becomes a brush tip.
+const SHAPE_TIP_SAMPLES: usize = 64;
+
 fn blend_mode_dropdown_idx(target_blend_mode: BlendMode) -> Option<u32> {
 	let mut i = 0;
 	for group in EXPOSED_BLEND_MODES {
@@ -74,6 +81,13 @@ pub struct BrushOptions {
 	color: ToolColorOptions,
 	blend_mode: BlendMode,
 	draw_mode: DrawMode,
+	dynamics: BrushDynamics,
+	tip: BrushTip,
+	/// The images of the image tips imported or loaded with presets in this session.
+	tip_images: BrushTipImages,
+	scattering: BrushScattering,
+	presets: Vec<BrushPreset>,
+	active_preset: Option<usize>,
 }
 
 impl Default for BrushOptions {
@@ -86,10 +100,73 @@ impl Default for BrushOptions {
 			color: ToolColorOptions::default(),
 			blend_mode: BlendMode::Normal,
 			draw_mode: DrawMode::Draw,
+			dynamics: BrushDynamics::default(),
+			tip: BrushTip::Round,
+			tip_images: BrushTipImages::default(),
+			scattering: BrushScattering::default(),
+			presets: Vec::new(),
+			active_preset: None,
 		}
 	}
 }
 
+impl BrushOptions {
+	/// The brush style of the options, with the diameter in viewport pixels.
+	fn brush_style(&self) -> BrushStyle {
+		let blend_mode = match self.draw_mode {
+			DrawMode::Draw => self.blend_mode,
+			DrawMode::Erase => BlendMode::Erase,
+			DrawMode::Restore => BlendMode::Restore,
+		};
+		BrushStyle {
+			color: self.color.active_color().unwrap_or_default(),
+			diameter: self.diameter,
+			hardness: self.hardness,
+			flow: self.flow,
+			spacing: self.spacing,
+			blend_mode,
+			dynamics: self.dynamics,
+			tip: self.tip.clone(),
+			scattering: self.scattering,
+		}
+	}
+
+	/// The preset with the options' style, carrying the image of an image tip.
+	fn preset(&self, name: String) -> BrushPreset {
+		let tip_image = match self.tip {
+			BrushTip::Image(id) => self.tip_images.get(id).cloned(),
+			_ => None,
+		};
+		BrushPreset {
+			name,
+			style: self.brush_style(),
+			tip_image,
+		}
+	}
+
+	/// Takes over everything but the color from a preset.
+	fn apply_preset(&mut self, preset: &BrushPreset) {
+		let style = &preset.style;
+		self.diameter = style.diameter.clamp(1., BRUSH_MAX_SIZE);
+		self.hardness = style.hardness;
+		self.flow = style.flow;
+		self.spacing = style.spacing;
+		(self.draw_mode, self.blend_mode) = match style.blend_mode {
+			BlendMode::Erase => (DrawMode::Erase, self.blend_mode),
+			BlendMode::Restore => (DrawMode::Restore, self.blend_mode),
+			blend_mode => (DrawMode::Draw, blend_mode),
+		};
+		self.dynamics = style.dynamics;
+		// The image of an image tip is looked up again in case the preset was edited by hand, falling back to the round tip without one
+		self.tip = match (&style.tip, &preset.tip_image) {
+			(BrushTip::Image(_), Some(image)) => BrushTip::Image(self.tip_images.insert(image.clone())),
+			(BrushTip::Image(_), None) => BrushTip::Round,
+			(tip, _) => tip.clone(),
+		};
+		self.scattering = style.scattering;
+	}
+}
+
 #[remain::sorted]
 #[impl_message(Message, ToolMessage, Brush)]
 #[derive(PartialEq, Clone, Debug, Serialize, Deserialize, specta::Type)]
@@ -110,15 +187,33 @@ pub enum BrushToolMessage {
 #[remain::sorted]
 #[derive(PartialEq, Clone, Debug, Serialize, Deserialize, specta::Type)]
 pub enum BrushToolMessageOptionsUpdate {
+	Angle(f64),
 	BlendMode(BlendMode),
 	ChangeDiameter(f64),
 	Color(Option<Color>),
+	ColorJitter(f64),
 	ColorType(ToolColorType),
+	DabCount(u32),
 	Diameter(f64),
+	DiameterDynamics(BrushDynamicsInput),
 	DrawMode(DrawMode),
+	ExportPreset,
 	Flow(f64),
+	FlowDynamics(BrushDynamicsInput),
 	Hardness(f64),
+	HardnessDynamics(BrushDynamicsInput),
+	ImportPreset,
+	ImportTipImage,
+	LoadPreset(String),
+	Rotation(BrushRotation),
+	RoundTip,
+	Scatter(f64),
+	Seed(u32),
+	SelectPreset(usize),
+	ShapeTipFromSelection,
+	SizeJitter(f64),
 	Spacing(f64),
+	TipImage(Image<Color>),
 	WorkingColors(Option<Color>, Option<Color>),
 }
 
@@ -182,6 +277,33 @@ impl LayoutHolder for BrushTool {
 
 		widgets.push(Separator::new(SeparatorType::Section).widget_holder());
 
+		let dynamics = &self.options.dynamics;
+		widgets.append(&mut dynamics_widgets("Diameter", dynamics.diameter.input, BrushToolMessageOptionsUpdate::DiameterDynamics));
+		widgets.push(Separator::new(SeparatorType::Related).widget_holder());
+		widgets.append(&mut dynamics_widgets("Hardness", dynamics.hardness.input, BrushToolMessageOptionsUpdate::HardnessDynamics));
+		widgets.push(Separator::new(SeparatorType::Related).widget_holder());
+		widgets.append(&mut dynamics_widgets("Flow", dynamics.flow.input, BrushToolMessageOptionsUpdate::FlowDynamics));
+		widgets.push(Separator::new(SeparatorType::Related).widget_holder());
+		widgets.push(
+			NumberInput::new(Some(dynamics.color_jitter))
+				.label("Color Jitter")
+				.min(0.)
+				.max(100.)
+				.mode_range()
+				.unit("%")
+				.tooltip("The largest random variation of the hue, saturation and lightness of each stamp of the stroke")
+				.on_update(|number_input: &NumberInput| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::ColorJitter(number_input.value.unwrap())).into())
+				.widget_holder(),
+		);
+
+		widgets.push(Separator::new(SeparatorType::Section).widget_holder());
+		widgets.append(&mut self.tip_widgets());
+
+		widgets.push(Separator::new(SeparatorType::Section).widget_holder());
+		widgets.append(&mut self.preset_widgets());
+
+		widgets.push(Separator::new(SeparatorType::Section).widget_holder());
+
 		let draw_mode_entries: Vec<_> = [DrawMode::Draw, DrawMode::Erase, DrawMode::Restore]
 			.into_iter()
 			.map(|draw_mode| RadioEntryData::new(format!("{draw_mode:?}")).on_update(move |_| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::DrawMode(draw_mode)).into()))
@@ -225,10 +347,157 @@ impl LayoutHolder for BrushTool {
 	}
 }
 
+impl BrushTool {
+	fn tip_widgets(&self) -> Vec<WidgetHolder> {
+		let tip_entries = vec![
+			DropdownEntryData::new("Round").on_update(|_| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::RoundTip).into()),
+			DropdownEntryData::new("Image…").on_update(|_| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::ImportTipImage).into()),
+			DropdownEntryData::new("Selected Shape").on_update(|_| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::ShapeTipFromSelection).into()),
+		];
+		let tip_index = match self.options.tip {
+			BrushTip::Round => 0,
+			BrushTip::Image(_) => 1,
+			BrushTip::Shape(_) => 2,
+		};
+
+		let rotation_entries = BrushRotation::list()
+			.into_iter()
+			.map(|rotation| {
+				DropdownEntryData::new(rotation.to_string())
+					.value(rotation.to_string())
+					.on_update(move |_| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::Rotation(rotation)).into())
+			})
+			.collect();
+		let rotation_index = BrushRotation::list()
+			.iter()
+			.position(|&rotation| rotation == self.options.scattering.rotation)
+			.map(|index| index as u32);
+
+		let scattering = &self.options.scattering;
+		vec![
+			TextLabel::new("Tip").widget_holder(),
+			Separator::new(SeparatorType::Unrelated).widget_holder(),
+			DropdownInput::new(vec![tip_entries])
+				.selected_index(Some(tip_index))
+				.tooltip("The shape of the mark left at each dab: the round brush, an image loaded from a file, or the selected vector shape")
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			DropdownInput::new(vec![rotation_entries])
+				.selected_index(rotation_index)
+				.tooltip("How the tip is rotated at each dab")
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			NumberInput::new(Some(scattering.angle))
+				.label("Angle")
+				.min(-180.)
+				.max(180.)
+				.unit("°")
+				.on_update(|number_input: &NumberInput| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::Angle(number_input.value.unwrap())).into())
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			NumberInput::new(Some(scattering.scatter))
+				.label("Scatter")
+				.min(0.)
+				.max(500.)
+				.unit("%")
+				.tooltip("The largest distance, relative to the diameter, that a dab is randomly moved away from the stroke")
+				.on_update(|number_input: &NumberInput| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::Scatter(number_input.value.unwrap())).into())
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			NumberInput::new(Some(scattering.count as f64))
+				.label("Count")
+				.int()
+				.min(1.)
+				.max(16.)
+				.tooltip("The number of dabs placed at each step of the stroke")
+				.on_update(|number_input: &NumberInput| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::DabCount(number_input.value.unwrap() as u32)).into())
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			NumberInput::new(Some(scattering.size_jitter))
+				.label("Size Jitter")
+				.min(0.)
+				.max(100.)
+				.mode_range()
+				.unit("%")
+				.tooltip("The largest random reduction of the diameter of each dab")
+				.on_update(|number_input: &NumberInput| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::SizeJitter(number_input.value.unwrap())).into())
+				.widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			NumberInput::new(Some(scattering.seed as f64))
+				.label("Seed")
+				.int()
+				.min(0.)
+				.tooltip("Picks the random rotation, scatter and size jitter of the dabs")
+				.on_update(|number_input: &NumberInput| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::Seed(number_input.value.unwrap() as u32)).into())
+				.widget_holder(),
+		]
+	}
+
+	fn preset_widgets(&self) -> Vec<WidgetHolder> {
+		let mut widgets = Vec::new();
+
+		if !self.options.presets.is_empty() {
+			let preset_entries = self
+				.options
+				.presets
+				.iter()
+				.enumerate()
+				.map(|(index, preset)| DropdownEntryData::new(preset.name.clone()).on_update(move |_| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::SelectPreset(index)).into()))
+				.collect();
+			widgets.push(
+				DropdownInput::new(vec![preset_entries])
+					.selected_index(self.options.active_preset.map(|index| index as u32))
+					.tooltip("The brush presets saved or loaded in this session")
+					.widget_holder(),
+			);
+			widgets.push(Separator::new(SeparatorType::Related).widget_holder());
+		}
+
+		widgets.push(
+			TextButton::new("Save Preset")
+				.tooltip("Save the brush settings to a file which can be shared and loaded again")
+				.on_update(|_| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::ExportPreset).into())
+				.widget_holder(),
+		);
+		widgets.push(Separator::new(SeparatorType::Related).widget_holder());
+		widgets.push(
+			TextButton::new("Load Preset")
+				.tooltip("Load the brush settings from a preset file")
+				.on_update(|_| BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::ImportPreset).into())
+				.widget_holder(),
+		);
+
+		widgets
+	}
+}
+
+/// A label and dropdown choosing the stylus input which varies a brush parameter along the stroke.
+fn dynamics_widgets(parameter: &'static str, selected: BrushDynamicsInput, update: fn(BrushDynamicsInput) -> BrushToolMessageOptionsUpdate) -> Vec<WidgetHolder> {
+	let entries = BrushDynamicsInput::list()
+		.into_iter()
+		.map(|input| {
+			DropdownEntryData::new(input.to_string())
+				.value(input.to_string())
+				.on_update(move |_| BrushToolMessage::UpdateOptions(update(input)).into())
+		})
+		.collect();
+	let selected_index = BrushDynamicsInput::list().iter().position(|&input| input == selected).map(|index| index as u32);
+
+	vec![
+		TextLabel::new(format!("{parameter} Dynamics")).widget_holder(),
+		Separator::new(SeparatorType::Unrelated).widget_holder(),
+		DropdownInput::new(vec![entries])
+			.selected_index(selected_index)
+			.tooltip(format!("The stylus input which varies the {} along the stroke", parameter.to_lowercase()))
+			.widget_holder(),
+	]
+}
+
 impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for BrushTool {
 	fn process_message(&mut self, message: ToolMessage, responses: &mut VecDeque<Message>, tool_data: &mut ToolActionHandlerData<'a>) {
 		if let ToolMessage::Brush(BrushToolMessage::UpdateOptions(action)) = message {
 			match action {
+				BrushToolMessageOptionsUpdate::Angle(angle) => self.options.scattering.angle = angle,
 				BrushToolMessageOptionsUpdate::BlendMode(blend_mode) => self.options.blend_mode = blend_mode,
 				BrushToolMessageOptionsUpdate::ChangeDiameter(change) => {
 					let needs_rounding = ((self.options.diameter + change.abs() / 2.) % change.abs() - change.abs() / 2.).abs() > 0.5;
@@ -242,15 +511,76 @@ impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for BrushTo
 					self.options.diameter = self.options.diameter.max(1.);
 					self.send_layout(responses, LayoutTarget::ToolOptions);
 				}
+				BrushToolMessageOptionsUpdate::DabCount(count) => self.options.scattering.count = count.max(1),
 				BrushToolMessageOptionsUpdate::Diameter(diameter) => self.options.diameter = diameter,
+				BrushToolMessageOptionsUpdate::DiameterDynamics(input) => self.options.dynamics.diameter = BrushDynamicsCurve::new(input),
 				BrushToolMessageOptionsUpdate::DrawMode(draw_mode) => self.options.draw_mode = draw_mode,
+				BrushToolMessageOptionsUpdate::ExportPreset => {
+					let name = format!("Brush {}", self.options.presets.len() + 1);
+					let preset = self.options.preset(name);
+					match serde_json::to_string_pretty(&preset) {
+						Ok(document) => responses.add(FrontendMessage::TriggerDownloadTextFile {
+							document,
+							name: format!("{}{BRUSH_PRESET_SUFFIX}", preset.name),
+						}),
+						Err(error) => log::error!("Failed to serialize the brush preset: {error}"),
+					}
+					self.options.active_preset = Some(self.options.presets.len());
+					self.options.presets.push(preset);
+				}
 				BrushToolMessageOptionsUpdate::Hardness(hardness) => self.options.hardness = hardness,
+				BrushToolMessageOptionsUpdate::HardnessDynamics(input) => self.options.dynamics.hardness = BrushDynamicsCurve::new(input),
 				BrushToolMessageOptionsUpdate::Flow(flow) => self.options.flow = flow,
+				BrushToolMessageOptionsUpdate::FlowDynamics(input) => self.options.dynamics.flow = BrushDynamicsCurve::new(input),
+				BrushToolMessageOptionsUpdate::ImportPreset => responses.add(FrontendMessage::TriggerImportBrushPreset),
+				BrushToolMessageOptionsUpdate::ImportTipImage => responses.add(FrontendMessage::TriggerImportBrushTip),
+				BrushToolMessageOptionsUpdate::LoadPreset(preset) => match serde_json::from_str::<BrushPreset>(&preset) {
+					Ok(preset) => {
+						self.options.apply_preset(&preset);
+						// A preset loaded again replaces the one with the same name
+						let index = self.options.presets.iter().position(|existing| existing.name == preset.name).unwrap_or(self.options.presets.len());
+						if index == self.options.presets.len() {
+							self.options.presets.push(preset);
+						} else {
+							self.options.presets[index] = preset;
+						}
+						self.options.active_preset = Some(index);
+					}
+					Err(error) => responses.add(DialogMessage::DisplayDialogError {
+						title: "Failed to load the brush preset".into(),
+						description: format!("The file is not a valid brush preset:\n{error}"),
+					}),
+				},
+				BrushToolMessageOptionsUpdate::Rotation(rotation) => self.options.scattering.rotation = rotation,
+				BrushToolMessageOptionsUpdate::RoundTip => self.options.tip = BrushTip::Round,
+				BrushToolMessageOptionsUpdate::Scatter(scatter) => self.options.scattering.scatter = scatter,
+				BrushToolMessageOptionsUpdate::Seed(seed) => self.options.scattering.seed = seed,
+				BrushToolMessageOptionsUpdate::SelectPreset(index) => {
+					if let Some(preset) = self.options.presets.get(index) {
+						let preset = preset.clone();
+						self.options.apply_preset(&preset);
+						self.options.active_preset = Some(index);
+					}
+				}
+				BrushToolMessageOptionsUpdate::ShapeTipFromSelection => {
+					let polygons = selected_shape_polygons(tool_data.document);
+					if polygons.is_empty() {
+						responses.add(DialogMessage::DisplayDialogError {
+							title: "No shape selected".into(),
+							description: "Select a vector layer to use its shape as the brush tip.".into(),
+						});
+					} else {
+						self.options.tip = BrushTip::shape(polygons);
+					}
+				}
+				BrushToolMessageOptionsUpdate::SizeJitter(size_jitter) => self.options.scattering.size_jitter = size_jitter,
 				BrushToolMessageOptionsUpdate::Spacing(spacing) => self.options.spacing = spacing,
+				BrushToolMessageOptionsUpdate::TipImage(image) => self.options.tip = BrushTip::Image(self.options.tip_images.insert(image)),
 				BrushToolMessageOptionsUpdate::Color(color) => {
 					self.options.color.custom_color = color;
 					self.options.color.color_type = ToolColorType::Custom;
 				}
+				BrushToolMessageOptionsUpdate::ColorJitter(color_jitter) => self.options.dynamics.color_jitter = color_jitter,
 				BrushToolMessageOptionsUpdate::ColorType(color_type) => self.options.color.color_type = color_type,
 				BrushToolMessageOptionsUpdate::WorkingColors(primary, secondary) => {
 					self.options.color.primary_working_color = primary;
@@ -299,6 +629,8 @@ impl ToolTransition for BrushTool {
 #[derive(Clone, Debug, Default)]
 struct BrushToolData {
 	strokes: Vec<BrushStroke>,
+	/// The images of the image tips used by the strokes, which the brush node holds once rather than each stroke holding its own.
+	tip_images: BrushTipImages,
 	layer_path: Vec<LayerId>,
 	transform: DAffine2,
 }
@@ -323,6 +655,13 @@ impl BrushToolData {
 					continue;
 				};
 				self.strokes = strokes.clone();
+				self.tip_images = match node.inputs.get(4) {
+					Some(NodeInput::Value {
+						tagged_value: TaggedValue::BrushTipImages(tip_images),
+						..
+					}) => tip_images.clone(),
+					_ => BrushTipImages::default(),
+				};
 
 				return Some(&self.layer_path);
 			} else if node.name == "Transform" {
@@ -338,7 +677,8 @@ impl BrushToolData {
 	fn update_strokes(&self, responses: &mut VecDeque<Message>) {
 		let layer = self.layer_path.clone();
 		let strokes = self.strokes.clone();
-		responses.add(GraphOperationMessage::Brush { layer, strokes });
+		let tips = self.tip_images.clone();
+		responses.add(GraphOperationMessage::Brush { layer, strokes, tips });
 	}
 }
 
@@ -377,20 +717,13 @@ impl Fsm for BrushToolFsmState {
 						.max((tool_data.transform.matrix2 * glam::DVec2::Y).length());
 
 					// Start a new stroke with a single sample
-					let blend_mode = match tool_options.draw_mode {
-						DrawMode::Draw => tool_options.blend_mode,
-						DrawMode::Erase => BlendMode::Erase,
-						DrawMode::Restore => BlendMode::Restore,
-					};
+					let style = tool_options.brush_style();
+					tool_data.tip_images.copy_tip(&style.tip, &tool_options.tip_images);
 					tool_data.strokes.push(BrushStroke {
-						trace: vec![BrushInputSample { position: layer_position }],
+						trace: vec![brush_input_sample(layer_position, &input.mouse.stylus)],
 						style: BrushStyle {
-							color: tool_options.color.active_color().unwrap_or_default(),
-							diameter: tool_options.diameter / layer_scale,
-							hardness: tool_options.hardness,
-							flow: tool_options.flow,
-							spacing: tool_options.spacing,
-							blend_mode,
+							diameter: style.diameter / layer_scale,
+							..style
 						},
 					});
 
@@ -404,7 +737,7 @@ impl Fsm for BrushToolFsmState {
 
 				(BrushToolFsmState::Drawing, BrushToolMessage::PointerMove) => {
 					if let Some(stroke) = tool_data.strokes.last_mut() {
-						stroke.trace.push(BrushInputSample { position: layer_position })
+						stroke.trace.push(brush_input_sample(layer_position, &input.mouse.stylus))
 					}
 					tool_data.update_strokes(responses);
 
@@ -419,6 +752,7 @@ impl Fsm for BrushToolFsmState {
 					}
 
 					tool_data.strokes.clear();
+					tool_data.tip_images = BrushTipImages::default();
 
 					BrushToolFsmState::Ready
 				}
@@ -454,6 +788,40 @@ impl Fsm for BrushToolFsmState {
 	}
 }
 
+/// The outlines of the selected vector layers in document space.
+fn selected_shape_polygons(document: &DocumentMessageHandler) -> Vec<Vec<DVec2>> {
+	document
+		.selected_layers()
+		.filter_map(|path| {
+			let vector_data = document.document_legacy.layer(path).ok()?.as_vector_data()?;
+			let transform = document.document_legacy.multiply_transforms(path).ok()? * vector_data.transform;
+			let polygons = vector_data
+				.subpaths
+				.iter()
+				.map(|subpath| {
+					subpath
+						.compute_lookup_table(Some(SHAPE_TIP_SAMPLES), None)
+						.into_iter()
+						.map(|point| transform.transform_point2(point))
+						.collect()
+				})
+				.collect::<Vec<_>>();
+			Some(polygons)
+		})
+		.flatten()
+		.collect()
+}
+
+fn brush_input_sample(position: DVec2, stylus: &StylusState) -> BrushInputSample {
+	BrushInputSample {
+		position,
+		pressure: stylus.pressure,
+		tilt: stylus.tilt,
+		twist: stylus.twist,
+		timestamp: stylus.timestamp,
+	}
+}
+
 fn add_brush_render(_tool_options: &BrushOptions, data: &BrushToolData, responses: &mut VecDeque<Message>) {
 	let mut network = NodeNetwork::default();
 	let output_node = network.push_output_node();
@@ -9,6 +9,7 @@ use crate::messages::tool::utility_types::{HintData, HintGroup, HintInfo};
 use document_legacy::intersection::Quad;
 use document_legacy::layers::layer_layer::CachedOutputData;
 use document_legacy::layers::style::Fill;
+use graphene_core::raster::{FloodFill, FloodFillMode};
 
 use glam::DVec2;
 use serde::{Deserialize, Serialize};
@@ -17,6 +18,24 @@ use serde::{Deserialize, Serialize};
 pub struct FillTool {
 	fsm_state: FillToolFsmState,
 	data: FillToolData,
+	options: FillOptions,
+}
+
+/// Options used when filling raster layers, vector layers are always filled completely.
+pub struct FillOptions {
+	tolerance: u32,
+	mode: FloodFillMode,
+	anti_alias: bool,
+}
+
+impl Default for FillOptions {
+	fn default() -> Self {
+		Self {
+			tolerance: 10,
+			mode: FloodFillMode::Contiguous,
+			anti_alias: true,
+		}
+	}
 }
 
 #[remain::sorted]
@@ -30,6 +49,15 @@ pub enum FillToolMessage {
 	// Tool-specific messages
 	LeftPointerDown,
 	RightPointerDown,
+	UpdateOptions(FillToolMessageOptionsUpdate),
+}
+
+#[remain::sorted]
+#[derive(PartialEq, Eq, Clone, Debug, Hash, Serialize, Deserialize, specta::Type)]
+pub enum FillToolMessageOptionsUpdate {
+	AntiAlias(bool),
+	Mode(FloodFillMode),
+	Tolerance(u32),
 }
 
 impl ToolMetadata for FillTool {
@@ -46,18 +74,58 @@ impl ToolMetadata for FillTool {
 
 impl LayoutHolder for FillTool {
 	fn layout(&self) -> Layout {
-		Layout::WidgetLayout(WidgetLayout::default())
+		let mode_entries = FloodFillMode::list()
+			.into_iter()
+			.map(|mode| RadioEntryData::new(mode.to_string()).on_update(move |_| FillToolMessage::UpdateOptions(FillToolMessageOptionsUpdate::Mode(mode)).into()))
+			.collect();
+
+		let widgets = vec![
+			NumberInput::new(Some(self.options.tolerance as f64))
+				.label("Tolerance")
+				.int()
+				.min(0.)
+				.max(100.)
+				.mode_range()
+				.unit("%")
+				.tooltip("How much the color of a pixel may differ from the clicked pixel to be filled. Only used for raster layers.")
+				.on_update(|number_input: &NumberInput| FillToolMessage::UpdateOptions(FillToolMessageOptionsUpdate::Tolerance(number_input.value.unwrap() as u32)).into())
+				.widget_holder(),
+			Separator::new(SeparatorType::Section).widget_holder(),
+			RadioInput::new(mode_entries).selected_index(self.options.mode as u32).widget_holder(),
+			Separator::new(SeparatorType::Section).widget_holder(),
+			TextLabel::new("Anti-aliasing").widget_holder(),
+			Separator::new(SeparatorType::Related).widget_holder(),
+			CheckboxInput::new(self.options.anti_alias)
+				.tooltip("Soften the border of the filled region")
+				.on_update(|checkbox_input: &CheckboxInput| FillToolMessage::UpdateOptions(FillToolMessageOptionsUpdate::AntiAlias(checkbox_input.checked)).into())
+				.widget_holder(),
+		];
+
+		Layout::WidgetLayout(WidgetLayout::new(vec![LayoutGroup::Row { widgets }]))
 	}
 }
 
 impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for FillTool {
 	fn process_message(&mut self, message: ToolMessage, responses: &mut VecDeque<Message>, tool_data: &mut ToolActionHandlerData<'a>) {
-		self.fsm_state.process_event(message, &mut self.data, tool_data, &(), responses, true);
+		if let ToolMessage::Fill(FillToolMessage::UpdateOptions(action)) = message {
+			match action {
+				FillToolMessageOptionsUpdate::AntiAlias(anti_alias) => self.options.anti_alias = anti_alias,
+				FillToolMessageOptionsUpdate::Mode(mode) => self.options.mode = mode,
+				FillToolMessageOptionsUpdate::Tolerance(tolerance) => self.options.tolerance = tolerance,
+			}
+
+			self.send_layout(responses, LayoutTarget::ToolOptions);
+
+			return;
+		}
+
+		self.fsm_state.process_event(message, &mut self.data, tool_data, &self.options, responses, true);
 	}
 
 	advertise_actions!(FillToolMessageDiscriminant;
 		LeftPointerDown,
 		RightPointerDown,
+		UpdateOptions,
 	);
 }
 
@@ -81,7 +149,7 @@ struct FillToolData {}
 
 impl Fsm for FillToolFsmState {
 	type ToolData = FillToolData;
-	type ToolOptions = ();
+	type ToolOptions = FillOptions;
 
 	fn transition(
 		self,
@@ -94,7 +162,7 @@ impl Fsm for FillToolFsmState {
 			render_data,
 			..
 		}: &mut ToolActionHandlerData,
-		_tool_options: &Self::ToolOptions,
+		tool_options: &Self::ToolOptions,
 		responses: &mut VecDeque<Message>,
 	) -> Self {
 		use FillToolFsmState::*;
@@ -115,22 +183,33 @@ impl Fsm for FillToolFsmState {
 							.and_then(|layer| layer.as_layer().ok())
 							.map_or(false, |layer| matches!(layer.cached_output_data, CachedOutputData::BlobURL(_) | CachedOutputData::SurfaceId(_)));
 
-						if is_bitmap {
-							return self;
-						}
-
 						let color = match lmb_or_rmb {
 							LeftPointerDown => global_tool_data.primary_color,
 							RightPointerDown => global_tool_data.secondary_color,
-							Abort => unreachable!(),
+							Abort | UpdateOptions(_) => unreachable!(),
 						};
-						let fill = Fill::Solid(color);
 
 						responses.add(DocumentMessage::StartTransaction);
 						responses.add(DocumentMessage::SetSelectedLayers {
 							replacement_selected_layers: vec![path.to_vec()],
 						});
-						responses.add(GraphOperationMessage::FillSet { layer: path.to_vec(), fill });
+						if is_bitmap {
+							// The image frame produced by the layer's graph lives in the space of the layer's parent
+							let parent_transform = document.document_legacy.multiply_transforms(&path[..path.len() - 1]).unwrap_or_default();
+							let position = parent_transform.inverse().transform_point2(mouse_pos);
+
+							let fill = FloodFill {
+								position,
+								color,
+								tolerance: tool_options.tolerance as f32,
+								mode: tool_options.mode,
+								anti_alias: tool_options.anti_alias,
+							};
+							responses.add(GraphOperationMessage::FloodFill { layer: path.to_vec(), fill });
+						} else {
+							let fill = Fill::Solid(color);
+							responses.add(GraphOperationMessage::FillSet { layer: path.to_vec(), fill });
+						}
 						responses.add(DocumentMessage::CommitTransaction);
 					}
 
@@ -40,6 +40,7 @@ pub struct SelectOptions {
 #[remain::sorted]
 #[derive(PartialEq, Eq, Clone, Debug, Hash, Serialize, Deserialize, specta::Type)]
 pub enum SelectOptionsUpdate {
+	DistortCorners(bool),
 	NestedSelectionBehavior(NestedSelectionBehavior),
 }
 
@@ -141,6 +142,13 @@ impl LayoutHolder for SelectTool {
 					.on_update(|pivot_assist: &PivotAssist| SelectToolMessage::SetPivot { position: pivot_assist.position }.into())
 					.widget_holder(),
 				Separator::new(SeparatorType::Section).widget_holder(),
+				TextLabel::new("Distort").widget_holder(),
+				Separator::new(SeparatorType::Related).widget_holder(),
+				CheckboxInput::new(self.tool_data.distort_corners)
+					.tooltip("Drag the corners of a single selected raster layer to move them independently, rather than to resize the layer")
+					.on_update(|checkbox_input: &CheckboxInput| SelectToolMessage::SelectOptions(SelectOptionsUpdate::DistortCorners(checkbox_input.checked)).into())
+					.widget_holder(),
+				Separator::new(SeparatorType::Section).widget_holder(),
 				IconButton::new("AlignLeft", 24)
 					.tooltip("Align Left")
 					.disabled(deactivate_alignment)
@@ -258,8 +266,14 @@ impl LayoutHolder for SelectTool {
 
 impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for SelectTool {
 	fn process_message(&mut self, message: ToolMessage, responses: &mut VecDeque<Message>, tool_data: &mut ToolActionHandlerData<'a>) {
-		if let ToolMessage::Select(SelectToolMessage::SelectOptions(SelectOptionsUpdate::NestedSelectionBehavior(nested_selection_behavior))) = message {
-			self.tool_data.nested_selection_behavior = nested_selection_behavior;
+		if let ToolMessage::Select(SelectToolMessage::SelectOptions(update)) = &message {
+			match *update {
+				SelectOptionsUpdate::DistortCorners(distort_corners) => {
+					self.tool_data.distort_corners = distort_corners;
+					self.send_layout(responses, LayoutTarget::ToolOptions);
+				}
+				SelectOptionsUpdate::NestedSelectionBehavior(nested_selection_behavior) => self.tool_data.nested_selection_behavior = nested_selection_behavior,
+			}
 			responses.add(ToolMessage::UpdateHints);
 		}
 
@@ -314,6 +328,7 @@ enum SelectToolFsmState {
 	ResizingBounds,
 	RotatingBounds,
 	DraggingPivot,
+	Distorting,
 }
 
 #[derive(Clone, Debug, Default)]
@@ -327,10 +342,12 @@ struct SelectToolData {
 	drag_box_overlay_layer: Option<Vec<LayerId>>,
 	path_outlines: PathOutline,
 	bounding_box_overlays: Option<BoundingBoxOverlays>,
+	distort_cage: Option<DistortCage>,
 	snap_manager: SnapManager,
 	cursor: MouseCursorIcon,
 	pivot: Pivot,
 	nested_selection_behavior: NestedSelectionBehavior,
+	distort_corners: bool,
 	selected_layers_count: usize,
 	selected_layers_changed: bool,
 }
@@ -526,12 +543,36 @@ impl Fsm for SelectToolFsmState {
 					let quad = tool_data.selection_quad();
 					let mut intersection = document.document_legacy.intersects_quad_root(quad, render_data);
 
+					// Distorting is only possible for a single selected raster layer, by dragging one of its corners while the distort option is on
+					let distort_cage = match selected.as_slice() {
+						[layer_path] if tool_data.distort_corners => DistortCage::new(document, layer_path).and_then(|mut distort_cage| {
+							distort_cage.dragging_corner = distort_cage.corner_under_cursor(input.mouse.position);
+							distort_cage.dragging_corner.is_some().then_some(distort_cage)
+						}),
+						_ => None,
+					};
+
+					// If the user is dragging a corner of a raster layer with the distort option on, go into Distorting mode.
 					// If the user is dragging the bounding box bounds, go into ResizingBounds mode.
 					// If the user is dragging the rotate trigger, go into RotatingBounds mode.
 					// If the user clicks on a layer that is in their current selection, go into the dragging mode.
 					// If the user clicks on new shape, make that layer their new selection.
 					// Otherwise enter the box select mode
-					let state = if tool_data.pivot.is_over(input.mouse.position) {
+					let state = if let Some(distort_cage) = distort_cage {
+						responses.add(DocumentMessage::StartTransaction);
+
+						tool_data
+							.snap_manager
+							.start_snap(document, input, document.bounding_boxes(Some(&selected), None, render_data), true, true);
+						tool_data
+							.snap_manager
+							.add_all_document_handles(document, input, &[], &selected.iter().map(|x| x.as_slice()).collect::<Vec<_>>(), &[]);
+
+						tool_data.layers_dragging = selected;
+						tool_data.distort_cage = Some(distort_cage);
+
+						Distorting
+					} else if tool_data.pivot.is_over(input.mouse.position) {
 						responses.add(DocumentMessage::StartTransaction);
 
 						tool_data.snap_manager.start_snap(document, input, document.bounding_boxes(None, None, render_data), true, true);
@@ -732,6 +773,14 @@ impl Fsm for SelectToolFsmState {
 
 					DraggingPivot
 				}
+				(Distorting, PointerMove { .. }) => {
+					let snapped_mouse_position = tool_data.snap_manager.snap_position(responses, document, input.mouse.position);
+					if let Some(distort_cage) = &mut tool_data.distort_cage {
+						distort_cage.drag_corner(snapped_mouse_position, responses);
+					}
+
+					Distorting
+				}
 				(DrawingBox, PointerMove { .. }) => {
 					tool_data.drag_current = input.mouse.position;
 
@@ -836,6 +885,18 @@ impl Fsm for SelectToolFsmState {
 
 					Ready
 				}
+				(Distorting, DragStop { .. } | Enter) => {
+					let response = match input.mouse.position.distance(tool_data.drag_start) < 10. * f64::EPSILON {
+						true => DocumentMessage::Undo,
+						false => DocumentMessage::CommitTransaction,
+					};
+					responses.add(response);
+
+					tool_data.snap_manager.cleanup(responses);
+					tool_data.distort_cage = None;
+
+					Ready
+				}
 				(DraggingPivot, DragStop { .. } | Enter) => {
 					let response = match input.mouse.position.distance(tool_data.drag_start) < 10. * f64::EPSILON {
 						true => DocumentMessage::Undo,
@@ -870,7 +931,7 @@ impl Fsm for SelectToolFsmState {
 						if selected_layers.next().is_none() {
 							if let Ok(layer) = document.document_legacy.layer(layer_path) {
 								if let Ok(network) = layer.as_layer_network() {
-									if network.nodes.values().any(|node| node.name == "Text") {
+									if network.nodes.values().any(|node| matches!(node.name.as_str(), "Text" | "Rich Text")) {
 										responses.add_front(ToolMessage::ActivateTool { tool_type: ToolType::Text });
 										responses.add(TextToolMessage::EditSelected);
 									}
@@ -892,6 +953,13 @@ impl Fsm for SelectToolFsmState {
 
 					Ready
 				}
+				(Distorting, Abort) => {
+					tool_data.snap_manager.cleanup(responses);
+					tool_data.distort_cage = None;
+					responses.add(DocumentMessage::Undo);
+
+					Ready
+				}
 				(_, Abort) => {
 					if let Some(path) = tool_data.drag_box_overlay_layer.take() {
 						responses.add_front(DocumentMessage::Overlays(Operation::DeleteLayer { path }.into()))
@@ -954,7 +1022,7 @@ impl Fsm for SelectToolFsmState {
 		// Check for standard hits or cursor events
 		match message {
 			ToolMessage::UpdateHints => {
-				let hint_data = HintData(vec![
+				let mut hint_data = HintData(vec![
 					HintGroup(vec![HintInfo::mouse(MouseMotion::LmbDrag, "Drag Selected")]),
 					HintGroup(vec![HintInfo::keys([Key::KeyG, Key::KeyR, Key::KeyS], "Grab/Rotate/Scale Selected")]),
 					HintGroup({
@@ -979,6 +1047,9 @@ impl Fsm for SelectToolFsmState {
 						HintInfo::keys([Key::Control, Key::KeyD], "Duplicate").add_mac_keys([Key::Command, Key::KeyD]),
 					]),
 				]);
+				if tool_data.distort_corners {
+					hint_data.0.insert(1, HintGroup(vec![HintInfo::mouse(MouseMotion::LmbDrag, "Distort Raster Corner")]));
+				}
 
 				responses.add(FrontendMessage::UpdateInputHints { hint_data });
 				self.update_hints(responses);
@@ -1346,7 +1417,7 @@ fn edit_layer_deepest_manipulation(intersect: &Layer, responses: &mut VecDeque<M
 			responses.add_front(ToolMessage::ActivateTool { tool_type: ToolType::Path });
 		}
 		LayerDataType::Layer(layer) if layer.as_vector_data().is_some() => {
-			if layer.network.nodes.values().any(|node| node.name == "Text") {
+			if layer.network.nodes.values().any(|node| matches!(node.name.as_str(), "Text" | "Rich Text")) {
 				responses.add_front(ToolMessage::ActivateTool { tool_type: ToolType::Text });
 				responses.add(TextToolMessage::EditSelected);
 			} else {
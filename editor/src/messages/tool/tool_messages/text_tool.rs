@@ -5,7 +5,7 @@ use crate::consts::{COLOR_ACCENT, SELECTION_TOLERANCE};
 use crate::messages::frontend::utility_types::MouseCursorIcon;
 use crate::messages::input_mapper::utility_types::input_keyboard::{Key, MouseMotion};
 use crate::messages::layout::utility_types::widget_prelude::*;
-use crate::messages::portfolio::document::node_graph::new_text_network;
+use crate::messages::portfolio::document::node_graph::{new_rich_text_network, new_text_network};
 use crate::messages::prelude::*;
 use crate::messages::tool::common_functionality::color_selector::{ToolColorOptions, ToolColorType};
 use crate::messages::tool::utility_types::{EventToMessageMap, Fsm, ToolActionHandlerData, ToolMetadata, ToolTransition, ToolType};
@@ -18,7 +18,7 @@ use document_legacy::LayerId;
 use document_legacy::Operation;
 use graph_craft::document::value::TaggedValue;
 use graph_craft::document::{DocumentNode, NodeId, NodeInput, NodeNetwork};
-use graphene_core::text::{load_face, Font};
+use graphene_core::text::{load_faces, Font, FontCache, FontVariation, RichText, TextAlign, TextStyle, TypesettingConfig};
 use graphene_core::Color;
 
 use glam::{DAffine2, DVec2};
@@ -35,6 +35,11 @@ pub struct TextOptions {
 	font_size: u32,
 	font_name: String,
 	font_style: String,
+	line_height_ratio: f64,
+	letter_spacing: f64,
+	paragraph_spacing: f64,
+	align: TextAlign,
+	rich_text: bool,
 	fill: ToolColorOptions,
 }
 
@@ -44,6 +49,11 @@ impl Default for TextOptions {
 			font_size: 24,
 			font_name: "Merriweather".into(),
 			font_style: "Normal (400)".into(),
+			line_height_ratio: 1.2,
+			letter_spacing: 0.,
+			paragraph_spacing: 0.,
+			align: TextAlign::default(),
+			rich_text: false,
 			fill: ToolColorOptions::new_primary(),
 		}
 	}
@@ -77,10 +87,15 @@ pub enum TextToolMessage {
 #[remain::sorted]
 #[derive(PartialEq, Clone, Debug, Serialize, Deserialize, specta::Type)]
 pub enum TextOptionsUpdate {
+	Align(TextAlign),
 	FillColor(Option<Color>),
 	FillColorType(ToolColorType),
 	Font { family: String, style: String },
 	FontSize(u32),
+	LetterSpacing(f64),
+	LineHeightRatio(f64),
+	ParagraphSpacing(f64),
+	RichText(bool),
 	WorkingColors(Option<Color>, Option<Color>),
 }
 
@@ -125,12 +140,50 @@ fn create_text_widgets(tool: &TextTool) -> Vec<WidgetHolder> {
 		.max((1u64 << std::f64::MANTISSA_DIGITS) as f64)
 		.on_update(|number_input: &NumberInput| TextToolMessage::UpdateOptions(TextOptionsUpdate::FontSize(number_input.value.unwrap() as u32)).into())
 		.widget_holder();
+	let line_height_ratio = NumberInput::new(Some(tool.options.line_height_ratio))
+		.label("Line Height")
+		.unit("x")
+		.min(0.)
+		.step(0.1)
+		.on_update(|number_input: &NumberInput| TextToolMessage::UpdateOptions(TextOptionsUpdate::LineHeightRatio(number_input.value.unwrap())).into())
+		.widget_holder();
+	let letter_spacing = NumberInput::new(Some(tool.options.letter_spacing))
+		.label("Letter Spacing")
+		.unit(" px")
+		.on_update(|number_input: &NumberInput| TextToolMessage::UpdateOptions(TextOptionsUpdate::LetterSpacing(number_input.value.unwrap())).into())
+		.widget_holder();
+	let paragraph_spacing = NumberInput::new(Some(tool.options.paragraph_spacing))
+		.label("Paragraph Spacing")
+		.unit(" px")
+		.on_update(|number_input: &NumberInput| TextToolMessage::UpdateOptions(TextOptionsUpdate::ParagraphSpacing(number_input.value.unwrap())).into())
+		.widget_holder();
+	let align_entries = vec![TextAlign::list()
+		.into_iter()
+		.map(|align| DropdownEntryData::new(align.to_string()).on_update(move |_| TextToolMessage::UpdateOptions(TextOptionsUpdate::Align(align)).into()))
+		.collect()];
+	let align = DropdownInput::new(align_entries).selected_index(Some(tool.options.align as u32)).widget_holder();
+	let rich_text = CheckboxInput::new(tool.options.rich_text)
+		.tooltip("Create new text as rich text, whose runs can each have their own font, size and fill")
+		.on_update(|checkbox: &CheckboxInput| TextToolMessage::UpdateOptions(TextOptionsUpdate::RichText(checkbox.checked)).into())
+		.widget_holder();
 	vec![
 		font,
 		Separator::new(SeparatorType::Related).widget_holder(),
 		style,
 		Separator::new(SeparatorType::Related).widget_holder(),
 		size,
+		Separator::new(SeparatorType::Section).widget_holder(),
+		align,
+		Separator::new(SeparatorType::Related).widget_holder(),
+		line_height_ratio,
+		Separator::new(SeparatorType::Related).widget_holder(),
+		letter_spacing,
+		Separator::new(SeparatorType::Related).widget_holder(),
+		paragraph_spacing,
+		Separator::new(SeparatorType::Section).widget_holder(),
+		rich_text,
+		Separator::new(SeparatorType::Related).widget_holder(),
+		TextLabel::new("Rich Text").widget_holder(),
 	]
 }
 
@@ -155,6 +208,15 @@ impl LayoutHolder for TextTool {
 impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for TextTool {
 	fn process_message(&mut self, message: ToolMessage, responses: &mut VecDeque<Message>, tool_data: &mut ToolActionHandlerData<'a>) {
 		if let ToolMessage::Text(TextToolMessage::UpdateOptions(action)) = message {
+			// Changing the style while editing rich text applies it to the text typed from then on
+			let restyles = matches!(
+				action,
+				TextOptionsUpdate::Font { .. } | TextOptionsUpdate::FontSize(_) | TextOptionsUpdate::FillColor(_) | TextOptionsUpdate::FillColorType(_)
+			);
+			if restyles && self.fsm_state == TextToolFsmState::Editing {
+				self.tool_data.style_changed = true;
+			}
+
 			match action {
 				TextOptionsUpdate::Font { family, style } => {
 					self.options.font_name = family;
@@ -163,6 +225,11 @@ impl<'a> MessageHandler<ToolMessage, &mut ToolActionHandlerData<'a>> for TextToo
 					self.send_layout(responses, LayoutTarget::ToolOptions);
 				}
 				TextOptionsUpdate::FontSize(font_size) => self.options.font_size = font_size,
+				TextOptionsUpdate::LineHeightRatio(line_height_ratio) => self.options.line_height_ratio = line_height_ratio,
+				TextOptionsUpdate::LetterSpacing(letter_spacing) => self.options.letter_spacing = letter_spacing,
+				TextOptionsUpdate::ParagraphSpacing(paragraph_spacing) => self.options.paragraph_spacing = paragraph_spacing,
+				TextOptionsUpdate::Align(align) => self.options.align = align,
+				TextOptionsUpdate::RichText(rich_text) => self.options.rich_text = rich_text,
 				TextOptionsUpdate::FillColor(color) => {
 					self.options.fill.custom_color = color;
 					self.options.fill.color_type = ToolColorType::Custom;
@@ -219,8 +286,10 @@ enum TextToolFsmState {
 #[derive(Clone, Debug)]
 pub struct EditingText {
 	text: String,
+	/// The runs of the text if it is rich text, whose plain text is edited in the text box.
+	rich_text: Option<RichText>,
 	font: Font,
-	font_size: f64,
+	typesetting: TypesettingConfig,
 	color: Option<Color>,
 	transform: DAffine2,
 }
@@ -231,6 +300,8 @@ struct TextToolData {
 	overlays: Vec<Vec<LayerId>>,
 	editing_text: Option<EditingText>,
 	new_text: String,
+	/// Whether the style in the tool options was changed while editing, so the newly typed text of rich text takes it.
+	style_changed: bool,
 }
 
 impl TextToolData {
@@ -242,8 +313,8 @@ impl TextToolData {
 		if let Some(editing_text) = self.editing_text.as_ref().filter(|_| editable) {
 			responses.add(FrontendMessage::DisplayEditableTextbox {
 				text: editing_text.text.clone(),
-				line_width: None,
-				font_size: editing_text.font_size,
+				line_width: editing_text.typesetting.max_width,
+				font_size: editing_text.typesetting.font_size,
 				color: editing_text.color.unwrap_or(Color::BLACK),
 				url: render_data.font_cache.get_preview_url(&editing_text.font).cloned().unwrap_or_default(),
 				transform: editing_text.transform.to_cols_array(),
@@ -265,11 +336,12 @@ impl TextToolData {
 		let node_id = get_text_node_id(network)?;
 		let node = network.nodes.get(&node_id)?;
 
-		let (text, font, font_size) = Self::extract_text_node_inputs(node)?;
+		let (text, rich_text, font, typesetting) = Self::extract_text_node_inputs(node)?;
 		self.editing_text = Some(EditingText {
 			text: text.clone(),
-			font: font.clone(),
-			font_size,
+			rich_text,
+			font,
+			typesetting,
 			color: Some(color),
 			transform,
 		});
@@ -283,6 +355,7 @@ impl TextToolData {
 		}
 
 		self.layer_path = layer_path.into();
+		self.style_changed = false;
 		self.load_layer_text_node(document);
 
 		responses.add(DocumentMessage::StartTransaction);
@@ -293,29 +366,66 @@ impl TextToolData {
 		responses.add(DocumentMessage::SetSelectedLayers { replacement_selected_layers });
 	}
 
-	fn extract_text_node_inputs(node: &DocumentNode) -> Option<(&String, &Font, f64)> {
-		let NodeInput::Value {
-			tagged_value: TaggedValue::String(text),
-			..
-		} = &node.inputs[1]
-		else {
+	/// Reads the text, its runs if it is rich text, the font used for the text box, and the typesetting from the inputs of a "Text" or "Rich Text" node.
+	fn extract_text_node_inputs(node: &DocumentNode) -> Option<(String, Option<RichText>, Font, TypesettingConfig)> {
+		let value = |index: usize| match node.inputs.get(index) {
+			Some(NodeInput::Value { tagged_value, .. }) => Some(tagged_value),
+			_ => None,
+		};
+		let number = |index: usize| match value(index) {
+			Some(&TaggedValue::F64(number)) => Some(number),
+			_ => None,
+		};
+		let optional_number = |index: usize| match value(index) {
+			Some(&TaggedValue::OptionalF64(number)) => Some(number),
+			_ => None,
+		};
+
+		// The rich text node has the runs in place of the text, font and size inputs
+		let (text, rich_text, font, font_size, first_typesetting_index) = match value(1)? {
+			TaggedValue::String(text) => {
+				let Some(TaggedValue::Font(font)) = value(2) else {
+					return None;
+				};
+				(text.clone(), None, font.clone(), number(3)?, 4)
+			}
+			TaggedValue::RichText(rich_text) => {
+				let style = rich_text.first_style()?;
+				(rich_text.text(), Some(rich_text.clone()), style.font.clone(), style.font_size, 2)
+			}
+			_ => return None,
+		};
+		let Some(&TaggedValue::TextAlign(align)) = value(first_typesetting_index + 3) else {
 			return None;
 		};
-		let NodeInput::Value {
-			tagged_value: TaggedValue::Font(font),
-			..
-		} = &node.inputs[2]
-		else {
+		let Some(&TaggedValue::TextOverflow(overflow)) = value(first_typesetting_index + 6) else {
 			return None;
 		};
-		let NodeInput::Value {
-			tagged_value: TaggedValue::F64(font_size),
-			..
-		} = &node.inputs[3]
-		else {
+		let Some(TaggedValue::String(font_features)) = value(first_typesetting_index + 7) else {
+			return None;
+		};
+		let axis_values = [
+			optional_number(first_typesetting_index + 8)?,
+			optional_number(first_typesetting_index + 9)?,
+			optional_number(first_typesetting_index + 10)?,
+			optional_number(first_typesetting_index + 11)?,
+		];
+		let Some(TaggedValue::String(variations)) = value(first_typesetting_index + 12) else {
 			return None;
 		};
-		Some((text, font, *font_size))
+		let typesetting = TypesettingConfig {
+			font_size,
+			line_height_ratio: number(first_typesetting_index)?,
+			letter_spacing: number(first_typesetting_index + 1)?,
+			paragraph_spacing: number(first_typesetting_index + 2)?,
+			align,
+			max_width: number(first_typesetting_index + 4).filter(|&width| width > 0.),
+			max_height: number(first_typesetting_index + 5).filter(|&height| height > 0.),
+			overflow,
+			font_features: font_features.clone(),
+			font_variations: FontVariation::list(axis_values, variations),
+		};
+		Some((text, rich_text, font, typesetting))
 	}
 
 	fn interact(&mut self, state: TextToolFsmState, mouse: DVec2, document: &DocumentMessageHandler, render_data: &RenderData, responses: &mut VecDeque<Message>) -> TextToolFsmState {
@@ -332,7 +442,11 @@ impl TextToolData {
 		else if let Some(editing_text) = self.editing_text.as_ref().filter(|_| state == TextToolFsmState::Ready) {
 			responses.add(DocumentMessage::StartTransaction);
 
-			let network = new_text_network(String::new(), editing_text.font.clone(), editing_text.font_size);
+			let network = match &editing_text.rich_text {
+				Some(rich_text) => new_rich_text_network(rich_text.clone(), editing_text.typesetting.clone()),
+				None => new_text_network(String::new(), editing_text.font.clone(), editing_text.typesetting.clone()),
+			};
+			self.style_changed = false;
 
 			responses.add(Operation::AddFrame {
 				path: self.layer_path.clone(),
@@ -372,8 +486,13 @@ impl TextToolData {
 		resize_overlays(&mut self.overlays, responses, 1);
 
 		let editing_text = self.editing_text.as_ref()?;
-		let buzz_face = render_data.font_cache.get(&editing_text.font).map(|data| load_face(data));
-		let far = graphene_core::text::bounding_box(&self.new_text, buzz_face, editing_text.font_size, None);
+		let far = text_box_corner(
+			&self.new_text,
+			editing_text.rich_text.as_ref(),
+			&editing_text.font,
+			editing_text.typesetting.clone(),
+			render_data.font_cache,
+		);
 		let quad = Quad::from_box([DVec2::ZERO, far]);
 
 		let transformed_quad = document.document_legacy.multiply_transforms(&self.layer_path).ok()? * quad;
@@ -389,8 +508,17 @@ impl TextToolData {
 
 	fn get_bounds(&self, text: &str, render_data: &RenderData) -> Option<[DVec2; 2]> {
 		let editing_text = self.editing_text.as_ref()?;
-		let buzz_face = render_data.font_cache.get(&editing_text.font).map(|data| load_face(data));
-		let subpaths = graphene_core::text::to_path(text, buzz_face, editing_text.font_size, None);
+		let subpaths = match &editing_text.rich_text {
+			Some(rich_text) => {
+				let mut rich_text = rich_text.clone();
+				rich_text.replace_text(text, None);
+				graphene_core::text::rich_text_to_path(&rich_text, render_data.font_cache, editing_text.typesetting.clone()).subpaths
+			}
+			None => {
+				let buzz_faces = load_faces(render_data.font_cache, &editing_text.font, &editing_text.typesetting.font_variations);
+				graphene_core::text::to_path(text, &buzz_faces, editing_text.typesetting.clone())
+			}
+		};
 		let bounds = subpaths.iter().filter_map(|subpath| subpath.bounding_box());
 		let combined_bounds = bounds.reduce(|a, b| [a[0].min(b[0]), a[1].max(b[1])]).unwrap_or_default();
 		Some(combined_bounds)
@@ -406,6 +534,30 @@ impl TextToolData {
 	}
 }
 
+/// The far corner of the text box, where rich text is given the new plain text while keeping the styles of the unchanged characters.
+fn text_box_corner(text: &str, rich_text: Option<&RichText>, font: &Font, typesetting: TypesettingConfig, font_cache: &FontCache) -> DVec2 {
+	match rich_text {
+		Some(rich_text) => {
+			let mut rich_text = rich_text.clone();
+			rich_text.replace_text(text, None);
+			graphene_core::text::rich_text_bounding_box(&rich_text, font_cache, typesetting)
+		}
+		None => {
+			let buzz_faces = load_faces(font_cache, font, &typesetting.font_variations);
+			graphene_core::text::bounding_box(text, &buzz_faces, typesetting)
+		}
+	}
+}
+
+/// The style that rich text typed with the current tool options is given.
+fn typing_style(options: &TextOptions) -> TextStyle {
+	let font = Font::new(options.font_name.clone(), options.font_style.clone());
+	TextStyle {
+		fill: options.fill.active_color(),
+		..TextStyle::new(font, options.font_size as f64)
+	}
+}
+
 fn transform_from_box(pos1: DVec2, pos2: DVec2) -> [f64; 6] {
 	DAffine2::from_scale_angle_translation((pos2 - pos1).round(), 0., pos1.round() - DVec2::splat(0.5)).to_cols_array()
 }
@@ -434,9 +586,8 @@ fn update_overlays(document: &DocumentMessageHandler, tool_data: &mut TextToolDa
 		let node_graph = layer.as_layer_network().ok()?;
 		let node_id = get_text_node_id(node_graph)?;
 		let document_node = node_graph.nodes.get(&node_id)?;
-		let (text, font, font_size) = TextToolData::extract_text_node_inputs(document_node)?;
-		let buzz_face = render_data.font_cache.get(font).map(|data| load_face(data));
-		let far = graphene_core::text::bounding_box(text, buzz_face, font_size, None);
+		let (text, rich_text, font, typesetting) = TextToolData::extract_text_node_inputs(document_node)?;
+		let far = text_box_corner(&text, rich_text.as_ref(), &font, typesetting, render_data.font_cache);
 		let quad = Quad::from_box([DVec2::ZERO, far]);
 		let multiplied = document.document_legacy.multiply_transforms(path).ok()? * quad;
 		Some(multiplied.bounding_box())
@@ -465,7 +616,7 @@ fn get_network<'a>(layer_path: &[LayerId], document: &'a DocumentMessageHandler)
 }
 
 fn get_text_node_id(network: &NodeNetwork) -> Option<NodeId> {
-	network.nodes.iter().find(|(_, node)| node.name == "Text").map(|(&id, _)| id)
+	network.nodes.iter().find(|(_, node)| matches!(node.name.as_str(), "Text" | "Rich Text")).map(|(&id, _)| id)
 }
 
 fn is_text_layer(document: &DocumentMessageHandler, layer_path: &[LayerId]) -> bool {
@@ -518,11 +669,20 @@ impl Fsm for TextToolFsmState {
 					state
 				}
 				(state, TextToolMessage::Interact) => {
+					let font = Font::new(tool_options.font_name.clone(), tool_options.font_style.clone());
 					tool_data.editing_text = Some(EditingText {
 						text: String::new(),
+						rich_text: tool_options.rich_text.then(|| RichText::new("", TextStyle::new(font.clone(), tool_options.font_size as f64))),
 						transform: DAffine2::from_translation(input.mouse.position),
-						font_size: tool_options.font_size as f64,
-						font: Font::new(tool_options.font_name.clone(), tool_options.font_style.clone()),
+						typesetting: TypesettingConfig {
+							font_size: tool_options.font_size as f64,
+							line_height_ratio: tool_options.line_height_ratio,
+							letter_spacing: tool_options.letter_spacing,
+							paragraph_spacing: tool_options.paragraph_spacing,
+							align: tool_options.align,
+							..Default::default()
+						},
+						font,
 						color: tool_options.fill.active_color(),
 					});
 					tool_data.new_text = String::new();
@@ -556,11 +716,18 @@ impl Fsm for TextToolFsmState {
 					let layer_path = tool_data.layer_path.clone();
 					let network = get_network(&layer_path, document).unwrap();
 					tool_data.fix_text_bounds(&new_text, document, render_data, responses);
+					let value = match tool_data.editing_text.as_ref().and_then(|editing_text| editing_text.rich_text.clone()) {
+						Some(mut rich_text) => {
+							rich_text.replace_text(&new_text, tool_data.style_changed.then(|| typing_style(tool_options)));
+							TaggedValue::RichText(rich_text)
+						}
+						None => TaggedValue::String(new_text),
+					};
 					responses.add(NodeGraphMessage::SetQualifiedInputValue {
 						layer_path,
 						node_path: vec![get_text_node_id(network).unwrap()],
 						input_index: 1,
-						value: TaggedValue::String(new_text),
+						value,
 					});
 
 					tool_data.set_editing(false, render_data, responses);
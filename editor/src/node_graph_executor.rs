@@ -4,7 +4,9 @@ use crate::messages::portfolio::document::node_graph::wrap_network_in_scope;
 use crate::messages::portfolio::utility_types::PersistentData;
 use crate::messages::prelude::*;
 
+use document_legacy::document::Document as DocumentLegacy;
 use document_legacy::layers::layer_info::LayerDataType;
+use document_legacy::layers::layer_layer::CachedOutputData;
 use document_legacy::{LayerId, Operation};
 
 use graph_craft::document::value::TaggedValue;
@@ -12,8 +14,8 @@ use graph_craft::document::{generate_uuid, DocumentNodeImplementation, NodeId, N
 use graph_craft::graphene_compiler::Compiler;
 use graph_craft::imaginate_input::ImaginatePreferences;
 use graph_craft::{concrete, Type, TypeDescriptor};
-use graphene_core::application_io::{ApplicationIo, NodeGraphUpdateMessage, NodeGraphUpdateSender};
-use graphene_core::raster::{Image, ImageFrame};
+use graphene_core::application_io::{ApplicationIo, NodeGraphUpdateMessage, NodeGraphUpdateSender, ResourceError};
+use graphene_core::raster::{ColorSpace, Image, ImageFrame, SRGBA8};
 use graphene_core::renderer::{SvgSegment, SvgSegmentList};
 use graphene_core::text::FontCache;
 use graphene_core::vector::style::ViewMode;
@@ -53,6 +55,8 @@ pub struct NodeRuntime {
 	wasm_io: Option<WasmApplicationIo>,
 	imaginate_preferences: ImaginatePreferences,
 	pub(crate) thumbnails: HashMap<GraphIdentifier, HashMap<NodeId, SvgSegmentList>>,
+	/// The errors of the nodes which failed to load their resources during the last evaluation of each graph.
+	pub(crate) node_errors: HashMap<GraphIdentifier, HashMap<NodeId, String>>,
 	canvas_cache: HashMap<Vec<LayerId>, SurfaceId>,
 }
 
@@ -67,6 +71,8 @@ pub(crate) struct GenerationRequest {
 	graph: NodeNetwork,
 	path: Vec<LayerId>,
 	image_frame: Option<ImageFrame<Color>>,
+	time: f64,
+	color_space: ColorSpace,
 }
 
 pub(crate) struct GenerationResponse {
@@ -74,6 +80,7 @@ pub(crate) struct GenerationResponse {
 	result: Result<TaggedValue, String>,
 	updates: VecDeque<Message>,
 	new_thumbnails: HashMap<GraphIdentifier, HashMap<NodeId, SvgSegmentList>>,
+	new_node_errors: HashMap<GraphIdentifier, HashMap<NodeId, String>>,
 }
 
 enum NodeGraphUpdate {
@@ -109,6 +116,7 @@ impl NodeRuntime {
 			font_cache: FontCache::default(),
 			imaginate_preferences: Default::default(),
 			thumbnails: Default::default(),
+			node_errors: Default::default(),
 			wasm_io: None,
 			canvas_cache: Default::default(),
 		}
@@ -132,6 +140,8 @@ impl NodeRuntime {
 					graph,
 					image_frame,
 					path,
+					time,
+					color_space,
 					..
 				}) => {
 					let network = wrap_network_in_scope(graph);
@@ -142,7 +152,7 @@ impl NodeRuntime {
 						.map(|node| node.path.clone().unwrap_or_default())
 						.collect();
 
-					let result = self.execute_network(&path, network, image_frame).await;
+					let result = self.execute_network(&path, network, image_frame, time, color_space).await;
 					let mut responses = VecDeque::new();
 					self.update_thumbnails(&path, monitor_nodes, &mut responses);
 					let response = GenerationResponse {
@@ -150,6 +160,7 @@ impl NodeRuntime {
 						result,
 						updates: responses,
 						new_thumbnails: self.thumbnails.clone(),
+						new_node_errors: self.node_errors.clone(),
 					};
 					self.sender.send_generation_response(response);
 				}
@@ -157,7 +168,14 @@ impl NodeRuntime {
 		}
 	}
 
-	async fn execute_network<'a>(&'a mut self, path: &[LayerId], scoped_network: NodeNetwork, image_frame: Option<ImageFrame<Color>>) -> Result<TaggedValue, String> {
+	async fn execute_network<'a>(
+		&'a mut self,
+		path: &[LayerId],
+		scoped_network: NodeNetwork,
+		image_frame: Option<ImageFrame<Color>>,
+		time: f64,
+		color_space: ColorSpace,
+	) -> Result<TaggedValue, String> {
 		if self.wasm_io.is_none() {
 			self.wasm_io = Some(WasmApplicationIo::new().await);
 		}
@@ -168,6 +186,8 @@ impl NodeRuntime {
 			application_io: self.wasm_io.as_ref().unwrap(),
 			node_graph_message_sender: &self.sender,
 			imaginate_preferences: &self.imaginate_preferences,
+			time,
+			color_space,
 		};
 
 		// We assume only one output
@@ -202,7 +222,7 @@ impl NodeRuntime {
 		Ok(result)
 	}
 
-	/// Recomputes the thumbnails for the layers in the graph, modifying the state and updating the UI.
+	/// Recomputes the thumbnails and errors for the layers in the graph, modifying the state and updating the UI.
 	pub fn update_thumbnails(&mut self, layer_path: &[LayerId], monitor_nodes: Vec<Vec<u64>>, responses: &mut VecDeque<Message>) {
 		let mut thumbnails_changed: bool = false;
 		let mut image_data: Vec<_> = Vec::new();
@@ -211,6 +231,24 @@ impl NodeRuntime {
 				warn!("No introspect");
 				continue;
 			};
+
+			// Monitors placed after nodes which load resources report whether loading failed instead of showing a thumbnail
+			if let Some(result) = value.downcast_ref::<Result<ImageFrame<Color>, ResourceError>>() {
+				let Some(node_id) = node_path.len().checked_sub(2).and_then(|index| node_path.get(index)).copied() else {
+					continue;
+				};
+				let node_errors = self.node_errors.entry(GraphIdentifier::new(layer_path.last().copied())).or_default();
+				let error = result.as_ref().err().map(|error| error.to_string());
+				if node_errors.get(&node_id) != error.as_ref() {
+					match error {
+						Some(error) => node_errors.insert(node_id, error),
+						None => node_errors.remove(&node_id),
+					};
+					thumbnails_changed = true;
+				}
+				continue;
+			}
+
 			let Some(graphic_group) = value.downcast_ref::<graphene_core::GraphicGroup>() else {
 				warn!("Not graphic");
 				continue;
@@ -223,7 +261,7 @@ impl NodeRuntime {
 			let [min, max] = bounds.unwrap_or_default();
 			render.format_svg(min, max);
 
-			if let Some(node_id) = node_path.get(node_path.len() - 2).copied() {
+			if let Some(node_id) = node_path.len().checked_sub(2).and_then(|index| node_path.get(index)).copied() {
 				let graph_identifier = GraphIdentifier::new(layer_path.last().copied());
 				let old_thumbnail = self.thumbnails.entry(graph_identifier).or_default().entry(node_id).or_default();
 				if *old_thumbnail != render.svg {
@@ -232,7 +270,7 @@ impl NodeRuntime {
 				}
 			}
 			let resize = Some(DVec2::splat(100.));
-			let create_image_data = |(node_id, image)| NodeGraphExecutor::to_frontend_image_data(image, None, layer_path, Some(node_id), resize).ok();
+			let create_image_data = |(node_id, image)| NodeGraphExecutor::to_frontend_image_data(&image, None, layer_path, Some(node_id), resize).ok();
 			image_data.extend(render.image_data.into_iter().filter_map(create_image_data))
 		}
 		if !image_data.is_empty() {
@@ -280,6 +318,11 @@ pub struct NodeGraphExecutor {
 	// TODO: This is a memory leak since layers are never removed
 	pub(crate) last_output_type: HashMap<Vec<LayerId>, Option<Type>>,
 	pub(crate) thumbnails: HashMap<GraphIdentifier, HashMap<NodeId, SvgSegmentList>>,
+	pub(crate) node_errors: HashMap<GraphIdentifier, HashMap<NodeId, String>>,
+	/// The last image output by each layer's graph in each document, since the document only keeps a blob URL the browser can display
+	///
+	/// They're kept as 8-bit samples like the pixels read back from surfaces, taking a quarter of the memory of their linear colors.
+	images: HashMap<u64, HashMap<Vec<LayerId>, Image<SRGBA8>>>,
 	futures: HashMap<u64, ExecutionContext>,
 }
 
@@ -287,6 +330,7 @@ pub struct NodeGraphExecutor {
 struct ExecutionContext {
 	layer_path: Vec<LayerId>,
 	document_id: u64,
+	time: f64,
 }
 
 impl Default for NodeGraphExecutor {
@@ -303,19 +347,23 @@ impl Default for NodeGraphExecutor {
 			receiver: response_receiver,
 			last_output_type: Default::default(),
 			thumbnails: Default::default(),
+			node_errors: Default::default(),
+			images: Default::default(),
 		}
 	}
 }
 
 impl NodeGraphExecutor {
 	/// Execute the network by flattening it and creating a borrow stack.
-	fn queue_execution(&self, network: NodeNetwork, image_frame: Option<ImageFrame<Color>>, layer_path: Vec<LayerId>) -> u64 {
+	fn queue_execution(&self, network: NodeNetwork, image_frame: Option<ImageFrame<Color>>, layer_path: Vec<LayerId>, time: f64, color_space: ColorSpace) -> u64 {
 		let generation_id = generate_uuid();
 		let request = GenerationRequest {
 			path: layer_path,
 			graph: network,
 			image_frame,
 			generation_id,
+			time,
+			color_space,
 		};
 		self.sender.send(NodeRuntimeMessage::GenerationRequest(request)).expect("Failed to send generation request");
 
@@ -358,11 +406,11 @@ impl NodeGraphExecutor {
 	}
 
 	/// Encodes an image into a format using the image crate
-	fn encode_img(image: Image<Color>, resize: Option<DVec2>, format: image::ImageOutputFormat) -> Result<(Vec<u8>, (u32, u32)), String> {
+	fn encode_img(image: &Image<Color>, resize: Option<DVec2>, format: image::ImageOutputFormat) -> Result<(Vec<u8>, (u32, u32)), String> {
 		use image::{ImageBuffer, Rgba};
 		use std::io::Cursor;
 
-		let (result_bytes, width, height) = image.into_flat_u8();
+		let (result_bytes, width, height) = image.to_flat_u8();
 
 		let mut output: ImageBuffer<Rgba<u8>, _> = image::ImageBuffer::from_raw(width, height, result_bytes).ok_or_else(|| "Invalid image size".to_string())?;
 		if let Some(size) = resize {
@@ -378,7 +426,7 @@ impl NodeGraphExecutor {
 	}
 
 	/// Generate a new [`FrontendImageData`] from the [`Image`].
-	fn to_frontend_image_data(image: Image<Color>, transform: Option<[f64; 6]>, layer_path: &[LayerId], node_id: Option<u64>, resize: Option<DVec2>) -> Result<FrontendImageData, String> {
+	fn to_frontend_image_data(image: &Image<Color>, transform: Option<[f64; 6]>, layer_path: &[LayerId], node_id: Option<u64>, resize: Option<DVec2>) -> Result<FrontendImageData, String> {
 		let (image_data, _size) = Self::encode_img(image, resize, image::ImageOutputFormat::Bmp)?;
 
 		let mime = "image/bmp".to_string();
@@ -423,10 +471,12 @@ impl NodeGraphExecutor {
 		let transform = DAffine2::IDENTITY;
 		let image_frame = ImageFrame { image, transform };
 
-		// Execute the node graph
-		let generation_id = self.queue_execution(network, Some(image_frame), layer_path.clone());
+		// Execute the node graph at the document's current animation time and in its working color space
+		let time = document.time;
+		let color_space = document.document_legacy.working_color_space;
+		let generation_id = self.queue_execution(network, Some(image_frame), layer_path.clone(), time, color_space);
 
-		self.futures.insert(generation_id, ExecutionContext { layer_path, document_id });
+		self.futures.insert(generation_id, ExecutionContext { layer_path, document_id, time });
 
 		Ok(())
 	}
@@ -440,21 +490,30 @@ impl NodeGraphExecutor {
 					result,
 					updates,
 					new_thumbnails,
+					new_node_errors,
 				}) => {
 					self.thumbnails = new_thumbnails;
-					let node_graph_output = result.map_err(|e| format!("Node graph evaluation failed: {:?}", e))?;
-					let execution_context = self.futures.remove(&generation_id).ok_or_else(|| "Invalid generation ID".to_string())?;
-					responses.extend(updates);
-					self.process_node_graph_output(node_graph_output, execution_context.layer_path.clone(), transform, responses, execution_context.document_id)?;
-					responses.add(DocumentMessage::LayerChanged {
-						affected_layer_path: execution_context.layer_path,
+					self.node_errors = new_node_errors;
+					let ExecutionContext { layer_path, document_id, time } = self.futures.remove(&generation_id).ok_or_else(|| "Invalid generation ID".to_string())?;
+					let output = result.map_err(|e| format!("Node graph evaluation failed: {:?}", e)).and_then(|node_graph_output| {
+						responses.extend(updates);
+						self.process_node_graph_output(node_graph_output, layer_path.clone(), transform, responses, document_id)
 					});
-					responses.add(DocumentMessage::RenderDocument);
-					responses.add(ArtboardMessage::RenderArtboards);
-					responses.add(DocumentMessage::DocumentStructureChanged);
-					responses.add(BroadcastEvent::DocumentIsDirty);
-					responses.add(DocumentMessage::DirtyRenderDocument);
-					responses.add(DocumentMessage::Overlays(OverlaysMessage::Rerender));
+					if output.is_ok() {
+						responses.add(DocumentMessage::LayerChanged {
+							affected_layer_path: layer_path.clone(),
+						});
+						responses.add(DocumentMessage::RenderDocument);
+						responses.add(ArtboardMessage::RenderArtboards);
+						responses.add(DocumentMessage::DocumentStructureChanged);
+						responses.add(BroadcastEvent::DocumentIsDirty);
+						responses.add(DocumentMessage::DirtyRenderDocument);
+						responses.add(DocumentMessage::Overlays(OverlaysMessage::Rerender));
+					}
+					// Report failed evaluations too, so anything waiting on this layer's output isn't left waiting forever
+					let message = DocumentMessage::NodeGraphEvaluated { layer_path, time };
+					responses.add(PortfolioMessage::DocumentPassMessage { document_id, message });
+					output?;
 				}
 				NodeGraphUpdate::NodeGraphUpdateMessage(NodeGraphUpdateMessage::ImaginateStatusUpdate) => {
 					responses.add(DocumentMessage::PropertiesPanel(PropertiesPanelMessage::ResendActiveProperties))
@@ -468,12 +527,17 @@ impl NodeGraphExecutor {
 		self.last_output_type.insert(layer_path.clone(), Some(node_graph_output.ty()));
 		match node_graph_output {
 			TaggedValue::VectorData(vector_data) => {
+				self.remove_layer_image(document_id, &layer_path);
+
 				// Update the cached vector data on the layer
 				let transform = vector_data.transform.to_cols_array();
 				responses.add(Operation::SetLayerTransform { path: layer_path.clone(), transform });
 				responses.add(Operation::SetVectorData { path: layer_path, vector_data });
 			}
 			TaggedValue::SurfaceFrame(SurfaceFrame { surface_id, transform }) => {
+				// The pixels are read back from the surface when they're needed, since the browser already keeps them
+				self.remove_layer_image(document_id, &layer_path);
+
 				let transform = transform.to_cols_array();
 				responses.add(Operation::SetLayerTransform { path: layer_path.clone(), transform });
 				responses.add(Operation::SetSurface { path: layer_path, surface_id });
@@ -484,6 +548,7 @@ impl NodeGraphExecutor {
 
 				// If no image was generated, clear the frame
 				if image.width == 0 || image.height == 0 {
+					self.remove_layer_image(document_id, &layer_path);
 					responses.add(DocumentMessage::FrameClear);
 
 					// Update the transform based on the graph output
@@ -492,8 +557,16 @@ impl NodeGraphExecutor {
 					}
 				} else {
 					// Update the image data
-					let image_data = vec![Self::to_frontend_image_data(image, transform, &layer_path, None, None)?];
+					let image_data = vec![Self::to_frontend_image_data(&image, transform, &layer_path, None, None)?];
 					responses.add(FrontendMessage::UpdateImageData { document_id, image_data });
+
+					// Keep the pixels around for raster exports, replacing those of the layer's previous output
+					let image = Image {
+						width: image.width,
+						height: image.height,
+						data: image.data.into_iter().map(SRGBA8::from).collect(),
+					};
+					self.images.entry(document_id).or_default().insert(layer_path, image);
 				}
 			}
 			TaggedValue::Artboard(artboard) => {
@@ -527,6 +600,59 @@ impl NodeGraphExecutor {
 		Ok(())
 	}
 
+	/// The pixels last output by the graphs of a document's layers, for raster exports and the palette extraction.
+	///
+	/// The kept 8-bit pixels are decoded and those of the visible layers which draw onto a surface are read back from it, so the linear colors only take up memory while they're needed.
+	pub fn layer_images(&self, document_id: u64, document: &DocumentLegacy) -> HashMap<Vec<LayerId>, Image<Color>> {
+		let kept = self.images.get(&document_id).into_iter().flatten();
+		let mut images: HashMap<_, _> = kept
+			.map(|(path, image)| {
+				let data = image.data.iter().map(|&pixel| Color::from(pixel)).collect();
+				(
+					path.clone(),
+					Image {
+						width: image.width,
+						height: image.height,
+						data,
+					},
+				)
+			})
+			.collect();
+
+		let mut paths = Vec::new();
+		if document.visible_layers(&mut Vec::new(), &mut paths).is_ok() {
+			for path in paths {
+				let Ok(layer) = document.layer(&path).and_then(|layer| layer.as_layer()) else { continue };
+				if let CachedOutputData::SurfaceId(surface_id) = layer.cached_output_data {
+					match WasmApplicationIo::surface_image(surface_id) {
+						Some(image) => images.insert(path, image),
+						None => images.remove(&path),
+					};
+				}
+			}
+		}
+
+		images
+	}
+
+	/// Drops the pixels kept for the layers which are no longer in the document.
+	pub fn retain_layer_images(&mut self, document_id: u64, document: &DocumentLegacy) {
+		if let Some(images) = self.images.get_mut(&document_id) {
+			images.retain(|path, _| document.layer(path).is_ok());
+		}
+	}
+
+	/// Drops the pixels kept for the layers of a document which has been closed.
+	pub fn remove_document_images(&mut self, document_id: u64) {
+		self.images.remove(&document_id);
+	}
+
+	fn remove_layer_image(&mut self, document_id: u64, layer_path: &[LayerId]) {
+		if let Some(images) = self.images.get_mut(&document_id) {
+			images.remove(layer_path);
+		}
+	}
+
 	/// When a blob url for a thumbnail is loaded, update the state and the UI.
 	pub fn insert_thumbnail_blob_url(&mut self, blob_url: String, layer_id: Option<LayerId>, node_id: NodeId, responses: &mut VecDeque<Message>) {
 		if let Some(layer) = self.thumbnails.get_mut(&GraphIdentifier::new(layer_id)) {
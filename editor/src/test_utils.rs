@@ -1,11 +1,15 @@
 use crate::application::set_uuid_seed;
 use crate::application::Editor;
 use crate::messages::input_mapper::utility_types::input_keyboard::ModifierKeys;
-use crate::messages::input_mapper::utility_types::input_mouse::{EditorMouseState, MouseKeys, ScrollDelta, ViewportPosition};
+use crate::messages::input_mapper::utility_types::input_mouse::{EditorMouseState, MouseKeys, ScrollDelta, StylusState, ViewportPosition};
+use crate::messages::portfolio::document::utility_types::layer_panel::LayerMetadata;
+use crate::messages::portfolio::document::utility_types::misc::DocumentSave;
 use crate::messages::portfolio::utility_types::Platform;
 use crate::messages::prelude::*;
 use crate::messages::tool::utility_types::ToolType;
 
+use document_legacy::document::Document as DocumentLegacy;
+use document_legacy::LayerId;
 use graphene_core::raster::color::Color;
 
 /// A set of utility functions to make the writing of editor test more declarative
@@ -69,6 +73,7 @@ impl EditorTestUtils for Editor {
 			editor_position: (x2, y2).into(),
 			mouse_keys: MouseKeys::empty(),
 			scroll_delta: ScrollDelta::default(),
+			stylus: StylusState::default(),
 		});
 	}
 
@@ -94,6 +99,7 @@ impl EditorTestUtils for Editor {
 			editor_position: (x, y).into(),
 			mouse_keys: MouseKeys::LEFT,
 			scroll_delta: ScrollDelta::default(),
+			stylus: StylusState::default(),
 		});
 	}
 
@@ -109,3 +115,12 @@ impl EditorTestUtils for Editor {
 		self.handle_message(Message::Tool(ToolMessage::SelectPrimaryColor { color }));
 	}
 }
+
+/// A save of the given state of a document without any artboards, as the document history and the journal are given before each change
+pub fn document_save(document: &DocumentLegacy, layer_metadata: &HashMap<Vec<LayerId>, LayerMetadata>) -> DocumentSave {
+	DocumentSave {
+		document: document.clone(),
+		artboard: ArtboardMessageHandler::default(),
+		layer_metadata: layer_metadata.clone(),
+	}
+}
@@ -1,12 +1,35 @@
+import { replaceBlobURLsWithBase64 } from "@graphite/utility-functions/files";
 import { type Editor } from "@graphite/wasm-communication/editor";
-import { TriggerTextCopy } from "@graphite/wasm-communication/messages";
+import { TriggerClipboardWrite } from "@graphite/wasm-communication/messages";
 import { imageToPNG } from "~src/utility-functions/rasterization";
 
 export function createClipboardManager(editor: Editor): void {
 	// Subscribe to process backend event
-	editor.subscriptions.subscribeJsMessage(TriggerTextCopy, (triggerTextCopy) => {
-		// If the Clipboard API is supported in the browser, copy text to the clipboard
-		navigator.clipboard?.writeText?.(triggerTextCopy.copyText);
+	editor.subscriptions.subscribeJsMessage(TriggerClipboardWrite, async (triggerClipboardWrite) => {
+		const { content, svg } = triggerClipboardWrite;
+
+		// Without the asynchronous Clipboard API, or without a rendering for other apps, only the text for pasting back into the editor is copied
+		if (!svg || !window.ClipboardItem || !navigator.clipboard?.write) {
+			navigator.clipboard?.writeText?.(content);
+			return;
+		}
+
+		// Embed the images, since their blob URLs can't be loaded outside this browser tab
+		const standaloneSvg = await replaceBlobURLsWithBase64(svg);
+
+		// Browsers which can't write SVG to the clipboard get it as HTML instead, which most vector apps also accept
+		const { supports } = ClipboardItem as { supports?: (type: string) => boolean };
+		const svgType = supports?.("image/svg+xml") ? "image/svg+xml" : "text/html";
+		const clipboardItem: Record<string, Blob> = {
+			"text/plain": new Blob([content], { type: "text/plain" }),
+			[svgType]: new Blob([standaloneSvg], { type: svgType }),
+		};
+
+		try {
+			await navigator.clipboard.write([new ClipboardItem(clipboardItem)]);
+		} catch {
+			navigator.clipboard.writeText(content);
+		}
 	});
 }
 
@@ -11,6 +11,13 @@ import { stripIndents } from "@graphite/utility-functions/strip-indents";
 import { type Editor } from "@graphite/wasm-communication/editor";
 import { TriggerPaste } from "@graphite/wasm-communication/messages";
 
+// The starts of the text which the editor writes to the clipboard, matching `CLIPBOARD_TEXT_PREFIX` in the editor and the legacy prefixes of layers and nodes copied by older versions
+const CLIPBOARD_TEXT_PREFIXES = ["graphite/clipboard: ", "graphite/layer: ", "graphite/nodes: "];
+
+function isEditorClipboardText(text: string): boolean {
+	return CLIPBOARD_TEXT_PREFIXES.some((prefix) => text.startsWith(prefix));
+}
+
 type EventName = keyof HTMLElementEventMap | keyof WindowEventHandlersEventMap | "modifyinputfield";
 type EventListenerTarget = {
 	addEventListener: typeof window.addEventListener;
@@ -144,7 +151,7 @@ export function createInputManager(editor: Editor, dialog: DialogState, portfoli
 		}
 
 		const modifiers = makeKeyboardModifiersBitfield(e);
-		editor.instance.onMouseMove(e.clientX, e.clientY, e.buttons, modifiers);
+		editor.instance.onMouseMove(e.clientX, e.clientY, e.buttons, modifiers, e.pressure, e.tiltX, e.tiltY, e.twist, e.timeStamp);
 	}
 
 	function onPointerDown(e: PointerEvent): void {
@@ -166,7 +173,7 @@ export function createInputManager(editor: Editor, dialog: DialogState, portfoli
 
 		if (viewportPointerInteractionOngoing) {
 			const modifiers = makeKeyboardModifiersBitfield(e);
-			editor.instance.onMouseDown(e.clientX, e.clientY, e.buttons, modifiers);
+			editor.instance.onMouseDown(e.clientX, e.clientY, e.buttons, modifiers, e.pressure, e.tiltX, e.tiltY, e.twist, e.timeStamp);
 		}
 
 		// Block middle mouse button auto-scroll mode (the circlar widget that appears and allows quick scrolling by moving the cursor above or below it)
@@ -178,7 +185,7 @@ export function createInputManager(editor: Editor, dialog: DialogState, portfoli
 
 		if (!textToolInteractiveInputElement) {
 			const modifiers = makeKeyboardModifiersBitfield(e);
-			editor.instance.onMouseUp(e.clientX, e.clientY, e.buttons, modifiers);
+			editor.instance.onMouseUp(e.clientX, e.clientY, e.buttons, modifiers, e.pressure, e.tiltX, e.tiltY, e.twist, e.timeStamp);
 		}
 	}
 
@@ -261,17 +268,14 @@ export function createInputManager(editor: Editor, dialog: DialogState, portfoli
 		if (!dataTransfer || targetIsTextField(e.target || undefined)) return;
 		e.preventDefault();
 
-		Array.from(dataTransfer.items).forEach((item) => {
-			if (item.type === "text/plain") {
-				item.getAsString((text) => {
-					if (text.startsWith("graphite/layer: ")) {
-						editor.instance.pasteSerializedData(text.substring(16, text.length));
-					} else if (text.startsWith("graphite/nodes: ")) {
-						editor.instance.pasteSerializedNodes(text.substring(16, text.length));
-					}
-				});
-			}
+		// Content copied from the editor is pasted as itself, rather than as the rendering of it copied alongside for other apps
+		const text = dataTransfer.getData("text/plain");
+		if (isEditorClipboardText(text)) {
+			editor.instance.pasteSerializedData(text, "All");
+			return;
+		}
 
+		Array.from(dataTransfer.items).forEach((item) => {
 			const file = item.getAsFile();
 			if (file?.type.startsWith("image")) {
 				extractPixelData(file).then((imageData): void => {
@@ -283,7 +287,7 @@ export function createInputManager(editor: Editor, dialog: DialogState, portfoli
 
 	// Frontend message subscriptions
 
-	editor.subscriptions.subscribeJsMessage(TriggerPaste, async () => {
+	editor.subscriptions.subscribeJsMessage(TriggerPaste, async (triggerPaste) => {
 		// In the try block, attempt to read from the Clipboard API, which may not have permission and may not be supported in all browsers
 		// In the catch block, explain to the user why the paste failed and how to fix or work around the problem
 		try {
@@ -300,20 +304,18 @@ export function createInputManager(editor: Editor, dialog: DialogState, portfoli
 
 			// Read any layer data or images from the clipboard
 			Array.from(clipboardItems).forEach(async (item) => {
-				// Read plain text and, if it is a layer, pass it to the editor
+				// Read plain text and, if it is content copied from the editor, pass it to the editor instead of the rendering of it copied alongside for other apps
 				if (item.types.includes("text/plain")) {
-					const blob = await item.getType("text/plain");
-					const reader = new FileReader();
-					reader.onload = (): void => {
-						const text = reader.result as string;
-
-						if (text.startsWith("graphite/layer: ")) {
-							editor.instance.pasteSerializedData(text.substring(16, text.length));
-						}
-					};
-					reader.readAsText(blob);
+					const text = await (await item.getType("text/plain")).text();
+					if (isEditorClipboardText(text)) {
+						editor.instance.pasteSerializedData(text, triggerPaste.mode);
+						return;
+					}
 				}
 
+				// Only the copied content carries a style or transform to paste
+				if (triggerPaste.mode !== "All") return;
+
 				// Read an image from the clipboard and pass it to the editor to be loaded
 				const imageType = item.types.find((type) => type.startsWith("image/"));
 				if (imageType) {
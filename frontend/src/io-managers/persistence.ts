@@ -1,12 +1,26 @@
-import { createStore, del, get, set, update } from "idb-keyval";
+import { createStore, del, delMany, get, getMany, keys, set, update } from "idb-keyval";
 import { get as getFromStore } from "svelte/store";
 
 import { type PortfolioState } from "@graphite/state-providers/portfolio";
 import { type Editor } from "@graphite/wasm-communication/editor";
-import { TriggerIndexedDbWriteDocument, TriggerIndexedDbRemoveDocument, TriggerSavePreferences, TriggerLoadAutoSaveDocuments, TriggerLoadPreferences } from "@graphite/wasm-communication/messages";
+import {
+	TriggerIndexedDbWriteDocument,
+	TriggerIndexedDbWriteJournalEntry,
+	TriggerIndexedDbRemoveDocument,
+	TriggerSavePreferences,
+	TriggerLoadAutoSaveDocuments,
+	TriggerLoadPreferences,
+} from "@graphite/wasm-communication/messages";
 
 const graphiteStore = createStore("graphite", "store");
 
+// Each journal entry is stored under its own key of the form [JOURNAL_KEY, document ID, index], so appending one doesn't rewrite the others
+const JOURNAL_KEY = "document_journal";
+
+function isJournalKey(key: IDBValidKey): key is [string, string, number] {
+	return Array.isArray(key) && key[0] === JOURNAL_KEY;
+}
+
 export function createPersistenceManager(editor: Editor, portfolio: PortfolioState): void {
 	// DOCUMENTS
 
@@ -17,7 +31,7 @@ export function createPersistenceManager(editor: Editor, portfolio: PortfolioSta
 	}
 
 	async function storeDocument(autoSaveDocument: TriggerIndexedDbWriteDocument): Promise<void> {
-		await update<Record<string, TriggerIndexedDbWriteDocument>>(
+		const documentWritten = update<Record<string, TriggerIndexedDbWriteDocument>>(
 			"documents",
 			(old) => {
 				const documents = old || {};
@@ -26,12 +40,15 @@ export function createPersistenceManager(editor: Editor, portfolio: PortfolioSta
 			},
 			graphiteStore
 		);
+		// The document now includes the changes in its journal, so the journal starts over
+		const journalTruncated = removeJournal(autoSaveDocument.details.id);
+		await Promise.all([documentWritten, journalTruncated]);
 
 		await storeDocumentOrder();
 	}
 
 	async function removeDocument(id: string): Promise<void> {
-		await update<Record<string, TriggerIndexedDbWriteDocument>>(
+		const documentRemoved = update<Record<string, TriggerIndexedDbWriteDocument>>(
 			"documents",
 			(old) => {
 				const documents = old || {};
@@ -40,15 +57,44 @@ export function createPersistenceManager(editor: Editor, portfolio: PortfolioSta
 			},
 			graphiteStore
 		);
+		await Promise.all([documentRemoved, removeJournal(id)]);
 
 		await storeDocumentOrder();
 	}
 
+	// The journal of each document holds the changes committed since it was last stored, which are replayed onto it when it's loaded to recover from a crash.
+	// The indices keep counting up across truncations, so that truncating the journal never removes the entries appended after the document was stored.
+	const journalLengths: Record<string, number> = {};
+
+	async function appendJournalEntry(id: string, entry: string): Promise<void> {
+		const index = journalLengths[id] || 0;
+		journalLengths[id] = index + 1;
+
+		await set([JOURNAL_KEY, id, index], entry, graphiteStore);
+	}
+
+	async function removeJournal(id: string): Promise<void> {
+		const length = journalLengths[id] || 0;
+		const journalKeys = (await keys(graphiteStore)).filter((key) => isJournalKey(key) && key[1] === id && key[2] < length);
+
+		await delMany(journalKeys, graphiteStore);
+	}
+
 	async function loadDocuments(): Promise<void> {
 		const previouslySavedDocuments = await get<Record<string, TriggerIndexedDbWriteDocument>>("documents", graphiteStore);
 		const documentOrder = await get<string[]>("documents_tab_order", graphiteStore);
 		if (!previouslySavedDocuments || !documentOrder) return;
 
+		// The keys are listed in order, which puts the entries of each journal in the order they were appended
+		const journalKeys = (await keys(graphiteStore)).filter(isJournalKey);
+		const journalEntries = await getMany<string>(journalKeys, graphiteStore);
+		const journals: Record<string, string[]> = {};
+		journalKeys.forEach(([, id, index], entryIndex) => {
+			if (!journals[id]) journals[id] = [];
+			journals[id].push(journalEntries[entryIndex]);
+			journalLengths[id] = Math.max(journalLengths[id] || 0, index + 1);
+		});
+
 		const orderedSavedDocuments = documentOrder.flatMap((id) => (previouslySavedDocuments[id] ? [previouslySavedDocuments[id]] : []));
 
 		const currentDocumentVersion = editor.instance.graphiteDocumentVersion();
@@ -58,7 +104,7 @@ export function createPersistenceManager(editor: Editor, portfolio: PortfolioSta
 				return;
 			}
 
-			editor.instance.openAutoSavedDocument(BigInt(doc.details.id), doc.details.name, doc.details.isSaved, doc.document);
+			editor.instance.openAutoSavedDocument(BigInt(doc.details.id), doc.details.name, doc.details.isSaved, doc.document, journals[doc.details.id] || []);
 		});
 	}
 
@@ -87,6 +133,9 @@ export function createPersistenceManager(editor: Editor, portfolio: PortfolioSta
 	editor.subscriptions.subscribeJsMessage(TriggerIndexedDbWriteDocument, async (autoSaveDocument) => {
 		await storeDocument(autoSaveDocument);
 	});
+	editor.subscriptions.subscribeJsMessage(TriggerIndexedDbWriteJournalEntry, async (journalEntry) => {
+		await appendJournalEntry(journalEntry.documentId, journalEntry.entry);
+	});
 	editor.subscriptions.subscribeJsMessage(TriggerIndexedDbRemoveDocument, async (removeAutoSaveDocument) => {
 		await removeDocument(removeAutoSaveDocument.documentId);
 	});
@@ -98,4 +147,5 @@ export function createPersistenceManager(editor: Editor, portfolio: PortfolioSta
 export async function wipeDocuments(): Promise<void> {
 	await del("documents_tab_order", graphiteStore);
 	await del("documents", graphiteStore);
+	await delMany((await keys(graphiteStore)).filter(isJournalKey), graphiteStore);
 }
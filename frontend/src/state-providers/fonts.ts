@@ -1,7 +1,7 @@
 import { writable } from "svelte/store";
 
 import { type Editor } from "@graphite/wasm-communication/editor";
-import { TriggerFontLoad } from "@graphite/wasm-communication/messages";
+import { type Font, TriggerFallbackFontsLoad, TriggerFontLoad } from "@graphite/wasm-communication/messages";
 
 // eslint-disable-next-line @typescript-eslint/explicit-function-return-type
 export function createFontsState(editor: Editor) {
@@ -47,15 +47,22 @@ export function createFontsState(editor: Editor) {
 		return `${weightName}${isItalic ? " Italic" : ""} (${weight})`;
 	}
 
-	// Subscribe to process backend events
-	editor.subscriptions.subscribeJsMessage(TriggerFontLoad, async (triggerFontLoad) => {
-		const url = await getFontFileUrl(triggerFontLoad.font.fontFamily, triggerFontLoad.font.fontStyle);
+	async function loadFont(font: Font, isDefault: boolean): Promise<void> {
+		const url = await getFontFileUrl(font.fontFamily, font.fontStyle);
 		if (url) {
 			const response = await (await fetch(url)).arrayBuffer();
-			editor.instance.onFontLoad(triggerFontLoad.font.fontFamily, triggerFontLoad.font.fontStyle, url, new Uint8Array(response), triggerFontLoad.isDefault);
+			editor.instance.onFontLoad(font.fontFamily, font.fontStyle, url, new Uint8Array(response), isDefault);
 		} else {
-			editor.instance.errorDialog("Failed to load font", `The font ${triggerFontLoad.font.fontFamily} with style ${triggerFontLoad.font.fontStyle} does not exist`);
+			editor.instance.errorDialog("Failed to load font", `The font ${font.fontFamily} with style ${font.fontStyle} does not exist`);
 		}
+	}
+
+	// Subscribe to process backend events
+	editor.subscriptions.subscribeJsMessage(TriggerFontLoad, async (triggerFontLoad) => {
+		await loadFont(triggerFontLoad.font, triggerFontLoad.isDefault);
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerFallbackFontsLoad, async (triggerFallbackFontsLoad) => {
+		await Promise.all(triggerFallbackFontsLoad.fonts.map((font) => loadFont(font, false)));
 	});
 
 	const fontList = new Promise<{ family: string; variants: string[]; files: Map<string, string> }[]>((resolve) => {
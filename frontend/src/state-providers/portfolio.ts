@@ -3,16 +3,18 @@
 import { writable } from "svelte/store";
 
 import { downloadFileText, downloadFileBlob, upload, downloadFileURL } from "@graphite/utility-functions/files";
-import { extractPixelData, imageToPNG, rasterizeSVG, rasterizeSVGCanvas } from "@graphite/utility-functions/rasterization";
+import { extractPixelData, imageToPNG, rasterizeSVGCanvas } from "@graphite/utility-functions/rasterization";
 import { type Editor } from "@graphite/wasm-communication/editor";
 import {
 	type FrontendDocumentDetails,
 	TriggerCopyToClipboardBlobUrl,
 	TriggerFetchAndOpenDocument,
 	TriggerDownloadBlobUrl,
-	TriggerDownloadRaster,
+	TriggerDownloadImage,
 	TriggerDownloadTextFile,
 	TriggerImport,
+	TriggerImportBrushPreset,
+	TriggerImportBrushTip,
 	TriggerOpenDocument,
 	TriggerRasterizeRegionBelowLayer,
 	TriggerRevokeBlobUrl,
@@ -64,9 +66,28 @@ export function createPortfolioState(editor: Editor) {
 		editor.instance.openDocumentFile(data.filename, data.content);
 	});
 	editor.subscriptions.subscribeJsMessage(TriggerImport, async () => {
+		const data = await upload("image/*,.tif,.tiff,.exr,.psd,.ora", "data");
+		if (/\.(psd|ora)$/i.test(data.filename)) {
+			editor.instance.pasteLayeredImage(data.filename, data.content);
+			return;
+		}
+		if (data.type === "image/svg+xml") {
+			editor.instance.pasteSvg(data.filename, new TextDecoder().decode(data.content));
+			return;
+		}
+
+		// Decoded in Rust so 16-bit and floating point files keep their full precision
+		editor.instance.pasteImageFile(data.content);
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerImportBrushPreset, async () => {
+		const extension = editor.instance.brushPresetSuffix();
+		const data = await upload(extension, "text");
+		editor.instance.loadBrushPreset(data.content);
+	});
+	editor.subscriptions.subscribeJsMessage(TriggerImportBrushTip, async () => {
 		const data = await upload("image/*", "data");
 		const imageData = await extractPixelData(new Blob([data.content], { type: data.type }));
-		editor.instance.pasteImage(new Uint8Array(imageData.data), imageData.width, imageData.height);
+		editor.instance.loadBrushTip(new Uint8Array(imageData.data), imageData.width, imageData.height);
 	});
 	editor.subscriptions.subscribeJsMessage(TriggerDownloadTextFile, (triggerFileDownload) => {
 		downloadFileText(triggerFileDownload.name, triggerFileDownload.document);
@@ -83,22 +104,11 @@ export function createPortfolioState(editor: Editor) {
 	editor.subscriptions.subscribeJsMessage(TriggerCopyToClipboardBlobUrl, (triggerDownloadBlobUrl) => {
 		copyToClipboardFileURL(triggerDownloadBlobUrl.blobUrl);
 	});
-	editor.subscriptions.subscribeJsMessage(TriggerDownloadRaster, async (triggerRasterDownload) => {
-		const { svg, name, mime, size } = triggerRasterDownload;
-
-		// Fill the canvas with white if it'll be a JPEG (which does not support transparency and defaults to black)
-		const backgroundColor = mime.endsWith("jpeg") ? "white" : undefined;
-
-		// Rasterize the SVG to an image file
-		try {
-			const blob = await rasterizeSVG(svg, size.x, size.y, mime, backgroundColor);
-
-			// Have the browser download the file to the user's disk
-			downloadFileBlob(name, blob);
-		} catch {
-			// Fail silently if there's an error rasterizing the SVG, such as a zero-sized image
-		}
+	editor.subscriptions.subscribeJsMessage(TriggerDownloadImage, (triggerDownloadImage) => {
+		const { name, mime, imageData } = triggerDownloadImage;
 
+		// The image is already encoded by the editor, so the browser only has to save it to the user's disk
+		downloadFileBlob(name, new Blob([imageData], { type: mime }));
 	});
 	editor.subscriptions.subscribeJsMessage(UpdateImageData, (updateImageData) => {
 		updateImageData.imageData.forEach(async (element) => {
@@ -122,9 +132,10 @@ export function createPortfolioState(editor: Editor) {
 		try {
 			if (size[0] >= 1 && size[1] >= 1) {
 				const imageData = (await rasterizeSVGCanvas(svg, size[0], size[1])).getContext("2d")?.getImageData(0, 0, size[0], size[1]);
-				if (!imageData) return;
-
-				editor.instance.renderGraphUsingRasterizedRegionBelowLayer(documentId, layerPath, new Uint8Array(imageData.data), imageData.width, imageData.height);
+				if (imageData) {
+					editor.instance.renderGraphUsingRasterizedRegionBelowLayer(documentId, layerPath, new Uint8Array(imageData.data), imageData.width, imageData.height);
+					return;
+				}
 			}
 		}
 		// getImageData may throw an exception if the resolution is too high
@@ -132,6 +143,9 @@ export function createPortfolioState(editor: Editor) {
 			console.error("Failed to rasterize the SVG canvas in JS to be sent back to Rust:", e);
 		}
 
+		// Evaluate the graph without the region below the layer, so anything waiting on this layer's output isn't left waiting forever
+		editor.instance.renderGraphUsingRasterizedRegionBelowLayer(documentId, layerPath, new Uint8Array(), 0, 0);
+
 	});
 	editor.subscriptions.subscribeJsMessage(TriggerRevokeBlobUrl, async (triggerRevokeBlobUrl) => {
 		URL.revokeObjectURL(triggerRevokeBlobUrl.url);
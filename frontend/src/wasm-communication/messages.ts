@@ -108,6 +108,8 @@ export class FrontendNode {
 	readonly disabled!: boolean;
 
 	readonly thumbnailSvg!: string | undefined;
+
+	readonly error!: string | undefined;
 }
 
 export class FrontendNodeLink {
@@ -146,6 +148,14 @@ export class TriggerIndexedDbRemoveDocument extends JsMessage {
 	documentId!: string;
 }
 
+export class TriggerIndexedDbWriteJournalEntry extends JsMessage {
+	// Use a string since IndexedDB can not use BigInts for keys
+	@Transform(({ value }: { value: bigint }) => value.toString())
+	documentId!: string;
+
+	entry!: string;
+}
+
 export class UpdateInputHints extends JsMessage {
 	@Type(() => HintInfo)
 	readonly hintData!: HintData;
@@ -518,7 +528,15 @@ export class TriggerOpenDocument extends JsMessage { }
 
 export class TriggerImport extends JsMessage { }
 
-export class TriggerPaste extends JsMessage { }
+export class TriggerImportBrushPreset extends JsMessage { }
+
+export class TriggerImportBrushTip extends JsMessage { }
+
+export type PasteMode = "All" | "StyleOnly" | "TransformOnly";
+
+export class TriggerPaste extends JsMessage {
+	readonly mode!: PasteMode;
+}
 
 export class TriggerCopyToClipboardBlobUrl extends JsMessage {
 	readonly blobUrl!: string;
@@ -530,15 +548,13 @@ export class TriggerDownloadBlobUrl extends JsMessage {
 	readonly blobUrl!: string;
 }
 
-export class TriggerDownloadRaster extends JsMessage {
-	readonly svg!: string;
-
+export class TriggerDownloadImage extends JsMessage {
 	readonly name!: string;
 
 	readonly mime!: string;
 
-	@TupleToVec2
-	readonly size!: XY;
+	@Transform(({ value }: { value: number[] }) => new Uint8Array(value))
+	readonly imageData!: Uint8Array;
 }
 
 export class TriggerDownloadTextFile extends JsMessage {
@@ -719,6 +735,11 @@ export class Font {
 	fontStyle!: string;
 }
 
+export class TriggerFallbackFontsLoad extends JsMessage {
+	@Type(() => Font)
+	fonts!: Font[];
+}
+
 export class TriggerFontLoad extends JsMessage {
 	@Type(() => Font)
 	font!: Font;
@@ -736,8 +757,10 @@ export class TriggerVisitLink extends JsMessage {
 
 export class TriggerTextCommit extends JsMessage { }
 
-export class TriggerTextCopy extends JsMessage {
-	readonly copyText!: string;
+export class TriggerClipboardWrite extends JsMessage {
+	readonly content!: string;
+
+	readonly svg!: string | undefined;
 }
 
 export class TriggerAboutGraphiteLocalizedCommitDate extends JsMessage {
@@ -1332,6 +1355,8 @@ export class UpdateDocumentModeLayout extends WidgetDiffUpdate { }
 
 export class UpdateGraphViewOverlayButtonLayout extends WidgetDiffUpdate { }
 
+export class UpdateHistoryPanelLayout extends WidgetDiffUpdate { }
+
 export class UpdateLayerTreeOptionsLayout extends WidgetDiffUpdate { }
 
 // Extends JsMessage instead of WidgetDiffUpdate because the menu bar isn't diffed
@@ -1388,16 +1413,21 @@ export const messageMakers: Record<string, MessageMaker> = {
 	DisplayEditableTextboxTransform,
 	DisplayRemoveEditableTextbox,
 	TriggerAboutGraphiteLocalizedCommitDate,
+	TriggerClipboardWrite,
 	TriggerCopyToClipboardBlobUrl,
 	TriggerFetchAndOpenDocument,
 	TriggerDownloadBlobUrl,
-	TriggerDownloadRaster,
+	TriggerDownloadImage,
 	TriggerDownloadTextFile,
+	TriggerFallbackFontsLoad,
 	TriggerFontLoad,
 	TriggerGraphViewOverlay,
 	TriggerImport,
+	TriggerImportBrushPreset,
+	TriggerImportBrushTip,
 	TriggerIndexedDbRemoveDocument,
 	TriggerIndexedDbWriteDocument,
+	TriggerIndexedDbWriteJournalEntry,
 	TriggerLoadAutoSaveDocuments,
 	TriggerLoadPreferences,
 	TriggerOpenDocument,
@@ -1407,7 +1437,6 @@ export const messageMakers: Record<string, MessageMaker> = {
 	TriggerRevokeBlobUrl,
 	TriggerSavePreferences,
 	TriggerTextCommit,
-	TriggerTextCopy,
 	TriggerViewportResize,
 	TriggerVisitLink,
 	UpdateActiveDocument,
@@ -1427,6 +1456,7 @@ export const messageMakers: Record<string, MessageMaker> = {
 	UpdateDocumentTransform,
 	UpdateEyedropperSamplingState,
 	UpdateGraphViewOverlayButtonLayout,
+	UpdateHistoryPanelLayout,
 	UpdateImageData,
 	UpdateInputHints,
 	UpdateLayerTreeOptionsLayout,
@@ -8,11 +8,12 @@ use crate::{EDITOR_HAS_CRASHED, EDITOR_INSTANCES, JS_EDITOR_HANDLES};
 use document_legacy::LayerId;
 use editor::application::generate_uuid;
 use editor::application::Editor;
-use editor::consts::{FILE_SAVE_SUFFIX, GRAPHITE_DOCUMENT_VERSION};
+use editor::consts::{BRUSH_PRESET_SUFFIX, FILE_SAVE_SUFFIX, GRAPHITE_DOCUMENT_VERSION};
 use editor::messages::input_mapper::utility_types::input_keyboard::ModifierKeys;
-use editor::messages::input_mapper::utility_types::input_mouse::{EditorMouseState, ScrollDelta, ViewportBounds};
+use editor::messages::input_mapper::utility_types::input_mouse::{EditorMouseState, ScrollDelta, StylusState, ViewportBounds};
 use editor::messages::portfolio::utility_types::Platform;
 use editor::messages::prelude::*;
+use editor::messages::tool::tool_messages::brush_tool::BrushToolMessageOptionsUpdate;
 use graph_craft::document::NodeId;
 use graphene_core::raster::color::Color;
 
@@ -270,6 +271,12 @@ impl JsEditorHandle {
 		FILE_SAVE_SUFFIX.into()
 	}
 
+	/// Get the constant `BRUSH_PRESET_SUFFIX`
+	#[wasm_bindgen(js_name = brushPresetSuffix)]
+	pub fn brush_preset_suffix(&self) -> String {
+		BRUSH_PRESET_SUFFIX.into()
+	}
+
 	/// Get the constant `GRAPHITE_DOCUMENT_VERSION`
 	#[wasm_bindgen(js_name = graphiteDocumentVersion)]
 	pub fn graphite_document_version(&self) -> String {
@@ -330,15 +337,18 @@ impl JsEditorHandle {
 	}
 
 	#[wasm_bindgen(js_name = openAutoSavedDocument)]
-	pub fn open_auto_saved_document(&self, document_id: u64, document_name: String, document_is_saved: bool, document_serialized_content: String) {
+	pub fn open_auto_saved_document(&self, document_id: u64, document_name: String, document_is_saved: bool, document_serialized_content: String, document_journal: JsValue) -> Result<(), JsValue> {
+		let document_journal = from_value(document_journal).map_err(|error| Error::new(&format!("Could not read the document journal\nDetails:\n{error}")))?;
 		let message = PortfolioMessage::OpenDocumentFileWithId {
 			document_id,
 			document_name,
 			document_is_auto_saved: true,
 			document_is_saved,
 			document_serialized_content,
+			document_journal,
 		};
 		self.dispatch(message);
+		Ok(())
 	}
 
 	#[wasm_bindgen(js_name = triggerAutoSave)]
@@ -374,8 +384,9 @@ impl JsEditorHandle {
 
 	/// Mouse movement within the screenspace bounds of the viewport
 	#[wasm_bindgen(js_name = onMouseMove)]
-	pub fn on_mouse_move(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8) {
-		let editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+	pub fn on_mouse_move(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8, pressure: f64, tilt_x: f64, tilt_y: f64, twist: f64, timestamp: f64) {
+		let mut editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+		editor_mouse_state.stylus = StylusState::new(pressure, tilt_x, tilt_y, twist, timestamp);
 
 		let modifier_keys = ModifierKeys::from_bits(modifiers).expect("Invalid modifier keys");
 
@@ -397,8 +408,9 @@ impl JsEditorHandle {
 
 	/// A mouse button depressed within screenspace the bounds of the viewport
 	#[wasm_bindgen(js_name = onMouseDown)]
-	pub fn on_mouse_down(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8) {
-		let editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+	pub fn on_mouse_down(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8, pressure: f64, tilt_x: f64, tilt_y: f64, twist: f64, timestamp: f64) {
+		let mut editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+		editor_mouse_state.stylus = StylusState::new(pressure, tilt_x, tilt_y, twist, timestamp);
 
 		let modifier_keys = ModifierKeys::from_bits(modifiers).expect("Invalid modifier keys");
 
@@ -408,8 +420,9 @@ impl JsEditorHandle {
 
 	/// A mouse button released
 	#[wasm_bindgen(js_name = onMouseUp)]
-	pub fn on_mouse_up(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8) {
-		let editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+	pub fn on_mouse_up(&self, x: f64, y: f64, mouse_keys: u8, modifiers: u8, pressure: f64, tilt_x: f64, tilt_y: f64, twist: f64, timestamp: f64) {
+		let mut editor_mouse_state = EditorMouseState::from_keys_and_editor_position(mouse_keys, (x, y).into());
+		editor_mouse_state.stylus = StylusState::new(pressure, tilt_x, tilt_y, twist, timestamp);
 
 		let modifier_keys = ModifierKeys::from_bits(modifiers).expect("Invalid modifier keys");
 
@@ -521,11 +534,13 @@ impl JsEditorHandle {
 		Ok(())
 	}
 
-	/// Paste layers from a serialized json representation
+	/// Paste the layers and nodes, or only the style or transform of the layers, read from the system clipboard
 	#[wasm_bindgen(js_name = pasteSerializedData)]
-	pub fn paste_serialized_data(&self, data: String) {
-		let message = PortfolioMessage::PasteSerializedData { data };
+	pub fn paste_serialized_data(&self, data: String, mode: JsValue) -> Result<(), JsValue> {
+		let mode = from_value(mode).map_err(|error| Error::new(&format!("Could not read the paste mode\nDetails:\n{error}")))?;
+		let message = PortfolioMessage::PasteSerializedData { data, mode };
 		self.dispatch(message);
+		Ok(())
 	}
 
 	/// Modify the layer selection based on the layer which is clicked while holding down the <kbd>Ctrl</kbd> and/or <kbd>Shift</kbd> modifier keys used for range selection behavior
@@ -660,13 +675,6 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
-	/// Pastes the nodes based on serialized data
-	#[wasm_bindgen(js_name = pasteSerializedNodes)]
-	pub fn paste_serialized_nodes(&self, serialized_nodes: String) {
-		let message = NodeGraphMessage::PasteNodes { serialized_nodes };
-		self.dispatch(message);
-	}
-
 	/// Notifies the backend that the user double clicked a node
 	#[wasm_bindgen(js_name = doubleClickNode)]
 	pub fn double_click_node(&self, node: u64) {
@@ -700,6 +708,45 @@ impl JsEditorHandle {
 		self.dispatch(message);
 	}
 
+	/// Pastes an image file, which is decoded at its full bit depth
+	#[wasm_bindgen(js_name = pasteImageFile)]
+	pub fn paste_image_file(&self, data: Vec<u8>, mouse_x: Option<f64>, mouse_y: Option<f64>) {
+		let mouse = mouse_x.and_then(|x| mouse_y.map(|y| (x, y)));
+		let message = DocumentMessage::PasteImageFile { data, mouse };
+		self.dispatch(message);
+	}
+
+	/// Imports an SVG file as editable layers
+	#[wasm_bindgen(js_name = pasteSvg)]
+	pub fn paste_svg(&self, name: String, svg: String, mouse_x: Option<f64>, mouse_y: Option<f64>) {
+		let mouse = mouse_x.and_then(|x| mouse_y.map(|y| (x, y)));
+		let message = DocumentMessage::PasteSvg { name, svg, mouse };
+		self.dispatch(message);
+	}
+
+	/// Imports a layered Photoshop or OpenRaster file as a folder of image layers
+	#[wasm_bindgen(js_name = pasteLayeredImage)]
+	pub fn paste_layered_image(&self, name: String, data: Vec<u8>, mouse_x: Option<f64>, mouse_y: Option<f64>) {
+		let mouse = mouse_x.and_then(|x| mouse_y.map(|y| (x, y)));
+		let message = DocumentMessage::PasteLayeredImage { name, data, mouse };
+		self.dispatch(message);
+	}
+
+	/// Load a brush preset file into the Brush tool
+	#[wasm_bindgen(js_name = loadBrushPreset)]
+	pub fn load_brush_preset(&self, preset: String) {
+		let message = BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::LoadPreset(preset));
+		self.dispatch(message);
+	}
+
+	/// Use an image as the tip of the Brush tool
+	#[wasm_bindgen(js_name = loadBrushTip)]
+	pub fn load_brush_tip(&self, image_data: Vec<u8>, width: u32, height: u32) {
+		let image = graphene_core::raster::Image::from_image_data(&image_data, width, height);
+		let message = BrushToolMessage::UpdateOptions(BrushToolMessageOptionsUpdate::TipImage(image));
+		self.dispatch(message);
+	}
+
 	/// Toggle visibility of a layer from the layer list
 	#[wasm_bindgen(js_name = toggleLayerVisibility)]
 	pub fn toggle_layer_visibility(&self, layer_path: Vec<LayerId>) {
@@ -1,4 +1,4 @@
-use crate::raster::ImageFrame;
+use crate::raster::{ColorSpace, ImageFrame};
 use crate::text::FontCache;
 use crate::transform::{Transform, TransformMut};
 use crate::{Color, Node};
@@ -123,6 +123,35 @@ pub enum ApplicationError {
 	InvalidUrl,
 }
 
+impl core::fmt::Display for ApplicationError {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			ApplicationError::NotFound => write!(f, "The resource could not be found"),
+			ApplicationError::InvalidUrl => write!(f, "The resource's URL is invalid"),
+		}
+	}
+}
+
+/// Why a resource could not be loaded or decoded, passed along the graph as the error of a `Result` in place of the node's output.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ResourceError {
+	Load(ApplicationError),
+	Decode(alloc::string::String),
+}
+
+impl core::fmt::Display for ResourceError {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			ResourceError::Load(error) => write!(f, "{error}"),
+			ResourceError::Decode(description) => write!(f, "The image could not be decoded: {description}"),
+		}
+	}
+}
+
+unsafe impl StaticType for ResourceError {
+	type Static = ResourceError;
+}
+
 #[derive(Debug, Clone)]
 pub enum NodeGraphUpdateMessage {
 	ImaginateStatusUpdate,
@@ -138,6 +167,10 @@ pub trait GetImaginatePreferences {
 
 pub struct EditorApi<'a, Io> {
 	pub image_frame: Option<ImageFrame<Color>>,
+	/// The time in seconds since the start of the document's animation at which the graph is evaluated.
+	pub time: f64,
+	/// The working color space of the document, which the colors of images loaded by the graph are converted into.
+	pub color_space: ColorSpace,
 	pub font_cache: &'a FontCache,
 	pub application_io: &'a Io,
 	pub node_graph_message_sender: &'a dyn NodeGraphUpdateSender,
@@ -148,6 +181,8 @@ impl<'a, Io> Clone for EditorApi<'a, Io> {
 	fn clone(&self) -> Self {
 		Self {
 			image_frame: self.image_frame.clone(),
+			time: self.time,
+			color_space: self.color_space,
 			font_cache: self.font_cache,
 			application_io: self.application_io,
 			node_graph_message_sender: self.node_graph_message_sender,
@@ -158,20 +193,27 @@ impl<'a, Io> Clone for EditorApi<'a, Io> {
 
 impl<'a, T> PartialEq for EditorApi<'a, T> {
 	fn eq(&self, other: &Self) -> bool {
-		self.image_frame == other.image_frame && self.font_cache == other.font_cache
+		self.image_frame == other.image_frame && self.time == other.time && self.color_space == other.color_space && self.font_cache == other.font_cache
 	}
 }
 
 impl<'a, T> Hash for EditorApi<'a, T> {
 	fn hash<H: Hasher>(&self, state: &mut H) {
 		self.image_frame.hash(state);
+		self.time.to_bits().hash(state);
+		self.color_space.hash(state);
 		self.font_cache.hash(state);
 	}
 }
 
 impl<'a, T> Debug for EditorApi<'a, T> {
 	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-		f.debug_struct("EditorApi").field("image_frame", &self.image_frame).field("font_cache", &self.font_cache).finish()
+		f.debug_struct("EditorApi")
+			.field("image_frame", &self.image_frame)
+			.field("time", &self.time)
+			.field("color_space", &self.color_space)
+			.field("font_cache", &self.font_cache)
+			.finish()
 	}
 }
 
@@ -200,3 +242,37 @@ impl ExtractImageFrame {
 		Self
 	}
 }
+
+/// Outputs the time in seconds at which the graph is evaluated, which advances from frame to frame when exporting animations.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExtractTime;
+
+impl<'a: 'input, 'input, T> Node<'input, EditorApi<'a, T>> for ExtractTime {
+	type Output = f32;
+	fn eval(&'input self, editor_api: EditorApi<'a, T>) -> Self::Output {
+		editor_api.time as f32
+	}
+}
+
+impl ExtractTime {
+	pub fn new() -> Self {
+		Self
+	}
+}
+
+/// Outputs the working color space of the document the graph belongs to.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ExtractColorSpace;
+
+impl<'a: 'input, 'input, T> Node<'input, EditorApi<'a, T>> for ExtractColorSpace {
+	type Output = ColorSpace;
+	fn eval(&'input self, editor_api: EditorApi<'a, T>) -> Self::Output {
+		editor_api.color_space
+	}
+}
+
+impl ExtractColorSpace {
+	pub fn new() -> Self {
+		Self
+	}
+}
@@ -1,10 +1,14 @@
-use crate::raster::{Image, ImageFrame};
+use crate::consts::{LAYER_OUTLINE_STROKE_COLOR, LAYER_OUTLINE_STROKE_WEIGHT};
+use crate::raster::{BlendMode, Image, ImageFrame};
+use crate::vector::style::{Fill, Stroke, ViewMode};
 use crate::{uuid::generate_uuid, vector::VectorData, Artboard, Color, GraphicElementData, GraphicGroup};
 use quad::Quad;
 
 use glam::{DAffine2, DVec2};
 
 mod quad;
+mod rasterizer;
+pub use rasterizer::{Paint, RasterRender};
 
 /// Mutable state used whilst rendering to an SVG
 pub struct SvgRender {
@@ -108,6 +112,7 @@ pub fn format_transform_matrix(transform: DAffine2) -> String {
 
 pub trait GraphicElementRendered {
 	fn render_svg(&self, render: &mut SvgRender, render_params: &RenderParams);
+	fn render_raster(&self, render: &mut RasterRender, render_params: &RenderParams);
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]>;
 }
 
@@ -115,6 +120,18 @@ impl GraphicElementRendered for GraphicGroup {
 	fn render_svg(&self, render: &mut SvgRender, render_params: &RenderParams) {
 		self.iter().for_each(|element| element.graphic_element_data.render_svg(render, render_params))
 	}
+	fn render_raster(&self, render: &mut RasterRender, render_params: &RenderParams) {
+		for element in self.iter().filter(|element| element.visible) {
+			if element.blend_mode == BlendMode::Normal && element.opacity >= 1. {
+				element.graphic_element_data.render_raster(render, render_params);
+			} else {
+				// Render the element on its own so it is blended as a whole
+				let mut layer = render.new_layer();
+				element.graphic_element_data.render_raster(&mut layer, render_params);
+				render.composite(&layer.image, element.blend_mode, element.opacity);
+			}
+		}
+	}
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		self.iter().filter_map(|element| element.graphic_element_data.bounding_box(transform)).reduce(Quad::combine_bounds)
 	}
@@ -125,17 +142,29 @@ impl GraphicElementRendered for VectorData {
 		let layer_bounds = self.bounding_box().unwrap_or_default();
 		let transformed_bounds = self.bounding_box_with_transform(render.transform).unwrap_or_default();
 
-		let mut path = String::new();
-		for subpath in &self.subpaths {
-			let _ = subpath.subpath_to_svg(&mut path, self.transform * render.transform);
+		for (subpaths, style) in self.styled_subpaths() {
+			let mut path = String::new();
+			for subpath in subpaths {
+				let _ = subpath.subpath_to_svg(&mut path, self.transform * render.transform);
+			}
+			render.leaf_tag("path", |attributes| {
+				attributes.push("class", "vector-data");
+				attributes.push("d", path);
+				let render = &mut attributes.0;
+				let style = style.render(render_params.view_mode, &mut render.svg_defs, render.transform, layer_bounds, transformed_bounds);
+				attributes.push_val(style);
+			});
+		}
+	}
+	fn render_raster(&self, render: &mut RasterRender, render_params: &RenderParams) {
+		let layer_bounds = self.bounding_box().unwrap_or_default();
+		for (subpaths, style) in self.styled_subpaths() {
+			let (fill, stroke) = match render_params.view_mode {
+				ViewMode::Outline => (Fill::None, Some(Stroke::new(Some(LAYER_OUTLINE_STROKE_COLOR), LAYER_OUTLINE_STROKE_WEIGHT))),
+				_ => (style.fill().clone(), style.stroke()),
+			};
+			render.draw_path(subpaths, render.transform * self.transform, &fill, stroke.as_ref(), layer_bounds);
 		}
-		render.leaf_tag("path", |attributes| {
-			attributes.push("class", "vector-data");
-			attributes.push("d", path);
-			let render = &mut attributes.0;
-			let style = self.style.render(render_params.view_mode, &mut render.svg_defs, render.transform, layer_bounds, transformed_bounds);
-			attributes.push_val(style);
-		});
 	}
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		self.bounding_box_with_transform(self.transform * transform)
@@ -193,6 +222,25 @@ impl GraphicElementRendered for Artboard {
 			},
 		);
 	}
+	fn render_raster(&self, render: &mut RasterRender, render_params: &RenderParams) {
+		let (min, max) = (self.location.as_dvec2(), (self.location + self.dimensions).as_dvec2());
+		let bounds = [min, DVec2::new(max.x, min.y), max, DVec2::new(min.x, max.y)]
+			.map(|corner| render.transform.transform_point2(corner))
+			.to_vec();
+
+		// Background, the label is only shown in the viewport
+		render.fill_polygons(&[bounds.clone()], &Paint::solid(self.background));
+
+		// Contents
+		if self.clip {
+			let mut layer = render.new_layer();
+			self.graphic_group.render_raster(&mut layer, render_params);
+			layer.clip(&bounds);
+			render.composite(&layer.image, BlendMode::Normal, 1.);
+		} else {
+			self.graphic_group.render_raster(render, render_params);
+		}
+	}
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		let artboard_bounds = (transform * Quad::from_box([self.location.as_dvec2(), self.location.as_dvec2() + self.dimensions.as_dvec2()])).bounding_box();
 		[self.graphic_group.bounding_box(transform), Some(artboard_bounds)].into_iter().flatten().reduce(Quad::combine_bounds)
@@ -212,6 +260,9 @@ impl GraphicElementRendered for ImageFrame<Color> {
 		});
 		render.image_data.push((uuid, self.image.clone()))
 	}
+	fn render_raster(&self, render: &mut RasterRender, _render_params: &RenderParams) {
+		render.draw_image(&self.image, render.transform * self.transform);
+	}
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		let transform = self.transform * transform;
 		(transform.matrix2 != glam::DMat2::ZERO).then(|| (transform * Quad::from_box([DVec2::ZERO, DVec2::ONE])).bounding_box())
@@ -229,6 +280,17 @@ impl GraphicElementRendered for GraphicElementData {
 		}
 	}
 
+	fn render_raster(&self, render: &mut RasterRender, render_params: &RenderParams) {
+		match self {
+			GraphicElementData::VectorShape(vector_data) => vector_data.render_raster(render, render_params),
+			GraphicElementData::ImageFrame(image_frame) => image_frame.render_raster(render, render_params),
+			// Text has no rendering of its own yet
+			GraphicElementData::Text(_) => {}
+			GraphicElementData::GraphicGroup(graphic_group) => graphic_group.render_raster(render, render_params),
+			GraphicElementData::Artboard(artboard) => artboard.render_raster(render, render_params),
+		}
+	}
+
 	fn bounding_box(&self, transform: DAffine2) -> Option<[DVec2; 2]> {
 		match self {
 			GraphicElementData::VectorShape(vector_data) => GraphicElementRendered::bounding_box(&**vector_data, transform),
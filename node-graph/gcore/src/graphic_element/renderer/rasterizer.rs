@@ -0,0 +1,650 @@
+//! CPU rasterization of graphic elements into an [`Image`], so raster exports produce the same pixels on every host without a browser rendering the SVG.
+//!
+//! Shapes are flattened into polygons in pixel space and filled with exact area coverage anti-aliasing. Strokes are built as a union of polygons for each segment,
+//! join and cap, which the nonzero coverage accumulation merges without seams. Like the SVG output, vector colors are treated as gamma encoded sRGB with
+//! straight alpha while images and the output hold linear colors with premultiplied alpha.
+
+use crate::raster::{blend_colors, sample_bilinear, BlendMode, Image};
+use crate::uuid::ManipulatorGroupId;
+use crate::vector::style::{Fill, GradientType, LineCap, LineJoin, Stroke};
+use crate::Color;
+
+use bezier_rs::{Bezier, Subpath, TValue};
+use glam::{DAffine2, DVec2};
+
+/// The largest distance in pixels between a curve and the line segments it is flattened into.
+const FLATTENING_TOLERANCE: f64 = 0.1;
+/// Upper limit of the number of line segments a single bezier or circle is flattened into.
+const MAX_FLATTENING_STEPS: usize = 256;
+/// Upper limit of the number of samples per axis taken for each pixel of a shrunk image.
+const MAX_IMAGE_SAMPLES: usize = 4;
+
+/// Mutable state used whilst rendering to an image
+pub struct RasterRender {
+	pub image: Image<Color>,
+	/// Maps document space to the pixel coordinates of the image
+	pub transform: DAffine2,
+}
+
+impl RasterRender {
+	/// A transparent image of the given size.
+	pub fn new(width: u32, height: u32, transform: DAffine2) -> Self {
+		Self {
+			image: Image::new(width, height, Color::TRANSPARENT),
+			transform,
+		}
+	}
+
+	/// A transparent image of the same size and transform, used to render content which is composited as a whole.
+	pub fn new_layer(&self) -> Self {
+		Self::new(self.image.width, self.image.height, self.transform)
+	}
+
+	/// The factor stroke weights and dash lengths are scaled by, which are measured in document space like in the SVG output.
+	fn stroke_scale(&self) -> f64 {
+		self.transform.matrix2.determinant().abs().sqrt()
+	}
+
+	/// Composites another image of the same size on top of this one.
+	pub fn composite(&mut self, layer: &Image<Color>, blend_mode: BlendMode, opacity: f32) {
+		for (background, &foreground) in self.image.data.iter_mut().zip(&layer.data) {
+			if foreground.a() > 0. {
+				*background = blend_colors(foreground, *background, blend_mode, opacity);
+			}
+		}
+	}
+
+	/// Makes everything outside of the polygon (given in pixel space) transparent.
+	pub fn clip(&mut self, polygon: &[DVec2]) {
+		let width = self.image.width as usize;
+		let mut coverage = vec![0.; self.image.data.len()];
+		if let Some(mask) = CoverageMask::new(self.image.width, self.image.height, &[polygon.to_vec()]) {
+			mask.for_each(|x, y, value| coverage[x + y * width] = value);
+		}
+		for (pixel, coverage) in self.image.data.iter_mut().zip(coverage) {
+			*pixel = pixel.apply_opacity(coverage);
+		}
+	}
+
+	/// Fills the polygons (given in pixel space) with the nonzero winding rule.
+	pub fn fill_polygons(&mut self, polygons: &[Vec<DVec2>], paint: &Paint) {
+		let Some(mask) = CoverageMask::new(self.image.width, self.image.height, polygons) else { return };
+		let width = self.image.width as usize;
+		let data = &mut self.image.data;
+		mask.for_each(|x, y, coverage| {
+			let color = paint.color_at(DVec2::new(x as f64 + 0.5, y as f64 + 0.5)).apply_opacity(coverage);
+			let pixel = &mut data[x + y * width];
+			*pixel = pixel.alpha_blend(color);
+		});
+	}
+
+	/// Draws the fill and stroke of the subpaths, which are in a layer space mapped to the pixels of the image by `layer_to_pixels`.
+	///
+	/// Gradients are positioned relative to the layer space bounds of the subpaths, as in the SVG output.
+	pub fn draw_path(&mut self, subpaths: &[Subpath<ManipulatorGroupId>], layer_to_pixels: DAffine2, fill: &Fill, stroke: Option<&Stroke>, layer_bounds: [DVec2; 2]) {
+		let polylines = subpaths.iter().filter_map(|subpath| flatten_subpath(subpath, layer_to_pixels)).collect::<Vec<_>>();
+		if polylines.is_empty() {
+			return;
+		}
+
+		if let Some(paint) = Paint::from_fill(fill, layer_to_pixels, layer_bounds) {
+			let polygons = polylines.iter().map(|(points, _)| points.clone()).collect::<Vec<_>>();
+			self.fill_polygons(&polygons, &paint);
+		}
+
+		let Some(stroke) = stroke else { return };
+		let Some(color) = stroke.color else { return };
+		let scale = self.stroke_scale();
+		let width = stroke.weight * scale;
+		if width <= 0. || color.a() <= 0. {
+			return;
+		}
+
+		let dashes = dash_pattern(&stroke.dash_lengths, scale);
+		let polygons = polylines
+			.iter()
+			.flat_map(|(points, closed)| match &dashes {
+				// Flattened closed subpaths end with their closing segment, so dashes continue along it
+				Some(dashes) => dash_polyline(points, dashes, stroke.dash_offset * scale).into_iter().map(|dash| (dash, false)).collect::<Vec<_>>(),
+				None => vec![(points.clone(), *closed)],
+			})
+			.flat_map(|(points, closed)| stroke_polyline(&points, closed, width / 2., stroke.line_cap, stroke.line_join, stroke.line_join_miter_limit))
+			.collect::<Vec<_>>();
+		self.fill_polygons(&polygons, &Paint::solid(color));
+	}
+
+	/// Draws an image stretched over the unit square, which is mapped to the pixels of the target by `transform`.
+	pub fn draw_image(&mut self, image: &Image<Color>, transform: DAffine2) {
+		if image.width == 0 || image.height == 0 || transform.matrix2.determinant().abs() <= f64::EPSILON {
+			return;
+		}
+
+		let corners = [DVec2::ZERO, DVec2::X, DVec2::ONE, DVec2::Y].map(|corner| transform.transform_point2(corner));
+		let Some(mask) = CoverageMask::new(self.image.width, self.image.height, &[corners.to_vec()]) else {
+			return;
+		};
+
+		// Take several samples per pixel when the image is shrunk so all of its pixels contribute
+		let inverse = transform.inverse();
+		let texels = DVec2::new(image.width as f64, image.height as f64);
+		let footprint = (inverse.matrix2.x_axis * texels).length().max((inverse.matrix2.y_axis * texels).length());
+		let samples = (footprint.ceil() as usize).clamp(1, MAX_IMAGE_SAMPLES);
+
+		let width = self.image.width as usize;
+		let data = &mut self.image.data;
+		mask.for_each(|x, y, coverage| {
+			let mut sum = [0.; 4];
+			for sample_y in 0..samples {
+				for sample_x in 0..samples {
+					let offset = (DVec2::new(sample_x as f64, sample_y as f64) + 0.5) / samples as f64;
+					let position = inverse.transform_point2(DVec2::new(x as f64, y as f64) + offset).clamp(DVec2::ZERO, DVec2::ONE);
+					let color = sample_bilinear(image, position);
+					sum = [sum[0] + color.r(), sum[1] + color.g(), sum[2] + color.b(), sum[3] + color.a()];
+				}
+			}
+			let weight = coverage / (samples * samples) as f32;
+			let color = Color::from_rgbaf32_unchecked(sum[0] * weight, sum[1] * weight, sum[2] * weight, sum[3] * weight);
+			let pixel = &mut data[x + y * width];
+			*pixel = pixel.alpha_blend(color);
+		});
+	}
+}
+
+/// How the inside of a shape is colored, in pixel space.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Paint {
+	/// A linear color with premultiplied alpha
+	Solid(Color),
+	Gradient {
+		gradient_type: GradientType,
+		start: DVec2,
+		end: DVec2,
+		/// Positions along the gradient with gamma encoded colors in straight alpha, which is the color space SVG gradients are interpolated in
+		stops: Vec<(f64, Color)>,
+	},
+}
+
+impl Paint {
+	/// A solid paint of a gamma encoded color with straight alpha, as used in the vector styles.
+	pub fn solid(color: Color) -> Self {
+		Self::Solid(to_linear_premultiplied(color))
+	}
+
+	/// Positions the fill in pixel space. Gradient positions are relative to the bounds of the shape in its layer space.
+	pub fn from_fill(fill: &Fill, layer_to_pixels: DAffine2, layer_bounds: [DVec2; 2]) -> Option<Self> {
+		match fill {
+			Fill::None => None,
+			Fill::Solid(color) => Some(Self::solid(*color)),
+			Fill::Gradient(gradient) => {
+				let stops = gradient.positions.iter().filter_map(|&(position, color)| color.map(|color| (position, color))).collect::<Vec<_>>();
+				if stops.is_empty() {
+					return None;
+				}
+				let bounds_to_pixels = layer_to_pixels * DAffine2::from_scale_angle_translation(layer_bounds[1] - layer_bounds[0], 0., layer_bounds[0]);
+				Some(Self::Gradient {
+					gradient_type: gradient.gradient_type,
+					start: bounds_to_pixels.transform_point2(gradient.start),
+					end: bounds_to_pixels.transform_point2(gradient.end),
+					stops,
+				})
+			}
+		}
+	}
+
+	/// The linear color with premultiplied alpha at a point in pixel space.
+	pub fn color_at(&self, point: DVec2) -> Color {
+		match self {
+			Self::Solid(color) => *color,
+			Self::Gradient { gradient_type, start, end, stops } => {
+				let axis = *end - *start;
+				let t = match gradient_type {
+					GradientType::Linear if axis.length_squared() > 0. => (point - *start).dot(axis) / axis.length_squared(),
+					GradientType::Radial if axis.length_squared() > 0. => (point - *start).length() / axis.length(),
+					_ => 1.,
+				};
+				to_linear_premultiplied(gradient_color(stops, t.clamp(0., 1.)))
+			}
+		}
+	}
+}
+
+/// The color of the gradient at a position, outside of the stops the nearest stop color extends.
+fn gradient_color(stops: &[(f64, Color)], t: f64) -> Color {
+	let next = stops.iter().position(|&(position, _)| position > t);
+	match next {
+		Some(0) => stops[0].1,
+		Some(index) => {
+			let (start, start_color) = stops[index - 1];
+			let (end, end_color) = stops[index];
+			start_color.lerp(end_color, ((t - start) / (end - start)).clamp(0., 1.) as f32)
+		}
+		None => stops[stops.len() - 1].1,
+	}
+}
+
+/// Converts a gamma encoded color with straight alpha, as used in the vector styles, into a linear color with premultiplied alpha.
+fn to_linear_premultiplied(color: Color) -> Color {
+	let linear = color.to_linear_srgb();
+	Color::from_unassociated_alpha(linear.r(), linear.g(), linear.b(), linear.a())
+}
+
+/// Flattens a subpath into a polyline in pixel space, returning whether it is closed.
+fn flatten_subpath(subpath: &Subpath<ManipulatorGroupId>, transform: DAffine2) -> Option<(Vec<DVec2>, bool)> {
+	let first = subpath.manipulator_groups().first()?;
+	let mut points = vec![transform.transform_point2(first.anchor)];
+	for bezier in subpath.iter() {
+		flatten_bezier(&bezier.apply_transformation(|point| transform.transform_point2(point)), &mut points);
+	}
+	points.iter().all(|point| point.is_finite()).then_some((points, subpath.closed()))
+}
+
+/// Appends the points of a bezier, except its start point, with enough steps to stay within the flattening tolerance.
+fn flatten_bezier(bezier: &Bezier, points: &mut Vec<DVec2>) {
+	let control_points = bezier.get_points().collect::<Vec<_>>();
+	let degree = (control_points.len() - 1) as f64;
+	// A curve deviates from its chords by at most degree * (degree - 1) / 8 * max |second difference of the control points| / steps²
+	let second_difference = control_points.windows(3).map(|points| (points[0] - 2. * points[1] + points[2]).length()).fold(0., f64::max);
+	let steps = (degree * (degree - 1.) * second_difference / (8. * FLATTENING_TOLERANCE)).sqrt().ceil();
+	let steps = (steps as usize).clamp(1, MAX_FLATTENING_STEPS);
+	points.extend((1..=steps).map(|step| bezier.evaluate(TValue::Parametric(step as f64 / steps as f64))));
+}
+
+/// A polygon approximating a circle.
+fn circle(center: DVec2, radius: f64) -> Vec<DVec2> {
+	let steps = core::f64::consts::PI / (1. - FLATTENING_TOLERANCE / radius).clamp(-1., 1.).acos();
+	let steps = (steps.ceil() as usize).clamp(8, MAX_FLATTENING_STEPS);
+	(0..steps)
+		.map(|step| center + radius * DVec2::from_angle(core::f64::consts::TAU * step as f64 / steps as f64))
+		.collect()
+}
+
+/// The dash and gap lengths in pixels, repeated to an even count like SVG does. Patterns which don't produce dashes draw solid strokes.
+fn dash_pattern(dash_lengths: &[f32], scale: f64) -> Option<Vec<f64>> {
+	let valid = dash_lengths.iter().all(|&length| length >= 0.) && dash_lengths.iter().sum::<f32>() > 0.;
+	if !valid {
+		return None;
+	}
+	let repeats = if dash_lengths.len() % 2 == 1 { 2 } else { 1 };
+	Some(dash_lengths.iter().cycle().take(dash_lengths.len() * repeats).map(|&length| length as f64 * scale).collect())
+}
+
+/// Splits a polyline into the polylines of its dashes, alternating between dash and gap lengths starting at the offset into the pattern.
+fn dash_polyline(points: &[DVec2], dashes: &[f64], offset: f64) -> Vec<Vec<DVec2>> {
+	let pattern_length = dashes.iter().sum::<f64>();
+
+	// Advance into the pattern by the offset
+	let mut index = 0;
+	let mut remaining = dashes[0];
+	let mut phase = offset.rem_euclid(pattern_length);
+	while phase > 0. {
+		if phase >= remaining {
+			phase -= remaining;
+			index = (index + 1) % dashes.len();
+			remaining = dashes[index];
+		} else {
+			remaining -= phase;
+			phase = 0.;
+		}
+	}
+
+	let mut result = Vec::new();
+	let mut current = if index % 2 == 0 { points.first().copied().into_iter().collect() } else { Vec::new() };
+	for segment in points.windows(2) {
+		let (mut start, end) = (segment[0], segment[1]);
+		let mut length = start.distance(end);
+		if length <= 0. {
+			continue;
+		}
+		let direction = (end - start) / length;
+
+		while length > remaining {
+			let point = start + direction * remaining;
+			length -= remaining;
+			start = point;
+			if index % 2 == 0 {
+				current.push(point);
+				result.push(core::mem::take(&mut current));
+			} else {
+				current = vec![point];
+			}
+			index = (index + 1) % dashes.len();
+			remaining = dashes[index];
+		}
+
+		remaining -= length;
+		if index % 2 == 0 {
+			current.push(end);
+		}
+	}
+	if index % 2 == 0 && !current.is_empty() {
+		result.push(current);
+	}
+	result
+}
+
+/// The polygons covering the stroke of a polyline, which all wind in the same direction so they merge when filled.
+fn stroke_polyline(points: &[DVec2], closed: bool, half_width: f64, cap: LineCap, join: LineJoin, miter_limit: f64) -> Vec<Vec<DVec2>> {
+	let mut points = points.to_vec();
+	points.dedup_by(|a, b| a.distance_squared(*b) < 1e-12);
+	if closed && points.len() > 1 && points[0].distance_squared(points[points.len() - 1]) < 1e-12 {
+		points.pop();
+	}
+
+	// Zero length subpaths only show their caps
+	if points.len() < 2 {
+		let Some(&point) = points.first() else { return Vec::new() };
+		return match cap {
+			LineCap::Butt => Vec::new(),
+			LineCap::Round => vec![circle(point, half_width)],
+			LineCap::Square => vec![[DVec2::new(-1., -1.), DVec2::new(1., -1.), DVec2::ONE, DVec2::new(-1., 1.)]
+				.map(|corner| point + corner * half_width)
+				.to_vec()],
+		};
+	}
+
+	let segment_count = if closed { points.len() } else { points.len() - 1 };
+	let segment = |index: usize| (points[index], points[(index + 1) % points.len()]);
+	let direction = |index: usize| {
+		let (start, end) = segment(index);
+		(end - start).normalize()
+	};
+
+	let mut polygons = Vec::new();
+	for index in 0..segment_count {
+		let (start, end) = segment(index);
+		let normal = direction(index).perp() * half_width;
+		polygons.push(vec![start + normal, end + normal, end - normal, start - normal]);
+	}
+
+	// Joins between consecutive segments
+	let joins = if closed { 0..segment_count } else { 1..segment_count };
+	for index in joins {
+		let incoming = direction((index + segment_count - 1) % segment_count);
+		let outgoing = direction(index);
+		let vertex = points[index];
+		let turn = incoming.perp_dot(outgoing);
+		if turn.abs() < 1e-9 && incoming.dot(outgoing) > 0. {
+			continue;
+		}
+
+		// The join fills the gap on the outer side of the turn
+		let side = if turn > 0. { -1. } else { 1. };
+		let incoming_corner = vertex + incoming.perp() * half_width * side;
+		let outgoing_corner = vertex + outgoing.perp() * half_width * side;
+		let half_turn_cosine = ((1. + incoming.dot(outgoing)) / 2.).max(0.).sqrt();
+		match join {
+			LineJoin::Round => polygons.push(circle(vertex, half_width)),
+			LineJoin::Miter if half_turn_cosine > 0. && 1. / half_turn_cosine <= miter_limit => {
+				let tip = vertex + (incoming.perp() + outgoing.perp()).normalize() * side * half_width / half_turn_cosine;
+				polygons.push(vec![vertex, incoming_corner, tip, outgoing_corner]);
+			}
+			_ => polygons.push(vec![vertex, incoming_corner, outgoing_corner]),
+		}
+	}
+
+	// Caps at both ends of open polylines
+	if !closed {
+		let ends = [(points[0], -direction(0)), (points[points.len() - 1], direction(segment_count - 1))];
+		for (point, outward) in ends {
+			let normal = outward.perp() * half_width;
+			match cap {
+				LineCap::Butt => {}
+				LineCap::Round => polygons.push(circle(point, half_width)),
+				LineCap::Square => {
+					let extent = outward * half_width;
+					polygons.push(vec![point + normal, point + normal + extent, point - normal + extent, point - normal]);
+				}
+			}
+		}
+	}
+
+	for polygon in &mut polygons {
+		if signed_area(polygon) < 0. {
+			polygon.reverse();
+		}
+	}
+	polygons
+}
+
+fn signed_area(polygon: &[DVec2]) -> f64 {
+	let next = polygon.iter().cycle().skip(1);
+	polygon.iter().zip(next).map(|(a, b)| a.perp_dot(*b)).sum::<f64>() / 2.
+}
+
+/// The coverage of the pixels within the bounds of some polygons, computed by accumulating the signed area each polygon edge covers to its right.
+struct CoverageMask {
+	/// The position of the mask within the image
+	offset: (usize, usize),
+	width: usize,
+	height: usize,
+	/// Each row has two extra cells for the area of edges at the right border
+	area: Vec<f32>,
+}
+
+impl CoverageMask {
+	/// Rasterizes the implicitly closed polygons, given in pixel space, within the image bounds.
+	fn new(image_width: u32, image_height: u32, polygons: &[Vec<DVec2>]) -> Option<Self> {
+		let points = || polygons.iter().flatten().filter(|point| point.is_finite());
+		let min = points().fold(DVec2::splat(f64::INFINITY), |min, &point| min.min(point));
+		let max = points().fold(DVec2::splat(f64::NEG_INFINITY), |max, &point| max.max(point));
+		if !min.is_finite() || !max.is_finite() {
+			return None;
+		}
+
+		let (left, top) = (min.x.floor().max(0.) as usize, min.y.floor().max(0.) as usize);
+		let (right, bottom) = ((max.x.ceil().max(0.) as usize).min(image_width as usize), (max.y.ceil().max(0.) as usize).min(image_height as usize));
+		if left >= right || top >= bottom {
+			return None;
+		}
+
+		let (width, height) = (right - left, bottom - top);
+		let mut mask = Self {
+			offset: (left, top),
+			width,
+			height,
+			area: vec![0.; (width + 2) * height],
+		};
+		let origin = DVec2::new(left as f64, top as f64);
+		for polygon in polygons.iter().filter(|polygon| polygon.iter().all(|point| point.is_finite())) {
+			for (index, &start) in polygon.iter().enumerate() {
+				let end = polygon[(index + 1) % polygon.len()];
+				mask.add_line(start - origin, end - origin);
+			}
+		}
+		Some(mask)
+	}
+
+	fn add_line(&mut self, from: DVec2, to: DVec2) {
+		if from.y == to.y {
+			return;
+		}
+		let (direction, top, bottom) = if from.y < to.y { (1., from, to) } else { (-1., to, from) };
+		let dx_dy = (bottom.x - top.x) / (bottom.y - top.y);
+		let width = self.width as f64;
+
+		let first_row = top.y.max(0.).floor() as usize;
+		let last_row = (bottom.y.max(0.).ceil() as usize).min(self.height);
+		for row in first_row..last_row {
+			let row_top = (row as f64).max(top.y);
+			let row_bottom = ((row + 1) as f64).min(bottom.y);
+			if row_bottom <= row_top {
+				continue;
+			}
+			let x_top = top.x + (row_top - top.y) * dx_dy;
+			let x_bottom = top.x + (row_bottom - top.y) * dx_dy;
+			// Edges beyond the left border cover whole pixels and edges beyond the right border cover none
+			let (left, right) = (x_top.min(x_bottom).clamp(0., width), x_top.max(x_bottom).clamp(0., width));
+			self.add_span(row, left, right, (row_bottom - row_top) * direction);
+		}
+	}
+
+	/// Adds an edge piece within one row, spanning from `left` to `right` horizontally, with a signed height.
+	fn add_span(&mut self, row: usize, left: f64, right: f64, height: f64) {
+		// The integral of the coverage a vertical edge at `-u` gives the pixel `[0, 1]`
+		let integral = |u: f64| {
+			if u <= 0. {
+				0.
+			} else if u <= 1. {
+				u * u / 2.
+			} else {
+				u - 0.5
+			}
+		};
+		// The fraction of the pixel in a column which lies to the right of the edge, averaged over the edge
+		let coverage = |column: f64| {
+			if right - left < 1e-9 {
+				(column + 1. - left).clamp(0., 1.)
+			} else {
+				(integral(column + 1. - left) - integral(column + 1. - right)) / (right - left)
+			}
+		};
+
+		let stride = self.width + 2;
+		let first_column = left.floor() as usize;
+		let last_column = (right.floor() as usize + 1).min(stride - 1);
+		let mut previous = 0.;
+		for column in first_column..=last_column {
+			let current = coverage(column as f64);
+			self.area[row * stride + column] += ((current - previous) * height) as f32;
+			previous = current;
+		}
+	}
+
+	/// Calls the function with the image coordinates and coverage of each pixel the polygons cover.
+	fn for_each(&self, mut function: impl FnMut(usize, usize, f32)) {
+		let stride = self.width + 2;
+		for row in 0..self.height {
+			let mut accumulated = 0.;
+			for column in 0..self.width {
+				accumulated += self.area[row * stride + column];
+				let coverage = accumulated.abs().min(1.);
+				if coverage > 1e-4 {
+					function(self.offset.0 + column, self.offset.1 + row, coverage);
+				}
+			}
+		}
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn coverage(width: u32, height: u32, polygons: &[Vec<DVec2>]) -> Vec<f32> {
+		let mut result = vec![0.; (width * height) as usize];
+		if let Some(mask) = CoverageMask::new(width, height, polygons) {
+			mask.for_each(|x, y, value| result[x + y * width as usize] = value);
+		}
+		result
+	}
+
+	fn rectangle(min: DVec2, max: DVec2) -> Vec<DVec2> {
+		vec![min, DVec2::new(max.x, min.y), max, DVec2::new(min.x, max.y)]
+	}
+
+	#[test]
+	fn rectangle_coverage_is_exact() {
+		let result = coverage(4, 2, &[rectangle(DVec2::new(0.5, 0.), DVec2::new(2.25, 1.))]);
+		let expected = [0.5, 1., 0.25, 0.];
+		for (value, expected) in result[..4].iter().zip(expected) {
+			assert!((value - expected).abs() < 1e-5, "{value} != {expected}");
+		}
+		assert!(result[4..].iter().all(|&value| value == 0.));
+	}
+
+	#[test]
+	fn circle_coverage_matches_area() {
+		let polygon = circle(DVec2::splat(16.), 10.);
+		let result = coverage(32, 32, &[polygon.clone()]);
+		let area = result.iter().sum::<f32>() as f64;
+		assert!((area - signed_area(&polygon).abs()).abs() < 0.01, "{area}");
+		assert!((area - core::f64::consts::PI * 100.).abs() < 10., "{area}");
+	}
+
+	#[test]
+	fn polygons_outside_the_image_are_clipped() {
+		// Covers the left half of the image while extending far beyond it
+		let result = coverage(4, 4, &[rectangle(DVec2::new(-100., -100.), DVec2::new(2., 100.))]);
+		for row in result.chunks(4) {
+			assert_eq!(row, [1., 1., 0., 0.]);
+		}
+	}
+
+	#[test]
+	fn overlapping_same_winding_does_not_exceed_full_coverage() {
+		let square = rectangle(DVec2::ZERO, DVec2::splat(2.));
+		let mut result = coverage(2, 2, &[square.clone(), square.clone()]);
+		assert!(result.iter().all(|&value| value == 1.));
+
+		// An opposite winding inner polygon cuts a hole
+		let mut hole = rectangle(DVec2::ZERO, DVec2::ONE);
+		hole.reverse();
+		result = coverage(2, 2, &[square, hole]);
+		assert_eq!(result, [0., 1., 1., 1.]);
+	}
+
+	#[test]
+	fn stroke_covers_line_width() {
+		let polygons = stroke_polyline(&[DVec2::new(0., 2.), DVec2::new(4., 2.)], false, 1., LineCap::Butt, LineJoin::Miter, 4.);
+		let result = coverage(4, 4, &polygons);
+		for (row, expected) in result.chunks(4).zip([0., 1., 1., 0.]) {
+			assert!(row.iter().all(|&value| (value - expected).abs() < 1e-5), "{row:?}");
+		}
+	}
+
+	#[test]
+	fn miter_join_fills_corner() {
+		let corner = [DVec2::new(1., 6.), DVec2::new(1., 1.), DVec2::new(6., 1.)];
+		let miter = coverage(8, 8, &stroke_polyline(&corner, false, 1., LineCap::Butt, LineJoin::Miter, 4.));
+		let bevel = coverage(8, 8, &stroke_polyline(&corner, false, 1., LineCap::Butt, LineJoin::Bevel, 4.));
+		// The outer corner pixel is covered by the miter but only half by the bevel
+		assert!((miter[0] - 1.).abs() < 1e-5);
+		assert!((bevel[0] - 0.5).abs() < 1e-5);
+	}
+
+	#[test]
+	fn dashes_follow_pattern() {
+		let dashes = dash_polyline(&[DVec2::ZERO, DVec2::new(10., 0.)], &[2., 3.], 1.);
+		let lengths = dashes.iter().map(|dash| dash[0].distance(dash[dash.len() - 1])).collect::<Vec<_>>();
+		assert_eq!(dashes[0][0], DVec2::ZERO);
+		assert_eq!(lengths, [1., 2., 1.]);
+		assert_eq!(dashes[1][0], DVec2::new(4., 0.));
+	}
+
+	#[test]
+	fn linear_gradient_interpolates_stops() {
+		let paint = Paint::Gradient {
+			gradient_type: GradientType::Linear,
+			start: DVec2::ZERO,
+			end: DVec2::new(10., 0.),
+			stops: vec![(0., Color::BLACK), (1., Color::WHITE)],
+		};
+		let red_at = |x: f64| paint.color_at(DVec2::new(x, 3.)).r();
+		assert!(red_at(-5.).abs() < 1e-5);
+		assert!((red_at(20.) - 1.).abs() < 1e-5);
+		assert!((red_at(5.) - Color::srgb_to_linear(0.5)).abs() < 1e-5);
+	}
+
+	#[test]
+	fn image_is_drawn_at_transform() {
+		let image = Image {
+			width: 2,
+			height: 1,
+			data: vec![Color::RED, Color::WHITE],
+		};
+		let mut render = RasterRender::new(4, 2, DAffine2::IDENTITY);
+		render.draw_image(&image, DAffine2::from_scale(DVec2::new(4., 2.)));
+		assert_eq!(render.image.data[0], Color::RED);
+		assert_eq!(render.image.data[7], Color::WHITE);
+	}
+
+	#[test]
+	fn layers_composite_with_blend_mode() {
+		let mut render = RasterRender::new(1, 1, DAffine2::IDENTITY);
+		render.fill_polygons(&[rectangle(DVec2::ZERO, DVec2::ONE)], &Paint::Solid(Color::from_rgbf32_unchecked(0.5, 0.5, 0.5)));
+		let mut layer = render.new_layer();
+		layer.fill_polygons(&[rectangle(DVec2::ZERO, DVec2::ONE)], &Paint::Solid(Color::from_rgbf32_unchecked(0.5, 1., 1.)));
+		render.composite(&layer.image, BlendMode::Multiply, 1.);
+		assert_eq!(render.image.data[0], Color::from_rgbf32_unchecked(0.25, 0.5, 0.5));
+	}
+}
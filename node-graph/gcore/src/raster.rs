@@ -6,6 +6,7 @@ use bytemuck::{Pod, Zeroable};
 use glam::DVec2;
 
 pub use self::color::{Color, Luma, SRGBA8};
+pub use self::color_space::ColorSpace;
 
 pub mod adjustments;
 pub mod bbox;
@@ -14,9 +15,30 @@ pub mod brightness_contrast;
 #[cfg(not(target_arch = "spirv"))]
 pub mod brush_cache;
 pub mod color;
+pub mod color_space;
 pub mod curve;
 pub mod discrete_srgb;
+#[cfg(feature = "std")]
+pub mod flood_fill;
+#[cfg(feature = "std")]
+pub mod icc;
+#[cfg(feature = "std")]
+pub mod morphology;
+#[cfg(feature = "std")]
+pub mod palette;
+#[cfg(feature = "std")]
+pub mod warp;
 pub use adjustments::*;
+#[cfg(feature = "std")]
+pub use color_space::ConvertColorSpaceNode;
+#[cfg(feature = "std")]
+pub use flood_fill::*;
+#[cfg(feature = "std")]
+pub use morphology::*;
+#[cfg(feature = "std")]
+pub use palette::*;
+#[cfg(feature = "std")]
+pub use warp::*;
 
 pub trait Linear {
 	fn from_f32(x: f32) -> Self;
@@ -1,7 +1,7 @@
 #![allow(clippy::too_many_arguments)]
 
 use super::curve::{Curve, CurveManipulatorGroup, ValueMapperNode};
-use super::{Channel, Color, Node};
+use super::{Channel, Color, ColorSpace, Node};
 
 use bezier_rs::{Bezier, TValue};
 use dyn_any::{DynAny, StaticType};
@@ -18,6 +18,12 @@ use spirv_std::num_traits::float::Float;
 pub enum LuminanceCalculation {
 	#[default]
 	SRGB,
+	/// Relative luminance of colors in a Display P3 working space
+	DisplayP3,
+	/// Relative luminance of colors in a Rec. 2020 working space
+	Rec2020,
+	/// Relative luminance of colors in an ACEScg working space
+	AcesCg,
 	Perceptual,
 	AverageChannels,
 	MinimumChannels,
@@ -25,21 +31,52 @@ pub enum LuminanceCalculation {
 }
 
 impl LuminanceCalculation {
-	pub fn list() -> [LuminanceCalculation; 5] {
+	pub fn list() -> [LuminanceCalculation; 8] {
 		[
 			LuminanceCalculation::SRGB,
+			LuminanceCalculation::DisplayP3,
+			LuminanceCalculation::Rec2020,
+			LuminanceCalculation::AcesCg,
 			LuminanceCalculation::Perceptual,
 			LuminanceCalculation::AverageChannels,
 			LuminanceCalculation::MinimumChannels,
 			LuminanceCalculation::MaximumChannels,
 		]
 	}
+
+	pub fn luminance(&self, color: Color) -> f32 {
+		match self {
+			LuminanceCalculation::SRGB => color.luminance_srgb(),
+			LuminanceCalculation::DisplayP3 => ColorSpace::DisplayP3.luminance(color),
+			LuminanceCalculation::Rec2020 => ColorSpace::Rec2020.luminance(color),
+			LuminanceCalculation::AcesCg => ColorSpace::AcesCg.luminance(color),
+			LuminanceCalculation::Perceptual => color.luminance_perceptual(),
+			LuminanceCalculation::AverageChannels => color.average_rgb_channels(),
+			LuminanceCalculation::MinimumChannels => color.minimum_rgb_channels(),
+			LuminanceCalculation::MaximumChannels => color.maximum_rgb_channels(),
+		}
+	}
+}
+
+impl From<ColorSpace> for LuminanceCalculation {
+	/// The relative luminance of colors in the given working space.
+	fn from(color_space: ColorSpace) -> Self {
+		match color_space {
+			ColorSpace::Srgb => LuminanceCalculation::SRGB,
+			ColorSpace::DisplayP3 => LuminanceCalculation::DisplayP3,
+			ColorSpace::Rec2020 => LuminanceCalculation::Rec2020,
+			ColorSpace::AcesCg => LuminanceCalculation::AcesCg,
+		}
+	}
 }
 
 impl core::fmt::Display for LuminanceCalculation {
 	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
 		match self {
 			LuminanceCalculation::SRGB => write!(f, "sRGB"),
+			LuminanceCalculation::DisplayP3 => write!(f, "Display P3"),
+			LuminanceCalculation::Rec2020 => write!(f, "Rec. 2020"),
+			LuminanceCalculation::AcesCg => write!(f, "ACEScg"),
 			LuminanceCalculation::Perceptual => write!(f, "Perceptual"),
 			LuminanceCalculation::AverageChannels => write!(f, "Average Channels"),
 			LuminanceCalculation::MinimumChannels => write!(f, "Minimum Channels"),
@@ -176,13 +213,7 @@ pub struct LuminanceNode<LuminanceCalculation> {
 
 #[node_macro::node_fn(LuminanceNode)]
 fn luminance_color_node(color: Color, luminance_calc: LuminanceCalculation) -> Color {
-	let luminance = match luminance_calc {
-		LuminanceCalculation::SRGB => color.luminance_srgb(),
-		LuminanceCalculation::Perceptual => color.luminance_perceptual(),
-		LuminanceCalculation::AverageChannels => color.average_rgb_channels(),
-		LuminanceCalculation::MinimumChannels => color.minimum_rgb_channels(),
-		LuminanceCalculation::MaximumChannels => color.maximum_rgb_channels(),
-	};
+	let luminance = luminance_calc.luminance(color);
 	color.map_rgb(|_| luminance)
 }
 
@@ -389,13 +420,7 @@ fn threshold_node(color: Color, min_luminance: f32, max_luminance: f32, luminanc
 	let min_luminance = Color::srgb_to_linear(min_luminance / 100.);
 	let max_luminance = Color::srgb_to_linear(max_luminance / 100.);
 
-	let luminance = match luminance_calc {
-		LuminanceCalculation::SRGB => color.luminance_srgb(),
-		LuminanceCalculation::Perceptual => color.luminance_perceptual(),
-		LuminanceCalculation::AverageChannels => color.average_rgb_channels(),
-		LuminanceCalculation::MinimumChannels => color.minimum_rgb_channels(),
-		LuminanceCalculation::MaximumChannels => color.maximum_rgb_channels(),
-	};
+	let luminance = luminance_calc.luminance(color);
 
 	if luminance >= min_luminance && luminance <= max_luminance {
 		Color::WHITE
@@ -61,12 +61,13 @@ impl BrushCacheImpl {
 		if !strokes.is_empty() && self.prev_input.len() > num_blended_strokes {
 			let last_stroke = &self.prev_input[num_blended_strokes];
 			let same_style = strokes[0].style == last_stroke.style;
-			let prev_points = last_stroke.compute_blit_points();
-			let new_points = strokes[0].compute_blit_points();
-			let is_point_prefix = new_points.get(..prev_points.len()) == Some(&prev_points);
-			if same_style && is_point_prefix {
+			// The stamps also carry the stylus input, so brush dynamics are taken into account.
+			let prev_stamps = last_stroke.compute_blit_stamps();
+			let new_stamps = strokes[0].compute_blit_stamps();
+			let is_stamp_prefix = new_stamps.get(..prev_stamps.len()) == Some(&prev_stamps);
+			if same_style && is_stamp_prefix {
 				first_stroke_texture = core::mem::take(&mut self.last_stroke_texture);
-				first_stroke_point_skip = prev_points.len();
+				first_stroke_point_skip = prev_stamps.len();
 			}
 		}
 
@@ -0,0 +1,222 @@
+//! RGB color spaces which the linear colors of a document can be in, and conversions between them.
+//!
+//! Each space is defined by the chromaticities of its primaries and white point along with the transfer function used when its colors are
+//! stored as gamma encoded integers. Conversions go through CIE XYZ adapted to the D50 white point with the Bradford transform, which is
+//! the profile connection space of ICC profiles, so colors from embedded profiles convert the same way.
+
+use crate::Color;
+#[cfg(feature = "std")]
+use crate::Node;
+
+use dyn_any::{DynAny, StaticType};
+#[cfg(feature = "std")]
+use glam::{DMat3, DVec3};
+
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, DynAny, Hash)]
+pub enum ColorSpace {
+	#[default]
+	Srgb,
+	DisplayP3,
+	Rec2020,
+	/// The linear space of the Academy Color Encoding System used for rendering and compositing.
+	AcesCg,
+}
+
+impl ColorSpace {
+	pub fn list() -> [ColorSpace; 4] {
+		[ColorSpace::Srgb, ColorSpace::DisplayP3, ColorSpace::Rec2020, ColorSpace::AcesCg]
+	}
+
+	/// The weights of the red, green and blue channels in the relative luminance of a linear color, which is the Y row of the matrix to XYZ.
+	pub const fn luminance_coefficients(&self) -> [f32; 3] {
+		match self {
+			ColorSpace::Srgb => [0.2126, 0.7152, 0.0722],
+			ColorSpace::DisplayP3 => [0.228_975, 0.691_739, 0.079_287],
+			ColorSpace::Rec2020 => [0.262_700, 0.677_998, 0.059_302],
+			ColorSpace::AcesCg => [0.272_229, 0.674_082, 0.053_689],
+		}
+	}
+
+	/// The relative luminance of a linear color in this space.
+	pub fn luminance(&self, color: Color) -> f32 {
+		let [red, green, blue] = self.luminance_coefficients();
+		red * color.r() + green * color.g() + blue * color.b()
+	}
+}
+
+impl core::fmt::Display for ColorSpace {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			ColorSpace::Srgb => write!(f, "sRGB"),
+			ColorSpace::DisplayP3 => write!(f, "Display P3"),
+			ColorSpace::Rec2020 => write!(f, "Rec. 2020"),
+			ColorSpace::AcesCg => write!(f, "ACEScg"),
+		}
+	}
+}
+
+/// The white point of the ICC profile connection space.
+#[cfg(feature = "std")]
+pub const D50: DVec3 = DVec3::new(0.9642, 1., 0.8249);
+
+#[cfg(feature = "std")]
+impl ColorSpace {
+	/// The xy chromaticities of the red, green and blue primaries.
+	pub fn primaries(&self) -> [(f64, f64); 3] {
+		match self {
+			ColorSpace::Srgb => [(0.64, 0.33), (0.3, 0.6), (0.15, 0.06)],
+			ColorSpace::DisplayP3 => [(0.68, 0.32), (0.265, 0.69), (0.15, 0.06)],
+			ColorSpace::Rec2020 => [(0.708, 0.292), (0.17, 0.797), (0.131, 0.046)],
+			ColorSpace::AcesCg => [(0.713, 0.293), (0.165, 0.83), (0.128, 0.044)],
+		}
+	}
+
+	/// The xy chromaticity of the white point.
+	pub fn white_point(&self) -> (f64, f64) {
+		match self {
+			ColorSpace::Srgb | ColorSpace::DisplayP3 | ColorSpace::Rec2020 => (0.3127, 0.329),
+			ColorSpace::AcesCg => (0.32168, 0.33767),
+		}
+	}
+
+	/// The matrix from linear RGB to XYZ relative to the space's own white point.
+	pub fn to_xyz(&self) -> DMat3 {
+		let xyz = |(x, y): (f64, f64)| DVec3::new(x / y, 1., (1. - x - y) / y);
+		let [red, green, blue] = self.primaries().map(xyz);
+		let primaries = DMat3::from_cols(red, green, blue);
+
+		// Scale the primaries so that full intensity in every channel is the white point
+		let scale = primaries.inverse() * xyz(self.white_point());
+		primaries * DMat3::from_diagonal(scale)
+	}
+
+	/// The Bradford chromatic adaptation from the space's white point to D50.
+	pub fn adaptation_to_d50(&self) -> DMat3 {
+		let (x, y) = self.white_point();
+		chromatic_adaptation(DVec3::new(x / y, 1., (1. - x - y) / y), D50)
+	}
+
+	/// The matrix from linear RGB to XYZ adapted to D50, as stored in the colorant tags of ICC profiles.
+	pub fn to_xyz_d50(&self) -> DMat3 {
+		self.adaptation_to_d50() * self.to_xyz()
+	}
+
+	/// The matrix converting linear colors in this space into linear colors in another.
+	pub fn conversion_to(&self, other: ColorSpace) -> DMat3 {
+		other.to_xyz_d50().inverse() * self.to_xyz_d50()
+	}
+
+	/// Converts a linear color with premultiplied alpha into another space, which is a linear map so premultiplication doesn't need to be undone.
+	pub fn convert(&self, color: Color, other: ColorSpace) -> Color {
+		if *self == other {
+			return color;
+		}
+		convert_with(&self.conversion_to(other), color)
+	}
+
+	/// Converts a gamma encoded channel value of this space into a linear one.
+	pub fn decode(&self, value: f32) -> f32 {
+		match self {
+			ColorSpace::Srgb | ColorSpace::DisplayP3 => Color::srgb_to_linear(value),
+			ColorSpace::Rec2020 => {
+				if value < 0.081 {
+					value / 4.5
+				} else {
+					((value + 0.099) / 1.099).powf(1. / 0.45)
+				}
+			}
+			ColorSpace::AcesCg => value,
+		}
+	}
+
+	/// Converts a linear channel value of this space into the gamma encoded value stored in integer image formats.
+	pub fn encode(&self, value: f32) -> f32 {
+		match self {
+			ColorSpace::Srgb | ColorSpace::DisplayP3 => Color::linear_to_srgb(value),
+			ColorSpace::Rec2020 => {
+				if value < 0.018 {
+					value * 4.5
+				} else {
+					1.099 * value.powf(0.45) - 0.099
+				}
+			}
+			ColorSpace::AcesCg => value,
+		}
+	}
+}
+
+/// Converts linear colors from one color space into another, such as after importing an image whose pixels were assigned the wrong space.
+#[cfg(feature = "std")]
+#[derive(Debug, Clone, Copy)]
+pub struct ConvertColorSpaceNode<From, To> {
+	from: From,
+	to: To,
+}
+
+#[cfg(feature = "std")]
+#[node_macro::node_fn(ConvertColorSpaceNode)]
+fn convert_color_space_node(color: Color, from: ColorSpace, to: ColorSpace) -> Color {
+	from.convert(color, to)
+}
+
+/// Applies a matrix to the red, green and blue channels of a color, leaving alpha unchanged.
+#[cfg(feature = "std")]
+pub fn convert_with(matrix: &DMat3, color: Color) -> Color {
+	let rgb = *matrix * DVec3::new(color.r() as f64, color.g() as f64, color.b() as f64);
+	Color::from_rgbaf32_unchecked(rgb.x as f32, rgb.y as f32, rgb.z as f32, color.a())
+}
+
+/// The Bradford transform adapting XYZ colors seen under one white point to how they appear under another.
+#[cfg(feature = "std")]
+pub fn chromatic_adaptation(source_white: DVec3, destination_white: DVec3) -> DMat3 {
+	let bradford = DMat3::from_cols_array(&[0.8951, -0.7502, 0.0389, 0.2664, 1.7135, -0.0685, -0.1614, 0.0367, 1.0296]);
+	let scale = (bradford * destination_white) / (bradford * source_white);
+	bradford.inverse() * DMat3::from_diagonal(scale) * bradford
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn luminance_coefficients_match_primaries() {
+		for color_space in ColorSpace::list() {
+			let computed = color_space.to_xyz().row(1);
+			let [red, green, blue] = color_space.luminance_coefficients();
+			assert!(computed.abs_diff_eq(DVec3::new(red as f64, green as f64, blue as f64), 1e-4), "{color_space}: {computed}");
+		}
+	}
+
+	#[test]
+	fn white_is_preserved_between_spaces() {
+		for from in ColorSpace::list() {
+			for to in ColorSpace::list() {
+				let white = from.convert(Color::WHITE, to);
+				assert!(
+					(white.r() - 1.).abs() < 1e-4 && (white.g() - 1.).abs() < 1e-4 && (white.b() - 1.).abs() < 1e-4,
+					"{from} to {to}: {white:?}"
+				);
+			}
+		}
+	}
+
+	#[test]
+	fn srgb_red_is_inside_display_p3() {
+		let red = ColorSpace::Srgb.convert(Color::from_rgbaf32_unchecked(1., 0., 0., 1.), ColorSpace::DisplayP3);
+		// Known value of linear sRGB red in linear Display P3
+		assert!((red.r() - 0.8225).abs() < 1e-3 && (red.g() - 0.0332).abs() < 1e-3 && (red.b() - 0.0171).abs() < 1e-3, "{red:?}");
+		let round_trip = ColorSpace::DisplayP3.convert(red, ColorSpace::Srgb);
+		assert!((round_trip.r() - 1.).abs() < 1e-5 && round_trip.g().abs() < 1e-5 && round_trip.b().abs() < 1e-5);
+	}
+
+	#[test]
+	fn transfer_functions_round_trip() {
+		for color_space in ColorSpace::list() {
+			for value in [0., 0.001, 0.05, 0.5, 1.] {
+				assert!((color_space.decode(color_space.encode(value)) - value).abs() < 1e-5, "{color_space}: {value}");
+			}
+		}
+	}
+}
@@ -0,0 +1,276 @@
+//! Flood fill and magic wand selection on raster images.
+//!
+//! Both operations compute a coverage mask for the pixels which are similar to a seed pixel. The magic wand outputs that mask as an opaque grayscale image
+//! (which can be used as the stencil of the mask node or as the segmentation mask of the image segmentation node), the flood fill blends a color into the image with it.
+
+use super::{Color, Image, ImageFrame};
+use crate::Node;
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+use dyn_any::{DynAny, StaticType};
+use glam::{DAffine2, DVec2};
+
+/// Which pixels similar to the seed pixel are selected.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, DynAny)]
+pub enum FloodFillMode {
+	/// Only pixels connected to the seed pixel through other similar pixels.
+	#[default]
+	Contiguous,
+	/// Every similar pixel of the image.
+	Global,
+}
+
+impl FloodFillMode {
+	pub fn list() -> [FloodFillMode; 2] {
+		[FloodFillMode::Contiguous, FloodFillMode::Global]
+	}
+}
+
+impl core::fmt::Display for FloodFillMode {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			FloodFillMode::Contiguous => write!(f, "Contiguous"),
+			FloodFillMode::Global => write!(f, "Global"),
+		}
+	}
+}
+
+/// Converts a position in the layer space of the image into the coordinates of the pixel it lies in, if it lies inside the image.
+pub fn pixel_at_position(image_frame: &ImageFrame<Color>, position: DVec2) -> Option<(u32, u32)> {
+	let (width, height) = (image_frame.image.width, image_frame.image.height);
+	if width == 0 || height == 0 || image_frame.transform.matrix2.determinant() == 0. {
+		return None;
+	}
+
+	let layer_to_pixels = DAffine2::from_scale(DVec2::new(width as f64, height as f64)) * image_frame.transform.inverse();
+	let pixel = layer_to_pixels.transform_point2(position).floor();
+	let inside = pixel.x >= 0. && pixel.y >= 0. && pixel.x < width as f64 && pixel.y < height as f64;
+	inside.then_some((pixel.x as u32, pixel.y as u32))
+}
+
+/// Computes the coverage (in the `0..=1` range) of the selection around the seed pixel.
+///
+/// A pixel is similar to the seed when none of its (premultiplied) channels differs by more than the tolerance, which is given in percent.
+/// Anti-aliasing softens the one pixel wide border of the selection.
+pub fn flood_fill_mask(image: &Image<Color>, seed: (u32, u32), tolerance: f32, mode: FloodFillMode, anti_alias: bool) -> Vec<f32> {
+	let (width, height) = (image.width as usize, image.height as usize);
+	let (seed_x, seed_y) = (seed.0 as usize, seed.1 as usize);
+	if seed_x >= width || seed_y >= height {
+		return vec![0.; width * height];
+	}
+
+	let tolerance = (tolerance / 100.).max(0.);
+	let seed_color = image.data[seed_x + seed_y * width];
+	let similar = |color: Color| {
+		let difference = [color.r() - seed_color.r(), color.g() - seed_color.g(), color.b() - seed_color.b(), color.a() - seed_color.a()];
+		difference.into_iter().all(|channel| channel.abs() <= tolerance)
+	};
+
+	let selected = match mode {
+		FloodFillMode::Global => image.data.iter().map(|&color| similar(color)).collect::<Vec<_>>(),
+		FloodFillMode::Contiguous => {
+			let mut selected = vec![false; width * height];
+			let mut queue = VecDeque::from([seed_x + seed_y * width]);
+			selected[seed_x + seed_y * width] = true;
+
+			while let Some(index) = queue.pop_front() {
+				let (x, y) = (index % width, index / width);
+				let neighbors = [
+					(x > 0).then(|| index - 1),
+					(x + 1 < width).then(|| index + 1),
+					(y > 0).then(|| index - width),
+					(y + 1 < height).then(|| index + width),
+				];
+				for neighbor in neighbors.into_iter().flatten() {
+					if !selected[neighbor] && similar(image.data[neighbor]) {
+						selected[neighbor] = true;
+						queue.push_back(neighbor);
+					}
+				}
+			}
+			selected
+		}
+	};
+
+	if !anti_alias {
+		return selected.into_iter().map(|selected| if selected { 1. } else { 0. }).collect();
+	}
+
+	// Average over the 3x3 neighborhood so pixels on the border of the selection are partially covered
+	let (width, height) = (width as i64, height as i64);
+	let is_selected = |x: i64, y: i64| selected[(x.clamp(0, width - 1) + y.clamp(0, height - 1) * width) as usize];
+	(0..height)
+		.flat_map(|y| (0..width).map(move |x| (x, y)))
+		.map(|(x, y)| {
+			let count = (-1..=1).flat_map(|dy| (-1..=1).map(move |dx| (dx, dy))).filter(|&(dx, dy)| is_selected(x + dx, y + dy)).count();
+			count as f32 / 9.
+		})
+		.collect()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MagicWandNode<Position, Tolerance, Mode, AntiAlias> {
+	position: Position,
+	tolerance: Tolerance,
+	mode: Mode,
+	anti_alias: AntiAlias,
+}
+
+#[node_macro::node_fn(MagicWandNode)]
+fn magic_wand_node(image_frame: ImageFrame<Color>, position: DVec2, tolerance: f32, mode: FloodFillMode, anti_alias: bool) -> ImageFrame<Color> {
+	let (width, height) = (image_frame.image.width, image_frame.image.height);
+	let coverage = match pixel_at_position(&image_frame, position) {
+		Some(seed) => flood_fill_mask(&image_frame.image, seed, tolerance, mode, anti_alias),
+		None => vec![0.; (width * height) as usize],
+	};
+
+	let data = coverage.into_iter().map(Color::from_luminance).collect();
+	ImageFrame {
+		image: Image { width, height, data },
+		transform: image_frame.transform,
+	}
+}
+
+/// One fill of the pixels similar to the one at the position, as done by a click of the fill tool.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Debug, Clone, Copy, PartialEq, DynAny)]
+pub struct FloodFill {
+	/// The position of the seed pixel in the layer space of the image.
+	pub position: DVec2,
+	pub color: Color,
+	/// How much the color of a pixel may differ from the seed pixel to be filled, in percent.
+	pub tolerance: f32,
+	pub mode: FloodFillMode,
+	pub anti_alias: bool,
+}
+
+impl Hash for FloodFill {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.position.x.to_bits().hash(state);
+		self.position.y.to_bits().hash(state);
+		self.color.hash(state);
+		self.tolerance.to_bits().hash(state);
+		self.mode.hash(state);
+		self.anti_alias.hash(state);
+	}
+}
+
+impl FloodFill {
+	/// Blends the color into the pixels of the image selected by this fill.
+	pub fn apply(&self, image_frame: &mut ImageFrame<Color>) {
+		let Some(seed) = pixel_at_position(image_frame, self.position) else { return };
+
+		let coverage = flood_fill_mask(&image_frame.image, seed, self.tolerance, self.mode, self.anti_alias);
+		for (pixel, coverage) in image_frame.image.data.iter_mut().zip(coverage) {
+			if coverage > 0. {
+				*pixel = pixel.lerp(self.color, coverage.min(1.));
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct FloodFillNode<Fills> {
+	fills: Fills,
+}
+
+/// Applies the fills in order, so each of them selects its pixels from the result of the fills before it.
+#[node_macro::node_fn(FloodFillNode)]
+fn flood_fill_node(mut image_frame: ImageFrame<Color>, fills: Vec<FloodFill>) -> ImageFrame<Color> {
+	for fill in &fills {
+		fill.apply(&mut image_frame);
+	}
+	image_frame
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// A 4x4 image with a black left half and a white right half, except for one white pixel in the bottom left corner.
+	fn split_image() -> Image<Color> {
+		let data = (0..16)
+			.map(|index| {
+				let (x, y) = (index % 4, index / 4);
+				if x >= 2 || (x == 0 && y == 3) {
+					Color::WHITE
+				} else {
+					Color::BLACK
+				}
+			})
+			.collect();
+		Image { width: 4, height: 4, data }
+	}
+
+	#[test]
+	fn contiguous_stops_at_different_pixels() {
+		let mask = flood_fill_mask(&split_image(), (3, 0), 0., FloodFillMode::Contiguous, false);
+		let selected = mask.iter().filter(|&&coverage| coverage == 1.).count();
+		assert_eq!(selected, 8);
+		assert_eq!(mask[12], 0.);
+	}
+
+	#[test]
+	fn global_selects_disconnected_pixels() {
+		let mask = flood_fill_mask(&split_image(), (3, 0), 0., FloodFillMode::Global, false);
+		let selected = mask.iter().filter(|&&coverage| coverage == 1.).count();
+		assert_eq!(selected, 9);
+		assert_eq!(mask[12], 1.);
+	}
+
+	#[test]
+	fn tolerance_includes_similar_colors() {
+		let image = Image {
+			width: 2,
+			height: 1,
+			data: vec![Color::from_rgbaf32_unchecked(0.5, 0.5, 0.5, 1.), Color::from_rgbaf32_unchecked(0.55, 0.5, 0.5, 1.)],
+		};
+		assert_eq!(flood_fill_mask(&image, (0, 0), 1., FloodFillMode::Contiguous, false), vec![1., 0.]);
+		assert_eq!(flood_fill_mask(&image, (0, 0), 10., FloodFillMode::Contiguous, false), vec![1., 1.]);
+	}
+
+	#[test]
+	fn anti_aliasing_softens_border() {
+		let mask = flood_fill_mask(&split_image(), (0, 0), 0., FloodFillMode::Contiguous, true);
+		// Inside the black region away from the border
+		assert_eq!(mask[4], 1.);
+		// On the border between black and white
+		assert!(mask[5] > 0. && mask[5] < 1.);
+		assert!(mask[6] > 0. && mask[6] < 1.);
+	}
+
+	#[test]
+	fn position_outside_image_has_no_pixel() {
+		let image_frame = ImageFrame {
+			image: split_image(),
+			transform: DAffine2::from_scale(DVec2::splat(4.)),
+		};
+		assert_eq!(pixel_at_position(&image_frame, DVec2::new(2.5, 1.5)), Some((2, 1)));
+		assert_eq!(pixel_at_position(&image_frame, DVec2::new(-0.5, 1.)), None);
+		assert_eq!(pixel_at_position(&image_frame, DVec2::new(4., 1.)), None);
+	}
+
+	#[test]
+	fn fills_build_upon_the_previous_fills() {
+		let image_frame = ImageFrame {
+			image: split_image(),
+			transform: DAffine2::from_scale(DVec2::splat(4.)),
+		};
+		let fill = |position, color| FloodFill {
+			position,
+			color,
+			tolerance: 0.,
+			mode: FloodFillMode::Contiguous,
+			anti_alias: false,
+		};
+		// The first fill turns the black half white, which joins both halves for the second fill
+		let fills = vec![fill(DVec2::new(0.5, 0.5), Color::WHITE), fill(DVec2::new(3.5, 0.5), Color::BLACK)];
+
+		let filled = FloodFillNode::new(crate::value::ClonedNode::new(fills)).eval(image_frame);
+		assert!(filled.image.data.iter().all(|&pixel| pixel == Color::BLACK));
+	}
+}
@@ -0,0 +1,283 @@
+//! Reading and writing the ICC profiles embedded in image files.
+//!
+//! Only RGB matrix/TRC display profiles are supported, which covers the profiles cameras, phones and image editors embed for working spaces
+//! such as sRGB, Display P3 and Adobe RGB. Profiles using lookup tables are rejected by the parser so callers can fall back to sRGB.
+
+use super::color_space::{convert_with, ColorSpace, D50};
+use crate::Color;
+
+use glam::{DMat3, DVec3};
+
+/// The transfer function of one channel of an ICC profile.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ToneCurve {
+	/// A pure power function.
+	Gamma(f32),
+	/// A table of samples spaced evenly over the encoded range.
+	Table(Vec<f32>),
+	/// A parametric curve with `[g, a, b, c, d, e, f]` parameters, unused parameters being set so the curve matches the ICC function type.
+	Parametric([f32; 7]),
+}
+
+impl ToneCurve {
+	/// Converts an encoded channel value into a linear one.
+	pub fn decode(&self, value: f32) -> f32 {
+		let value = value.clamp(0., 1.);
+		match self {
+			ToneCurve::Gamma(gamma) => value.powf(*gamma),
+			ToneCurve::Table(table) => {
+				let position = value * (table.len() - 1) as f32;
+				let index = (position as usize).min(table.len() - 2);
+				let fraction = position - index as f32;
+				table[index] * (1. - fraction) + table[index + 1] * fraction
+			}
+			ToneCurve::Parametric([g, a, b, c, d, e, f]) => {
+				if value >= *d {
+					(a * value + b).max(0.).powf(*g) + e
+				} else {
+					c * value + f
+				}
+			}
+		}
+	}
+}
+
+/// An RGB matrix/TRC ICC profile.
+#[derive(Debug, Clone, PartialEq)]
+pub struct IccProfile {
+	/// The matrix from linear RGB to XYZ adapted to D50, whose columns are the red, green and blue colorant tags.
+	pub to_xyz_d50: DMat3,
+	pub curves: [ToneCurve; 3],
+}
+
+impl IccProfile {
+	/// Parses an RGB matrix/TRC profile, returning `None` for other kinds of profiles or malformed data.
+	pub fn parse(data: &[u8]) -> Option<Self> {
+		if data.len() < 132 || &data[36..40] != b"acsp" || &data[16..20] != b"RGB " || &data[20..24] != b"XYZ " {
+			return None;
+		}
+
+		// Each entry of the tag table takes 12 bytes, so a count which doesn't fit in the data is malformed
+		let tag_count = read_u32(data, 128)? as usize;
+		if tag_count > (data.len() - 132) / 12 {
+			return None;
+		}
+		let tag = |signature: &[u8; 4]| {
+			(0..tag_count).find_map(|index| {
+				let entry = 132 + index * 12;
+				let offset = read_u32(data, entry + 4)? as usize;
+				let size = read_u32(data, entry + 8)? as usize;
+				(data.get(entry..entry + 4)? == signature).then(|| data.get(offset..offset.checked_add(size)?)).flatten()
+			})
+		};
+
+		let colorant = |signature| read_xyz(tag(signature)?);
+		let to_xyz_d50 = DMat3::from_cols(colorant(b"rXYZ")?, colorant(b"gXYZ")?, colorant(b"bXYZ")?);
+		let curves = [read_curve(tag(b"rTRC")?)?, read_curve(tag(b"gTRC")?)?, read_curve(tag(b"bTRC")?)?];
+
+		Some(Self { to_xyz_d50, curves })
+	}
+
+	/// Converts a gamma encoded color with straight alpha in the profile's space into a linear color with premultiplied alpha in the working space.
+	pub fn to_linear(&self, conversion: &DMat3, red: f32, green: f32, blue: f32, alpha: f32) -> Color {
+		let [r, g, b] = &self.curves;
+		let linear = convert_with(conversion, Color::from_rgbf32_unchecked(r.decode(red), g.decode(green), b.decode(blue)));
+		Color::from_unassociated_alpha(linear.r(), linear.g(), linear.b(), alpha)
+	}
+
+	/// The matrix converting linear colors in the profile's space into linear colors in the working space.
+	pub fn conversion_to(&self, color_space: ColorSpace) -> DMat3 {
+		color_space.to_xyz_d50().inverse() * self.to_xyz_d50
+	}
+}
+
+fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+	Some(u32::from_be_bytes(data.get(offset..offset + 4)?.try_into().ok()?))
+}
+
+fn read_s15_fixed16(data: &[u8], offset: usize) -> Option<f64> {
+	Some(read_u32(data, offset)? as i32 as f64 / 65536.)
+}
+
+fn read_xyz(tag: &[u8]) -> Option<DVec3> {
+	if tag.get(0..4)? != b"XYZ " {
+		return None;
+	}
+	Some(DVec3::new(read_s15_fixed16(tag, 8)?, read_s15_fixed16(tag, 12)?, read_s15_fixed16(tag, 16)?))
+}
+
+fn read_curve(tag: &[u8]) -> Option<ToneCurve> {
+	match tag.get(0..4)? {
+		b"curv" => {
+			let count = read_u32(tag, 8)? as usize;
+			let entry = |index: usize| Some(u16::from_be_bytes(tag.get(12 + index * 2..14 + index * 2)?.try_into().ok()?));
+			match count {
+				0 => Some(ToneCurve::Gamma(1.)),
+				1 => Some(ToneCurve::Gamma(entry(0)? as f32 / 256.)),
+				_ => (0..count)
+					.map(|index| entry(index).map(|value| value as f32 / u16::MAX as f32))
+					.collect::<Option<_>>()
+					.map(ToneCurve::Table),
+			}
+		}
+		b"para" => {
+			let function_type = u16::from_be_bytes(tag.get(8..10)?.try_into().ok()?);
+			let parameter_count = [1, 3, 4, 5, 7].get(function_type as usize)?;
+			let mut parameters = [0.; 7];
+			for (index, parameter) in parameters.iter_mut().enumerate().take(*parameter_count) {
+				*parameter = read_s15_fixed16(tag, 12 + index * 4)? as f32;
+			}
+			let [g, a, b, c, d, e, f] = parameters;
+			// Express every function type as the most general one, type 4
+			let parameters = match function_type {
+				0 => [g, 1., 0., 0., 0., 0., 0.],
+				// Below -b/a the curve is zero
+				1 => [g, a, b, 0., -b / a, 0., 0.],
+				2 => [g, a, b, 0., -b / a, c, c],
+				3 => [g, a, b, c, d, 0., 0.],
+				_ => [g, a, b, c, d, e, f],
+			};
+			Some(ToneCurve::Parametric(parameters))
+		}
+		_ => None,
+	}
+}
+
+/// Writes a version 4 ICC display profile describing one of the working color spaces, for embedding in exported images.
+pub fn icc_profile(color_space: ColorSpace) -> Vec<u8> {
+	let mut tags: Vec<([u8; 4], Vec<u8>)> = Vec::new();
+
+	tags.push((*b"desc", multi_localized_unicode(&color_space.to_string())));
+	tags.push((*b"cprt", multi_localized_unicode("No copyright, use freely")));
+	tags.push((*b"wtpt", xyz_type(D50)));
+
+	let adaptation = color_space.adaptation_to_d50();
+	let mut chad = signature_type(b"sf32");
+	for row in 0..3 {
+		for value in adaptation.row(row).to_array() {
+			chad.extend(s15_fixed16(value));
+		}
+	}
+	tags.push((*b"chad", chad));
+
+	let to_xyz_d50 = color_space.to_xyz_d50();
+	for (signature, column) in [(b"rXYZ", 0), (b"gXYZ", 1), (b"bXYZ", 2)] {
+		tags.push((*signature, xyz_type(to_xyz_d50.col(column))));
+	}
+
+	let curve = parametric_curve(color_space);
+	for signature in [b"rTRC", b"gTRC", b"bTRC"] {
+		tags.push((*signature, curve.clone()));
+	}
+
+	let table_size = 4 + tags.len() * 12;
+	let mut data = vec![0; 128 + table_size];
+	data[128..132].copy_from_slice(&(tags.len() as u32).to_be_bytes());
+	for (index, (signature, tag)) in tags.iter().enumerate() {
+		let entry = 132 + index * 12;
+		let offset = data.len() as u32;
+		data[entry..entry + 4].copy_from_slice(signature);
+		data[entry + 4..entry + 8].copy_from_slice(&offset.to_be_bytes());
+		data[entry + 8..entry + 12].copy_from_slice(&(tag.len() as u32).to_be_bytes());
+		data.extend(tag);
+		// Tags start on four byte boundaries
+		data.resize((data.len() + 3) / 4 * 4, 0);
+	}
+
+	let size = data.len() as u32;
+	data[0..4].copy_from_slice(&size.to_be_bytes());
+	data[8..12].copy_from_slice(&0x0430_0000_u32.to_be_bytes());
+	data[12..16].copy_from_slice(b"mntr");
+	data[16..20].copy_from_slice(b"RGB ");
+	data[20..24].copy_from_slice(b"XYZ ");
+	data[36..40].copy_from_slice(b"acsp");
+	data[68..80].copy_from_slice(&xyz_type(D50)[8..20]);
+
+	data
+}
+
+fn signature_type(signature: &[u8; 4]) -> Vec<u8> {
+	let mut tag = signature.to_vec();
+	tag.extend([0; 4]);
+	tag
+}
+
+fn s15_fixed16(value: f64) -> [u8; 4] {
+	((value * 65536.).round() as i32).to_be_bytes()
+}
+
+fn xyz_type(xyz: DVec3) -> Vec<u8> {
+	let mut tag = signature_type(b"XYZ ");
+	for value in xyz.to_array() {
+		tag.extend(s15_fixed16(value));
+	}
+	tag
+}
+
+fn multi_localized_unicode(text: &str) -> Vec<u8> {
+	let mut tag = signature_type(b"mluc");
+	let text = text.encode_utf16().flat_map(u16::to_be_bytes).collect::<Vec<_>>();
+	tag.extend(1_u32.to_be_bytes());
+	tag.extend(12_u32.to_be_bytes());
+	tag.extend(b"enUS");
+	tag.extend((text.len() as u32).to_be_bytes());
+	tag.extend(28_u32.to_be_bytes());
+	tag.extend(text);
+	tag
+}
+
+fn parametric_curve(color_space: ColorSpace) -> Vec<u8> {
+	let (function_type, parameters): (u16, &[f64]) = match color_space {
+		ColorSpace::Srgb | ColorSpace::DisplayP3 => (3, &[2.4, 1. / 1.055, 0.055 / 1.055, 1. / 12.92, 0.04045]),
+		ColorSpace::Rec2020 => (3, &[1. / 0.45, 1. / 1.099, 0.099 / 1.099, 1. / 4.5, 0.081]),
+		ColorSpace::AcesCg => (0, &[1.]),
+	};
+	let mut tag = signature_type(b"para");
+	tag.extend(function_type.to_be_bytes());
+	tag.extend([0; 2]);
+	for parameter in parameters {
+		tag.extend(s15_fixed16(*parameter));
+	}
+	tag
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn written_profiles_parse() {
+		for color_space in ColorSpace::list() {
+			let profile = IccProfile::parse(&icc_profile(color_space)).expect("Profile should parse");
+			assert!(profile.to_xyz_d50.abs_diff_eq(color_space.to_xyz_d50(), 1e-4), "{color_space}");
+			for value in [0., 0.02, 0.3, 0.8, 1.] {
+				for curve in &profile.curves {
+					assert!((curve.decode(value) - color_space.decode(value)).abs() < 1e-3, "{color_space}: {value}");
+				}
+			}
+		}
+	}
+
+	#[test]
+	fn gamma_and_table_curves() {
+		assert_eq!(ToneCurve::Gamma(2.2).decode(1.), 1.);
+		let table = ToneCurve::Table(vec![0., 0.25, 1.]);
+		assert_eq!(table.decode(0.5), 0.25);
+		assert_eq!(table.decode(0.75), 0.625);
+	}
+
+	#[test]
+	fn non_rgb_profiles_are_rejected() {
+		let mut profile = icc_profile(ColorSpace::Srgb);
+		profile[16..20].copy_from_slice(b"GRAY");
+		assert_eq!(IccProfile::parse(&profile), None);
+		assert_eq!(IccProfile::parse(&[0; 64]), None);
+	}
+
+	#[test]
+	fn tag_counts_past_the_end_are_rejected() {
+		let mut profile = icc_profile(ColorSpace::Srgb);
+		profile[128..132].copy_from_slice(&u32::MAX.to_be_bytes());
+		assert_eq!(IccProfile::parse(&profile), None);
+	}
+}
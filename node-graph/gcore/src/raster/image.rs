@@ -144,7 +144,12 @@ where
 {
 	/// Flattens each channel cast to a u8
 	pub fn into_flat_u8(self) -> (Vec<u8>, u32, u32) {
-		let Image { width, height, data } = self;
+		self.to_flat_u8()
+	}
+
+	/// Flattens each channel cast to a u8, leaving the image as it is
+	pub fn to_flat_u8(&self) -> (Vec<u8>, u32, u32) {
+		let Image { width, height, ref data } = *self;
 		assert!(data.len() == width as usize * height as usize);
 
 		// Cache the last sRGB value we computed, speeds up fills.
@@ -0,0 +1,471 @@
+//! Morphological operators, edge detection and distance transforms on raster images.
+//!
+//! Morphology works on every channel of the (premultiplied) color independently, which keeps the result a valid premultiplied color.
+//! Edge detection and the distance transform reduce the image to a single [`ImageChannel`] first and produce an opaque grayscale image.
+
+use super::{Color, Image, ImageFrame, ImageWindowIterator};
+use crate::Node;
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+use dyn_any::{DynAny, StaticType};
+use glam::DAffine2;
+
+/// The channel of a color which is read by single-channel operations like edge detection and the distance transform.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, DynAny)]
+pub enum ImageChannel {
+	Red,
+	Green,
+	Blue,
+	#[default]
+	Alpha,
+	Luminance,
+}
+
+impl ImageChannel {
+	pub fn list() -> [ImageChannel; 5] {
+		[ImageChannel::Red, ImageChannel::Green, ImageChannel::Blue, ImageChannel::Alpha, ImageChannel::Luminance]
+	}
+
+	pub fn value(&self, color: Color) -> f32 {
+		match self {
+			ImageChannel::Red => color.r(),
+			ImageChannel::Green => color.g(),
+			ImageChannel::Blue => color.b(),
+			ImageChannel::Alpha => color.a(),
+			ImageChannel::Luminance => color.luminance_srgb(),
+		}
+	}
+}
+
+impl core::fmt::Display for ImageChannel {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			ImageChannel::Red => write!(f, "Red"),
+			ImageChannel::Green => write!(f, "Green"),
+			ImageChannel::Blue => write!(f, "Blue"),
+			ImageChannel::Alpha => write!(f, "Alpha"),
+			ImageChannel::Luminance => write!(f, "Luminance"),
+		}
+	}
+}
+
+/// The convolution kernel pair used to approximate the image gradient.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, DynAny)]
+pub enum EdgeDetectionOperator {
+	#[default]
+	Sobel,
+	Prewitt,
+}
+
+impl EdgeDetectionOperator {
+	pub fn list() -> [EdgeDetectionOperator; 2] {
+		[EdgeDetectionOperator::Sobel, EdgeDetectionOperator::Prewitt]
+	}
+
+	/// Weight of the center row/column of the 3x3 kernel, the outer rows/columns have a weight of one.
+	fn center_weight(&self) -> f32 {
+		match self {
+			EdgeDetectionOperator::Sobel => 2.,
+			EdgeDetectionOperator::Prewitt => 1.,
+		}
+	}
+}
+
+impl core::fmt::Display for EdgeDetectionOperator {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			EdgeDetectionOperator::Sobel => write!(f, "Sobel"),
+			EdgeDetectionOperator::Prewitt => write!(f, "Prewitt"),
+		}
+	}
+}
+
+/// Replaces every pixel by the per-channel combination (e.g. `max` or `min`) of all pixels within a disk of the given radius.
+fn morphology(image_frame: ImageFrame<Color>, radius: u32, combine: impl Fn(f32, f32) -> f32) -> ImageFrame<Color> {
+	let (width, height) = (image_frame.image.width, image_frame.image.height);
+	if radius == 0 || width == 0 || height == 0 {
+		return image_frame;
+	}
+
+	let radius_squared = radius as i64 * radius as i64;
+	let slice = image_frame.image.as_slice();
+	let data = (0..width * height)
+		.map(|index| {
+			ImageWindowIterator::new(slice, radius, index)
+				.filter(|&(_, (x, y))| radius_squared >= x as i64 * x as i64 + y as i64 * y as i64)
+				.map(|(pixel, _)| pixel)
+				.reduce(|a, b| Color::from_rgbaf32_unchecked(combine(a.r(), b.r()), combine(a.g(), b.g()), combine(a.b(), b.b()), combine(a.a(), b.a())))
+				.unwrap_or_default()
+		})
+		.collect();
+
+	ImageFrame {
+		image: Image { width, height, data },
+		transform: image_frame.transform,
+	}
+}
+
+pub fn dilate(image_frame: ImageFrame<Color>, radius: u32) -> ImageFrame<Color> {
+	morphology(image_frame, radius, f32::max)
+}
+
+pub fn erode(image_frame: ImageFrame<Color>, radius: u32) -> ImageFrame<Color> {
+	morphology(image_frame, radius, f32::min)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DilateNode<Radius> {
+	radius: Radius,
+}
+
+#[node_macro::node_fn(DilateNode)]
+fn dilate_node(image_frame: ImageFrame<Color>, radius: u32) -> ImageFrame<Color> {
+	dilate(image_frame, radius)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct ErodeNode<Radius> {
+	radius: Radius,
+}
+
+#[node_macro::node_fn(ErodeNode)]
+fn erode_node(image_frame: ImageFrame<Color>, radius: u32) -> ImageFrame<Color> {
+	erode(image_frame, radius)
+}
+
+/// Erosion followed by dilation, removes details smaller than the radius.
+#[derive(Debug, Clone, Copy)]
+pub struct OpenNode<Radius> {
+	radius: Radius,
+}
+
+#[node_macro::node_fn(OpenNode)]
+fn open_node(image_frame: ImageFrame<Color>, radius: u32) -> ImageFrame<Color> {
+	dilate(erode(image_frame, radius), radius)
+}
+
+/// Dilation followed by erosion, fills holes and gaps smaller than the radius.
+#[derive(Debug, Clone, Copy)]
+pub struct CloseNode<Radius> {
+	radius: Radius,
+}
+
+#[node_macro::node_fn(CloseNode)]
+fn close_node(image_frame: ImageFrame<Color>, radius: u32) -> ImageFrame<Color> {
+	erode(dilate(image_frame, radius), radius)
+}
+
+/// Reads the selected channel of every pixel into a flat buffer.
+fn channel_values(image: &Image<Color>, channel: ImageChannel) -> Vec<f32> {
+	image.data.iter().map(|&color| channel.value(color)).collect()
+}
+
+/// Builds an opaque grayscale image from a buffer of values in the `0..=1` range.
+fn grayscale_frame(values: impl Iterator<Item = f32>, width: u32, height: u32, transform: DAffine2) -> ImageFrame<Color> {
+	let data = values.map(|value| Color::from_luminance(value.clamp(0., 1.))).collect();
+	ImageFrame {
+		image: Image { width, height, data },
+		transform,
+	}
+}
+
+/// Computes the horizontal and vertical gradient with a 3x3 kernel, clamping lookups at the image border.
+fn gradients(values: &[f32], width: u32, height: u32, operator: EdgeDetectionOperator) -> Vec<(f32, f32)> {
+	let (width, height) = (width as i64, height as i64);
+	let get = |x: i64, y: i64| values[(x.clamp(0, width - 1) + y.clamp(0, height - 1) * width) as usize];
+	let center = operator.center_weight();
+	// Normalize so the gradient of a hard 0 to 1 step has a magnitude of 1
+	let normalization = 1. / (2. + center);
+
+	(0..height)
+		.flat_map(|y| (0..width).map(move |x| (x, y)))
+		.map(|(x, y)| {
+			let gradient_x = (get(x + 1, y - 1) + center * get(x + 1, y) + get(x + 1, y + 1)) - (get(x - 1, y - 1) + center * get(x - 1, y) + get(x - 1, y + 1));
+			let gradient_y = (get(x - 1, y + 1) + center * get(x, y + 1) + get(x + 1, y + 1)) - (get(x - 1, y - 1) + center * get(x, y - 1) + get(x + 1, y - 1));
+			(gradient_x * normalization, gradient_y * normalization)
+		})
+		.collect()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct EdgeDetectionNode<Operator, Channel> {
+	operator: Operator,
+	channel: Channel,
+}
+
+#[node_macro::node_fn(EdgeDetectionNode)]
+fn edge_detection_node(image_frame: ImageFrame<Color>, operator: EdgeDetectionOperator, channel: ImageChannel) -> ImageFrame<Color> {
+	let (width, height) = (image_frame.image.width, image_frame.image.height);
+	if width == 0 || height == 0 {
+		return image_frame;
+	}
+
+	let values = channel_values(&image_frame.image, channel);
+	let magnitudes = gradients(&values, width, height, operator).into_iter().map(|(x, y)| (x * x + y * y).sqrt());
+	grayscale_frame(magnitudes, width, height, image_frame.transform)
+}
+
+/// Blurs the values with a separable gaussian kernel, clamping lookups at the image border.
+fn gaussian_blur(values: &[f32], width: u32, height: u32, sigma: f64) -> Vec<f32> {
+	if sigma <= 0. {
+		return values.to_vec();
+	}
+
+	let radius = (sigma * 3.).ceil() as i64;
+	let kernel = (-radius..=radius).map(|offset| (-(offset * offset) as f64 / (2. * sigma * sigma)).exp() as f32).collect::<Vec<_>>();
+	let kernel_sum: f32 = kernel.iter().sum();
+
+	let (width, height) = (width as i64, height as i64);
+	let blur_pass = |source: &[f32], horizontal: bool| -> Vec<f32> {
+		(0..height)
+			.flat_map(|y| (0..width).map(move |x| (x, y)))
+			.map(|(x, y)| {
+				let weighted_sum: f32 = (-radius..=radius)
+					.zip(kernel.iter())
+					.map(|(offset, weight)| {
+						let (sample_x, sample_y) = if horizontal {
+							((x + offset).clamp(0, width - 1), y)
+						} else {
+							(x, (y + offset).clamp(0, height - 1))
+						};
+						source[(sample_x + sample_y * width) as usize] * weight
+					})
+					.sum();
+				weighted_sum / kernel_sum
+			})
+			.collect()
+	};
+
+	let horizontal = blur_pass(values, true);
+	blur_pass(&horizontal, false)
+}
+
+/// Canny edge detection: gaussian smoothing, Sobel gradients, non-maximum suppression and hysteresis thresholding.
+///
+/// The thresholds are given in percent of the strongest gradient found in the image.
+pub fn canny_edges(values: &[f32], width: u32, height: u32, sigma: f64, low_threshold: f32, high_threshold: f32) -> Vec<bool> {
+	let smoothed = gaussian_blur(values, width, height, sigma);
+	let gradients = gradients(&smoothed, width, height, EdgeDetectionOperator::Sobel);
+	let magnitudes = gradients.iter().map(|(x, y)| (x * x + y * y).sqrt()).collect::<Vec<_>>();
+	let max_magnitude = magnitudes.iter().copied().fold(0., f32::max);
+	if max_magnitude <= f32::EPSILON {
+		return vec![false; magnitudes.len()];
+	}
+
+	let (width, height) = (width as i64, height as i64);
+	let magnitude_at = |x: i64, y: i64| if x < 0 || y < 0 || x >= width || y >= height { 0. } else { magnitudes[(x + y * width) as usize] };
+
+	// Only keep pixels which are a local maximum along the gradient direction, quantized to one of four directions
+	let suppressed = (0..height)
+		.flat_map(|y| (0..width).map(move |x| (x, y)))
+		.map(|(x, y)| {
+			let index = (x + y * width) as usize;
+			let (gradient_x, gradient_y) = gradients[index];
+			let angle = gradient_y.atan2(gradient_x).to_degrees().rem_euclid(180.);
+			let (dx, dy) = match angle {
+				a if !(22.5..157.5).contains(&a) => (1, 0),
+				a if a < 67.5 => (1, 1),
+				a if a < 112.5 => (0, 1),
+				_ => (-1, 1),
+			};
+			let magnitude = magnitudes[index];
+			let is_maximum = magnitude >= magnitude_at(x + dx, y + dy) && magnitude >= magnitude_at(x - dx, y - dy);
+			if is_maximum {
+				magnitude / max_magnitude
+			} else {
+				0.
+			}
+		})
+		.collect::<Vec<_>>();
+
+	let low_threshold = low_threshold / 100.;
+	let high_threshold = (high_threshold / 100.).max(low_threshold);
+
+	// Hysteresis: weak edges survive only when they are connected to a strong edge
+	let mut edges = vec![false; suppressed.len()];
+	let mut queue = suppressed
+		.iter()
+		.enumerate()
+		.filter(|(_, &value)| value > 0. && value >= high_threshold)
+		.map(|(index, _)| index)
+		.collect::<VecDeque<_>>();
+	queue.iter().for_each(|&index| edges[index] = true);
+
+	while let Some(index) = queue.pop_front() {
+		let (x, y) = (index as i64 % width, index as i64 / width);
+		for (dx, dy) in [(-1, -1), (0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1), (1, 1)] {
+			let (neighbor_x, neighbor_y) = (x + dx, y + dy);
+			if neighbor_x < 0 || neighbor_y < 0 || neighbor_x >= width || neighbor_y >= height {
+				continue;
+			}
+			let neighbor = (neighbor_x + neighbor_y * width) as usize;
+			if !edges[neighbor] && suppressed[neighbor] > 0. && suppressed[neighbor] >= low_threshold {
+				edges[neighbor] = true;
+				queue.push_back(neighbor);
+			}
+		}
+	}
+
+	edges
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CannyEdgeDetectionNode<Channel, Sigma, LowThreshold, HighThreshold> {
+	channel: Channel,
+	sigma: Sigma,
+	low_threshold: LowThreshold,
+	high_threshold: HighThreshold,
+}
+
+#[node_macro::node_fn(CannyEdgeDetectionNode)]
+fn canny_edge_detection_node(image_frame: ImageFrame<Color>, channel: ImageChannel, sigma: f64, low_threshold: f32, high_threshold: f32) -> ImageFrame<Color> {
+	let (width, height) = (image_frame.image.width, image_frame.image.height);
+	if width == 0 || height == 0 {
+		return image_frame;
+	}
+
+	let values = channel_values(&image_frame.image, channel);
+	let edges = canny_edges(&values, width, height, sigma, low_threshold, high_threshold);
+	grayscale_frame(edges.into_iter().map(|edge| if edge { 1. } else { 0. }), width, height, image_frame.transform)
+}
+
+/// One dimensional squared euclidean distance transform of a sampled function (Felzenszwalb & Huttenlocher).
+fn distance_transform_1d(function: &[f32], output: &mut [f32]) {
+	let length = function.len();
+	let mut parabola_locations = vec![0_usize; length];
+	let mut boundaries = vec![0_f32; length + 1];
+	let mut rightmost = 0;
+	boundaries[0] = f32::NEG_INFINITY;
+	boundaries[1] = f32::INFINITY;
+
+	let intersection = |q: usize, p: usize| ((function[q] + (q * q) as f32) - (function[p] + (p * p) as f32)) / (2. * q as f32 - 2. * p as f32);
+
+	for q in 1..length {
+		let mut s = intersection(q, parabola_locations[rightmost]);
+		while s <= boundaries[rightmost] {
+			rightmost -= 1;
+			s = intersection(q, parabola_locations[rightmost]);
+		}
+		rightmost += 1;
+		parabola_locations[rightmost] = q;
+		boundaries[rightmost] = s;
+		boundaries[rightmost + 1] = f32::INFINITY;
+	}
+
+	rightmost = 0;
+	for (q, output) in output.iter_mut().enumerate() {
+		while boundaries[rightmost + 1] < q as f32 {
+			rightmost += 1;
+		}
+		let offset = q as f32 - parabola_locations[rightmost] as f32;
+		*output = offset * offset + function[parabola_locations[rightmost]];
+	}
+}
+
+/// Computes the euclidean distance in pixels from every pixel to the nearest pixel for which `inside` is true.
+pub fn distance_transform(inside: &[bool], width: u32, height: u32) -> Vec<f32> {
+	let (width, height) = (width as usize, height as usize);
+	if width == 0 || height == 0 {
+		return Vec::new();
+	}
+
+	// Larger than any distance within the image while staying finite so the parabola intersections remain well defined
+	let far = ((width * width + height * height) as f32 + 1.) * 2.;
+	let mut squared = inside.iter().map(|&inside| if inside { 0. } else { far }).collect::<Vec<_>>();
+
+	let mut column = vec![0.; height];
+	let mut column_output = vec![0.; height];
+	for x in 0..width {
+		for y in 0..height {
+			column[y] = squared[x + y * width];
+		}
+		distance_transform_1d(&column, &mut column_output);
+		for y in 0..height {
+			squared[x + y * width] = column_output[y];
+		}
+	}
+
+	let mut row_output = vec![0.; width];
+	for row in squared.chunks_exact_mut(width) {
+		distance_transform_1d(row, &mut row_output);
+		row.copy_from_slice(&row_output);
+	}
+
+	squared.into_iter().map(f32::sqrt).collect()
+}
+
+/// Outputs the distance of every pixel to the shape made up of the pixels whose channel is at least the threshold,
+/// mapped from `0` at the shape to `1` at the maximum distance.
+#[derive(Debug, Clone, Copy)]
+pub struct DistanceTransformNode<Channel, Threshold, MaxDistance> {
+	channel: Channel,
+	threshold: Threshold,
+	max_distance: MaxDistance,
+}
+
+#[node_macro::node_fn(DistanceTransformNode)]
+fn distance_transform_node(image_frame: ImageFrame<Color>, channel: ImageChannel, threshold: f32, max_distance: f64) -> ImageFrame<Color> {
+	let (width, height) = (image_frame.image.width, image_frame.image.height);
+	if width == 0 || height == 0 {
+		return image_frame;
+	}
+
+	let threshold = threshold / 100.;
+	let inside = image_frame.image.data.iter().map(|&color| channel.value(color) >= threshold).collect::<Vec<_>>();
+	let max_distance = max_distance.max(f64::EPSILON) as f32;
+	let distances = distance_transform(&inside, width, height).into_iter().map(|distance| distance / max_distance);
+	grayscale_frame(distances, width, height, image_frame.transform)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn single_dot(size: u32) -> ImageFrame<Color> {
+		let mut image = Image::new(size, size, Color::TRANSPARENT);
+		let center = (size / 2 + size / 2 * size) as usize;
+		image.data[center] = Color::WHITE;
+		ImageFrame { image, transform: DAffine2::IDENTITY }
+	}
+
+	#[test]
+	fn dilate_grows_disk() {
+		let dilated = dilate(single_dot(7), 2);
+		let opaque = dilated.image.data.iter().filter(|color| color.a() > 0.5).count();
+		// A disk of radius 2 on the pixel grid covers 13 pixels
+		assert_eq!(opaque, 13);
+	}
+
+	#[test]
+	fn erode_reverts_dilate() {
+		let original = single_dot(9);
+		let closed = erode(dilate(original.clone(), 2), 2);
+		assert_eq!(closed.image, original.image);
+	}
+
+	#[test]
+	fn distance_transform_of_dot() {
+		let inside = single_dot(5).image.data.iter().map(|color| color.a() > 0.5).collect::<Vec<_>>();
+		let distances = distance_transform(&inside, 5, 5);
+		assert_eq!(distances[12], 0.);
+		assert_eq!(distances[2], 2.);
+		assert!((distances[0] - 8_f32.sqrt()).abs() < 1e-5);
+	}
+
+	#[test]
+	fn canny_finds_step_edge() {
+		let (width, height) = (8, 4);
+		let values = (0..width * height).map(|index| if index % width < width / 2 { 0. } else { 1. }).collect::<Vec<_>>();
+		let edges = canny_edges(&values, width, height, 0., 10., 50.);
+		for y in 0..height {
+			let row = &edges[(y * width) as usize..((y + 1) * width) as usize];
+			assert!(row[3] || row[4]);
+			assert!(!row[0] && !row[7]);
+		}
+	}
+}
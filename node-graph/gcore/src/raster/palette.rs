@@ -0,0 +1,223 @@
+//! Dominant color analysis of raster images.
+//!
+//! The palette of an image is found by clustering its pixels, which can then populate the document swatches or pick colors for fills downstream.
+
+use super::{Color, Image, ImageFrame};
+use crate::Node;
+
+use alloc::vec::Vec;
+use core::hash::{Hash, Hasher};
+use dyn_any::{DynAny, StaticType};
+
+/// The most pixels that are looked at when analyzing an image, larger images are sampled with an even stride.
+const MAX_SAMPLES: usize = 16384;
+/// The most passes of k-means refinement run after the median cut has picked the starting clusters.
+const REFINEMENT_ITERATIONS: usize = 8;
+
+/// One of the dominant colors of an image.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[derive(Clone, Copy, Debug, PartialEq, DynAny)]
+pub struct PaletteColor {
+	/// The opaque color, linear in the document's working space like the image it came from.
+	pub color: Color,
+	/// The fraction of the image's (alpha weighted) coverage that is closest to this color, the weights of a palette add up to one.
+	pub weight: f32,
+}
+
+impl Hash for PaletteColor {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.color.hash(state);
+		self.weight.to_bits().hash(state);
+	}
+}
+
+#[derive(Clone, Copy)]
+struct Sample {
+	color: [f32; 3],
+	weight: f32,
+}
+
+/// Finds up to `count` colors which best represent the image, sorted from the most to the least common.
+///
+/// The colors are clustered in gamma encoded space so they are picked closer to how different they look, with each pixel counting by its alpha.
+/// A median cut finds the starting clusters, which are then refined with k-means.
+/// Fewer colors are returned if the image has fewer distinct colors, and none if it is empty or fully transparent.
+pub fn dominant_colors(image: &Image<Color>, count: usize) -> Vec<PaletteColor> {
+	let stride = (image.data.len() / MAX_SAMPLES).max(1);
+	let samples = image
+		.data
+		.iter()
+		.step_by(stride)
+		.filter(|pixel| pixel.a() > f32::EPSILON)
+		.map(|pixel| {
+			let color = pixel.to_unassociated_alpha().to_gamma_srgb();
+			Sample {
+				color: [color.r(), color.g(), color.b()],
+				weight: pixel.a(),
+			}
+		})
+		.collect::<Vec<_>>();
+	if samples.is_empty() || count == 0 {
+		return Vec::new();
+	}
+
+	let mut centroids = median_cut(samples.clone(), count).iter().map(|cluster| mean(cluster).0).collect::<Vec<_>>();
+	let mut weights = Vec::new();
+	for _ in 0..REFINEMENT_ITERATIONS {
+		let mut sums = vec![([0.; 3], 0.); centroids.len()];
+		for sample in &samples {
+			let (sum, weight) = &mut sums[nearest(&centroids, sample.color)];
+			for (sum, channel) in sum.iter_mut().zip(sample.color) {
+				*sum += channel * sample.weight;
+			}
+			*weight += sample.weight;
+		}
+
+		// Clusters which lost all of their samples to their neighbors are dropped
+		let previous = core::mem::take(&mut centroids);
+		weights.clear();
+		for (sum, weight) in sums.into_iter().filter(|&(_, weight)| weight > 0.) {
+			centroids.push(sum.map(|channel| channel / weight));
+			weights.push(weight);
+		}
+		let converged = previous.len() == centroids.len() && previous.iter().zip(&centroids).all(|(a, b)| distance(*a, *b) < 1e-6);
+		if converged {
+			break;
+		}
+	}
+
+	let total = weights.iter().sum::<f32>();
+	let mut palette = centroids
+		.into_iter()
+		.zip(weights)
+		.map(|([r, g, b], weight)| PaletteColor {
+			color: Color::from_rgbaf32_unchecked(r, g, b, 1.).to_linear_srgb(),
+			weight: weight / total,
+		})
+		.collect::<Vec<_>>();
+	palette.sort_by(|a, b| b.weight.total_cmp(&a.weight));
+	palette
+}
+
+/// Splits the samples into up to `count` clusters, each time halving the cluster with the widest spread of color at the weighted median of its widest channel.
+fn median_cut(samples: Vec<Sample>, count: usize) -> Vec<Vec<Sample>> {
+	let mut clusters = vec![samples];
+	while clusters.len() < count {
+		let widest = clusters
+			.iter()
+			.enumerate()
+			.map(|(index, cluster)| (index, widest_channel(cluster)))
+			.max_by(|(_, (_, a)), (_, (_, b))| a.total_cmp(b));
+		let Some((index, (channel, range))) = widest else { break };
+		if range <= 0. {
+			break;
+		}
+
+		let mut cluster = clusters.swap_remove(index);
+		cluster.sort_by(|a, b| a.color[channel].total_cmp(&b.color[channel]));
+		let half = cluster.iter().map(|sample| sample.weight).sum::<f32>() / 2.;
+		let mut accumulated = 0.;
+		let median = cluster
+			.iter()
+			.position(|sample| {
+				accumulated += sample.weight;
+				accumulated >= half
+			})
+			.unwrap_or_default();
+		// Both halves keep at least one sample, and since the channel has a nonzero range they end up with different colors
+		let split = (median + 1).clamp(1, cluster.len() - 1);
+		let upper = cluster.split_off(split);
+		clusters.push(cluster);
+		clusters.push(upper);
+	}
+	clusters
+}
+
+fn widest_channel(cluster: &[Sample]) -> (usize, f32) {
+	(0..3)
+		.map(|channel| {
+			let (min, max) = cluster
+				.iter()
+				.fold((f32::MAX, f32::MIN), |(min, max), sample| (min.min(sample.color[channel]), max.max(sample.color[channel])));
+			(channel, max - min)
+		})
+		.max_by(|(_, a), (_, b)| a.total_cmp(b))
+		.unwrap()
+}
+
+fn mean(cluster: &[Sample]) -> ([f32; 3], f32) {
+	let weight = cluster.iter().map(|sample| sample.weight).sum::<f32>();
+	let sum = cluster
+		.iter()
+		.fold([0.; 3], |sum, sample| [0, 1, 2].map(|channel| sum[channel] + sample.color[channel] * sample.weight));
+	(sum.map(|channel| channel / weight), weight)
+}
+
+fn nearest(centroids: &[[f32; 3]], color: [f32; 3]) -> usize {
+	centroids
+		.iter()
+		.enumerate()
+		.min_by(|(_, a), (_, b)| distance(**a, color).total_cmp(&distance(**b, color)))
+		.map(|(index, _)| index)
+		.unwrap_or_default()
+}
+
+fn distance(a: [f32; 3], b: [f32; 3]) -> f32 {
+	(0..3).map(|channel| (a[channel] - b[channel]).powi(2)).sum()
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct DominantColorsNode<Count> {
+	count: Count,
+}
+
+#[node_macro::node_fn(DominantColorsNode)]
+fn dominant_colors_node(image_frame: ImageFrame<Color>, count: u32) -> Vec<PaletteColor> {
+	dominant_colors(&image_frame.image, count as usize)
+}
+
+/// Picks one color out of a palette, indices past the end clamp to its least common color so the output stays stable as the palette shrinks.
+#[derive(Debug, Clone, Copy)]
+pub struct PaletteColorNode<Index> {
+	index: Index,
+}
+
+#[node_macro::node_fn(PaletteColorNode)]
+fn palette_color_node(palette: Vec<PaletteColor>, index: u32) -> Option<Color> {
+	palette.get(index as usize).or(palette.last()).map(|entry| entry.color)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn image(pixels: &[(Color, usize)]) -> Image<Color> {
+		let data = pixels.iter().flat_map(|&(color, count)| vec![color; count]).collect::<Vec<_>>();
+		Image {
+			width: data.len() as u32,
+			height: 1,
+			data,
+		}
+	}
+
+	#[test]
+	fn dominant_colors_are_weighted_by_coverage() {
+		let red = Color::from_rgbaf32_unchecked(1., 0., 0., 1.);
+		let blue = Color::from_rgbaf32_unchecked(0., 0., 1., 1.);
+		let palette = dominant_colors(&image(&[(red, 75), (blue, 25), (Color::TRANSPARENT, 50)]), 2);
+
+		assert_eq!(palette.len(), 2);
+		for (entry, (color, weight)) in palette.iter().zip([(red, 0.75), (blue, 0.25)]) {
+			assert!((entry.weight - weight).abs() < 1e-4, "{entry:?}");
+			let channels = [entry.color.r() - color.r(), entry.color.g() - color.g(), entry.color.b() - color.b()];
+			assert!(channels.iter().all(|channel| channel.abs() < 1e-4), "{entry:?}");
+		}
+	}
+
+	#[test]
+	fn dominant_colors_of_few_colors() {
+		let gray = Color::from_rgbaf32_unchecked(0.2, 0.2, 0.2, 1.);
+		assert_eq!(dominant_colors(&image(&[(gray, 10)]), 4).len(), 1);
+		assert!(dominant_colors(&image(&[(Color::TRANSPARENT, 10)]), 4).is_empty());
+	}
+}
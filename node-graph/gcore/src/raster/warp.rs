@@ -0,0 +1,420 @@
+//! Non-affine distortions of raster images: perspective, bezier mesh and cage warps.
+//!
+//! Every warp computes for each pixel of the output image the normalized position (`(0, 0)` is the top left and `(1, 1)` the bottom right corner) in the input image it is sampled from.
+//! The output image covers the bounding box of the warped image and keeps roughly the pixel density of the input image.
+
+use super::{Color, Image, ImageFrame};
+use crate::Node;
+
+use alloc::vec::Vec;
+use glam::{DAffine2, DMat3, DVec2, DVec3};
+
+/// Largest width or height of a warped image, protects against runaway sizes when control points are dragged far away.
+const MAX_WARP_SIZE: f64 = 8192.;
+
+/// Number of segments each bezier patch is split into along each axis when tessellating a mesh warp.
+const PATCH_SUBDIVISIONS: usize = 16;
+
+/// Samples the image with bilinear filtering at a normalized position, positions outside the image are transparent.
+pub fn sample_bilinear(image: &Image<Color>, position: DVec2) -> Color {
+	if !(0. ..=1.).contains(&position.x) || !(0. ..=1.).contains(&position.y) || image.width == 0 || image.height == 0 {
+		return Color::TRANSPARENT;
+	}
+
+	let (width, height) = (image.width as i64, image.height as i64);
+	let pixel = position * DVec2::new(width as f64, height as f64) - 0.5;
+	let (x, y) = (pixel.x.floor(), pixel.y.floor());
+	let (fraction_x, fraction_y) = ((pixel.x - x) as f32, (pixel.y - y) as f32);
+	let get = |x: i64, y: i64| image.data[(x.clamp(0, width - 1) + y.clamp(0, height - 1) * width) as usize];
+
+	let (x, y) = (x as i64, y as i64);
+	let top = get(x, y).lerp(get(x + 1, y), fraction_x);
+	let bottom = get(x, y + 1).lerp(get(x + 1, y + 1), fraction_x);
+	top.lerp(bottom, fraction_y)
+}
+
+/// The output raster covering the bounds of a warped image, in the layer space of the image frame.
+struct WarpTarget {
+	width: u32,
+	height: u32,
+	/// Maps pixel coordinates of the output image to layer space
+	pixel_to_layer: DAffine2,
+}
+
+impl WarpTarget {
+	fn new(input_transform: DAffine2, input_width: u32, input_height: u32, points: impl Iterator<Item = DVec2>) -> Option<Self> {
+		let (min, max) = points.fold((DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY)), |(min, max), point| (min.min(point), max.max(point)));
+		let area = input_transform.matrix2.determinant().abs();
+		if !min.is_finite() || !max.is_finite() || area <= f64::EPSILON {
+			return None;
+		}
+
+		// Pixels per unit of layer space in the input image
+		let density = ((input_width as f64 * input_height as f64) / area).sqrt();
+		let size = ((max - min) * density).ceil().clamp(DVec2::ONE, DVec2::splat(MAX_WARP_SIZE));
+		let scale = (max - min).max(DVec2::splat(f64::EPSILON)) / size;
+
+		Some(Self {
+			width: size.x as u32,
+			height: size.y as u32,
+			pixel_to_layer: DAffine2::from_translation(min) * DAffine2::from_scale(scale),
+		})
+	}
+
+	/// Builds the output frame by sampling the input at the normalized position returned for the center of every output pixel.
+	fn render(&self, input: &Image<Color>, source_position: impl Fn(u32, u32, DVec2) -> Option<DVec2>) -> ImageFrame<Color> {
+		let data = (0..self.height)
+			.flat_map(|y| (0..self.width).map(move |x| (x, y)))
+			.map(|(x, y)| {
+				let layer_position = self.pixel_to_layer.transform_point2(DVec2::new(x as f64 + 0.5, y as f64 + 0.5));
+				source_position(x, y, layer_position).map_or(Color::TRANSPARENT, |position| sample_bilinear(input, position))
+			})
+			.collect();
+
+		ImageFrame {
+			image: Image {
+				width: self.width,
+				height: self.height,
+				data,
+			},
+			transform: self.pixel_to_layer * DAffine2::from_scale(DVec2::new(self.width as f64, self.height as f64)),
+		}
+	}
+}
+
+/// Computes the projective transform which maps the unit square onto the quad with the corners in the order top left, top right, bottom right and bottom left.
+pub fn square_to_quad(corners: [DVec2; 4]) -> DMat3 {
+	let [p0, p1, p2, p3] = corners;
+	let d1 = p1 - p2;
+	let d2 = p3 - p2;
+	let d3 = p0 - p1 + p2 - p3;
+
+	let (g, h) = if d3.abs_diff_eq(DVec2::ZERO, 1e-12) {
+		(0., 0.)
+	} else {
+		let determinant = d1.perp_dot(d2);
+		(d3.perp_dot(d2) / determinant, d1.perp_dot(d3) / determinant)
+	};
+	let u = p1 - p0 + g * p1;
+	let v = p3 - p0 + h * p3;
+
+	DMat3::from_cols(DVec3::new(u.x, u.y, g), DVec3::new(v.x, v.y, h), DVec3::new(p0.x, p0.y, 1.))
+}
+
+/// Applies a projective transform to a point, returns `None` for points mapped to infinity.
+pub fn project_point(matrix: DMat3, point: DVec2) -> Option<DVec2> {
+	let projected = matrix * point.extend(1.);
+	(projected.z.abs() > f64::EPSILON).then(|| projected.truncate() / projected.z)
+}
+
+/// Warps the image into the quad given by its corners (top left, top right, bottom right and bottom left) in layer space.
+pub fn perspective_warp(image_frame: ImageFrame<Color>, corners: [DVec2; 4]) -> ImageFrame<Color> {
+	let (width, height) = (image_frame.image.width, image_frame.image.height);
+	if width == 0 || height == 0 {
+		return image_frame;
+	}
+
+	let forward = square_to_quad(corners);
+	if forward.determinant().abs() <= f64::EPSILON {
+		return image_frame;
+	}
+	let inverse = forward.inverse();
+	let Some(target) = WarpTarget::new(image_frame.transform, width, height, corners.into_iter()) else {
+		return image_frame;
+	};
+
+	// Points on the other side of the horizon also project into the unit square, so their sign has to agree with the quad's interior
+	let interior_sign = (inverse * ((corners[0] + corners[1] + corners[2] + corners[3]) / 4.).extend(1.)).z.signum();
+	target.render(&image_frame.image, |_, _, layer_position| {
+		let projected = inverse * layer_position.extend(1.);
+		(projected.z * interior_sign > f64::EPSILON).then(|| projected.truncate() / projected.z)
+	})
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct PerspectiveWarpNode<Corners> {
+	corners: Corners,
+}
+
+/// Corners which aren't exactly four points leave the image unchanged.
+#[node_macro::node_fn(PerspectiveWarpNode)]
+fn perspective_warp_node(image_frame: ImageFrame<Color>, corners: Vec<DVec2>) -> ImageFrame<Color> {
+	match corners[..] {
+		[top_left, top_right, bottom_right, bottom_left] => perspective_warp(image_frame, [top_left, top_right, bottom_right, bottom_left]),
+		_ => image_frame,
+	}
+}
+
+/// Evaluates the cubic bernstein polynomials at `t`.
+fn cubic_bernstein(t: f64) -> [f64; 4] {
+	let s = 1. - t;
+	[s * s * s, 3. * s * s * t, 3. * s * t * t, t * t * t]
+}
+
+/// Control points of a mesh with `columns` by `rows` cubic bezier patches which leave the image unchanged.
+pub fn identity_mesh(columns: u32, rows: u32) -> Vec<DVec2> {
+	let (points_x, points_y) = (columns as usize * 3 + 1, rows as usize * 3 + 1);
+	(0..points_y)
+		.flat_map(|y| (0..points_x).map(move |x| DVec2::new(x as f64 / (points_x - 1) as f64, y as f64 / (points_y - 1) as f64)))
+		.collect()
+}
+
+/// Evaluates a mesh of cubic bezier patches at the normalized position, `u` and `v` run from zero to one across the whole mesh.
+fn evaluate_mesh(control_points: &[DVec2], columns: usize, rows: usize, u: f64, v: f64) -> DVec2 {
+	let points_x = columns * 3 + 1;
+	let column = ((u * columns as f64).floor() as usize).min(columns - 1);
+	let row = ((v * rows as f64).floor() as usize).min(rows - 1);
+	let s = u * columns as f64 - column as f64;
+	let t = v * rows as f64 - row as f64;
+
+	let (weights_s, weights_t) = (cubic_bernstein(s), cubic_bernstein(t));
+	let mut point = DVec2::ZERO;
+	for (j, weight_t) in weights_t.iter().enumerate() {
+		for (i, weight_s) in weights_s.iter().enumerate() {
+			point += control_points[(row * 3 + j) * points_x + column * 3 + i] * *weight_s * *weight_t;
+		}
+	}
+	point
+}
+
+/// Warps the image with a grid of `columns` by `rows` cubic bezier patches.
+///
+/// The control points are given row by row in normalized image coordinates, so a mesh has `(3 * columns + 1) * (3 * rows + 1)` of them.
+pub fn mesh_warp(image_frame: ImageFrame<Color>, columns: u32, rows: u32, control_points: &[DVec2]) -> ImageFrame<Color> {
+	let (width, height) = (image_frame.image.width, image_frame.image.height);
+	let (columns, rows) = (columns as usize, rows as usize);
+	if width == 0 || height == 0 || columns == 0 || rows == 0 || control_points.len() != (columns * 3 + 1) * (rows * 3 + 1) {
+		return image_frame;
+	}
+
+	// Tessellate the mesh into a grid of small quads with known source positions
+	let (vertices_x, vertices_y) = (columns * PATCH_SUBDIVISIONS + 1, rows * PATCH_SUBDIVISIONS + 1);
+	let sources = (0..vertices_y)
+		.flat_map(|y| (0..vertices_x).map(move |x| DVec2::new(x as f64 / (vertices_x - 1) as f64, y as f64 / (vertices_y - 1) as f64)))
+		.collect::<Vec<_>>();
+	let vertices = sources
+		.iter()
+		.map(|source| image_frame.transform.transform_point2(evaluate_mesh(control_points, columns, rows, source.x, source.y)))
+		.collect::<Vec<_>>();
+
+	let Some(target) = WarpTarget::new(image_frame.transform, width, height, vertices.iter().copied()) else {
+		return image_frame;
+	};
+
+	// Rasterize both triangles of every quad, writing the interpolated source position into each covered output pixel
+	let layer_to_pixel = target.pixel_to_layer.inverse();
+	let pixel_vertices = vertices.iter().map(|&vertex| layer_to_pixel.transform_point2(vertex)).collect::<Vec<_>>();
+	let mut source_positions = vec![None; (target.width * target.height) as usize];
+	for y in 0..vertices_y - 1 {
+		for x in 0..vertices_x - 1 {
+			let [a, b, c, d] = [x + y * vertices_x, x + 1 + y * vertices_x, x + 1 + (y + 1) * vertices_x, x + (y + 1) * vertices_x];
+			for triangle in [[a, b, c], [a, c, d]] {
+				rasterize_triangle(triangle.map(|index| (pixel_vertices[index], sources[index])), target.width, target.height, &mut source_positions);
+			}
+		}
+	}
+
+	target.render(&image_frame.image, |x, y, _| source_positions[(x + y * target.width) as usize])
+}
+
+/// Writes the barycentric interpolation of the triangle's source positions into every pixel whose center lies inside the triangle.
+fn rasterize_triangle(triangle: [(DVec2, DVec2); 3], width: u32, height: u32, output: &mut [Option<DVec2>]) {
+	let [(p0, s0), (p1, s1), (p2, s2)] = triangle;
+	let area = (p1 - p0).perp_dot(p2 - p0);
+	if area.abs() <= f64::EPSILON {
+		return;
+	}
+
+	let min = p0.min(p1).min(p2).floor().max(DVec2::ZERO);
+	let max = p0.max(p1).max(p2).ceil().min(DVec2::new(width as f64, height as f64));
+	// Tolerate rounding errors so pixels on the shared edge of two triangles aren't skipped
+	const EDGE_TOLERANCE: f64 = -1e-9;
+
+	for y in min.y as u32..max.y as u32 {
+		for x in min.x as u32..max.x as u32 {
+			let point = DVec2::new(x as f64 + 0.5, y as f64 + 0.5);
+			let w0 = (p1 - point).perp_dot(p2 - point) / area;
+			let w1 = (p2 - point).perp_dot(p0 - point) / area;
+			let w2 = 1. - w0 - w1;
+			if w0 >= EDGE_TOLERANCE && w1 >= EDGE_TOLERANCE && w2 >= EDGE_TOLERANCE {
+				output[(x + y * width) as usize] = Some(s0 * w0 + s1 * w1 + s2 * w2);
+			}
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct MeshWarpNode<Columns, Rows, ControlPoints> {
+	columns: Columns,
+	rows: Rows,
+	control_points: ControlPoints,
+}
+
+#[node_macro::node_fn(MeshWarpNode)]
+fn mesh_warp_node(image_frame: ImageFrame<Color>, columns: u32, rows: u32, control_points: Vec<DVec2>) -> ImageFrame<Color> {
+	mesh_warp(image_frame, columns, rows, &control_points)
+}
+
+/// Checks if the point lies inside the polygon using the even-odd rule.
+fn polygon_contains(polygon: &[DVec2], point: DVec2) -> bool {
+	let mut inside = false;
+	for (index, &current) in polygon.iter().enumerate() {
+		let previous = polygon[(index + polygon.len() - 1) % polygon.len()];
+		if (current.y > point.y) != (previous.y > point.y) && point.x < (previous.x - current.x) * (point.y - current.y) / (previous.y - current.y) + current.x {
+			inside = !inside;
+		}
+	}
+	inside
+}
+
+/// Computes the mean value coordinates of the point with respect to the polygon, which reproduce the point as a weighted sum of the polygon's vertices.
+pub fn mean_value_coordinates(polygon: &[DVec2], point: DVec2) -> Vec<f64> {
+	let count = polygon.len();
+	let offsets = polygon.iter().map(|&vertex| vertex - point).collect::<Vec<_>>();
+	let distances = offsets.iter().map(|offset| offset.length()).collect::<Vec<_>>();
+	let mut weights = vec![0.; count];
+
+	// Points on a vertex or an edge are interpolated linearly
+	for index in 0..count {
+		let next = (index + 1) % count;
+		if distances[index] <= f64::EPSILON {
+			weights[index] = 1.;
+			return weights;
+		}
+		let (area, dot) = (offsets[index].perp_dot(offsets[next]), offsets[index].dot(offsets[next]));
+		if area.abs() <= f64::EPSILON && dot < 0. {
+			let total = distances[index] + distances[next];
+			weights[index] = distances[next] / total;
+			weights[next] = distances[index] / total;
+			return weights;
+		}
+	}
+
+	// Tangent of half the angle each edge spans as seen from the point
+	let half_angle_tangents = (0..count)
+		.map(|index| {
+			let next = (index + 1) % count;
+			let area = offsets[index].perp_dot(offsets[next]);
+			(distances[index] * distances[next] - offsets[index].dot(offsets[next])) / area
+		})
+		.collect::<Vec<_>>();
+	for index in 0..count {
+		let previous = (index + count - 1) % count;
+		weights[index] = (half_angle_tangents[previous] + half_angle_tangents[index]) / distances[index];
+	}
+
+	let total: f64 = weights.iter().sum();
+	weights.iter_mut().for_each(|weight| *weight /= total);
+	weights
+}
+
+/// Deforms the image by moving the vertices of a cage polygon, given in normalized image coordinates.
+///
+/// The inside of the deformed cage is mapped back into the original cage with mean value coordinates, pixels outside of it keep their original position.
+pub fn cage_warp(image_frame: ImageFrame<Color>, cage: &[DVec2], deformed_cage: &[DVec2]) -> ImageFrame<Color> {
+	let (width, height) = (image_frame.image.width, image_frame.image.height);
+	if width == 0 || height == 0 || cage.len() < 3 || cage.len() != deformed_cage.len() {
+		return image_frame;
+	}
+
+	let transform = image_frame.transform;
+	let corners = [DVec2::ZERO, DVec2::X, DVec2::ONE, DVec2::Y];
+	let bounds = corners.into_iter().chain(deformed_cage.iter().copied()).map(|point| transform.transform_point2(point));
+	let Some(target) = WarpTarget::new(transform, width, height, bounds) else { return image_frame };
+
+	let layer_to_normalized = transform.inverse();
+	target.render(&image_frame.image, |_, _, layer_position| {
+		let position = layer_to_normalized.transform_point2(layer_position);
+		if !polygon_contains(deformed_cage, position) {
+			return Some(position);
+		}
+		let weights = mean_value_coordinates(deformed_cage, position);
+		Some(cage.iter().zip(weights).map(|(&vertex, weight)| vertex * weight).sum())
+	})
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CageWarpNode<Cage, DeformedCage> {
+	cage: Cage,
+	deformed_cage: DeformedCage,
+}
+
+#[node_macro::node_fn(CageWarpNode)]
+fn cage_warp_node(image_frame: ImageFrame<Color>, cage: Vec<DVec2>, deformed_cage: Vec<DVec2>) -> ImageFrame<Color> {
+	cage_warp(image_frame, &cage, &deformed_cage)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn gradient_frame(transform: DAffine2) -> ImageFrame<Color> {
+		let data = (0..64).map(|index| Color::from_rgbaf32_unchecked((index % 8) as f32 / 7., (index / 8) as f32 / 7., 0., 1.)).collect();
+		ImageFrame {
+			image: Image { width: 8, height: 8, data },
+			transform,
+		}
+	}
+
+	fn assert_same_image(a: &ImageFrame<Color>, b: &ImageFrame<Color>) {
+		assert_eq!((a.image.width, a.image.height), (b.image.width, b.image.height));
+		assert!(a.transform.abs_diff_eq(b.transform, 1e-6), "{:?} != {:?}", a.transform, b.transform);
+		for (&a, &b) in a.image.data.iter().zip(&b.image.data) {
+			assert_similar(a, b);
+		}
+	}
+
+	fn assert_similar(a: Color, b: Color) {
+		assert!((a.r() - b.r()).abs() < 1e-4 && (a.g() - b.g()).abs() < 1e-4 && (a.a() - b.a()).abs() < 1e-4, "{a:?} != {b:?}");
+	}
+
+	#[test]
+	fn square_to_quad_maps_corners() {
+		let corners = [DVec2::new(10., 5.), DVec2::new(40., 0.), DVec2::new(35., 30.), DVec2::new(0., 20.)];
+		let matrix = square_to_quad(corners);
+		for (unit, corner) in [DVec2::ZERO, DVec2::X, DVec2::ONE, DVec2::Y].into_iter().zip(corners) {
+			assert!(project_point(matrix, unit).unwrap().abs_diff_eq(corner, 1e-9));
+			assert!(project_point(matrix.inverse(), corner).unwrap().abs_diff_eq(unit, 1e-9));
+		}
+	}
+
+	#[test]
+	fn perspective_warp_onto_own_bounds_is_identity() {
+		let transform = DAffine2::from_scale_angle_translation(DVec2::splat(16.), 0., DVec2::new(4., 2.));
+		let image_frame = gradient_frame(transform);
+		let corners = [DVec2::ZERO, DVec2::X, DVec2::ONE, DVec2::Y].map(|corner| transform.transform_point2(corner));
+		assert_same_image(&perspective_warp(image_frame.clone(), corners), &image_frame);
+	}
+
+	#[test]
+	fn identity_mesh_warp_is_identity() {
+		let image_frame = gradient_frame(DAffine2::from_scale(DVec2::splat(8.)));
+		let warped = mesh_warp(image_frame.clone(), 2, 3, &identity_mesh(2, 3));
+		assert_same_image(&warped, &image_frame);
+	}
+
+	#[test]
+	fn mean_value_coordinates_reproduce_point() {
+		let polygon = [DVec2::new(0., 0.), DVec2::new(4., 0.), DVec2::new(5., 3.), DVec2::new(2., 5.), DVec2::new(-1., 2.)];
+		for point in [DVec2::new(1., 1.), DVec2::new(2.5, 2.5), DVec2::new(2., 0.), DVec2::new(4., 0.)] {
+			let weights = mean_value_coordinates(&polygon, point);
+			let reproduced: DVec2 = polygon.iter().zip(&weights).map(|(&vertex, &weight)| vertex * weight).sum();
+			assert!(reproduced.abs_diff_eq(point, 1e-9), "{reproduced} != {point}");
+			assert!((weights.iter().sum::<f64>() - 1.).abs() < 1e-9);
+		}
+	}
+
+	#[test]
+	fn cage_warp_moves_pixels_with_cage() {
+		let image_frame = gradient_frame(DAffine2::from_scale(DVec2::splat(8.)));
+		let cage = [DVec2::ZERO, DVec2::X, DVec2::ONE, DVec2::Y];
+		assert_same_image(&cage_warp(image_frame.clone(), &cage, &cage), &image_frame);
+
+		// Moving the whole cage to the right moves the image with it
+		let moved = cage.map(|vertex| vertex + DVec2::new(0.5, 0.));
+		let warped = cage_warp(image_frame.clone(), &cage, &moved);
+		assert_eq!((warped.image.width, warped.image.height), (12, 8));
+		assert_similar(warped.image.data[4], image_frame.image.data[0]);
+		assert_similar(warped.image.data[0], image_frame.image.data[0]);
+	}
+}
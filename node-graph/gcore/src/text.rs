@@ -1,21 +1,179 @@
+#![allow(clippy::too_many_arguments)]
+
 mod font_cache;
+mod layout;
+mod rich_text;
+mod text_on_path;
 mod to_path;
 
 use crate::application_io::EditorApi;
 pub use font_cache::*;
+pub use layout::*;
 use node_macro::node_fn;
+pub use rich_text::*;
+pub use text_on_path::*;
 pub use to_path::*;
 
 use crate::Node;
 
-pub struct TextGenerator<Text, FontName, Size> {
+/// Lays out the text in a box, where a box width or height of zero leaves the box unconstrained along that dimension.
+///
+/// The font features are OpenType feature tags separated by commas, such as `smcp, tnum, ss01, -liga`, where a leading `-` turns off a feature on by default.
+/// The weight, width, slant and optical size set those axes of a variable font, where an unset axis is left at its default, and the variations list any other axes as tags with values such as `GRAD=50, XTRA=400`.
+pub struct TextGenerator<Text, FontName, Size, LineHeight, LetterSpacing, ParagraphSpacing, Align, BoxWidth, BoxHeight, Overflow, Features, Weight, Width, Slant, OpticalSize, Variations> {
 	text: Text,
 	font_name: FontName,
 	font_size: Size,
+	line_height_ratio: LineHeight,
+	letter_spacing: LetterSpacing,
+	paragraph_spacing: ParagraphSpacing,
+	align: Align,
+	max_width: BoxWidth,
+	max_height: BoxHeight,
+	overflow: Overflow,
+	font_features: Features,
+	weight: Weight,
+	width: Width,
+	slant: Slant,
+	optical_size: OpticalSize,
+	variations: Variations,
 }
 
 #[node_fn(TextGenerator)]
-fn generate_text<'a: 'input, T>(editor: EditorApi<'a, T>, text: String, font_name: Font, font_size: f64) -> crate::vector::VectorData {
-	let buzz_face = editor.font_cache.get(&font_name).map(|data| load_face(data));
-	crate::vector::VectorData::from_subpaths(to_path(&text, buzz_face, font_size, None))
+fn generate_text<'a: 'input, T>(
+	editor: EditorApi<'a, T>,
+	text: String,
+	font_name: Font,
+	font_size: f64,
+	line_height_ratio: f64,
+	letter_spacing: f64,
+	paragraph_spacing: f64,
+	align: TextAlign,
+	max_width: f64,
+	max_height: f64,
+	overflow: TextOverflow,
+	font_features: String,
+	weight: Option<f64>,
+	width: Option<f64>,
+	slant: Option<f64>,
+	optical_size: Option<f64>,
+	variations: String,
+) -> crate::vector::VectorData {
+	let typesetting = TypesettingConfig {
+		font_size,
+		line_height_ratio,
+		letter_spacing,
+		paragraph_spacing,
+		max_width: (max_width > 0.).then_some(max_width),
+		max_height: (max_height > 0.).then_some(max_height),
+		align,
+		overflow,
+		font_features,
+		font_variations: FontVariation::list([weight, width, slant, optical_size], &variations),
+	};
+	let buzz_faces = load_faces(editor.font_cache, &font_name, &typesetting.font_variations);
+	crate::vector::VectorData::from_subpaths(to_path(&text, &buzz_faces, typesetting))
+}
+
+/// Lays out text like the [`TextGenerator`], where each run has its own font, size and fill.
+pub struct RichTextGenerator<Text, LineHeight, LetterSpacing, ParagraphSpacing, Align, BoxWidth, BoxHeight, Overflow, Features, Weight, Width, Slant, OpticalSize, Variations> {
+	text: Text,
+	line_height_ratio: LineHeight,
+	letter_spacing: LetterSpacing,
+	paragraph_spacing: ParagraphSpacing,
+	align: Align,
+	max_width: BoxWidth,
+	max_height: BoxHeight,
+	overflow: Overflow,
+	font_features: Features,
+	weight: Weight,
+	width: Width,
+	slant: Slant,
+	optical_size: OpticalSize,
+	variations: Variations,
+}
+
+#[node_fn(RichTextGenerator)]
+fn generate_rich_text<'a: 'input, T>(
+	editor: EditorApi<'a, T>,
+	text: RichText,
+	line_height_ratio: f64,
+	letter_spacing: f64,
+	paragraph_spacing: f64,
+	align: TextAlign,
+	max_width: f64,
+	max_height: f64,
+	overflow: TextOverflow,
+	font_features: String,
+	weight: Option<f64>,
+	width: Option<f64>,
+	slant: Option<f64>,
+	optical_size: Option<f64>,
+	variations: String,
+) -> crate::vector::VectorData {
+	let typesetting = TypesettingConfig {
+		font_size: text.first_style().map_or(TypesettingConfig::default().font_size, |style| style.font_size),
+		line_height_ratio,
+		letter_spacing,
+		paragraph_spacing,
+		max_width: (max_width > 0.).then_some(max_width),
+		max_height: (max_height > 0.).then_some(max_height),
+		align,
+		overflow,
+		font_features,
+		font_variations: FontVariation::list([weight, width, slant, optical_size], &variations),
+	};
+	rich_text_to_path(&text, editor.font_cache, typesetting)
+}
+
+/// Sets a line of text along the first subpath of the vector data, with each glyph rotated to follow the path.
+///
+/// The weight, width, slant, optical size and variations set the axes of a variable font like those of the [`TextGenerator`].
+pub struct TextOnPathNode<Text, FontName, Size, LetterSpacing, Path, StartOffset, Align, Side, Weight, Width, Slant, OpticalSize, Variations> {
+	text: Text,
+	font_name: FontName,
+	font_size: Size,
+	letter_spacing: LetterSpacing,
+	path: Path,
+	start_offset: StartOffset,
+	align: Align,
+	side: Side,
+	weight: Weight,
+	width: Width,
+	slant: Slant,
+	optical_size: OpticalSize,
+	variations: Variations,
+}
+
+#[node_fn(TextOnPathNode)]
+fn generate_text_on_path<'a: 'input, T>(
+	editor: EditorApi<'a, T>,
+	text: String,
+	font_name: Font,
+	font_size: f64,
+	letter_spacing: f64,
+	path: crate::vector::VectorData,
+	start_offset: f64,
+	align: TextAlign,
+	side: TextPathSide,
+	weight: Option<f64>,
+	width: Option<f64>,
+	slant: Option<f64>,
+	optical_size: Option<f64>,
+	variations: String,
+) -> crate::vector::VectorData {
+	let Some(mut subpath) = path.subpaths.first().cloned() else {
+		return crate::vector::VectorData::empty();
+	};
+	subpath.apply_transform(path.transform);
+
+	let typesetting = TypesettingConfig {
+		font_size,
+		letter_spacing,
+		align,
+		font_variations: FontVariation::list([weight, width, slant, optical_size], &variations),
+		..Default::default()
+	};
+	let buzz_faces = load_faces(editor.font_cache, &font_name, &typesetting.font_variations);
+	crate::vector::VectorData::from_subpaths(text_on_path(&text, &buzz_faces, typesetting, &subpath, start_offset, side))
 }
@@ -1,6 +1,7 @@
 use dyn_any::{DynAny, StaticType};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use unicode_script::{Script, UnicodeScript};
 
 /// A font type (storing font family and font style and an optional preview URL)
 #[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq, DynAny, specta::Type)]
@@ -16,6 +17,58 @@ impl Font {
 	}
 }
 
+/// A font which glyphs missing from other fonts are taken from, which is only loaded once a text needs it.
+#[derive(Debug, Clone, Serialize, Deserialize, Hash, PartialEq, Eq)]
+pub struct FallbackFont {
+	pub font: Font,
+	/// The ISO 15924 codes of the scripts the font is loaded for, such as `Arab` or `Zsye` for emoji, or none for the font to be loaded for any script which no other fallback font is for.
+	pub scripts: Vec<String>,
+}
+
+/// A position along one of the design axes of a variable font.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontVariation {
+	/// The four letter tag of the axis, such as `wght` for the weight or `wdth` for the width.
+	pub tag: [u8; 4],
+	pub value: f64,
+}
+
+impl FontVariation {
+	/// The axes which have inputs of their own on the text nodes: weight, width, slant and optical size.
+	pub const STANDARD_AXES: [[u8; 4]; 4] = [*b"wght", *b"wdth", *b"slnt", *b"opsz"];
+
+	/// The variations of the standard axes, where `None` leaves an axis at the font's default, followed by the others listed in `custom` as tags with values such as `GRAD=50, XTRA=400`.
+	pub fn list(standard_values: [Option<f64>; 4], custom: &str) -> Vec<Self> {
+		let standard = Self::STANDARD_AXES.into_iter().zip(standard_values);
+		let standard = standard.filter_map(|(tag, value)| value.map(|value| Self { tag, value }));
+		standard.chain(custom.split(',').filter_map(Self::parse)).collect()
+	}
+
+	/// Splits the variations into the values of the standard axes and the listing of the others, undoing [`FontVariation::list`].
+	pub fn split(variations: &[Self]) -> ([Option<f64>; 4], String) {
+		let standard_values = Self::STANDARD_AXES.map(|tag| variations.iter().rev().find(|variation| variation.tag == tag).map(|variation| variation.value));
+		let custom = variations.iter().filter(|variation| !Self::STANDARD_AXES.contains(&variation.tag));
+		let custom = custom.map(|variation| format!("{}={}", String::from_utf8_lossy(&variation.tag), variation.value)).collect::<Vec<_>>();
+		(standard_values, custom.join(", "))
+	}
+
+	fn parse(variation: &str) -> Option<Self> {
+		let (tag, value) = variation.split_once('=')?;
+		let tag = tag.trim().as_bytes().try_into().ok()?;
+		let value = value.trim().parse().ok()?;
+		Some(Self { tag, value })
+	}
+}
+
+/// A design axis of a variable font, with the range of values it supports.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FontAxis {
+	pub tag: [u8; 4],
+	pub min: f64,
+	pub default: f64,
+	pub max: f64,
+}
+
 /// A cache of all loaded font data and preview urls along with the default font (send from `init_app` in `editor_api.rs`)
 #[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq)]
 pub struct FontCache {
@@ -25,6 +78,9 @@ pub struct FontCache {
 	preview_urls: HashMap<Font, String>,
 	/// The default font (used as a fallback)
 	default_font: Option<Font>,
+	/// The fonts which glyphs missing from a font are taken from, in order of preference
+	#[serde(default)]
+	fallback_fonts: Vec<FallbackFont>,
 }
 impl FontCache {
 	/// Returns the font family name if the font is cached, otherwise returns the default font family name if that is cached
@@ -41,6 +97,67 @@ impl FontCache {
 		self.resolve_font(font).and_then(|font| self.font_file_data.get(font))
 	}
 
+	/// Gets the bytes for a font followed by those of the default font and each loaded fallback font, or nothing if neither the font nor the default font is loaded
+	pub fn get_with_fallbacks<'a>(&'a self, font: &Font) -> Vec<&'a Vec<u8>> {
+		let Some(font) = self.resolve_font(font) else { return Vec::new() };
+
+		let mut fonts = vec![font];
+		for fallback in self.default_font.iter().chain(self.fallback_fonts.iter().map(|fallback| &fallback.font)) {
+			if !fonts.contains(&fallback) {
+				fonts.push(fallback);
+			}
+		}
+		fonts.into_iter().filter_map(|font| self.font_file_data.get(font)).collect()
+	}
+
+	/// The design axes of the font if it is a loaded variable font
+	pub fn variation_axes(&self, font: &Font) -> Vec<FontAxis> {
+		let Some(face) = self.get(font).and_then(|data| rustybuzz::Face::from_slice(data, 0)) else {
+			return Vec::new();
+		};
+		let axes = face.variation_axes().into_iter();
+		axes.map(|axis| FontAxis {
+			tag: axis.tag.to_bytes(),
+			min: axis.min_value as f64,
+			default: axis.def_value as f64,
+			max: axis.max_value as f64,
+		})
+		.collect()
+	}
+
+	/// Sets the fonts which glyphs missing from a font are taken from, in order of preference
+	pub fn set_fallback_fonts(&mut self, fallback_fonts: Vec<FallbackFont>) {
+		self.fallback_fonts = fallback_fonts;
+	}
+
+	/// The fallback fonts which need to be loaded for the characters of the text which neither the font nor any of the loaded fallback fonts have glyphs for.
+	///
+	/// Nothing is needed until the font or the default font has loaded, since there's no telling which glyphs are missing before then.
+	pub fn missing_fallback_fonts(&self, font: &Font, text: &str) -> Vec<Font> {
+		let faces = self.get_with_fallbacks(font).into_iter().filter_map(|data| rustybuzz::Face::from_slice(data, 0)).collect::<Vec<_>>();
+		if faces.is_empty() {
+			return Vec::new();
+		}
+
+		let mut missing = Vec::new();
+		// Combining marks and joiners are drawn with the face of the character they modify, so they don't need a glyph of their own
+		let characters = text
+			.chars()
+			.filter(|character| !character.is_whitespace() && !character.is_control() && character.script() != Script::Inherited);
+		for character in characters {
+			if faces.iter().any(|face| face.glyph_index(character).is_some()) {
+				continue;
+			}
+			let script = fallback_script(character);
+			let for_script = self.fallback_fonts.iter().find(|fallback| fallback.scripts.iter().any(|code| code == script));
+			let fallback = for_script.or_else(|| self.fallback_fonts.iter().find(|fallback| fallback.scripts.is_empty()));
+			if let Some(fallback) = fallback.filter(|fallback| !self.loaded_font(&fallback.font) && !missing.contains(&fallback.font)) {
+				missing.push(fallback.font.clone());
+			}
+		}
+		missing
+	}
+
 	/// Check if the font is already loaded
 	pub fn loaded_font(&self, font: &Font) -> bool {
 		self.font_file_data.contains_key(font)
@@ -75,5 +192,29 @@ impl core::hash::Hash for FontCache {
 		});
 		self.font_file_data.len().hash(state);
 		self.font_file_data.keys().for_each(|font| font.hash(state));
+		self.fallback_fonts.hash(state);
+	}
+}
+
+/// The ISO 15924 code of the script of a character, which picks the fallback font it's loaded from.
+fn fallback_script(character: char) -> &'static str {
+	// Emoji are mostly in the common script shared with punctuation, so they're told apart by the blocks which hold them
+	match character as u32 {
+		0x2600..=0x27BF | 0x1F000..=0x1FAFF => "Zsye",
+		_ => character.script().short_name(),
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn variations_from_node_inputs() {
+		let variations = FontVariation::list([Some(650.), None, Some(0.), None], "GRAD=50, XTRA = 400.5, bad, width=1");
+		let tags = variations.iter().map(|variation| (&variation.tag, variation.value)).collect::<Vec<_>>();
+		assert_eq!(tags, [(b"wght", 650.), (b"slnt", 0.), (b"GRAD", 50.), (b"XTRA", 400.5)]);
+
+		assert_eq!(FontVariation::split(&variations), ([Some(650.), None, Some(0.), None], "GRAD=50, XTRA=400.5".to_string()));
 	}
 }
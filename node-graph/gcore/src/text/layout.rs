@@ -0,0 +1,731 @@
+//! Paragraph layout of shaped text.
+//!
+//! Text is laid out once into positioned glyphs, which both the outlines from [`super::to_path`] and the box from [`super::bounding_box`] are computed from.
+//! Each paragraph is split into items which are shaped separately wherever the direction, script or font face changes, and the words of each line are reordered for right-to-left text once the lines are broken.
+
+use super::FontVariation;
+
+use dyn_any::{DynAny, StaticType};
+use glam::DVec2;
+use rustybuzz::{Direction, Feature, UnicodeBuffer};
+use unicode_bidi::BidiInfo;
+use unicode_script::{Script, UnicodeScript};
+
+/// How the lines of a paragraph are positioned horizontally within the text box.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, DynAny)]
+pub enum TextAlign {
+	#[default]
+	Left,
+	Center,
+	Right,
+	/// Stretches the spaces between words so every line but the last of each paragraph fills the width of the text box.
+	Justify,
+}
+
+impl TextAlign {
+	pub fn list() -> [TextAlign; 4] {
+		[TextAlign::Left, TextAlign::Center, TextAlign::Right, TextAlign::Justify]
+	}
+}
+
+impl core::fmt::Display for TextAlign {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			TextAlign::Left => write!(f, "Left"),
+			TextAlign::Center => write!(f, "Center"),
+			TextAlign::Right => write!(f, "Right"),
+			TextAlign::Justify => write!(f, "Justify"),
+		}
+	}
+}
+
+/// What happens to the lines which don't fit within the height of the text box.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, DynAny)]
+pub enum TextOverflow {
+	/// The lines continue below the box.
+	#[default]
+	Visible,
+	/// The lines which extend below the box are left out.
+	Clip,
+}
+
+impl TextOverflow {
+	pub fn list() -> [TextOverflow; 2] {
+		[TextOverflow::Visible, TextOverflow::Clip]
+	}
+}
+
+impl core::fmt::Display for TextOverflow {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			TextOverflow::Visible => write!(f, "Visible"),
+			TextOverflow::Clip => write!(f, "Clip"),
+		}
+	}
+}
+
+/// The parameters text is laid out with.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypesettingConfig {
+	pub font_size: f64,
+	/// The distance between the tops of consecutive lines, as a multiple of the font size.
+	pub line_height_ratio: f64,
+	/// Extra space added between glyphs, in pixels.
+	pub letter_spacing: f64,
+	/// Extra space added between paragraphs (the lines separated by line breaks in the text), in pixels.
+	pub paragraph_spacing: f64,
+	/// The width of the text box which lines are wrapped to fit, or `None` for lines which only end at line breaks.
+	pub max_width: Option<f64>,
+	/// The height of the text box, or `None` for a box which grows to fit the lines.
+	pub max_height: Option<f64>,
+	pub align: TextAlign,
+	pub overflow: TextOverflow,
+	/// The OpenType features the text is shaped with besides those on by default, such as `smcp, tnum, ss01, -liga`.
+	pub font_features: String,
+	/// The positions along the axes of a variable font which its faces are loaded with.
+	pub font_variations: Vec<FontVariation>,
+}
+
+impl Default for TypesettingConfig {
+	fn default() -> Self {
+		Self {
+			font_size: 24.,
+			line_height_ratio: 1.2,
+			letter_spacing: 0.,
+			paragraph_spacing: 0.,
+			max_width: None,
+			max_height: None,
+			align: TextAlign::default(),
+			overflow: TextOverflow::default(),
+			font_features: String::new(),
+			font_variations: Vec::new(),
+		}
+	}
+}
+
+/// A glyph placed by the layout.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PositionedGlyph {
+	pub glyph_id: u16,
+	/// The index of the [`ShapingRun`] the glyph was shaped from.
+	pub run: usize,
+	/// The index of the face within the faces of its run which the glyph is from.
+	pub face: usize,
+	/// The pen position of the glyph at the top of its em box, in pixels from the top left of the text box.
+	pub position: DVec2,
+	/// The distance the pen moves past the glyph, without letter spacing.
+	pub advance: f64,
+}
+
+/// The result of laying out text, in pixels.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct TextLayout {
+	pub glyphs: Vec<PositionedGlyph>,
+	/// The size of the text box, where a dimension the box doesn't constrain fits the laid out lines.
+	pub size: DVec2,
+	/// Whether there are lines which extend below the text box.
+	pub overflowed: bool,
+}
+
+/// A span of the text which is shaped with a single font and size.
+pub struct ShapingRun<'a> {
+	pub text: &'a str,
+	/// The face of the run's font followed by those of its fallback fonts, which the glyphs missing from the first face are taken from.
+	pub buzz_faces: &'a [rustybuzz::Face<'a>],
+	pub font_size: f64,
+	/// The distance the glyphs are raised above the baseline, in pixels.
+	pub baseline_shift: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ShapedGlyph {
+	glyph_id: u16,
+	run: usize,
+	face: usize,
+	/// The advance including letter spacing.
+	advance: f64,
+	/// The offset from the pen position.
+	offset: DVec2,
+	/// The distance from the top of the em box to the baseline of the run's font.
+	ascent: f64,
+}
+
+/// A run of glyphs which is kept together on a line, shaped from a word of the text or the part of one that was too long for a line or changed direction.
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Word {
+	/// The glyphs in the order they are drawn from left to right.
+	glyphs: Vec<ShapedGlyph>,
+	/// The advance of the space which separates the word from the previous one, or `None` if it continues the previous word.
+	space: Option<f64>,
+	/// The largest font size of the runs in the word, which sets the height of its line.
+	font_size: f64,
+	/// The largest ascent of the runs in the word, which sets the baseline of its line.
+	ascent: f64,
+	/// The bidi embedding level of the word, where odd levels are right-to-left.
+	level: u8,
+}
+
+impl Word {
+	/// The width of the glyphs, without the letter spacing after the last one.
+	fn width(&self, letter_spacing: f64) -> f64 {
+		match self.glyphs.is_empty() {
+			true => 0.,
+			false => self.glyphs.iter().map(|glyph| glyph.advance).sum::<f64>() - letter_spacing,
+		}
+	}
+}
+
+struct Line {
+	words: Vec<Word>,
+	top: f64,
+	height: f64,
+	ascent: f64,
+	ends_paragraph: bool,
+}
+
+impl Line {
+	fn new(words: Vec<Word>, top: f64, ends_paragraph: bool, config: &TypesettingConfig) -> Self {
+		let font_size = words.iter().map(|word| word.font_size).reduce(f64::max).unwrap_or(config.font_size);
+		let ascent = words.iter().map(|word| word.ascent).fold(0., f64::max);
+		Self {
+			words,
+			top,
+			height: font_size * config.line_height_ratio,
+			ascent,
+			ends_paragraph,
+		}
+	}
+}
+
+/// A span of a word which is shaped as one, since its characters share a run, direction, script and face.
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct Item {
+	start: usize,
+	end: usize,
+	run: usize,
+	level: u8,
+	face: usize,
+}
+
+/// Shapes items of text, reusing the buffer between them.
+struct Shaper<'a> {
+	buffer: UnicodeBuffer,
+	features: &'a [Feature],
+	letter_spacing: f64,
+}
+
+impl Shaper<'_> {
+	fn shape(&mut self, text: &str, runs: &[ShapingRun], run: usize, face: usize, right_to_left: bool) -> Vec<ShapedGlyph> {
+		let shaping_run = &runs[run];
+		let buzz_face = &shaping_run.buzz_faces[face];
+		let scale = font_scale(buzz_face, shaping_run.font_size);
+		// Glyphs from fallback faces sit on the baseline of the run's own font
+		let ascent = font_ascent(&shaping_run.buzz_faces[0], shaping_run.font_size);
+
+		self.buffer.push_str(text);
+		self.buffer.set_direction(if right_to_left { Direction::RightToLeft } else { Direction::LeftToRight });
+		self.buffer.guess_segment_properties();
+		let glyph_buffer = rustybuzz::shape(buzz_face, self.features, core::mem::replace(&mut self.buffer, UnicodeBuffer::new()));
+		let glyphs = glyph_buffer
+			.glyph_positions()
+			.iter()
+			.zip(glyph_buffer.glyph_infos())
+			.map(|(position, info)| ShapedGlyph {
+				glyph_id: info.glyph_id as u16,
+				run,
+				face,
+				advance: position.x_advance as f64 * scale + self.letter_spacing,
+				offset: DVec2::new(position.x_offset as f64, position.y_offset as f64) * scale - DVec2::new(0., shaping_run.baseline_shift),
+				ascent,
+			})
+			.collect();
+		self.buffer = glyph_buffer.clear();
+		glyphs
+	}
+}
+
+/// The factor from font units into pixels.
+pub fn font_scale(buzz_face: &rustybuzz::Face, font_size: f64) -> f64 {
+	(buzz_face.units_per_em() as f64).recip() * font_size
+}
+
+/// The distance from the top of the em box to the baseline, in pixels.
+pub fn font_ascent(buzz_face: &rustybuzz::Face, font_size: f64) -> f64 {
+	buzz_face.ascender() as f64 / buzz_face.height() as f64 * font_size
+}
+
+/// Parses OpenType features separated by commas or spaces, such as `smcp, tnum, ss01, -liga`, leaving out those which aren't valid.
+pub fn parse_font_features(features: &str) -> Vec<Feature> {
+	features.split([',', ' ']).filter(|feature| !feature.is_empty()).filter_map(|feature| feature.parse().ok()).collect()
+}
+
+/// Shapes and lays out the text, where each line break starts a new paragraph.
+///
+/// The first face is the one of the text's font, and the glyphs it is missing are taken from the others in order.
+pub fn layout_text(text: &str, buzz_faces: &[rustybuzz::Face], config: &TypesettingConfig) -> TextLayout {
+	let run = ShapingRun {
+		text,
+		buzz_faces,
+		font_size: config.font_size,
+		baseline_shift: 0.,
+	};
+	layout_runs(&[run], config)
+}
+
+/// Shapes each run with its own faces and size and lays out their text as one, where words may continue across runs.
+pub fn layout_runs(runs: &[ShapingRun], config: &TypesettingConfig) -> TextLayout {
+	if runs.is_empty() {
+		return layout_words(vec![Vec::new()], config);
+	}
+
+	let features = parse_font_features(&config.font_features);
+	let mut shaper = Shaper {
+		buffer: UnicodeBuffer::new(),
+		features: &features,
+		letter_spacing: config.letter_spacing,
+	};
+	let space_advances = (0..runs.len())
+		.map(|run| shaper.shape(" ", runs, run, 0, false).iter().map(|glyph| glyph.advance - config.letter_spacing).sum::<f64>())
+		.collect::<Vec<_>>();
+
+	// Paragraphs and words may continue across runs, so the text is split as a whole
+	let text = runs.iter().map(|run| run.text).collect::<String>();
+	let run_ends = runs
+		.iter()
+		.scan(0, |end, run| {
+			*end += run.text.len();
+			Some(*end)
+		})
+		.collect::<Vec<_>>();
+	let run_at = |offset: usize| run_ends.iter().position(|&end| offset < end).unwrap_or(runs.len() - 1);
+
+	let mut paragraphs = Vec::new();
+	let mut paragraph_start = 0;
+	for paragraph in text.split('\n') {
+		let bidi_info = BidiInfo::new(paragraph, None);
+		let paragraph_level = bidi_info.paragraphs.first().map_or(0, |info| info.level.number());
+
+		let mut words: Vec<Word> = Vec::new();
+		let mut word_start = paragraph_start;
+		for (word_index, word) in paragraph.split(' ').enumerate() {
+			let space = if word_index == 0 { 0. } else { space_advances[run_at(word_start - 1)] };
+
+			if word.is_empty() {
+				let run = &runs[run_at(word_start)];
+				words.push(Word {
+					glyphs: Vec::new(),
+					space: Some(space),
+					font_size: run.font_size,
+					ascent: font_ascent(&run.buzz_faces[0], run.font_size),
+					level: paragraph_level,
+				});
+			}
+
+			let properties = |offset: usize, character: char| {
+				let script = character.script();
+				let script = (!matches!(script, Script::Common | Script::Inherited | Script::Unknown)).then_some(script);
+				(run_at(word_start + offset), bidi_info.levels[word_start + offset - paragraph_start].number(), script)
+			};
+			let face_of = |run: usize, character: char, current: Option<usize>| {
+				let buzz_faces = runs[run].buzz_faces;
+				let has_glyph = |&face: &usize| buzz_faces[face].glyph_index(character).is_some();
+				match (current, character.script()) {
+					// Combining marks and joiners stay with the face of the character they modify, and punctuation and digits keep to the face of the text around them
+					(Some(face), Script::Inherited) => face,
+					(Some(face), Script::Common) if has_glyph(&face) => face,
+					_ => (0..buzz_faces.len()).find(has_glyph).unwrap_or(0),
+				}
+			};
+
+			let mut space = Some(space);
+			for item in itemize(word, properties, face_of) {
+				let run = &runs[item.run];
+				let ascent = font_ascent(&run.buzz_faces[0], run.font_size);
+				let glyphs = shaper.shape(&word[item.start..item.end], runs, item.run, item.face, item.level % 2 == 1);
+				match (space.take(), words.last_mut()) {
+					(None, Some(word)) if word.level == item.level => {
+						// Right-to-left items are drawn to the left of the ones before them
+						if item.level % 2 == 1 {
+							let mut glyphs = glyphs;
+							glyphs.append(&mut word.glyphs);
+							word.glyphs = glyphs;
+						} else {
+							word.glyphs.extend(glyphs);
+						}
+						word.font_size = word.font_size.max(run.font_size);
+						word.ascent = word.ascent.max(ascent);
+					}
+					(space, _) => words.push(Word {
+						glyphs,
+						space,
+						font_size: run.font_size,
+						ascent,
+						level: item.level,
+					}),
+				}
+			}
+
+			word_start += word.len() + 1;
+		}
+
+		paragraphs.push(words);
+		paragraph_start += paragraph.len() + 1;
+	}
+
+	layout_words(paragraphs, config)
+}
+
+/// Splits the text into [`Item`]s from the run, bidi level and script of each character at its offset, and the face chosen for it in its run given the face of the item before it.
+///
+/// Characters without a script of their own, such as spaces, digits, punctuation and combining marks, continue the script of their item.
+fn itemize<S: Copy + PartialEq>(text: &str, properties: impl Fn(usize, char) -> (usize, u8, Option<S>), face_of: impl Fn(usize, char, Option<usize>) -> usize) -> Vec<Item> {
+	let mut items: Vec<Item> = Vec::new();
+	let mut item_script = None;
+	for (offset, character) in text.char_indices() {
+		let (run, level, script) = properties(offset, character);
+		let end = offset + character.len_utf8();
+
+		let same_script = script.is_none() || item_script.is_none() || script == item_script;
+		let current_face = items.last().filter(|item| item.run == run && item.level == level && same_script).map(|item| item.face);
+		let face = face_of(run, character, current_face);
+		match items.last_mut() {
+			Some(item) if current_face == Some(face) => {
+				item.end = end;
+				item_script = item_script.or(script);
+			}
+			_ => {
+				items.push(Item { start: offset, end, run, level, face });
+				item_script = script;
+			}
+		}
+	}
+	items
+}
+
+/// The order the words of a line are drawn in from left to right, given their bidi levels, along with the word whose preceding space is drawn before each position.
+///
+/// Following rule L2 of the Unicode bidirectional algorithm, every sequence of words at or above each odd level is reversed, from the highest level down.
+fn visual_order(levels: &[u8]) -> (Vec<usize>, Vec<usize>) {
+	let mut order = (0..levels.len()).collect::<Vec<_>>();
+	let mut gap_order = order.clone();
+
+	let highest = levels.iter().copied().max().unwrap_or(0);
+	let Some(lowest_odd) = levels.iter().copied().filter(|level| level % 2 == 1).min() else {
+		return (order, gap_order);
+	};
+	for level in (lowest_odd..=highest).rev() {
+		let mut start = 0;
+		while start < order.len() {
+			let end = start + order[start..].iter().take_while(|&&index| levels[index] >= level).count();
+			if end > start {
+				// The space before the sequence stays in place while those within it are reversed with the words
+				order[start..end].reverse();
+				gap_order[start + 1..end].reverse();
+			}
+			start = end + 1;
+		}
+	}
+	(order, gap_order)
+}
+
+/// Breaks the shaped words of each paragraph into lines and places them in the text box.
+fn layout_words(paragraphs: Vec<Vec<Word>>, config: &TypesettingConfig) -> TextLayout {
+	let letter_spacing = config.letter_spacing;
+	// The letter spacing after the last glyph of the previous word, then the space with its own letter spacing
+	let gap = |word: &Word| match word.space {
+		Some(space) => space + 2. * letter_spacing,
+		None => letter_spacing,
+	};
+
+	let mut lines = Vec::new();
+	let mut top = 0.;
+	for (index, paragraph) in paragraphs.into_iter().enumerate() {
+		if index > 0 {
+			top += config.paragraph_spacing;
+		}
+
+		let words = match config.max_width {
+			Some(max_width) => paragraph.into_iter().flat_map(|word| split_overlong_word(word, max_width, letter_spacing)).collect(),
+			None => paragraph,
+		};
+
+		// Greedily fill each line with as many words as fit within the box width
+		let mut line: Vec<Word> = Vec::new();
+		let mut width = 0.;
+		for word in words {
+			let word_width = word.width(letter_spacing);
+			let mut advance = if line.is_empty() { word_width } else { gap(&word) + word_width };
+			if config.max_width.is_some_and(|max_width| !line.is_empty() && width + advance > max_width) {
+				let full_line = Line::new(core::mem::take(&mut line), top, false, config);
+				top += full_line.height;
+				lines.push(full_line);
+				width = 0.;
+				advance = word_width;
+			}
+			width += advance;
+			line.push(word);
+		}
+		let last_line = Line::new(line, top, true, config);
+		top += last_line.height;
+		lines.push(last_line);
+	}
+
+	let line_width = |line: &Line| {
+		let gaps = line.words.iter().skip(1).map(gap).sum::<f64>();
+		line.words.iter().map(|word| word.width(letter_spacing)).sum::<f64>() + gaps
+	};
+	let widest = lines.iter().map(line_width).fold(0., f64::max);
+	let box_width = config.max_width.unwrap_or(widest);
+
+	let mut layout = TextLayout {
+		size: DVec2::new(box_width, config.max_height.unwrap_or(top)),
+		..Default::default()
+	};
+	for line in &lines {
+		if config.max_height.is_some_and(|max_height| line.top + line.height > max_height + 1e-6) {
+			layout.overflowed = true;
+			if config.overflow == TextOverflow::Clip {
+				break;
+			}
+		}
+
+		let free_space = (box_width - line_width(line)).max(0.);
+		let stretched_gaps = line.words.iter().skip(1).filter(|word| word.space.is_some()).count();
+		let (order, gap_order) = visual_order(&line.words.iter().map(|word| word.level).collect::<Vec<_>>());
+		let (mut x, extra_gap) = match config.align {
+			TextAlign::Left => (0., 0.),
+			TextAlign::Center => (free_space / 2., 0.),
+			TextAlign::Right => (free_space, 0.),
+			TextAlign::Justify if !line.ends_paragraph && stretched_gaps > 0 => (0., free_space / stretched_gaps as f64),
+			TextAlign::Justify => (0., 0.),
+		};
+
+		for (position, &index) in order.iter().enumerate() {
+			let word = &line.words[index];
+			if position > 0 {
+				let gap_word = &line.words[gap_order[position]];
+				x += gap(gap_word) + if gap_word.space.is_some() { extra_gap } else { 0. };
+			}
+			for glyph in &word.glyphs {
+				// Glyphs of smaller fonts are moved down so they share the baseline of the line
+				let position = DVec2::new(x, line.top + line.ascent - glyph.ascent) + glyph.offset;
+				layout.glyphs.push(PositionedGlyph {
+					glyph_id: glyph.glyph_id,
+					run: glyph.run,
+					face: glyph.face,
+					position,
+					advance: glyph.advance - letter_spacing,
+				});
+				x += glyph.advance;
+			}
+			if !word.glyphs.is_empty() {
+				x -= letter_spacing;
+			}
+		}
+	}
+
+	layout
+}
+
+/// Breaks a word that is wider than the text box into parts which each fit on a line, as long as they have at least one glyph.
+fn split_overlong_word(word: Word, max_width: f64, letter_spacing: f64) -> Vec<Word> {
+	if word.width(letter_spacing) <= max_width {
+		return vec![word];
+	}
+
+	// Right-to-left words are broken from their right end, where they start
+	let right_to_left = word.level % 2 == 1;
+	let mut glyphs = word.glyphs;
+	if right_to_left {
+		glyphs.reverse();
+	}
+
+	let mut parts = Vec::new();
+	let mut part = Word { glyphs: Vec::new(), ..word };
+	let mut width = 0.;
+	for glyph in glyphs {
+		if !part.glyphs.is_empty() && width + glyph.advance - letter_spacing > max_width {
+			let next = Word {
+				glyphs: Vec::new(),
+				space: None,
+				..part
+			};
+			parts.push(core::mem::replace(&mut part, next));
+			width = 0.;
+		}
+		width += glyph.advance;
+		part.glyphs.push(glyph);
+	}
+	parts.push(part);
+	if right_to_left {
+		parts.iter_mut().for_each(|part| part.glyphs.reverse());
+	}
+	parts
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	/// A word of glyphs which are all 10 pixels wide, in a font of size 10.
+	fn word(length: usize) -> Word {
+		let glyph = ShapedGlyph {
+			glyph_id: 1,
+			run: 0,
+			face: 0,
+			advance: 10.,
+			offset: DVec2::ZERO,
+			ascent: 8.,
+		};
+		Word {
+			glyphs: vec![glyph; length],
+			space: Some(5.),
+			font_size: 10.,
+			ascent: 8.,
+			level: 0,
+		}
+	}
+
+	fn config(max_width: Option<f64>, align: TextAlign) -> TypesettingConfig {
+		TypesettingConfig {
+			font_size: 10.,
+			line_height_ratio: 1.5,
+			max_width,
+			align,
+			..Default::default()
+		}
+	}
+
+	fn line_starts(layout: &TextLayout) -> Vec<DVec2> {
+		let mut starts: Vec<DVec2> = Vec::new();
+		for glyph in &layout.glyphs {
+			match starts.last_mut() {
+				Some(start) if start.y == glyph.position.y => start.x = start.x.min(glyph.position.x),
+				_ => starts.push(glyph.position),
+			}
+		}
+		starts
+	}
+
+	#[test]
+	fn wraps_and_aligns_lines() {
+		let paragraphs = vec![vec![word(3), word(3), word(3)], vec![word(2)]];
+
+		let layout = layout_words(paragraphs.clone(), &config(Some(70.), TextAlign::Left));
+		assert_eq!(line_starts(&layout), vec![DVec2::new(0., 0.), DVec2::new(0., 15.), DVec2::new(0., 30.)]);
+		assert_eq!(layout.size, DVec2::new(70., 45.));
+
+		let layout = layout_words(paragraphs.clone(), &config(Some(70.), TextAlign::Right));
+		assert_eq!(line_starts(&layout), vec![DVec2::new(5., 0.), DVec2::new(40., 15.), DVec2::new(50., 30.)]);
+
+		// The gap of the first line is stretched to fill the box, the last line of each paragraph is left aligned
+		let layout = layout_words(paragraphs.clone(), &config(Some(70.), TextAlign::Justify));
+		assert_eq!(layout.glyphs[3].position, DVec2::new(40., 0.));
+		assert_eq!(line_starts(&layout)[1..], [DVec2::new(0., 15.), DVec2::new(0., 30.)]);
+
+		let unbounded = layout_words(paragraphs, &config(None, TextAlign::Center));
+		assert_eq!(unbounded.size, DVec2::new(100., 30.));
+		assert_eq!(line_starts(&unbounded), vec![DVec2::new(0., 0.), DVec2::new(40., 15.)]);
+	}
+
+	#[test]
+	fn breaks_overlong_words_and_clips_overflow() {
+		let mut config = config(Some(25.), TextAlign::Left);
+		config.max_height = Some(30.);
+
+		let layout = layout_words(vec![vec![word(7)]], &config);
+		assert_eq!(line_starts(&layout).len(), 4);
+		assert!(layout.overflowed);
+		assert_eq!(layout.size, DVec2::new(25., 30.));
+
+		config.overflow = TextOverflow::Clip;
+		let clipped = layout_words(vec![vec![word(7)]], &config);
+		assert_eq!(clipped.glyphs.len(), 4);
+	}
+
+	#[test]
+	fn letter_and_paragraph_spacing() {
+		let mut config = config(None, TextAlign::Left);
+		config.letter_spacing = 2.;
+		config.paragraph_spacing = 6.;
+		let paragraphs = vec![vec![word(2), word(1)], vec![word(1)]]
+			.into_iter()
+			.map(|words| {
+				words
+					.into_iter()
+					.map(|word| Word {
+						glyphs: word.glyphs.iter().map(|&glyph| ShapedGlyph { advance: 12., ..glyph }).collect(),
+						..word
+					})
+					.collect()
+			})
+			.collect();
+
+		let layout = layout_words(paragraphs, &config);
+		let positions = layout.glyphs.iter().map(|glyph| glyph.position).collect::<Vec<_>>();
+		assert_eq!(positions, vec![DVec2::new(0., 0.), DVec2::new(12., 0.), DVec2::new(31., 0.), DVec2::new(0., 21.)]);
+		assert_eq!(layout.size, DVec2::new(41., 36.));
+	}
+
+	#[test]
+	fn mixed_sizes_share_a_baseline() {
+		let mut large = word(1);
+		large.font_size = 20.;
+		large.ascent = 16.;
+		large.glyphs[0].ascent = 16.;
+		large.glyphs[0].run = 1;
+		let paragraphs = vec![vec![word(1), large], vec![word(1)]];
+
+		let layout = layout_words(paragraphs, &config(None, TextAlign::Left));
+		let positions = layout.glyphs.iter().map(|glyph| glyph.position).collect::<Vec<_>>();
+		// The first line is as tall as its largest font, and the smaller glyph moves down by the difference in ascent
+		assert_eq!(positions, vec![DVec2::new(0., 8.), DVec2::new(15., 0.), DVec2::new(0., 30.)]);
+		assert_eq!(layout.glyphs[1].run, 1);
+		assert_eq!(layout.size, DVec2::new(25., 45.));
+	}
+
+	#[test]
+	fn items_split_at_direction_script_and_face() {
+		// Latin and Hebrew letters have scripts of their own, and the star is only in the second face
+		fn spans(text: &str) -> Vec<(&str, usize)> {
+			let properties = |_, character: char| match character {
+				'a'..='z' => (0, 0, Some('L')),
+				'α'..='ω' => (0, 0, Some('G')),
+				'א'..='ת' => (0, 1, Some('H')),
+				_ => (0, 0, None),
+			};
+			let face_of = |_, character: char, current: Option<usize>| match character {
+				'★' => 1,
+				'0'..='9' => current.unwrap_or(0),
+				_ => 0,
+			};
+			itemize(text, properties, face_of).iter().map(|item| (&text[item.start..item.end], item.face)).collect()
+		}
+
+		assert_eq!(spans("ab1★2cd"), [("ab1", 0), ("★2", 1), ("cd", 0)]);
+		assert_eq!(spans("abאב"), [("ab", 0), ("אב", 0)]);
+		assert_eq!(spans("aαb"), [("a", 0), ("α", 0), ("b", 0)]);
+	}
+
+	#[test]
+	fn right_to_left_words_are_reordered() {
+		assert_eq!(visual_order(&[0, 0]), (vec![0, 1], vec![0, 1]));
+		assert_eq!(visual_order(&[0, 1, 1, 0]), (vec![0, 2, 1, 3], vec![0, 1, 2, 3]));
+		// Numbers in right-to-left text stay left-to-right within it
+		assert_eq!(visual_order(&[1, 2, 2, 1]), (vec![3, 1, 2, 0], vec![0, 3, 2, 1]));
+
+		let mut first = word(1);
+		first.level = 1;
+		first.glyphs[0].run = 1;
+		let mut second = word(2);
+		second.level = 1;
+		let layout = layout_words(vec![vec![first, second]], &config(None, TextAlign::Left));
+		let glyphs = layout.glyphs.iter().map(|glyph| (glyph.run, glyph.position.x)).collect::<Vec<_>>();
+		// The first word is drawn at the right end of the line
+		assert_eq!(glyphs, vec![(0, 0.), (0, 10.), (1, 25.)]);
+	}
+}
@@ -0,0 +1,210 @@
+//! Attributed text, made of runs which each have their own font, size and fill.
+
+use super::Font;
+use crate::Color;
+
+use core::hash::{Hash, Hasher};
+use dyn_any::{DynAny, StaticType};
+use serde::{Deserialize, Serialize};
+
+/// The attributes shared by all the characters of a [`TextRun`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, DynAny)]
+pub struct TextStyle {
+	pub font: Font,
+	pub font_size: f64,
+	/// The color the run is filled with, or `None` to use the fill of the layer.
+	pub fill: Option<Color>,
+	/// The distance the run is raised above the baseline, in pixels, where a negative shift lowers it.
+	pub baseline_shift: f64,
+	pub underline: bool,
+	pub strikethrough: bool,
+}
+
+impl TextStyle {
+	/// A style using the layer fill, without a baseline shift or any lines.
+	pub fn new(font: Font, font_size: f64) -> Self {
+		Self {
+			font,
+			font_size,
+			fill: None,
+			baseline_shift: 0.,
+			underline: false,
+			strikethrough: false,
+		}
+	}
+}
+
+impl Hash for TextStyle {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.font.hash(state);
+		self.font_size.to_bits().hash(state);
+		self.fill.hash(state);
+		self.baseline_shift.to_bits().hash(state);
+		self.underline.hash(state);
+		self.strikethrough.hash(state);
+	}
+}
+
+/// A span of text with a single [`TextStyle`].
+#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize, DynAny)]
+pub struct TextRun {
+	pub text: String,
+	pub style: TextStyle,
+}
+
+/// Text made of consecutive [`TextRun`]s, where line breaks and words may continue from one run into the next.
+#[derive(Debug, Clone, PartialEq, Hash, Serialize, Deserialize, DynAny)]
+pub struct RichText {
+	pub runs: Vec<TextRun>,
+}
+
+impl RichText {
+	/// Text with a single run.
+	pub fn new(text: impl Into<String>, style: TextStyle) -> Self {
+		Self {
+			runs: vec![TextRun { text: text.into(), style }],
+		}
+	}
+
+	/// The text of all the runs, without their styles.
+	pub fn text(&self) -> String {
+		self.runs.iter().map(|run| run.text.as_str()).collect()
+	}
+
+	/// The fonts used by the runs, which need to be loaded before the text can be drawn.
+	pub fn fonts(&self) -> impl Iterator<Item = &Font> {
+		self.runs.iter().map(|run| &run.style.font)
+	}
+
+	/// The style of the first run, which is used for the text box when editing the text.
+	pub fn first_style(&self) -> Option<&TextStyle> {
+		self.runs.first().map(|run| &run.style)
+	}
+
+	/// Replaces the text while keeping the styles of the characters that are unchanged.
+	///
+	/// The characters which differ from the old text take the `typing_style` if there is one, or otherwise the style of the run they were typed at the end of.
+	pub fn replace_text(&mut self, new_text: &str, typing_style: Option<TextStyle>) {
+		let old_text = self.text();
+
+		// The changed characters are those between the longest common prefix and suffix
+		let prefix = old_text
+			.char_indices()
+			.zip(new_text.chars())
+			.find(|&((_, old), new)| old != new)
+			.map_or(old_text.len().min(new_text.len()), |((index, _), _)| index);
+		let max_suffix = old_text.len().min(new_text.len()) - prefix;
+		let suffix = old_text
+			.char_indices()
+			.rev()
+			.zip(new_text.chars().rev())
+			.take_while(|&((index, old), new)| old == new && old_text.len() - index <= max_suffix)
+			.last()
+			.map_or(0, |((index, _), _)| old_text.len() - index);
+		let (removed_end, inserted) = (old_text.len() - suffix, &new_text[prefix..new_text.len() - suffix]);
+
+		// Text typed at the boundary of two runs continues the earlier one
+		let mut offset = 0;
+		let typed_at = self.runs.iter().find(|run| {
+			offset += run.text.len();
+			offset >= prefix
+		});
+		let Some(inserted_style) = typing_style.or_else(|| typed_at.map(|run| run.style.clone())) else {
+			return;
+		};
+
+		let mut runs = Vec::new();
+		let mut offset = 0;
+		for run in &self.runs {
+			let start = offset;
+			offset += run.text.len();
+			runs.push(TextRun {
+				text: run.text[..prefix.clamp(start, offset) - start].to_string(),
+				style: run.style.clone(),
+			});
+		}
+		runs.push(TextRun {
+			text: inserted.to_string(),
+			style: inserted_style.clone(),
+		});
+		let mut offset = 0;
+		for run in &self.runs {
+			let start = offset;
+			offset += run.text.len();
+			runs.push(TextRun {
+				text: run.text[removed_end.clamp(start, offset) - start..].to_string(),
+				style: run.style.clone(),
+			});
+		}
+
+		self.runs = runs;
+		self.merge_runs(inserted_style);
+	}
+
+	/// Removes the empty runs and joins neighbouring runs with the same style, keeping a single empty run with the `fallback_style` if there is no text.
+	fn merge_runs(&mut self, fallback_style: TextStyle) {
+		let mut runs: Vec<TextRun> = Vec::new();
+		for run in self.runs.drain(..).filter(|run| !run.text.is_empty()) {
+			match runs.last_mut() {
+				Some(last) if last.style == run.style => last.text.push_str(&run.text),
+				_ => runs.push(run),
+			}
+		}
+		if runs.is_empty() {
+			runs.push(TextRun {
+				text: String::new(),
+				style: fallback_style,
+			});
+		}
+		self.runs = runs;
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn style(font_size: f64) -> TextStyle {
+		TextStyle::new(Font::new("Merriweather".into(), "Normal (400)".into()), font_size)
+	}
+
+	fn rich_text(runs: &[(&str, f64)]) -> RichText {
+		let runs = runs
+			.iter()
+			.map(|&(text, font_size)| TextRun {
+				text: text.into(),
+				style: style(font_size),
+			})
+			.collect();
+		RichText { runs }
+	}
+
+	#[test]
+	fn edits_keep_the_styles_of_unchanged_text() {
+		let mut text = rich_text(&[("Hello ", 12.), ("bold", 20.), (" world", 12.)]);
+
+		// Typing at the end of a run continues it
+		text.replace_text("Hello bolder world", None);
+		assert_eq!(text, rich_text(&[("Hello ", 12.), ("bolder", 20.), (" world", 12.)]));
+
+		// Deleting a whole run joins its neighbours
+		text.replace_text("Hello  world", None);
+		assert_eq!(text, rich_text(&[("Hello  world", 12.)]));
+
+		// Characters at the edit aren't counted in both the common prefix and suffix
+		let mut text = rich_text(&[("aa", 12.), ("a", 20.)]);
+		text.replace_text("aaaa", None);
+		assert_eq!(text, rich_text(&[("aa", 12.), ("aa", 20.)]));
+	}
+
+	#[test]
+	fn typing_style_starts_a_new_run() {
+		let mut text = rich_text(&[("ab", 12.)]);
+		text.replace_text("aéb", Some(style(30.)));
+		assert_eq!(text, rich_text(&[("a", 12.), ("é", 30.), ("b", 12.)]));
+
+		text.replace_text("", None);
+		assert_eq!(text.runs.len(), 1);
+		assert_eq!(text.text(), "");
+	}
+}
@@ -0,0 +1,158 @@
+//! Text set along a path, for badges, seals and circular logos.
+
+use super::layout::{font_ascent, font_scale, layout_text, TextAlign, TypesettingConfig};
+use super::to_path::Builder;
+use crate::uuid::ManipulatorGroupId;
+
+use bezier_rs::{Subpath, SubpathTValue};
+use dyn_any::{DynAny, StaticType};
+use glam::{DAffine2, DVec2};
+use rustybuzz::ttf_parser::GlyphId;
+
+/// Which side of the path the glyphs are placed on, relative to the direction the path is drawn in.
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Hash, DynAny)]
+pub enum TextPathSide {
+	/// The glyphs stand on the path, with their baseline along it.
+	#[default]
+	Above,
+	/// The glyphs hang from the path, with the top of their em box along it.
+	Below,
+}
+
+impl TextPathSide {
+	pub fn list() -> [TextPathSide; 2] {
+		[TextPathSide::Above, TextPathSide::Below]
+	}
+}
+
+impl core::fmt::Display for TextPathSide {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			TextPathSide::Above => write!(f, "Above"),
+			TextPathSide::Below => write!(f, "Below"),
+		}
+	}
+}
+
+/// Outlines a single line of text along the path, with each glyph rotated to follow the tangent of the path at its center.
+///
+/// The `start_offset` moves the text along the path, in pixels. Glyphs which fall off the ends of an open path are left out, while those on a closed path wrap around it.
+pub fn text_on_path(
+	text: &str,
+	buzz_faces: &[rustybuzz::Face],
+	typesetting: TypesettingConfig,
+	path: &Subpath<ManipulatorGroupId>,
+	start_offset: f64,
+	side: TextPathSide,
+) -> Vec<Subpath<ManipulatorGroupId>> {
+	// Show blank layer if font has not loaded
+	if buzz_faces.is_empty() || path.len_segments() == 0 {
+		return Vec::new();
+	}
+
+	// The text is set on a single line, since the path takes the place of the text box
+	let typesetting = TypesettingConfig {
+		max_width: None,
+		max_height: None,
+		..typesetting
+	};
+	let layout = layout_text(&text.replace(['\r', '\n'], " "), buzz_faces, &typesetting);
+	let centers = layout.glyphs.iter().map(|glyph| glyph.position.x + glyph.advance / 2.).collect::<Vec<_>>();
+	let path_length = path.length(None);
+	let distances = glyph_distances(&centers, layout.size.x, path_length, path.closed(), start_offset, typesetting.align);
+
+	let ascent = font_ascent(&buzz_faces[0], typesetting.font_size);
+	let mut builder = Builder {
+		current_subpath: Subpath::new(Vec::new(), false),
+		other_subpaths: Vec::new(),
+		pos: DVec2::ZERO,
+		ascender: 0.,
+		scale: 1.,
+		id: ManipulatorGroupId::ZERO,
+	};
+
+	for (glyph, distance) in layout.glyphs.iter().zip(distances) {
+		let Some(distance) = distance else { continue };
+
+		// The glyph is outlined around its center on the baseline, then moved onto the path
+		let top = match side {
+			TextPathSide::Above => glyph.position.y - ascent,
+			TextPathSide::Below => glyph.position.y,
+		};
+		let buzz_face = &buzz_faces[glyph.face];
+		builder.scale = font_scale(buzz_face, typesetting.font_size);
+		builder.ascender = ascent / builder.scale;
+		builder.pos = DVec2::new(-glyph.advance / 2., top);
+		let start = builder.other_subpaths.len();
+		buzz_face.outline_glyph(GlyphId(glyph.glyph_id), &mut builder);
+		if !builder.current_subpath.is_empty() {
+			builder.other_subpaths.push(core::mem::replace(&mut builder.current_subpath, Subpath::new(Vec::new(), false)));
+		}
+
+		let t = SubpathTValue::GlobalEuclidean((distance / path_length).clamp(0., 1.));
+		let tangent = path.tangent(t);
+		let transform = DAffine2::from_angle_translation(tangent.y.atan2(tangent.x), path.evaluate(t));
+		for subpath in &mut builder.other_subpaths[start..] {
+			subpath.apply_transform(transform);
+		}
+	}
+	builder.other_subpaths
+}
+
+/// The distances along the path at which the centers of the glyphs are placed, or `None` for glyphs which fall off the ends of an open path.
+///
+/// Justified text spreads the space left on the path between the glyphs.
+fn glyph_distances(centers: &[f64], text_width: f64, path_length: f64, closed: bool, start_offset: f64, align: TextAlign) -> Vec<Option<f64>> {
+	if path_length <= 0. {
+		return vec![None; centers.len()];
+	}
+
+	let free_space = path_length - text_width;
+	// A closed path also has a gap between its last glyph and the first
+	let gaps = if closed { centers.len() } else { centers.len().saturating_sub(1) };
+	let (start, gap) = match align {
+		TextAlign::Left => (0., 0.),
+		TextAlign::Center => (free_space / 2., 0.),
+		TextAlign::Right => (free_space, 0.),
+		TextAlign::Justify if gaps > 0 && free_space > 0. => (0., free_space / gaps as f64),
+		TextAlign::Justify => (0., 0.),
+	};
+
+	let distances = centers.iter().enumerate().map(|(index, center)| start_offset + start + center + gap * index as f64);
+	distances
+		.map(|distance| match closed {
+			true => Some(distance.rem_euclid(path_length)),
+			false => (0.0..=path_length).contains(&distance).then_some(distance),
+		})
+		.collect()
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn aligned_glyphs_on_open_path() {
+		let centers = [5., 15., 25.];
+
+		assert_eq!(glyph_distances(&centers, 30., 100., false, 0., TextAlign::Left), [Some(5.), Some(15.), Some(25.)]);
+		assert_eq!(glyph_distances(&centers, 30., 100., false, 0., TextAlign::Center), [Some(40.), Some(50.), Some(60.)]);
+		assert_eq!(glyph_distances(&centers, 30., 100., false, 0., TextAlign::Right), [Some(75.), Some(85.), Some(95.)]);
+		assert_eq!(glyph_distances(&centers, 30., 100., false, 0., TextAlign::Justify), [Some(5.), Some(50.), Some(95.)]);
+
+		// Glyphs moved past the end of the path are left out
+		assert_eq!(glyph_distances(&centers, 30., 100., false, 80., TextAlign::Left), [Some(85.), Some(95.), None]);
+		assert_eq!(glyph_distances(&centers, 30., 100., false, -10., TextAlign::Left), [None, Some(5.), Some(15.)]);
+	}
+
+	#[test]
+	fn glyphs_wrap_around_closed_path() {
+		let centers = [5., 15., 25.];
+
+		assert_eq!(glyph_distances(&centers, 30., 100., true, 80., TextAlign::Left), [Some(85.), Some(95.), Some(5.)]);
+		assert_eq!(glyph_distances(&centers, 30., 90., true, 0., TextAlign::Justify), [Some(5.), Some(35.), Some(65.)]);
+		assert_eq!(glyph_distances(&centers, 30., 0., true, 0., TextAlign::Left), [None, None, None]);
+	}
+}
@@ -1,24 +1,26 @@
+use super::layout::{font_ascent, font_scale, layout_runs, layout_text, PositionedGlyph, ShapingRun, TypesettingConfig};
+use super::{Font, FontCache, FontVariation, RichText, TextStyle};
 use crate::uuid::ManipulatorGroupId;
+use crate::vector::style::{Fill, PathStyle};
+use crate::vector::{StyleRegion, VectorData};
 
 use bezier_rs::{ManipulatorGroup, Subpath};
 
 use glam::DVec2;
-use rustybuzz::ttf_parser::{GlyphId, OutlineBuilder};
-use rustybuzz::{GlyphBuffer, UnicodeBuffer};
-
-struct Builder {
-	current_subpath: Subpath<ManipulatorGroupId>,
-	other_subpaths: Vec<Subpath<ManipulatorGroupId>>,
-	pos: DVec2,
-	offset: DVec2,
-	ascender: f64,
-	scale: f64,
-	id: ManipulatorGroupId,
+use rustybuzz::ttf_parser::{GlyphId, OutlineBuilder, Tag};
+
+pub(super) struct Builder {
+	pub(super) current_subpath: Subpath<ManipulatorGroupId>,
+	pub(super) other_subpaths: Vec<Subpath<ManipulatorGroupId>>,
+	pub(super) pos: DVec2,
+	pub(super) ascender: f64,
+	pub(super) scale: f64,
+	pub(super) id: ManipulatorGroupId,
 }
 
 impl Builder {
 	fn point(&self, x: f32, y: f32) -> DVec2 {
-		self.pos + self.offset + DVec2::new(x as f64, self.ascender - y as f64) * self.scale
+		self.pos + DVec2::new(x as f64, self.ascender - y as f64) * self.scale
 	}
 }
 
@@ -53,125 +55,180 @@ impl OutlineBuilder for Builder {
 	}
 }
 
-fn font_properties(buzz_face: &rustybuzz::Face, font_size: f64) -> (f64, f64, UnicodeBuffer) {
-	let scale = (buzz_face.units_per_em() as f64).recip() * font_size;
-	let line_height = font_size;
-	let buffer = UnicodeBuffer::new();
-	(scale, line_height, buffer)
-}
-
-fn push_str(buffer: &mut UnicodeBuffer, word: &str, trailing_space: bool) {
-	buffer.push_str(word);
-
-	if trailing_space {
-		buffer.push_str(" ");
+/// Outlines the text, where the first face is the one of the text's font and the glyphs it is missing are taken from the others in order.
+pub fn to_path(str: &str, buzz_faces: &[rustybuzz::Face], typesetting: TypesettingConfig) -> Vec<Subpath<ManipulatorGroupId>> {
+	// Show blank layer if font has not loaded
+	if buzz_faces.is_empty() {
+		return vec![];
 	}
-}
-
-fn wrap_word(line_width: Option<f64>, glyph_buffer: &GlyphBuffer, scale: f64, x_pos: f64) -> bool {
-	if let Some(line_width) = line_width {
-		let word_length: i32 = glyph_buffer.glyph_positions().iter().map(|pos| pos.x_advance).sum();
-		let scaled_word_length = word_length as f64 * scale;
 
-		if scaled_word_length + x_pos > line_width {
-			return true;
-		}
-	}
-	false
+	let layout = layout_text(str, buzz_faces, &typesetting);
+	let mut builder = Builder {
+		current_subpath: Subpath::new(Vec::new(), false),
+		other_subpaths: Vec::new(),
+		pos: DVec2::ZERO,
+		ascender: 0.,
+		scale: 1.,
+		id: ManipulatorGroupId::ZERO,
+	};
+	outline_glyphs(&mut builder, &layout.glyphs, buzz_faces, typesetting.font_size);
+	builder.other_subpaths
 }
 
-pub fn to_path(str: &str, buzz_face: Option<rustybuzz::Face>, font_size: f64, line_width: Option<f64>) -> Vec<Subpath<ManipulatorGroupId>> {
-	let buzz_face = match buzz_face {
-		Some(face) => face,
-		// Show blank layer if font has not loaded
-		None => return vec![],
+/// Outlines the rich text, where the runs which have a fill of their own are given it as a [`StyleRegion`] of the vector data.
+pub fn rich_text_to_path(rich_text: &RichText, font_cache: &FontCache, typesetting: TypesettingConfig) -> VectorData {
+	// Show blank layer if the fonts have not loaded
+	let Some(buzz_faces) = load_run_faces(rich_text, font_cache, &typesetting.font_variations) else {
+		return VectorData::empty();
 	};
-
-	let (scale, line_height, mut buffer) = font_properties(&buzz_face, font_size);
+	let layout = layout_runs(&shaping_runs(rich_text, &buzz_faces), &typesetting);
 
 	let mut builder = Builder {
 		current_subpath: Subpath::new(Vec::new(), false),
 		other_subpaths: Vec::new(),
 		pos: DVec2::ZERO,
-		offset: DVec2::ZERO,
-		ascender: (buzz_face.ascender() as f64 / buzz_face.height() as f64) * font_size / scale,
-		scale,
+		ascender: 0.,
+		scale: 1.,
 		id: ManipulatorGroupId::ZERO,
 	};
+	let mut style_regions = Vec::new();
+
+	let mut glyphs = layout.glyphs.as_slice();
+	while let Some(first) = glyphs.first() {
+		// Consecutive glyphs of the same run are outlined together, where reordered right-to-left text may split a run into several groups
+		let length = glyphs.iter().position(|glyph| glyph.run != first.run).unwrap_or(glyphs.len());
+		let (run_glyphs, rest) = glyphs.split_at(length);
+		glyphs = rest;
+
+		let (run_faces, style) = (&buzz_faces[first.run], &rich_text.runs[first.run].style);
+		let start = builder.other_subpaths.len();
 
-	for line in str.split('\n') {
-		let length = line.split(' ').count();
-		for (index, word) in line.split(' ').enumerate() {
-			push_str(&mut buffer, word, index != length - 1);
-			let glyph_buffer = rustybuzz::shape(&buzz_face, &[], buffer);
-
-			if wrap_word(line_width, &glyph_buffer, scale, builder.pos.x) {
-				builder.pos = DVec2::new(0., builder.pos.y + line_height);
-			}
-
-			for (glyph_position, glyph_info) in glyph_buffer.glyph_positions().iter().zip(glyph_buffer.glyph_infos()) {
-				if let Some(line_width) = line_width {
-					if builder.pos.x + (glyph_position.x_advance as f64 * builder.scale) >= line_width {
-						builder.pos = DVec2::new(0., builder.pos.y + line_height);
-					}
-				}
-				builder.offset = DVec2::new(glyph_position.x_offset as f64, glyph_position.y_offset as f64) * builder.scale;
-				buzz_face.outline_glyph(GlyphId(glyph_info.glyph_id as u16), &mut builder);
-				if !builder.current_subpath.is_empty() {
-					builder.other_subpaths.push(core::mem::replace(&mut builder.current_subpath, Subpath::new(Vec::new(), false)));
-				}
-
-				builder.pos += DVec2::new(glyph_position.x_advance as f64, glyph_position.y_advance as f64) * builder.scale;
-			}
-
-			buffer = glyph_buffer.clear();
+		outline_glyphs(&mut builder, run_glyphs, run_faces, style.font_size);
+		builder.other_subpaths.extend(decoration_lines(run_glyphs, &run_faces[0], style));
+
+		if let Some(color) = style.fill {
+			let end = builder.other_subpaths.len();
+			let style = PathStyle::new(None, Fill::Solid(color));
+			style_regions.push(StyleRegion { start, end, style });
 		}
-		builder.pos = DVec2::new(0., builder.pos.y + line_height);
 	}
-	builder.other_subpaths
-}
 
-pub fn bounding_box(str: &str, buzz_face: Option<rustybuzz::Face>, font_size: f64, line_width: Option<f64>) -> DVec2 {
-	let buzz_face = match buzz_face {
-		Some(face) => face,
-		// Show blank layer if font has not loaded
-		None => return DVec2::ZERO,
-	};
-
-	let (scale, line_height, mut buffer) = font_properties(&buzz_face, font_size);
+	VectorData {
+		style_regions,
+		..VectorData::from_subpaths(builder.other_subpaths)
+	}
+}
 
-	let mut pos = DVec2::ZERO;
-	let mut bounds = DVec2::ZERO;
+/// The far corner of the rich text's box, whose other corner is at the origin.
+pub fn rich_text_bounding_box(rich_text: &RichText, font_cache: &FontCache, typesetting: TypesettingConfig) -> DVec2 {
+	match load_run_faces(rich_text, font_cache, &typesetting.font_variations) {
+		Some(buzz_faces) => layout_runs(&shaping_runs(rich_text, &buzz_faces), &typesetting).size,
+		// Show blank layer if the fonts have not loaded
+		None => DVec2::ZERO,
+	}
+}
 
-	for line in str.split('\n') {
-		let length = line.split(' ').count();
-		for (index, word) in line.split(' ').enumerate() {
-			push_str(&mut buffer, word, index != length - 1);
+/// Outlines glyphs of the same size, which are shaped from the given faces, where the first is the one whose baseline they sit on.
+fn outline_glyphs(builder: &mut Builder, glyphs: &[PositionedGlyph], buzz_faces: &[rustybuzz::Face], font_size: f64) {
+	let ascent = font_ascent(&buzz_faces[0], font_size);
+	for glyph in glyphs {
+		let buzz_face = &buzz_faces[glyph.face];
+		builder.scale = font_scale(buzz_face, font_size);
+		builder.ascender = ascent / builder.scale;
+		builder.pos = glyph.position;
+		buzz_face.outline_glyph(GlyphId(glyph.glyph_id), builder);
+		if !builder.current_subpath.is_empty() {
+			builder.other_subpaths.push(core::mem::replace(&mut builder.current_subpath, Subpath::new(Vec::new(), false)));
+		}
+	}
+}
 
-			let glyph_buffer = rustybuzz::shape(&buzz_face, &[], buffer);
+fn load_run_faces<'a>(rich_text: &RichText, font_cache: &'a FontCache, variations: &[FontVariation]) -> Option<Vec<Vec<rustybuzz::Face<'a>>>> {
+	let run_faces = rich_text.runs.iter().map(|run| load_faces(font_cache, &run.style.font, variations));
+	run_faces.map(|buzz_faces| (!buzz_faces.is_empty()).then_some(buzz_faces)).collect()
+}
 
-			if wrap_word(line_width, &glyph_buffer, scale, pos.x) {
-				pos = DVec2::new(0., pos.y + line_height);
-			}
+fn shaping_runs<'a>(rich_text: &'a RichText, buzz_faces: &'a [Vec<rustybuzz::Face<'a>>]) -> Vec<ShapingRun<'a>> {
+	let runs = rich_text.runs.iter().zip(buzz_faces);
+	runs.map(|(run, buzz_faces)| ShapingRun {
+		text: &run.text,
+		buzz_faces,
+		font_size: run.style.font_size,
+		baseline_shift: run.style.baseline_shift,
+	})
+	.collect()
+}
 
-			for glyph_position in glyph_buffer.glyph_positions() {
-				if let Some(line_width) = line_width {
-					if pos.x + (glyph_position.x_advance as f64 * scale) >= line_width {
-						pos = DVec2::new(0., pos.y + line_height);
-					}
-				}
-				pos += DVec2::new(glyph_position.x_advance as f64, glyph_position.y_advance as f64) * scale;
-			}
-			bounds = bounds.max(pos + DVec2::new(0., line_height));
+/// The rectangles of the underline and strikethrough of the glyphs of a run, with one for each line the run is on.
+fn decoration_lines(glyphs: &[PositionedGlyph], buzz_face: &rustybuzz::Face, style: &TextStyle) -> Vec<Subpath<ManipulatorGroupId>> {
+	let scale = font_scale(buzz_face, style.font_size);
+	let em = buzz_face.units_per_em() as f32;
+	// The positions are the top edge of the line above the baseline, in font units
+	let mut metrics = Vec::new();
+	if style.underline {
+		metrics.push(
+			buzz_face
+				.underline_metrics()
+				.map_or((-0.1 * em, 0.05 * em), |metrics| (metrics.position as f32, metrics.thickness as f32)),
+		);
+	}
+	if style.strikethrough {
+		metrics.push(
+			buzz_face
+				.strikeout_metrics()
+				.map_or((0.3 * em, 0.05 * em), |metrics| (metrics.position as f32, metrics.thickness as f32)),
+		);
+	}
 
-			buffer = glyph_buffer.clear();
+	let mut lines = Vec::new();
+	let mut glyphs = glyphs;
+	while let Some(first) = glyphs.first() {
+		let length = glyphs.iter().position(|glyph| glyph.position.y != first.position.y).unwrap_or(glyphs.len());
+		let (line_glyphs, rest) = glyphs.split_at(length);
+		glyphs = rest;
+
+		let last = line_glyphs[length - 1];
+		let baseline = first.position.y + font_ascent(buzz_face, style.font_size);
+		for &(position, thickness) in &metrics {
+			let top = baseline - position as f64 * scale;
+			lines.push(Subpath::new_rect(
+				DVec2::new(first.position.x, top),
+				DVec2::new(last.position.x + last.advance, top + thickness as f64 * scale),
+			));
 		}
-		pos = DVec2::new(0., pos.y + line_height);
 	}
+	lines
+}
 
-	bounds
+/// The far corner of the text box, whose other corner is at the origin.
+pub fn bounding_box(str: &str, buzz_faces: &[rustybuzz::Face], typesetting: TypesettingConfig) -> DVec2 {
+	match buzz_faces.is_empty() {
+		false => layout_text(str, buzz_faces, &typesetting).size,
+		// Show blank layer if font has not loaded
+		true => DVec2::ZERO,
+	}
 }
 
 pub fn load_face(data: &[u8]) -> rustybuzz::Face {
 	rustybuzz::Face::from_slice(data, 0).expect("Loading font failed")
 }
+
+/// Loads the face of the font followed by the faces of the default and fallback fonts, or nothing if the font hasn't loaded.
+///
+/// The variations are applied to each face which has their axes, so the fallback fonts follow the weight and width of the font where they can.
+pub fn load_faces<'a>(font_cache: &'a FontCache, font: &Font, variations: &[FontVariation]) -> Vec<rustybuzz::Face<'a>> {
+	let variations = variations
+		.iter()
+		.map(|variation| rustybuzz::Variation {
+			tag: Tag::from_bytes(&variation.tag),
+			value: variation.value as f32,
+		})
+		.collect::<Vec<_>>();
+	let faces = font_cache.get_with_fallbacks(font).into_iter().map(|data| load_face(data));
+	faces
+		.map(|mut buzz_face| {
+			buzz_face.set_variations(&variations);
+			buzz_face
+		})
+		.collect()
+}
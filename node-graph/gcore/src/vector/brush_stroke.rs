@@ -1,9 +1,11 @@
 use crate::raster::bbox::AxisAlignedBbox;
-use crate::raster::BlendMode;
+use crate::raster::{BlendMode, Image};
 use crate::Color;
 
 use dyn_any::{DynAny, StaticType};
 use glam::DVec2;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::hash::{Hash, Hasher};
 
 /// The style of a brush.
@@ -16,6 +18,12 @@ pub struct BrushStyle {
 	pub flow: f64,
 	pub spacing: f64, // Spacing as a fraction of the diameter.
 	pub blend_mode: BlendMode,
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub dynamics: BrushDynamics,
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub tip: BrushTip,
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub scattering: BrushScattering,
 }
 
 impl Default for BrushStyle {
@@ -27,6 +35,9 @@ impl Default for BrushStyle {
 			flow: 100.,
 			spacing: 50., // Percentage of diameter.
 			blend_mode: BlendMode::Normal,
+			dynamics: BrushDynamics::default(),
+			tip: BrushTip::Round,
+			scattering: BrushScattering::default(),
 		}
 	}
 }
@@ -38,6 +49,9 @@ impl Hash for BrushStyle {
 		self.hardness.to_bits().hash(state);
 		self.flow.to_bits().hash(state);
 		self.spacing.to_bits().hash(state);
+		self.dynamics.hash(state);
+		self.tip.hash(state);
+		self.scattering.hash(state);
 	}
 }
 
@@ -51,23 +65,471 @@ impl PartialEq for BrushStyle {
 			&& self.flow.to_bits() == other.flow.to_bits()
 			&& self.spacing.to_bits() == other.spacing.to_bits()
 			&& self.blend_mode == other.blend_mode
+			&& self.dynamics == other.dynamics
+			&& self.tip == other.tip
+			&& self.scattering == other.scattering
 	}
 }
 
+impl BrushStyle {
+	/// Whether every dab of a stroke is the same round stamp, which lets the stroke be drawn by blitting a single texture.
+	pub fn has_uniform_dabs(&self) -> bool {
+		self.tip == BrushTip::Round && !self.dynamics.is_active() && !self.scattering.is_active()
+	}
+
+	/// The largest distance from a blit point covered by its dabs.
+	pub fn max_dab_radius(&self) -> f64 {
+		// Tips other than the round one fill a square, which reaches its diagonal when rotated
+		let tip_extent = if self.tip == BrushTip::Round { 1. } else { core::f64::consts::SQRT_2 };
+		self.diameter / 2. * tip_extent + self.scattering.scatter.max(0.) / 100. * self.diameter
+	}
+}
+
+/// The shape of the mark the brush leaves at each dab.
+#[derive(Clone, Debug, Default, PartialEq, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+pub enum BrushTip {
+	/// A round stamp whose edge is softened by the hardness of the brush.
+	#[default]
+	Round,
+	/// An image held in the [`BrushTipImages`] of the brush node, scaled so its larger side spans the diameter. Its alpha multiplied by its
+	/// inverted luminance gives the coverage, so a black shape paints the same on a white or a transparent background.
+	Image(BrushTipId),
+	/// Closed polygons filled with the even-odd rule, centered on the origin and normalized so the larger side of their bounding box is 1 (see [`BrushTip::shape`]).
+	Shape(Vec<Vec<DVec2>>),
+}
+
+impl Hash for BrushTip {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		core::mem::discriminant(self).hash(state);
+		match self {
+			BrushTip::Round => {}
+			BrushTip::Image(id) => id.hash(state),
+			BrushTip::Shape(polygons) => polygons
+				.iter()
+				.flatten()
+				.for_each(|point| [point.x, point.y].iter().for_each(|coordinate| coordinate.to_bits().hash(state))),
+		}
+	}
+}
+
+impl BrushTip {
+	/// A shape tip from polygons in any coordinate space, which falls back to the round tip when the polygons have no area.
+	pub fn shape(polygons: Vec<Vec<DVec2>>) -> Self {
+		let polygons: Vec<_> = polygons.into_iter().filter(|polygon| polygon.len() >= 3).collect();
+		let (min, max) = polygons
+			.iter()
+			.flatten()
+			.fold((DVec2::splat(f64::INFINITY), DVec2::splat(f64::NEG_INFINITY)), |(min, max), &point| (min.min(point), max.max(point)));
+		let size = (max - min).max_element();
+		if !size.is_finite() || size <= f64::EPSILON {
+			return BrushTip::Round;
+		}
+
+		let center = (min + max) / 2.;
+		BrushTip::Shape(polygons.into_iter().map(|polygon| polygon.into_iter().map(|point| (point - center) / size).collect()).collect())
+	}
+}
+
+/// The identifier of the image of an image tip, which is the hash of its pixels.
+pub type BrushTipId = u64;
+
+/// The images of the image tips used by the strokes of a brush node, each held once however many strokes refer to it.
+#[derive(Clone, Debug, Default, PartialEq, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BrushTipImages {
+	images: HashMap<BrushTipId, Image<Color>>,
+}
+
+impl Hash for BrushTipImages {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		// The identifiers are hashes of the images, so they stand in for the images themselves
+		let mut ids: Vec<_> = self.images.keys().collect();
+		ids.sort_unstable();
+		ids.hash(state);
+	}
+}
+
+impl BrushTipImages {
+	/// Adds the image unless it is already held, returning the identifier the tip refers to it by.
+	pub fn insert(&mut self, image: Image<Color>) -> BrushTipId {
+		let mut hasher = DefaultHasher::new();
+		image.hash(&mut hasher);
+		let id = hasher.finish();
+		self.images.entry(id).or_insert(image);
+		id
+	}
+
+	pub fn get(&self, id: BrushTipId) -> Option<&Image<Color>> {
+		self.images.get(&id)
+	}
+
+	/// Copies the image of the tip over from another set of images, if it is an image tip.
+	pub fn copy_tip(&mut self, tip: &BrushTip, from: &BrushTipImages) {
+		if let BrushTip::Image(id) = *tip {
+			if let Some(image) = from.get(id) {
+				self.images.entry(id).or_insert_with(|| image.clone());
+			}
+		}
+	}
+}
+
+/// How the dabs of a stroke are rotated.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+pub enum BrushRotation {
+	/// Every dab has the same angle.
+	#[default]
+	Fixed,
+	/// The angle is added to the direction the stroke travels in.
+	FollowStroke,
+	/// The angle is added to a random rotation.
+	Random,
+}
+
+impl BrushRotation {
+	pub fn list() -> [BrushRotation; 3] {
+		[BrushRotation::Fixed, BrushRotation::FollowStroke, BrushRotation::Random]
+	}
+}
+
+impl core::fmt::Display for BrushRotation {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			BrushRotation::Fixed => write!(f, "Fixed"),
+			BrushRotation::FollowStroke => write!(f, "Follow Stroke"),
+			BrushRotation::Random => write!(f, "Random"),
+		}
+	}
+}
+
+/// How the dabs are placed around each blit point of a stroke.
+#[derive(Clone, Copy, Debug, PartialEq, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BrushScattering {
+	pub rotation: BrushRotation,
+	/// The angle of the dabs, in degrees.
+	pub angle: f64,
+	/// The largest distance (in percent of the diameter) a dab is randomly moved away from its blit point.
+	pub scatter: f64,
+	/// The number of dabs at each blit point.
+	pub count: u32,
+	/// The largest random reduction (in percent) of the diameter of a dab.
+	pub size_jitter: f64,
+	/// Picks the random rotation, scatter and size jitter.
+	pub seed: u32,
+}
+
+impl Default for BrushScattering {
+	fn default() -> Self {
+		Self {
+			rotation: BrushRotation::Fixed,
+			angle: 0.,
+			scatter: 0.,
+			count: 1,
+			size_jitter: 0.,
+			seed: 0,
+		}
+	}
+}
+
+impl Hash for BrushScattering {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.rotation.hash(state);
+		self.angle.to_bits().hash(state);
+		self.scatter.to_bits().hash(state);
+		self.count.hash(state);
+		self.size_jitter.to_bits().hash(state);
+		self.seed.hash(state);
+	}
+}
+
+impl BrushScattering {
+	/// Whether the dabs can differ from a single dab at each blit point, not counting their rotation which only shows with tips other than the round one.
+	pub fn is_active(&self) -> bool {
+		self.count > 1 || self.scatter > 0. || self.size_jitter > 0.
+	}
+}
+
+/// A named brush style which can be saved to a file and shared.
+#[derive(Clone, Debug, Default, PartialEq, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BrushPreset {
+	pub name: String,
+	pub style: BrushStyle,
+	/// The image of the tip, if the style has an image tip, which travels with the preset so it can be shared.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub tip_image: Option<Image<Color>>,
+}
+
+/// The property of the stylus input which drives a brush dynamic.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "std", derive(specta::Type))]
+pub enum BrushDynamicsInput {
+	#[default]
+	Off,
+	Pressure,
+	Tilt,
+	Twist,
+	Velocity,
+}
+
+impl BrushDynamicsInput {
+	pub fn list() -> [BrushDynamicsInput; 5] {
+		[
+			BrushDynamicsInput::Off,
+			BrushDynamicsInput::Pressure,
+			BrushDynamicsInput::Tilt,
+			BrushDynamicsInput::Twist,
+			BrushDynamicsInput::Velocity,
+		]
+	}
+
+	/// The input at the stamp, normalized to the `0..=1` range. `None` when the dynamic is turned off.
+	pub fn value(&self, stamp: &BrushStamp) -> Option<f64> {
+		let value = match self {
+			BrushDynamicsInput::Off => return None,
+			BrushDynamicsInput::Pressure => stamp.pressure,
+			// An upright stylus has no tilt, one lying flat on the tablet is tilted by 90°
+			BrushDynamicsInput::Tilt => stamp.tilt.length() / 90.,
+			BrushDynamicsInput::Twist => stamp.twist / 360.,
+			BrushDynamicsInput::Velocity => stamp.velocity / MAX_DYNAMICS_VELOCITY,
+		};
+		Some(value.clamp(0., 1.))
+	}
+}
+
+impl core::fmt::Display for BrushDynamicsInput {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		match self {
+			BrushDynamicsInput::Off => write!(f, "Off"),
+			BrushDynamicsInput::Pressure => write!(f, "Pressure"),
+			BrushDynamicsInput::Tilt => write!(f, "Tilt"),
+			BrushDynamicsInput::Twist => write!(f, "Twist"),
+			BrushDynamicsInput::Velocity => write!(f, "Velocity"),
+		}
+	}
+}
+
+/// The stylus speed, in pixels per millisecond, at which the velocity input reaches its maximum.
+pub const MAX_DYNAMICS_VELOCITY: f64 = 2.;
+
+/// Maps a stylus input onto the factor applied to one of the brush parameters.
+#[derive(Clone, Copy, Debug, PartialEq, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BrushDynamicsCurve {
+	pub input: BrushDynamicsInput,
+	/// The factor (in percent) applied when the input is at its lowest.
+	pub minimum: f64,
+	/// The exponent of the response: above 1 the input needs to be pushed further before it has an effect, below 1 it responds sooner.
+	pub gamma: f64,
+}
+
+impl Default for BrushDynamicsCurve {
+	fn default() -> Self {
+		Self {
+			input: BrushDynamicsInput::Off,
+			minimum: 0.,
+			gamma: 1.,
+		}
+	}
+}
+
+impl Hash for BrushDynamicsCurve {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.input.hash(state);
+		self.minimum.to_bits().hash(state);
+		self.gamma.to_bits().hash(state);
+	}
+}
+
+impl BrushDynamicsCurve {
+	pub fn new(input: BrushDynamicsInput) -> Self {
+		Self { input, ..Default::default() }
+	}
+
+	pub fn is_active(&self) -> bool {
+		self.input != BrushDynamicsInput::Off
+	}
+
+	/// The factor (in the `0..=1` range) for the stylus input at the stamp, which is always 1 when the curve is turned off.
+	pub fn evaluate(&self, stamp: &BrushStamp) -> f64 {
+		let Some(value) = self.input.value(stamp) else { return 1. };
+		let minimum = (self.minimum / 100.).clamp(0., 1.);
+		minimum + (1. - minimum) * value.powf(self.gamma.max(0.01))
+	}
+}
+
+/// How the stylus input varies the brush from one stamp to the next along a stroke.
+#[derive(Clone, Copy, Debug, Default, PartialEq, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BrushDynamics {
+	pub diameter: BrushDynamicsCurve,
+	pub flow: BrushDynamicsCurve,
+	pub hardness: BrushDynamicsCurve,
+	/// The largest random variation (in percent) of the hue, saturation and lightness of each stamp.
+	pub color_jitter: f64,
+	/// Scales the color jitter, which is applied at full strength when the curve is turned off.
+	pub color_jitter_curve: BrushDynamicsCurve,
+}
+
+impl Hash for BrushDynamics {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		self.diameter.hash(state);
+		self.flow.hash(state);
+		self.hardness.hash(state);
+		self.color_jitter.to_bits().hash(state);
+		self.color_jitter_curve.hash(state);
+	}
+}
+
+impl BrushDynamics {
+	/// Whether the stamps of a stroke can differ from each other, otherwise every stamp uses the style of the stroke.
+	pub fn is_active(&self) -> bool {
+		self.diameter.is_active() || self.flow.is_active() || self.hardness.is_active() || self.color_jitter > 0.
+	}
+}
+
+/// The parameters of a single stamp of a stroke, after the brush dynamics are applied.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StampStyle {
+	pub color: Color,
+	pub diameter: f64,
+	pub hardness: f64,
+	pub flow: f64,
+}
+
+impl From<&BrushStyle> for StampStyle {
+	fn from(style: &BrushStyle) -> Self {
+		Self {
+			color: style.color,
+			diameter: style.diameter,
+			hardness: style.hardness,
+			flow: style.flow,
+		}
+	}
+}
+
+impl BrushStyle {
+	/// The style of a single stamp, with the brush dynamics applied to the stylus input at that stamp.
+	///
+	/// The seed picks the color jitter, so a stamp gets the same color every time the stroke is rendered.
+	pub fn with_dynamics(&self, stamp: &BrushStamp, seed: u64) -> StampStyle {
+		let dynamics = &self.dynamics;
+		let mut style = StampStyle::from(self);
+		style.diameter *= dynamics.diameter.evaluate(stamp);
+		style.flow *= dynamics.flow.evaluate(stamp);
+		style.hardness *= dynamics.hardness.evaluate(stamp);
+
+		let jitter = (dynamics.color_jitter / 100.).clamp(0., 1.) * dynamics.color_jitter_curve.evaluate(stamp);
+		if jitter > 0. {
+			let [hue, saturation, lightness, alpha] = self.color.to_unassociated_alpha().to_hsla();
+			let offset = |channel: u64| (jitter * jitter_noise(seed, channel) / 2.) as f32;
+			let hue = hue + offset(0);
+			let saturation = (saturation + offset(1)).clamp(0., 1.);
+			let lightness = (lightness + offset(2)).clamp(0., 1.);
+			style.color = Color::from_hsla(hue, saturation, lightness, 1.).to_associated_alpha(alpha);
+		}
+
+		style
+	}
+}
+
+/// A reproducible pseudo-random value in the `-1..=1` range, using the SplitMix64 mixing function.
+fn jitter_noise(seed: u64, channel: u64) -> f64 {
+	let mut z = (seed ^ channel.wrapping_mul(0xD1B5_4A32_D192_ED03)).wrapping_add(0x9E37_79B9_7F4A_7C15);
+	z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+	z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+	z ^= z >> 31;
+	(z >> 11) as f64 / (1_u64 << 53) as f64 * 2. - 1.
+}
+
 /// A single sample of brush parameters across the brush stroke.
 #[derive(Clone, Debug, PartialEq, DynAny)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct BrushInputSample {
 	// The position of the sample in layer space, in pixels.
 	// The origin of layer space is not specified.
 	pub position: DVec2,
-	// Future work: pressure, stylus angle, etc.
+	// The pressure of the stylus, in the 0..=1 range.
+	pub pressure: f64,
+	// The tilt of the stylus along the x and y axes of the screen, in degrees.
+	pub tilt: DVec2,
+	// The clockwise rotation of the stylus around its own axis, in degrees.
+	pub twist: f64,
+	// The time at which the sample was taken, in milliseconds.
+	pub timestamp: f64,
+}
+
+impl Default for BrushInputSample {
+	fn default() -> Self {
+		Self {
+			position: DVec2::ZERO,
+			pressure: 1.,
+			tilt: DVec2::ZERO,
+			twist: 0.,
+			timestamp: 0.,
+		}
+	}
+}
+
+impl BrushInputSample {
+	pub fn from_position(position: DVec2) -> Self {
+		Self { position, ..Default::default() }
+	}
 }
 
 impl Hash for BrushInputSample {
 	fn hash<H: Hasher>(&self, state: &mut H) {
 		self.position.x.to_bits().hash(state);
 		self.position.y.to_bits().hash(state);
+		self.pressure.to_bits().hash(state);
+		self.tilt.x.to_bits().hash(state);
+		self.tilt.y.to_bits().hash(state);
+		self.twist.to_bits().hash(state);
+		self.timestamp.to_bits().hash(state);
+	}
+}
+
+/// A blit point of a stroke, along with the stylus input interpolated at that point.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BrushStamp {
+	pub position: DVec2,
+	pub pressure: f64,
+	pub tilt: DVec2,
+	pub twist: f64,
+	/// The speed of the stylus along the stroke, in pixels per millisecond.
+	pub velocity: f64,
+	/// The angle (in radians) of the direction the stroke travels in.
+	pub direction: f64,
+}
+
+/// A single mark of the brush tip, several of which can be placed around each stamp of a stroke.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BrushDab {
+	pub position: DVec2,
+	/// The rotation of the tip, in radians.
+	pub angle: f64,
+	pub style: StampStyle,
+}
+
+impl BrushStamp {
+	fn between(start: &BrushInputSample, end: &BrushInputSample, t: f64, position: DVec2, velocity: f64, direction: f64) -> Self {
+		let lerp = |a: f64, b: f64| a + (b - a) * t;
+		// Take the shorter way around so the twist doesn't spin backwards when it wraps past 360°
+		let twist_delta = (end.twist - start.twist + 540.).rem_euclid(360.) - 180.;
+		Self {
+			position,
+			pressure: lerp(start.pressure, end.pressure),
+			tilt: start.tilt.lerp(end.tilt, t),
+			twist: (start.twist + twist_delta * t).rem_euclid(360.),
+			velocity,
+			direction,
+		}
 	}
 }
 
@@ -81,7 +543,7 @@ pub struct BrushStroke {
 
 impl BrushStroke {
 	pub fn bounding_box(&self) -> AxisAlignedBbox {
-		let radius = self.style.diameter / 2.;
+		let radius = self.style.max_dab_radius();
 		self.compute_blit_points()
 			.iter()
 			.map(|pos| AxisAlignedBbox {
@@ -93,6 +555,10 @@ impl BrushStroke {
 	}
 
 	pub fn compute_blit_points(&self) -> Vec<DVec2> {
+		self.compute_blit_stamps().into_iter().map(|stamp| stamp.position).collect()
+	}
+
+	pub fn compute_blit_stamps(&self) -> Vec<BrushStamp> {
 		// We always travel in a straight line towards the next user input,
 		// placing a blit point every time we travelled our spacing distance.
 		let spacing_dist = self.style.spacing / 100. * self.style.diameter;
@@ -101,14 +567,29 @@ impl BrushStroke {
 			return Vec::new();
 		};
 
+		// The first stamp faces the second sample, until then it can't know where the stroke is heading
+		let first_direction = self.trace.get(1).map_or(0., |sample| {
+			let delta = sample.position - first_sample.position;
+			delta.y.atan2(delta.x)
+		});
+
 		let mut cur_pos = first_sample.position;
-		let mut result = vec![cur_pos];
+		let mut result = vec![BrushStamp::between(first_sample, first_sample, 0., cur_pos, 0., first_direction)];
 		let mut dist_until_next_blit = spacing_dist;
-		for sample in &self.trace[1..] {
+		let mut velocity = 0.;
+		for (prev_sample, sample) in self.trace.iter().zip(&self.trace[1..]) {
 			// Travel to the next sample.
 			let delta = sample.position - cur_pos;
 			let mut dist_left = delta.length();
 			let unit_step = delta / dist_left;
+			let direction = delta.y.atan2(delta.x);
+
+			// The velocity only depends on the samples at either end of the segment, so extending the stroke doesn't change its earlier stamps.
+			let segment_length = dist_left;
+			let elapsed = sample.timestamp - prev_sample.timestamp;
+			if elapsed > 0. {
+				velocity = segment_length / elapsed;
+			}
 
 			while dist_left >= dist_until_next_blit {
 				// Take a step to the next blit point.
@@ -116,7 +597,8 @@ impl BrushStroke {
 				dist_left -= dist_until_next_blit;
 
 				// Blit.
-				result.push(cur_pos);
+				let t = 1. - dist_left / segment_length;
+				result.push(BrushStamp::between(prev_sample, sample, t, cur_pos, velocity, direction));
 				dist_until_next_blit = spacing_dist;
 			}
 
@@ -127,4 +609,55 @@ impl BrushStroke {
 
 		result
 	}
+
+	/// Seeds the random variations of the stroke with its start, which stays the same while the stroke is being drawn.
+	fn stroke_seed(&self) -> u64 {
+		self.trace.first().map_or(0, |sample| sample.position.x.to_bits() ^ sample.position.y.to_bits().rotate_left(32))
+	}
+
+	/// The style of the stamp at the given index of [`Self::compute_blit_stamps`].
+	pub fn stamp_style(&self, stamp: &BrushStamp, index: usize) -> StampStyle {
+		self.style.with_dynamics(stamp, self.stroke_seed().wrapping_add(index as u64))
+	}
+
+	/// The dabs of the stamps after the first `skip` ones, with the brush dynamics, rotation and scattering applied.
+	pub fn compute_dabs(&self, skip: usize) -> Vec<BrushDab> {
+		let scattering = &self.style.scattering;
+		let count = scattering.count.max(1) as usize;
+		let seed = self.stroke_seed() ^ (scattering.seed as u64).rotate_left(17);
+		let random = |dab_index: usize, channel: u64| (jitter_noise(seed.wrapping_add(dab_index as u64), channel) + 1.) / 2.;
+
+		let stamps = self.compute_blit_stamps();
+		let mut dabs = Vec::with_capacity(stamps.len().saturating_sub(skip) * count);
+		for (index, stamp) in stamps.iter().enumerate().skip(skip) {
+			let style = self.stamp_style(stamp, index);
+			for copy in 0..count {
+				let dab_index = index * count + copy;
+
+				let angle = scattering.angle.to_radians()
+					+ match scattering.rotation {
+						BrushRotation::Fixed => 0.,
+						BrushRotation::FollowStroke => stamp.direction,
+						BrushRotation::Random => random(dab_index, 0) * core::f64::consts::TAU,
+					};
+
+				// Uniformly distributed over a disk with the scatter distance as its radius
+				let scatter_distance = scattering.scatter.max(0.) / 100. * style.diameter * random(dab_index, 1).sqrt();
+				let scatter_offset = DVec2::from_angle(random(dab_index, 2) * core::f64::consts::TAU) * scatter_distance;
+
+				let size_jitter = (scattering.size_jitter / 100.).clamp(0., 1.) * random(dab_index, 3);
+				let style = StampStyle {
+					diameter: style.diameter * (1. - size_jitter),
+					..style
+				};
+
+				dabs.push(BrushDab {
+					position: stamp.position + scatter_offset,
+					angle,
+					style,
+				});
+			}
+		}
+		dabs
+	}
 }
@@ -60,6 +60,32 @@ impl Subpath {
 		Self(groups)
 	}
 
+	/// Convert the legacy Subpath into `bezier_rs::Subpath`s, starting a new one after each close marker.
+	pub fn to_bezier_rs(&self) -> Vec<bezier_rs::Subpath<ManipulatorGroupId>> {
+		let position = |group: &ManipulatorGroup, manipulator_type: ManipulatorType| group.points[manipulator_type].as_ref().map(|point| point.position);
+
+		let mut subpaths = Vec::new();
+		let mut groups = Vec::new();
+		for group in self.manipulator_groups().iter() {
+			match position(group, ManipulatorType::Anchor) {
+				Some(anchor) => groups.push(bezier_rs::ManipulatorGroup::new(
+					anchor,
+					position(group, ManipulatorType::InHandle),
+					position(group, ManipulatorType::OutHandle),
+				)),
+				None if !groups.is_empty() => {
+					let closed = groups.len() > 1;
+					subpaths.push(bezier_rs::Subpath::new(core::mem::take(&mut groups), closed));
+				}
+				None => {}
+			}
+		}
+		if !groups.is_empty() {
+			subpaths.push(bezier_rs::Subpath::new(groups, false));
+		}
+		subpaths
+	}
+
 	// ** PRIMITIVE CONSTRUCTION **
 
 	/// constructs a rectangle with `p1` as the lower left and `p2` as the top right
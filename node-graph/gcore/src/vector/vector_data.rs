@@ -16,6 +16,20 @@ pub struct VectorData {
 	pub transform: DAffine2,
 	pub style: PathStyle,
 	pub mirror_angle: Vec<ManipulatorGroupId>,
+	/// Styles which replace parts of [`VectorData::style`] for ranges of the subpaths, sorted by their start and not overlapping.
+	#[cfg_attr(feature = "serde", serde(default))]
+	pub style_regions: Vec<StyleRegion>,
+}
+
+/// A style for the subpaths in `start..end`, such as the outlines of one run of rich text.
+///
+/// Its fill and stroke replace those of the [`VectorData::style`], except where it has no fill or stroke of its own.
+#[derive(Clone, Debug, PartialEq, Hash, DynAny)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StyleRegion {
+	pub start: usize,
+	pub end: usize,
+	pub style: PathStyle,
 }
 
 impl core::hash::Hash for VectorData {
@@ -24,6 +38,7 @@ impl core::hash::Hash for VectorData {
 		self.transform.to_cols_array().iter().for_each(|x| x.to_bits().hash(state));
 		self.style.hash(state);
 		self.mirror_angle.hash(state);
+		self.style_regions.hash(state);
 	}
 }
 
@@ -35,6 +50,7 @@ impl VectorData {
 			transform: DAffine2::IDENTITY,
 			style: PathStyle::new(Some(Stroke::new(Some(Color::BLACK), 0.)), super::style::Fill::None),
 			mirror_angle: Vec::new(),
+			style_regions: Vec::new(),
 		}
 	}
 
@@ -57,6 +73,33 @@ impl VectorData {
 		super::VectorData { subpaths, ..Self::empty() }
 	}
 
+	/// Splits the subpaths into consecutive groups which each share one style, so each group can be drawn as a single path.
+	pub fn styled_subpaths(&self) -> Vec<(&[bezier_rs::Subpath<ManipulatorGroupId>], PathStyle)> {
+		let mut groups = Vec::new();
+		let mut index = 0;
+		for region in &self.style_regions {
+			let (start, end) = (region.start.clamp(index, self.subpaths.len()), region.end.min(self.subpaths.len()));
+			if start > index {
+				groups.push((&self.subpaths[index..start], self.style.clone()));
+			}
+			if end > start {
+				let mut style = self.style.clone();
+				if region.style.fill().is_some() {
+					style.set_fill(region.style.fill().clone());
+				}
+				if let Some(stroke) = region.style.stroke() {
+					style.set_stroke(stroke);
+				}
+				groups.push((&self.subpaths[start..end], style));
+				index = end;
+			}
+		}
+		if index < self.subpaths.len() || groups.is_empty() {
+			groups.push((&self.subpaths[index..], self.style.clone()));
+		}
+		groups
+	}
+
 	/// Compute the bounding boxes of the subpaths without any transform
 	pub fn bounding_box(&self) -> Option<[DVec2; 2]> {
 		self.bounding_box_with_transform(DAffine2::IDENTITY)
@@ -4,7 +4,7 @@ pub use crate::imaginate_input::{ImaginateCache, ImaginateController, ImaginateM
 use crate::proto::{Any as DAny, FutureAny};
 
 use graphene_core::raster::brush_cache::BrushCache;
-use graphene_core::raster::{BlendMode, LuminanceCalculation};
+use graphene_core::raster::{BlendMode, ColorSpace, LuminanceCalculation};
 use graphene_core::{Color, Node, Type};
 
 use dyn_any::DynAny;
@@ -24,6 +24,7 @@ pub enum TaggedValue {
 	F64(f64),
 	Bool(bool),
 	DVec2(DVec2),
+	OptionalF64(Option<f64>),
 	OptionalDVec2(Option<DVec2>),
 	DAffine2(DAffine2),
 	Image(graphene_core::raster::Image<Color>),
@@ -34,6 +35,7 @@ pub enum TaggedValue {
 	RcSubpath(Arc<bezier_rs::Subpath<graphene_core::uuid::ManipulatorGroupId>>),
 	BlendMode(BlendMode),
 	LuminanceCalculation(LuminanceCalculation),
+	ColorSpace(ColorSpace),
 	ImaginateSamplingMethod(ImaginateSamplingMethod),
 	ImaginateMaskStartingFill(ImaginateMaskStartingFill),
 	ImaginateController(ImaginateController),
@@ -45,6 +47,10 @@ pub enum TaggedValue {
 	VecDVec2(Vec<DVec2>),
 	RedGreenBlue(graphene_core::raster::RedGreenBlue),
 	NoiseType(graphene_core::raster::NoiseType),
+	ImageChannel(graphene_core::raster::ImageChannel),
+	EdgeDetectionOperator(graphene_core::raster::EdgeDetectionOperator),
+	FloodFillMode(graphene_core::raster::FloodFillMode),
+	FloodFills(Vec<graphene_core::raster::FloodFill>),
 	RelativeAbsolute(graphene_core::raster::RelativeAbsolute),
 	SelectiveColorChoice(graphene_core::raster::SelectiveColorChoice),
 	LineCap(graphene_core::vector::style::LineCap),
@@ -56,13 +62,19 @@ pub enum TaggedValue {
 	OptionalColor(Option<graphene_core::raster::color::Color>),
 	ManipulatorGroupIds(Vec<graphene_core::uuid::ManipulatorGroupId>),
 	Font(graphene_core::text::Font),
+	TextAlign(graphene_core::text::TextAlign),
+	TextOverflow(graphene_core::text::TextOverflow),
+	TextPathSide(graphene_core::text::TextPathSide),
+	RichText(graphene_core::text::RichText),
 	BrushStrokes(Vec<graphene_core::vector::brush_stroke::BrushStroke>),
 	BrushCache(BrushCache),
+	BrushTipImages(graphene_core::vector::brush_stroke::BrushTipImages),
 	Segments(Vec<graphene_core::raster::ImageFrame<Color>>),
 	DocumentNode(DocumentNode),
 	GraphicGroup(graphene_core::GraphicGroup),
 	Artboard(graphene_core::Artboard),
 	Curve(graphene_core::raster::curve::Curve),
+	Palette(Vec<graphene_core::raster::PaletteColor>),
 	IVec2(glam::IVec2),
 	SurfaceFrame(graphene_core::SurfaceFrame),
 }
@@ -79,6 +91,7 @@ impl Hash for TaggedValue {
 			Self::F64(f) => f.to_bits().hash(state),
 			Self::Bool(b) => b.hash(state),
 			Self::DVec2(v) => v.to_array().iter().for_each(|x| x.to_bits().hash(state)),
+			Self::OptionalF64(x) => x.map(f64::to_bits).hash(state),
 			Self::OptionalDVec2(None) => 0.hash(state),
 			Self::OptionalDVec2(Some(v)) => {
 				1.hash(state);
@@ -92,6 +105,7 @@ impl Hash for TaggedValue {
 			Self::RcSubpath(s) => s.hash(state),
 			Self::BlendMode(b) => b.hash(state),
 			Self::LuminanceCalculation(l) => l.hash(state),
+			Self::ColorSpace(x) => x.hash(state),
 			Self::ImaginateSamplingMethod(m) => m.hash(state),
 			Self::ImaginateMaskStartingFill(f) => f.hash(state),
 			Self::ImaginateController(s) => s.hash(state),
@@ -104,6 +118,10 @@ impl Hash for TaggedValue {
 			Self::VecDVec2(vec_dvec2) => vec_dvec2.iter().for_each(|val| val.to_array().iter().for_each(|x| x.to_bits().hash(state))),
 			Self::RedGreenBlue(red_green_blue) => red_green_blue.hash(state),
 			Self::NoiseType(noise_type) => noise_type.hash(state),
+			Self::ImageChannel(image_channel) => image_channel.hash(state),
+			Self::EdgeDetectionOperator(edge_detection_operator) => edge_detection_operator.hash(state),
+			Self::FloodFillMode(flood_fill_mode) => flood_fill_mode.hash(state),
+			Self::FloodFills(fills) => fills.hash(state),
 			Self::RelativeAbsolute(relative_absolute) => relative_absolute.hash(state),
 			Self::SelectiveColorChoice(selective_color_choice) => selective_color_choice.hash(state),
 			Self::LineCap(line_cap) => line_cap.hash(state),
@@ -121,8 +139,13 @@ impl Hash for TaggedValue {
 			Self::OptionalColor(color) => color.hash(state),
 			Self::ManipulatorGroupIds(mirror) => mirror.hash(state),
 			Self::Font(font) => font.hash(state),
+			Self::TextAlign(align) => align.hash(state),
+			Self::TextOverflow(overflow) => overflow.hash(state),
+			Self::TextPathSide(side) => side.hash(state),
+			Self::RichText(rich_text) => rich_text.hash(state),
 			Self::BrushStrokes(brush_strokes) => brush_strokes.hash(state),
 			Self::BrushCache(brush_cache) => brush_cache.hash(state),
+			Self::BrushTipImages(tip_images) => tip_images.hash(state),
 			Self::Segments(segments) => {
 				for segment in segments {
 					segment.hash(state)
@@ -132,6 +155,7 @@ impl Hash for TaggedValue {
 			Self::GraphicGroup(graphic_group) => graphic_group.hash(state),
 			Self::Artboard(artboard) => artboard.hash(state),
 			Self::Curve(curve) => curve.hash(state),
+			Self::Palette(palette) => palette.hash(state),
 			Self::IVec2(v) => v.hash(state),
 			Self::SurfaceFrame(surface_id) => surface_id.hash(state),
 		}
@@ -149,6 +173,7 @@ impl<'a> TaggedValue {
 			TaggedValue::F64(x) => Box::new(x),
 			TaggedValue::Bool(x) => Box::new(x),
 			TaggedValue::DVec2(x) => Box::new(x),
+			TaggedValue::OptionalF64(x) => Box::new(x),
 			TaggedValue::OptionalDVec2(x) => Box::new(x),
 			TaggedValue::DAffine2(x) => Box::new(x),
 			TaggedValue::Image(x) => Box::new(x),
@@ -159,6 +184,7 @@ impl<'a> TaggedValue {
 			TaggedValue::RcSubpath(x) => Box::new(x),
 			TaggedValue::BlendMode(x) => Box::new(x),
 			TaggedValue::LuminanceCalculation(x) => Box::new(x),
+			TaggedValue::ColorSpace(x) => Box::new(x),
 			TaggedValue::ImaginateSamplingMethod(x) => Box::new(x),
 			TaggedValue::ImaginateMaskStartingFill(x) => Box::new(x),
 			TaggedValue::ImaginateController(x) => Box::new(x),
@@ -170,6 +196,10 @@ impl<'a> TaggedValue {
 			TaggedValue::VecDVec2(x) => Box::new(x),
 			TaggedValue::RedGreenBlue(x) => Box::new(x),
 			TaggedValue::NoiseType(x) => Box::new(x),
+			TaggedValue::ImageChannel(x) => Box::new(x),
+			TaggedValue::EdgeDetectionOperator(x) => Box::new(x),
+			TaggedValue::FloodFillMode(x) => Box::new(x),
+			TaggedValue::FloodFills(x) => Box::new(x),
 			TaggedValue::RelativeAbsolute(x) => Box::new(x),
 			TaggedValue::SelectiveColorChoice(x) => Box::new(x),
 			TaggedValue::LineCap(x) => Box::new(x),
@@ -181,13 +211,19 @@ impl<'a> TaggedValue {
 			TaggedValue::OptionalColor(x) => Box::new(x),
 			TaggedValue::ManipulatorGroupIds(x) => Box::new(x),
 			TaggedValue::Font(x) => Box::new(x),
+			TaggedValue::TextAlign(x) => Box::new(x),
+			TaggedValue::TextOverflow(x) => Box::new(x),
+			TaggedValue::TextPathSide(x) => Box::new(x),
+			TaggedValue::RichText(x) => Box::new(x),
 			TaggedValue::BrushStrokes(x) => Box::new(x),
 			TaggedValue::BrushCache(x) => Box::new(x),
+			TaggedValue::BrushTipImages(x) => Box::new(x),
 			TaggedValue::Segments(x) => Box::new(x),
 			TaggedValue::DocumentNode(x) => Box::new(x),
 			TaggedValue::GraphicGroup(x) => Box::new(x),
 			TaggedValue::Artboard(x) => Box::new(x),
 			TaggedValue::Curve(x) => Box::new(x),
+			TaggedValue::Palette(x) => Box::new(x),
 			TaggedValue::IVec2(x) => Box::new(x),
 			TaggedValue::SurfaceFrame(x) => Box::new(x),
 		}
@@ -218,6 +254,7 @@ impl<'a> TaggedValue {
 			TaggedValue::F64(_) => concrete!(f64),
 			TaggedValue::Bool(_) => concrete!(bool),
 			TaggedValue::DVec2(_) => concrete!(DVec2),
+			TaggedValue::OptionalF64(_) => concrete!(Option<f64>),
 			TaggedValue::OptionalDVec2(_) => concrete!(Option<DVec2>),
 			TaggedValue::Image(_) => concrete!(graphene_core::raster::Image<Color>),
 			TaggedValue::ImaginateCache(_) => concrete!(ImaginateCache),
@@ -232,6 +269,7 @@ impl<'a> TaggedValue {
 			TaggedValue::LayerPath(_) => concrete!(Option<Vec<u64>>),
 			TaggedValue::DAffine2(_) => concrete!(DAffine2),
 			TaggedValue::LuminanceCalculation(_) => concrete!(LuminanceCalculation),
+			TaggedValue::ColorSpace(_) => concrete!(ColorSpace),
 			TaggedValue::VectorData(_) => concrete!(graphene_core::vector::VectorData),
 			TaggedValue::Fill(_) => concrete!(graphene_core::vector::style::Fill),
 			TaggedValue::Stroke(_) => concrete!(graphene_core::vector::style::Stroke),
@@ -239,6 +277,10 @@ impl<'a> TaggedValue {
 			TaggedValue::VecDVec2(_) => concrete!(Vec<DVec2>),
 			TaggedValue::RedGreenBlue(_) => concrete!(graphene_core::raster::RedGreenBlue),
 			TaggedValue::NoiseType(_) => concrete!(graphene_core::raster::NoiseType),
+			TaggedValue::ImageChannel(_) => concrete!(graphene_core::raster::ImageChannel),
+			TaggedValue::EdgeDetectionOperator(_) => concrete!(graphene_core::raster::EdgeDetectionOperator),
+			TaggedValue::FloodFillMode(_) => concrete!(graphene_core::raster::FloodFillMode),
+			TaggedValue::FloodFills(_) => concrete!(Vec<graphene_core::raster::FloodFill>),
 			TaggedValue::RelativeAbsolute(_) => concrete!(graphene_core::raster::RelativeAbsolute),
 			TaggedValue::SelectiveColorChoice(_) => concrete!(graphene_core::raster::SelectiveColorChoice),
 			TaggedValue::LineCap(_) => concrete!(graphene_core::vector::style::LineCap),
@@ -250,13 +292,19 @@ impl<'a> TaggedValue {
 			TaggedValue::OptionalColor(_) => concrete!(Option<graphene_core::Color>),
 			TaggedValue::ManipulatorGroupIds(_) => concrete!(Vec<graphene_core::uuid::ManipulatorGroupId>),
 			TaggedValue::Font(_) => concrete!(graphene_core::text::Font),
+			TaggedValue::TextAlign(_) => concrete!(graphene_core::text::TextAlign),
+			TaggedValue::TextOverflow(_) => concrete!(graphene_core::text::TextOverflow),
+			TaggedValue::TextPathSide(_) => concrete!(graphene_core::text::TextPathSide),
+			TaggedValue::RichText(_) => concrete!(graphene_core::text::RichText),
 			TaggedValue::BrushStrokes(_) => concrete!(Vec<graphene_core::vector::brush_stroke::BrushStroke>),
 			TaggedValue::BrushCache(_) => concrete!(BrushCache),
+			TaggedValue::BrushTipImages(_) => concrete!(graphene_core::vector::brush_stroke::BrushTipImages),
 			TaggedValue::Segments(_) => concrete!(graphene_core::raster::IndexNode<Vec<graphene_core::raster::ImageFrame<Color>>>),
 			TaggedValue::DocumentNode(_) => concrete!(crate::document::DocumentNode),
 			TaggedValue::GraphicGroup(_) => concrete!(graphene_core::GraphicGroup),
 			TaggedValue::Artboard(_) => concrete!(graphene_core::Artboard),
 			TaggedValue::Curve(_) => concrete!(graphene_core::raster::curve::Curve),
+			TaggedValue::Palette(_) => concrete!(Vec<graphene_core::raster::PaletteColor>),
 			TaggedValue::IVec2(_) => concrete!(glam::IVec2),
 			TaggedValue::SurfaceFrame(_) => concrete!(graphene_core::SurfaceFrame),
 		}
@@ -274,6 +322,7 @@ impl<'a> TaggedValue {
 			x if x == TypeId::of::<f64>() => Ok(TaggedValue::F64(*downcast(input).unwrap())),
 			x if x == TypeId::of::<bool>() => Ok(TaggedValue::Bool(*downcast(input).unwrap())),
 			x if x == TypeId::of::<DVec2>() => Ok(TaggedValue::DVec2(*downcast(input).unwrap())),
+			x if x == TypeId::of::<Option<f64>>() => Ok(TaggedValue::OptionalF64(*downcast(input).unwrap())),
 			x if x == TypeId::of::<Option<DVec2>>() => Ok(TaggedValue::OptionalDVec2(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::raster::Image<Color>>() => Ok(TaggedValue::Image(*downcast(input).unwrap())),
 			x if x == TypeId::of::<ImaginateCache>() => Ok(TaggedValue::ImaginateCache(*downcast(input).unwrap())),
@@ -288,6 +337,7 @@ impl<'a> TaggedValue {
 			x if x == TypeId::of::<Option<Vec<u64>>>() => Ok(TaggedValue::LayerPath(*downcast(input).unwrap())),
 			x if x == TypeId::of::<DAffine2>() => Ok(TaggedValue::DAffine2(*downcast(input).unwrap())),
 			x if x == TypeId::of::<LuminanceCalculation>() => Ok(TaggedValue::LuminanceCalculation(*downcast(input).unwrap())),
+			x if x == TypeId::of::<ColorSpace>() => Ok(TaggedValue::ColorSpace(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::vector::VectorData>() => Ok(TaggedValue::VectorData(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::vector::style::Fill>() => Ok(TaggedValue::Fill(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::vector::style::Stroke>() => Ok(TaggedValue::Stroke(*downcast(input).unwrap())),
@@ -295,6 +345,10 @@ impl<'a> TaggedValue {
 			x if x == TypeId::of::<Vec<DVec2>>() => Ok(TaggedValue::VecDVec2(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::raster::RedGreenBlue>() => Ok(TaggedValue::RedGreenBlue(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::raster::NoiseType>() => Ok(TaggedValue::NoiseType(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::raster::ImageChannel>() => Ok(TaggedValue::ImageChannel(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::raster::EdgeDetectionOperator>() => Ok(TaggedValue::EdgeDetectionOperator(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::raster::FloodFillMode>() => Ok(TaggedValue::FloodFillMode(*downcast(input).unwrap())),
+			x if x == TypeId::of::<Vec<graphene_core::raster::FloodFill>>() => Ok(TaggedValue::FloodFills(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::raster::RelativeAbsolute>() => Ok(TaggedValue::RelativeAbsolute(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::raster::SelectiveColorChoice>() => Ok(TaggedValue::SelectiveColorChoice(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::vector::style::LineCap>() => Ok(TaggedValue::LineCap(*downcast(input).unwrap())),
@@ -306,12 +360,18 @@ impl<'a> TaggedValue {
 			x if x == TypeId::of::<Option<graphene_core::Color>>() => Ok(TaggedValue::OptionalColor(*downcast(input).unwrap())),
 			x if x == TypeId::of::<Vec<graphene_core::uuid::ManipulatorGroupId>>() => Ok(TaggedValue::ManipulatorGroupIds(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::text::Font>() => Ok(TaggedValue::Font(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::text::TextAlign>() => Ok(TaggedValue::TextAlign(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::text::TextOverflow>() => Ok(TaggedValue::TextOverflow(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::text::TextPathSide>() => Ok(TaggedValue::TextPathSide(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::text::RichText>() => Ok(TaggedValue::RichText(*downcast(input).unwrap())),
 			x if x == TypeId::of::<Vec<graphene_core::vector::brush_stroke::BrushStroke>>() => Ok(TaggedValue::BrushStrokes(*downcast(input).unwrap())),
 			x if x == TypeId::of::<BrushCache>() => Ok(TaggedValue::BrushCache(*downcast(input).unwrap())),
+			x if x == TypeId::of::<graphene_core::vector::brush_stroke::BrushTipImages>() => Ok(TaggedValue::BrushTipImages(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::raster::IndexNode<Vec<graphene_core::raster::ImageFrame<Color>>>>() => Ok(TaggedValue::Segments(*downcast(input).unwrap())),
 			x if x == TypeId::of::<crate::document::DocumentNode>() => Ok(TaggedValue::DocumentNode(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::GraphicGroup>() => Ok(TaggedValue::GraphicGroup(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::Artboard>() => Ok(TaggedValue::Artboard(*downcast(input).unwrap())),
+			x if x == TypeId::of::<Vec<graphene_core::raster::PaletteColor>>() => Ok(TaggedValue::Palette(*downcast(input).unwrap())),
 			x if x == TypeId::of::<glam::IVec2>() => Ok(TaggedValue::IVec2(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::SurfaceFrame>() => Ok(TaggedValue::SurfaceFrame(*downcast(input).unwrap())),
 			x if x == TypeId::of::<graphene_core::WasmSurfaceHandleFrame>() => {
@@ -51,6 +51,8 @@ async fn main() -> Result<(), Box<dyn Error>> {
 
 	let editor_api = WasmEditorApi {
 		image_frame: None,
+		time: 0.,
+		color_space: Default::default(),
 		font_cache: &FontCache::default(),
 		application_io: &application_io,
 		node_graph_message_sender: &UpdateLogger {},
@@ -203,6 +205,8 @@ mod test {
 		let executor = create_executor(document_string.to_string()).unwrap();
 		let editor_api = WasmEditorApi {
 			image_frame: None,
+			time: 0.,
+			color_space: Default::default(),
 			font_cache: &FontCache::default(),
 			application_io: &block_on(WasmApplicationIo::new()),
 			node_graph_message_sender: &UpdateLogger {},
@@ -219,6 +223,8 @@ mod test {
 		let executor = create_executor(document_string.to_string()).unwrap();
 		let editor_api = WasmEditorApi {
 			image_frame: None,
+			time: 0.,
+			color_space: Default::default(),
 			font_cache: &FontCache::default(),
 			application_io: &block_on(WasmApplicationIo::new()),
 			node_graph_message_sender: &UpdateLogger {},
@@ -0,0 +1,500 @@
+//! Encoding of the frames of an animated document into animated image files or a sequence of images.
+//!
+//! GIF and APNG use the `image` and `png` crates. WebP frames are written losslessly by the small VP8L writer below, which compresses them with prefix codes
+//! but without the backward references of a full encoder, since the `image` crate can only encode WebP through the C library, which isn't available on the web.
+
+use crate::archive::zip_files;
+use crate::export::{png_encoder, to_gamma, write_png_profile};
+
+use graphene_core::raster::icc::icc_profile;
+use graphene_core::raster::{ColorSpace, Image};
+use graphene_core::Color;
+
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+use std::io::Cursor;
+
+/// The most memory the frames of an animation may take up, since they're all kept until the animation is encoded.
+pub const MAX_ANIMATION_FRAME_BYTES: u64 = 1 << 30;
+
+/// The widest and tallest a lossless WebP image can be, since its dimensions are stored in 14 bits.
+const WEBP_MAX_DIMENSION: u32 = 1 << 14;
+
+/// The file formats the frames of an animation can be encoded into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnimationFormat {
+	/// An animated GIF, which is limited to 256 colors per frame and binary transparency.
+	Gif,
+	/// An animated PNG.
+	Apng,
+	/// An animated WebP with lossless frames.
+	WebP,
+	/// A ZIP archive of numbered PNG files.
+	PngSequence,
+}
+
+impl AnimationFormat {
+	pub fn mime(&self) -> &'static str {
+		match self {
+			AnimationFormat::Gif => "image/gif",
+			AnimationFormat::Apng => "image/apng",
+			AnimationFormat::WebP => "image/webp",
+			AnimationFormat::PngSequence => "application/zip",
+		}
+	}
+
+	/// Checks that an animation of this size can be kept in memory and encoded in this format, before its frames are rendered.
+	pub fn check_size(&self, width: u32, height: u32, frame_count: u32) -> Result<(), String> {
+		let frame_bytes = width as u64 * height as u64 * 4 * frame_count as u64;
+		if frame_bytes > MAX_ANIMATION_FRAME_BYTES {
+			return Err(format!(
+				"The frames of the animation would take up {} MB, more than the limit of {} MB. Export a smaller region, at a lower scale, or fewer frames.",
+				frame_bytes >> 20,
+				MAX_ANIMATION_FRAME_BYTES >> 20
+			));
+		}
+		if *self == AnimationFormat::WebP && (width > WEBP_MAX_DIMENSION || height > WEBP_MAX_DIMENSION) {
+			return Err(format!("WebP animations can be at most {WEBP_MAX_DIMENSION} pixels wide and tall, but this one is {width}x{height}."));
+		}
+		Ok(())
+	}
+}
+
+/// A rendered frame of an animation, kept as the 8-bit samples it is encoded with rather than linear colors, which take up four times the memory.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnimationFrame {
+	pub width: u32,
+	pub height: u32,
+	/// Interleaved RGBA samples with straight alpha, gamma encoded in the color space of the animation.
+	pub rgba: Vec<u8>,
+}
+
+impl AnimationFrame {
+	/// Quantizes an image with linear, premultiplied colors in the given color space.
+	pub fn new(image: &Image<Color>, color_space: ColorSpace) -> Self {
+		Self {
+			width: image.width,
+			height: image.height,
+			rgba: to_gamma(&image.data, color_space, |channel| (channel * u8::MAX as f32).round() as u8),
+		}
+	}
+}
+
+/// Encodes frames in the given color space into an animation which loops forever.
+///
+/// All frames must be the same size. Frames are shown for `1 / frame_rate` seconds each, rounded to what the format can represent.
+pub fn encode_animation(frames: Vec<AnimationFrame>, frame_rate: f64, format: AnimationFormat, color_space: ColorSpace) -> Result<Vec<u8>, String> {
+	let Some(first) = frames.first() else { return Err("The animation has no frames".to_string()) };
+	let (width, height) = (first.width, first.height);
+	if frames.iter().any(|frame| frame.width != width || frame.height != height) {
+		return Err("The frames of the animation differ in size".to_string());
+	}
+	format.check_size(width, height, frames.len() as u32)?;
+	let frame_duration_ms = (1000. / frame_rate).round().clamp(1., u16::MAX as f64) as u32;
+
+	let mut bytes = Vec::new();
+	match format {
+		AnimationFormat::Gif => {
+			// GIF has no way of describing its color space
+			let mut encoder = GifEncoder::new(&mut bytes);
+			encoder.set_repeat(Repeat::Infinite).map_err(|e| e.to_string())?;
+			let frames = frames.into_iter().map(|frame| {
+				let buffer = RgbaImage::from_raw(width, height, frame.rgba).expect("Frame data should match its size");
+				Frame::from_parts(buffer, 0, 0, Delay::from_numer_denom_ms(frame_duration_ms, 1))
+			});
+			encoder.encode_frames(frames).map_err(|e| e.to_string())?;
+		}
+		AnimationFormat::Apng => {
+			let mut encoder = png_encoder(Cursor::new(&mut bytes), width, height, png::BitDepth::Eight, color_space);
+			// Zero plays means the animation loops forever
+			encoder.set_animated(frames.len() as u32, 0).map_err(|e| e.to_string())?;
+			encoder.set_frame_delay(frame_duration_ms as u16, 1000).map_err(|e| e.to_string())?;
+			let mut writer = encoder.write_header().map_err(|e| e.to_string())?;
+			write_png_profile(&mut writer, color_space)?;
+			for frame in &frames {
+				writer.write_image_data(&frame.rgba).map_err(|e| e.to_string())?;
+			}
+			writer.finish().map_err(|e| e.to_string())?;
+		}
+		AnimationFormat::WebP => {
+			let profile = (color_space != ColorSpace::Srgb).then(|| icc_profile(color_space));
+			bytes = encode_animated_webp(&frames, width, height, frame_duration_ms, profile.as_deref());
+		}
+		AnimationFormat::PngSequence => {
+			// Numbers are padded so the files sort in order
+			let digits = frames.len().to_string().len().max(4);
+			let files = frames
+				.into_iter()
+				.enumerate()
+				.map(|(index, frame)| {
+					let mut png = Vec::new();
+					let mut writer = png_encoder(&mut png, width, height, png::BitDepth::Eight, color_space).write_header().map_err(|e| e.to_string())?;
+					write_png_profile(&mut writer, color_space)?;
+					writer.write_image_data(&frame.rgba).map_err(|e| e.to_string())?;
+					writer.finish().map_err(|e| e.to_string())?;
+					Ok((format!("frame_{:0digits$}.png", index + 1), png))
+				})
+				.collect::<Result<Vec<_>, String>>()?;
+			bytes = zip_files(&files);
+		}
+	}
+	Ok(bytes)
+}
+
+/// Writes an animated WebP file from frames no wider or taller than [`WEBP_MAX_DIMENSION`].
+fn encode_animated_webp(frames: &[AnimationFrame], width: u32, height: u32, frame_duration_ms: u32, icc_profile: Option<&[u8]>) -> Vec<u8> {
+	let mut chunks = Vec::new();
+
+	// The extended format header, with flags for the ICC profile, alpha and animation
+	let mut vp8x = Vec::with_capacity(10);
+	vp8x.push(if icc_profile.is_some() { 0b0010_0000 } else { 0 } | 0b0001_0000 | 0b0000_0010);
+	vp8x.extend([0; 3]);
+	vp8x.extend(&(width - 1).to_le_bytes()[..3]);
+	vp8x.extend(&(height - 1).to_le_bytes()[..3]);
+	write_riff_chunk(&mut chunks, b"VP8X", &vp8x);
+
+	if let Some(profile) = icc_profile {
+		write_riff_chunk(&mut chunks, b"ICCP", profile);
+	}
+
+	// A transparent background and a loop count of zero, which loops forever
+	write_riff_chunk(&mut chunks, b"ANIM", &[0, 0, 0, 0, 0, 0]);
+
+	for frame in frames {
+		let mut anmf = Vec::new();
+		// The frame covers the whole canvas, so its offset is zero
+		anmf.extend([0; 6]);
+		anmf.extend(&(width - 1).to_le_bytes()[..3]);
+		anmf.extend(&(height - 1).to_le_bytes()[..3]);
+		anmf.extend(&frame_duration_ms.to_le_bytes()[..3]);
+		// Frames replace the previous one rather than being blended onto it, and aren't disposed
+		anmf.push(0b0000_0010);
+		write_riff_chunk(&mut anmf, b"VP8L", &encode_vp8l(&frame.rgba, width, height));
+		write_riff_chunk(&mut chunks, b"ANMF", &anmf);
+	}
+
+	let mut file = b"RIFF".to_vec();
+	file.extend((4 + chunks.len() as u32).to_le_bytes());
+	file.extend(b"WEBP");
+	file.extend(chunks);
+	file
+}
+
+fn write_riff_chunk(output: &mut Vec<u8>, fourcc: &[u8; 4], data: &[u8]) {
+	output.extend(fourcc);
+	output.extend((data.len() as u32).to_le_bytes());
+	output.extend(data);
+	// Chunks are padded to an even size
+	if data.len() % 2 == 1 {
+		output.push(0);
+	}
+}
+
+/// The order in which the lengths of the code length code are stored.
+const CODE_LENGTH_CODE_ORDER: [usize; 19] = [17, 18, 0, 1, 2, 3, 4, 5, 16, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15];
+
+/// Writes a lossless VP8L bitstream which stores each pixel as literals, with the subtract green transform and prefix codes built from how often each value occurs in the frame.
+///
+/// There are no backward references or color cache, so repeated areas compress less than with a full encoder, but flat colors and smooth gradients take only a few bits per pixel.
+fn encode_vp8l(rgba: &[u8], width: u32, height: u32) -> Vec<u8> {
+	let mut writer = BitWriter::default();
+	writer.write(0x2f, 8);
+	writer.write(width - 1, 14);
+	writer.write(height - 1, 14);
+	let alpha_is_used = rgba.chunks_exact(4).any(|pixel| pixel[3] != u8::MAX);
+	writer.write(alpha_is_used as u32, 1);
+	// Version
+	writer.write(0, 3);
+
+	// The subtract green transform, which is the only one, followed by no color cache and a single group of prefix codes for the whole image
+	writer.write(1, 1);
+	writer.write(2, 2);
+	writer.write(0, 1);
+	writer.write(0, 1);
+	writer.write(0, 1);
+
+	// Red and blue are stored as their difference from green, which is small for grays and many natural colors
+	let pixels: Vec<[u8; 4]> = rgba
+		.chunks_exact(4)
+		.map(|pixel| [pixel[1], pixel[0].wrapping_sub(pixel[1]), pixel[2].wrapping_sub(pixel[1]), pixel[3]])
+		.collect();
+
+	// The green code's alphabet also has the lengths of backward references, which are unused
+	let mut histograms = [vec![0u32; 256 + 24], vec![0; 256], vec![0; 256], vec![0; 256]];
+	for pixel in &pixels {
+		for (histogram, &value) in histograms.iter_mut().zip(pixel) {
+			histogram[value as usize] += 1;
+		}
+	}
+	let codes: Vec<_> = histograms.iter().map(|histogram| PrefixCode::new(histogram, 15)).collect();
+	for code in &codes {
+		code.write_definition(&mut writer);
+	}
+	// The distance code is a simple code with a single symbol, since there are no backward references
+	writer.write(1, 1);
+	writer.write(0, 1);
+	writer.write(0, 1);
+	writer.write(0, 1);
+
+	for pixel in &pixels {
+		for (code, &value) in codes.iter().zip(pixel) {
+			code.write_symbol(&mut writer, value as usize);
+		}
+	}
+
+	writer.finish()
+}
+
+/// A canonical prefix code, where each symbol's code is given by its length and the codes of the symbols before it.
+struct PrefixCode {
+	lengths: Vec<u32>,
+	codes: Vec<u32>,
+	/// Whether only one symbol is used, which the decoder reads without taking any bits.
+	single_symbol: bool,
+}
+
+impl PrefixCode {
+	/// Builds the code with the shortest total length for how often each symbol occurs, with no code longer than `max_length` bits.
+	fn new(histogram: &[u32], max_length: u32) -> Self {
+		let lengths = limited_code_lengths(histogram, max_length);
+
+		let mut length_counts = vec![0; max_length as usize + 1];
+		for &length in lengths.iter().filter(|&&length| length > 0) {
+			length_counts[length as usize] += 1;
+		}
+		let mut next_code = vec![0; max_length as usize + 1];
+		let mut code = 0;
+		for length in 1..=max_length as usize {
+			code = (code + length_counts[length - 1]) << 1;
+			next_code[length] = code;
+		}
+		let codes = lengths
+			.iter()
+			.map(|&length| {
+				let code = next_code[length as usize];
+				next_code[length as usize] += 1;
+				code
+			})
+			.collect();
+
+		let single_symbol = lengths.iter().filter(|&&length| length > 0).count() == 1;
+		Self { lengths, codes, single_symbol }
+	}
+
+	fn write_symbol(&self, writer: &mut BitWriter, symbol: usize) {
+		if self.single_symbol {
+			return;
+		}
+		// Prefix codes are read starting from their most significant bit
+		let length = self.lengths[symbol];
+		writer.write(self.codes[symbol].reverse_bits() >> (32 - length), length);
+	}
+
+	/// Writes the lengths of the code, compressed with a code of their own.
+	fn write_definition(&self, writer: &mut BitWriter) {
+		let mut used = self.lengths.iter().enumerate().filter(|(_, &length)| length > 0).map(|(symbol, _)| symbol as u32);
+		if self.single_symbol || self.lengths.iter().all(|&length| length == 0) {
+			// A simple code with a single 8-bit symbol
+			writer.write(1, 1);
+			writer.write(0, 1);
+			writer.write(1, 1);
+			writer.write(used.next().unwrap_or(0), 8);
+			return;
+		}
+
+		// Each length is written as itself, except for runs of zeros which are written as 17 with 3 extra bits or 18 with 7 extra bits
+		let mut tokens = Vec::new();
+		let mut index = 0;
+		while index < self.lengths.len() {
+			let zeros = self.lengths[index..].iter().take_while(|&&length| length == 0).count();
+			let (symbol, run, extra, extra_bits) = match zeros {
+				11.. => (18, zeros.min(138), zeros.min(138) - 11, 7),
+				3..=10 => (17, zeros, zeros - 3, 3),
+				_ => (self.lengths[index], 1, 0, 0),
+			};
+			tokens.push((symbol, extra as u32, extra_bits));
+			index += run;
+		}
+
+		let mut histogram = [0u32; 19];
+		for &(symbol, _, _) in &tokens {
+			histogram[symbol as usize] += 1;
+		}
+		let code_length_code = PrefixCode::new(&histogram, 7);
+		let stored = CODE_LENGTH_CODE_ORDER
+			.iter()
+			.rposition(|&symbol| code_length_code.lengths[symbol] > 0)
+			.map_or(0, |position| position + 1)
+			.max(4);
+
+		// A normal code, whose lengths are given for the whole alphabet
+		writer.write(0, 1);
+		writer.write(stored as u32 - 4, 4);
+		for &symbol in &CODE_LENGTH_CODE_ORDER[..stored] {
+			writer.write(code_length_code.lengths[symbol], 3);
+		}
+		writer.write(0, 1);
+		for (symbol, extra, extra_bits) in tokens {
+			code_length_code.write_symbol(writer, symbol as usize);
+			writer.write(extra, extra_bits);
+		}
+	}
+}
+
+/// The lengths of a Huffman code for the histogram, made no longer than `max_length` by raising the smallest counts until it fits.
+///
+/// A single used symbol gets a length of 1, since a code needs at least one bit even though the decoder reads none for it.
+fn limited_code_lengths(histogram: &[u32], max_length: u32) -> Vec<u32> {
+	let mut minimum_count = 1;
+	loop {
+		let weights: Vec<u64> = histogram.iter().map(|&count| if count == 0 { 0 } else { count.max(minimum_count) as u64 }).collect();
+		let lengths = huffman_code_lengths(&weights);
+		if lengths.iter().all(|&length| length <= max_length) {
+			return lengths;
+		}
+		minimum_count *= 2;
+	}
+}
+
+/// The depths of the symbols in a Huffman tree built by repeatedly joining the two least frequent nodes, or 0 for unused symbols.
+fn huffman_code_lengths(weights: &[u64]) -> Vec<u32> {
+	let mut heap: BinaryHeap<_> = weights
+		.iter()
+		.enumerate()
+		.filter(|(_, &weight)| weight > 0)
+		.map(|(symbol, &weight)| Reverse((weight, symbol)))
+		.collect();
+	if heap.len() == 1 {
+		return weights.iter().map(|&weight| (weight > 0) as u32).collect();
+	}
+
+	// Nodes after the symbols are the joined ones, so each node's parent comes after it
+	let mut parents = vec![None; weights.len()];
+	while let (Some(Reverse((first_weight, first))), Some(Reverse((second_weight, second)))) = (heap.pop(), heap.pop()) {
+		let node = parents.len();
+		parents.push(None);
+		parents[first] = Some(node);
+		parents[second] = Some(node);
+		heap.push(Reverse((first_weight + second_weight, node)));
+	}
+
+	let mut depths = vec![0; parents.len()];
+	for node in (0..parents.len()).rev() {
+		depths[node] = parents[node].map_or(0, |parent| depths[parent] + 1);
+	}
+	depths.truncate(weights.len());
+	depths
+}
+
+/// Packs values into bytes starting from the least significant bit, as VP8L is read.
+#[derive(Default)]
+struct BitWriter {
+	bytes: Vec<u8>,
+	buffer: u64,
+	bits: u32,
+}
+
+impl BitWriter {
+	fn write(&mut self, value: u32, bits: u32) {
+		self.buffer |= (value as u64) << self.bits;
+		self.bits += bits;
+		while self.bits >= 8 {
+			self.bytes.push(self.buffer as u8);
+			self.buffer >>= 8;
+			self.bits -= 8;
+		}
+	}
+
+	fn finish(mut self) -> Vec<u8> {
+		if self.bits > 0 {
+			self.bytes.push(self.buffer as u8);
+		}
+		self.bytes
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use image::AnimationDecoder;
+
+	fn frames() -> Vec<AnimationFrame> {
+		[Color::from_rgbaf32_unchecked(1., 0., 0., 1.), Color::from_rgbaf32_unchecked(0., 0., 0.5, 0.5), Color::TRANSPARENT]
+			.into_iter()
+			.map(|color| {
+				let image = Image {
+					width: 3,
+					height: 2,
+					data: vec![color; 6],
+				};
+				AnimationFrame::new(&image, ColorSpace::Srgb)
+			})
+			.collect()
+	}
+
+	fn decoded_frames<'a>(decoder: impl AnimationDecoder<'a>) -> Vec<Frame> {
+		decoder.into_frames().collect_frames().unwrap()
+	}
+
+	#[test]
+	fn webp_frames_decode_losslessly() {
+		let bytes = encode_animation(frames(), 10., AnimationFormat::WebP, ColorSpace::Srgb).unwrap();
+		let decoded = decoded_frames(image::codecs::webp::WebPDecoder::new(Cursor::new(bytes)).unwrap());
+		assert_eq!(decoded.len(), 3);
+		assert_eq!(decoded[0].buffer().get_pixel(2, 1).0, [255, 0, 0, 255]);
+		assert_eq!(decoded[1].buffer().get_pixel(0, 0).0, [0, 0, 255, 128]);
+		assert_eq!(decoded[2].buffer().get_pixel(1, 1).0[3], 0);
+		assert_eq!(decoded[0].delay().numer_denom_ms(), (100, 1));
+	}
+
+	#[test]
+	fn webp_frames_are_compressed() {
+		// A gray ramp, whose red and blue are the same as its green and whose green takes 64 values
+		let rgba: Vec<u8> = (0..64 * 64).flat_map(|index| [(index % 64 * 4) as u8; 3].into_iter().chain([u8::MAX])).collect();
+		let frame = AnimationFrame { width: 64, height: 64, rgba };
+		let bytes = encode_animation(vec![frame.clone()], 10., AnimationFormat::WebP, ColorSpace::Srgb).unwrap();
+		assert!(bytes.len() < frame.rgba.len() / 4);
+
+		let decoded = decoded_frames(image::codecs::webp::WebPDecoder::new(Cursor::new(bytes)).unwrap());
+		assert_eq!(decoded[0].buffer().as_raw(), &frame.rgba);
+	}
+
+	#[test]
+	fn apng_has_every_frame() {
+		let bytes = encode_animation(frames(), 25., AnimationFormat::Apng, ColorSpace::Srgb).unwrap();
+		let decoder = image::codecs::png::PngDecoder::new(Cursor::new(bytes)).unwrap();
+		assert!(decoder.is_apng());
+		let decoded = decoded_frames(decoder.apng());
+		assert_eq!(decoded.len(), 3);
+		assert_eq!(decoded[0].buffer().get_pixel(0, 0).0, [255, 0, 0, 255]);
+		assert_eq!(decoded[1].buffer().get_pixel(0, 0).0, [0, 0, 255, 128]);
+	}
+
+	#[test]
+	fn gif_has_every_frame() {
+		let bytes = encode_animation(frames(), 10., AnimationFormat::Gif, ColorSpace::Srgb).unwrap();
+		let decoded = decoded_frames(image::codecs::gif::GifDecoder::new(Cursor::new(bytes)).unwrap());
+		assert_eq!(decoded.len(), 3);
+		assert_eq!(decoded[0].buffer().get_pixel(0, 0).0, [255, 0, 0, 255]);
+		assert_eq!(decoded[2].buffer().get_pixel(0, 0).0[3], 0);
+	}
+
+	#[test]
+	fn mismatched_frame_sizes_are_rejected() {
+		let mut frames = frames();
+		frames[1].width = 2;
+		frames[1].rgba.truncate(16);
+		assert!(encode_animation(frames, 10., AnimationFormat::Apng, ColorSpace::Srgb).is_err());
+		assert!(encode_animation(Vec::new(), 10., AnimationFormat::Gif, ColorSpace::Srgb).is_err());
+	}
+
+	#[test]
+	fn oversized_animations_are_rejected() {
+		assert!(AnimationFormat::WebP.check_size(16384, 16, 1).is_ok());
+		assert!(AnimationFormat::WebP.check_size(16385, 16, 1).is_err());
+		assert!(AnimationFormat::Apng.check_size(16385, 16, 1).is_ok());
+		assert!(AnimationFormat::Gif.check_size(4096, 4096, 100).is_err());
+	}
+}
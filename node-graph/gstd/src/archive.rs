@@ -0,0 +1,151 @@
+//! Writing ZIP archives, for exports which produce several files but can only be downloaded as one, and reading them for formats such as
+//! OpenRaster which are stored as ZIP archives.
+
+/// Writes files into a ZIP archive without compressing them, since the files exported are images which are already compressed.
+pub fn zip_files(files: &[(String, Vec<u8>)]) -> Vec<u8> {
+	let mut archive = Vec::new();
+	let mut central_directory = Vec::new();
+
+	for (name, data) in files {
+		let offset = archive.len() as u32;
+		let crc = crc32fast::hash(data);
+
+		// The fields shared by the local header and the central directory entry, starting from the version needed to extract
+		let mut header = Vec::new();
+		header.extend(20_u16.to_le_bytes());
+		// The names are UTF-8
+		header.extend((1_u16 << 11).to_le_bytes());
+		// Stored without compression
+		header.extend(0_u16.to_le_bytes());
+		// The modification time and date of 1980-01-01 00:00, the earliest that can be represented
+		header.extend(0_u16.to_le_bytes());
+		header.extend(((1 << 5) | 1_u16).to_le_bytes());
+		header.extend(crc.to_le_bytes());
+		header.extend((data.len() as u32).to_le_bytes());
+		header.extend((data.len() as u32).to_le_bytes());
+		header.extend((name.len() as u16).to_le_bytes());
+		// No extra field
+		header.extend(0_u16.to_le_bytes());
+
+		archive.extend(0x04034b50_u32.to_le_bytes());
+		archive.extend(&header);
+		archive.extend(name.as_bytes());
+		archive.extend(data);
+
+		central_directory.extend(0x02014b50_u32.to_le_bytes());
+		// Made by version 2.0
+		central_directory.extend(20_u16.to_le_bytes());
+		central_directory.extend(&header);
+		// No comment, disk number 0, no internal or external attributes
+		central_directory.extend([0; 10]);
+		central_directory.extend(offset.to_le_bytes());
+		central_directory.extend(name.as_bytes());
+	}
+
+	let central_directory_offset = archive.len() as u32;
+	archive.extend(&central_directory);
+
+	archive.extend(0x06054b50_u32.to_le_bytes());
+	// Everything is on the first disk
+	archive.extend([0; 4]);
+	archive.extend((files.len() as u16).to_le_bytes());
+	archive.extend((files.len() as u16).to_le_bytes());
+	archive.extend((central_directory.len() as u32).to_le_bytes());
+	archive.extend(central_directory_offset.to_le_bytes());
+	// No comment
+	archive.extend(0_u16.to_le_bytes());
+
+	archive
+}
+
+/// Reads the files of a ZIP archive, which may be stored or compressed with deflate.
+pub fn unzip_files(archive: &[u8]) -> Result<Vec<(String, Vec<u8>)>, String> {
+	let truncated = || "The archive ends unexpectedly".to_string();
+	let u16_at = |offset: usize| archive.get(offset..offset + 2).map(|bytes| u16::from_le_bytes([bytes[0], bytes[1]]) as usize).ok_or_else(truncated);
+	let u32_at = |offset: usize| {
+		archive
+			.get(offset..offset + 4)
+			.map(|bytes| u32::from_le_bytes(bytes.try_into().unwrap()) as usize)
+			.ok_or_else(truncated)
+	};
+
+	// The end of central directory record is followed only by a comment of at most 65535 bytes
+	let end = (0..archive.len().saturating_sub(21))
+		.rev()
+		.take(u16::MAX as usize + 1)
+		.find(|&offset| archive[offset..].starts_with(&0x06054b50_u32.to_le_bytes()))
+		.ok_or("The file isn't a ZIP archive")?;
+	let count = u16_at(end + 10)?;
+	let mut entry = u32_at(end + 16)?;
+
+	let mut files = Vec::with_capacity(count);
+	for _ in 0..count {
+		if u32_at(entry)? != 0x02014b50 {
+			return Err("The archive's central directory is malformed".to_string());
+		}
+		let method = u16_at(entry + 10)?;
+		let compressed_size = u32_at(entry + 20)?;
+		let uncompressed_size = u32_at(entry + 24)?;
+		let name_length = u16_at(entry + 28)?;
+		let name = archive.get(entry + 46..entry + 46 + name_length).ok_or_else(truncated)?;
+		let name = String::from_utf8_lossy(name).into_owned();
+		let local_header = u32_at(entry + 42)?;
+		entry += 46 + name_length + u16_at(entry + 30)? + u16_at(entry + 32)?;
+
+		// The local header repeats the name and has its own extra field before the data
+		let start = local_header + 30 + u16_at(local_header + 26)? + u16_at(local_header + 28)?;
+		let data = archive.get(start..start + compressed_size).ok_or_else(truncated)?;
+		let data = match method {
+			0 => data.to_vec(),
+			// The declared size limits the output, so a small entry can't expand into more memory than it claims to need
+			8 => miniz_oxide::inflate::decompress_to_vec_with_limit(data, uncompressed_size).map_err(|error| format!("{name} could not be decompressed: {:?}", error.status))?,
+			_ => return Err(format!("{name} uses an unsupported compression method")),
+		};
+		files.push((name, data));
+	}
+	Ok(files)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	#[test]
+	fn archive_structure() {
+		let archive = zip_files(&[("a.txt".to_string(), b"hello".to_vec()), ("b.txt".to_string(), Vec::new())]);
+		assert_eq!(&archive[0..4], &[0x50, 0x4b, 0x03, 0x04]);
+		// The stored data follows the 30 byte local header and the name
+		assert_eq!(&archive[35..40], b"hello");
+		assert_eq!(u32::from_le_bytes(archive[14..18].try_into().unwrap()), crc32fast::hash(b"hello"));
+
+		let end = &archive[archive.len() - 22..];
+		assert_eq!(&end[0..4], &[0x50, 0x4b, 0x05, 0x06]);
+		assert_eq!(u16::from_le_bytes([end[10], end[11]]), 2);
+		let central_directory_offset = u32::from_le_bytes(end[16..20].try_into().unwrap()) as usize;
+		assert_eq!(&archive[central_directory_offset..central_directory_offset + 4], &[0x50, 0x4b, 0x01, 0x02]);
+	}
+
+	#[test]
+	fn archives_round_trip() {
+		let files = vec![("stack.xml".to_string(), b"<image/>".to_vec()), ("data/empty.png".to_string(), Vec::new())];
+		assert_eq!(unzip_files(&zip_files(&files)), Ok(files));
+		assert!(unzip_files(b"not an archive").is_err());
+	}
+
+	#[test]
+	fn compressed_entries_are_limited_to_their_declared_size() {
+		let text = b"hello hello hello hello";
+		let compressed = miniz_oxide::deflate::compress_to_vec(text, 6);
+		let mut archive = zip_files(&[("hello.txt".to_string(), compressed)]);
+
+		// Marks the entry in the central directory as deflated, with the given uncompressed size
+		let entry = u32::from_le_bytes(archive[archive.len() - 6..archive.len() - 2].try_into().unwrap()) as usize;
+		let mut declare = |size: u32| {
+			archive[entry + 10..entry + 12].copy_from_slice(&8_u16.to_le_bytes());
+			archive[entry + 24..entry + 28].copy_from_slice(&size.to_le_bytes());
+			archive.clone()
+		};
+		assert_eq!(unzip_files(&declare(text.len() as u32)), Ok(vec![("hello.txt".to_string(), text.to_vec())]));
+		assert!(unzip_files(&declare(5)).is_err());
+	}
+}
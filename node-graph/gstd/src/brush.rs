@@ -3,11 +3,11 @@ use crate::raster::{blend_image_closure, BlendImageTupleNode, EmptyImageNode, Ex
 use graphene_core::raster::adjustments::blend_colors;
 use graphene_core::raster::bbox::{AxisAlignedBbox, Bbox};
 use graphene_core::raster::brush_cache::BrushCache;
-use graphene_core::raster::{Alpha, Color, Image, ImageFrame, Pixel, Sample};
+use graphene_core::raster::{sample_bilinear, Alpha, Color, Image, ImageFrame, Pixel, Sample};
 use graphene_core::raster::{BlendMode, BlendNode};
 use graphene_core::transform::{Transform, TransformMut};
 use graphene_core::value::{ClonedNode, CopiedNode, OnceCellNode, ValueNode};
-use graphene_core::vector::brush_stroke::{BrushStroke, BrushStyle};
+use graphene_core::vector::brush_stroke::{BrushStroke, BrushStyle, BrushTip, BrushTipImages};
 use graphene_core::vector::VectorData;
 use graphene_core::Node;
 use node_macro::node_fn;
@@ -221,6 +221,152 @@ pub fn create_brush_texture(brush_style: &BrushStyle) -> Image<Color> {
 	blend_executor.eval((blank_texture, stamp)).image
 }
 
+fn cached_brush_texture(cache: &BrushCache, brush_style: &BrushStyle) -> Image<Color> {
+	cache.get_cached_brush(brush_style).unwrap_or_else(|| {
+		let tex = create_brush_texture(brush_style);
+		cache.store_brush(brush_style.clone(), tex.clone());
+		tex
+	})
+}
+
+/// Draws the dabs of a stroke whose dabs differ from each other, skipping the stamps which have already been drawn.
+///
+/// Round tips are cached as white masks with a quantized diameter and hardness, image and shape tips are turned into a mask once per stroke.
+/// Every dab samples its mask through its own transform and tints it with its color and flow. An image tip whose image is missing from
+/// the tip images is drawn as a round tip.
+fn blit_dabs(mut target: ImageFrame<Color>, stroke: &BrushStroke, skip: usize, tips: &BrushTipImages, cache: &BrushCache, blend_mode: BlendMode) -> ImageFrame<Color> {
+	let tip_mask = match &stroke.style.tip {
+		BrushTip::Round => None,
+		BrushTip::Image(id) => tips.get(*id).map(image_tip_mask),
+		BrushTip::Shape(polygons) => Some(shape_tip_mask(polygons, stroke.style.diameter.ceil().max(1.) as u32)),
+	};
+	let blend_params = BlendNode::new(CopiedNode::new(blend_mode), CopiedNode::new(100.));
+	let layer_to_target = DAffine2::from_scale(DVec2::new(target.image.width as f64, target.image.height as f64)) * target.transform.inverse();
+
+	for dab in stroke.compute_dabs(skip) {
+		let round_mask;
+		let mask = match &tip_mask {
+			Some(mask) => mask,
+			None => {
+				let mask_style = BrushStyle {
+					color: Color::WHITE,
+					diameter: ((dab.style.diameter * 4.).round() / 4.).max(1.),
+					hardness: dab.style.hardness.round().clamp(0., 100.),
+					flow: 100.,
+					..Default::default()
+				};
+				round_mask = cached_brush_texture(cache, &mask_style);
+				&round_mask
+			}
+		};
+		if mask.width == 0 || mask.height == 0 {
+			continue;
+		}
+
+		let mask_size = DVec2::new(mask.width as f64, mask.height as f64);
+		let scale = dab.style.diameter / mask_size.max_element();
+		let mask_to_target = layer_to_target * DAffine2::from_scale_angle_translation(DVec2::splat(scale), dab.angle, dab.position) * DAffine2::from_translation(-mask_size / 2.);
+		let tint = dab.style.color.apply_opacity((dab.style.flow / 100.) as f32);
+		blit_mask(&mut target, mask, mask_to_target, tint, &blend_params);
+	}
+	target
+}
+
+/// Blends the mask, tinted with the color, into the target through the transform from mask pixels to target pixels.
+fn blit_mask<BlendFn>(target: &mut ImageFrame<Color>, mask: &Image<Color>, mask_to_target: DAffine2, tint: Color, blend_mode: &BlendFn)
+where
+	BlendFn: for<'any_input> Node<'any_input, (Color, Color), Output = Color>,
+{
+	if mask_to_target.matrix2.determinant().abs() <= f64::EPSILON {
+		return;
+	}
+
+	let mask_size = DVec2::new(mask.width as f64, mask.height as f64);
+	let target_size = DVec2::new(target.image.width as f64, target.image.height as f64);
+	let corners = [DVec2::ZERO, DVec2::new(mask_size.x, 0.), mask_size, DVec2::new(0., mask_size.y)].map(|corner| mask_to_target.transform_point2(corner));
+	let start = corners
+		.iter()
+		.fold(DVec2::splat(f64::INFINITY), |start, &corner| start.min(corner))
+		.floor()
+		.clamp(DVec2::ZERO, target_size);
+	let stop = corners
+		.iter()
+		.fold(DVec2::splat(f64::NEG_INFINITY), |stop, &corner| stop.max(corner))
+		.ceil()
+		.clamp(DVec2::ZERO, target_size);
+
+	let target_to_normalized_mask = DAffine2::from_scale(1. / mask_size) * mask_to_target.inverse();
+	for y in start.y as u32..stop.y as u32 {
+		for x in start.x as u32..stop.x as u32 {
+			let position = target_to_normalized_mask.transform_point2(DVec2::new(x as f64 + 0.5, y as f64 + 0.5));
+			let coverage = sample_bilinear(mask, position).a();
+			if coverage <= 0. {
+				continue;
+			}
+
+			let dst_pixel = &mut target.image.data[(y * target.image.width + x) as usize];
+			*dst_pixel = blend_mode.eval((tint.apply_opacity(coverage), *dst_pixel));
+		}
+	}
+}
+
+/// Converts an image tip into a white mask, with the image alpha multiplied by its inverted luminance as coverage.
+fn image_tip_mask(image: &Image<Color>) -> Image<Color> {
+	let data = image
+		.data
+		.iter()
+		.map(|color| {
+			let coverage = color.a() * (1. - color.to_unassociated_alpha().luminance_srgb()).clamp(0., 1.);
+			Color::WHITE.apply_opacity(coverage)
+		})
+		.collect();
+	Image {
+		width: image.width,
+		height: image.height,
+		data,
+	}
+}
+
+/// Fills the normalized polygons of a shape tip into a square white mask with the given size, anti-aliased with four samples per pixel row.
+fn shape_tip_mask(polygons: &[Vec<DVec2>], size: u32) -> Image<Color> {
+	const SUBSAMPLES: u32 = 4;
+
+	let to_pixels = |point: DVec2| (point + DVec2::splat(0.5)) * size as f64;
+	let edges: Vec<_> = polygons
+		.iter()
+		.flat_map(|polygon| polygon.iter().zip(polygon.iter().cycle().skip(1)).map(|(&start, &end)| (to_pixels(start), to_pixels(end))))
+		.collect();
+
+	let mut coverage = vec![0_f32; (size * size) as usize];
+	let mut crossings = Vec::new();
+	for y in 0..size {
+		for subsample in 0..SUBSAMPLES {
+			let scanline = y as f64 + (subsample as f64 + 0.5) / SUBSAMPLES as f64;
+
+			crossings.clear();
+			crossings.extend(
+				edges
+					.iter()
+					.filter(|(start, end)| (start.y <= scanline) != (end.y <= scanline))
+					.map(|(start, end)| start.x + (scanline - start.y) / (end.y - start.y) * (end.x - start.x)),
+			);
+			crossings.sort_by(|a, b| a.total_cmp(b));
+
+			// Even-odd rule: the spans between pairs of crossings are inside
+			for span in crossings.chunks_exact(2) {
+				let (span_start, span_end) = (span[0].clamp(0., size as f64), span[1].clamp(0., size as f64));
+				for x in span_start.floor() as u32..(span_end.ceil() as u32).min(size) {
+					let overlap = span_end.min(x as f64 + 1.) - span_start.max(x as f64);
+					coverage[(y * size + x) as usize] += overlap.max(0.) as f32 / SUBSAMPLES as f32;
+				}
+			}
+		}
+	}
+
+	let data = coverage.into_iter().map(|coverage| Color::WHITE.apply_opacity(coverage.min(1.))).collect();
+	Image { width: size, height: size, data }
+}
+
 macro_rules! inline_blend_funcs {
 	($bg:ident, $fg:ident, $blend_mode:ident, $opacity:ident, [$($mode:path,)*]) => {
 		match std::hint::black_box($blend_mode) {
@@ -281,14 +427,15 @@ pub fn blend_with_mode(background: ImageFrame<Color>, foreground: ImageFrame<Col
 	)
 }
 
-pub struct BrushNode<Bounds, Strokes, Cache> {
+pub struct BrushNode<Bounds, Strokes, Cache, Tips> {
 	bounds: Bounds,
 	strokes: Strokes,
 	cache: Cache,
+	tips: Tips,
 }
 
 #[node_macro::node_fn(BrushNode)]
-async fn brush(image: ImageFrame<Color>, bounds: ImageFrame<Color>, strokes: Vec<BrushStroke>, cache: BrushCache) -> ImageFrame<Color> {
+async fn brush(image: ImageFrame<Color>, bounds: ImageFrame<Color>, strokes: Vec<BrushStroke>, cache: BrushCache, tips: BrushTipImages) -> ImageFrame<Color> {
 	let stroke_bbox = strokes.iter().map(|s| s.bounding_box()).reduce(|a, b| a.union(&b)).unwrap_or(AxisAlignedBbox::ZERO);
 	let image_bbox = Bbox::from_transform(image.transform).to_axis_aligned_bbox();
 	let bbox = if image_bbox.size().length() < 0.1 { stroke_bbox } else { stroke_bbox.union(&image_bbox) };
@@ -307,14 +454,6 @@ async fn brush(image: ImageFrame<Color>, bounds: ImageFrame<Color>, strokes: Vec
 	let mut actual_image = ExtendImageToBoundsNode::new(OnceCellNode::new(background_bounds)).eval(brush_plan.background);
 	let final_stroke_idx = brush_plan.strokes.len().saturating_sub(1);
 	for (idx, stroke) in brush_plan.strokes.into_iter().enumerate() {
-		// Create brush texture.
-		// TODO: apply rotation from layer to stamp for non-rotationally-symmetric brushes.
-		let brush_texture = cache.get_cached_brush(&stroke.style).unwrap_or_else(|| {
-			let tex = create_brush_texture(&stroke.style);
-			cache.store_brush(stroke.style.clone(), tex.clone());
-			tex
-		});
-
 		// Compute transformation from stroke texture space into layer space, and create the stroke texture.
 		let skip = if idx == 0 { brush_plan.first_stroke_point_skip } else { 0 };
 		let positions: Vec<_> = stroke.compute_blit_points().into_iter().skip(skip).collect();
@@ -331,15 +470,23 @@ async fn brush(image: ImageFrame<Color>, bounds: ImageFrame<Color>, strokes: Vec
 			let stroke_origin_in_layer = bbox.start - snap_offset - DVec2::splat(stroke.style.diameter / 2.0);
 			let stroke_to_layer = DAffine2::from_translation(stroke_origin_in_layer) * DAffine2::from_scale(stroke_size);
 
-			let normal_blend = BlendNode::new(CopiedNode::new(BlendMode::Normal), CopiedNode::new(100.));
-			let blit_node = BlitNode::new(ClonedNode::new(brush_texture), ClonedNode::new(positions), ClonedNode::new(normal_blend));
 			let blit_target = if idx == 0 {
 				let target = core::mem::take(&mut brush_plan.first_stroke_texture);
 				ExtendImageToBoundsNode::new(CopiedNode::new(stroke_to_layer)).eval(target)
 			} else {
 				EmptyImageNode::new(CopiedNode::new(Color::TRANSPARENT)).eval(stroke_to_layer)
 			};
-			blit_node.eval(blit_target)
+
+			if !stroke.style.has_uniform_dabs() {
+				blit_dabs(blit_target, &stroke, skip, &tips, &cache, BlendMode::Normal)
+			} else {
+				// Create brush texture.
+				// TODO: apply rotation from layer to stamp for non-rotationally-symmetric brushes.
+				let brush_texture = cached_brush_texture(&cache, &stroke.style);
+				let normal_blend = BlendNode::new(CopiedNode::new(BlendMode::Normal), CopiedNode::new(100.));
+				let blit_node = BlitNode::new(ClonedNode::new(brush_texture), ClonedNode::new(positions), ClonedNode::new(normal_blend));
+				blit_node.eval(blit_target)
+			}
 		};
 
 		// Cache image before doing final blend, and store final stroke texture.
@@ -360,11 +507,12 @@ async fn brush(image: ImageFrame<Color>, bounds: ImageFrame<Color>, strokes: Vec
 		let mut erase_restore_mask = opaque_image;
 
 		for stroke in erase_restore_strokes {
-			let brush_texture = cache.get_cached_brush(&stroke.style).unwrap_or_else(|| {
-				let tex = create_brush_texture(&stroke.style);
-				cache.store_brush(stroke.style.clone(), tex.clone());
-				tex
-			});
+			if !stroke.style.has_uniform_dabs() {
+				erase_restore_mask = blit_dabs(erase_restore_mask, &stroke, 0, &tips, &cache, stroke.style.blend_mode);
+				continue;
+			}
+
+			let brush_texture = cached_brush_texture(&cache, &stroke.style);
 			let positions: Vec<_> = stroke.compute_blit_points().into_iter().collect();
 
 			match stroke.style.blend_mode {
@@ -403,6 +551,7 @@ mod test {
 	use graphene_core::structural::Then;
 	use graphene_core::transform::{Transform, TransformMut};
 	use graphene_core::value::{ClonedNode, ValueNode};
+	use graphene_core::vector::brush_stroke::{BrushDynamics, BrushDynamicsCurve, BrushDynamicsInput, BrushInputSample, BrushRotation, BrushScattering, StampStyle};
 
 	use glam::DAffine2;
 
@@ -454,4 +603,175 @@ mod test {
 		assert_eq!(final_image.image.width, 30);
 		drop(final_image);
 	}
+
+	fn pressure_stroke(dynamics: BrushDynamics) -> BrushStroke {
+		let sample = |x: f64, pressure: f64, timestamp: f64| BrushInputSample {
+			position: DVec2::new(x, 0.),
+			pressure,
+			timestamp,
+			..Default::default()
+		};
+		BrushStroke {
+			style: BrushStyle {
+				diameter: 10.,
+				spacing: 50.,
+				dynamics,
+				..Default::default()
+			},
+			trace: vec![sample(0., 0., 0.), sample(20., 1., 10.)],
+		}
+	}
+
+	#[test]
+	fn test_blit_stamps_interpolate_input() {
+		let stroke = pressure_stroke(BrushDynamics::default());
+		let stamps = stroke.compute_blit_stamps();
+		assert_eq!(stamps.len(), 5);
+		assert_eq!(stamps.iter().map(|stamp| stamp.position).collect::<Vec<_>>(), stroke.compute_blit_points());
+		assert_eq!(stamps[2].position, DVec2::new(10., 0.));
+		assert!((stamps[2].pressure - 0.5).abs() < 1e-9);
+		assert_eq!(stamps[0].velocity, 0.);
+		assert!((stamps[4].velocity - 2.).abs() < 1e-9);
+	}
+
+	#[test]
+	fn test_pressure_dynamics() {
+		let dynamics = BrushDynamics {
+			diameter: BrushDynamicsCurve {
+				minimum: 20.,
+				..BrushDynamicsCurve::new(BrushDynamicsInput::Pressure)
+			},
+			..Default::default()
+		};
+		let stroke = pressure_stroke(dynamics);
+		let stamps = stroke.compute_blit_stamps();
+		let diameters: Vec<_> = stamps.iter().enumerate().map(|(index, stamp)| stroke.stamp_style(stamp, index).diameter).collect();
+		assert!((diameters[0] - 2.).abs() < 1e-9);
+		assert!((diameters[4] - 10.).abs() < 1e-9);
+		assert!(diameters.windows(2).all(|pair| pair[0] < pair[1]));
+
+		// Without dynamics every stamp has the style of the stroke
+		let stroke = pressure_stroke(BrushDynamics::default());
+		assert!(stroke
+			.compute_blit_stamps()
+			.iter()
+			.enumerate()
+			.all(|(index, stamp)| stroke.stamp_style(stamp, index) == StampStyle::from(&stroke.style)));
+	}
+
+	#[test]
+	fn test_color_jitter_is_reproducible() {
+		let dynamics = BrushDynamics {
+			color_jitter: 50.,
+			..Default::default()
+		};
+		let mut stroke = pressure_stroke(dynamics);
+		stroke.style.color = Color::from_rgbaf32_unchecked(0.5, 0.2, 0.2, 1.);
+		let stamps = stroke.compute_blit_stamps();
+		let colors: Vec<_> = stamps.iter().enumerate().map(|(index, stamp)| stroke.stamp_style(stamp, index).color).collect();
+		assert!(colors.windows(2).any(|pair| pair[0] != pair[1]));
+
+		// Extending the stroke keeps the colors of the existing stamps
+		stroke.trace.push(BrushInputSample::from_position(DVec2::new(40., 0.)));
+		let extended_stamps = stroke.compute_blit_stamps();
+		assert_eq!(&extended_stamps[..stamps.len()], &stamps[..]);
+		let extended_colors: Vec<_> = extended_stamps.iter().enumerate().map(|(index, stamp)| stroke.stamp_style(stamp, index).color).collect();
+		assert_eq!(&extended_colors[..colors.len()], &colors[..]);
+	}
+
+	#[test]
+	fn test_dab_scattering() {
+		let mut stroke = pressure_stroke(BrushDynamics::default());
+		stroke.style.scattering = BrushScattering {
+			rotation: BrushRotation::FollowStroke,
+			angle: 90.,
+			scatter: 50.,
+			count: 3,
+			size_jitter: 50.,
+			seed: 7,
+		};
+		let stamps = stroke.compute_blit_stamps();
+		let dabs = stroke.compute_dabs(0);
+		assert_eq!(dabs.len(), stamps.len() * 3);
+		for (index, dab) in dabs.iter().enumerate() {
+			let stamp = &stamps[index / 3];
+			assert!(dab.position.distance(stamp.position) <= 5. + 1e-9);
+			assert!(dab.style.diameter >= 5. && dab.style.diameter <= 10.);
+			// The stroke travels along the x axis
+			assert!((dab.angle - core::f64::consts::FRAC_PI_2).abs() < 1e-9);
+		}
+		assert_eq!(stroke.compute_dabs(2), dabs[6..]);
+
+		let mut reseeded = stroke.clone();
+		reseeded.style.scattering.seed = 8;
+		assert_ne!(reseeded.compute_dabs(0), dabs);
+	}
+
+	#[test]
+	fn test_shape_tip_mask() {
+		let square = BrushTip::shape(vec![vec![DVec2::new(0., 0.), DVec2::new(4., 0.), DVec2::new(4., 4.), DVec2::new(0., 4.)]]);
+		let BrushTip::Shape(polygons) = square else { panic!("The square has an area") };
+		let mask = shape_tip_mask(&polygons, 8);
+		assert!(mask.data.iter().all(|texel| (texel.a() - 1.).abs() < 1e-6));
+
+		// The lower right half of the square, filled with the even-odd rule
+		let triangle = vec![vec![DVec2::new(0.5, -0.5), DVec2::new(0.5, 0.5), DVec2::new(-0.5, 0.5)]];
+		let mask = shape_tip_mask(&triangle, 8);
+		let area: f32 = mask.data.iter().map(|texel| texel.a()).sum();
+		assert!((area - 32.).abs() < 0.5);
+		assert_eq!(mask.data[0].a(), 0.);
+		assert!((mask.data[63].a() - 1.).abs() < 1e-6);
+
+		assert_eq!(
+			BrushTip::shape(vec![vec![DVec2::ZERO, DVec2::X, DVec2::new(2., 0.)]]),
+			BrushTip::Shape(vec![vec![DVec2::new(-0.5, 0.), DVec2::ZERO, DVec2::new(0.5, 0.)]])
+		);
+		assert_eq!(BrushTip::shape(vec![vec![DVec2::ZERO, DVec2::ZERO, DVec2::ZERO]]), BrushTip::Round);
+	}
+
+	#[test]
+	fn test_image_tip_mask() {
+		let image = Image {
+			width: 3,
+			height: 1,
+			data: vec![Color::BLACK, Color::WHITE, Color::TRANSPARENT],
+		};
+		let mask = image_tip_mask(&image);
+		assert_eq!(mask.data.iter().map(|texel| texel.a()).collect::<Vec<_>>(), vec![1., 0., 0.]);
+	}
+
+	#[test]
+	fn test_tip_images_are_held_once() {
+		let image = Image::new(2, 2, Color::BLACK);
+		let mut imported = BrushTipImages::default();
+		let id = imported.insert(image.clone());
+		assert_eq!(imported.insert(image.clone()), id);
+		assert_ne!(imported.insert(Image::new(1, 1, Color::WHITE)), id);
+
+		// Only the image of the tip is copied over to the strokes' images
+		let mut used = BrushTipImages::default();
+		used.copy_tip(&BrushTip::Image(id), &imported);
+		used.copy_tip(&BrushTip::Round, &imported);
+		let mut expected = BrushTipImages::default();
+		expected.insert(image);
+		assert_eq!(used, expected);
+	}
+
+	#[test]
+	fn test_blit_rotated_mask() {
+		let mut target = ImageFrame {
+			image: Image::new(4, 4, Color::TRANSPARENT),
+			transform: DAffine2::from_scale(DVec2::splat(4.)),
+		};
+		let mask = Image::new(2, 1, Color::WHITE);
+		let blend_params = BlendNode::new(CopiedNode::new(BlendMode::Normal), CopiedNode::new(100.));
+
+		// A 2x1 mask scaled up to 4x2 and turned upright in the center of the target
+		let mask_to_target = DAffine2::from_scale_angle_translation(DVec2::splat(2.), core::f64::consts::FRAC_PI_2, DVec2::splat(2.)) * DAffine2::from_translation(-DVec2::new(1., 0.5));
+		blit_mask(&mut target, &mask, mask_to_target, Color::BLACK, &blend_params);
+
+		let covered: Vec<_> = target.image.data.iter().map(|pixel| pixel.a() > 0.5).collect();
+		let expected: Vec<_> = (0..16).map(|index| (1..3).contains(&(index % 4))).collect();
+		assert_eq!(covered, expected);
+	}
 }
@@ -0,0 +1,386 @@
+//! Encoding of rendered documents into image files, and decoding of image files into linear colors.
+//!
+//! Encoding happens here rather than in the host so the web frontend, the desktop app and tests all produce the same bytes for the same render.
+//! Integer formats store gamma encoded colors with straight alpha as their specifications require, while OpenEXR stores the linear colors with
+//! premultiplied alpha unchanged, so it round-trips the document's colors without any loss of precision.
+
+use graphene_core::raster::color_space::convert_with;
+use graphene_core::raster::icc::{icc_profile, IccProfile};
+use graphene_core::raster::{ColorSpace, Image};
+use graphene_core::Color;
+
+use exr::math::Vec2;
+use exr::meta::attribute::Chromaticities;
+use exr::prelude::{SpecificChannels, WritableImage};
+use image::codecs::jpeg::JpegDecoder;
+use image::codecs::png::PngDecoder;
+use image::{DynamicImage, ImageBuffer, ImageDecoder, ImageFormat, ImageOutputFormat, Rgba};
+use std::borrow::Cow;
+use std::io::{Cursor, Seek, Write};
+use tiff::encoder::{colortype, TiffEncoder, TiffValue};
+use tiff::tags::Tag;
+
+/// The quality used for JPEG exports, on the `image` crate's scale of 1 to 100.
+pub const JPEG_QUALITY: u8 = 92;
+
+/// The file formats a rendered [`Image`] can be encoded into.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RasterFormat {
+	Png,
+	/// A PNG with 16 bits per channel.
+	Png16,
+	/// JPEG has no alpha channel, so transparent pixels are flattened onto white.
+	Jpeg,
+	Tiff,
+	/// A TIFF with 16 bits per channel.
+	Tiff16,
+	/// An OpenEXR file with 32-bit floating point channels.
+	OpenExr,
+}
+
+impl RasterFormat {
+	pub fn mime(&self) -> &'static str {
+		match self {
+			RasterFormat::Png | RasterFormat::Png16 => "image/png",
+			RasterFormat::Jpeg => "image/jpeg",
+			RasterFormat::Tiff | RasterFormat::Tiff16 => "image/tiff",
+			RasterFormat::OpenExr => "image/x-exr",
+		}
+	}
+}
+
+/// Encodes an image with linear, premultiplied colors in the given color space into the bytes of an image file.
+///
+/// PNG, JPEG and TIFF files are tagged with the color space, as an sRGB chunk or an embedded ICC profile, and OpenEXR files with the
+/// chromaticities of its primaries and white point.
+pub fn encode_image(mut image: Image<Color>, format: RasterFormat, color_space: ColorSpace) -> Result<Vec<u8>, String> {
+	let mut bytes = Vec::new();
+	let mut cursor = Cursor::new(&mut bytes);
+	let (width, height) = (image.width, image.height);
+
+	match format {
+		RasterFormat::Png | RasterFormat::Png16 => {
+			let data = match format {
+				RasterFormat::Png => to_gamma(&image.data, color_space, |channel| (channel * u8::MAX as f32).round() as u8),
+				_ => to_gamma(&image.data, color_space, |channel| (channel * u16::MAX as f32).round() as u16)
+					.into_iter()
+					.flat_map(u16::to_be_bytes)
+					.collect(),
+			};
+
+			let depth = if format == RasterFormat::Png { png::BitDepth::Eight } else { png::BitDepth::Sixteen };
+			let mut writer = png_encoder(&mut cursor, width, height, depth, color_space).write_header().map_err(|e| e.to_string())?;
+			write_png_profile(&mut writer, color_space)?;
+			writer.write_image_data(&data).map_err(|e| e.to_string())?;
+			writer.finish().map_err(|e| e.to_string())?;
+		}
+		RasterFormat::Jpeg => {
+			image.data.iter_mut().for_each(|color| *color = Color::WHITE.alpha_blend(*color));
+
+			let data = to_gamma(&image.data, color_space, |channel| (channel * u8::MAX as f32).round() as u8);
+			let buffer: ImageBuffer<Rgba<u8>, _> = ImageBuffer::from_raw(width, height, data).ok_or_else(|| "Invalid image size".to_string())?;
+			DynamicImage::ImageRgba8(buffer)
+				.to_rgb8()
+				.write_to(&mut cursor, ImageOutputFormat::Jpeg(JPEG_QUALITY))
+				.map_err(|e| e.to_string())?;
+
+			if color_space != ColorSpace::Srgb {
+				embed_jpeg_icc_profile(&mut bytes, &icc_profile(color_space));
+			}
+		}
+		RasterFormat::Tiff => {
+			let data = to_gamma(&image.data, color_space, |channel| (channel * u8::MAX as f32).round() as u8);
+			write_tiff::<colortype::RGBA8>(&mut cursor, width, height, &data, color_space).map_err(|e| e.to_string())?;
+		}
+		RasterFormat::Tiff16 => {
+			let data = to_gamma(&image.data, color_space, |channel| (channel * u16::MAX as f32).round() as u16);
+			write_tiff::<colortype::RGBA16>(&mut cursor, width, height, &data, color_space).map_err(|e| e.to_string())?;
+		}
+		RasterFormat::OpenExr => {
+			let pixel = |position: Vec2<usize>| {
+				let color = image.data[position.flat_index_for_size(Vec2(width as usize, height as usize))];
+				(color.r(), color.g(), color.b(), color.a())
+			};
+			let mut exr_image = exr::prelude::Image::from_channels((width as usize, height as usize), SpecificChannels::rgba(pixel));
+			exr_image.attributes.chromaticities = Some(exr_chromaticities(color_space));
+			exr_image.write().to_buffered(&mut cursor).map_err(|e| e.to_string())?;
+		}
+	}
+	Ok(bytes)
+}
+
+/// Creates a PNG encoder for RGBA images tagged as sRGB, the color spaces of other images are written after the header by [`write_png_profile`].
+pub(crate) fn png_encoder<W: Write>(w: W, width: u32, height: u32, depth: png::BitDepth, color_space: ColorSpace) -> png::Encoder<'static, W> {
+	// Alpha in PNG is always straight
+	let mut encoder = png::Encoder::new(w, width, height);
+	encoder.set_color(png::ColorType::Rgba);
+	encoder.set_depth(depth);
+	if color_space == ColorSpace::Srgb {
+		encoder.set_source_srgb(png::SrgbRenderingIntent::Perceptual);
+	}
+	encoder
+}
+
+/// Embeds the ICC profile of color spaces other than sRGB, which has to come before the image data.
+pub(crate) fn write_png_profile<W: Write>(writer: &mut png::Writer<W>, color_space: ColorSpace) -> Result<(), String> {
+	if color_space == ColorSpace::Srgb {
+		return Ok(());
+	}
+	// The profile name, a null separator, the compression method and the zlib compressed profile
+	let mut chunk = format!("{color_space}\0\0").into_bytes();
+	chunk.extend(miniz_oxide::deflate::compress_to_vec_zlib(&icc_profile(color_space), 6));
+	writer.write_chunk(png::chunk::ChunkType(*b"iCCP"), &chunk).map_err(|e| e.to_string())
+}
+
+/// The TIFF tag holding an embedded ICC profile.
+const TIFF_ICC_PROFILE: u16 = 34675;
+
+/// The bytes of an ICC profile, which TIFF files store with the undefined field type rather than as a list of bytes.
+struct TiffIccProfile<'a>(&'a [u8]);
+
+impl TiffValue for TiffIccProfile<'_> {
+	const BYTE_LEN: u8 = 1;
+	const FIELD_TYPE: tiff::tags::Type = tiff::tags::Type::UNDEFINED;
+
+	fn count(&self) -> usize {
+		self.0.len()
+	}
+
+	fn data(&self) -> Cow<[u8]> {
+		Cow::Borrowed(self.0)
+	}
+}
+
+/// Writes an RGBA TIFF with straight alpha, embedding the ICC profile of color spaces other than sRGB.
+fn write_tiff<C: colortype::ColorType>(w: impl Write + Seek, width: u32, height: u32, data: &[C::Inner], color_space: ColorSpace) -> tiff::TiffResult<()>
+where
+	[C::Inner]: TiffValue,
+{
+	let mut encoder = TiffEncoder::new(w)?;
+	let mut image = encoder.new_image::<C>(width, height)?;
+	// Marks the fourth sample as unassociated alpha, without this tag its meaning is unspecified
+	image.encoder().write_tag(Tag::ExtraSamples, 2_u16)?;
+	if color_space != ColorSpace::Srgb {
+		image.encoder().write_tag(Tag::Unknown(TIFF_ICC_PROFILE), TiffIccProfile(&icc_profile(color_space)))?;
+	}
+	image.write_data(data)
+}
+
+/// The chromaticities of the primaries and white point of a color space, as stored in the header of OpenEXR files.
+fn exr_chromaticities(color_space: ColorSpace) -> Chromaticities {
+	let point = |(x, y): (f64, f64)| Vec2(x as f32, y as f32);
+	let [red, green, blue] = color_space.primaries().map(point);
+	let white = point(color_space.white_point());
+	Chromaticities { red, green, blue, white }
+}
+
+/// Reads the color space of an OpenEXR file from the chromaticities in its header. Files without them, or with chromaticities which aren't
+/// those of one of the supported color spaces, are assumed to be in linear sRGB.
+fn exr_color_space(data: &[u8]) -> ColorSpace {
+	let Ok(meta_data) = exr::meta::MetaData::read_from_buffered(Cursor::new(data), false) else {
+		return ColorSpace::Srgb;
+	};
+	let Some(chromaticities) = meta_data.headers.first().and_then(|header| header.shared_attributes.chromaticities) else {
+		return ColorSpace::Srgb;
+	};
+
+	let close = |a: Vec2<f32>, b: Vec2<f32>| (a.0 - b.0).abs() < 1e-3 && (a.1 - b.1).abs() < 1e-3;
+	ColorSpace::list()
+		.into_iter()
+		.find(|&color_space| {
+			let expected = exr_chromaticities(color_space);
+			close(chromaticities.red, expected.red) && close(chromaticities.green, expected.green) && close(chromaticities.blue, expected.blue) && close(chromaticities.white, expected.white)
+		})
+		.unwrap_or(ColorSpace::Srgb)
+}
+
+/// Converts linear colors with premultiplied alpha into the interleaved samples of gamma encoded colors with straight alpha.
+pub(crate) fn to_gamma<T>(data: &[Color], color_space: ColorSpace, quantize: impl Fn(f32) -> T) -> Vec<T> {
+	let quantize = |channel: f32| quantize(channel.clamp(0., 1.));
+	data.iter()
+		.flat_map(|color| {
+			let straight = color.to_unassociated_alpha();
+			let [red, green, blue] = [straight.r(), straight.g(), straight.b()].map(|channel| quantize(color_space.encode(channel)));
+			[red, green, blue, quantize(color.a())]
+		})
+		.collect()
+}
+
+/// Inserts an ICC profile as an APP2 segment directly after the start of image marker of a JPEG file.
+fn embed_jpeg_icc_profile(jpeg: &mut Vec<u8>, profile: &[u8]) {
+	// A segment holds at most 65535 bytes including its length and identifier, so larger profiles would need to be split
+	const IDENTIFIER: &[u8] = b"ICC_PROFILE\0";
+	let length = 2 + IDENTIFIER.len() + 2 + profile.len();
+	let Ok(length) = u16::try_from(length) else { return };
+
+	let mut segment = vec![0xFF, 0xE2];
+	segment.extend(length.to_be_bytes());
+	segment.extend(IDENTIFIER);
+	// The sequence number of this chunk and the total number of chunks
+	segment.extend([1, 1]);
+	segment.extend(profile);
+	jpeg.splice(2..2, segment);
+}
+
+/// Decodes an image file into linear colors with premultiplied alpha in the given working space, keeping the full precision of high bit depth
+/// and floating point formats.
+///
+/// Integer formats are converted from the RGB profile embedded in the file, or from sRGB when there is none or it isn't a matrix/TRC profile.
+pub fn decode_image(data: &[u8], color_space: ColorSpace) -> Result<Image<Color>, image::ImageError> {
+	let profile = embedded_icc_profile(data).and_then(|profile| IccProfile::parse(&profile));
+	let image = image::load_from_memory(data)?;
+	let (width, height) = (image.width(), image.height());
+
+	// Floating point formats already hold linear colors with premultiplied alpha, in the color space given by the header of OpenEXR files
+	let float_color_space = match image::guess_format(data) {
+		Ok(ImageFormat::OpenExr) => exr_color_space(data),
+		_ => ColorSpace::Srgb,
+	};
+	let float_conversion = (float_color_space != color_space).then(|| float_color_space.conversion_to(color_space));
+	let from_float_space = |color: Color| float_conversion.as_ref().map_or(color, |conversion| convert_with(conversion, color));
+
+	let data = match (image, profile) {
+		(image @ (DynamicImage::ImageRgb32F(_) | DynamicImage::ImageRgba32F(_)), _) => image
+			.into_rgba32f()
+			.pixels()
+			.map(|pixel| from_float_space(Color::from_rgbaf32_unchecked(pixel[0], pixel[1], pixel[2], pixel[3])))
+			.collect(),
+		// Integer formats hold gamma encoded colors with straight alpha, which are normalized without rounding to 8 bits
+		(image, profile) => {
+			let to_linear = gamma_decoder(profile.as_ref(), color_space);
+			image.into_rgba32f().pixels().map(|pixel| to_linear(pixel.0)).collect()
+		}
+	};
+	Ok(Image { width, height, data })
+}
+
+/// Converts gamma encoded `[red, green, blue, alpha]` samples with straight alpha in the given profile into linear colors with premultiplied alpha
+/// in the working space. Samples without a usable profile are assumed to be sRGB.
+pub(crate) fn gamma_decoder(profile: Option<&IccProfile>, color_space: ColorSpace) -> impl Fn([f32; 4]) -> Color + '_ {
+	let conversion = profile.map_or_else(|| ColorSpace::Srgb.conversion_to(color_space), |profile| profile.conversion_to(color_space));
+	move |[red, green, blue, alpha]| match profile {
+		Some(profile) => profile.to_linear(&conversion, red, green, blue, alpha),
+		None => {
+			let linear = Color::from_rgbaf32_unchecked(red, green, blue, alpha).to_linear_srgb();
+			let color = Color::from_unassociated_alpha(linear.r(), linear.g(), linear.b(), linear.a());
+			// Skipping the identity conversion keeps sRGB colors exact
+			match color_space {
+				ColorSpace::Srgb => color,
+				_ => convert_with(&conversion, color),
+			}
+		}
+	}
+}
+
+/// Reads the ICC profile embedded in the formats which can carry one.
+fn embedded_icc_profile(data: &[u8]) -> Option<Vec<u8>> {
+	match image::guess_format(data).ok()? {
+		ImageFormat::Png => PngDecoder::new(Cursor::new(data)).ok()?.icc_profile(),
+		ImageFormat::Jpeg => JpegDecoder::new(Cursor::new(data)).ok()?.icc_profile(),
+		ImageFormat::Tiff => tiff::decoder::Decoder::new(Cursor::new(data)).ok()?.get_tag_u8_vec(Tag::Unknown(TIFF_ICC_PROFILE)).ok(),
+		_ => None,
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn test_image() -> Image<Color> {
+		let data = vec![
+			Color::from_rgbaf32_unchecked(1., 0., 0., 1.),
+			Color::TRANSPARENT,
+			Color::from_rgbaf32_unchecked(0., 0., 0.5, 0.5),
+			Color::BLACK,
+		];
+		Image { width: 2, height: 2, data }
+	}
+
+	#[test]
+	fn png_round_trips_pixels() {
+		let bytes = encode_image(test_image(), RasterFormat::Png, ColorSpace::Srgb).unwrap();
+		let decoded = image::load_from_memory(&bytes).unwrap().to_rgba8();
+		assert_eq!(decoded.dimensions(), (2, 2));
+		assert_eq!(decoded.get_pixel(0, 0).0, [255, 0, 0, 255]);
+		assert_eq!(decoded.get_pixel(1, 0).0[3], 0);
+		assert_eq!(decoded.get_pixel(0, 1).0, [0, 0, 255, 128]);
+		assert_eq!(decoded.get_pixel(1, 1).0, [0, 0, 0, 255]);
+	}
+
+	#[test]
+	fn jpeg_flattens_transparency_onto_white() {
+		let transparent = Image {
+			width: 8,
+			height: 8,
+			data: vec![Color::TRANSPARENT; 64],
+		};
+		let bytes = encode_image(transparent, RasterFormat::Jpeg, ColorSpace::Srgb).unwrap();
+		let decoded = image::load_from_memory(&bytes).unwrap().to_rgb8();
+		assert_eq!(decoded.dimensions(), (8, 8));
+		assert!(decoded.pixels().all(|pixel| pixel.0.iter().all(|&channel| channel > 250)));
+	}
+
+	#[test]
+	fn png16_keeps_precision() {
+		let gradient = Image {
+			width: 256,
+			height: 1,
+			data: (0..256).map(|x| Color::from_rgbaf32_unchecked(x as f32 / 65535., 0.5, 0., 1.)).collect(),
+		};
+		let bytes = encode_image(gradient.clone(), RasterFormat::Png16, ColorSpace::Srgb).unwrap();
+		let decoded = decode_image(&bytes, ColorSpace::Srgb).unwrap();
+		assert_eq!(decoded.width, 256);
+		// Distinct dark values would collapse into a handful of levels with 8 bits per channel
+		let levels = decoded.data.iter().map(|color| (color.r() * 65535.).round() as u32).collect::<std::collections::HashSet<_>>();
+		assert!(levels.len() > 100);
+		for (original, decoded) in gradient.data.iter().zip(&decoded.data) {
+			assert!((original.g() - decoded.g()).abs() < 1e-4);
+		}
+	}
+
+	#[test]
+	fn tiff16_round_trips_alpha() {
+		let bytes = encode_image(test_image(), RasterFormat::Tiff16, ColorSpace::Srgb).unwrap();
+		let decoded = decode_image(&bytes, ColorSpace::Srgb).unwrap();
+		for (original, decoded) in test_image().data.iter().zip(&decoded.data) {
+			assert!((original.a() - decoded.a()).abs() < 1e-4);
+			assert!((original.b() - decoded.b()).abs() < 1e-3);
+		}
+	}
+
+	#[test]
+	fn exr_round_trips_linear_colors() {
+		let hdr = Image {
+			width: 2,
+			height: 1,
+			data: vec![Color::from_rgbaf32_unchecked(4.5, 0.001, 0.25, 1.), Color::from_rgbaf32_unchecked(0.1, 0.2, 0.3, 0.5)],
+		};
+		let bytes = encode_image(hdr.clone(), RasterFormat::OpenExr, ColorSpace::Srgb).unwrap();
+		assert_eq!(decode_image(&bytes, ColorSpace::Srgb).unwrap().data, hdr.data);
+	}
+
+	#[test]
+	fn embedded_profiles_round_trip_wide_gamut_colors() {
+		// Fully saturated Display P3 green is outside of sRGB, so it only survives the round trip if the color space is read back
+		let green = Image {
+			width: 1,
+			height: 1,
+			data: vec![Color::from_rgbaf32_unchecked(0., 1., 0., 1.)],
+		};
+		for format in [
+			RasterFormat::Png,
+			RasterFormat::Png16,
+			RasterFormat::Jpeg,
+			RasterFormat::Tiff,
+			RasterFormat::Tiff16,
+			RasterFormat::OpenExr,
+		] {
+			let bytes = encode_image(green.clone(), format, ColorSpace::DisplayP3).unwrap();
+			let decoded = decode_image(&bytes, ColorSpace::DisplayP3).unwrap().data[0];
+			assert!(decoded.r() < 0.02 && decoded.g() > 0.97 && decoded.b() < 0.02, "{format:?}: {decoded:?}");
+
+			let in_srgb = decode_image(&bytes, ColorSpace::Srgb).unwrap().data[0];
+			assert!(in_srgb.r() < 0., "{format:?}: {in_srgb:?}");
+		}
+	}
+}
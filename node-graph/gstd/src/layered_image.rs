@@ -0,0 +1,866 @@
+//! Reading layered raster files, Photoshop documents (PSD) and OpenRaster archives (ORA), into a [`GraphicGroup`] with an element for each layer.
+//!
+//! Pixel layers become [`ImageFrame`]s whose transform places the layer's pixels at its offset within the canvas, and groups become nested
+//! [`GraphicGroup`]s. The name, opacity, visibility and blend mode of every layer and group are kept. Adjustment layers, masks and layer effects
+//! have no equivalent and are left out.
+
+use crate::archive::unzip_files;
+use crate::export::{decode_image, gamma_decoder};
+
+use graphene_core::raster::icc::IccProfile;
+use graphene_core::raster::{BlendMode, ColorSpace, Image, ImageFrame};
+use graphene_core::{Color, GraphicElement, GraphicElementData, GraphicGroup};
+
+use glam::{DAffine2, DVec2};
+
+/// The most pixels the canvas or a layer may have, which keeps huge or malformed files from using up the memory, since each pixel takes 16 bytes once decoded.
+const MAX_PIXELS: u64 = 1 << 26;
+
+/// The layers of a layered raster file, ordered from bottom to top, along with the size of its canvas in pixels.
+#[derive(Clone, Debug, PartialEq)]
+pub struct LayeredImage {
+	pub width: u32,
+	pub height: u32,
+	pub layers: GraphicGroup,
+}
+
+/// Whether the data is a Photoshop document or an OpenRaster archive, judging by its first bytes.
+pub fn is_layered_image(data: &[u8]) -> bool {
+	data.starts_with(b"8BPS") || (data.starts_with(b"PK\x03\x04") && data.get(30..38) == Some(b"mimetype") && data.get(38..54) == Some(b"image/openraster"))
+}
+
+/// Decodes a Photoshop document or OpenRaster archive, converting the colors of its layers into linear colors with premultiplied alpha in the
+/// given working space.
+pub fn decode_layered_image(data: &[u8], color_space: ColorSpace) -> Result<LayeredImage, String> {
+	if data.starts_with(b"8BPS") {
+		decode_psd(data, color_space)
+	} else {
+		decode_ora(data, color_space)
+	}
+}
+
+fn layer(name: String, blend_mode: BlendMode, opacity: f32, visible: bool, graphic_element_data: GraphicElementData) -> GraphicElement {
+	GraphicElement {
+		name,
+		blend_mode,
+		opacity,
+		visible,
+		locked: false,
+		collapsed: false,
+		graphic_element_data,
+	}
+}
+
+/// The number of pixels of an image of the given size, or an error if it has more than [`MAX_PIXELS`].
+fn pixel_count(width: u32, height: u32) -> Result<usize, String> {
+	let pixels = (width as u64).checked_mul(height as u64).filter(|&pixels| pixels <= MAX_PIXELS);
+	pixels.map(|pixels| pixels as usize).ok_or_else(|| format!("Images of {width}×{height} pixels are too large to import"))
+}
+
+/// The number of bytes of the samples of one channel of an image with the given size and bit depth.
+fn plane_length(width: u32, height: u32, depth: u16) -> Result<usize, String> {
+	Ok(pixel_count(width, height)? * (depth as usize / 8))
+}
+
+/// An image frame covering the pixels from `offset` to `offset + size` in the canvas.
+fn image_frame(image: Image<Color>, offset: DVec2) -> ImageFrame<Color> {
+	let size = DVec2::new(image.width as f64, image.height as f64);
+	ImageFrame {
+		image,
+		transform: DAffine2::from_scale_angle_translation(size, 0., offset),
+	}
+}
+
+// ==========
+// PHOTOSHOP
+// ==========
+
+/// Reads the big endian values of the Photoshop format.
+struct Reader<'a> {
+	data: &'a [u8],
+	position: usize,
+}
+
+impl<'a> Reader<'a> {
+	fn new(data: &'a [u8]) -> Self {
+		Self { data, position: 0 }
+	}
+
+	fn remaining(&self) -> usize {
+		self.data.len() - self.position
+	}
+
+	fn bytes(&mut self, length: usize) -> Result<&'a [u8], String> {
+		let end = self.position.checked_add(length).ok_or("The file ends unexpectedly")?;
+		let bytes = self.data.get(self.position..end).ok_or("The file ends unexpectedly")?;
+		self.position = end;
+		Ok(bytes)
+	}
+
+	fn skip(&mut self, length: usize) -> Result<(), String> {
+		self.bytes(length).map(|_| ())
+	}
+
+	fn u8(&mut self) -> Result<u8, String> {
+		Ok(self.bytes(1)?[0])
+	}
+
+	fn u16(&mut self) -> Result<u16, String> {
+		Ok(u16::from_be_bytes(self.bytes(2)?.try_into().unwrap()))
+	}
+
+	fn u32(&mut self) -> Result<u32, String> {
+		Ok(u32::from_be_bytes(self.bytes(4)?.try_into().unwrap()))
+	}
+
+	fn i32(&mut self) -> Result<i32, String> {
+		Ok(self.u32()? as i32)
+	}
+
+	/// Reads a section prefixed by its 32 bit length, returning a reader over its contents.
+	fn section(&mut self) -> Result<Reader<'a>, String> {
+		let length = self.u32()? as usize;
+		Ok(Reader::new(self.bytes(length)?))
+	}
+}
+
+/// A layer record from the layer info section, which precedes the pixel data of all layers.
+struct LayerRecord {
+	top: i32,
+	left: i32,
+	width: u32,
+	height: u32,
+	/// The ID and byte length of each channel's data.
+	channels: Vec<(i16, usize)>,
+	blend_mode: BlendMode,
+	opacity: f32,
+	visible: bool,
+	name: String,
+	/// The `lsct` section divider type, which marks the start and end of groups.
+	divider: Option<u32>,
+}
+
+const GROUP_OPEN: u32 = 1;
+const GROUP_CLOSED: u32 = 2;
+const GROUP_END: u32 = 3;
+
+fn decode_psd(data: &[u8], color_space: ColorSpace) -> Result<LayeredImage, String> {
+	let mut reader = Reader::new(data);
+	if reader.bytes(4)? != b"8BPS" {
+		return Err("The file isn't a Photoshop document".to_string());
+	}
+	if reader.u16()? != 1 {
+		return Err("Large documents (PSB) aren't supported".to_string());
+	}
+	reader.skip(6)?;
+	let channel_count = reader.u16()? as usize;
+	let height = reader.u32()?;
+	let width = reader.u32()?;
+	let depth = reader.u16()?;
+	if depth != 8 && depth != 16 {
+		return Err(format!("{depth}-bit documents aren't supported, only 8-bit and 16-bit"));
+	}
+	let grayscale = match reader.u16()? {
+		1 => true,
+		3 => false,
+		_ => return Err("Only RGB and grayscale documents are supported".to_string()),
+	};
+
+	// Color mode data, which only indexed and duotone documents use
+	reader.section()?;
+
+	let mut resources = reader.section()?;
+	let mut profile = None;
+	while resources.remaining() >= 12 && resources.bytes(4)? == b"8BIM" {
+		let id = resources.u16()?;
+		// A Pascal string name padded to an even length
+		let name_length = resources.u8()? as usize;
+		resources.skip(name_length + (name_length + 1) % 2)?;
+		let length = resources.u32()? as usize;
+		let resource = resources.bytes(length)?;
+		resources.skip((length % 2).min(resources.remaining()))?;
+		if id == 1039 {
+			profile = IccProfile::parse(resource);
+		}
+	}
+	let to_linear = gamma_decoder(profile.as_ref(), color_space);
+
+	let mut layer_and_mask = reader.section()?;
+	let mut layer_info = if layer_and_mask.remaining() > 0 { layer_and_mask.section()? } else { Reader::new(&[]) };
+	// The layers of 16-bit documents are kept in an `Lr16` block after the global layer mask, leaving the usual layer info empty
+	if layer_info.remaining() == 0 && depth == 16 && layer_and_mask.remaining() > 0 {
+		layer_and_mask.section()?;
+		if let Some(layers) = additional_layer_info(&mut layer_and_mask, b"Lr16")? {
+			layer_info = layers;
+		}
+	}
+
+	// Documents with only a background layer may leave out the layer info and keep just the flattened image
+	if layer_info.remaining() == 0 {
+		let planes = decode_planes(&mut reader, width, height, depth, channel_count)?;
+		let image = planes_to_image(&planes, width, height, grayscale, &to_linear);
+		let background = layer("Background".to_string(), BlendMode::Normal, 1., true, image_frame(image, DVec2::ZERO).into());
+		let mut layers = GraphicGroup::EMPTY;
+		layers.push(background);
+		return Ok(LayeredImage { width, height, layers });
+	}
+
+	// A negative count means the first alpha channel of the flattened image holds its transparency, which doesn't matter for the layers
+	let layer_count = (layer_info.u16()? as i16).unsigned_abs();
+	let records = (0..layer_count).map(|_| read_layer_record(&mut layer_info)).collect::<Result<Vec<_>, _>>()?;
+
+	// The records are ordered from bottom to top, where a group's end divider comes before its contents and the group itself after them
+	let mut groups = vec![Vec::new()];
+	for record in records {
+		let mut channel_data = Vec::with_capacity(record.channels.len());
+		for &(id, length) in &record.channels {
+			channel_data.push((id, layer_info.bytes(length)?));
+		}
+
+		match record.divider {
+			Some(GROUP_END) => groups.push(Vec::new()),
+			Some(GROUP_OPEN | GROUP_CLOSED) if groups.len() > 1 => {
+				let mut children = GraphicGroup::EMPTY;
+				children.extend(groups.pop().unwrap());
+				let group = layer(record.name, record.blend_mode, record.opacity, record.visible, children.into());
+				groups.last_mut().unwrap().push(group);
+			}
+			_ if record.width > 0 && record.height > 0 => {
+				let (width, height) = (record.width, record.height);
+				let pixels = pixel_count(width, height)?;
+				let mut planes: [Option<Vec<f32>>; 4] = Default::default();
+				for (id, data) in channel_data {
+					// Channels 0 to 2 are the colors, -1 is the transparency and lower ones are masks
+					let index = match id {
+						-1 => 3,
+						0..=2 => id as usize,
+						_ => continue,
+					};
+					planes[index] = Some(decode_channel(data, width, height, depth)?);
+				}
+				let plane = |index: usize, pixel: usize, default: f32| planes[index].as_ref().map_or(default, |plane| plane[pixel]);
+				let data = (0..pixels)
+					.map(|pixel| {
+						let red = plane(0, pixel, 0.);
+						let (green, blue) = match grayscale {
+							true => (red, red),
+							false => (plane(1, pixel, 0.), plane(2, pixel, 0.)),
+						};
+						to_linear([red, green, blue, plane(3, pixel, 1.)])
+					})
+					.collect();
+				let image = Image { width, height, data };
+				let offset = DVec2::new(record.left as f64, record.top as f64);
+				groups
+					.last_mut()
+					.unwrap()
+					.push(layer(record.name, record.blend_mode, record.opacity, record.visible, image_frame(image, offset).into()));
+			}
+			// Layers without pixels, such as adjustment layers
+			_ => {}
+		}
+	}
+
+	// Groups missing their start, which only malformed files have, are flattened into their parent
+	let mut layers = GraphicGroup::EMPTY;
+	layers.extend(groups.into_iter().flatten());
+	Ok(LayeredImage { width, height, layers })
+}
+
+/// Finds the additional layer information block with the given key among those which follow the global layer mask, returning a reader over its contents.
+fn additional_layer_info<'a>(reader: &mut Reader<'a>, key: &[u8; 4]) -> Result<Option<Reader<'a>>, String> {
+	while reader.remaining() >= 12 {
+		let signature = reader.bytes(4)?;
+		if signature != b"8BIM" && signature != b"8B64" {
+			break;
+		}
+		let block_key = reader.bytes(4)?;
+		let block = reader.section()?;
+		if block_key == key {
+			return Ok(Some(block));
+		}
+	}
+	Ok(None)
+}
+
+fn read_layer_record(reader: &mut Reader) -> Result<LayerRecord, String> {
+	let top = reader.i32()?;
+	let left = reader.i32()?;
+	let bottom = reader.i32()?;
+	let right = reader.i32()?;
+
+	let channel_count = reader.u16()?;
+	let channels = (0..channel_count).map(|_| Ok((reader.u16()? as i16, reader.u32()? as usize))).collect::<Result<Vec<_>, String>>()?;
+
+	if reader.bytes(4)? != b"8BIM" {
+		return Err("A layer record is malformed".to_string());
+	}
+	let blend_key: [u8; 4] = reader.bytes(4)?.try_into().unwrap();
+	let opacity = reader.u8()? as f32 / 255.;
+	let _clipping = reader.u8()?;
+	// Bit 1 is set for hidden layers
+	let visible = reader.u8()? & 0b10 == 0;
+	let _filler = reader.u8()?;
+
+	let mut extra = reader.section()?;
+	// The layer mask and blending ranges
+	extra.section()?;
+	extra.section()?;
+	// A Pascal string padded to a multiple of four bytes, which the Unicode name below supersedes if present
+	let name_length = extra.u8()? as usize;
+	let mut name = extra.bytes(name_length)?.iter().map(|&byte| byte as char).collect::<String>();
+	extra.skip((3 - name_length % 4).min(extra.remaining()))?;
+
+	let mut divider = None;
+	while extra.remaining() >= 12 {
+		let signature = extra.bytes(4)?;
+		if signature != b"8BIM" && signature != b"8B64" {
+			break;
+		}
+		let key = extra.bytes(4)?;
+		let mut block = extra.section()?;
+		match key {
+			b"luni" => {
+				let length = block.u32()? as usize;
+				let units = (0..length).map(|_| block.u16()).collect::<Result<Vec<_>, _>>()?;
+				name = String::from_utf16_lossy(&units).trim_end_matches('\0').to_string();
+			}
+			b"lsct" => divider = Some(block.u32()?),
+			_ => {}
+		}
+	}
+
+	Ok(LayerRecord {
+		top,
+		left,
+		width: (right as i64 - left as i64).clamp(0, u32::MAX as i64) as u32,
+		height: (bottom as i64 - top as i64).clamp(0, u32::MAX as i64) as u32,
+		channels,
+		blend_mode: psd_blend_mode(&blend_key),
+		opacity,
+		visible,
+		name,
+		divider,
+	})
+}
+
+fn psd_blend_mode(key: &[u8; 4]) -> BlendMode {
+	match key {
+		b"mul " => BlendMode::Multiply,
+		b"dark" => BlendMode::Darken,
+		b"idiv" => BlendMode::ColorBurn,
+		b"lbrn" => BlendMode::LinearBurn,
+		b"dkCl" => BlendMode::DarkerColor,
+		b"scrn" => BlendMode::Screen,
+		b"lite" => BlendMode::Lighten,
+		b"div " => BlendMode::ColorDodge,
+		b"lddg" => BlendMode::LinearDodge,
+		b"lgCl" => BlendMode::LighterColor,
+		b"over" => BlendMode::Overlay,
+		b"sLit" => BlendMode::SoftLight,
+		b"hLit" => BlendMode::HardLight,
+		b"vLit" => BlendMode::VividLight,
+		b"lLit" => BlendMode::LinearLight,
+		b"pLit" => BlendMode::PinLight,
+		b"hMix" => BlendMode::HardMix,
+		b"diff" => BlendMode::Difference,
+		b"smud" => BlendMode::Exclusion,
+		b"fsub" => BlendMode::Subtract,
+		b"fdiv" => BlendMode::Divide,
+		b"hue " => BlendMode::Hue,
+		b"sat " => BlendMode::Saturation,
+		b"colr" => BlendMode::Color,
+		b"lum " => BlendMode::Luminosity,
+		// Normal, pass through for groups, and dissolve which has no equivalent
+		_ => BlendMode::Normal,
+	}
+}
+
+/// Decodes the data of one channel of a layer, which starts with its compression method, into samples normalized to the range 0 to 1.
+fn decode_channel(data: &[u8], width: u32, height: u32, depth: u16) -> Result<Vec<f32>, String> {
+	let mut reader = Reader::new(data);
+	let compression = reader.u16()?;
+	let length = plane_length(width, height, depth)?;
+	let rows = height as usize;
+	let row_length = length / rows.max(1);
+
+	let bytes = match compression {
+		0 => reader.bytes(length)?.to_vec(),
+		1 => {
+			let row_byte_counts = (0..rows).map(|_| reader.u16()).collect::<Result<Vec<_>, _>>()?;
+			let mut bytes = Vec::with_capacity(length);
+			for count in row_byte_counts {
+				bytes.extend(unpack_bits(reader.bytes(count as usize)?, row_length)?);
+			}
+			bytes
+		}
+		2 | 3 => {
+			let remaining = reader.remaining();
+			// A channel never holds more than one plane, so the output is limited to that rather than to what the data expands into
+			let compressed = reader.bytes(remaining)?;
+			let mut bytes = miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(compressed, length).map_err(|error| format!("A channel could not be decompressed: {:?}", error.status))?;
+			if bytes.len() < length {
+				return Err("A channel has too little data".to_string());
+			}
+			// Each sample is stored as the difference from the one before it in the row
+			if compression == 3 {
+				for row in bytes.chunks_exact_mut(row_length.max(1)) {
+					match depth {
+						8 => (1..row.len()).for_each(|index| row[index] = row[index].wrapping_add(row[index - 1])),
+						_ => (2..row.len()).step_by(2).for_each(|index| {
+							let previous = u16::from_be_bytes([row[index - 2], row[index - 1]]);
+							let current = u16::from_be_bytes([row[index], row[index + 1]]);
+							row[index..index + 2].copy_from_slice(&current.wrapping_add(previous).to_be_bytes());
+						}),
+					}
+				}
+			}
+			bytes
+		}
+		_ => return Err(format!("Compression method {compression} isn't supported")),
+	};
+
+	Ok(normalize_samples(&bytes[..length], depth))
+}
+
+/// Decodes the flattened image at the end of the file, whose channels are stored one after another with a single compression method.
+fn decode_planes(reader: &mut Reader, width: u32, height: u32, depth: u16, channel_count: usize) -> Result<Vec<Vec<f32>>, String> {
+	let compression = reader.u16()?;
+	let length = plane_length(width, height, depth)?;
+	let rows = height as usize;
+	let row_length = length / rows.max(1);
+	let too_large = || "The image has too many channels to import".to_string();
+	let total_length = length.checked_mul(channel_count).ok_or_else(too_large)?;
+
+	let bytes = match compression {
+		0 => reader.bytes(total_length)?.to_vec(),
+		1 => {
+			// The byte counts of every row of every channel come before all of the data
+			let row_byte_counts = (0..rows.checked_mul(channel_count).ok_or_else(too_large)?).map(|_| reader.u16()).collect::<Result<Vec<_>, _>>()?;
+			let mut bytes = Vec::with_capacity(total_length);
+			for count in row_byte_counts {
+				bytes.extend(unpack_bits(reader.bytes(count as usize)?, row_length)?);
+			}
+			bytes
+		}
+		_ => return Err(format!("Compression method {compression} isn't supported")),
+	};
+
+	Ok(bytes.chunks_exact(length.max(1)).map(|plane| normalize_samples(plane, depth)).collect())
+}
+
+fn planes_to_image(planes: &[Vec<f32>], width: u32, height: u32, grayscale: bool, to_linear: &impl Fn([f32; 4]) -> Color) -> Image<Color> {
+	// Any channel after the colors is treated as transparency
+	let alpha_index = if grayscale { 1 } else { 3 };
+	let sample = |index: usize, pixel: usize, default: f32| planes.get(index).map_or(default, |plane| plane[pixel]);
+	let data = (0..width as usize * height as usize)
+		.map(|pixel| {
+			let red = sample(0, pixel, 0.);
+			let (green, blue) = match grayscale {
+				true => (red, red),
+				false => (sample(1, pixel, 0.), sample(2, pixel, 0.)),
+			};
+			to_linear([red, green, blue, sample(alpha_index, pixel, 1.)])
+		})
+		.collect();
+	Image { width, height, data }
+}
+
+fn normalize_samples(bytes: &[u8], depth: u16) -> Vec<f32> {
+	match depth {
+		8 => bytes.iter().map(|&sample| sample as f32 / u8::MAX as f32).collect(),
+		_ => bytes.chunks_exact(2).map(|sample| u16::from_be_bytes([sample[0], sample[1]]) as f32 / u16::MAX as f32).collect(),
+	}
+}
+
+/// Decompresses a row of PackBits run length encoded data.
+fn unpack_bits(mut data: &[u8], length: usize) -> Result<Vec<u8>, String> {
+	let mut row = Vec::with_capacity(length);
+	while let Some((&header, rest)) = data.split_first() {
+		let header = header as i8;
+		data = rest;
+		match header {
+			// A literal run of the following bytes
+			0..=127 => {
+				let count = header as usize + 1;
+				row.extend(data.get(..count).ok_or("A channel's data is truncated")?);
+				data = &data[count..];
+			}
+			// Ignored by the specification
+			-128 => {}
+			// The following byte repeated
+			_ => {
+				let count = 1 - header as isize;
+				let (&byte, rest) = data.split_first().ok_or("A channel's data is truncated")?;
+				row.resize(row.len() + count as usize, byte);
+				data = rest;
+			}
+		}
+	}
+	if row.len() < length {
+		return Err("A channel has too little data".to_string());
+	}
+	row.truncate(length);
+	Ok(row)
+}
+
+// ===========
+// OPENRASTER
+// ===========
+
+/// The attributes of a stack which hasn't been closed yet, along with the children found so far.
+type OpenStack = (Vec<(String, String)>, Vec<GraphicElement>);
+
+fn decode_ora(data: &[u8], color_space: ColorSpace) -> Result<LayeredImage, String> {
+	let files = unzip_files(data)?;
+	let file = |name: &str| files.iter().find(|(file_name, _)| file_name == name).map(|(_, data)| data.as_slice());
+	let stack = file("stack.xml").ok_or("The file isn't an OpenRaster image, it has no stack.xml")?;
+	let stack = std::str::from_utf8(stack).map_err(|_| "The stack.xml file isn't valid UTF-8")?;
+
+	let (mut width, mut height) = (0, 0);
+	let mut stacks: Vec<OpenStack> = Vec::new();
+	let mut root = Vec::new();
+
+	for tag in xml_tags(stack)? {
+		let attribute = |name: &str| tag.attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+		let number = |name: &str| attribute(name).and_then(|value| value.trim().parse::<f64>().ok());
+
+		match (tag.name.as_str(), tag.closing) {
+			("image", false) => {
+				width = number("w").unwrap_or_default() as u32;
+				height = number("h").unwrap_or_default() as u32;
+			}
+			("stack", false) => {
+				stacks.push((tag.attributes.clone(), Vec::new()));
+				if tag.self_closing {
+					close_ora_stack(&mut stacks, &mut root);
+				}
+			}
+			("stack", true) => close_ora_stack(&mut stacks, &mut root),
+			("layer", false) => {
+				let Some(source) = attribute("src") else { continue };
+				let data = file(source).ok_or_else(|| format!("The layer image {source} is missing"))?;
+				let image = decode_image(data, color_space).map_err(|error| format!("The layer image {source} could not be decoded: {error}"))?;
+				let offset = DVec2::new(number("x").unwrap_or_default(), number("y").unwrap_or_default());
+				let element = ora_element(&tag.attributes, image_frame(image, offset).into());
+				match stacks.last_mut() {
+					Some((_, children)) => children.push(element),
+					None => root.push(element),
+				}
+			}
+			_ => {}
+		}
+	}
+	while !stacks.is_empty() {
+		close_ora_stack(&mut stacks, &mut root);
+	}
+
+	let mut layers = GraphicGroup::EMPTY;
+	layers.extend(root);
+	Ok(LayeredImage { width, height, layers })
+}
+
+/// Finishes the innermost open stack, whose children are listed from top to bottom. The outermost stack is the image itself, so its children
+/// become the top level layers.
+fn close_ora_stack(stacks: &mut Vec<OpenStack>, root: &mut Vec<GraphicElement>) {
+	let Some((attributes, mut children)) = stacks.pop() else { return };
+	children.reverse();
+	match stacks.last_mut() {
+		Some((_, parent)) => {
+			let mut group = GraphicGroup::EMPTY;
+			group.extend(children);
+			parent.push(ora_element(&attributes, group.into()));
+		}
+		None => root.extend(children),
+	}
+}
+
+fn ora_element(attributes: &[(String, String)], graphic_element_data: GraphicElementData) -> GraphicElement {
+	let attribute = |name: &str| attributes.iter().find(|(key, _)| key == name).map(|(_, value)| value.as_str());
+	let name = attribute("name").unwrap_or_default().to_string();
+	let opacity = attribute("opacity").and_then(|value| value.trim().parse::<f32>().ok()).unwrap_or(1.).clamp(0., 1.);
+	let visible = attribute("visibility") != Some("hidden");
+	let blend_mode = match attribute("composite-op").unwrap_or_default() {
+		"svg:multiply" => BlendMode::Multiply,
+		"svg:darken" => BlendMode::Darken,
+		"svg:color-burn" => BlendMode::ColorBurn,
+		"svg:screen" => BlendMode::Screen,
+		"svg:lighten" => BlendMode::Lighten,
+		"svg:color-dodge" => BlendMode::ColorDodge,
+		"svg:plus" => BlendMode::LinearDodge,
+		"svg:overlay" => BlendMode::Overlay,
+		"svg:soft-light" => BlendMode::SoftLight,
+		"svg:hard-light" => BlendMode::HardLight,
+		"svg:difference" => BlendMode::Difference,
+		"svg:exclusion" => BlendMode::Exclusion,
+		"svg:hue" => BlendMode::Hue,
+		"svg:saturation" => BlendMode::Saturation,
+		"svg:color" => BlendMode::Color,
+		"svg:luminosity" => BlendMode::Luminosity,
+		"svg:dst-out" => BlendMode::Erase,
+		_ => BlendMode::Normal,
+	};
+	layer(name, blend_mode, opacity, visible, graphic_element_data)
+}
+
+/// A start or end tag of an XML document.
+#[derive(Debug, PartialEq)]
+struct XmlTag {
+	name: String,
+	attributes: Vec<(String, String)>,
+	closing: bool,
+	self_closing: bool,
+}
+
+/// Lists the tags of an XML document, which is all that's needed from the simple `stack.xml` files since their content is in the attributes.
+fn xml_tags(xml: &str) -> Result<Vec<XmlTag>, String> {
+	let malformed = || "The stack.xml file is malformed".to_string();
+	let mut tags = Vec::new();
+	let mut rest = xml;
+
+	while let Some(start) = rest.find('<') {
+		rest = &rest[start + 1..];
+
+		// Comments, declarations and processing instructions
+		if let Some(comment) = rest.strip_prefix("!--") {
+			rest = &comment[comment.find("-->").ok_or_else(malformed)? + 3..];
+			continue;
+		}
+		if rest.starts_with('?') || rest.starts_with('!') {
+			rest = &rest[rest.find('>').ok_or_else(malformed)? + 1..];
+			continue;
+		}
+
+		let closing = rest.starts_with('/');
+		let name_end = rest.find(|c: char| c.is_whitespace() || c == '>' || c == '/').filter(|&end| end > 0 || closing).ok_or_else(malformed)?;
+		let name_end = if closing {
+			rest[1..].find(|c: char| c.is_whitespace() || c == '>').ok_or_else(malformed)? + 1
+		} else {
+			name_end
+		};
+		let name = rest[usize::from(closing)..name_end].to_string();
+		rest = &rest[name_end..];
+
+		let mut attributes = Vec::new();
+		let self_closing = loop {
+			rest = rest.trim_start();
+			if let Some(after) = rest.strip_prefix("/>") {
+				rest = after;
+				break true;
+			}
+			if let Some(after) = rest.strip_prefix('>') {
+				rest = after;
+				break false;
+			}
+
+			let equals = rest.find('=').ok_or_else(malformed)?;
+			let key = rest[..equals].trim().to_string();
+			rest = rest[equals + 1..].trim_start();
+			let quote = rest.chars().next().filter(|&c| c == '"' || c == '\'').ok_or_else(malformed)?;
+			let value_end = rest[1..].find(quote).ok_or_else(malformed)? + 1;
+			attributes.push((key, unescape_xml(&rest[1..value_end])));
+			rest = &rest[value_end + 1..];
+		};
+
+		tags.push(XmlTag {
+			name,
+			attributes,
+			closing,
+			self_closing,
+		});
+	}
+
+	Ok(tags)
+}
+
+fn unescape_xml(text: &str) -> String {
+	let mut result = String::with_capacity(text.len());
+	let mut rest = text;
+	while let Some(start) = rest.find('&') {
+		result.push_str(&rest[..start]);
+		rest = &rest[start..];
+		let Some(end) = rest.find(';') else { break };
+		let entity = &rest[1..end];
+		let character = match entity {
+			"amp" => Some('&'),
+			"lt" => Some('<'),
+			"gt" => Some('>'),
+			"quot" => Some('"'),
+			"apos" => Some('\''),
+			_ => entity
+				.strip_prefix("#x")
+				.map(|hex| u32::from_str_radix(hex, 16).ok())
+				.unwrap_or_else(|| entity.strip_prefix('#').and_then(|decimal| decimal.parse().ok()))
+				.and_then(char::from_u32),
+		};
+		match character {
+			Some(character) => {
+				result.push(character);
+				rest = &rest[end + 1..];
+			}
+			None => {
+				result.push('&');
+				rest = &rest[1..];
+			}
+		}
+	}
+	result.push_str(rest);
+	result
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+
+	fn children(element: &GraphicElement) -> &GraphicGroup {
+		match &element.graphic_element_data {
+			GraphicElementData::GraphicGroup(group) => group,
+			_ => panic!("{} should be a group", element.name),
+		}
+	}
+
+	fn image_frame(element: &GraphicElement) -> &ImageFrame<Color> {
+		match &element.graphic_element_data {
+			GraphicElementData::ImageFrame(image_frame) => image_frame,
+			_ => panic!("{} should be an image", element.name),
+		}
+	}
+
+	/// `layers.psd` is an 8-bit RGB document of 4 by 3 pixels. From bottom to top, it has a group named "Group" containing a hidden layer
+	/// "Hidden" with the multiply blend mode, followed by a layer "Top layé" at 50% opacity whose 2 by 2 pixels start at (1, 1).
+	#[test]
+	fn photoshop_layers() {
+		let data = include_bytes!("../test_files/layers.psd");
+		assert!(is_layered_image(data));
+		let image = decode_layered_image(data, ColorSpace::Srgb).unwrap();
+		assert_eq!((image.width, image.height), (4, 3));
+		assert_eq!(image.layers.len(), 2);
+
+		let group = &image.layers[0];
+		assert_eq!(group.name, "Group");
+		let hidden = &children(group)[0];
+		assert_eq!((hidden.name.as_str(), hidden.visible, hidden.blend_mode), ("Hidden", false, BlendMode::Multiply));
+		let hidden = image_frame(hidden);
+		assert_eq!((hidden.image.width, hidden.image.height), (4, 3));
+		assert_eq!(hidden.image.data[0], Color::from_rgbaf32_unchecked(1., 0., 0., 1.));
+
+		let top = &image.layers[1];
+		assert_eq!((top.name.as_str(), top.visible), ("Top layé", true));
+		assert!((top.opacity - 128. / 255.).abs() < 1e-6);
+		let top = image_frame(top);
+		assert_eq!(top.transform, DAffine2::from_scale_angle_translation(DVec2::splat(2.), 0., DVec2::ONE));
+		// A pixel with half transparency, stored with straight alpha
+		let pixel = top.image.data[3];
+		let expected = Color::from_unassociated_alpha(0., 0., 1., 0.5);
+		assert!((pixel.b() - expected.b()).abs() < 1e-2 && (pixel.a() - expected.a()).abs() < 1e-2, "{pixel:?}");
+	}
+
+	/// `layers.ora` is a 4 by 3 pixel image with a top layer "Top" at (1, 1) over a stack "Stack" holding the hidden layer "Bottom" with the
+	/// screen blend mode, where the layer images are 2 by 2 pixel PNGs.
+	#[test]
+	fn openraster_layers() {
+		let data = include_bytes!("../test_files/layers.ora");
+		assert!(is_layered_image(data));
+		let image = decode_layered_image(data, ColorSpace::Srgb).unwrap();
+		assert_eq!((image.width, image.height), (4, 3));
+		assert_eq!(image.layers.len(), 2);
+
+		let stack = &image.layers[0];
+		assert_eq!(stack.name, "Stack & co");
+		let bottom = &children(stack)[0];
+		assert_eq!((bottom.name.as_str(), bottom.visible, bottom.blend_mode), ("Bottom", false, BlendMode::Screen));
+
+		let top = &image.layers[1];
+		assert_eq!(top.name, "Top");
+		assert!((top.opacity - 0.75).abs() < 1e-6);
+		let top = image_frame(top);
+		assert_eq!(top.transform, DAffine2::from_scale_angle_translation(DVec2::splat(2.), 0., DVec2::ONE));
+		assert_eq!(top.image.data[0], Color::from_rgbaf32_unchecked(0., 1., 0., 1.));
+	}
+
+	/// Writes a section prefixed by its 32 bit length.
+	fn section(data: &mut Vec<u8>, contents: &[u8]) {
+		data.extend((contents.len() as u32).to_be_bytes());
+		data.extend(contents);
+	}
+
+	/// A 16-bit RGB document of 1 by 1 pixel, whose single layer "A" is stored in an `Lr16` block as Photoshop writes it.
+	fn sixteen_bit_psd(layer_size: [i32; 2]) -> Vec<u8> {
+		let mut layer_info = 1_i16.to_be_bytes().to_vec();
+		for coordinate in [0, 0, layer_size[1], layer_size[0]] {
+			layer_info.extend(coordinate.to_be_bytes());
+		}
+		layer_info.extend(3_u16.to_be_bytes());
+		for id in 0..3_i16 {
+			layer_info.extend(id.to_be_bytes());
+			layer_info.extend(4_u32.to_be_bytes());
+		}
+		layer_info.extend(b"8BIMnorm");
+		layer_info.extend([255, 0, 0, 0]);
+		// The layer mask, blending ranges and name padded to four bytes
+		section(&mut layer_info, &[0, 0, 0, 0, 0, 0, 0, 0, 1, b'A', 0, 0]);
+		for sample in [u16::MAX, 0, 0] {
+			layer_info.extend(0_u16.to_be_bytes());
+			layer_info.extend(sample.to_be_bytes());
+		}
+
+		let mut layer_and_mask = Vec::new();
+		// The empty layer info and global layer mask, followed by the layers
+		section(&mut layer_and_mask, &[]);
+		section(&mut layer_and_mask, &[]);
+		layer_and_mask.extend(b"8BIMLr16");
+		section(&mut layer_and_mask, &layer_info);
+
+		let mut data = b"8BPS".to_vec();
+		data.extend(1_u16.to_be_bytes());
+		data.extend([0; 6]);
+		data.extend(3_u16.to_be_bytes());
+		data.extend(1_u32.to_be_bytes());
+		data.extend(1_u32.to_be_bytes());
+		data.extend(16_u16.to_be_bytes());
+		data.extend(3_u16.to_be_bytes());
+		section(&mut data, &[]);
+		section(&mut data, &[]);
+		section(&mut data, &layer_and_mask);
+		// The flattened image
+		data.extend(0_u16.to_be_bytes());
+		data.extend([0; 6]);
+		data
+	}
+
+	#[test]
+	fn sixteen_bit_photoshop_layers() {
+		let image = decode_layered_image(&sixteen_bit_psd([1, 1]), ColorSpace::Srgb).unwrap();
+		assert_eq!(image.layers.len(), 1);
+		assert_eq!(image.layers[0].name, "A");
+		assert_eq!(image_frame(&image.layers[0]).image.data[0], Color::from_rgbaf32_unchecked(1., 0., 0., 1.));
+	}
+
+	#[test]
+	fn oversized_photoshop_layers_are_refused() {
+		assert!(decode_layered_image(&sixteen_bit_psd([1 << 14, 1 << 14]), ColorSpace::Srgb).is_err());
+		assert!(decode_layered_image(&sixteen_bit_psd([i32::MAX, i32::MAX]), ColorSpace::Srgb).is_err());
+	}
+
+	#[test]
+	fn packbits() {
+		assert_eq!(unpack_bits(&[2, 1, 2, 3, 0xfe, 9], 6), Ok(vec![1, 2, 3, 9, 9, 9]));
+		assert!(unpack_bits(&[5, 1], 6).is_err());
+	}
+
+	#[test]
+	fn zip_compressed_channels_are_limited_to_one_plane() {
+		let channel = |samples: &[u8]| [&2_u16.to_be_bytes()[..], &miniz_oxide::deflate::compress_to_vec_zlib(samples, 6)[..]].concat();
+		assert_eq!(decode_channel(&channel(&[0, 255, 0, 255]), 2, 2, 8), Ok(vec![0., 1., 0., 1.]));
+		assert!(decode_channel(&channel(&[0; 1 << 16]), 2, 2, 8).is_err());
+	}
+
+	#[test]
+	fn xml() {
+		let tags = xml_tags("<?xml version='1.0'?><!-- stack --><image w=\"4\"><layer name='a &amp; b' /></image>").unwrap();
+		assert_eq!(tags.len(), 3);
+		assert_eq!(tags[1].attributes, vec![("name".to_string(), "a & b".to_string())]);
+		assert!(tags[1].self_closing);
+		assert!(tags[2].closing);
+	}
+}
@@ -23,6 +23,16 @@ pub mod image_segmentation;
 
 pub mod brush;
 
+pub mod export;
+
+pub mod pdf;
+
+pub mod animation;
+
+pub mod archive;
+
+pub mod layered_image;
+
 #[cfg(feature = "wasm")]
 pub mod wasm_application_io;
 
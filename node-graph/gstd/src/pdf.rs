@@ -0,0 +1,603 @@
+//! Encoding of graphic elements into vector PDF files.
+//!
+//! Paths stay paths, images are embedded at their full resolution with their alpha channel as a soft mask, and gradients become shadings.
+//! Layers with an opacity or blend mode are drawn into transparency groups so they are composited as a whole, like in the SVG and raster output.
+//! Like the SVG output, vector colors are written as gamma encoded sRGB.
+
+use graphene_core::raster::{BlendMode, ImageFrame};
+use graphene_core::uuid::ManipulatorGroupId;
+use graphene_core::vector::style::{Fill, Gradient, GradientType, LineCap, LineJoin, Stroke};
+use graphene_core::vector::VectorData;
+use graphene_core::{Artboard, Color, GraphicElementData, GraphicGroup};
+
+use bezier_rs::Subpath;
+use glam::{DAffine2, DVec2};
+use pdf_writer::types::{FunctionShadingType, LineCapStyle, LineJoinStyle, MaskType};
+use pdf_writer::writers::Resources;
+use pdf_writer::{Content, Dict, Filter, Finish, Name, Pdf, Rect, Ref};
+
+/// The compression level used for content streams and images, on `miniz_oxide`'s scale of 0 to 10.
+const COMPRESSION_LEVEL: u8 = 6;
+
+/// A page of a PDF file showing part of a document.
+#[derive(Clone, Debug)]
+pub struct PdfPage {
+	/// The size of the page in points.
+	pub size: DVec2,
+	/// Maps document space to the page, with the origin at the top left corner and the y axis pointing down.
+	pub transform: DAffine2,
+	pub graphic_group: GraphicGroup,
+}
+
+/// Encodes each page into a single PDF file.
+pub fn encode_pdf(pages: &[PdfPage]) -> Vec<u8> {
+	let mut writer = PdfWriter::new();
+	let catalog_id = writer.alloc();
+	let page_tree_id = writer.alloc();
+
+	let page_ids = pages.iter().map(|page| writer.write_page(page, page_tree_id)).collect::<Vec<_>>();
+
+	writer.pdf.catalog(catalog_id).pages(page_tree_id);
+	writer.pdf.pages(page_tree_id).kids(page_ids.iter().copied()).count(page_ids.len() as i32);
+	writer.pdf.finish()
+}
+
+/// The parameters of an external graphics state, which are the only way to set opacity and blend modes in PDF.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct GraphicsState {
+	fill_alpha: f32,
+	stroke_alpha: f32,
+	blend_mode: BlendMode,
+}
+
+/// A content stream along with the resources it refers to by name.
+struct PdfContent {
+	content: Content,
+	x_objects: Vec<Ref>,
+	graphics_states: Vec<Ref>,
+	shadings: Vec<Ref>,
+}
+
+impl PdfContent {
+	fn new() -> Self {
+		Self {
+			content: Content::new(),
+			x_objects: Vec::new(),
+			graphics_states: Vec::new(),
+			shadings: Vec::new(),
+		}
+	}
+
+	fn resource_name(prefix: &str, resources: &mut Vec<Ref>, id: Ref) -> String {
+		let index = resources.iter().position(|&existing| existing == id).unwrap_or_else(|| {
+			resources.push(id);
+			resources.len() - 1
+		});
+		format!("{prefix}{index}")
+	}
+
+	fn x_object(&mut self, id: Ref) {
+		let name = Self::resource_name("X", &mut self.x_objects, id);
+		self.content.x_object(Name(name.as_bytes()));
+	}
+
+	fn set_graphics_state(&mut self, id: Ref) {
+		let name = Self::resource_name("G", &mut self.graphics_states, id);
+		self.content.set_parameters(Name(name.as_bytes()));
+	}
+
+	fn shading(&mut self, id: Ref) {
+		let name = Self::resource_name("S", &mut self.shadings, id);
+		self.content.shading(Name(name.as_bytes()));
+	}
+
+	/// Adds the subpaths, transformed into the space of the content, as the current path.
+	fn path(&mut self, subpaths: &[Subpath<ManipulatorGroupId>], transform: DAffine2) {
+		for subpath in subpaths {
+			let groups = subpath.manipulator_groups();
+			let Some(first) = groups.first() else { continue };
+			let point = |point: DVec2| transform.transform_point2(point).as_vec2();
+
+			let start = point(first.anchor);
+			self.content.move_to(start.x, start.y);
+
+			let closing = subpath.closed().then(|| (groups.last().unwrap(), first));
+			for (previous, next) in groups.iter().zip(groups.iter().skip(1)).chain(closing) {
+				let end = point(next.anchor);
+				if previous.out_handle.is_none() && next.in_handle.is_none() {
+					self.content.line_to(end.x, end.y);
+				} else {
+					let handle_start = point(previous.out_handle.unwrap_or(previous.anchor));
+					let handle_end = point(next.in_handle.unwrap_or(next.anchor));
+					self.content.cubic_to(handle_start.x, handle_start.y, handle_end.x, handle_end.y, end.x, end.y);
+				}
+			}
+			if subpath.closed() {
+				self.content.close_path();
+			}
+		}
+	}
+
+	fn write_resources(&self, mut resources: Resources) {
+		let write_names = |mut dict: Dict, prefix: &str, ids: &[Ref]| {
+			for (index, &id) in ids.iter().enumerate() {
+				dict.pair(Name(format!("{prefix}{index}").as_bytes()), id);
+			}
+		};
+		if !self.x_objects.is_empty() {
+			write_names(resources.x_objects(), "X", &self.x_objects);
+		}
+		if !self.graphics_states.is_empty() {
+			write_names(resources.ext_g_states(), "G", &self.graphics_states);
+		}
+		if !self.shadings.is_empty() {
+			write_names(resources.shadings(), "S", &self.shadings);
+		}
+	}
+}
+
+struct PdfWriter {
+	pdf: Pdf,
+	next_id: i32,
+	graphics_states: Vec<(GraphicsState, Ref)>,
+	/// Maps document space to the page being written
+	transform: DAffine2,
+	/// The bounds of the page being written, in the space of its content
+	page_bounds: Rect,
+}
+
+impl PdfWriter {
+	fn new() -> Self {
+		Self {
+			pdf: Pdf::new(),
+			next_id: 1,
+			graphics_states: Vec::new(),
+			transform: DAffine2::IDENTITY,
+			page_bounds: Rect::new(0., 0., 0., 0.),
+		}
+	}
+
+	fn alloc(&mut self) -> Ref {
+		let id = Ref::new(self.next_id);
+		self.next_id += 1;
+		id
+	}
+
+	fn write_page(&mut self, page: &PdfPage, page_tree_id: Ref) -> Ref {
+		let (width, height) = (page.size.x as f32, page.size.y as f32);
+		self.transform = page.transform;
+		self.page_bounds = Rect::new(0., 0., width, height);
+
+		// PDF pages have their origin at the bottom left with the y axis pointing up, so the content is flipped to match the document
+		let mut content = PdfContent::new();
+		content.content.transform([1., 0., 0., -1., 0., height]);
+		self.write_group(&page.graphic_group, &mut content);
+
+		let page_id = self.alloc();
+		let content_id = self.alloc();
+		let data = compress(&std::mem::replace(&mut content.content, Content::new()).finish());
+		self.pdf.stream(content_id, &data).filter(Filter::FlateDecode);
+
+		let mut pdf_page = self.pdf.page(page_id);
+		pdf_page.media_box(Rect::new(0., 0., width, height));
+		pdf_page.parent(page_tree_id);
+		pdf_page.contents(content_id);
+		content.write_resources(pdf_page.resources());
+		pdf_page.finish();
+
+		page_id
+	}
+
+	/// Writes a form XObject which is drawn as a transparency group, optionally in grayscale for use as a luminosity soft mask.
+	fn write_form(&mut self, mut content: PdfContent, grayscale: bool) -> Ref {
+		let id = self.alloc();
+		let data = compress(&std::mem::replace(&mut content.content, Content::new()).finish());
+
+		let mut form = self.pdf.form_xobject(id, &data);
+		form.filter(Filter::FlateDecode);
+		form.bbox(self.page_bounds);
+		let mut group = form.group();
+		group.transparency();
+		if grayscale {
+			group.color_space().device_gray();
+		} else {
+			group.color_space().device_rgb();
+		}
+		group.finish();
+		content.write_resources(form.resources());
+		form.finish();
+
+		id
+	}
+
+	fn graphics_state(&mut self, state: GraphicsState) -> Ref {
+		if let Some(&(_, id)) = self.graphics_states.iter().find(|(existing, _)| *existing == state) {
+			return id;
+		}
+
+		let id = self.alloc();
+		let mut ext_graphics = self.pdf.ext_graphics(id);
+		ext_graphics.non_stroking_alpha(state.fill_alpha).stroking_alpha(state.stroke_alpha);
+		if let Some(blend_mode) = pdf_blend_mode(state.blend_mode) {
+			ext_graphics.blend_mode(blend_mode);
+		}
+		ext_graphics.finish();
+
+		self.graphics_states.push((state, id));
+		id
+	}
+
+	fn write_group(&mut self, graphic_group: &GraphicGroup, content: &mut PdfContent) {
+		for element in graphic_group.iter().filter(|element| element.visible) {
+			if element.blend_mode == BlendMode::Normal && element.opacity >= 1. {
+				self.write_element(&element.graphic_element_data, content);
+				continue;
+			}
+
+			// Draw the element on its own so it is blended as a whole
+			let mut group_content = PdfContent::new();
+			self.write_element(&element.graphic_element_data, &mut group_content);
+			let form = self.write_form(group_content, false);
+
+			let state = self.graphics_state(GraphicsState {
+				fill_alpha: element.opacity,
+				stroke_alpha: element.opacity,
+				blend_mode: element.blend_mode,
+			});
+			content.content.save_state();
+			content.set_graphics_state(state);
+			content.x_object(form);
+			content.content.restore_state();
+		}
+	}
+
+	fn write_element(&mut self, graphic_element_data: &GraphicElementData, content: &mut PdfContent) {
+		match graphic_element_data {
+			GraphicElementData::VectorShape(vector_data) => self.write_vector(vector_data, content),
+			GraphicElementData::ImageFrame(image_frame) => self.write_image(image_frame, content),
+			// Text has no rendering of its own yet
+			GraphicElementData::Text(_) => {}
+			GraphicElementData::GraphicGroup(graphic_group) => self.write_group(graphic_group, content),
+			GraphicElementData::Artboard(artboard) => self.write_artboard(artboard, content),
+		}
+	}
+
+	fn write_vector(&mut self, vector_data: &VectorData, content: &mut PdfContent) {
+		let transform = self.transform * vector_data.transform;
+		let layer_bounds = vector_data.bounding_box().unwrap_or_default();
+
+		for (subpaths, style) in vector_data.styled_subpaths() {
+			match style.fill() {
+				Fill::None => {}
+				Fill::Solid(color) => self.fill_solid(content, subpaths, transform, *color),
+				Fill::Gradient(gradient) => self.fill_gradient(content, subpaths, transform, gradient, layer_bounds),
+			}
+
+			if let Some(stroke) = style.stroke() {
+				self.stroke(content, subpaths, transform, &stroke);
+			}
+		}
+	}
+
+	fn fill_solid(&mut self, content: &mut PdfContent, subpaths: &[Subpath<ManipulatorGroupId>], transform: DAffine2, color: Color) {
+		if color.a() <= 0. {
+			return;
+		}
+
+		content.content.save_state();
+		if color.a() < 1. {
+			let state = self.graphics_state(GraphicsState {
+				fill_alpha: color.a(),
+				stroke_alpha: 1.,
+				blend_mode: BlendMode::Normal,
+			});
+			content.set_graphics_state(state);
+		}
+		content.content.set_fill_rgb(color.r(), color.g(), color.b());
+		content.path(subpaths, transform);
+		content.content.fill_nonzero();
+		content.content.restore_state();
+	}
+
+	/// Fills the subpaths with a shading, using a soft mask made from another shading of the stop opacities if any stop is translucent.
+	fn fill_gradient(&mut self, content: &mut PdfContent, subpaths: &[Subpath<ManipulatorGroupId>], transform: DAffine2, gradient: &Gradient, layer_bounds: [DVec2; 2]) {
+		let mut stops = gradient.positions.iter().filter_map(|&(position, color)| Some((position.clamp(0., 1.), color?))).collect::<Vec<_>>();
+		stops.sort_by(|a, b| a.0.total_cmp(&b.0));
+		let (Some(&(first_position, first)), Some(&(last_position, last))) = (stops.first(), stops.last()) else {
+			return;
+		};
+		if stops.len() == 1 {
+			self.fill_solid(content, subpaths, transform, first);
+			return;
+		}
+		// Outside of the stops the nearest stop color extends
+		if first_position > 0. {
+			stops.insert(0, (0., first));
+		}
+		if last_position < 1. {
+			stops.push((1., last));
+		}
+
+		// Gradient positions are relative to the bounds of the shape in its layer space, as in the SVG output
+		let bounds_to_page = transform * DAffine2::from_scale_angle_translation(layer_bounds[1] - layer_bounds[0], 0., layer_bounds[0]);
+		let (start, end) = (bounds_to_page.transform_point2(gradient.start), bounds_to_page.transform_point2(gradient.end));
+		let coordinates = match gradient.gradient_type {
+			GradientType::Linear => vec![start.x, start.y, end.x, end.y],
+			GradientType::Radial => vec![start.x, start.y, 0., start.x, start.y, (end - start).length()],
+		};
+
+		let colors = stops.iter().map(|&(position, color)| (position, vec![color.r(), color.g(), color.b()])).collect::<Vec<_>>();
+		let shading = self.write_shading(gradient.gradient_type, &coordinates, &colors, false);
+
+		content.content.save_state();
+		if stops.iter().any(|(_, color)| color.a() < 1.) {
+			let alphas = stops.iter().map(|&(position, color)| (position, vec![color.a()])).collect::<Vec<_>>();
+			let alpha_shading = self.write_shading(gradient.gradient_type, &coordinates, &alphas, true);
+
+			let mut mask_content = PdfContent::new();
+			mask_content.shading(alpha_shading);
+			let mask = self.write_form(mask_content, true);
+
+			let state = self.alloc();
+			self.pdf.ext_graphics(state).soft_mask().subtype(MaskType::Luminosity).group(mask);
+			content.set_graphics_state(state);
+		}
+		content.path(subpaths, transform);
+		content.content.clip_nonzero();
+		content.content.end_path();
+		content.shading(shading);
+		content.content.restore_state();
+	}
+
+	/// Writes an axial or radial shading interpolating between the components of each stop, which must start at 0 and end at 1.
+	fn write_shading(&mut self, gradient_type: GradientType, coordinates: &[f64], stops: &[(f64, Vec<f32>)], grayscale: bool) -> Ref {
+		// Each pair of stops is interpolated by its own function, which are joined together at the positions of the inner stops
+		let functions = stops
+			.windows(2)
+			.map(|pair| {
+				let id = self.alloc();
+				self.pdf.exponential_function(id).domain([0., 1.]).c0(pair[0].1.iter().copied()).c1(pair[1].1.iter().copied()).n(1.);
+				id
+			})
+			.collect::<Vec<_>>();
+		let function = self.alloc();
+		self.pdf
+			.stitching_function(function)
+			.domain([0., 1.])
+			.functions(functions.iter().copied())
+			.bounds(stops[1..stops.len() - 1].iter().map(|&(position, _)| position as f32))
+			.encode(functions.iter().flat_map(|_| [0., 1.]));
+
+		let id = self.alloc();
+		let mut shading = self.pdf.function_shading(id);
+		shading.shading_type(match gradient_type {
+			GradientType::Linear => FunctionShadingType::Axial,
+			GradientType::Radial => FunctionShadingType::Radial,
+		});
+		if grayscale {
+			shading.color_space().device_gray();
+		} else {
+			shading.color_space().device_rgb();
+		}
+		shading.coords(coordinates.iter().map(|&coordinate| coordinate as f32));
+		shading.function(function);
+		shading.extend([true, true]);
+		shading.finish();
+
+		id
+	}
+
+	fn stroke(&mut self, content: &mut PdfContent, subpaths: &[Subpath<ManipulatorGroupId>], transform: DAffine2, stroke: &Stroke) {
+		let Some(color) = stroke.color else { return };
+		// Stroke weights are measured in document space like in the SVG output
+		let scale = self.transform.matrix2.determinant().abs().sqrt();
+		let width = stroke.weight * scale;
+		if width <= 0. || color.a() <= 0. {
+			return;
+		}
+
+		content.content.save_state();
+		if color.a() < 1. {
+			let state = self.graphics_state(GraphicsState {
+				fill_alpha: 1.,
+				stroke_alpha: color.a(),
+				blend_mode: BlendMode::Normal,
+			});
+			content.set_graphics_state(state);
+		}
+		content.content.set_stroke_rgb(color.r(), color.g(), color.b());
+		content.content.set_line_width(width as f32);
+		content.content.set_line_cap(match stroke.line_cap {
+			LineCap::Butt => LineCapStyle::ButtCap,
+			LineCap::Round => LineCapStyle::RoundCap,
+			LineCap::Square => LineCapStyle::ProjectingSquareCap,
+		});
+		content.content.set_line_join(match stroke.line_join {
+			LineJoin::Miter => LineJoinStyle::MiterJoin,
+			LineJoin::Bevel => LineJoinStyle::BevelJoin,
+			LineJoin::Round => LineJoinStyle::RoundJoin,
+		});
+		content.content.set_miter_limit(stroke.line_join_miter_limit as f32);
+
+		let valid_dashes = stroke.dash_lengths.iter().all(|&length| length >= 0.) && stroke.dash_lengths.iter().sum::<f32>() > 0.;
+		if valid_dashes {
+			let dashes = stroke.dash_lengths.iter().map(|&length| length * scale as f32);
+			content.content.set_dash_pattern(dashes, (stroke.dash_offset * scale) as f32);
+		}
+
+		content.path(subpaths, transform);
+		content.content.stroke();
+		content.content.restore_state();
+	}
+
+	/// Embeds the image with its alpha channel as a soft mask, drawn over the unit square mapped through the image transform.
+	fn write_image(&mut self, image_frame: &ImageFrame<Color>, content: &mut PdfContent) {
+		let transform = self.transform * image_frame.transform;
+		if image_frame.image.width == 0 || image_frame.image.height == 0 || transform.matrix2.determinant().abs() <= f64::EPSILON {
+			return;
+		}
+
+		let (data, width, height) = image_frame.image.clone().into_flat_u8();
+		let rgb = data.chunks_exact(4).flat_map(|pixel| [pixel[0], pixel[1], pixel[2]]).collect::<Vec<_>>();
+		let alpha = data.chunks_exact(4).map(|pixel| pixel[3]).collect::<Vec<_>>();
+
+		let mask = alpha.iter().any(|&alpha| alpha < u8::MAX).then(|| {
+			let id = self.alloc();
+			let compressed = compress(&alpha);
+			let mut mask = self.pdf.image_xobject(id, &compressed);
+			mask.filter(Filter::FlateDecode);
+			mask.width(width as i32).height(height as i32).bits_per_component(8);
+			mask.color_space().device_gray();
+			mask.finish();
+			id
+		});
+
+		let id = self.alloc();
+		let compressed = compress(&rgb);
+		let mut image = self.pdf.image_xobject(id, &compressed);
+		image.filter(Filter::FlateDecode);
+		image.width(width as i32).height(height as i32).bits_per_component(8);
+		image.color_space().device_rgb();
+		if let Some(mask) = mask {
+			image.s_mask(mask);
+		}
+		image.finish();
+
+		// Images are drawn with their first row at the top of the unit square, which is the bottom in the flipped space of the page
+		let flip = DAffine2::from_cols_array(&[1., 0., 0., -1., 0., 1.]);
+		content.content.save_state();
+		content.content.transform((transform * flip).to_cols_array().map(|value| value as f32));
+		content.x_object(id);
+		content.content.restore_state();
+	}
+
+	fn write_artboard(&mut self, artboard: &Artboard, content: &mut PdfContent) {
+		let (min, max) = (artboard.location.as_dvec2(), (artboard.location + artboard.dimensions).as_dvec2());
+		let bounds = [Subpath::new_rect(min, max)];
+
+		self.fill_solid(content, &bounds, self.transform, artboard.background);
+
+		content.content.save_state();
+		if artboard.clip {
+			content.path(&bounds, self.transform);
+			content.content.clip_nonzero();
+			content.content.end_path();
+		}
+		self.write_group(&artboard.graphic_group, content);
+		content.content.restore_state();
+	}
+}
+
+/// The PDF equivalent of a blend mode, where `None` is the normal blend mode and those PDF doesn't support.
+fn pdf_blend_mode(blend_mode: BlendMode) -> Option<pdf_writer::types::BlendMode> {
+	use pdf_writer::types::BlendMode as Pdf;
+
+	Some(match blend_mode {
+		BlendMode::Multiply => Pdf::Multiply,
+		BlendMode::Screen => Pdf::Screen,
+		BlendMode::Overlay => Pdf::Overlay,
+		BlendMode::Darken => Pdf::Darken,
+		BlendMode::Lighten => Pdf::Lighten,
+		BlendMode::ColorDodge => Pdf::ColorDodge,
+		BlendMode::ColorBurn => Pdf::ColorBurn,
+		BlendMode::HardLight => Pdf::HardLight,
+		BlendMode::SoftLight => Pdf::SoftLight,
+		BlendMode::Difference => Pdf::Difference,
+		BlendMode::Exclusion => Pdf::Exclusion,
+		BlendMode::Hue => Pdf::Hue,
+		BlendMode::Saturation => Pdf::Saturation,
+		BlendMode::Color => Pdf::Color,
+		BlendMode::Luminosity => Pdf::Luminosity,
+		_ => return None,
+	})
+}
+
+fn compress(data: &[u8]) -> Vec<u8> {
+	miniz_oxide::deflate::compress_to_vec_zlib(data, COMPRESSION_LEVEL)
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graphene_core::raster::Image;
+	use graphene_core::vector::style::PathStyle;
+	use graphene_core::GraphicElement;
+
+	fn element(graphic_element_data: GraphicElementData, blend_mode: BlendMode, opacity: f32) -> GraphicElement {
+		GraphicElement {
+			name: String::new(),
+			blend_mode,
+			opacity,
+			visible: true,
+			locked: false,
+			collapsed: false,
+			graphic_element_data,
+		}
+	}
+
+	fn page(elements: Vec<GraphicElement>) -> PdfPage {
+		let mut graphic_group = GraphicGroup::EMPTY;
+		graphic_group.extend(elements);
+		PdfPage {
+			size: DVec2::new(100., 50.),
+			transform: DAffine2::IDENTITY,
+			graphic_group,
+		}
+	}
+
+	fn rectangle(fill: Fill) -> GraphicElementData {
+		let mut style = PathStyle::default();
+		style.set_fill(fill);
+		VectorData {
+			style,
+			..VectorData::from_subpath(Subpath::new_rect(DVec2::ZERO, DVec2::splat(10.)))
+		}
+		.into()
+	}
+
+	fn contains(pdf: &[u8], text: &str) -> bool {
+		pdf.windows(text.len()).any(|window| window == text.as_bytes())
+	}
+
+	#[test]
+	fn each_page_is_written() {
+		let pdf = encode_pdf(&[page(Vec::new()), page(Vec::new())]);
+		assert!(pdf.starts_with(b"%PDF-"));
+		assert!(contains(&pdf, "/Count 2"));
+		assert!(contains(&pdf, "/MediaBox [0 0 100 50]"));
+	}
+
+	#[test]
+	fn blended_layers_become_transparency_groups() {
+		let shape = rectangle(Fill::Solid(Color::BLACK));
+		let pdf = encode_pdf(&[page(vec![element(shape, BlendMode::Multiply, 0.5)])]);
+		assert!(contains(&pdf, "/BM /Multiply"));
+		assert!(contains(&pdf, "/ca 0.5"));
+		assert!(contains(&pdf, "/S /Transparency"));
+	}
+
+	#[test]
+	fn gradients_become_shadings() {
+		let gradient = Gradient::new(DVec2::ZERO, Color::BLACK, DVec2::X, Color::TRANSPARENT, DAffine2::IDENTITY, 0, GradientType::Linear);
+		let pdf = encode_pdf(&[page(vec![element(rectangle(Fill::Gradient(gradient)), BlendMode::Normal, 1.)])]);
+		assert!(contains(&pdf, "/ShadingType 2"));
+		// The transparent stop is drawn through a soft mask
+		assert!(contains(&pdf, "/S /Luminosity"));
+	}
+
+	#[test]
+	fn images_are_embedded_with_alpha() {
+		let image = Image {
+			width: 2,
+			height: 1,
+			data: vec![Color::BLACK, Color::TRANSPARENT],
+		};
+		let image_frame = ImageFrame {
+			image,
+			transform: DAffine2::from_scale(DVec2::new(20., 10.)),
+		};
+		let pdf = encode_pdf(&[page(vec![element(image_frame.into(), BlendMode::Normal, 1.)])]);
+		assert!(contains(&pdf, "/Subtype /Image"));
+		assert!(contains(&pdf, "/SMask"));
+	}
+}
@@ -2,11 +2,10 @@ use std::cell::RefCell;
 
 use core::future::Future;
 use dyn_any::StaticType;
-use graphene_core::application_io::{ApplicationError, ApplicationIo, ResourceFuture, SurfaceHandle, SurfaceHandleFrame, SurfaceId};
-use graphene_core::raster::Image;
+use graphene_core::application_io::{ApplicationError, ApplicationIo, ResourceError, ResourceFuture, SurfaceHandle, SurfaceHandleFrame, SurfaceId};
 use graphene_core::Color;
 use graphene_core::{
-	raster::{color::SRGBA8, ImageFrame},
+	raster::{color::SRGBA8, ColorSpace, Image, ImageFrame},
 	Node,
 };
 #[cfg(target_arch = "wasm32")]
@@ -69,6 +68,28 @@ impl WasmApplicationIo {
 		io.resources.insert("null".to_string(), Arc::from(include_bytes!("null.png").to_vec()));
 		io
 	}
+
+	/// Reads back the pixels drawn onto a surface by the [`DrawImageFrameNode`], since only the browser holds them, so that they can be exported.
+	#[cfg(target_arch = "wasm32")]
+	pub fn surface_image(surface_id: SurfaceId) -> Option<Image<Color>> {
+		let canvases = Reflect::get(&Object::from(window()?), &JsValue::from_str("imageCanvases")).ok()?;
+		let canvas = Reflect::get(&canvases, &JsValue::from_str(format!("canvas{}", surface_id.0).as_str())).ok()?;
+		let canvas = canvas.dyn_into::<HtmlCanvasElement>().ok()?;
+		let (width, height) = (canvas.width(), canvas.height());
+		if width == 0 || height == 0 {
+			return None;
+		}
+
+		let context = canvas.get_context("2d").ok()??.dyn_into::<CanvasRenderingContext2d>().ok()?;
+		let image_data = context.get_image_data(0., 0., width as f64, height as f64).ok()?;
+		let data = bytemuck::cast_slice::<u8, SRGBA8>(&image_data.data()).iter().map(|&pixel| pixel.into()).collect();
+		Some(Image { width, height, data })
+	}
+
+	#[cfg(not(target_arch = "wasm32"))]
+	pub fn surface_image(_surface_id: SurfaceId) -> Option<Image<Color>> {
+		None
+	}
 }
 
 unsafe impl StaticType for WasmApplicationIo {
@@ -260,23 +281,59 @@ pub struct LoadResourceNode<Url> {
 }
 
 #[node_macro::node_fn(LoadResourceNode)]
-async fn load_resource_node<'a: 'input>(editor: WasmEditorApi<'a>, url: String) -> Arc<[u8]> {
-	editor.application_io.load_resource(url).unwrap().await.unwrap()
+async fn load_resource_node<'a: 'input>(editor: WasmEditorApi<'a>, url: String) -> Result<Arc<[u8]>, ResourceError> {
+	match editor.application_io.load_resource(url) {
+		Ok(resource) => resource.await.map_err(ResourceError::Load),
+		Err(error) => Err(ResourceError::Load(error)),
+	}
 }
 
-pub struct DecodeImageNode;
+pub struct DecodeImageNode<ColorSpace> {
+	color_space: ColorSpace,
+}
 
+/// Decodes the loaded data into an image in the given working color space, passing along the error if the data couldn't be loaded.
 #[node_macro::node_fn(DecodeImageNode)]
-fn decode_image_node<'a: 'input>(data: Arc<[u8]>) -> ImageFrame<Color> {
-	let image = image::load_from_memory(data.as_ref()).expect("Failed to decode image");
-	let image = image.to_rgba32f();
-	let image = ImageFrame {
-		image: Image {
-			data: image.chunks(4).map(|pixel| Color::from_unassociated_alpha(pixel[0], pixel[1], pixel[2], pixel[3])).collect(),
-			width: image.width(),
-			height: image.height(),
-		},
+fn decode_image_node<'a: 'input>(data: Result<Arc<[u8]>, ResourceError>, color_space: ColorSpace) -> Result<ImageFrame<Color>, ResourceError> {
+	let image = crate::export::decode_image(data?.as_ref(), color_space).map_err(|error| ResourceError::Decode(error.to_string()))?;
+	Ok(ImageFrame {
+		image,
 		transform: glam::DAffine2::IDENTITY,
-	};
-	image
+	})
+}
+
+pub struct ImageOrPlaceholderNode;
+
+/// Unwraps a loaded image, substituting a magenta and black checkerboard for images which failed to load so the rest of the graph can still be evaluated.
+#[node_macro::node_fn(ImageOrPlaceholderNode)]
+fn image_or_placeholder_node(image: Result<ImageFrame<Color>, ResourceError>) -> ImageFrame<Color> {
+	image.unwrap_or_else(|_| {
+		const SIZE: u32 = 8;
+		let magenta = Color::from_rgbf32_unchecked(1., 0., 1.);
+		let data = (0..SIZE * SIZE).map(|index| if (index % SIZE + index / SIZE) % 2 == 0 { magenta } else { Color::BLACK }).collect();
+		ImageFrame {
+			image: Image { width: SIZE, height: SIZE, data },
+			transform: glam::DAffine2::IDENTITY,
+		}
+	})
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use graphene_core::value::ClonedNode;
+
+	#[test]
+	fn undecodable_images_become_placeholders() {
+		let decode = DecodeImageNode::new(ClonedNode::new(ColorSpace::Srgb));
+		let data: Arc<[u8]> = Arc::from(b"not an image".as_slice());
+		let decoded = decode.eval(Ok(data));
+		assert!(matches!(decoded, Err(ResourceError::Decode(_))));
+
+		let missing = decode.eval(Err(ResourceError::Load(ApplicationError::NotFound)));
+		assert_eq!(missing, Err(ResourceError::Load(ApplicationError::NotFound)));
+
+		let placeholder = ImageOrPlaceholderNode.eval(missing);
+		assert_eq!((placeholder.image.width, placeholder.image.height), (8, 8));
+	}
 }
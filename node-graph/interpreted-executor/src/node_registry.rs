@@ -3,17 +3,18 @@ use graph_craft::proto::{NodeConstructor, TypeErasedBox};
 use graphene_core::ops::IdNode;
 use graphene_core::quantization::{PackedPixel, QuantizationChannels};
 
+use graphene_core::application_io::{ResourceError, SurfaceHandle};
 use graphene_core::raster::brush_cache::BrushCache;
 use graphene_core::raster::color::Color;
 use graphene_core::structural::Then;
 use graphene_core::value::{ClonedNode, CopiedNode, ValueNode};
-use graphene_core::vector::brush_stroke::BrushStroke;
+use graphene_core::vector::brush_stroke::{BrushStroke, BrushTipImages};
 use graphene_core::vector::VectorData;
-use graphene_core::{application_io::SurfaceHandle, SurfaceFrame, WasmSurfaceHandleFrame};
 use graphene_core::{concrete, generic};
 use graphene_core::{fn_type, raster::*};
 use graphene_core::{Cow, NodeIdentifier, Type, TypeDescriptor};
 use graphene_core::{Node, NodeIO, NodeIOTypes};
+use graphene_core::{SurfaceFrame, WasmSurfaceHandleFrame};
 use graphene_std::any::{ComposeTypeErased, DowncastBothNode, DynAnyNode, FutureWrapperNode, IntoTypeErasedNode};
 use graphene_std::wasm_application_io::*;
 
@@ -319,8 +320,10 @@ fn node_registry() -> HashMap<NodeIdentifier, HashMap<NodeIOTypes, NodeConstruct
 		register_node!(graphene_std::raster::EmptyImageNode<_, _>, input: DAffine2, params: [Color]),
 		register_node!(graphene_core::memo::MonitorNode<_>, input: ImageFrame<Color>, params: []),
 		register_node!(graphene_core::memo::MonitorNode<_>, input: graphene_core::GraphicGroup, params: []),
-		async_node!(graphene_std::wasm_application_io::LoadResourceNode<_>, input: WasmEditorApi, output: Arc<[u8]>, params: [String]),
-		register_node!(graphene_std::wasm_application_io::DecodeImageNode, input: Arc<[u8]>, params: []),
+		async_node!(graphene_std::wasm_application_io::LoadResourceNode<_>, input: WasmEditorApi, output: Result<Arc<[u8]>, ResourceError>, params: [String]),
+		register_node!(graphene_std::wasm_application_io::DecodeImageNode<_>, input: Result<Arc<[u8]>, ResourceError>, params: [graphene_core::raster::ColorSpace]),
+		register_node!(graphene_core::memo::MonitorNode<_>, input: Result<ImageFrame<Color>, ResourceError>, params: []),
+		register_node!(graphene_std::wasm_application_io::ImageOrPlaceholderNode, input: Result<ImageFrame<Color>, ResourceError>, params: []),
 		async_node!(graphene_std::wasm_application_io::CreateSurfaceNode, input: WasmEditorApi, output: Arc<SurfaceHandle<<graphene_std::wasm_application_io::WasmApplicationIo as graphene_core::application_io::ApplicationIo>::Surface>>, params: []),
 		async_node!(
 			graphene_std::wasm_application_io::DrawImageFrameNode<_>,
@@ -419,15 +422,30 @@ fn node_registry() -> HashMap<NodeIdentifier, HashMap<NodeIOTypes, NodeConstruct
 			),
 		)],
 		register_node!(graphene_std::brush::IntoIterNode<_>, input: &Vec<BrushStroke>, params: []),
-		async_node!(graphene_std::brush::BrushNode<_, _, _>, input: ImageFrame<Color>, output: ImageFrame<Color>, params: [ImageFrame<Color>, Vec<BrushStroke>, BrushCache]),
+		async_node!(graphene_std::brush::BrushNode<_, _, _, _>, input: ImageFrame<Color>, output: ImageFrame<Color>, params: [ImageFrame<Color>, Vec<BrushStroke>, BrushCache, BrushTipImages]),
 		// Filters
 		raster_node!(graphene_core::raster::LuminanceNode<_>, params: [LuminanceCalculation]),
+		raster_node!(graphene_core::raster::ConvertColorSpaceNode<_, _>, params: [ColorSpace, ColorSpace]),
 		raster_node!(graphene_core::raster::ExtractChannelNode<_>, params: [RedGreenBlue]),
 		raster_node!(graphene_core::raster::ExtractAlphaNode<>, params: []),
 		raster_node!(graphene_core::raster::ExtractOpaqueNode<>, params: []),
 		raster_node!(graphene_core::raster::LevelsNode<_, _, _, _, _>, params: [f32, f32, f32, f32, f32]),
 		register_node!(graphene_std::image_segmentation::ImageSegmentationNode<_>, input: ImageFrame<Color>, params: [ImageFrame<Color>]),
+		register_node!(graphene_core::raster::DilateNode<_>, input: ImageFrame<Color>, params: [u32]),
+		register_node!(graphene_core::raster::ErodeNode<_>, input: ImageFrame<Color>, params: [u32]),
+		register_node!(graphene_core::raster::OpenNode<_>, input: ImageFrame<Color>, params: [u32]),
+		register_node!(graphene_core::raster::CloseNode<_>, input: ImageFrame<Color>, params: [u32]),
+		register_node!(graphene_core::raster::EdgeDetectionNode<_, _>, input: ImageFrame<Color>, params: [EdgeDetectionOperator, ImageChannel]),
+		register_node!(graphene_core::raster::CannyEdgeDetectionNode<_, _, _, _>, input: ImageFrame<Color>, params: [ImageChannel, f64, f32, f32]),
+		register_node!(graphene_core::raster::DistanceTransformNode<_, _, _>, input: ImageFrame<Color>, params: [ImageChannel, f32, f64]),
+		register_node!(graphene_core::raster::MagicWandNode<_, _, _, _>, input: ImageFrame<Color>, params: [DVec2, f32, FloodFillMode, bool]),
+		register_node!(graphene_core::raster::FloodFillNode<_>, input: ImageFrame<Color>, params: [Vec<FloodFill>]),
+		register_node!(graphene_core::raster::PerspectiveWarpNode<_>, input: ImageFrame<Color>, params: [Vec<DVec2>]),
+		register_node!(graphene_core::raster::MeshWarpNode<_, _, _>, input: ImageFrame<Color>, params: [u32, u32, Vec<DVec2>]),
+		register_node!(graphene_core::raster::CageWarpNode<_, _>, input: ImageFrame<Color>, params: [Vec<DVec2>, Vec<DVec2>]),
 		register_node!(graphene_core::raster::IndexNode<_>, input: Vec<ImageFrame<Color>>, params: [u32]),
+		register_node!(graphene_core::raster::DominantColorsNode<_>, input: ImageFrame<Color>, params: [u32]),
+		register_node!(graphene_core::raster::PaletteColorNode<_>, input: Vec<PaletteColor>, params: [u32]),
 		vec![(
 			NodeIdentifier::new("graphene_core::raster::BlendNode<_, _, _, _>"),
 			|args| {
@@ -648,9 +666,13 @@ fn node_registry() -> HashMap<NodeIdentifier, HashMap<NodeIOTypes, NodeConstruct
 			input: Vec<graphene_core::vector::bezier_rs::Subpath<graphene_core::uuid::ManipulatorGroupId>>,
 			params: [Vec<graphene_core::uuid::ManipulatorGroupId>]
 		),
-		register_node!(graphene_core::text::TextGenerator<_, _, _>, input: WasmEditorApi, params: [String, graphene_core::text::Font, f64]),
+		register_node!(graphene_core::text::TextGenerator<_, _, _, _, _, _, _, _, _, _, _, _, _, _, _, _>, input: WasmEditorApi, params: [String, graphene_core::text::Font, f64, f64, f64, f64, graphene_core::text::TextAlign, f64, f64, graphene_core::text::TextOverflow, String, Option<f64>, Option<f64>, Option<f64>, Option<f64>, String]),
+		register_node!(graphene_core::text::RichTextGenerator<_, _, _, _, _, _, _, _, _, _, _, _, _, _>, input: WasmEditorApi, params: [graphene_core::text::RichText, f64, f64, f64, graphene_core::text::TextAlign, f64, f64, graphene_core::text::TextOverflow, String, Option<f64>, Option<f64>, Option<f64>, Option<f64>, String]),
+		register_node!(graphene_core::text::TextOnPathNode<_, _, _, _, _, _, _, _, _, _, _, _, _>, input: WasmEditorApi, params: [String, graphene_core::text::Font, f64, f64, VectorData, f64, graphene_core::text::TextAlign, graphene_core::text::TextPathSide, Option<f64>, Option<f64>, Option<f64>, Option<f64>, String]),
 		register_node!(graphene_std::brush::VectorPointsNode, input: VectorData, params: []),
 		register_node!(graphene_core::ExtractImageFrame, input: WasmEditorApi, params: []),
+		register_node!(graphene_core::ExtractTime, input: WasmEditorApi, params: []),
+		register_node!(graphene_core::ExtractColorSpace, input: WasmEditorApi, params: []),
 		register_node!(graphene_core::ConstructLayerNode<_, _, _, _, _, _, _>, input: graphene_core::vector::VectorData, params: [String, BlendMode, f32, bool, bool, bool, graphene_core::GraphicGroup]),
 		register_node!(graphene_core::ConstructLayerNode<_, _, _, _, _, _, _>, input: ImageFrame<Color>, params: [String, BlendMode, f32, bool, bool, bool, graphene_core::GraphicGroup]),
 		register_node!(graphene_core::ConstructLayerNode<_, _, _, _, _, _, _>, input: graphene_core::GraphicGroup, params: [String, BlendMode, f32, bool, bool, bool, graphene_core::GraphicGroup]),